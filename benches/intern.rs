@@ -0,0 +1,36 @@
+// Copyright (C) 2024 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use apca::data::v2::intern::Interned;
+use apca::data::v2::stream::DetailedQuote;
+
+use criterion::black_box;
+use criterion::criterion_group;
+use criterion::criterion_main;
+use criterion::Criterion;
+
+use serde_json::from_str as from_json;
+
+
+/// A single quote message as sent by the server.
+const QUOTE: &str = r#"{"T":"q","S":"AAPL","bx":"U","bp":126.47,"bs":1,"ax":"Q","ap":126.5,"as":1,"t":"2021-02-22T15:51:45.335689322Z","c":["R"],"z":"C"}"#;
+
+
+/// Benchmark deserialization of a batch of quotes with symbols,
+/// exchange codes, and conditions represented as plain `String` and
+/// as `Interned` objects.
+fn deserialize(c: &mut Criterion) {
+  let json = format!("[{}]", vec![QUOTE; 100].join(","));
+
+  let mut group = c.benchmark_group("deserialize-quotes");
+  let _group = group.bench_function("string", |b| {
+    b.iter(|| from_json::<Vec<DetailedQuote<String>>>(black_box(&json)).unwrap())
+  });
+  let _group = group.bench_function("interned", |b| {
+    b.iter(|| from_json::<Vec<DetailedQuote<Interned>>>(black_box(&json)).unwrap())
+  });
+  let () = group.finish();
+}
+
+criterion_group!(benches, deserialize);
+criterion_main!(benches);
@@ -0,0 +1,45 @@
+// Copyright (C) 2024 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use apca::data::v2::stream::Format;
+use apca::data::v2::stream::Json;
+use apca::data::v2::stream::MsgPack;
+use apca::data::v2::stream::Trade;
+
+use criterion::black_box;
+use criterion::criterion_group;
+use criterion::criterion_main;
+use criterion::Criterion;
+
+use serde_json::Value;
+
+
+/// A single trade message as sent by the server.
+const TRADE: &str = r#"{"T":"t","i":96921,"S":"AAPL","x":"D","p":126.55,"s":1,"t":"2021-02-22T15:51:44.208Z","c":["@","I"],"z":"C"}"#;
+
+
+/// Create a message containing `count` trades in JSON format.
+fn trades(count: usize) -> String {
+  format!("[{}]", vec![TRADE; count].join(","))
+}
+
+
+/// Benchmark decoding of a batch of trades in JSON and MessagePack
+/// format.
+fn decode(c: &mut Criterion) {
+  let json = trades(100);
+  let value = serde_json::from_str::<Value>(&json).unwrap();
+  let msgpack = rmp_serde::to_vec(&value).unwrap();
+
+  let mut group = c.benchmark_group("decode-trades");
+  let _group = group.bench_function("json", |b| {
+    b.iter(|| <Json as Format>::decode::<Vec<Trade>>(black_box(json.as_bytes())).unwrap())
+  });
+  let _group = group.bench_function("msgpack", |b| {
+    b.iter(|| <MsgPack as Format>::decode::<Vec<Trade>>(black_box(&msgpack)).unwrap())
+  });
+  let () = group.finish();
+}
+
+criterion_group!(benches, decode);
+criterion_main!(benches);
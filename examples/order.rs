@@ -21,13 +21,11 @@ async fn main() {
 
   // Create request for a limit order for AAPL with a limit price of USD
   // 100.
-  let request = order::CreateReqInit {
-    type_: order::Type::Limit,
-    limit_price: Some(Num::from(100)),
-    ..Default::default()
-  }
-  // We want to go long on AAPL, buying a single share.
-  .init("AAPL", order::Side::Buy, order::Amount::quantity(1));
+  let request = order::CreateReqInit::new()
+    .type_(order::Type::Limit)
+    .limit_price(Num::from(100))
+    // We want to go long on AAPL, buying a single share.
+    .init("AAPL", order::Side::Buy, order::Amount::quantity(1));
 
   let order = client.issue::<order::Create>(&request).await.unwrap();
   println!("Created order {}", order.id.as_hyphenated());
@@ -6,7 +6,11 @@ pub mod v2;
 
 /// The API base URL used for paper trading.
 pub(crate) const API_BASE_URL: &str = "https://paper-api.alpaca.markets";
+/// The API base URL used for live trading.
+pub(crate) const LIVE_API_BASE_URL: &str = "https://api.alpaca.markets";
 /// The HTTP header representing the key ID.
 pub(crate) const HDR_KEY_ID: &str = "APCA-API-KEY-ID";
 /// The HTTP header representing the secret key.
 pub(crate) const HDR_SECRET: &str = "APCA-API-SECRET-KEY";
+/// The HTTP header containing the ID Alpaca assigned to a request.
+pub(crate) const HDR_REQUEST_ID: &str = "X-Request-ID";
@@ -55,6 +55,19 @@ pub enum Status {
   /// The account application has been rejected.
   #[serde(rename = "REJECTED")]
   Rejected,
+  /// The account is not (yet) enabled, e.g., for crypto trading.
+  #[serde(rename = "INACTIVE")]
+  Inactive,
+  /// Additional information is required before the account can be
+  /// approved.
+  #[serde(rename = "ACTION_REQUIRED")]
+  ActionRequired,
+  /// The account has been disabled.
+  #[serde(rename = "DISABLED")]
+  Disabled,
+  /// The account has been closed.
+  #[serde(rename = "ACCOUNT_CLOSED")]
+  Closed,
   /// Any other account status that we have not accounted for.
   ///
   /// Note that having any such status should be considered a bug.
@@ -74,6 +87,10 @@ pub struct Account {
   /// The account's status.
   #[serde(rename = "status")]
   pub status: Status,
+  /// The status of the account with respect to crypto trading, if
+  /// reported.
+  #[serde(rename = "crypto_status")]
+  pub crypto_status: Option<Status>,
   /// The currency the account uses.
   #[serde(rename = "currency")]
   pub currency: String,
@@ -143,12 +160,32 @@ pub struct Account {
   /// five trading days (including today).
   #[serde(rename = "daytrade_count")]
   pub daytrade_count: u64,
+  /// The options trading level the account is permitted to trade at,
+  /// if reported.
+  #[serde(rename = "options_trading_level")]
+  pub options_trading_level: Option<u8>,
   /// The type is non-exhaustive and open to extension.
   #[doc(hidden)]
   #[serde(skip)]
   pub _non_exhaustive: (),
 }
 
+impl Account {
+  /// Check whether the account is permitted to trade, i.e., whether
+  /// it is active and neither the account nor trading are blocked or
+  /// suspended.
+  ///
+  /// Note that this check does not take into account pattern day
+  /// trading restrictions.
+  #[inline]
+  pub fn can_trade(&self) -> bool {
+    self.status == Status::Active
+      && !self.account_blocked
+      && !self.trading_blocked
+      && !self.trading_suspended
+  }
+}
+
 
 Endpoint! {
   /// The representation of a GET request to the /v2/account endpoint.
@@ -190,6 +227,7 @@ mod tests {
     let json = r#"{
   "id": "904837e3-3b76-47ec-b432-046db621571b",
   "status": "ACTIVE",
+  "crypto_status": "ACTIVE",
   "currency": "USD",
   "buying_power": "0.0",
   "cash": "1000.00",
@@ -209,7 +247,8 @@ mod tests {
   "initial_margin": "5000.00",
   "maintenance_margin": "3000.00",
   "daytrade_count": 0,
-  "sma": "0.0"
+  "sma": "0.0",
+  "options_trading_level": 1
 }"#;
 
     let acc =
@@ -231,6 +270,32 @@ mod tests {
     assert_eq!(acc.last_equity, Num::from(5000));
     assert_eq!(acc.maintenance_margin, Num::from(3000));
     assert_eq!(acc.daytrade_count, 0);
+    assert_eq!(acc.crypto_status, Some(Status::Active));
+    assert_eq!(acc.options_trading_level, Some(1));
+    assert!(acc.can_trade());
+
+    let acc = Account {
+      trading_blocked: true,
+      ..acc
+    };
+    assert!(!acc.can_trade());
+  }
+
+  /// Check that we can deserialize the various account states.
+  #[test]
+  fn deserialize_status() {
+    let statuses = r#"["ACCOUNT_UPDATED","INACTIVE","ACTION_REQUIRED","ACCOUNT_CLOSED","FOOBAR"]"#;
+    let statuses = from_json::<Vec<Status>>(statuses).unwrap();
+    assert_eq!(
+      statuses,
+      vec![
+        Status::Updating,
+        Status::Inactive,
+        Status::ActionRequired,
+        Status::Closed,
+        Status::Unknown,
+      ]
+    );
   }
 
   /// Test that we can retrieve information about the account.
@@ -6,21 +6,35 @@ use chrono::NaiveDate;
 use chrono::TimeZone as _;
 use chrono::Utc;
 
+use futures::stream::iter;
+use futures::stream::unfold;
+use futures::Stream;
+use futures::TryStreamExt as _;
+
 use num_decimal::Num;
 
 use serde::Deserialize;
 use serde::Deserializer;
 use serde::Serialize;
 use serde_urlencoded::to_string as to_query;
+use serde_variant::to_variant_name;
 
 use crate::api::v2::de::ContentDeserializer;
 use crate::api::v2::de::TaggedContentVisitor;
 use crate::api::v2::order;
 use crate::util::abs_num_from_str;
 use crate::util::enum_slice_to_str;
+use crate::Client;
+use crate::PageToken;
+use crate::RequestError;
 use crate::Str;
 
 
+/// The number of activities reported per page if not specified
+/// otherwise.
+const DEFAULT_PAGE_SIZE: usize = 100;
+
+
 /// Deserialize a `DateTime<Utc>` from a simple date.
 fn datetime_from_date_str<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
 where
@@ -149,6 +163,28 @@ pub enum ActivityType {
 }
 
 
+/// The status of a non-trade activity.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Status {
+  /// The activity has been executed.
+  #[serde(rename = "executed")]
+  Executed,
+  /// The activity is a correction of a previous one.
+  #[serde(rename = "correct")]
+  Correct,
+  /// The activity has been canceled.
+  #[serde(rename = "canceled")]
+  Canceled,
+  /// Any other status that we have not accounted for.
+  ///
+  /// Note that having any such status should be considered a bug.
+  #[doc(hidden)]
+  #[serde(other)]
+  Unknown,
+}
+
+
 /// An enumeration describing the side of a trade activity.
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
 #[non_exhaustive]
@@ -249,6 +285,17 @@ pub struct NonTradeActivityImpl<T> {
   /// A description of the activity.
   #[serde(rename = "description")]
   pub description: Option<String>,
+  /// The status of the activity. Not present for all activity types.
+  #[serde(rename = "status")]
+  pub status: Option<Status>,
+  /// For cash transactions, the ID of the transfer that caused the
+  /// activity.
+  #[serde(rename = "transfer_id")]
+  pub transfer_id: Option<String>,
+  /// An ID grouping related activities, e.g., a transaction and its
+  /// subsequent corrections.
+  #[serde(rename = "group_id")]
+  pub group_id: Option<String>,
   /// The type is non-exhaustive and open to extension.
   #[doc(hidden)]
   #[serde(skip)]
@@ -267,6 +314,9 @@ impl<T> NonTradeActivityImpl<T> {
       price,
       per_share_amount,
       description,
+      status,
+      transfer_id,
+      group_id,
       _non_exhaustive: (),
     } = self;
 
@@ -280,6 +330,9 @@ impl<T> NonTradeActivityImpl<T> {
       price,
       per_share_amount,
       description,
+      status,
+      transfer_id,
+      group_id,
       _non_exhaustive: (),
     }
   }
@@ -291,6 +344,143 @@ impl<T> NonTradeActivityImpl<T> {
 /// Examples include dividend payments or cash transfers.
 pub type NonTradeActivity = NonTradeActivityImpl<ActivityType>;
 
+impl NonTradeActivity {
+  /// Classify the activity into one of the typed representations of
+  /// the more common activity types.
+  ///
+  /// Activities of other types, or ones lacking data required by the
+  /// typed representation, are reported as
+  /// [`ClassifiedActivity::Other`].
+  pub fn classify(self) -> ClassifiedActivity {
+    match self.type_ {
+      ActivityType::Dividend
+      | ActivityType::CapitalGainLongTerm
+      | ActivityType::CapitalGainShortTerm
+      | ActivityType::DividendReturnOfCapital
+      | ActivityType::DividendTaxExtempt => match (self.symbol, self.per_share_amount) {
+        (Some(symbol), Some(per_share_amount)) => ClassifiedActivity::Dividend(Dividend {
+          id: self.id,
+          type_: self.type_,
+          date: self.date,
+          net_amount: self.net_amount,
+          symbol,
+          quantity: self.quantity,
+          per_share_amount,
+          _non_exhaustive: (),
+        }),
+        (symbol, per_share_amount) => ClassifiedActivity::Other(Self {
+          symbol,
+          per_share_amount,
+          ..self
+        }),
+      },
+      ActivityType::Interest
+      | ActivityType::InterestAdjustedNraWithheld
+      | ActivityType::InterestAdjustedTefraWithheld => ClassifiedActivity::Interest(Interest {
+        id: self.id,
+        type_: self.type_,
+        date: self.date,
+        net_amount: self.net_amount,
+        description: self.description,
+        _non_exhaustive: (),
+      }),
+      ActivityType::Fee | ActivityType::DividendFee | ActivityType::PassThruCharge => {
+        ClassifiedActivity::Fee(Fee {
+          id: self.id,
+          type_: self.type_,
+          date: self.date,
+          net_amount: self.net_amount,
+          symbol: self.symbol,
+          description: self.description,
+          _non_exhaustive: (),
+        })
+      },
+      _ => ClassifiedActivity::Other(self),
+    }
+  }
+}
+
+
+/// A dividend payment.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Dividend {
+  /// The ID of the activity.
+  pub id: String,
+  /// The type of dividend, e.g., [`ActivityType::Dividend`] or
+  /// [`ActivityType::CapitalGainLongTerm`].
+  pub type_: ActivityType,
+  /// The date on which the dividend was paid.
+  pub date: DateTime<Utc>,
+  /// The net amount of money paid.
+  pub net_amount: Num,
+  /// The symbol of the security paying the dividend.
+  pub symbol: String,
+  /// The number of shares that contributed to the payment, if known.
+  pub quantity: Option<Num>,
+  /// The amount paid per share.
+  pub per_share_amount: Num,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+
+/// An interest payment, either credited or charged.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Interest {
+  /// The ID of the activity.
+  pub id: String,
+  /// The type of interest activity, e.g., [`ActivityType::Interest`].
+  pub type_: ActivityType,
+  /// The date on which the interest was paid.
+  pub date: DateTime<Utc>,
+  /// The net amount of money (positive or negative) paid.
+  pub net_amount: Num,
+  /// A description of the activity.
+  pub description: Option<String>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+
+/// A fee charged to the account.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Fee {
+  /// The ID of the activity.
+  pub id: String,
+  /// The type of fee, e.g., [`ActivityType::Fee`] or
+  /// [`ActivityType::DividendFee`].
+  pub type_: ActivityType,
+  /// The date on which the fee was charged.
+  pub date: DateTime<Utc>,
+  /// The net amount of money charged, typically negative.
+  pub net_amount: Num,
+  /// The symbol of the security the fee relates to, if any.
+  pub symbol: Option<String>,
+  /// A description of the activity.
+  pub description: Option<String>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+
+/// A non-trade activity classified by its type, as returned by
+/// [`NonTradeActivity::classify`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ClassifiedActivity {
+  /// A dividend payment.
+  Dividend(Dividend),
+  /// An interest payment.
+  Interest(Interest),
+  /// A fee.
+  Fee(Fee),
+  /// Any other non-trade activity.
+  Other(NonTradeActivity),
+}
+
 
 /// An activity.
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -386,6 +576,19 @@ impl Default for Direction {
 }
 
 
+/// A category of account activities.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub enum Category {
+  /// Trade activities, i.e., fills.
+  #[serde(rename = "trade_activity")]
+  Trade,
+  /// Non-trade activities.
+  #[serde(rename = "non_trade_activity")]
+  NonTrade,
+}
+
+
 /// A GET request to be made to the /v2/account/activities endpoint.
 #[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
 pub struct ActivityReq {
@@ -394,9 +597,19 @@ pub struct ActivityReq {
   /// If empty all activities will be retrieved.
   #[serde(rename = "activity_types", serialize_with = "enum_slice_to_str")]
   pub types: Vec<ActivityType>,
+  /// The category of activities to retrieve.
+  ///
+  /// Mutually exclusive with `types`.
+  #[serde(rename = "category")]
+  pub category: Option<Category>,
   /// The direction in which to report account activities.
   #[serde(rename = "direction")]
   pub direction: Direction,
+  /// The response will contain only activities on this date.
+  ///
+  /// Mutually exclusive with `until` and `after`.
+  #[serde(rename = "date")]
+  pub date: Option<NaiveDate>,
   /// The response will contain only activities until this time.
   #[serde(rename = "until")]
   pub until: Option<DateTime<Utc>>,
@@ -410,7 +623,7 @@ pub struct ActivityReq {
   pub page_size: Option<usize>,
   /// The ID of the end of your current page of results.
   #[serde(rename = "page_token")]
-  pub page_token: Option<String>,
+  pub page_token: Option<PageToken>,
   /// The type is non-exhaustive and open to extension.
   #[doc(hidden)]
   #[serde(skip)]
@@ -439,12 +652,137 @@ Endpoint! {
 }
 
 
+/// A GET request to be made to the
+/// /v2/account/activities/{activity_type} endpoint.
+///
+/// In contrast to [`ActivityReq`], activities of only a single type are
+/// retrieved.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct TypedActivityReq {
+  /// The type of activities to retrieve.
+  #[serde(skip)]
+  pub activity_type: ActivityType,
+  /// The direction in which to report account activities.
+  #[serde(rename = "direction")]
+  pub direction: Direction,
+  /// The response will contain only activities on this date.
+  ///
+  /// Mutually exclusive with `until` and `after`.
+  #[serde(rename = "date")]
+  pub date: Option<NaiveDate>,
+  /// The response will contain only activities until this time.
+  #[serde(rename = "until")]
+  pub until: Option<DateTime<Utc>>,
+  /// The response will contain only activities dated after this time.
+  #[serde(rename = "after")]
+  pub after: Option<DateTime<Utc>>,
+  /// The maximum number of entries to return in the response.
+  ///
+  /// The default and maximum value is 100.
+  #[serde(rename = "page_size")]
+  pub page_size: Option<usize>,
+  /// The ID of the end of your current page of results.
+  #[serde(rename = "page_token")]
+  pub page_token: Option<PageToken>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+impl From<ActivityType> for TypedActivityReq {
+  #[inline]
+  fn from(activity_type: ActivityType) -> Self {
+    Self {
+      activity_type,
+      direction: Direction::default(),
+      date: None,
+      until: None,
+      after: None,
+      page_size: None,
+      page_token: None,
+      _non_exhaustive: (),
+    }
+  }
+}
+
+
+Endpoint! {
+  /// The representation of a GET request to the
+  /// /v2/account/activities/{activity_type} endpoint.
+  pub GetByType(TypedActivityReq),
+  Ok => Vec<Activity>, [
+    /// The activities were retrieved successfully.
+    /* 200 */ OK,
+  ],
+  Err => GetByTypeError, []
+
+  #[inline]
+  fn path(input: &Self::Input) -> Str {
+    // SANITY: `ActivityType` is a plain enum, so determining the name
+    //         of the variant is infallible.
+    let activity_type = to_variant_name(&input.activity_type).unwrap();
+    format!("/v2/account/activities/{activity_type}").into()
+  }
+
+  fn query(input: &Self::Input) -> Result<Option<Str>, Self::ConversionError> {
+    Ok(Some(to_query(input)?.into()))
+  }
+}
+
+
+/// Retrieve all account activities matching the provided request as a
+/// stream.
+///
+/// Pages of activities are retrieved lazily and in the order
+/// requested, with the page token being advanced transparently. Any
+/// `page_token` set on the request marks the starting point. The stream
+/// ends after the first error.
+pub fn fetch_all(
+  client: &Client,
+  request: ActivityReq,
+) -> impl Stream<Item = Result<Activity, RequestError<GetError>>> + '_ {
+  let page_size = request.page_size.unwrap_or(DEFAULT_PAGE_SIZE);
+
+  unfold(Some(request), move |request| async move {
+    let mut request = request?;
+    match client.issue::<Get>(&request).await {
+      Ok(activities) => {
+        // A page not filled up completely is the last one.
+        let next = match activities.last() {
+          Some(last) if activities.len() >= page_size => {
+            request.page_token = Some(last.id().to_string().into());
+            Some(request)
+          },
+          _ => None,
+        };
+        Some((Ok(activities), next))
+      },
+      Err(err) => Some((Err(err), None)),
+    }
+  })
+  .map_ok(|activities| iter(activities.into_iter().map(Ok)))
+  .try_flatten()
+}
+
+
 #[cfg(test)]
 mod tests {
   use super::*;
 
   use chrono::Duration;
 
+  use futures::future::ready;
+  use futures::future::BoxFuture;
+  use futures::FutureExt as _;
+
+  use http::Request;
+  use http::Response;
+  use http_body_util::Full;
+  use http_endpoint::Endpoint as _;
+
+  use hyper::body::Bytes;
+
   use serde_json::from_str as from_json;
 
   use test_log::test;
@@ -452,7 +790,40 @@ mod tests {
   use uuid::Uuid;
 
   use crate::api_info::ApiInfo;
-  use crate::Client;
+  use crate::transport::test::mock_client;
+  use crate::Transport;
+  use crate::TransportError;
+
+
+  /// Create the JSON representation of a dividend activity with the
+  /// given ID.
+  fn dividend(id: &str) -> String {
+    format!(r#"{{"id":"{id}","activity_type":"DIV","date":"2024-03-01","net_amount":"1.0"}}"#)
+  }
+
+
+  /// A transport serving two pages of activities, distinguished by the
+  /// page token provided.
+  #[derive(Debug)]
+  struct Pages;
+
+  impl Transport for Pages {
+    fn issue(
+      &self,
+      request: Request<Full<Bytes>>,
+    ) -> BoxFuture<'_, Result<Response<Bytes>, TransportError>> {
+      let query = request.uri().query().unwrap_or("");
+      let body = if query.contains("page_token=2") {
+        format!("[{}]", dividend("3"))
+      } else {
+        format!("[{},{}]", dividend("1"), dividend("2"))
+      };
+      let response = Response::builder()
+        .body(Bytes::from(body))
+        .map_err(TransportError::from);
+      ready(response).boxed()
+    }
+  }
 
 
   #[test]
@@ -509,6 +880,41 @@ mod tests {
     );
     assert_eq!(non_trade.symbol, Some("T".into()));
     assert_eq!(non_trade.per_share_amount, Some(Num::new(51, 100)));
+    assert_eq!(non_trade.status, None);
+  }
+
+
+  /// Check that we can parse the status and transfer information of a
+  /// cash deposit.
+  #[test]
+  fn parse_cash_deposit() {
+    let response = r#"{
+  "id": "20240102000000000::1a2b3c4d-0000-4000-8000-000000000001",
+  "activity_type": "CSD",
+  "date": "2024-01-02",
+  "net_amount": "1000",
+  "description": "",
+  "status": "executed",
+  "transfer_id": "1fa6a7b0-3c4d-4e5f-8a9b-0c1d2e3f4a5b",
+  "group_id": "3e0c1d2e-3f4a-4b5c-8d6e-7f8a9b0c1d2e"
+}"#;
+
+    let non_trade = from_json::<Activity>(response)
+      .unwrap()
+      .into_non_trade()
+      .unwrap();
+
+    assert_eq!(non_trade.type_, ActivityType::CashDeposit);
+    assert_eq!(non_trade.net_amount, Num::from(1000));
+    assert_eq!(non_trade.status, Some(Status::Executed));
+    assert_eq!(
+      non_trade.transfer_id.as_deref(),
+      Some("1fa6a7b0-3c4d-4e5f-8a9b-0c1d2e3f4a5b")
+    );
+    assert_eq!(
+      non_trade.group_id.as_deref(),
+      Some("3e0c1d2e-3f4a-4b5c-8d6e-7f8a9b0c1d2e")
+    );
   }
 
 
@@ -541,6 +947,75 @@ mod tests {
     assert_eq!(non_trade.per_share_amount, Some(Num::new(108783, 1000000)));
   }
 
+  /// Check that we can classify non-trade activities by their type.
+  #[test]
+  fn classify_non_trade_activities() {
+    let response = r#"[{
+  "activity_type": "DIVCGL",
+  "id": "20190801011955195::5f596936-6f23-4cef-bdf1-3806aae57dbf",
+  "date": "2019-08-01",
+  "net_amount": "1.02",
+  "symbol": "T",
+  "per_share_amount": "0.51"
+}, {
+  "activity_type": "INT",
+  "id": "20190802011955195::5f596936-6f23-4cef-bdf1-3806aae57dbf",
+  "date": "2019-08-02",
+  "net_amount": "-0.17",
+  "description": "margin interest"
+}, {
+  "activity_type": "FEE",
+  "id": "20190803011955195::5f596936-6f23-4cef-bdf1-3806aae57dbf",
+  "date": "2019-08-03",
+  "net_amount": "-0.01",
+  "symbol": "SPY"
+}, {
+  "activity_type": "DIV",
+  "id": "20190804011955195::5f596936-6f23-4cef-bdf1-3806aae57dbf",
+  "date": "2019-08-04",
+  "net_amount": "1.02"
+}]"#;
+
+    let mut classified = from_json::<Vec<Activity>>(response)
+      .unwrap()
+      .into_iter()
+      .map(|activity| activity.into_non_trade().unwrap().classify());
+
+    match classified.next().unwrap() {
+      ClassifiedActivity::Dividend(dividend) => {
+        assert_eq!(dividend.type_, ActivityType::CapitalGainLongTerm);
+        assert_eq!(dividend.symbol, "T");
+        assert_eq!(dividend.quantity, None);
+        assert_eq!(dividend.per_share_amount, Num::new(51, 100));
+      },
+      activity => panic!("encountered unexpected activity: {activity:?}"),
+    }
+
+    match classified.next().unwrap() {
+      ClassifiedActivity::Interest(interest) => {
+        assert_eq!(interest.net_amount, Num::new(-17, 100));
+        assert_eq!(interest.description.as_deref(), Some("margin interest"));
+      },
+      activity => panic!("encountered unexpected activity: {activity:?}"),
+    }
+
+    match classified.next().unwrap() {
+      ClassifiedActivity::Fee(fee) => {
+        assert_eq!(fee.net_amount, Num::new(-1, 100));
+        assert_eq!(fee.symbol.as_deref(), Some("SPY"));
+      },
+      activity => panic!("encountered unexpected activity: {activity:?}"),
+    }
+
+    // A dividend without the required data is not classified as such.
+    match classified.next().unwrap() {
+      ClassifiedActivity::Other(non_trade) => {
+        assert_eq!(non_trade.type_, ActivityType::Dividend);
+      },
+      activity => panic!("encountered unexpected activity: {activity:?}"),
+    }
+  }
+
   #[test(tokio::test)]
   async fn retrieve_some_activities() {
     let api_info = ApiInfo::from_env().unwrap();
@@ -638,7 +1113,7 @@ mod tests {
     assert_eq!(activities.len(), 1);
     let newest_activity = &activities[0];
 
-    request.page_token = Some(newest_activity.id().to_string());
+    request.page_token = Some(newest_activity.id().to_string().into());
 
     let activities = client.issue::<Get>(&request).await.unwrap();
     assert_eq!(activities.len(), 1);
@@ -649,6 +1124,59 @@ mod tests {
     assert_ne!(newest_activity.id(), next_activity.id());
   }
 
+  /// Check that the `date` and `category` members are serialized as
+  /// expected.
+  #[test]
+  fn serialize_date_category() {
+    let request = ActivityReq {
+      category: Some(Category::NonTrade),
+      date: Some(NaiveDate::from_ymd_opt(2024, 3, 1).unwrap()),
+      ..Default::default()
+    };
+    assert_eq!(
+      Get::query(&request).unwrap().as_deref(),
+      Some("category=non_trade_activity&direction=desc&date=2024-03-01")
+    );
+  }
+
+  /// Check that a request for activities of a single type is mapped
+  /// onto the expected path and query.
+  #[test]
+  fn typed_activity_request() {
+    let mut request = TypedActivityReq::from(ActivityType::Dividend);
+    request.direction = Direction::Ascending;
+    request.page_size = Some(10);
+
+    assert_eq!(GetByType::path(&request), "/v2/account/activities/DIV");
+    assert_eq!(
+      GetByType::query(&request).unwrap().as_deref(),
+      Some("direction=asc&page_size=10")
+    );
+
+    let request = TypedActivityReq::from(ActivityType::Fill);
+    assert_eq!(GetByType::path(&request), "/v2/account/activities/FILL");
+  }
+
+  /// Check that `fetch_all` transparently pages through activities.
+  #[test(tokio::test)]
+  async fn fetch_all_activities() {
+    let client = mock_client(Pages);
+    let request = ActivityReq {
+      page_size: Some(2),
+      ..Default::default()
+    };
+
+    let activities = fetch_all(&client, request)
+      .try_collect::<Vec<_>>()
+      .await
+      .unwrap();
+    let ids = activities
+      .iter()
+      .map(|activity| activity.id())
+      .collect::<Vec<_>>();
+    assert_eq!(ids, ["1", "2", "3"]);
+  }
+
   /// Verify that the `after` request argument is honored properly.
   #[test(tokio::test)]
   #[ignore = "broken upstream functionality"]
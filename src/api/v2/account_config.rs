@@ -37,6 +37,9 @@ pub struct Configuration {
   /// If enabled, the account can only submit buy orders.
   #[serde(rename = "no_shorting")]
   pub no_shorting: bool,
+  /// Whether the account may trade fractional shares.
+  #[serde(rename = "fractional_trading")]
+  pub fractional_trading: bool,
   /// The type is non-exhaustive and open to extension.
   #[doc(hidden)]
   #[serde(skip)]
@@ -110,13 +113,15 @@ mod tests {
   "dtbp_check": "entry",
   "no_shorting": false,
   "suspend_trade": false,
-  "trade_confirm_email": "all"
+  "trade_confirm_email": "all",
+  "fractional_trading": true
 }"#;
 
     let config = from_json::<Configuration>(response).unwrap();
     assert_eq!(config.trade_confirmation, TradeConfirmation::Email);
     assert!(!config.trading_suspended);
     assert!(!config.no_shorting);
+    assert!(config.fractional_trading);
   }
 
   #[test(tokio::test)]
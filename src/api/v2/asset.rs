@@ -1,12 +1,18 @@
 // Copyright (C) 2019-2024 The apca Developers
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::fmt::Display;
 use std::fmt::Formatter;
 use std::fmt::Result as FmtResult;
 use std::ops::Deref;
 use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+use num_decimal::Num;
 
 use serde::Deserialize;
 use serde::Serialize;
@@ -15,6 +21,8 @@ use serde::Serializer;
 use uuid::Error as UuidError;
 use uuid::Uuid;
 
+use crate::Client;
+use crate::RequestError;
 use crate::Str;
 
 
@@ -62,6 +70,13 @@ impl AsRef<str> for Class {
   }
 }
 
+impl Display for Class {
+  #[inline]
+  fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+    fmt.write_str(self.as_ref())
+  }
+}
+
 impl Default for Class {
   #[inline]
   fn default() -> Self {
@@ -118,6 +133,13 @@ impl AsRef<str> for Status {
   }
 }
 
+impl Display for Status {
+  #[inline]
+  fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+    fmt.write_str(self.as_ref())
+  }
+}
+
 impl Default for Status {
   #[inline]
   fn default() -> Self {
@@ -125,6 +147,24 @@ impl Default for Status {
   }
 }
 
+impl FromStr for Status {
+  type Err = ();
+
+  #[inline]
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    if s == Status::Active.as_ref() {
+      Ok(Status::Active)
+    } else if s == Status::Inactive.as_ref() {
+      Ok(Status::Inactive)
+    } else {
+      // Note that we do not support creating the `Unknown` variant
+      // here. This variant is really only meant to cover
+      // deserialization.
+      Err(())
+    }
+  }
+}
+
 
 /// An enumeration of all possible symbol parsing errors.
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -300,6 +340,12 @@ impl AsRef<str> for Exchange {
   }
 }
 
+impl Display for Exchange {
+  fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+    fmt.write_str(self.as_ref())
+  }
+}
+
 impl FromStr for Exchange {
   type Err = ();
 
@@ -316,6 +362,8 @@ impl FromStr for Exchange {
       Ok(Exchange::Nyse)
     } else if s == Exchange::Nysearca.as_ref() {
       Ok(Exchange::Nysearca)
+    } else if s == Exchange::Otc.as_ref() {
+      Ok(Exchange::Otc)
     } else {
       // Note that we do not support creating the `Unknown` variant
       // here. This variant is really only meant to cover
@@ -361,11 +409,33 @@ pub struct Asset {
   /// Whether the asset is fractionable or not.
   #[serde(rename = "fractionable")]
   pub fractionable: bool,
+  /// The increment prices of the asset have to be a multiple of, if
+  /// reported.
+  ///
+  /// This information is only reported for crypto currencies.
+  #[serde(
+    rename = "price_increment",
+    default,
+    skip_serializing_if = "Option::is_none"
+  )]
+  pub price_increment: Option<Num>,
   #[doc(hidden)]
   #[serde(skip)]
   pub _non_exhaustive: (),
 }
 
+impl Asset {
+  /// Check whether the asset can be sold short without a locate.
+  ///
+  /// Alpaca does not support locating hard-to-borrow securities, so
+  /// only tradable assets that are both shortable and easy-to-borrow
+  /// can be shorted.
+  #[inline]
+  pub fn can_short(&self) -> bool {
+    self.tradable && self.shortable && self.easy_to_borrow
+  }
+}
+
 
 Endpoint! {
   /// The representation of a GET request to the /v2/assets/{symbol} endpoint.
@@ -386,6 +456,105 @@ Endpoint! {
 }
 
 
+/// A cache of assets retrieved by means of the [`Get`] endpoint, with
+/// entries expiring after a fixed amount of time.
+#[derive(Debug)]
+pub(crate) struct TtlCache {
+  /// The time after which entries expire.
+  ttl: Duration,
+  /// The cached assets, keyed by the symbol they were retrieved for.
+  assets: Mutex<HashMap<String, (Instant, Asset)>>,
+}
+
+impl TtlCache {
+  /// Create a new empty cache with the given time to live for its
+  /// entries.
+  pub(crate) fn new(ttl: Duration) -> Self {
+    Self {
+      ttl,
+      assets: Mutex::new(HashMap::new()),
+    }
+  }
+
+  /// Retrieve the non-expired asset cached for the given symbol, if
+  /// any.
+  fn get(&self, symbol: &str) -> Option<Asset> {
+    let mut assets = self.assets.lock().unwrap();
+    match assets.get(symbol) {
+      Some((retrieved, asset)) if retrieved.elapsed() < self.ttl => Some(asset.clone()),
+      Some(..) => {
+        let _expired = assets.remove(symbol);
+        None
+      },
+      None => None,
+    }
+  }
+
+  /// Cache the provided asset as retrieved for the given symbol.
+  fn insert(&self, symbol: String, asset: Asset) {
+    let _prev = self
+      .assets
+      .lock()
+      .unwrap()
+      .insert(symbol, (Instant::now(), asset));
+  }
+}
+
+
+/// Retrieve the asset for the given symbol, consulting the client's
+/// asset cache first, if enabled.
+///
+/// Caching is enabled by means of the `asset_cache_ttl` method of the
+/// builder returned by [`Client::builder`]. Without it, this function
+/// always issues a request to the [`Get`] endpoint.
+pub async fn get_cached(client: &Client, symbol: &Symbol) -> Result<Asset, RequestError<GetError>> {
+  let cache = client.asset_cache();
+  let key = symbol.to_string();
+  if let Some(asset) = cache.and_then(|cache| cache.get(&key)) {
+    return Ok(asset)
+  }
+
+  let asset = client.issue::<Get>(symbol).await?;
+  if let Some(cache) = cache {
+    let () = cache.insert(key, asset.clone());
+  }
+  Ok(asset)
+}
+
+
+#[cfg(test)]
+pub(crate) mod test {
+  use super::*;
+
+  use serde_json::from_str as from_json;
+
+
+  /// Create the JSON representation of an `AAPL` asset with the given
+  /// properties.
+  pub(crate) fn asset_json(tradable: bool, shortable: bool, fractionable: bool) -> String {
+    format!(
+      r#"{{
+  "id": "b0b6dd9d-8b9b-48a9-ba46-b9d54906e415",
+  "class": "us_equity",
+  "exchange": "NASDAQ",
+  "symbol": "AAPL",
+  "status": "active",
+  "tradable": {tradable},
+  "marginable": true,
+  "shortable": {shortable},
+  "easy_to_borrow": {shortable},
+  "fractionable": {fractionable}
+}}"#
+    )
+  }
+
+  /// Create an `AAPL` [`Asset`] object with the given properties.
+  pub(crate) fn asset(tradable: bool, shortable: bool, fractionable: bool) -> Asset {
+    from_json::<Asset>(&asset_json(tradable, shortable, fractionable)).unwrap()
+  }
+}
+
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -512,6 +681,7 @@ mod tests {
 
     let asset = from_json::<Asset>(response).unwrap();
     assert_eq!(asset.exchange, Exchange::Unknown);
+    assert_eq!(asset.price_increment, None);
   }
 
   /// Check that we can serialize and deserialize an `Asset` object.
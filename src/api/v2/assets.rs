@@ -1,13 +1,21 @@
 // Copyright (C) 2019-2024 The apca Developers
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+use std::collections::HashMap;
+use std::sync::Mutex;
+
 use serde::Deserialize;
 use serde::Serialize;
 use serde_urlencoded::to_string as to_query;
 
 use crate::api::v2::asset::Asset;
 use crate::api::v2::asset::Class;
+use crate::api::v2::asset::Id;
 use crate::api::v2::asset::Status;
+use crate::api::v2::asset::Symbol;
+use crate::api::v2::order::CreateReq;
+use crate::Client;
+use crate::RequestError;
 use crate::Str;
 
 
@@ -47,6 +55,139 @@ Endpoint! {
 }
 
 
+/// The assets held by a [`Cache`], indexed for lookups in both
+/// directions.
+#[derive(Debug, Default)]
+struct Indexed {
+  /// The assets, keyed by their symbol.
+  by_symbol: HashMap<String, Asset>,
+  /// The symbols of assets, keyed by the asset ID.
+  by_id: HashMap<Id, String>,
+}
+
+impl From<Vec<Asset>> for Indexed {
+  fn from(assets: Vec<Asset>) -> Self {
+    let by_id = assets
+      .iter()
+      .map(|asset| (asset.id, asset.symbol.clone()))
+      .collect();
+    let by_symbol = assets
+      .into_iter()
+      .map(|asset| (asset.symbol.clone(), asset))
+      .collect();
+
+    Self { by_symbol, by_id }
+  }
+}
+
+
+/// A cache of the assets as reported by the [`List`] endpoint,
+/// providing for resolution of symbols to asset IDs and vice versa.
+///
+/// Assets are loaded lazily on first use and kept until explicitly
+/// [refreshed][Cache::refresh].
+#[derive(Debug)]
+pub struct Cache {
+  /// The request used for retrieving the assets to cache.
+  request: ListReq,
+  /// The cached assets, if loaded already.
+  assets: Mutex<Option<Indexed>>,
+}
+
+impl Cache {
+  /// Create a new cache for the assets matching the provided request.
+  pub fn new(request: ListReq) -> Self {
+    Self {
+      request,
+      assets: Mutex::new(None),
+    }
+  }
+
+  /// (Re-)load the cached assets.
+  pub async fn refresh(&self, client: &Client) -> Result<(), RequestError<ListError>> {
+    let assets = client.issue::<List>(&self.request).await?;
+    *self.assets.lock().unwrap() = Some(Indexed::from(assets));
+    Ok(())
+  }
+
+  /// Evaluate the given function on the cached assets, loading them
+  /// first if necessary.
+  async fn with<F, T>(&self, client: &Client, f: F) -> Result<T, RequestError<ListError>>
+  where
+    F: Fn(&Indexed) -> T,
+  {
+    if let Some(assets) = &*self.assets.lock().unwrap() {
+      return Ok(f(assets))
+    }
+
+    let () = self.refresh(client).await?;
+    // SANITY: We just loaded the assets.
+    Ok(f(self.assets.lock().unwrap().as_ref().unwrap()))
+  }
+
+  /// Retrieve the asset with the given symbol.
+  pub async fn get(
+    &self,
+    client: &Client,
+    symbol: &str,
+  ) -> Result<Option<Asset>, RequestError<ListError>> {
+    self
+      .with(client, |assets| assets.by_symbol.get(symbol).cloned())
+      .await
+  }
+
+  /// Resolve a symbol to the ID of the asset it represents.
+  pub async fn resolve(
+    &self,
+    client: &Client,
+    symbol: &str,
+  ) -> Result<Option<Id>, RequestError<ListError>> {
+    self
+      .with(client, |assets| {
+        assets.by_symbol.get(symbol).map(|asset| asset.id)
+      })
+      .await
+  }
+
+  /// Look up the symbol of the asset with the given ID.
+  pub async fn symbol(
+    &self,
+    client: &Client,
+    id: Id,
+  ) -> Result<Option<String>, RequestError<ListError>> {
+    self
+      .with(client, |assets| assets.by_id.get(&id).cloned())
+      .await
+  }
+
+  /// Replace the plain symbol of an order request with the ID of the
+  /// asset it represents, for submitting an order that unambiguously
+  /// refers to a single asset.
+  ///
+  /// Returns `false` if the request already refers to the asset in a
+  /// different way or the symbol is not known to the cache, in which
+  /// case the request is left untouched.
+  pub async fn resolve_order(
+    &self,
+    client: &Client,
+    request: &mut CreateReq,
+  ) -> Result<bool, RequestError<ListError>> {
+    let id = match &request.symbol {
+      Symbol::Sym(symbol) => self.resolve(client, symbol).await?,
+      _ => None,
+    };
+
+    match id {
+      Some(id) => {
+        request.symbol = Symbol::Id(id);
+        Ok(true)
+      },
+      None => Ok(false),
+    }
+  }
+}
+
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -56,9 +197,17 @@ mod tests {
 
   use test_log::test;
 
+  use http::StatusCode;
+
+  use uuid::Uuid;
+
   use crate::api::v2::asset::Exchange;
+  use crate::api::v2::order::Amount;
+  use crate::api::v2::order::CreateReqInit;
+  use crate::api::v2::order::Side;
   use crate::api_info::ApiInfo;
-  use crate::Client;
+  use crate::transport::test::mock_client;
+  use crate::MockTransport;
 
 
   /// Check that we can serialize and deserialize a [`ListReq`].
@@ -74,6 +223,49 @@ mod tests {
     assert_eq!(from_json::<ListReq>(&json).unwrap(), request);
   }
 
+  /// Check that an asset [`Cache`] resolves symbols and IDs.
+  #[test(tokio::test)]
+  async fn cache_resolution() {
+    let mock = MockTransport::new();
+    let () = mock.respond::<List>(
+      &ListReq::default(),
+      StatusCode::OK,
+      r#"[{
+  "id": "b0b6dd9d-8b9b-48a9-ba46-b9d54906e415",
+  "class": "us_equity",
+  "exchange": "NASDAQ",
+  "symbol": "AAPL",
+  "status": "active",
+  "tradable": true,
+  "marginable": true,
+  "shortable": true,
+  "easy_to_borrow": true,
+  "fractionable": true
+}]"#,
+    );
+
+    let client = mock_client(mock.clone());
+    let cache = Cache::new(ListReq::default());
+
+    let id = Id(Uuid::parse_str("b0b6dd9d-8b9b-48a9-ba46-b9d54906e415").unwrap());
+    assert_eq!(cache.resolve(&client, "AAPL").await.unwrap(), Some(id));
+    assert_eq!(cache.resolve(&client, "MSFT").await.unwrap(), None);
+    assert_eq!(
+      cache.symbol(&client, id).await.unwrap().as_deref(),
+      Some("AAPL")
+    );
+
+    let mut request = CreateReqInit::default().init("AAPL", Side::Buy, Amount::quantity(1));
+    assert!(cache.resolve_order(&client, &mut request).await.unwrap());
+    assert_eq!(request.symbol, Symbol::Id(id));
+    assert!(!cache.resolve_order(&client, &mut request).await.unwrap());
+
+    // Assets are only reloaded when refreshing explicitly.
+    let () = mock.respond::<List>(&ListReq::default(), StatusCode::OK, "[]");
+    assert!(cache.get(&client, "AAPL").await.unwrap().is_some());
+    let () = cache.refresh(&client).await.unwrap();
+    assert!(cache.get(&client, "AAPL").await.unwrap().is_none());
+  }
 
   /// Make sure that we can list available US stock assets.
   #[test(tokio::test)]
@@ -1,10 +1,17 @@
 // Copyright (C) 2022-2024 The apca Developers
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+use std::collections::BTreeMap;
 use std::ops::Range;
 
+use chrono::DateTime;
+use chrono::Datelike as _;
+use chrono::Duration;
 use chrono::NaiveDate;
+use chrono::NaiveDateTime;
 use chrono::NaiveTime;
+use chrono::Utc;
+use chrono::Weekday;
 
 use serde::de::Error;
 use serde::de::Unexpected;
@@ -14,6 +21,8 @@ use serde::Serialize;
 use serde::Serializer;
 use serde_urlencoded::to_string as to_query;
 
+use crate::Client;
+use crate::RequestError;
 use crate::Str;
 
 
@@ -39,6 +48,68 @@ where
   serializer.serialize_str(&time.format("%H:%M").to_string())
 }
 
+/// Deserialize an optional session `NaiveTime` from a string.
+fn deserialize_session_time<'de, D>(deserializer: D) -> Result<Option<NaiveTime>, D::Error>
+where
+  D: Deserializer<'de>,
+{
+  let string = Option::<String>::deserialize(deserializer)?;
+  string
+    .map(|string| {
+      NaiveTime::parse_from_str(&string, "%H%M").map_err(|_| {
+        Error::invalid_value(
+          Unexpected::Str(&string),
+          &"a time stamp string in format %H%M",
+        )
+      })
+    })
+    .transpose()
+}
+
+/// Serialize an optional session `NaiveTime` into a string.
+fn serialize_session_time<S>(time: &Option<NaiveTime>, serializer: S) -> Result<S::Ok, S::Error>
+where
+  S: Serializer,
+{
+  match time {
+    Some(time) => serializer.serialize_some(&time.format("%H%M").to_string()),
+    None => serializer.serialize_none(),
+  }
+}
+
+
+/// The time at which the market regularly closes.
+fn regular_close() -> NaiveTime {
+  NaiveTime::from_hms_opt(16, 0, 0).unwrap()
+}
+
+
+/// Convert the provided time stamp into the exchange's local time (US
+/// Eastern).
+///
+/// Daylight saving time is accounted for based on the rules in effect
+/// since 2007, i.e., it starts on the second Sunday in March and ends
+/// on the first Sunday in November, in both cases at 02:00 local time.
+pub fn to_exchange_time(time: DateTime<Utc>) -> NaiveDateTime {
+  let year = time.year();
+  let transition = |month, n, hour| {
+    NaiveDate::from_weekday_of_month_opt(year, month, Weekday::Sun, n)
+      .and_then(|date| date.and_hms_opt(hour, 0, 0))
+      .unwrap()
+  };
+  // 02:00 EST and 02:00 EDT, respectively, expressed in UTC.
+  let dst_start = transition(3, 2, 7);
+  let dst_end = transition(11, 1, 6);
+
+  let utc = time.naive_utc();
+  let offset = if utc >= dst_start && utc < dst_end {
+    4
+  } else {
+    5
+  };
+  utc - Duration::try_hours(offset).unwrap()
+}
+
 
 /// The market open and close times for a specific date.
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -60,12 +131,39 @@ pub struct OpenClose {
     serialize_with = "serialize_naive_time"
   )]
   pub close: NaiveTime,
+  /// The time the extended hours session opens at, i.e., the start of
+  /// pre-market trading.
+  #[serde(
+    rename = "session_open",
+    default,
+    deserialize_with = "deserialize_session_time",
+    serialize_with = "serialize_session_time"
+  )]
+  pub session_open: Option<NaiveTime>,
+  /// The time the extended hours session closes at, i.e., the end of
+  /// post-market trading.
+  #[serde(
+    rename = "session_close",
+    default,
+    deserialize_with = "deserialize_session_time",
+    serialize_with = "serialize_session_time"
+  )]
+  pub session_close: Option<NaiveTime>,
   /// The type is non-exhaustive and open to extension.
   #[doc(hidden)]
   #[serde(skip)]
   pub _non_exhaustive: (),
 }
 
+impl OpenClose {
+  /// Check whether the market closes early on this date, i.e., before
+  /// the regular 16:00 close.
+  #[inline]
+  pub fn is_early_close(&self) -> bool {
+    self.close < regular_close()
+  }
+}
+
 
 /// A GET request to be made to the /v2/calendar endpoint.
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -106,10 +204,14 @@ pub struct ListReqInit {
   pub _non_exhaustive: (),
 }
 
+Setters! {
+  ListReqInit {}
+}
+
 impl ListReqInit {
   /// Create a [`ListReq`] from a `ListReqInit`.
   #[inline]
-  pub fn init(self, start: NaiveDate, end: NaiveDate) -> ListReq {
+  pub fn init(&self, start: NaiveDate, end: NaiveDate) -> ListReq {
     let Self { _non_exhaustive } = self;
 
     ListReq {
@@ -140,6 +242,122 @@ Endpoint! {
 }
 
 
+/// A market calendar answering trading day related queries without
+/// issuing further requests.
+///
+/// The calendar only knows about the dates it was created with, e.g.,
+/// the range of dates retrieved via [`load`][TradingCalendar::load].
+/// Dates outside of this range are treated as non-trading days. All
+/// times are in the exchange's local time zone (US Eastern).
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TradingCalendar {
+  /// The trading days, keyed by date.
+  days: BTreeMap<NaiveDate, OpenClose>,
+}
+
+impl TradingCalendar {
+  /// Create a `TradingCalendar` from the provided trading days.
+  pub fn new<I>(days: I) -> Self
+  where
+    I: IntoIterator<Item = OpenClose>,
+  {
+    Self {
+      days: days
+        .into_iter()
+        .map(|open_close| (open_close.date, open_close))
+        .collect(),
+    }
+  }
+
+  /// Load the trading calendar for the provided range of dates.
+  pub async fn load(client: &Client, request: &ListReq) -> Result<Self, RequestError<ListError>> {
+    let days = client.issue::<List>(request).await?;
+    Ok(Self::new(days))
+  }
+
+  /// Retrieve the open and close times for the provided date, if it
+  /// is a trading day.
+  #[inline]
+  pub fn get(&self, date: NaiveDate) -> Option<&OpenClose> {
+    self.days.get(&date)
+  }
+
+  /// Check whether the provided date is a trading day.
+  #[inline]
+  pub fn is_trading_day(&self, date: NaiveDate) -> bool {
+    self.days.contains_key(&date)
+  }
+
+  /// Find the first trading day strictly after the provided date.
+  pub fn next_trading_day(&self, date: NaiveDate) -> Option<NaiveDate> {
+    self
+      .days
+      .range(date.succ_opt()?..)
+      .next()
+      .map(|(date, _)| *date)
+  }
+
+  /// Find the last trading day strictly before the provided date.
+  pub fn previous_trading_day(&self, date: NaiveDate) -> Option<NaiveDate> {
+    self.days.range(..date).next_back().map(|(date, _)| *date)
+  }
+
+  /// Retrieve the start and end of the regular trading session on the
+  /// provided date, if it is a trading day.
+  pub fn session_bounds(&self, date: NaiveDate) -> Option<(NaiveDateTime, NaiveDateTime)> {
+    self.days.get(&date).map(|open_close| {
+      (
+        date.and_time(open_close.open),
+        date.and_time(open_close.close),
+      )
+    })
+  }
+
+  /// Check whether the regular trading session is in progress at the
+  /// provided time.
+  pub fn is_open_at(&self, time: DateTime<Utc>) -> bool {
+    let time = to_exchange_time(time);
+    self
+      .session_bounds(time.date())
+      .map(|(open, close)| open <= time && time < close)
+      .unwrap_or(false)
+  }
+
+  /// Count the number of minutes of regular trading between `start`
+  /// (inclusive) and `end` (exclusive).
+  ///
+  /// Partial minutes are truncated. Zero is returned if `end` is not
+  /// after `start`.
+  pub fn trading_minutes_between(&self, start: NaiveDateTime, end: NaiveDateTime) -> i64 {
+    if end <= start {
+      return 0
+    }
+
+    self
+      .days
+      .range(start.date()..=end.date())
+      .map(|(date, open_close)| {
+        let open = date.and_time(open_close.open).max(start);
+        let close = date.and_time(open_close.close).min(end);
+        if close > open {
+          (close - open).num_seconds()
+        } else {
+          0
+        }
+      })
+      .sum::<i64>()
+      / 60
+  }
+}
+
+impl From<Vec<OpenClose>> for TradingCalendar {
+  #[inline]
+  fn from(days: Vec<OpenClose>) -> Self {
+    Self::new(days)
+  }
+}
+
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -160,6 +378,8 @@ mod tests {
       date: NaiveDate::from_ymd_opt(2020, 4, 9).unwrap(),
       open: NaiveTime::from_hms_opt(9, 30, 0).unwrap(),
       close: NaiveTime::from_hms_opt(16, 0, 0).unwrap(),
+      session_open: NaiveTime::from_hms_opt(4, 0, 0),
+      session_close: NaiveTime::from_hms_opt(20, 0, 0),
       _non_exhaustive: (),
     };
 
@@ -167,6 +387,29 @@ mod tests {
     assert_eq!(from_json::<OpenClose>(&json).unwrap(), open_close);
   }
 
+  /// Check that we can parse a reference calendar entry, including
+  /// session times, and detect early closes.
+  #[test]
+  fn parse_reference_open_close() {
+    let serialized = br#"{
+  "date": "2023-11-24",
+  "open": "09:30",
+  "close": "13:00",
+  "session_open": "0400",
+  "session_close": "1700"
+}"#;
+    let open_close = from_json::<OpenClose>(serialized).unwrap();
+    assert_eq!(open_close.close, NaiveTime::from_hms_opt(13, 0, 0).unwrap());
+    assert_eq!(open_close.session_open, NaiveTime::from_hms_opt(4, 0, 0));
+    assert_eq!(open_close.session_close, NaiveTime::from_hms_opt(17, 0, 0));
+    assert!(open_close.is_early_close());
+
+    let serialized = br#"{"date":"2023-11-27","open":"09:30","close":"16:00"}"#;
+    let open_close = from_json::<OpenClose>(serialized).unwrap();
+    assert_eq!(open_close.session_open, None);
+    assert!(!open_close.is_early_close());
+  }
+
   /// Check that we error out as expected when failing to parse an
   /// `OpenClose` object because the time format is unexpected.
   #[test]
@@ -178,6 +421,93 @@ mod tests {
       .starts_with("invalid value: string \"09:30:00\""));
   }
 
+  /// Check that a `TradingCalendar` answers queries as expected.
+  #[test]
+  fn trading_calendar() {
+    let day = |day, close| OpenClose {
+      date: NaiveDate::from_ymd_opt(2023, 11, day).unwrap(),
+      open: NaiveTime::from_hms_opt(9, 30, 0).unwrap(),
+      close: NaiveTime::from_hms_opt(close, 0, 0).unwrap(),
+      session_open: NaiveTime::from_hms_opt(4, 0, 0),
+      session_close: NaiveTime::from_hms_opt(20, 0, 0),
+      _non_exhaustive: (),
+    };
+    let date = |day| NaiveDate::from_ymd_opt(2023, 11, day).unwrap();
+    let time = |day, hour, minute| date(day).and_hms_opt(hour, minute, 0).unwrap();
+
+    // Thanksgiving (the 23rd) is a holiday and the market closes early
+    // the day after.
+    let calendar = TradingCalendar::new([day(22, 16), day(24, 13), day(27, 16)]);
+    assert!(calendar.is_trading_day(date(22)));
+    assert!(!calendar.is_trading_day(date(23)));
+    assert!(!calendar.is_trading_day(date(25)));
+    assert_eq!(calendar.next_trading_day(date(22)), Some(date(24)));
+    assert_eq!(calendar.next_trading_day(date(24)), Some(date(27)));
+    assert_eq!(calendar.next_trading_day(date(27)), None);
+    assert_eq!(calendar.previous_trading_day(date(27)), Some(date(24)));
+    assert_eq!(calendar.previous_trading_day(date(22)), None);
+    assert_eq!(
+      calendar.session_bounds(date(24)),
+      Some((time(24, 9, 30), time(24, 13, 0)))
+    );
+    assert_eq!(calendar.session_bounds(date(23)), None);
+
+    // 14:30 UTC is 09:30 EST.
+    let utc = |day, hour, minute| time(day, hour, minute).and_utc();
+    assert!(!calendar.is_open_at(utc(22, 14, 29)));
+    assert!(calendar.is_open_at(utc(22, 14, 30)));
+    assert!(calendar.is_open_at(utc(22, 20, 59)));
+    assert!(!calendar.is_open_at(utc(22, 21, 0)));
+    assert!(!calendar.is_open_at(utc(23, 15, 0)));
+    assert!(calendar.is_open_at(utc(24, 17, 59)));
+    assert!(!calendar.is_open_at(utc(24, 18, 0)));
+
+    assert_eq!(
+      calendar.trading_minutes_between(time(22, 15, 0), time(24, 10, 0)),
+      60 + 30
+    );
+    assert_eq!(
+      calendar.trading_minutes_between(time(22, 0, 0), time(28, 0, 0)),
+      390 + 210 + 390
+    );
+    assert_eq!(
+      calendar.trading_minutes_between(time(24, 14, 0), time(27, 9, 30)),
+      0
+    );
+    assert_eq!(
+      calendar.trading_minutes_between(time(27, 10, 0), time(27, 9, 0)),
+      0
+    );
+  }
+
+  /// Check that we convert time stamps into the exchange's local time
+  /// correctly, including around daylight saving time transitions.
+  #[test]
+  fn exchange_time_conversion() {
+    let utc = |month, day, hour, minute| {
+      NaiveDate::from_ymd_opt(2024, month, day)
+        .unwrap()
+        .and_hms_opt(hour, minute, 0)
+        .unwrap()
+        .and_utc()
+    };
+    let local = |month, day, hour, minute| {
+      NaiveDate::from_ymd_opt(2024, month, day)
+        .unwrap()
+        .and_hms_opt(hour, minute, 0)
+        .unwrap()
+    };
+
+    assert_eq!(to_exchange_time(utc(1, 2, 14, 30)), local(1, 2, 9, 30));
+    assert_eq!(to_exchange_time(utc(7, 1, 13, 30)), local(7, 1, 9, 30));
+    // Daylight saving time started on March 10th, 2024, at 02:00 EST.
+    assert_eq!(to_exchange_time(utc(3, 10, 6, 59)), local(3, 10, 1, 59));
+    assert_eq!(to_exchange_time(utc(3, 10, 7, 0)), local(3, 10, 3, 0));
+    // ... and ended on November 3rd, 2024, at 02:00 EDT.
+    assert_eq!(to_exchange_time(utc(11, 3, 5, 59)), local(11, 3, 1, 59));
+    assert_eq!(to_exchange_time(utc(11, 3, 6, 0)), local(11, 3, 1, 0));
+  }
+
   /// Check that we can serialize and deserialize a [`CalendarReq`].
   #[test]
   fn serialize_deserialize_calendar_request() {
@@ -208,6 +538,8 @@ mod tests {
         date: NaiveDate::from_ymd_opt(2020, 4, day).unwrap(),
         open: NaiveTime::from_hms_opt(9, 30, 0).unwrap(),
         close: NaiveTime::from_hms_opt(16, 0, 0).unwrap(),
+        session_open: NaiveTime::from_hms_opt(4, 0, 0),
+        session_close: NaiveTime::from_hms_opt(20, 0, 0),
         _non_exhaustive: (),
       })
       .collect::<Vec<_>>();
@@ -1,16 +1,36 @@
 // Copyright (C) 2019-2024 The apca Developers
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+use std::time::Duration;
+
 use chrono::DateTime;
+use chrono::Duration as ChronoDuration;
 use chrono::Utc;
 
 use serde::Deserialize;
 use serde::Serialize;
 
+use tokio::time::sleep;
+
+use crate::Client;
+use crate::RequestError;
 use crate::Str;
 
 
+/// The minimum amount of time we sleep between polls of the clock.
+const MIN_SLEEP: Duration = Duration::from_secs(1);
+/// The maximum amount of time we sleep between polls of the clock.
+///
+/// We re-synchronize with the server's clock at least this often, so
+/// that local clock drift and system suspension do not delay us
+/// indefinitely.
+const MAX_SLEEP: Duration = Duration::from_secs(30 * 60);
+
+
 /// A type encapsulating market open/close timing information.
+///
+/// A `Clock` is a snapshot of the market state at the time it was
+/// retrieved and can be serialized for later use.
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct Clock {
   /// An indication whether the market is currently open or not.
@@ -31,6 +51,37 @@ pub struct Clock {
   pub _non_exhaustive: (),
 }
 
+impl Clock {
+  /// Check whether the market is open at the provided time, based
+  /// solely on the information contained in this snapshot.
+  ///
+  /// The clock only knows about the time span from its `current` time
+  /// up to the next market open or close, whichever comes first, and,
+  /// if the market is open, up to the next open after the close.
+  /// `None` is returned for times outside of it. Use
+  /// [`TradingCalendar::is_open_at`][crate::api::v2::calendar::TradingCalendar::is_open_at]
+  /// for answering the question for arbitrary times.
+  pub fn is_open_at(&self, time: DateTime<Utc>) -> Option<bool> {
+    if time < self.current {
+      return None
+    }
+
+    if self.open {
+      if time < self.next_close {
+        Some(true)
+      } else if time < self.next_open {
+        Some(false)
+      } else {
+        None
+      }
+    } else if time < self.next_open {
+      Some(false)
+    } else {
+      None
+    }
+  }
+}
+
 
 Endpoint! {
   /// The representation of a GET request to the /v2/clock endpoint.
@@ -48,13 +99,81 @@ Endpoint! {
 }
 
 
+/// Calculate the time to sleep before re-checking the clock, given the
+/// remaining time until the event of interest.
+fn sleep_duration(remaining: ChronoDuration) -> Duration {
+  remaining
+    .to_std()
+    .unwrap_or(Duration::ZERO)
+    .clamp(MIN_SLEEP, MAX_SLEEP)
+}
+
+
+/// Measure the skew of the server's clock relative to the local one.
+///
+/// The server's time is compared against the local time half way
+/// through the request, meaning that the precision of the measurement
+/// is bounded by the request's latency. A positive skew means that the
+/// server's clock is ahead. The result is also recorded on the client,
+/// where it can subsequently be retrieved by means of
+/// [`Client::clock_skew`]. A warning is emitted if the skew exceeds the
+/// threshold configured on the builder, if any. Call this function
+/// again to refresh the measurement.
+pub async fn measure_skew(client: &Client) -> Result<ChronoDuration, RequestError<GetError>> {
+  let before = Utc::now();
+  let clock = client.issue::<Get>(&()).await?;
+  let after = Utc::now();
+
+  let local = before + (after - before) / 2;
+  let skew = clock.current - local;
+  let () = client.set_clock_skew(skew);
+  Ok(skew)
+}
+
+
+/// Wait until the market is open.
+///
+/// If the market is currently open this function returns immediately.
+/// Otherwise it sleeps until the next open, periodically re-checking
+/// the market clock along the way. The most recently retrieved
+/// [`Clock`] is returned.
+pub async fn wait_until_open(client: &Client) -> Result<Clock, RequestError<GetError>> {
+  loop {
+    let clock = client.issue::<Get>(&()).await?;
+    if clock.open {
+      break Ok(clock)
+    }
+
+    let () = sleep(sleep_duration(clock.next_open - clock.current)).await;
+  }
+}
+
+
+/// Wait until the market is closed.
+///
+/// If the market is currently closed this function returns
+/// immediately. Otherwise it sleeps until the next close, periodically
+/// re-checking the market clock along the way. The most recently
+/// retrieved [`Clock`] is returned.
+pub async fn wait_until_close(client: &Client) -> Result<Clock, RequestError<GetError>> {
+  loop {
+    let clock = client.issue::<Get>(&()).await?;
+    if !clock.open {
+      break Ok(clock)
+    }
+
+    let () = sleep(sleep_duration(clock.next_close - clock.current)).await;
+  }
+}
+
+
 #[cfg(test)]
 mod tests {
   use super::*;
 
   use std::str::FromStr as _;
 
-  use chrono::Duration;
+  use http::StatusCode;
 
   use serde_json::from_str as from_json;
   use serde_json::to_string as to_json;
@@ -63,7 +182,9 @@ mod tests {
 
   use crate::api::API_BASE_URL;
   use crate::api_info::ApiInfo;
+  use crate::transport::test::mock_api_info;
   use crate::Client;
+  use crate::MockTransport;
   use crate::RequestError;
 
 
@@ -86,6 +207,96 @@ mod tests {
     );
   }
 
+  /// Check that a `Clock` answers whether the market is open within the
+  /// time span it knows about.
+  #[test]
+  fn clock_is_open_at() {
+    let time = |s| DateTime::<Utc>::from_str(s).unwrap();
+    let clock = Clock {
+      open: true,
+      current: time("2024-01-02T15:00:00Z"),
+      next_open: time("2024-01-03T14:30:00Z"),
+      next_close: time("2024-01-02T21:00:00Z"),
+      _non_exhaustive: (),
+    };
+
+    assert_eq!(clock.is_open_at(time("2024-01-02T14:59:59Z")), None);
+    assert_eq!(clock.is_open_at(time("2024-01-02T15:00:00Z")), Some(true));
+    assert_eq!(clock.is_open_at(time("2024-01-02T20:59:59Z")), Some(true));
+    assert_eq!(clock.is_open_at(time("2024-01-02T21:00:00Z")), Some(false));
+    assert_eq!(clock.is_open_at(time("2024-01-03T14:29:59Z")), Some(false));
+    assert_eq!(clock.is_open_at(time("2024-01-03T14:30:00Z")), None);
+
+    let clock = Clock {
+      open: false,
+      current: time("2024-01-02T22:00:00Z"),
+      ..clock
+    };
+    assert_eq!(clock.is_open_at(time("2024-01-03T14:29:59Z")), Some(false));
+    assert_eq!(clock.is_open_at(time("2024-01-03T14:30:00Z")), None);
+  }
+
+  /// Check that we can measure the skew of the server's clock and
+  /// that it gets recorded on the client.
+  #[test(tokio::test)]
+  async fn measure_clock_skew() {
+    let mock = MockTransport::new();
+    let () = mock.respond::<Get>(
+      &(),
+      StatusCode::OK,
+      r#"{
+  "timestamp": "2018-04-01T12:00:00.000Z",
+  "is_open": true,
+  "next_open": "2018-04-01T12:00:00.000Z",
+  "next_close": "2018-04-01T12:00:00.000Z"
+}"#,
+    );
+    let api_info = mock_api_info();
+    let client = Client::builder()
+      .with_transport(mock)
+      .clock_skew_threshold(Duration::from_secs(1))
+      .build(api_info);
+    assert_eq!(client.clock_skew(), None);
+
+    let skew = measure_skew(&client).await.unwrap();
+    // The canned server time lies in the past.
+    assert!(skew < ChronoDuration::zero());
+    assert_eq!(client.clock_skew(), Some(skew));
+  }
+
+  /// Check that we clamp the time we sleep between clock polls.
+  #[test]
+  fn clamp_sleep_duration() {
+    let duration = sleep_duration(ChronoDuration::try_minutes(5).unwrap());
+    assert_eq!(duration, Duration::from_secs(5 * 60));
+
+    let duration = sleep_duration(ChronoDuration::try_hours(14).unwrap());
+    assert_eq!(duration, MAX_SLEEP);
+
+    let duration = sleep_duration(ChronoDuration::zero());
+    assert_eq!(duration, MIN_SLEEP);
+
+    let duration = sleep_duration(ChronoDuration::try_seconds(-10).unwrap());
+    assert_eq!(duration, MIN_SLEEP);
+  }
+
+  /// Check that waiting for the market to be in the state it currently
+  /// is in returns immediately.
+  #[test(tokio::test)]
+  async fn wait_for_current_state() {
+    let api_info = ApiInfo::from_env().unwrap();
+    let client = Client::new(api_info);
+    let clock = client.issue::<Get>(&()).await.unwrap();
+
+    if clock.open {
+      let clock = wait_until_open(&client).await.unwrap();
+      assert!(clock.open);
+    } else {
+      let clock = wait_until_close(&client).await.unwrap();
+      assert!(!clock.open);
+    }
+  }
+
   /// Verify that we can retrieve the current market clock.
   #[test(tokio::test)]
   async fn current_market_clock() {
@@ -99,8 +310,8 @@ mod tests {
     // current time reported by Alpaca is within one hour of our local
     // time (mainly to rule out wrong time zone handling).
     let now = Utc::now();
-    assert!(now > clock.current - Duration::try_hours(1).unwrap());
-    assert!(now < clock.current + Duration::try_hours(1).unwrap());
+    assert!(now > clock.current - ChronoDuration::try_hours(1).unwrap());
+    assert!(now < clock.current + ChronoDuration::try_hours(1).unwrap());
 
     assert!(clock.current < clock.next_open);
     assert!(clock.current < clock.next_close);
@@ -0,0 +1,414 @@
+// Copyright (C) 2024 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::cmp::min;
+use std::ops::Deref;
+
+use chrono::Duration;
+use chrono::NaiveDate;
+
+use num_decimal::Num;
+
+use serde::Deserialize;
+use serde::Serialize;
+use serde_urlencoded::to_string as to_query;
+
+use uuid::Uuid;
+
+use crate::util::enum_slice_to_str;
+use crate::Str;
+
+
+/// The maximum number of days a single announcements request may
+/// cover.
+const MAX_WINDOW_DAYS: i64 = 90;
+
+
+/// An ID uniquely identifying a corporate action announcement.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct Id(pub Uuid);
+
+impl Deref for Id {
+  type Target = Uuid;
+
+  #[inline]
+  fn deref(&self) -> &Self::Target {
+    &self.0
+  }
+}
+
+
+/// The type of a corporate action.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub enum Type {
+  /// A cash or stock dividend.
+  #[serde(rename(serialize = "Dividend", deserialize = "dividend"))]
+  Dividend,
+  /// A merger of two companies.
+  #[serde(rename(serialize = "Merger", deserialize = "merger"))]
+  Merger,
+  /// A spin-off of part of a company into a new one.
+  #[serde(rename(serialize = "Spinoff", deserialize = "spinoff"))]
+  Spinoff,
+  /// A (forward or reverse) stock split.
+  #[serde(rename(serialize = "Split", deserialize = "split"))]
+  Split,
+  /// Any other corporate action type that we have not accounted for.
+  ///
+  /// Note that having any such unknown type should be considered a bug.
+  #[doc(hidden)]
+  #[serde(other, rename(serialize = "unknown"))]
+  Unknown,
+}
+
+
+/// The date of an announcement that the `since` and `until` filters
+/// of a [`ListReq`] apply to.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub enum DateType {
+  /// The date the corporate action was declared.
+  #[serde(rename = "declaration_date")]
+  Declaration,
+  /// The first date the security trades without the corporate action
+  /// applied.
+  #[serde(rename = "ex_date")]
+  Ex,
+  /// The date a holder of the security has to be on record to be
+  /// entitled to the corporate action.
+  #[serde(rename = "record_date")]
+  Record,
+  /// The date the corporate action is paid out.
+  #[serde(rename = "payable_date")]
+  Payable,
+}
+
+
+/// A corporate action announcement.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct Announcement {
+  /// The announcement's ID.
+  #[serde(rename = "id")]
+  pub id: Id,
+  /// An identifier for the corporate action itself. Several
+  /// announcements may refer to the same corporate action.
+  #[serde(rename = "corporate_action_id")]
+  pub corporate_action_id: String,
+  /// The type of the corporate action.
+  #[serde(rename = "ca_type")]
+  pub type_: Type,
+  /// A more detailed description of the type of the corporate action,
+  /// e.g., "cash" or "stock" for a dividend.
+  #[serde(rename = "ca_sub_type")]
+  pub sub_type: String,
+  /// The symbol of the company initiating the corporate action.
+  #[serde(rename = "initiating_symbol")]
+  pub initiating_symbol: String,
+  /// The CUSIP of the company initiating the corporate action.
+  #[serde(rename = "initiating_original_cusip")]
+  pub initiating_cusip: String,
+  /// The symbol of the company affected by the corporate action, if
+  /// any.
+  #[serde(rename = "target_symbol")]
+  pub target_symbol: Option<String>,
+  /// The CUSIP of the company affected by the corporate action, if
+  /// any.
+  #[serde(rename = "target_original_cusip")]
+  pub target_cusip: Option<String>,
+  /// The date the corporate action was declared.
+  #[serde(rename = "declaration_date")]
+  pub declaration_date: Option<NaiveDate>,
+  /// The first date the security trades without the corporate action
+  /// applied.
+  #[serde(rename = "ex_date")]
+  pub ex_date: Option<NaiveDate>,
+  /// The date a holder of the security has to be on record to be
+  /// entitled to the corporate action.
+  #[serde(rename = "record_date")]
+  pub record_date: Option<NaiveDate>,
+  /// The date the corporate action is paid out.
+  #[serde(rename = "payable_date")]
+  pub payable_date: Option<NaiveDate>,
+  /// The amount of cash paid out per share, if any.
+  #[serde(rename = "cash")]
+  pub cash: Option<Num>,
+  /// The number of shares held before the corporate action per
+  /// `new_rate` shares held afterwards.
+  #[serde(rename = "old_rate")]
+  pub old_rate: Option<Num>,
+  /// The number of shares held after the corporate action per
+  /// `old_rate` shares held before.
+  #[serde(rename = "new_rate")]
+  pub new_rate: Option<Num>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// A GET request to be made to the
+/// /v2/corporate_actions/announcements endpoint.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct ListReq {
+  /// The types of corporate actions to retrieve announcements for.
+  #[serde(rename = "ca_types", serialize_with = "enum_slice_to_str")]
+  pub types: Vec<Type>,
+  /// Only include announcements with a date on or after this one.
+  #[serde(rename = "since")]
+  pub since: NaiveDate,
+  /// Only include announcements with a date on or before this one.
+  ///
+  /// Note that the server rejects requests spanning more than 90 days.
+  /// Use [`ListReq::windows`] to split up larger date ranges.
+  #[serde(rename = "until")]
+  pub until: NaiveDate,
+  /// Only include announcements for the given symbol.
+  #[serde(rename = "symbol", skip_serializing_if = "Option::is_none")]
+  pub symbol: Option<String>,
+  /// Only include announcements for the given CUSIP.
+  #[serde(rename = "cusip", skip_serializing_if = "Option::is_none")]
+  pub cusip: Option<String>,
+  /// The date of an announcement that `since` and `until` apply to.
+  ///
+  /// If not provided, any of the announcement's dates is checked.
+  #[serde(rename = "date_type", skip_serializing_if = "Option::is_none")]
+  pub date_type: Option<DateType>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+impl ListReq {
+  /// Split the request into a series of requests that each covers at
+  /// most the 90 days accepted by the server, together spanning the
+  /// entire `since`-`until` range.
+  pub fn windows(&self) -> impl Iterator<Item = ListReq> + '_ {
+    let mut since = Some(self.since);
+
+    std::iter::from_fn(move || {
+      let start = since.filter(|since| *since <= self.until)?;
+      let end = min(start + Duration::days(MAX_WINDOW_DAYS - 1), self.until);
+      since = end.succ_opt();
+
+      Some(ListReq {
+        since: start,
+        until: end,
+        ..self.clone()
+      })
+    })
+  }
+}
+
+
+/// A helper for initializing [`ListReq`] objects.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ListReqInit {
+  /// See `ListReq::symbol`.
+  pub symbol: Option<String>,
+  /// See `ListReq::cusip`.
+  pub cusip: Option<String>,
+  /// See `ListReq::date_type`.
+  pub date_type: Option<DateType>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+Setters! {
+  ListReqInit {
+    symbol: Option<String>,
+    cusip: Option<String>,
+    date_type: Option<DateType>,
+  }
+}
+
+impl ListReqInit {
+  /// Create a [`ListReq`] from a `ListReqInit`.
+  #[inline]
+  pub fn init<T>(&self, types: T, since: NaiveDate, until: NaiveDate) -> ListReq
+  where
+    T: Into<Vec<Type>>,
+  {
+    ListReq {
+      types: types.into(),
+      since,
+      until,
+      symbol: self.symbol.clone(),
+      cusip: self.cusip.clone(),
+      date_type: self.date_type,
+      _non_exhaustive: (),
+    }
+  }
+}
+
+
+Endpoint! {
+  /// The representation of a GET request to the
+  /// /v2/corporate_actions/announcements endpoint.
+  pub List(ListReq),
+  Ok => Vec<Announcement>, [
+    /// The corporate action announcements were retrieved successfully.
+    /* 200 */ OK,
+  ],
+  Err => ListError, [
+    /// Some of the provided data was invalid.
+    /* 422 */ UNPROCESSABLE_ENTITY => InvalidInput,
+  ]
+
+  #[inline]
+  fn path(_input: &Self::Input) -> Str {
+    "/v2/corporate_actions/announcements".into()
+  }
+
+  fn query(input: &Self::Input) -> Result<Option<Str>, Self::ConversionError> {
+    Ok(Some(to_query(input)?.into()))
+  }
+}
+
+
+Endpoint! {
+  /// The representation of a GET request to the
+  /// /v2/corporate_actions/announcements/{id} endpoint.
+  pub Get(Id),
+  Ok => Announcement, [
+    /// The corporate action announcement was retrieved successfully.
+    /* 200 */ OK,
+  ],
+  Err => GetError, [
+    /// No announcement was found with the given ID.
+    /* 404 */ NOT_FOUND => NotFound,
+  ]
+
+  fn path(input: &Self::Input) -> Str {
+    format!("/v2/corporate_actions/announcements/{}", input.as_hyphenated()).into()
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use serde_json::from_str as from_json;
+
+  use test_log::test;
+
+  use crate::api_info::ApiInfo;
+  use crate::Client;
+  use crate::RequestError;
+
+
+  /// Check that we can parse a reference announcement.
+  #[test]
+  fn parse_reference_announcement() {
+    let response = r#"{
+  "id": "be3c368a-4c7c-4384-808e-f02c9f5a8afe",
+  "corporate_action_id": "F58684224_XY37",
+  "ca_type": "dividend",
+  "ca_sub_type": "cash",
+  "initiating_symbol": "MLLAX",
+  "initiating_original_cusip": "55275E101",
+  "target_symbol": "MLLAX",
+  "target_original_cusip": "55275E101",
+  "declaration_date": "2021-01-05",
+  "ex_date": "2021-01-12",
+  "record_date": "2021-01-13",
+  "payable_date": "2021-01-14",
+  "cash": "0.018",
+  "old_rate": "1",
+  "new_rate": "1"
+}"#;
+
+    let announcement = from_json::<Announcement>(response).unwrap();
+    assert_eq!(announcement.type_, Type::Dividend);
+    assert_eq!(announcement.sub_type, "cash");
+    assert_eq!(announcement.initiating_symbol, "MLLAX");
+    assert_eq!(
+      announcement.ex_date,
+      Some(NaiveDate::from_ymd_opt(2021, 1, 12).unwrap())
+    );
+    assert_eq!(announcement.cash, Some(Num::new(18, 1000)));
+    assert_eq!(announcement.old_rate, Some(Num::from(1)));
+  }
+
+  /// Verify that the request is serialized as expected.
+  #[test]
+  fn serialize_request() {
+    let since = NaiveDate::from_ymd_opt(2021, 1, 1).unwrap();
+    let until = NaiveDate::from_ymd_opt(2021, 2, 1).unwrap();
+    let request = ListReqInit {
+      symbol: Some("AAPL".to_string()),
+      date_type: Some(DateType::Ex),
+      ..Default::default()
+    }
+    .init([Type::Dividend, Type::Split], since, until);
+
+    let expected =
+      "ca_types=Dividend%2CSplit&since=2021-01-01&until=2021-02-01&symbol=AAPL&date_type=ex_date";
+    assert_eq!(to_query(&request).unwrap(), expected);
+  }
+
+  /// Check that we can split a request into windows that are accepted
+  /// by the server.
+  #[test]
+  fn split_request_into_windows() {
+    let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+
+    let request = ListReqInit::default().init([Type::Split], date(2021, 1, 1), date(2021, 1, 1));
+    let windows = request.windows().collect::<Vec<_>>();
+    assert_eq!(windows, vec![request.clone()]);
+
+    let request = ListReqInit::default().init([Type::Split], date(2021, 1, 1), date(2021, 12, 31));
+    let windows = request.windows().collect::<Vec<_>>();
+    assert_eq!(windows.len(), 5);
+    assert_eq!(windows[0].since, date(2021, 1, 1));
+    assert_eq!(windows[0].until, date(2021, 3, 31));
+    assert_eq!(windows[1].since, date(2021, 4, 1));
+    assert_eq!(windows[4].until, date(2021, 12, 31));
+    assert!(windows
+      .windows(2)
+      .all(|pair| pair[0].until.succ_opt() == Some(pair[1].since)));
+
+    let request = ListReqInit::default().init([Type::Split], date(2021, 1, 2), date(2021, 1, 1));
+    assert_eq!(request.windows().count(), 0);
+  }
+
+  /// Check that we can list corporate action announcements and
+  /// retrieve one of them by ID.
+  #[test(tokio::test)]
+  async fn list_and_get_announcements() {
+    let api_info = ApiInfo::from_env().unwrap();
+    let client = Client::new(api_info);
+    let since = NaiveDate::from_ymd_opt(2023, 1, 1).unwrap();
+    let until = NaiveDate::from_ymd_opt(2023, 3, 1).unwrap();
+    let request = ListReqInit::default().init([Type::Dividend, Type::Split], since, until);
+    let announcements = client.issue::<List>(&request).await.unwrap();
+
+    assert!(!announcements.is_empty());
+    assert!(announcements
+      .iter()
+      .all(|announcement| matches!(announcement.type_, Type::Dividend | Type::Split)));
+
+    let announcement = client.issue::<Get>(&announcements[0].id).await.unwrap();
+    assert_eq!(announcement, announcements[0]);
+  }
+
+  /// Check that we report an appropriate error when retrieving an
+  /// announcement that does not exist.
+  #[test(tokio::test)]
+  async fn get_non_existent_announcement() {
+    let api_info = ApiInfo::from_env().unwrap();
+    let client = Client::new(api_info);
+    let id = Id(Uuid::new_v4());
+    let result = client.issue::<Get>(&id).await;
+
+    let err = result.unwrap_err();
+    match err {
+      RequestError::Endpoint(GetError::NotFound(_)) => (),
+      e => panic!("received unexpected error: {e:?}"),
+    }
+  }
+}
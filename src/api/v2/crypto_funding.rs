@@ -0,0 +1,546 @@
+// Copyright (C) 2024 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::ops::Deref;
+
+use chrono::DateTime;
+use chrono::Utc;
+
+use http::Method;
+use http_endpoint::Bytes;
+
+use num_decimal::Num;
+
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::from_slice as from_json;
+use serde_json::to_vec as to_json;
+
+use uuid::Uuid;
+
+use crate::api::v2::asset;
+use crate::Str;
+
+
+/// A wallet for depositing a crypto currency into the account.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct Wallet {
+  /// The ID of the asset the wallet holds.
+  #[serde(rename = "asset_id", default)]
+  pub asset_id: Option<asset::Id>,
+  /// The block chain the wallet lives on.
+  #[serde(rename = "chain")]
+  pub chain: String,
+  /// The address funds can be deposited to.
+  #[serde(rename = "address")]
+  pub address: String,
+  /// Timestamp this wallet was created at.
+  #[serde(rename = "created_at", default)]
+  pub created_at: Option<DateTime<Utc>>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// An ID uniquely identifying a crypto transfer.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct Id(pub Uuid);
+
+impl Deref for Id {
+  type Target = Uuid;
+
+  #[inline]
+  fn deref(&self) -> &Self::Target {
+    &self.0
+  }
+}
+
+
+/// The direction of a crypto transfer, from the perspective of the
+/// account.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub enum Direction {
+  /// Funds are deposited into the account.
+  #[serde(rename = "INCOMING")]
+  Incoming,
+  /// Funds are withdrawn from the account.
+  #[serde(rename = "OUTGOING")]
+  Outgoing,
+  /// Any other direction that we have not accounted for.
+  ///
+  /// Note that having any such direction should be considered a bug.
+  #[doc(hidden)]
+  #[serde(other, rename(serialize = "unknown"))]
+  Unknown,
+}
+
+
+/// An enumeration of the various states a crypto transfer can be in.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub enum Status {
+  /// The transfer is being processed.
+  #[serde(rename = "PROCESSING")]
+  Processing,
+  /// The transfer has failed.
+  #[serde(rename = "FAILED")]
+  Failed,
+  /// The transfer has been completed.
+  #[serde(rename = "COMPLETE")]
+  Complete,
+  /// Any other status that we have not accounted for.
+  ///
+  /// Note that having any such status should be considered a bug.
+  #[doc(hidden)]
+  #[serde(other, rename(serialize = "unknown"))]
+  Unknown,
+}
+
+
+/// A transfer of crypto currency into or out of the account.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct Transfer {
+  /// The transfer's ID.
+  #[serde(rename = "id")]
+  pub id: Id,
+  /// The hash of the on-chain transaction, once available.
+  #[serde(rename = "tx_hash", default)]
+  pub tx_hash: Option<String>,
+  /// The direction of the transfer.
+  #[serde(rename = "direction")]
+  pub direction: Direction,
+  /// The transfer's status.
+  #[serde(rename = "status")]
+  pub status: Status,
+  /// The amount of the asset being transferred.
+  #[serde(rename = "amount")]
+  pub amount: Num,
+  /// The value of the transferred amount in US dollars.
+  #[serde(rename = "usd_value", default)]
+  pub usd_value: Option<Num>,
+  /// The fee paid to the network for the transfer.
+  #[serde(rename = "network_fee", default)]
+  pub network_fee: Option<Num>,
+  /// Any additional fees charged for the transfer.
+  #[serde(rename = "fees", default)]
+  pub fees: Option<Num>,
+  /// The block chain the transfer happens on.
+  #[serde(rename = "chain")]
+  pub chain: String,
+  /// The symbol of the asset being transferred, e.g., `BTC`.
+  #[serde(rename = "asset")]
+  pub asset: String,
+  /// The address funds are transferred from.
+  #[serde(rename = "from_address", default)]
+  pub from_address: Option<String>,
+  /// The address funds are transferred to.
+  #[serde(rename = "to_address", default)]
+  pub to_address: Option<String>,
+  /// Timestamp this transfer was created at.
+  #[serde(rename = "created_at")]
+  pub created_at: DateTime<Utc>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// An ID uniquely identifying a whitelisted address.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct WhitelistId(pub Uuid);
+
+impl Deref for WhitelistId {
+  type Target = Uuid;
+
+  #[inline]
+  fn deref(&self) -> &Self::Target {
+    &self.0
+  }
+}
+
+
+/// An enumeration of the various states a whitelisted address can be
+/// in.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub enum WhitelistStatus {
+  /// The address is awaiting approval.
+  #[serde(rename = "PENDING")]
+  Pending,
+  /// The address has been approved and can be withdrawn to.
+  #[serde(rename = "APPROVED")]
+  Approved,
+  /// Any other status that we have not accounted for.
+  ///
+  /// Note that having any such status should be considered a bug.
+  #[doc(hidden)]
+  #[serde(other, rename(serialize = "unknown"))]
+  Unknown,
+}
+
+
+/// An address funds may be withdrawn to.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct WhitelistedAddress {
+  /// The whitelisted address's ID.
+  #[serde(rename = "id")]
+  pub id: WhitelistId,
+  /// The block chain the address lives on.
+  #[serde(rename = "chain")]
+  pub chain: String,
+  /// The symbol of the asset the address is whitelisted for, e.g.,
+  /// `BTC`.
+  #[serde(rename = "asset")]
+  pub asset: String,
+  /// The address itself.
+  #[serde(rename = "address")]
+  pub address: String,
+  /// The status of the address.
+  #[serde(rename = "status")]
+  pub status: WhitelistStatus,
+  /// Timestamp this address was whitelisted at.
+  #[serde(rename = "created_at")]
+  pub created_at: DateTime<Utc>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// A request to whitelist an address for withdrawals.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct WhitelistReq {
+  /// The address to whitelist.
+  #[serde(rename = "address")]
+  pub address: String,
+  /// The symbol of the asset to whitelist the address for, e.g.,
+  /// `BTC`.
+  #[serde(rename = "asset")]
+  pub asset: String,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+impl WhitelistReq {
+  /// Create a request for whitelisting the provided address for the
+  /// given asset.
+  #[inline]
+  pub fn new(asset: impl Into<String>, address: impl Into<String>) -> Self {
+    Self {
+      address: address.into(),
+      asset: asset.into(),
+      _non_exhaustive: (),
+    }
+  }
+}
+
+
+/// A request to withdraw crypto currency from the account.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct WithdrawalReq {
+  /// The amount of the asset to withdraw.
+  #[serde(rename = "amount")]
+  pub amount: Num,
+  /// The address to send funds to.
+  ///
+  /// Withdrawals are only possible to whitelisted addresses.
+  #[serde(rename = "address")]
+  pub address: String,
+  /// The symbol of the asset to withdraw, e.g., `BTC`.
+  #[serde(rename = "asset")]
+  pub asset: String,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+impl WithdrawalReq {
+  /// Create a request for withdrawing the given amount of an asset to
+  /// the provided address.
+  #[inline]
+  pub fn new(asset: impl Into<String>, amount: Num, address: impl Into<String>) -> Self {
+    Self {
+      amount,
+      address: address.into(),
+      asset: asset.into(),
+      _non_exhaustive: (),
+    }
+  }
+}
+
+
+Endpoint! {
+  /// The representation of a GET request to the /v2/wallets endpoint.
+  pub ListWallets(()),
+  Ok => Vec<Wallet>, [
+    /// The wallets were retrieved successfully.
+    /* 200 */ OK,
+  ],
+  Err => ListWalletsError, []
+
+  #[inline]
+  fn path(_input: &Self::Input) -> Str {
+    "/v2/wallets".into()
+  }
+}
+
+
+Endpoint! {
+  /// The representation of a GET request to the
+  /// /v2/wallets/transfers endpoint.
+  pub ListTransfers(()),
+  Ok => Vec<Transfer>, [
+    /// The transfers were retrieved successfully.
+    /* 200 */ OK,
+  ],
+  Err => ListTransfersError, []
+
+  #[inline]
+  fn path(_input: &Self::Input) -> Str {
+    "/v2/wallets/transfers".into()
+  }
+}
+
+
+Endpoint! {
+  /// The representation of a GET request to the
+  /// /v2/wallets/transfers/{transfer-id} endpoint.
+  pub GetTransfer(Id),
+  Ok => Transfer, [
+    /// The transfer with the given ID was retrieved successfully.
+    /* 200 */ OK,
+  ],
+  Err => GetTransferError, [
+    /// No transfer was found with the given ID.
+    /* 404 */ NOT_FOUND => NotFound,
+  ]
+
+  fn path(input: &Self::Input) -> Str {
+    format!("/v2/wallets/transfers/{}", input.as_hyphenated()).into()
+  }
+}
+
+
+Endpoint! {
+  /// The representation of a POST request to the
+  /// /v2/wallets/transfers endpoint.
+  pub Withdraw(WithdrawalReq),
+  Ok => Transfer, [
+    /// The withdrawal was initiated successfully.
+    /* 200 */ OK,
+  ],
+  Err => WithdrawError, [
+    /// Some of the provided data was invalid.
+    /* 400 */ BAD_REQUEST => InvalidInput,
+    /// The withdrawal could not be processed, e.g., because of
+    /// insufficient funds.
+    /* 422 */ UNPROCESSABLE_ENTITY => Unprocessable,
+  ]
+
+  #[inline]
+  fn path(_input: &Self::Input) -> Str {
+    "/v2/wallets/transfers".into()
+  }
+
+  #[inline]
+  fn method() -> Method {
+    Method::POST
+  }
+
+  fn body(input: &Self::Input) -> Result<Option<Bytes>, Self::ConversionError> {
+    let json = to_json(input)?;
+    let bytes = Bytes::from(json);
+    Ok(Some(bytes))
+  }
+}
+
+
+Endpoint! {
+  /// The representation of a GET request to the
+  /// /v2/wallets/whitelists endpoint.
+  pub ListWhitelisted(()),
+  Ok => Vec<WhitelistedAddress>, [
+    /// The whitelisted addresses were retrieved successfully.
+    /* 200 */ OK,
+  ],
+  Err => ListWhitelistedError, []
+
+  #[inline]
+  fn path(_input: &Self::Input) -> Str {
+    "/v2/wallets/whitelists".into()
+  }
+}
+
+
+Endpoint! {
+  /// The representation of a POST request to the
+  /// /v2/wallets/whitelists endpoint.
+  pub Whitelist(WhitelistReq),
+  Ok => WhitelistedAddress, [
+    /// The address was whitelisted successfully.
+    /* 200 */ OK,
+  ],
+  Err => WhitelistError, [
+    /// Some of the provided data was invalid.
+    /* 400 */ BAD_REQUEST => InvalidInput,
+  ]
+
+  #[inline]
+  fn path(_input: &Self::Input) -> Str {
+    "/v2/wallets/whitelists".into()
+  }
+
+  #[inline]
+  fn method() -> Method {
+    Method::POST
+  }
+
+  fn body(input: &Self::Input) -> Result<Option<Bytes>, Self::ConversionError> {
+    let json = to_json(input)?;
+    let bytes = Bytes::from(json);
+    Ok(Some(bytes))
+  }
+}
+
+
+EndpointNoParse! {
+  /// The representation of a DELETE request to the
+  /// /v2/wallets/whitelists/{whitelisted-address-id} endpoint.
+  pub DeleteWhitelisted(WhitelistId),
+  Ok => (), [
+    /// The address was removed from the whitelist successfully.
+    /* 200 */ OK,
+  ],
+  Err => DeleteWhitelistedError, [
+    /// No whitelisted address was found with the given ID.
+    /* 404 */ NOT_FOUND => NotFound,
+  ]
+
+  #[inline]
+  fn method() -> Method {
+    Method::DELETE
+  }
+
+  fn path(input: &Self::Input) -> Str {
+    format!("/v2/wallets/whitelists/{}", input.as_hyphenated()).into()
+  }
+
+  #[inline]
+  fn parse(_body: &[u8]) -> Result<Self::Output, Self::ConversionError> {
+    Ok(())
+  }
+
+  fn parse_err(body: &[u8]) -> Result<Self::ApiError, Vec<u8>> {
+    from_json::<Self::ApiError>(body).map_err(|_| body.to_vec())
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use serde_json::from_str as from_json;
+  use serde_json::to_string as to_json;
+
+  use test_log::test;
+
+
+  /// Check that we can parse a reference wallet.
+  #[test]
+  fn parse_reference_wallet() {
+    let response = r#"[{
+  "asset_id": "276e2673-764b-4ab6-a611-caf665ca6340",
+  "chain": "BTC",
+  "address": "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4",
+  "created_at": "2022-05-10T15:42:07.240193Z"
+}]"#;
+
+    let wallets = from_json::<Vec<Wallet>>(response).unwrap();
+    assert_eq!(wallets.len(), 1);
+    assert_eq!(wallets[0].chain, "BTC");
+    assert_eq!(
+      wallets[0].address,
+      "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"
+    );
+  }
+
+  /// Check that we can parse a reference transfer.
+  #[test]
+  fn parse_reference_transfer() {
+    let response = r#"{
+  "id": "d1d5e4a0-c3d0-4d3b-8a4c-0a7c2b3af1b2",
+  "tx_hash": null,
+  "direction": "OUTGOING",
+  "status": "PROCESSING",
+  "amount": "0.01",
+  "usd_value": "302.17",
+  "network_fee": "0.0001",
+  "fees": "0",
+  "chain": "BTC",
+  "asset": "BTC",
+  "from_address": null,
+  "to_address": "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4",
+  "created_at": "2022-05-10T15:42:07.240193Z"
+}"#;
+
+    let transfer = from_json::<Transfer>(response).unwrap();
+    assert_eq!(transfer.direction, Direction::Outgoing);
+    assert_eq!(transfer.status, Status::Processing);
+    assert_eq!(transfer.amount, Num::new(1, 100));
+    assert_eq!(transfer.network_fee, Some(Num::new(1, 10000)));
+    assert_eq!(transfer.tx_hash, None);
+    assert_eq!(transfer.asset, "BTC");
+  }
+
+  /// Verify that we serialize a withdrawal request as expected.
+  #[test]
+  fn serialize_withdrawal_request() {
+    let request = WithdrawalReq::new(
+      "BTC",
+      Num::new(1, 100),
+      "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4",
+    );
+
+    let json = to_json(&request).unwrap();
+    let expected =
+      r#"{"amount":"0.01","address":"bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4","asset":"BTC"}"#;
+    assert_eq!(json, expected);
+  }
+
+  /// Check that we can parse a reference whitelisted address.
+  #[test]
+  fn parse_reference_whitelisted_address() {
+    let response = r#"[{
+  "id": "7e4c0aa4-41a2-4a7e-9d48-0f5b3c1c25b6",
+  "chain": "ETH",
+  "asset": "ETH",
+  "address": "0x742d35Cc6634C0532925a3b844Bc454e4438f44e",
+  "status": "APPROVED",
+  "created_at": "2022-05-10T15:42:07.240193Z"
+}]"#;
+
+    let addresses = from_json::<Vec<WhitelistedAddress>>(response).unwrap();
+    assert_eq!(addresses.len(), 1);
+    assert_eq!(addresses[0].asset, "ETH");
+    assert_eq!(addresses[0].status, WhitelistStatus::Approved);
+  }
+
+  /// Verify that we serialize a whitelist request as expected.
+  #[test]
+  fn serialize_whitelist_request() {
+    let request = WhitelistReq::new("ETH", "0x742d35Cc6634C0532925a3b844Bc454e4438f44e");
+
+    let json = to_json(&request).unwrap();
+    let expected = r#"{"address":"0x742d35Cc6634C0532925a3b844Bc454e4438f44e","asset":"ETH"}"#;
+    assert_eq!(json, expected);
+  }
+}
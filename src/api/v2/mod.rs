@@ -17,15 +17,27 @@ pub mod calendar;
 /// Functionality for retrieving market open/close timing information
 /// for the current trading day.
 pub mod clock;
+/// Functionality for retrieving corporate action announcements.
+pub mod corporate_actions;
+/// Functionality for depositing and withdrawing crypto currencies.
+pub mod crypto_funding;
 /// Definitions surrounding orders.
 pub mod order;
 /// Functionality for listing orders.
 pub mod orders;
+/// Functionality for retrieving the account's portfolio history.
+pub mod portfolio_history;
 /// Definitions surrounding open positions.
 pub mod position;
 /// Functionality for listing open positions.
 pub mod positions;
-/// Definitions for trade related updates.
+/// Functionality for planning and executing the rebalancing of a
+/// portfolio towards target weights.
+pub mod rebalance;
+/// Functionality for checking orders for likely rejection reasons
+/// before submitting them.
+pub mod risk;
+/// Definitions for trade and account related updates.
 pub mod updates;
 /// Definitions surrounding watchlists.
 pub mod watchlist;
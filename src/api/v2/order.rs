@@ -1,8 +1,12 @@
 // Copyright (C) 2019-2024 The apca Developers
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
 use std::ops::Deref;
 use std::ops::Not;
+use std::str::FromStr;
 
 use chrono::DateTime;
 use chrono::Utc;
@@ -20,10 +24,15 @@ use serde_json::from_slice as from_json;
 use serde_json::to_vec as to_json;
 use serde_urlencoded::to_string as to_query;
 
+use thiserror::Error;
+
 use uuid::Uuid;
 
 use crate::api::v2::asset;
+use crate::api::v2::asset::Asset;
 use crate::util::vec_from_str;
+use crate::Client;
+use crate::RequestError;
 use crate::Str;
 
 
@@ -125,6 +134,87 @@ pub enum Status {
   Unknown,
 }
 
+impl AsRef<str> for Status {
+  #[inline]
+  fn as_ref(&self) -> &'static str {
+    match *self {
+      Status::New => "new",
+      Status::Replaced => "replaced",
+      Status::PartiallyFilled => "partially_filled",
+      Status::Filled => "filled",
+      Status::DoneForDay => "done_for_day",
+      Status::Canceled => "canceled",
+      Status::Expired => "expired",
+      Status::Accepted => "accepted",
+      Status::PendingNew => "pending_new",
+      Status::AcceptedForBidding => "accepted_for_bidding",
+      Status::PendingCancel => "pending_cancel",
+      Status::PendingReplace => "pending_replace",
+      Status::Stopped => "stopped",
+      Status::Rejected => "rejected",
+      Status::Suspended => "suspended",
+      Status::Calculated => "calculated",
+      Status::Held => "held",
+      Status::Unknown => "unknown",
+    }
+  }
+}
+
+impl Display for Status {
+  #[inline]
+  fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+    fmt.write_str(self.as_ref())
+  }
+}
+
+impl FromStr for Status {
+  type Err = ();
+
+  #[inline]
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    if s == Status::New.as_ref() {
+      Ok(Status::New)
+    } else if s == Status::Replaced.as_ref() {
+      Ok(Status::Replaced)
+    } else if s == Status::PartiallyFilled.as_ref() {
+      Ok(Status::PartiallyFilled)
+    } else if s == Status::Filled.as_ref() {
+      Ok(Status::Filled)
+    } else if s == Status::DoneForDay.as_ref() {
+      Ok(Status::DoneForDay)
+    } else if s == Status::Canceled.as_ref() {
+      Ok(Status::Canceled)
+    } else if s == Status::Expired.as_ref() {
+      Ok(Status::Expired)
+    } else if s == Status::Accepted.as_ref() {
+      Ok(Status::Accepted)
+    } else if s == Status::PendingNew.as_ref() {
+      Ok(Status::PendingNew)
+    } else if s == Status::AcceptedForBidding.as_ref() {
+      Ok(Status::AcceptedForBidding)
+    } else if s == Status::PendingCancel.as_ref() {
+      Ok(Status::PendingCancel)
+    } else if s == Status::PendingReplace.as_ref() {
+      Ok(Status::PendingReplace)
+    } else if s == Status::Stopped.as_ref() {
+      Ok(Status::Stopped)
+    } else if s == Status::Rejected.as_ref() {
+      Ok(Status::Rejected)
+    } else if s == Status::Suspended.as_ref() {
+      Ok(Status::Suspended)
+    } else if s == Status::Calculated.as_ref() {
+      Ok(Status::Calculated)
+    } else if s == Status::Held.as_ref() {
+      Ok(Status::Held)
+    } else {
+      // Note that we do not support creating the `Unknown` variant
+      // here. This variant is really only meant to cover
+      // deserialization.
+      Err(())
+    }
+  }
+}
+
 impl Status {
   /// Check whether the status is terminal, i.e., no more changes will
   /// occur to the associated order.
@@ -149,6 +239,38 @@ pub enum Side {
   Sell,
 }
 
+impl AsRef<str> for Side {
+  #[inline]
+  fn as_ref(&self) -> &'static str {
+    match *self {
+      Side::Buy => "buy",
+      Side::Sell => "sell",
+    }
+  }
+}
+
+impl Display for Side {
+  #[inline]
+  fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+    fmt.write_str(self.as_ref())
+  }
+}
+
+impl FromStr for Side {
+  type Err = ();
+
+  #[inline]
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    if s == Side::Buy.as_ref() {
+      Ok(Side::Buy)
+    } else if s == Side::Sell.as_ref() {
+      Ok(Side::Sell)
+    } else {
+      Err(())
+    }
+  }
+}
+
 impl Not for Side {
   type Output = Self;
 
@@ -186,6 +308,54 @@ pub enum Class {
   /// entry order.
   #[serde(rename = "oto")]
   OneTriggersOther,
+  /// Any other order class that we have not accounted for.
+  ///
+  /// Note that having any such order class should be considered a bug.
+  #[doc(hidden)]
+  #[serde(other, rename(serialize = "unknown"))]
+  Unknown,
+}
+
+impl AsRef<str> for Class {
+  #[inline]
+  fn as_ref(&self) -> &'static str {
+    match *self {
+      Class::Simple => "simple",
+      Class::Bracket => "bracket",
+      Class::OneCancelsOther => "oco",
+      Class::OneTriggersOther => "oto",
+      Class::Unknown => "unknown",
+    }
+  }
+}
+
+impl Display for Class {
+  #[inline]
+  fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+    fmt.write_str(self.as_ref())
+  }
+}
+
+impl FromStr for Class {
+  type Err = ();
+
+  #[inline]
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    if s == Class::Simple.as_ref() {
+      Ok(Class::Simple)
+    } else if s == Class::Bracket.as_ref() {
+      Ok(Class::Bracket)
+    } else if s == Class::OneCancelsOther.as_ref() {
+      Ok(Class::OneCancelsOther)
+    } else if s == Class::OneTriggersOther.as_ref() {
+      Ok(Class::OneTriggersOther)
+    } else {
+      // Note that we do not support creating the `Unknown` variant
+      // here. This variant is really only meant to cover
+      // deserialization.
+      Err(())
+    }
+  }
 }
 
 impl Default for Class {
@@ -197,7 +367,6 @@ impl Default for Class {
 
 
 /// The type of an order.
-// Note that we currently do not support `stop_limit` orders.
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
 #[non_exhaustive]
 pub enum Type {
@@ -216,6 +385,57 @@ pub enum Type {
   /// A trailing stop order.
   #[serde(rename = "trailing_stop")]
   TrailingStop,
+  /// Any other order type that we have not accounted for.
+  ///
+  /// Note that having any such order type should be considered a bug.
+  #[doc(hidden)]
+  #[serde(other, rename(serialize = "unknown"))]
+  Unknown,
+}
+
+impl AsRef<str> for Type {
+  #[inline]
+  fn as_ref(&self) -> &'static str {
+    match *self {
+      Type::Market => "market",
+      Type::Limit => "limit",
+      Type::Stop => "stop",
+      Type::StopLimit => "stop_limit",
+      Type::TrailingStop => "trailing_stop",
+      Type::Unknown => "unknown",
+    }
+  }
+}
+
+impl Display for Type {
+  #[inline]
+  fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+    fmt.write_str(self.as_ref())
+  }
+}
+
+impl FromStr for Type {
+  type Err = ();
+
+  #[inline]
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    if s == Type::Market.as_ref() {
+      Ok(Type::Market)
+    } else if s == Type::Limit.as_ref() {
+      Ok(Type::Limit)
+    } else if s == Type::Stop.as_ref() {
+      Ok(Type::Stop)
+    } else if s == Type::StopLimit.as_ref() {
+      Ok(Type::StopLimit)
+    } else if s == Type::TrailingStop.as_ref() {
+      Ok(Type::TrailingStop)
+    } else {
+      // Note that we do not support creating the `Unknown` variant
+      // here. This variant is really only meant to cover
+      // deserialization.
+      Err(())
+    }
+  }
 }
 
 impl Default for Type {
@@ -253,6 +473,60 @@ pub enum TimeInForce {
   /// auction. Any unfilled orders after the close will be canceled.
   #[serde(rename = "cls")]
   UntilMarketClose,
+  /// Any other time in force that we have not accounted for.
+  ///
+  /// Note that having any such time in force should be considered a bug.
+  #[doc(hidden)]
+  #[serde(other, rename(serialize = "unknown"))]
+  Unknown,
+}
+
+impl AsRef<str> for TimeInForce {
+  #[inline]
+  fn as_ref(&self) -> &'static str {
+    match *self {
+      TimeInForce::Day => "day",
+      TimeInForce::FillOrKill => "fok",
+      TimeInForce::ImmediateOrCancel => "ioc",
+      TimeInForce::UntilCanceled => "gtc",
+      TimeInForce::UntilMarketOpen => "opg",
+      TimeInForce::UntilMarketClose => "cls",
+      TimeInForce::Unknown => "unknown",
+    }
+  }
+}
+
+impl Display for TimeInForce {
+  #[inline]
+  fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+    fmt.write_str(self.as_ref())
+  }
+}
+
+impl FromStr for TimeInForce {
+  type Err = ();
+
+  #[inline]
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    if s == TimeInForce::Day.as_ref() {
+      Ok(TimeInForce::Day)
+    } else if s == TimeInForce::FillOrKill.as_ref() {
+      Ok(TimeInForce::FillOrKill)
+    } else if s == TimeInForce::ImmediateOrCancel.as_ref() {
+      Ok(TimeInForce::ImmediateOrCancel)
+    } else if s == TimeInForce::UntilCanceled.as_ref() {
+      Ok(TimeInForce::UntilCanceled)
+    } else if s == TimeInForce::UntilMarketOpen.as_ref() {
+      Ok(TimeInForce::UntilMarketOpen)
+    } else if s == TimeInForce::UntilMarketClose.as_ref() {
+      Ok(TimeInForce::UntilMarketClose)
+    } else {
+      // Note that we do not support creating the `Unknown` variant
+      // here. This variant is really only meant to cover
+      // deserialization.
+      Err(())
+    }
+  }
 }
 
 impl Default for TimeInForce {
@@ -383,6 +657,193 @@ impl Amount {
 }
 
 
+/// The entry part of a bracket or one-triggers-other order.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Entry {
+  /// Enter the position with a market order.
+  Market,
+  /// Enter the position with a limit order using the given limit
+  /// price.
+  Limit(Num),
+}
+
+impl Entry {
+  /// Retrieve the order type and limit price to use for the entry.
+  fn into_parts(self) -> (Type, Option<Num>) {
+    match self {
+      Self::Market => (Type::Market, None),
+      Self::Limit(limit_price) => (Type::Limit, Some(limit_price)),
+    }
+  }
+}
+
+
+/// The single exit leg of a one-triggers-other order.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Exit {
+  /// Exit the position with a take profit order.
+  TakeProfit(TakeProfit),
+  /// Exit the position with a stop loss order.
+  StopLoss(StopLoss),
+}
+
+
+/// The maximum length of a client order ID, in characters.
+const MAX_CLIENT_ORDER_ID_LEN: usize = 48;
+
+
+/// The increments in which the prices of an instrument are expressed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum TickSize {
+  /// The increments used for US equities: prices of at least one
+  /// dollar have to be whole cents, whereas lower prices may have up
+  /// to four decimal places.
+  Equity,
+  /// A fixed increment, as used for crypto currencies.
+  Fixed(Num),
+}
+
+impl TickSize {
+  /// Determine the tick size of the provided asset.
+  ///
+  /// The asset's price increment is used, if reported, and the rules
+  /// for US equities otherwise.
+  pub fn of_asset(asset: &Asset) -> Self {
+    match &asset.price_increment {
+      Some(increment) => Self::Fixed(increment.clone()),
+      None => Self::Equity,
+    }
+  }
+
+  /// Retrieve the tick applicable to the given price.
+  pub fn tick(&self, price: &Num) -> Num {
+    match self {
+      Self::Equity => {
+        if price < &Num::from(1) {
+          Num::new(1, 10000)
+        } else {
+          Num::new(1, 100)
+        }
+      },
+      Self::Fixed(increment) => increment.clone(),
+    }
+  }
+}
+
+
+/// The direction in which to round a price to a multiple of a tick.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Rounding {
+  /// Round to the nearest multiple.
+  Nearest,
+  /// Round to the next lower multiple, unless already a multiple.
+  Down,
+  /// Round to the next higher multiple, unless already a multiple.
+  Up,
+}
+
+impl Rounding {
+  /// Determine the rounding to use for a limit price of an order on
+  /// the given side, such that rounding never results in a worse price:
+  /// buy limits are rounded down and sell limits up.
+  #[inline]
+  pub fn limit(side: Side) -> Self {
+    match side {
+      Side::Buy => Self::Down,
+      Side::Sell => Self::Up,
+    }
+  }
+}
+
+
+/// Round a price to a multiple of the tick applicable to it, in the
+/// given direction, as Alpaca rejects orders with prices in invalid
+/// increments.
+pub fn round_to_tick(price: &Num, tick_size: &TickSize, rounding: Rounding) -> Num {
+  let tick = tick_size.tick(price);
+  if tick.is_zero() {
+    return price.clone()
+  }
+
+  let ticks = price / &tick;
+  let ticks = match rounding {
+    Rounding::Nearest => ticks.round(),
+    Rounding::Down if ticks.is_negative() && !ticks.fract().is_zero() => ticks.trunc() - 1,
+    Rounding::Up if ticks.is_positive() && !ticks.fract().is_zero() => ticks.trunc() + 1,
+    Rounding::Down | Rounding::Up => ticks.trunc(),
+  };
+  ticks * tick
+}
+
+
+/// An error reported when a `CreateReqInit` object describes an order
+/// that violates the documented constraints for its type or class.
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+#[non_exhaustive]
+pub enum ValidationError {
+  /// The order type requires a limit price, but none was provided.
+  #[error("{0:?} orders require a limit price")]
+  MissingLimitPrice(Type),
+  /// The order type does not accept a limit price, but one was
+  /// provided.
+  #[error("{0:?} orders do not accept a limit price")]
+  UnexpectedLimitPrice(Type),
+  /// The order type requires a stop price, but none was provided.
+  #[error("{0:?} orders require a stop price")]
+  MissingStopPrice(Type),
+  /// The order type does not accept a stop price, but one was
+  /// provided.
+  #[error("{0:?} orders do not accept a stop price")]
+  UnexpectedStopPrice(Type),
+  /// A trailing stop order did not have exactly one of trail price and
+  /// trail percent set.
+  #[error("trailing stop orders require exactly one of a trail price or a trail percent")]
+  InvalidTrail,
+  /// The order type does not accept a trail price or trail percent, but
+  /// one was provided.
+  #[error("{0:?} orders do not accept a trail price or a trail percent")]
+  UnexpectedTrail(Type),
+  /// The order class does not support the provided order type.
+  #[error("{0:?} orders do not support order type {1:?}")]
+  UnsupportedType(Class, Type),
+  /// The order class requires a take profit and/or stop loss leg that
+  /// was not provided.
+  #[error("{0:?} orders are missing a take profit or stop loss leg")]
+  MissingLeg(Class),
+  /// The order class does not accept a provided take profit or stop
+  /// loss leg.
+  #[error("{0:?} orders do not accept the provided take profit or stop loss leg")]
+  UnexpectedLeg(Class),
+  /// Extended hours trading was requested for an order that is not a
+  /// limit order good for the day.
+  #[error("extended hours orders must be limit orders that are good for the day")]
+  InvalidExtendedHours,
+  /// The client order ID exceeds the maximum length of 48 characters.
+  #[error("client order ID of {0} characters exceeds the maximum of 48")]
+  ClientOrderIdTooLong(usize),
+  /// Orders for assets of the given class do not support the provided
+  /// order type.
+  #[error("{0:?} orders do not support order type {1:?}")]
+  UnsupportedAssetType(asset::Class, Type),
+  /// Orders for assets of the given class do not support the provided
+  /// time in force.
+  #[error("{0:?} orders do not support time in force {1:?}")]
+  UnsupportedTimeInForce(asset::Class, TimeInForce),
+  /// Orders for assets of the given class do not support the provided
+  /// order class.
+  #[error("{0:?} orders do not support order class {1:?}")]
+  UnsupportedClass(asset::Class, Class),
+  /// Extended hours trading was requested for an order for assets of
+  /// a class not supporting it.
+  #[error("{0:?} orders do not support extended hours trading")]
+  UnsupportedExtendedHours(asset::Class),
+}
+
+
 /// A helper for initializing `CreateReq` objects.
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct CreateReqInit {
@@ -408,11 +869,37 @@ pub struct CreateReqInit {
   pub extended_hours: bool,
   /// See `CreateReq::client_order_id`.
   pub client_order_id: Option<String>,
+  /// The tick size to round all prices of the order to, if any.
+  ///
+  /// Rounding happens as part of [`init`][CreateReqInit::init], by
+  /// means of [`round_to_tick`]. Limit prices are rounded as per
+  /// [`Rounding::limit`] for the side of the order (or its opposite,
+  /// for the exit legs of order classes), such that they never get
+  /// worse, while stop prices are rounded to the nearest tick. The
+  /// trail price of trailing stop orders is left untouched.
+  pub tick_size: Option<TickSize>,
   /// The type is non-exhaustive and open to extension.
   #[doc(hidden)]
   pub _non_exhaustive: (),
 }
 
+Setters! {
+  CreateReqInit {
+    class: Class,
+    type_: Type,
+    time_in_force: TimeInForce,
+    limit_price: Option<Num>,
+    stop_price: Option<Num>,
+    trail_price: Option<Num>,
+    trail_percent: Option<Num>,
+    take_profit: Option<TakeProfit>,
+    stop_loss: Option<StopLoss>,
+    extended_hours: bool,
+    client_order_id: Option<String>,
+    tick_size: Option<TickSize>,
+  }
+}
+
 impl CreateReqInit {
   /// Create a `CreateReq` from a `CreateReqInit`.
   ///
@@ -420,7 +907,7 @@ impl CreateReqInit {
   /// of the composite forms of the [`Symbol`][asset::Symbol] enum. That
   /// is, it is not being parsed but directly treated as the
   /// [`Sym`][asset::Symbol::Sym] variant.
-  pub fn init<S>(self, symbol: S, side: Side, amount: Amount) -> CreateReq
+  pub fn init<S>(&self, symbol: S, side: Side, amount: Amount) -> CreateReq
   where
     S: Into<String>,
   {
@@ -431,17 +918,221 @@ impl CreateReqInit {
       class: self.class,
       type_: self.type_,
       time_in_force: self.time_in_force,
-      limit_price: self.limit_price,
-      stop_price: self.stop_price,
-      take_profit: self.take_profit,
-      stop_loss: self.stop_loss,
+      limit_price: self
+        .limit_price
+        .as_ref()
+        .map(|price| self.round(price, Rounding::limit(side))),
+      stop_price: self
+        .stop_price
+        .as_ref()
+        .map(|price| self.round(price, Rounding::Nearest)),
+      take_profit: self
+        .take_profit
+        .as_ref()
+        .map(|take_profit| match take_profit {
+          TakeProfit::Limit(limit) => TakeProfit::Limit(self.round(limit, Rounding::limit(!side))),
+        }),
+      stop_loss: self.stop_loss.as_ref().map(|stop_loss| match stop_loss {
+        StopLoss::Stop(stop) => StopLoss::Stop(self.round(stop, Rounding::Nearest)),
+        StopLoss::StopLimit(stop, limit) => StopLoss::StopLimit(
+          self.round(stop, Rounding::Nearest),
+          self.round(limit, Rounding::limit(!side)),
+        ),
+      }),
       extended_hours: self.extended_hours,
-      client_order_id: self.client_order_id,
-      trail_price: self.trail_price,
-      trail_percent: self.trail_percent,
+      client_order_id: self.client_order_id.clone(),
+      // The trail price is an offset from the market price and not a
+      // price in and of itself, so we do not round it.
+      trail_price: self.trail_price.clone(),
+      trail_percent: self.trail_percent.clone(),
       _non_exhaustive: (),
     }
   }
+
+  /// Round the provided price to the configured tick size, if any.
+  fn round(&self, price: &Num, rounding: Rounding) -> Num {
+    match &self.tick_size {
+      Some(tick_size) => round_to_tick(price, tick_size, rounding),
+      None => price.clone(),
+    }
+  }
+
+  /// Create a `CreateReq` from a `CreateReqInit`, after checking it
+  /// against the constraints Alpaca documents for the respective order
+  /// type and class.
+  ///
+  /// Contrary to [`init`][Self::init], which leaves it to the server to
+  /// reject semantically invalid orders, this method reports such
+  /// problems locally and before submission. Symbol handling is the
+  /// same as for [`init`][Self::init].
+  pub fn build<S>(self, symbol: S, side: Side, amount: Amount) -> Result<CreateReq, ValidationError>
+  where
+    S: Into<String>,
+  {
+    let () = self.validate()?;
+    Ok(self.init(symbol, side, amount))
+  }
+
+  /// Create a `CreateReq` from a `CreateReqInit` just like
+  /// [`build`][Self::build] does, but additionally check it against
+  /// the constraints Alpaca documents for orders of assets of the
+  /// given class.
+  ///
+  /// Crypto currency orders, for instance, only support market, limit,
+  /// and stop limit orders of the simple order class that are good
+  /// until canceled or immediate or cancel, and they can not be
+  /// flagged for extended hours trading.
+  pub fn build_for<S>(
+    self,
+    asset_class: asset::Class,
+    symbol: S,
+    side: Side,
+    amount: Amount,
+  ) -> Result<CreateReq, ValidationError>
+  where
+    S: Into<String>,
+  {
+    let () = self.validate()?;
+    let () = self.validate_for(asset_class)?;
+    Ok(self.init(symbol, side, amount))
+  }
+
+  /// Check the object against the documented constraints for orders of
+  /// assets of the given class.
+  fn validate_for(&self, asset_class: asset::Class) -> Result<(), ValidationError> {
+    match asset_class {
+      asset::Class::Crypto => {
+        match self.type_ {
+          Type::Market | Type::Limit | Type::StopLimit | Type::Unknown => (),
+          Type::Stop | Type::TrailingStop => {
+            return Err(ValidationError::UnsupportedAssetType(
+              asset_class,
+              self.type_,
+            ))
+          },
+        }
+
+        match self.time_in_force {
+          TimeInForce::UntilCanceled | TimeInForce::ImmediateOrCancel | TimeInForce::Unknown => (),
+          TimeInForce::Day
+          | TimeInForce::FillOrKill
+          | TimeInForce::UntilMarketOpen
+          | TimeInForce::UntilMarketClose => {
+            return Err(ValidationError::UnsupportedTimeInForce(
+              asset_class,
+              self.time_in_force,
+            ))
+          },
+        }
+
+        if self.class != Class::Simple {
+          return Err(ValidationError::UnsupportedClass(asset_class, self.class))
+        }
+
+        if self.extended_hours {
+          return Err(ValidationError::UnsupportedExtendedHours(asset_class))
+        }
+      },
+      asset::Class::UsEquity | asset::Class::Unknown => (),
+    }
+    Ok(())
+  }
+
+  /// Check the object against the documented order constraints.
+  fn validate(&self) -> Result<(), ValidationError> {
+    let type_ = self.type_;
+    let trail = self.trail_price.is_some() || self.trail_percent.is_some();
+
+    // Note that we do not know the constraints of unknown order types
+    // and classes and leave their checking to the server.
+    match type_ {
+      Type::Limit | Type::StopLimit => {
+        // The limit price of one-cancels-other orders is conveyed
+        // through their take profit leg instead.
+        if self.limit_price.is_none() && self.class != Class::OneCancelsOther {
+          return Err(ValidationError::MissingLimitPrice(type_))
+        }
+      },
+      Type::Market | Type::Stop | Type::TrailingStop => {
+        if self.limit_price.is_some() {
+          return Err(ValidationError::UnexpectedLimitPrice(type_))
+        }
+      },
+      Type::Unknown => (),
+    }
+
+    match type_ {
+      Type::Stop | Type::StopLimit => {
+        if self.stop_price.is_none() {
+          return Err(ValidationError::MissingStopPrice(type_))
+        }
+      },
+      Type::Market | Type::Limit | Type::TrailingStop => {
+        if self.stop_price.is_some() {
+          return Err(ValidationError::UnexpectedStopPrice(type_))
+        }
+      },
+      Type::Unknown => (),
+    }
+
+    match type_ {
+      Type::TrailingStop => {
+        if self.trail_price.is_some() == self.trail_percent.is_some() {
+          return Err(ValidationError::InvalidTrail)
+        }
+      },
+      Type::Market | Type::Limit | Type::Stop | Type::StopLimit => {
+        if trail {
+          return Err(ValidationError::UnexpectedTrail(type_))
+        }
+      },
+      Type::Unknown => (),
+    }
+
+    let take_profit = self.take_profit.is_some();
+    let stop_loss = self.stop_loss.is_some();
+    match self.class {
+      Class::Simple => {
+        if take_profit || stop_loss {
+          return Err(ValidationError::UnexpectedLeg(self.class))
+        }
+      },
+      Class::Bracket => {
+        if !take_profit || !stop_loss {
+          return Err(ValidationError::MissingLeg(self.class))
+        }
+      },
+      Class::OneCancelsOther => {
+        if type_ != Type::Limit {
+          return Err(ValidationError::UnsupportedType(self.class, type_))
+        }
+        if !take_profit || !stop_loss {
+          return Err(ValidationError::MissingLeg(self.class))
+        }
+      },
+      Class::OneTriggersOther => {
+        if !take_profit && !stop_loss {
+          return Err(ValidationError::MissingLeg(self.class))
+        }
+        if take_profit && stop_loss {
+          return Err(ValidationError::UnexpectedLeg(self.class))
+        }
+      },
+      Class::Unknown => (),
+    }
+
+    if self.extended_hours && (type_ != Type::Limit || self.time_in_force != TimeInForce::Day) {
+      return Err(ValidationError::InvalidExtendedHours)
+    }
+
+    if let Some(client_order_id) = &self.client_order_id {
+      let len = client_order_id.chars().count();
+      if len > MAX_CLIENT_ORDER_ID_LEN {
+        return Err(ValidationError::ClientOrderIdTooLong(len))
+      }
+    }
+    Ok(())
+  }
 }
 
 
@@ -506,6 +1197,174 @@ pub struct CreateReq {
 }
 
 
+impl CreateReq {
+  /// Create a request for a bracket order, entering a position and
+  /// attaching both a take profit and a stop loss leg to it.
+  ///
+  /// Symbol handling is the same as for [`CreateReqInit::init`].
+  pub fn bracket<S>(
+    symbol: S,
+    side: Side,
+    amount: Amount,
+    entry: Entry,
+    take_profit: TakeProfit,
+    stop_loss: StopLoss,
+  ) -> Self
+  where
+    S: Into<String>,
+  {
+    let (type_, limit_price) = entry.into_parts();
+    CreateReqInit {
+      class: Class::Bracket,
+      type_,
+      limit_price,
+      take_profit: Some(take_profit),
+      stop_loss: Some(stop_loss),
+      ..Default::default()
+    }
+    .init(symbol, side, amount)
+  }
+
+  /// Create a request for a one-cancels-other order, adding a take
+  /// profit and a stop loss leg to an already existing position.
+  ///
+  /// Symbol handling is the same as for [`CreateReqInit::init`].
+  pub fn oco<S>(
+    symbol: S,
+    side: Side,
+    amount: Amount,
+    take_profit: TakeProfit,
+    stop_loss: StopLoss,
+  ) -> Self
+  where
+    S: Into<String>,
+  {
+    CreateReqInit {
+      class: Class::OneCancelsOther,
+      type_: Type::Limit,
+      take_profit: Some(take_profit),
+      stop_loss: Some(stop_loss),
+      ..Default::default()
+    }
+    .init(symbol, side, amount)
+  }
+
+  /// Create a request for a one-triggers-other order, entering a
+  /// position and attaching a single exit leg to it.
+  ///
+  /// Symbol handling is the same as for [`CreateReqInit::init`].
+  pub fn oto<S>(symbol: S, side: Side, amount: Amount, entry: Entry, exit: Exit) -> Self
+  where
+    S: Into<String>,
+  {
+    let (type_, limit_price) = entry.into_parts();
+    let (take_profit, stop_loss) = match exit {
+      Exit::TakeProfit(take_profit) => (Some(take_profit), None),
+      Exit::StopLoss(stop_loss) => (None, Some(stop_loss)),
+    };
+
+    CreateReqInit {
+      class: Class::OneTriggersOther,
+      type_,
+      limit_price,
+      take_profit,
+      stop_loss,
+      ..Default::default()
+    }
+    .init(symbol, side, amount)
+  }
+}
+
+
+/// An error reported when an order is not permissible for the asset it
+/// refers to.
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+#[non_exhaustive]
+pub enum AssetError {
+  /// The asset is inactive or not tradable on Alpaca.
+  #[error("asset {0} is not tradable")]
+  NotTradable(String),
+  /// The order is for a fractional quantity or a notional amount, but
+  /// the asset is not fractionable.
+  #[error("asset {0} is not fractionable")]
+  NotFractionable(String),
+  /// The order opens or extends a short position, but the asset can
+  /// not be sold short.
+  #[error("asset {0} is not shortable")]
+  NotShortable(String),
+}
+
+
+impl CreateReq {
+  /// Check whether the order is permissible for the provided asset,
+  /// as per its metadata.
+  ///
+  /// Specifically, the asset has to be active and tradable and, for
+  /// orders of fractional quantities or notional amounts, fractionable.
+  /// Whether an order is a short sale cannot be told from the request
+  /// alone; use [`check_short`][CreateReq::check_short] for such
+  /// orders.
+  pub fn check_asset(&self, asset: &Asset) -> Result<(), AssetError> {
+    if asset.status != asset::Status::Active || !asset.tradable {
+      return Err(AssetError::NotTradable(asset.symbol.clone()))
+    }
+
+    let fractional = match &self.amount {
+      Amount::Quantity { quantity } => !quantity.fract().is_zero(),
+      Amount::Notional { .. } => true,
+    };
+    if fractional && !asset.fractionable {
+      return Err(AssetError::NotFractionable(asset.symbol.clone()))
+    }
+    Ok(())
+  }
+
+  /// Check whether the order, which is known to open or extend a short
+  /// position, is permissible for the provided asset.
+  ///
+  /// In addition to the checks performed by
+  /// [`check_asset`][CreateReq::check_asset], the asset has to be
+  /// shortable and easy to borrow.
+  pub fn check_short(&self, asset: &Asset) -> Result<(), AssetError> {
+    let () = self.check_asset(asset)?;
+    if !asset.shortable || !asset.easy_to_borrow {
+      return Err(AssetError::NotShortable(asset.symbol.clone()))
+    }
+    Ok(())
+  }
+}
+
+
+/// An error as reported by [`check_asset`].
+#[derive(Debug, Error)]
+pub enum CheckAssetError {
+  /// The asset could not be retrieved.
+  #[error("failed to retrieve asset")]
+  Get(#[source] RequestError<asset::GetError>),
+  /// The order is not permissible for the asset.
+  #[error("the order is not permissible for the asset")]
+  Asset(#[source] AssetError),
+}
+
+
+/// Retrieve the asset an order refers to and check that the order is
+/// permissible for it, as per [`CreateReq::check_asset`].
+///
+/// The asset is retrieved by means of [`asset::get_cached`], meaning
+/// that repeated checks for the same symbol are answered locally if
+/// the client has asset caching enabled. The asset is returned on
+/// success.
+pub async fn check_asset(client: &Client, request: &CreateReq) -> Result<Asset, CheckAssetError> {
+  let asset = asset::get_cached(client, &request.symbol)
+    .await
+    .map_err(CheckAssetError::Get)?;
+  let () = request
+    .check_asset(&asset)
+    .map_err(CheckAssetError::Asset)?;
+  Ok(asset)
+}
+
+
 /// A PATCH request to be made to the /v2/orders/{order-id} endpoint.
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub struct ChangeReq {
@@ -582,6 +1441,18 @@ pub struct Order {
   /// Timestamp this order expired at.
   #[serde(rename = "canceled_at")]
   pub canceled_at: Option<DateTime<Utc>>,
+  /// Timestamp this order failed at.
+  #[serde(rename = "failed_at")]
+  pub failed_at: Option<DateTime<Utc>>,
+  /// Timestamp this order was replaced at.
+  #[serde(rename = "replaced_at")]
+  pub replaced_at: Option<DateTime<Utc>>,
+  /// The ID of the order that replaced this one, if any.
+  #[serde(rename = "replaced_by")]
+  pub replaced_by: Option<Id>,
+  /// The ID of the order that this one replaced, if any.
+  #[serde(rename = "replaces")]
+  pub replaces: Option<Id>,
   /// The order's asset class.
   #[serde(rename = "asset_class")]
   pub asset_class: asset::Class,
@@ -621,6 +1492,10 @@ pub struct Order {
   /// The percent value away from the high water mark.
   #[serde(rename = "trail_percent")]
   pub trail_percent: Option<Num>,
+  /// The high water mark, i.e., the highest (lowest) price seen since
+  /// submission of a trailing stop sell (buy) order.
+  #[serde(rename = "hwm")]
+  pub high_water_mark: Option<Num>,
   /// The average price at which the order was filled.
   #[serde(rename = "filled_avg_price")]
   pub average_fill_price: Option<Num>,
@@ -628,6 +1503,17 @@ pub struct Order {
   /// trading hours.
   #[serde(rename = "extended_hours")]
   pub extended_hours: bool,
+  /// The commission charged for the order, if any.
+  #[serde(rename = "commission", default)]
+  pub commission: Option<Num>,
+  /// A sub-classification of the order's origin, as used for routing
+  /// purposes, if any.
+  #[serde(rename = "subtag", default)]
+  pub subtag: Option<String>,
+  /// The origin of the order, e.g., the API or a correspondent, if
+  /// reported.
+  #[serde(rename = "source", default)]
+  pub source: Option<String>,
   /// Additional legs of the order.
   ///
   /// Such an additional leg could be, for example, the order for the
@@ -640,6 +1526,39 @@ pub struct Order {
   pub _non_exhaustive: (),
 }
 
+impl Order {
+  /// Retrieve the quantity requested, if the order was placed for a
+  /// quantity (as opposed to a notional amount).
+  #[inline]
+  pub fn quantity(&self) -> Option<&Num> {
+    match &self.amount {
+      Amount::Quantity { quantity } => Some(quantity),
+      Amount::Notional { .. } => None,
+    }
+  }
+
+  /// Retrieve the notional amount requested, if the order was placed
+  /// for one (as opposed to a quantity).
+  #[inline]
+  pub fn notional(&self) -> Option<&Num> {
+    match &self.amount {
+      Amount::Quantity { .. } => None,
+      Amount::Notional { notional } => Some(notional),
+    }
+  }
+
+  /// Calculate the value of the filled part of the order, i.e., the
+  /// filled quantity multiplied by the average fill price.
+  ///
+  /// The value is unavailable if nothing was filled yet.
+  pub fn filled_value(&self) -> Option<Num> {
+    self
+      .average_fill_price
+      .as_ref()
+      .map(|price| &self.filled_quantity * price)
+  }
+}
+
 
 Endpoint! {
   /// The representation of a GET request to the /v2/orders/{order-id}
@@ -800,10 +1719,13 @@ EndpointNoParse! {
 mod tests {
   use super::*;
 
-  use std::str::FromStr as _;
+  use std::fmt::Debug;
+  use std::time::Duration;
 
   use futures::TryFutureExt;
 
+  use http::StatusCode;
+
   use serde_json::from_slice as from_json;
 
   use test_log::test;
@@ -811,12 +1733,14 @@ mod tests {
   use uuid::Uuid;
 
   use crate::api::v2::asset;
+  use crate::api::v2::asset::test::asset;
+  use crate::api::v2::asset::test::asset_json;
   use crate::api::v2::asset::Exchange;
   use crate::api::v2::asset::Symbol;
   use crate::api::v2::order_util::order_aapl;
   use crate::api_info::ApiInfo;
-  use crate::Client;
-  use crate::RequestError;
+  use crate::transport::test::mock_api_info;
+  use crate::MockTransport;
 
 
   /// Check that we can serialize a [`Side`] object.
@@ -826,6 +1750,55 @@ mod tests {
     assert_eq!(to_json(&Side::Sell).unwrap(), br#""sell""#);
   }
 
+  /// Check that the string representations of the various order enums
+  /// match their serialized forms and can be parsed back.
+  #[test]
+  fn enum_string_representations() {
+    fn test<E>(variants: &[E])
+    where
+      E: AsRef<str> + Display + FromStr<Err = ()> + Serialize + PartialEq + Debug,
+    {
+      for variant in variants {
+        let json = to_json(variant).unwrap();
+        assert_eq!(json, format!(r#""{variant}""#).into_bytes());
+        assert_eq!(variant.to_string(), variant.as_ref());
+        assert_eq!(&E::from_str(variant.as_ref()).unwrap(), variant);
+      }
+      assert_eq!(E::from_str("foobar"), Err(()));
+    }
+
+    test(&[Side::Buy, Side::Sell]);
+    test(&[
+      Class::Simple,
+      Class::Bracket,
+      Class::OneCancelsOther,
+      Class::OneTriggersOther,
+    ]);
+    test(&[
+      Type::Market,
+      Type::Limit,
+      Type::Stop,
+      Type::StopLimit,
+      Type::TrailingStop,
+    ]);
+    test(&[
+      TimeInForce::Day,
+      TimeInForce::FillOrKill,
+      TimeInForce::ImmediateOrCancel,
+      TimeInForce::UntilCanceled,
+      TimeInForce::UntilMarketOpen,
+      TimeInForce::UntilMarketClose,
+    ]);
+    test(&[
+      Status::New,
+      Status::PartiallyFilled,
+      Status::Filled,
+      Status::Canceled,
+      Status::Held,
+    ]);
+    assert_eq!(Status::from_str("unknown"), Err(()));
+  }
+
   /// Check that we can properly negate a [`Side`] object.
   #[test]
   fn negate_side() {
@@ -894,6 +1867,9 @@ mod tests {
     "expired_at": "2018-10-05T05:48:59Z",
     "canceled_at": "2018-10-05T05:48:59Z",
     "failed_at": "2018-10-05T05:48:59Z",
+    "replaced_at": "2018-10-05T05:49:01Z",
+    "replaced_by": "fc61b6cc-0e1f-4d2e-9a0b-fb0b1e7c4cde",
+    "replaces": null,
     "asset_id": "904837e3-3b76-47ec-b432-046db621571b",
     "symbol": "AAPL",
     "asset_class": "us_equity",
@@ -905,6 +1881,7 @@ mod tests {
     "time_in_force": "day",
     "limit_price": "107.00",
     "stop_price": "106.00",
+    "hwm": "108.50",
     "filled_avg_price": "106.25",
     "status": "accepted",
     "extended_hours": false,
@@ -926,6 +1903,82 @@ mod tests {
     assert_eq!(order.limit_price, Some(Num::from(107)));
     assert_eq!(order.stop_price, Some(Num::from(106)));
     assert_eq!(order.average_fill_price, Some(Num::new(10625, 100)));
+    assert_eq!(
+      order.failed_at,
+      Some(
+        DateTime::parse_from_rfc3339("2018-10-05T05:48:59Z")
+          .unwrap()
+          .into()
+      )
+    );
+    assert_eq!(
+      order.replaced_by,
+      Some(Id(
+        Uuid::parse_str("fc61b6cc-0e1f-4d2e-9a0b-fb0b1e7c4cde").unwrap()
+      ))
+    );
+    assert_eq!(order.replaces, None);
+    assert_eq!(order.high_water_mark, Some(Num::new(10850, 100)));
+  }
+
+  /// Check that we can deserialize order enum values that we do not
+  /// know about.
+  #[test]
+  fn deserialize_unknown_order_enums() {
+    assert_eq!(from_json::<Class>(br#""mleg""#).unwrap(), Class::Unknown);
+    assert_eq!(from_json::<Type>(br#""foobar""#).unwrap(), Type::Unknown);
+    assert_eq!(
+      from_json::<TimeInForce>(br#""gtd""#).unwrap(),
+      TimeInForce::Unknown
+    );
+    assert_eq!(to_json(&Type::Unknown).unwrap(), br#""unknown""#);
+  }
+
+  /// Check that we can access the amounts of a notional order.
+  #[test]
+  fn notional_order_amounts() {
+    let json = br#"{
+    "id": "904837e3-3b76-47ec-b432-046db621571b",
+    "client_order_id": "904837e3-3b76-47ec-b432-046db621571b",
+    "created_at": "2018-10-05T05:48:59Z",
+    "updated_at": "2018-10-05T05:48:59Z",
+    "submitted_at": "2018-10-05T05:48:59Z",
+    "filled_at": "2018-10-05T05:48:59Z",
+    "expired_at": null,
+    "canceled_at": null,
+    "failed_at": null,
+    "asset_id": "904837e3-3b76-47ec-b432-046db621571b",
+    "symbol": "AAPL",
+    "asset_class": "us_equity",
+    "notional": "100",
+    "qty": null,
+    "filled_qty": "0.5",
+    "type": "market",
+    "order_class": "simple",
+    "side": "buy",
+    "time_in_force": "day",
+    "limit_price": null,
+    "stop_price": null,
+    "filled_avg_price": "200.00",
+    "status": "filled",
+    "extended_hours": false,
+    "commission": "0.25",
+    "subtag": "ext",
+    "source": "access_key",
+    "legs": null
+}"#;
+
+    let order = from_json::<Order>(json).unwrap();
+    assert_eq!(order.subtag.as_deref(), Some("ext"));
+    assert_eq!(order.source.as_deref(), Some("access_key"));
+    assert_eq!(order.quantity(), None);
+    assert_eq!(order.notional(), Some(&Num::from(100)));
+    assert_eq!(order.filled_value(), Some(Num::from(100)));
+    assert_eq!(order.commission, Some(Num::new(25, 100)));
+
+    let order = from_json::<Order>(&to_json(&order).unwrap()).unwrap();
+    assert_eq!(order.commission, Some(Num::new(25, 100)));
+    assert_eq!(order.source.as_deref(), Some("access_key"));
   }
 
   /// Verify that we can deserialize an order with an empty order class.
@@ -979,6 +2032,347 @@ mod tests {
     assert_eq!(from_json::<CreateReq>(&json).unwrap(), request);
   }
 
+  /// Check that `CreateReqInit::build` accepts valid orders.
+  #[test]
+  fn build_valid_order_requests() {
+    let request = CreateReqInit {
+      type_: Type::StopLimit,
+      limit_price: Some(Num::from(2)),
+      stop_price: Some(Num::from(3)),
+      ..Default::default()
+    }
+    .build("SPY", Side::Buy, Amount::quantity(1))
+    .unwrap();
+    assert_eq!(request.type_, Type::StopLimit);
+
+    let request = CreateReqInit {
+      class: Class::OneCancelsOther,
+      type_: Type::Limit,
+      take_profit: Some(TakeProfit::Limit(Num::from(3))),
+      stop_loss: Some(StopLoss::Stop(Num::from(1))),
+      ..Default::default()
+    }
+    .build("SPY", Side::Sell, Amount::quantity(1))
+    .unwrap();
+    assert_eq!(request.class, Class::OneCancelsOther);
+
+    let request = CreateReqInit {
+      type_: Type::Limit,
+      limit_price: Some(Num::from(2)),
+      extended_hours: true,
+      ..Default::default()
+    }
+    .build("SPY", Side::Buy, Amount::quantity(1))
+    .unwrap();
+    assert!(request.extended_hours);
+  }
+
+  /// Check that `CreateReqInit::build` rejects semantically invalid
+  /// orders.
+  #[test]
+  fn build_invalid_order_requests() {
+    let build = |init: CreateReqInit| init.build("SPY", Side::Buy, Amount::quantity(1));
+
+    let err = build(CreateReqInit {
+      limit_price: Some(Num::from(2)),
+      ..Default::default()
+    })
+    .unwrap_err();
+    assert_eq!(err, ValidationError::UnexpectedLimitPrice(Type::Market));
+
+    let err = build(CreateReqInit {
+      type_: Type::StopLimit,
+      stop_price: Some(Num::from(3)),
+      ..Default::default()
+    })
+    .unwrap_err();
+    assert_eq!(err, ValidationError::MissingLimitPrice(Type::StopLimit));
+
+    let err = build(CreateReqInit {
+      type_: Type::TrailingStop,
+      trail_price: Some(Num::from(1)),
+      trail_percent: Some(Num::from(1)),
+      ..Default::default()
+    })
+    .unwrap_err();
+    assert_eq!(err, ValidationError::InvalidTrail);
+
+    let err = build(CreateReqInit {
+      class: Class::Bracket,
+      type_: Type::Limit,
+      limit_price: Some(Num::from(2)),
+      take_profit: Some(TakeProfit::Limit(Num::from(3))),
+      ..Default::default()
+    })
+    .unwrap_err();
+    assert_eq!(err, ValidationError::MissingLeg(Class::Bracket));
+
+    let err = build(CreateReqInit {
+      type_: Type::Limit,
+      limit_price: Some(Num::from(2)),
+      time_in_force: TimeInForce::UntilCanceled,
+      extended_hours: true,
+      ..Default::default()
+    })
+    .unwrap_err();
+    assert_eq!(err, ValidationError::InvalidExtendedHours);
+
+    let err = build(CreateReqInit {
+      client_order_id: Some("x".repeat(49)),
+      ..Default::default()
+    })
+    .unwrap_err();
+    assert_eq!(err, ValidationError::ClientOrderIdTooLong(49));
+  }
+
+  /// Check that `CreateReqInit::build_for` enforces the documented
+  /// order type and time in force matrix of crypto currency orders.
+  #[test]
+  fn build_crypto_order_requests() {
+    let types = [
+      (Type::Market, true),
+      (Type::Limit, true),
+      (Type::Stop, false),
+      (Type::StopLimit, true),
+      (Type::TrailingStop, false),
+    ];
+    let time_in_forces = [
+      (TimeInForce::Day, false),
+      (TimeInForce::FillOrKill, false),
+      (TimeInForce::ImmediateOrCancel, true),
+      (TimeInForce::UntilCanceled, true),
+      (TimeInForce::UntilMarketOpen, false),
+      (TimeInForce::UntilMarketClose, false),
+    ];
+
+    for (type_, type_ok) in types {
+      for (time_in_force, time_in_force_ok) in time_in_forces {
+        let price = |needed: bool| needed.then(|| Num::from(2));
+        let init = CreateReqInit {
+          type_,
+          time_in_force,
+          limit_price: price(matches!(type_, Type::Limit | Type::StopLimit)),
+          stop_price: price(matches!(type_, Type::Stop | Type::StopLimit)),
+          trail_percent: price(type_ == Type::TrailingStop),
+          ..Default::default()
+        };
+
+        // All combinations are fine for equities.
+        let result = init.clone().build_for(
+          asset::Class::UsEquity,
+          "SPY",
+          Side::Buy,
+          Amount::quantity(1),
+        );
+        assert!(result.is_ok(), "{type_:?} {time_in_force:?}: {result:?}");
+
+        let result = init.build_for(
+          asset::Class::Crypto,
+          "BTC/USD",
+          Side::Buy,
+          Amount::notional(10),
+        );
+        match (type_ok, time_in_force_ok) {
+          (true, true) => assert!(result.is_ok(), "{type_:?} {time_in_force:?}: {result:?}"),
+          (false, _) => assert_eq!(
+            result.unwrap_err(),
+            ValidationError::UnsupportedAssetType(asset::Class::Crypto, type_)
+          ),
+          (true, false) => assert_eq!(
+            result.unwrap_err(),
+            ValidationError::UnsupportedTimeInForce(asset::Class::Crypto, time_in_force)
+          ),
+        }
+      }
+    }
+
+    let err = CreateReqInit {
+      class: Class::OneTriggersOther,
+      time_in_force: TimeInForce::UntilCanceled,
+      take_profit: Some(TakeProfit::Limit(Num::from(3))),
+      ..Default::default()
+    }
+    .build_for(
+      asset::Class::Crypto,
+      "BTC/USD",
+      Side::Buy,
+      Amount::quantity(1),
+    )
+    .unwrap_err();
+    assert_eq!(
+      err,
+      ValidationError::UnsupportedClass(asset::Class::Crypto, Class::OneTriggersOther)
+    );
+
+    let err = CreateReqInit {
+      type_: Type::Limit,
+      limit_price: Some(Num::from(2)),
+      time_in_force: TimeInForce::UntilCanceled,
+      extended_hours: true,
+      ..Default::default()
+    }
+    .build_for(
+      asset::Class::Crypto,
+      "BTC/USD",
+      Side::Buy,
+      Amount::quantity(1),
+    )
+    .unwrap_err();
+    // Extended hours orders have to be good for the day, which crypto
+    // orders can not be, so the generic check already fails.
+    assert_eq!(err, ValidationError::InvalidExtendedHours);
+  }
+
+  /// Check that we round prices to the applicable tick size.
+  #[test]
+  fn round_prices_to_tick() {
+    let equity = TickSize::Equity;
+    assert_eq!(
+      round_to_tick(&Num::new(123456, 1000), &equity, Rounding::Nearest),
+      Num::new(12346, 100)
+    );
+    assert_eq!(
+      round_to_tick(&Num::new(123449, 1000), &equity, Rounding::Nearest),
+      Num::new(12345, 100)
+    );
+    assert_eq!(
+      round_to_tick(&Num::new(123456, 1000000), &equity, Rounding::Nearest),
+      Num::new(1235, 10000)
+    );
+    assert_eq!(
+      round_to_tick(&Num::from(5), &equity, Rounding::Nearest),
+      Num::from(5)
+    );
+
+    assert_eq!(
+      round_to_tick(&Num::new(123456, 1000), &equity, Rounding::Down),
+      Num::new(12345, 100)
+    );
+    assert_eq!(
+      round_to_tick(&Num::new(123451, 1000), &equity, Rounding::Up),
+      Num::new(12346, 100)
+    );
+    assert_eq!(
+      round_to_tick(&Num::new(12345, 100), &equity, Rounding::Up),
+      Num::new(12345, 100)
+    );
+
+    let fixed = TickSize::Fixed(Num::new(5, 100));
+    assert_eq!(
+      round_to_tick(&Num::new(10012, 1000), &fixed, Rounding::Nearest),
+      Num::from(10)
+    );
+    assert_eq!(
+      round_to_tick(&Num::new(10026, 1000), &fixed, Rounding::Nearest),
+      Num::new(1005, 100)
+    );
+    assert_eq!(
+      round_to_tick(&Num::new(-10012, 1000), &fixed, Rounding::Down),
+      Num::new(-1005, 100)
+    );
+    assert_eq!(
+      round_to_tick(&Num::new(-10012, 1000), &fixed, Rounding::Up),
+      Num::from(-10)
+    );
+  }
+
+  /// Check that `CreateReqInit::init` rounds all prices if a tick size
+  /// is configured.
+  #[test]
+  fn init_rounds_prices() {
+    let request = CreateReqInit::new()
+      .class(Class::Bracket)
+      .type_(Type::StopLimit)
+      .limit_price(Num::new(100123, 1000))
+      .stop_price(Num::new(100987, 1000))
+      .take_profit(TakeProfit::Limit(Num::new(110004, 1000)))
+      .stop_loss(StopLoss::StopLimit(
+        Num::new(90001, 1000),
+        Num::new(89996, 1000),
+      ))
+      .tick_size(TickSize::Equity)
+      .init("SPY", Side::Buy, Amount::quantity(1));
+
+    assert_eq!(request.limit_price, Some(Num::new(10012, 100)));
+    assert_eq!(request.stop_price, Some(Num::new(10099, 100)));
+    assert_eq!(
+      request.take_profit,
+      Some(TakeProfit::Limit(Num::new(11001, 100)))
+    );
+    assert_eq!(
+      request.stop_loss,
+      Some(StopLoss::StopLimit(Num::from(90), Num::from(90)))
+    );
+
+    let request = CreateReqInit::new()
+      .type_(Type::Limit)
+      .limit_price(Num::new(100123, 1000))
+      .tick_size(TickSize::Equity)
+      .init("SPY", Side::Sell, Amount::quantity(1));
+    assert_eq!(request.limit_price, Some(Num::new(10013, 100)));
+
+    let request = CreateReqInit::new()
+      .type_(Type::TrailingStop)
+      .trail_price(Num::new(1234, 1000))
+      .tick_size(TickSize::Equity)
+      .init("SPY", Side::Sell, Amount::quantity(1));
+    assert_eq!(request.trail_price, Some(Num::new(1234, 1000)));
+
+    let request = CreateReqInit::new()
+      .type_(Type::Limit)
+      .limit_price(Num::new(100123, 1000))
+      .init("SPY", Side::Buy, Amount::quantity(1));
+    assert_eq!(request.limit_price, Some(Num::new(100123, 1000)));
+  }
+
+  /// Check that the typed order class constructors create valid
+  /// requests.
+  #[test]
+  fn create_order_class_requests() {
+    let request = CreateReq::bracket(
+      "SPY",
+      Side::Buy,
+      Amount::quantity(1),
+      Entry::Limit(Num::from(2)),
+      TakeProfit::Limit(Num::from(3)),
+      StopLoss::Stop(Num::from(1)),
+    );
+    let expected = CreateReqInit {
+      class: Class::Bracket,
+      type_: Type::Limit,
+      limit_price: Some(Num::from(2)),
+      take_profit: Some(TakeProfit::Limit(Num::from(3))),
+      stop_loss: Some(StopLoss::Stop(Num::from(1))),
+      ..Default::default()
+    }
+    .build("SPY", Side::Buy, Amount::quantity(1))
+    .unwrap();
+    assert_eq!(request, expected);
+
+    let request = CreateReq::oco(
+      "SPY",
+      Side::Sell,
+      Amount::quantity(1),
+      TakeProfit::Limit(Num::from(3)),
+      StopLoss::StopLimit(Num::from(1), Num::from(1)),
+    );
+    assert_eq!(request.class, Class::OneCancelsOther);
+    assert_eq!(request.type_, Type::Limit);
+    assert_eq!(request.limit_price, None);
+
+    let request = CreateReq::oto(
+      "SPY",
+      Side::Buy,
+      Amount::quantity(1),
+      Entry::Market,
+      Exit::StopLoss(StopLoss::Stop(Num::from(1))),
+    );
+    assert_eq!(request.class, Class::OneTriggersOther);
+    assert_eq!(request.type_, Type::Market);
+    assert_eq!(request.take_profit, None);
+    assert_eq!(request.stop_loss, Some(StopLoss::Stop(Num::from(1))));
+  }
+
   /// Check that we can serialize and deserialize a [`ChangeReq`].
   #[test]
   fn serialize_deserialize_change_request() {
@@ -993,6 +2387,70 @@ mod tests {
     assert_eq!(from_json::<ChangeReq>(&json).unwrap(), request);
   }
 
+  /// Check that we can validate order requests against asset metadata.
+  #[test]
+  fn check_order_asset() {
+    let whole = CreateReqInit::default().init("AAPL", Side::Buy, Amount::quantity(2));
+    let fractional =
+      CreateReqInit::default().init("AAPL", Side::Buy, Amount::quantity(Num::new(1, 2)));
+    let notional = CreateReqInit::default().init("AAPL", Side::Buy, Amount::notional(100));
+
+    assert_eq!(whole.check_asset(&asset(true, false, false)), Ok(()));
+    assert_eq!(fractional.check_asset(&asset(true, false, true)), Ok(()));
+    assert_eq!(
+      whole.check_asset(&asset(false, true, true)),
+      Err(AssetError::NotTradable("AAPL".to_string()))
+    );
+    assert_eq!(
+      fractional.check_asset(&asset(true, true, false)),
+      Err(AssetError::NotFractionable("AAPL".to_string()))
+    );
+    assert_eq!(
+      notional.check_asset(&asset(true, true, false)),
+      Err(AssetError::NotFractionable("AAPL".to_string()))
+    );
+
+    assert_eq!(whole.check_short(&asset(true, true, false)), Ok(()));
+    assert_eq!(
+      whole.check_short(&asset(true, false, false)),
+      Err(AssetError::NotShortable("AAPL".to_string()))
+    );
+  }
+
+  /// Check that asset metadata are cached by the client when checking
+  /// orders, if so configured.
+  #[test(tokio::test)]
+  async fn check_order_asset_cached() {
+    let symbol = Symbol::Sym("AAPL".to_string());
+    let mock = MockTransport::new();
+    let () = mock.respond::<asset::Get>(&symbol, StatusCode::OK, asset_json(true, true, true));
+
+    let api_info = mock_api_info();
+    let client = Client::builder()
+      .with_transport(mock.clone())
+      .asset_cache_ttl(Duration::from_secs(60))
+      .build(api_info);
+
+    let request = CreateReqInit::default().init("AAPL", Side::Buy, Amount::notional(100));
+    let asset = check_asset(&client, &request).await.unwrap();
+    assert_eq!(asset.symbol, "AAPL");
+
+    // The asset turning non-fractionable is not noticed while the
+    // cached metadata are valid...
+    let () = mock.respond::<asset::Get>(&symbol, StatusCode::OK, asset_json(true, true, false));
+    let _asset = check_asset(&client, &request).await.unwrap();
+
+    // ... but it is when bypassing the cache.
+    let client = Client::builder()
+      .with_transport(mock)
+      .build(client.api_info().clone());
+    let err = check_asset(&client, &request).await.unwrap_err();
+    assert!(
+      matches!(err, CheckAssetError::Asset(AssetError::NotFractionable(..))),
+      "{err:?}"
+    );
+  }
+
   /// Verify that we can submit a limit order.
   #[test(tokio::test)]
   async fn submit_limit_order() {
@@ -1,15 +1,71 @@
 // Copyright (C) 2019-2024 The apca Developers
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::time::Duration;
+
+use chrono::DateTime;
+use chrono::Utc;
+
+use futures::stream::iter;
+use futures::stream::once;
+use futures::stream::select;
+use futures::stream::BoxStream;
+use futures::Stream;
+use futures::StreamExt as _;
+use futures::TryStreamExt as _;
+
+use http::StatusCode;
+
+use num_decimal::Num;
+
 use serde::Deserialize;
 use serde::Serialize;
 use serde_urlencoded::to_string as to_query;
 
+use thiserror::Error as ThisError;
+
+use tokio::time::timeout;
+
+use uuid::Uuid;
+
+use crate::api::v2::account_activities;
+use crate::api::v2::account_activities::ActivityReq;
+use crate::api::v2::account_activities::ActivityType;
+use crate::api::v2::order;
+use crate::api::v2::order::Change;
+use crate::api::v2::order::ChangeError;
+use crate::api::v2::order::ChangeReq;
+use crate::api::v2::order::Create;
+use crate::api::v2::order::CreateError;
+use crate::api::v2::order::CreateReq;
+use crate::api::v2::order::GetByClientId;
+use crate::api::v2::order::GetByClientIdError;
+use crate::api::v2::order::GetError;
 use crate::api::v2::order::Order;
+use crate::api::v2::updates::OrderStatus;
+use crate::api::v2::updates::OrderUpdate;
+use crate::api::v2::updates::OrderUpdates;
+use crate::subscribable::Subscribable;
 use crate::util::string_slice_to_str;
 use crate::util::vec_from_comma_separated_str;
+use crate::Client;
+use crate::Error;
+use crate::RequestError;
 use crate::Str;
 
+
+/// The maximum number of submission attempts made by
+/// [`submit_idempotent`].
+const MAX_SUBMIT_ATTEMPTS: usize = 3;
+
+/// The maximum number of orders retrieved per request by
+/// [`resume_updates`].
+const MAX_LIST_ORDERS: usize = 500;
+
+
 /// The status of orders to list.
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum Status {
@@ -41,10 +97,19 @@ pub struct ListReq {
   /// The status of orders to list.
   #[serde(rename = "status")]
   pub status: Status,
+  /// If set, only list orders on the given side.
+  #[serde(rename = "side")]
+  pub side: Option<order::Side>,
   /// The maximum number of orders contained in the response. Defaults
   /// to 50 and max is 500.
   #[serde(rename = "limit")]
   pub limit: Option<usize>,
+  /// If set, only list orders submitted after this time.
+  #[serde(rename = "after")]
+  pub after: Option<DateTime<Utc>>,
+  /// If set, only list orders submitted until this time.
+  #[serde(rename = "until")]
+  pub until: Option<DateTime<Utc>>,
   /// If false the result will not roll up multi-leg orders under the
   /// legs field of the primary order.
   #[serde(rename = "nested")]
@@ -60,7 +125,10 @@ impl Default for ListReq {
     Self {
       symbols: Vec::new(),
       status: Status::Open,
+      side: None,
       limit: None,
+      after: None,
+      until: None,
       // Nested orders merely appear as legs in each order being
       // returned. As such, having them included is very non-intrusive
       // and should be a reasonable default.
@@ -91,16 +159,756 @@ Endpoint! {
 }
 
 
+/// A trimmed down representation of an order, as retrieved in bulk
+/// by means of [`ListSummaries`].
+///
+/// A summary captures the members most relevant when sweeping through
+/// large numbers of orders, such as all orders of a portfolio. By
+/// skipping over everything else, deserializing a list of summaries
+/// allocates considerably less than a list of [`Order`] objects does.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct Summary {
+  /// The order's ID.
+  #[serde(rename = "id")]
+  pub id: order::Id,
+  /// Client unique order ID (free form string).
+  #[serde(rename = "client_order_id")]
+  pub client_order_id: String,
+  /// The status of the order.
+  #[serde(rename = "status")]
+  pub status: order::Status,
+  /// The symbol of the asset being traded.
+  #[serde(rename = "symbol")]
+  pub symbol: String,
+  /// The quantity being requested, if the order is quantity based.
+  #[serde(rename = "qty", default)]
+  pub quantity: Option<Num>,
+  /// The dollar amount being requested, if the order is notional.
+  #[serde(rename = "notional", default)]
+  pub notional: Option<Num>,
+  /// The quantity that was filled.
+  #[serde(rename = "filled_qty")]
+  pub filled_quantity: Num,
+  /// The average price at which the order was filled, if any.
+  #[serde(rename = "filled_avg_price", default)]
+  pub average_fill_price: Option<Num>,
+  /// The type of order.
+  #[serde(rename = "type")]
+  pub type_: order::Type,
+  /// The side the order is on.
+  #[serde(rename = "side")]
+  pub side: order::Side,
+  /// The limit price, if any.
+  #[serde(rename = "limit_price", default)]
+  pub limit_price: Option<Num>,
+  /// The stop price, if any.
+  #[serde(rename = "stop_price", default)]
+  pub stop_price: Option<Num>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+Endpoint! {
+  /// The representation of a GET request to the /v2/orders endpoint,
+  /// retrieving order [`Summary`] objects.
+  ///
+  /// Legs of multi-leg orders are not part of a summary. Set
+  /// [`ListReq::nested`] to `false` to have them reported as separate
+  /// entries instead.
+  pub ListSummaries(ListReq),
+  Ok => Vec<Summary>, [
+    /// The list of orders was retrieved successfully.
+    /* 200 */ OK,
+  ],
+  Err => ListSummariesError, []
+
+  #[inline]
+  fn path(_input: &Self::Input) -> Str {
+    "/v2/orders".into()
+  }
+
+  fn query(input: &Self::Input) -> Result<Option<Str>, Self::ConversionError> {
+    Ok(Some(to_query(input)?.into()))
+  }
+}
+
+
+/// Derive a client order ID from the provided seed.
+///
+/// The same seed always maps to the same ID, irrespective of the
+/// process or machine the function is invoked on, allowing for the
+/// seed to be persisted instead of the ID.
+pub fn client_order_id<S>(seed: S) -> String
+where
+  S: AsRef<[u8]>,
+{
+  // We use the 128 bit variant of FNV-1a, for its simplicity.
+  const OFFSET: u128 = 0x6c62272e07bb014262b821756295c58d;
+  const PRIME: u128 = 0x0000000001000000000000000000013b;
+
+  let hash = seed.as_ref().iter().fold(OFFSET, |hash, byte| {
+    (hash ^ u128::from(*byte)).wrapping_mul(PRIME)
+  });
+  Uuid::new_v8(hash.to_be_bytes()).as_simple().to_string()
+}
+
+
+/// Check whether an error leaves it unclear whether an order was
+/// created.
+fn is_ambiguous(error: &RequestError<CreateError>) -> bool {
+  match error {
+    RequestError::Endpoint(CreateError::UnexpectedStatus(status, _)) => {
+      *status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+    },
+    RequestError::Endpoint(..) => false,
+    RequestError::Hyper(..)
+    | RequestError::HyperUtil(..)
+    | RequestError::Io(..)
+    | RequestError::Transport(..)
+    | RequestError::Timeout(..) => true,
+    RequestError::LiveTradingNotConfirmed => false,
+  }
+}
+
+
+/// Submit an order, making sure that it is created at most once.
+///
+/// The order's client order ID is derived from the provided `seed`
+/// (see [`client_order_id`]), overwriting any ID set on the request.
+/// When submission fails in a way that leaves it unclear whether the
+/// order was created (e.g., due to a network error or a timeout), the
+/// order is looked up by its client order ID and, if found, returned.
+/// Otherwise submission is retried, up to a small number of times.
+///
+/// Using the same seed for all submissions of what is logically the
+/// same order, including across restarts of the program, guarantees
+/// that no duplicate order is created.
+pub async fn submit_idempotent<S>(
+  client: &Client,
+  request: &CreateReq,
+  seed: S,
+) -> Result<Order, RequestError<CreateError>>
+where
+  S: AsRef<[u8]>,
+{
+  let client_order_id = client_order_id(seed);
+  let request = CreateReq {
+    client_order_id: Some(client_order_id.clone()),
+    ..request.clone()
+  };
+
+  let mut attempts = 0;
+  let mut ambiguous = false;
+
+  loop {
+    attempts += 1;
+
+    let error = match client.issue::<Create>(&request).await {
+      Ok(order) => return Ok(order),
+      Err(error) => error,
+    };
+
+    let retry = is_ambiguous(&error);
+    // If a previous attempt actually created the order, the server
+    // rejects the duplicate client order ID and we still want to report
+    // the existing order.
+    let duplicate =
+      ambiguous && matches!(error, RequestError::Endpoint(CreateError::InvalidInput(..)));
+    if !retry && !duplicate {
+      return Err(error)
+    }
+    ambiguous = true;
+
+    match client.issue::<GetByClientId>(&client_order_id).await {
+      Ok(order) => return Ok(order),
+      Err(RequestError::Endpoint(GetByClientIdError::NotFound(..)))
+        if retry && attempts < MAX_SUBMIT_ATTEMPTS =>
+      {
+        continue
+      },
+      Err(_) => return Err(error),
+    }
+  }
+}
+
+
+/// An error as reported by [`submit_and_await`].
+#[derive(Debug, ThisError)]
+pub enum SubmitAndAwaitError {
+  /// The order could not be submitted.
+  #[error("failed to submit order")]
+  Create(#[source] RequestError<CreateError>),
+  /// The order could not be retrieved.
+  #[error("failed to retrieve order")]
+  Get(#[source] RequestError<GetError>),
+  /// The order update stream reported an error.
+  #[error("failed to stream order updates")]
+  Stream(#[source] Error),
+  /// The order did not reach a terminal state within the given time.
+  #[error("order did not reach a terminal state within {0:?}")]
+  Timeout(Duration),
+}
+
+
+/// Submit an order and wait for it to reach a terminal state, i.e., to
+/// be filled, canceled, rejected, or similar.
+///
+/// Order updates are subscribed to before the order is submitted, so
+/// that no update can be missed. `duration` bounds the time spent
+/// waiting for the order to reach a terminal state after it was
+/// submitted. Once it elapsed, or should the update stream end
+/// prematurely, the order's state is retrieved one final time before
+/// giving up.
+pub async fn submit_and_await(
+  client: &Client,
+  request: &CreateReq,
+  duration: Duration,
+) -> Result<Order, SubmitAndAwaitError> {
+  let (mut stream, _subscription) = client
+    .subscribe::<OrderUpdates>()
+    .await
+    .map_err(SubmitAndAwaitError::Stream)?;
+
+  let order = client
+    .issue::<Create>(request)
+    .await
+    .map_err(SubmitAndAwaitError::Create)?;
+  if order.status.is_terminal() {
+    return Ok(order)
+  }
+
+  let id = order.id;
+  let wait = async {
+    while let Some(result) = stream.next().await {
+      let update = result.map_err(Error::WebSocket)?.map_err(Error::Json)?;
+      if update.order.id == id && update.order.status.is_terminal() {
+        return Ok(Some(update.order))
+      }
+    }
+    Ok(None)
+  };
+
+  let error = match timeout(duration, wait).await {
+    Ok(Ok(Some(order))) => return Ok(order),
+    Ok(Ok(None)) => {
+      SubmitAndAwaitError::Stream(Error::Str("order update stream ended unexpectedly".into()))
+    },
+    Ok(Err(err)) => return Err(SubmitAndAwaitError::Stream(err)),
+    Err(_elapsed) => SubmitAndAwaitError::Timeout(duration),
+  };
+
+  let order = client
+    .issue::<order::Get>(&id)
+    .await
+    .map_err(SubmitAndAwaitError::Get)?;
+  if order.status.is_terminal() {
+    Ok(order)
+  } else {
+    Err(error)
+  }
+}
+
+
+/// An error as reported by [`resume_updates`].
+#[derive(Debug, ThisError)]
+pub enum ResumeUpdatesError {
+  /// The order update stream could not be subscribed to or reported
+  /// an error.
+  #[error("failed to receive order updates")]
+  Stream(#[source] Error),
+  /// The orders closed in the gap window could not be listed.
+  #[error("failed to list orders")]
+  List(#[source] RequestError<ListError>),
+  /// The fills in the gap window could not be retrieved.
+  #[error("failed to retrieve account activities")]
+  Activities(#[source] RequestError<account_activities::GetError>),
+  /// An order affected by a fill could not be retrieved.
+  #[error("failed to retrieve order")]
+  Get(#[source] RequestError<GetError>),
+}
+
+
+/// The item type of the order update stream.
+type UpdateItem = <<OrderUpdates as Subscribable>::Stream as Stream>::Item;
+
+/// A stream of order updates, as returned by [`resume_updates`].
+///
+/// The updates synthesized for the gap window are interleaved with
+/// those received over the order update stream.
+pub type Resumed<'c> = BoxStream<'c, Result<OrderUpdate, ResumeUpdatesError>>;
+
+
+/// Synthesize the terminal event of `order`, if it reached a terminal
+/// state at or after `since`.
+fn terminal_update(order: Order, since: DateTime<Utc>) -> Option<OrderUpdate> {
+  let (event, timestamp) = match order.status {
+    order::Status::Filled => (OrderStatus::Filled, order.filled_at),
+    order::Status::Canceled => (OrderStatus::Canceled, order.canceled_at),
+    order::Status::Expired => (OrderStatus::Expired, order.expired_at),
+    order::Status::Rejected => (OrderStatus::Rejected, order.failed_at),
+    order::Status::Replaced => (OrderStatus::Replaced, order.replaced_at),
+    _ => return None,
+  };
+  let timestamp = timestamp.or(order.updated_at).unwrap_or(order.created_at);
+  if timestamp < since {
+    return None
+  }
+
+  Some(OrderUpdate {
+    event,
+    order,
+    timestamp,
+    execution_id: None,
+    price: None,
+    quantity: None,
+    position_quantity: None,
+  })
+}
+
+
+/// Flatten an item of the order update stream.
+fn live_update(item: UpdateItem) -> Result<OrderUpdate, Error> {
+  item
+    .map_err(Error::from)
+    .and_then(|result| result.map_err(Error::Json))
+}
+
+
+/// Synthesize the terminal events of orders that reached a terminal
+/// state since `since`, ordered by time.
+async fn reconcile(
+  client: &Client,
+  since: DateTime<Utc>,
+) -> Result<Vec<OrderUpdate>, ResumeUpdatesError> {
+  let mut seen = HashSet::new();
+  let mut updates = Vec::new();
+
+  // Orders are listed most recent first, so we page backwards in time
+  // by moving `until` to the oldest order received.
+  let mut request = ListReq {
+    status: Status::Closed,
+    limit: Some(MAX_LIST_ORDERS),
+    after: Some(since),
+    nested: false,
+    ..Default::default()
+  };
+  loop {
+    let orders = client
+      .issue::<List>(&request)
+      .await
+      .map_err(ResumeUpdatesError::List)?;
+    let count = orders.len();
+    let oldest = orders
+      .last()
+      .map(|order| order.submitted_at.unwrap_or(order.created_at));
+
+    for order in orders {
+      if seen.insert(order.id) {
+        if let Some(update) = terminal_update(order, since) {
+          let () = updates.push(update);
+        }
+      }
+    }
+
+    match oldest {
+      Some(oldest) if count >= MAX_LIST_ORDERS && request.until != Some(oldest) => {
+        request.until = Some(oldest)
+      },
+      _ => break,
+    }
+  }
+
+  // Orders submitted before `since` are not covered by the above, but
+  // the ones among them that got filled in the gap window can be found
+  // by means of their fills.
+  let request = ActivityReq {
+    types: vec![ActivityType::Fill],
+    direction: account_activities::Direction::Ascending,
+    after: Some(since),
+    ..Default::default()
+  };
+  let activities = account_activities::fetch_all(client, request)
+    .try_collect::<Vec<_>>()
+    .await
+    .map_err(ResumeUpdatesError::Activities)?;
+
+  for activity in activities {
+    let id = match activity.into_trade() {
+      Ok(trade) => trade.order_id,
+      Err(..) => continue,
+    };
+    if !seen.insert(id) {
+      continue
+    }
+
+    let order = client
+      .issue::<order::Get>(&id)
+      .await
+      .map_err(ResumeUpdatesError::Get)?;
+    if let Some(update) = terminal_update(order, since) {
+      let () = updates.push(update);
+    }
+  }
+
+  let () = updates.sort_by_key(|update| update.timestamp);
+  Ok(updates)
+}
+
+
+/// Subscribe to order updates, replaying the terminal events of orders
+/// closed since `since`.
+///
+/// The order update stream does not offer any replay functionality,
+/// meaning that updates sent while not being connected, e.g., after a
+/// disconnect, are lost. This function subscribes to order updates and
+/// reconciles the gap window starting at `since`: closed orders
+/// submitted in this window as well as orders with fills reported as
+/// account activities in it are retrieved and, if they reached a
+/// terminal state, corresponding updates synthesized.
+///
+/// Reconciliation happens concurrently with the reception of live
+/// updates, which are not held back in the meantime. Synthesized
+/// updates are yielded in time order among themselves, but may be
+/// interleaved with live ones. A failed reconciliation is reported as
+/// an error by the stream, after which live updates continue to be
+/// yielded.
+///
+/// Synthesized updates lack execution specific data, i.e., execution
+/// ID, price, and quantity. Orders submitted before `since` that
+/// reached a terminal state without being filled (e.g., by being
+/// canceled) are not covered. An update for a given order may be
+/// reported both as synthesized and live, if it occurred while the
+/// subscription was being established.
+pub async fn resume_updates(
+  client: &Client,
+  since: DateTime<Utc>,
+) -> Result<(Resumed<'_>, <OrderUpdates as Subscribable>::Subscription), ResumeUpdatesError> {
+  // Subscribe first, so that no update can slip through between the
+  // reconciliation and the start of the stream.
+  let (stream, subscription) = client
+    .subscribe::<OrderUpdates>()
+    .await
+    .map_err(ResumeUpdatesError::Stream)?;
+
+  let replay = once(reconcile(client, since))
+    .map_ok(|updates| iter(updates).map(Ok))
+    .try_flatten();
+  let live = stream.map(live_update).map_err(ResumeUpdatesError::Stream);
+  Ok((select(replay, live).boxed(), subscription))
+}
+
+
+/// An error as reported by [`patch_take_profit`] and
+/// [`patch_stop_loss`].
+#[derive(Debug, ThisError)]
+pub enum PatchLegError {
+  /// The parent order could not be retrieved.
+  #[error("failed to retrieve parent order")]
+  Get(#[source] RequestError<GetError>),
+  /// The parent order does not have a leg of the requested kind.
+  #[error("order {0:?} has no matching leg")]
+  NoLeg(order::Id),
+  /// The leg could not be changed.
+  #[error("failed to change order leg")]
+  Change(#[source] RequestError<ChangeError>),
+}
+
+
+/// Change the first leg of the order with ID `parent_id` for which
+/// `is_leg` returns `true`.
+async fn patch_leg(
+  client: &Client,
+  parent_id: order::Id,
+  is_leg: fn(&Order) -> bool,
+  request: ChangeReq,
+) -> Result<Order, PatchLegError> {
+  let parent = client
+    .issue::<order::Get>(&parent_id)
+    .await
+    .map_err(PatchLegError::Get)?;
+  let leg = parent
+    .legs
+    .iter()
+    .find(|leg| is_leg(leg))
+    .ok_or(PatchLegError::NoLeg(parent_id))?;
+
+  client
+    .issue::<Change>(&(leg.id, request))
+    .await
+    .map_err(PatchLegError::Change)
+}
+
+
+/// Change the limit price of the take-profit leg of a bracket (or
+/// one-triggers-other) order.
+///
+/// The leg is located among the `legs` of the order with ID
+/// `parent_id`, which is retrieved first. The changed leg is returned.
+pub async fn patch_take_profit(
+  client: &Client,
+  parent_id: order::Id,
+  limit_price: Num,
+) -> Result<Order, PatchLegError> {
+  let request = ChangeReq {
+    limit_price: Some(limit_price),
+    ..Default::default()
+  };
+  patch_leg(
+    client,
+    parent_id,
+    |leg| leg.type_ == order::Type::Limit,
+    request,
+  )
+  .await
+}
+
+
+/// Change the stop price of the stop-loss leg of a bracket (or
+/// one-triggers-other) order.
+///
+/// If the stop-loss leg is a stop-limit order, its limit price can
+/// be adjusted along with the stop price by providing `limit_price`.
+/// The leg is located among the `legs` of the order with ID
+/// `parent_id`, which is retrieved first. The changed leg is returned.
+pub async fn patch_stop_loss(
+  client: &Client,
+  parent_id: order::Id,
+  stop_price: Num,
+  limit_price: Option<Num>,
+) -> Result<Order, PatchLegError> {
+  let request = ChangeReq {
+    stop_price: Some(stop_price),
+    limit_price,
+    ..Default::default()
+  };
+  patch_leg(
+    client,
+    parent_id,
+    |leg| matches!(leg.type_, order::Type::Stop | order::Type::StopLimit),
+    request,
+  )
+  .await
+}
+
+
+/// An event in the lifecycle of an order, as recorded by a [`Journal`].
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Event {
+  /// The kind of event that occurred.
+  #[serde(rename = "event")]
+  pub event: OrderStatus,
+  /// The time at which the event occurred.
+  #[serde(rename = "timestamp")]
+  pub timestamp: DateTime<Utc>,
+  /// The ID of the execution that caused the event, if any.
+  #[serde(rename = "execution_id")]
+  pub execution_id: Option<Uuid>,
+  /// The price at which the order was (partially) filled, for fill
+  /// events.
+  #[serde(rename = "price")]
+  pub price: Option<Num>,
+  /// The quantity filled as part of the event, for fill events.
+  #[serde(rename = "qty")]
+  pub quantity: Option<Num>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// The recorded lifecycle of a single order.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Lifecycle {
+  /// The most recent state of the order.
+  #[serde(rename = "order")]
+  pub order: Order,
+  /// The events the order went through, ordered by time.
+  #[serde(rename = "events")]
+  pub events: Vec<Event>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// An in-memory journal of order lifecycles, as reconstructed from the
+/// updates received through an [`OrderUpdates`] stream.
+///
+/// ```no_run
+/// # use apca::ApiInfo;
+/// # use apca::Client;
+/// use apca::api::v2::orders::Journal;
+/// use apca::api::v2::updates::OrderUpdates;
+/// use futures::StreamExt as _;
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let client = Client::new(ApiInfo::from_env().unwrap());
+/// let (mut stream, _subscription) = client.subscribe::<OrderUpdates>().await.unwrap();
+///
+/// let mut journal = Journal::new();
+/// while let Some(result) = stream.next().await {
+///   if let Ok(Ok(update)) = result {
+///     let () = journal.record(update);
+///   }
+/// }
+/// # })
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Journal {
+  /// The lifecycles of all recorded orders, keyed on their IDs.
+  orders: HashMap<order::Id, Lifecycle>,
+}
+
+impl Journal {
+  /// Create a new, empty `Journal`.
+  #[inline]
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Record an order update.
+  ///
+  /// Events are kept ordered by their timestamp. The most recent state
+  /// of the order is only replaced by updates not older than the
+  /// latest recorded event, so updates received out of order do not
+  /// clobber newer state.
+  pub fn record(&mut self, update: OrderUpdate) {
+    let OrderUpdate {
+      event,
+      order,
+      timestamp,
+      execution_id,
+      price,
+      quantity,
+      ..
+    } = update;
+
+    let event = Event {
+      event,
+      timestamp,
+      execution_id,
+      price,
+      quantity,
+      _non_exhaustive: (),
+    };
+
+    match self.orders.get_mut(&order.id) {
+      Some(lifecycle) => {
+        let latest = lifecycle.events.last().map(|event| event.timestamp);
+        if latest.map_or(true, |latest| timestamp >= latest) {
+          lifecycle.order = order;
+        }
+
+        let idx = lifecycle
+          .events
+          .partition_point(|event| event.timestamp <= timestamp);
+        let () = lifecycle.events.insert(idx, event);
+      },
+      None => {
+        let lifecycle = Lifecycle {
+          order,
+          events: vec![event],
+          _non_exhaustive: (),
+        };
+        let _prev = self.orders.insert(lifecycle.order.id, lifecycle);
+      },
+    }
+  }
+
+  /// Retrieve the recorded lifecycle of the order with the given ID.
+  #[inline]
+  pub fn lifecycle(&self, id: order::Id) -> Option<&Lifecycle> {
+    self.orders.get(&id)
+  }
+
+  /// Retrieve the most recent state of all orders that are still open,
+  /// i.e., in a non-terminal state.
+  pub fn open_orders(&self) -> impl Iterator<Item = &Order> {
+    self
+      .orders
+      .values()
+      .map(|lifecycle| &lifecycle.order)
+      .filter(|order| !order.status.is_terminal())
+  }
+
+  /// Retrieve all open orders grouped by the symbol they are for.
+  ///
+  /// Orders are sorted by their creation time within each group.
+  pub fn open_orders_by_symbol(&self) -> BTreeMap<&str, Vec<&Order>> {
+    let mut symbols = BTreeMap::<_, Vec<_>>::new();
+    for order in self.open_orders() {
+      let () = symbols
+        .entry(order.symbol.as_str())
+        .or_default()
+        .push(order);
+    }
+
+    for orders in symbols.values_mut() {
+      let () = orders.sort_by_key(|order| order.created_at);
+    }
+    symbols
+  }
+
+  /// Export a snapshot of all recorded lifecycles, ordered by the
+  /// creation time of the respective order.
+  pub fn snapshot(&self) -> Vec<Lifecycle> {
+    let mut lifecycles = self.orders.values().cloned().collect::<Vec<_>>();
+    let () = lifecycles.sort_by_key(|lifecycle| (lifecycle.order.created_at, lifecycle.order.id.0));
+    lifecycles
+  }
+
+  /// Retrieve the number of orders recorded.
+  #[inline]
+  pub fn len(&self) -> usize {
+    self.orders.len()
+  }
+
+  /// Check whether no orders have been recorded.
+  #[inline]
+  pub fn is_empty(&self) -> bool {
+    self.orders.is_empty()
+  }
+}
+
+impl Extend<OrderUpdate> for Journal {
+  fn extend<I>(&mut self, iter: I)
+  where
+    I: IntoIterator<Item = OrderUpdate>,
+  {
+    iter.into_iter().for_each(|update| self.record(update))
+  }
+}
+
+
 #[cfg(test)]
 mod tests {
   use super::*;
 
+  use std::collections::VecDeque;
+  use std::future::Future;
+  use std::sync::Arc;
+  use std::sync::Mutex;
+
+  use futures::channel::oneshot::channel;
   use futures::future::ok;
+  use futures::future::ready;
+  use futures::future::BoxFuture;
   use futures::pin_mut;
+  use futures::FutureExt as _;
+  use futures::SinkExt as _;
   use futures::StreamExt;
   use futures::TryStreamExt;
 
-  use num_decimal::Num;
+  use http::Request;
+  use http::Response;
+  use http_body_util::Full;
+
+  use hyper::body::Bytes;
 
   use serde_json::from_slice as from_json;
   use serde_json::to_vec as to_json;
@@ -109,12 +917,21 @@ mod tests {
 
   use test_log::test;
 
+  use url::Url;
+
+  use websocket_util::test::mock_server;
+  use websocket_util::test::WebSocketStream;
+  use websocket_util::tungstenite::Message;
+
   use crate::api::v2::order;
   use crate::api::v2::order_util::order_aapl;
   use crate::api::v2::order_util::order_stock;
-  use crate::api::v2::updates;
   use crate::api_info::ApiInfo;
+  use crate::transport::test::mock_api_info;
+  use crate::transport::test::mock_client;
   use crate::Client;
+  use crate::Transport;
+  use crate::TransportError;
 
 
   /// Make sure that we can serialize and deserialize an `ListReq`.
@@ -151,15 +968,586 @@ mod tests {
     let query = to_query(&request).unwrap();
     assert_eq!(from_query::<ListReq>(&query).unwrap(), request);
 
+    request.side = Some(order::Side::Sell);
+    let query = to_query(&request).unwrap();
+    assert_eq!(
+      query,
+      "symbols=ABC&status=closed&side=sell&limit=42&nested=true"
+    );
+    assert_eq!(from_query::<ListReq>(&query).unwrap(), request);
+
+    request.after = Some(
+      DateTime::parse_from_rfc3339("2018-10-05T05:48:00Z")
+        .unwrap()
+        .into(),
+    );
+    let query = to_query(&request).unwrap();
+    assert_eq!(from_query::<ListReq>(&query).unwrap(), request);
+
     request.symbols.clear();
     let query = to_query(&request).unwrap();
     assert_eq!(from_query::<ListReq>(&query).unwrap(), request);
   }
 
+  /// The reference order as returned by the mock server.
+  const ORDER: &str = r#"{
+    "id": "904837e3-3b76-47ec-b432-046db621571b",
+    "client_order_id": "e5bd74f5b2e6f217a2e2aeee0e4e1b7b",
+    "created_at": "2018-10-05T05:48:59Z",
+    "updated_at": "2018-10-05T05:48:59Z",
+    "submitted_at": "2018-10-05T05:48:59Z",
+    "filled_at": null,
+    "expired_at": null,
+    "canceled_at": null,
+    "failed_at": null,
+    "replaced_at": null,
+    "replaced_by": null,
+    "replaces": null,
+    "asset_id": "904837e3-3b76-47ec-b432-046db621571b",
+    "symbol": "AAPL",
+    "asset_class": "us_equity",
+    "qty": "1",
+    "filled_qty": "0",
+    "type": "limit",
+    "order_class": "simple",
+    "side": "buy",
+    "time_in_force": "day",
+    "limit_price": "1",
+    "stop_price": null,
+    "hwm": null,
+    "filled_avg_price": null,
+    "status": "accepted",
+    "extended_hours": false,
+    "legs": null
+  }"#;
+
+
+  /// Check that we can parse order summaries.
+  #[test]
+  fn parse_summaries() {
+    let response = format!("[{ORDER}]");
+    let summaries = from_json::<Vec<Summary>>(response.as_bytes()).unwrap();
+    assert_eq!(summaries.len(), 1);
+
+    let summary = &summaries[0];
+    let order = from_json::<Order>(ORDER.as_bytes()).unwrap();
+    assert_eq!(summary.id, order.id);
+    assert_eq!(summary.client_order_id, order.client_order_id);
+    assert_eq!(summary.symbol, "AAPL");
+    assert_eq!(summary.status, order::Status::Accepted);
+    assert_eq!(summary.quantity, Some(Num::from(1)));
+    assert_eq!(summary.notional, None);
+    assert_eq!(summary.limit_price, Some(Num::from(1)));
+    assert_eq!(summary.side, order::Side::Buy);
+  }
+
+
+  /// Create an order update for the reference order.
+  fn update(
+    event: OrderStatus,
+    status: order::Status,
+    timestamp: &str,
+    symbol: &str,
+  ) -> OrderUpdate {
+    let mut order = from_json::<Order>(ORDER.as_bytes()).unwrap();
+    order.status = status;
+    order.symbol = symbol.to_string();
+    if symbol != "AAPL" {
+      order.id = order::Id(Uuid::new_v4());
+    }
+
+    OrderUpdate {
+      event,
+      order,
+      timestamp: DateTime::parse_from_rfc3339(timestamp)
+        .unwrap()
+        .with_timezone(&Utc),
+      execution_id: None,
+      price: None,
+      quantity: None,
+      position_quantity: None,
+    }
+  }
+
+  /// Check that a [`Journal`] reconstructs order lifecycles from
+  /// updates.
+  #[test]
+  fn journal_lifecycles() {
+    let new = update(
+      OrderStatus::New,
+      order::Status::New,
+      "2018-10-05T05:49:00Z",
+      "AAPL",
+    );
+    let id = new.order.id;
+    let partial = update(
+      OrderStatus::PartialFill,
+      order::Status::PartiallyFilled,
+      "2018-10-05T05:49:01Z",
+      "AAPL",
+    );
+    let fill = update(
+      OrderStatus::Filled,
+      order::Status::Filled,
+      "2018-10-05T05:49:02Z",
+      "AAPL",
+    );
+    let other = update(
+      OrderStatus::New,
+      order::Status::New,
+      "2018-10-05T05:49:00Z",
+      "SPY",
+    );
+
+    let mut journal = Journal::new();
+    assert!(journal.is_empty());
+
+    // Record the fill before the partial fill, to check that events
+    // are ordered by time and the newest state wins.
+    let () = journal.extend([new, fill, partial, other]);
+    assert_eq!(journal.len(), 2);
+
+    let lifecycle = journal.lifecycle(id).unwrap();
+    assert_eq!(lifecycle.order.status, order::Status::Filled);
+    let events = lifecycle
+      .events
+      .iter()
+      .map(|event| event.event)
+      .collect::<Vec<_>>();
+    assert_eq!(
+      events,
+      [
+        OrderStatus::New,
+        OrderStatus::PartialFill,
+        OrderStatus::Filled
+      ]
+    );
+
+    let open = journal.open_orders_by_symbol();
+    assert_eq!(open.len(), 1);
+    assert_eq!(open["SPY"].len(), 1);
+
+    let snapshot = journal.snapshot();
+    assert_eq!(snapshot.len(), 2);
+    let json = serde_json::to_value(&snapshot).unwrap();
+    assert_eq!(
+      json[0]["events"].as_array().unwrap().len(),
+      snapshot[0].events.len()
+    );
+  }
+
+
+  /// A transport replying to requests with a scripted sequence of
+  /// responses, recording the requests made.
+  #[derive(Debug, Default)]
+  struct Scripted {
+    /// The responses to reply with, in order. `None` indicates a
+    /// transport error.
+    responses: Mutex<VecDeque<Option<(StatusCode, String)>>>,
+    /// The method and path of all requests issued.
+    requests: Mutex<Vec<String>>,
+  }
+
+  impl Scripted {
+    fn new<I, B>(responses: I) -> Arc<Self>
+    where
+      I: IntoIterator<Item = Option<(StatusCode, B)>>,
+      B: Into<String>,
+    {
+      Arc::new(Self {
+        responses: Mutex::new(
+          responses
+            .into_iter()
+            .map(|response| response.map(|(status, body)| (status, body.into())))
+            .collect(),
+        ),
+        requests: Mutex::default(),
+      })
+    }
+
+    fn requests(&self) -> Vec<String> {
+      self.requests.lock().unwrap().clone()
+    }
+  }
+
+  impl Transport for Arc<Scripted> {
+    fn issue(
+      &self,
+      request: Request<Full<Bytes>>,
+    ) -> BoxFuture<'_, Result<Response<Bytes>, TransportError>> {
+      let () = self.requests.lock().unwrap().push(format!(
+        "{} {}",
+        request.method(),
+        request.uri().path()
+      ));
+
+      let response = match self.responses.lock().unwrap().pop_front().unwrap() {
+        Some((status, body)) => Ok(
+          Response::builder()
+            .status(status)
+            .body(Bytes::from(body))
+            .unwrap(),
+        ),
+        None => Err(TransportError::from("connection reset")),
+      };
+      ready(response).boxed()
+    }
+  }
+
+
+  /// Create a client using the provided transport.
+  fn client(transport: &Arc<Scripted>) -> Client {
+    mock_client(Arc::clone(transport))
+  }
+
+  /// Create a request for a single share of AAPL.
+  fn request() -> CreateReq {
+    order::CreateReqInit {
+      type_: order::Type::Limit,
+      limit_price: Some(Num::from(1)),
+      ..Default::default()
+    }
+    .init("AAPL", order::Side::Buy, order::Amount::quantity(1))
+  }
+
+
+  /// Check that client order IDs are derived deterministically from
+  /// the seed.
+  #[test]
+  fn derive_client_order_id() {
+    let id = client_order_id("strategy-1/2024-03-01/AAPL");
+    assert_eq!(id, client_order_id(b"strategy-1/2024-03-01/AAPL"));
+    assert_ne!(id, client_order_id("strategy-1/2024-03-01/MSFT"));
+    assert_eq!(id.len(), 32);
+  }
+
+  /// Check that we return an existing order when submission failed
+  /// ambiguously but the order got created.
+  #[test(tokio::test)]
+  async fn submit_idempotent_existing() {
+    let transport = Scripted::new([None, Some((StatusCode::OK, ORDER))]);
+    let client = client(&transport);
+
+    let order = submit_idempotent(&client, &request(), "seed")
+      .await
+      .unwrap();
+    assert_eq!(order.symbol, "AAPL");
+    assert_eq!(
+      transport.requests(),
+      vec!["POST /v2/orders", "GET /v2/orders:by_client_order_id"]
+    );
+  }
+
+  /// Check that we resubmit an order if it was not created.
+  #[test(tokio::test)]
+  async fn submit_idempotent_resubmit() {
+    let transport = Scripted::new([
+      Some((StatusCode::BAD_GATEWAY, "")),
+      Some((
+        StatusCode::NOT_FOUND,
+        r#"{"code":40410000,"message":"order not found"}"#,
+      )),
+      Some((StatusCode::OK, ORDER)),
+    ]);
+    let client = client(&transport);
+
+    let _order = submit_idempotent(&client, &request(), "seed")
+      .await
+      .unwrap();
+    assert_eq!(
+      transport.requests(),
+      vec![
+        "POST /v2/orders",
+        "GET /v2/orders:by_client_order_id",
+        "POST /v2/orders",
+      ]
+    );
+  }
+
+  /// Check that unambiguous errors are reported right away.
+  #[test(tokio::test)]
+  async fn submit_idempotent_rejected() {
+    let transport = Scripted::new([Some((
+      StatusCode::UNPROCESSABLE_ENTITY,
+      r#"{"code":40010001,"message":"insufficient buying power"}"#,
+    ))]);
+    let client = client(&transport);
+
+    let err = submit_idempotent(&client, &request(), "seed")
+      .await
+      .unwrap_err();
+    match err {
+      RequestError::Endpoint(CreateError::InvalidInput(..)) => (),
+      _ => panic!("Received unexpected error: {err:?}"),
+    }
+    assert_eq!(transport.requests(), vec!["POST /v2/orders"]);
+  }
+
+  /// Check that we patch the correct legs of a bracket order.
+  #[test(tokio::test)]
+  async fn patch_bracket_legs() {
+    let leg = |id, type_, limit: &str, stop: &str| {
+      ORDER
+        .replace(
+          "904837e3-3b76-47ec-b432-046db621571b\",\n    \"client",
+          &format!("{id}\",\n    \"client"),
+        )
+        .replace(r#""type": "limit""#, &format!(r#""type": "{type_}""#))
+        .replace(
+          r#""limit_price": "1""#,
+          &format!(r#""limit_price": {limit}"#),
+        )
+        .replace(r#""stop_price": null"#, &format!(r#""stop_price": {stop}"#))
+        .replace(r#""status": "accepted""#, r#""status": "held""#)
+    };
+    let take_profit = leg(
+      "4b3a2c1d-0000-4000-8000-000000000001",
+      "limit",
+      r#""2""#,
+      "null",
+    );
+    let stop_loss = leg(
+      "4b3a2c1d-0000-4000-8000-000000000002",
+      "stop_limit",
+      r#""0.5""#,
+      r#""0.6""#,
+    );
+    let parent = ORDER
+      .replace(r#""order_class": "simple""#, r#""order_class": "bracket""#)
+      .replace(
+        r#""legs": null"#,
+        &format!(r#""legs": [{take_profit},{stop_loss}]"#),
+      );
+
+    let parent_id = from_json::<Order>(parent.as_bytes()).unwrap().id;
+    let transport = Scripted::new([
+      Some((StatusCode::OK, parent.clone())),
+      Some((StatusCode::OK, take_profit)),
+      Some((StatusCode::OK, parent)),
+      Some((StatusCode::OK, stop_loss)),
+    ]);
+    let client = client(&transport);
+
+    let order = patch_take_profit(&client, parent_id, Num::from(2))
+      .await
+      .unwrap();
+    assert_eq!(order.type_, order::Type::Limit);
+
+    let order = patch_stop_loss(&client, parent_id, Num::new(6, 10), Some(Num::new(5, 10)))
+      .await
+      .unwrap();
+    assert_eq!(order.type_, order::Type::StopLimit);
+
+    assert_eq!(
+      transport.requests(),
+      vec![
+        "GET /v2/orders/904837e33b7647ecb432046db621571b",
+        "PATCH /v2/orders/4b3a2c1d000040008000000000000001",
+        "GET /v2/orders/904837e33b7647ecb432046db621571b",
+        "PATCH /v2/orders/4b3a2c1d000040008000000000000002",
+      ]
+    );
+  }
+
+  /// Check that we report a missing leg as such.
+  #[test(tokio::test)]
+  async fn patch_missing_leg() {
+    let transport = Scripted::new([Some((StatusCode::OK, ORDER))]);
+    let client = client(&transport);
+    let parent_id = from_json::<Order>(ORDER.as_bytes()).unwrap().id;
+
+    let err = patch_take_profit(&client, parent_id, Num::from(2))
+      .await
+      .unwrap_err();
+    match err {
+      PatchLegError::NoLeg(id) => assert_eq!(id, parent_id),
+      _ => panic!("Received unexpected error: {err:?}"),
+    }
+  }
+
+  /// Check that we synthesize terminal events for orders closed while
+  /// not connected, along with live updates.
+  #[test(tokio::test)]
+  async fn resume_missed_updates() {
+    let fill = ORDER
+      .replace(r#""status": "accepted""#, r#""status": "filled""#)
+      .replace(
+        r#""filled_at": null"#,
+        r#""filled_at": "2018-10-05T05:49:00Z""#,
+      );
+    let activities = r#"[{
+  "activity_type": "FILL",
+  "cum_qty": "1",
+  "id": "20181005054900000::8efc7b9a-8b2b-4000-9955-d36e7db0df74",
+  "leaves_qty": "0",
+  "price": "1",
+  "qty": "1",
+  "side": "buy",
+  "symbol": "AAPL",
+  "transaction_time": "2018-10-05T05:49:00Z",
+  "order_id": "904837e3-3b76-47ec-b432-046db621571b",
+  "type": "fill"
+}]"#;
+    let closed = ORDER
+      .replace(
+        "904837e3-3b76-47ec-b432-046db621571b",
+        "904837e3-3b76-47ec-b432-046db621571c",
+      )
+      .replace(r#""status": "accepted""#, r#""status": "canceled""#)
+      .replace(
+        r#""canceled_at": null"#,
+        r#""canceled_at": "2018-10-05T05:49:30Z""#,
+      );
+    let canceled = ORDER.replace(r#""status": "accepted""#, r#""status": "canceled""#);
+    let update = format!(
+      r#"{{"stream":"trade_updates","data":{{"event":"canceled","order":{canceled},"timestamp":"2018-10-05T05:50:00Z"}}}}"#
+    );
+
+    let (sender, receiver) = channel::<()>();
+    let transport = Scripted::new([
+      Some((StatusCode::OK, format!("[{closed}]"))),
+      Some((StatusCode::OK, activities.to_string())),
+      Some((StatusCode::OK, fill)),
+    ]);
+    let client = client_with_stream(&transport, vec![update], async {
+      let _result = receiver.await;
+    })
+    .await;
+
+    let since = DateTime::parse_from_rfc3339("2018-10-05T05:48:00Z")
+      .unwrap()
+      .into();
+    let (stream, _subscription) = resume_updates(&client, since).await.unwrap();
+    let mut updates = stream
+      .take(3)
+      .map(|result| result.unwrap())
+      .collect::<Vec<_>>()
+      .await;
+    // Synthesized and live updates may be interleaved arbitrarily.
+    let () = updates.sort_by_key(|update| update.timestamp);
+
+    assert_eq!(updates[0].event, OrderStatus::Filled);
+    assert_eq!(
+      updates[0].timestamp,
+      DateTime::parse_from_rfc3339("2018-10-05T05:49:00Z").unwrap()
+    );
+    assert_eq!(updates[1].event, OrderStatus::Canceled);
+    assert_eq!(
+      updates[1].timestamp,
+      DateTime::parse_from_rfc3339("2018-10-05T05:49:30Z").unwrap()
+    );
+    assert_eq!(updates[2].event, OrderStatus::Canceled);
+    assert_ne!(updates[2].order.id, updates[1].order.id);
+    assert_eq!(
+      transport.requests(),
+      vec![
+        "GET /v2/orders",
+        "GET /v2/account/activities",
+        "GET /v2/orders/904837e33b7647ecb432046db621571b",
+      ]
+    );
+    drop(sender);
+  }
+
+  /// Instantiate a dummy order update stream server, serving the
+  /// provided messages after authentication and until `stop` resolves,
+  /// and create a client using it along with the provided transport.
+  async fn client_with_stream<F>(
+    transport: &Arc<Scripted>,
+    messages: Vec<String>,
+    stop: F,
+  ) -> Client
+  where
+    F: Future<Output = ()> + Send + Sync + 'static,
+  {
+    let test = |mut stream: WebSocketStream| async move {
+      let _auth = stream.next().await.unwrap()?;
+      let () = stream
+        .send(Message::Text(
+          r#"{"stream":"authorization","data":{"action":"authenticate","status":"authorized"}}"#
+            .into(),
+        ))
+        .await?;
+      let _listen = stream.next().await.unwrap()?;
+      let () = stream
+        .send(Message::Text(
+          r#"{"stream":"listening","data":{"streams":["trade_updates"]}}"#.into(),
+        ))
+        .await?;
+
+      for message in messages {
+        let () = stream.send(Message::Text(message.into())).await?;
+      }
+      let () = stop.await;
+      Ok(())
+    };
+
+    let addr = mock_server(test).await;
+    let mut api_info = mock_api_info();
+    api_info.api_stream_url = Url::parse(&format!("ws://{addr}")).unwrap();
+
+    Client::builder()
+      .with_transport(Arc::clone(transport))
+      .build(api_info)
+  }
+
+  /// Check that we report the terminal state of an order, even if the
+  /// corresponding update arrives before submission completed.
+  #[test(tokio::test)]
+  async fn submit_and_await_fill() {
+    let fill = ORDER.replace(r#""status": "accepted""#, r#""status": "filled""#);
+    let update = format!(
+      r#"{{"stream":"trade_updates","data":{{"event":"fill","order":{fill},"timestamp":"2018-10-05T05:49:00Z"}}}}"#
+    );
+
+    let (sender, receiver) = channel::<()>();
+    let transport = Scripted::new([Some((StatusCode::OK, ORDER))]);
+    let client = client_with_stream(&transport, vec![update], async {
+      let _result = receiver.await;
+    })
+    .await;
+
+    let order = submit_and_await(&client, &request(), Duration::from_secs(10))
+      .await
+      .unwrap();
+    assert_eq!(order.status, order::Status::Filled);
+    assert_eq!(transport.requests(), vec!["POST /v2/orders"]);
+    drop(sender);
+  }
+
+  /// Check that we report a timeout if an order does not reach a
+  /// terminal state in time.
+  #[test(tokio::test)]
+  async fn submit_and_await_timeout() {
+    let (sender, receiver) = channel::<()>();
+    let transport = Scripted::new([Some((StatusCode::OK, ORDER)), Some((StatusCode::OK, ORDER))]);
+    let client = client_with_stream(&transport, Vec::new(), async {
+      let _result = receiver.await;
+    })
+    .await;
+
+    let duration = Duration::from_millis(50);
+    let err = submit_and_await(&client, &request(), duration)
+      .await
+      .unwrap_err();
+    match err {
+      SubmitAndAwaitError::Timeout(timeout) => assert_eq!(timeout, duration),
+      _ => panic!("Received unexpected error: {err:?}"),
+    }
+    assert_eq!(
+      transport.requests(),
+      vec![
+        "POST /v2/orders",
+        "GET /v2/orders/904837e33b7647ecb432046db621571b",
+      ]
+    );
+    drop(sender);
+  }
+
   /// Cancel an order and wait for the corresponding cancellation event
   /// to arrive.
   async fn cancel_order(client: &Client, id: order::Id) {
-    let (stream, _subscription) = client.subscribe::<updates::OrderUpdates>().await.unwrap();
+    let (stream, _subscription) = client.subscribe::<OrderUpdates>().await.unwrap();
     pin_mut!(stream);
 
     client.issue::<order::Delete>(&id).await.unwrap();
@@ -174,7 +1562,7 @@ mod tests {
       // only interested in ones belonging to the order canceled
       // earlier.
       .try_skip_while(|update| {
-        ok(update.order.id != id || !matches!(update.event, updates::OrderStatus::Canceled))
+        ok(update.order.id != id || !matches!(update.event, OrderStatus::Canceled))
       })
       .next()
       .await
@@ -238,7 +1626,7 @@ mod tests {
     let api_info = ApiInfo::from_env().unwrap();
     let client = Client::new(api_info);
 
-    let order = client.issue::<order::Create>(&request).await.unwrap();
+    let order = client.issue::<Create>(&request).await.unwrap();
     assert_eq!(order.legs.len(), 1);
 
     let request = ListReq {
@@ -289,4 +1677,36 @@ mod tests {
     assert_eq!(ibm_orders.unwrap().len(), num_ibm);
     assert_eq!(goog_orders.unwrap().len(), num_goog + 1);
   }
+
+  /// Test that orders can be filtered by side.
+  #[test(tokio::test)]
+  #[ignore]
+  async fn side_filter_orders() {
+    let api_info = ApiInfo::from_env().unwrap();
+    let client = Client::new(api_info);
+
+    let buy_order = order_aapl(&client).await.unwrap();
+    let request = ListReq {
+      symbols: vec!["AAPL".to_string()],
+      side: Some(order::Side::Sell),
+      ..Default::default()
+    };
+    let sell_orders = client.issue::<List>(&request).await;
+    let request = ListReq {
+      symbols: vec!["AAPL".to_string()],
+      side: Some(order::Side::Buy),
+      ..Default::default()
+    };
+    let buy_orders = client.issue::<List>(&request).await;
+
+    cancel_order(&client, buy_order.id).await;
+
+    let sell_orders = sell_orders.unwrap();
+    assert!(sell_orders.iter().all(|x| x.side == order::Side::Sell));
+    assert!(!sell_orders.iter().any(|x| x.id == buy_order.id));
+
+    let buy_orders = buy_orders.unwrap();
+    assert!(buy_orders.iter().all(|x| x.side == order::Side::Buy));
+    assert!(buy_orders.iter().any(|x| x.id == buy_order.id));
+  }
 }
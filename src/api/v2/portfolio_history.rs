@@ -0,0 +1,529 @@
+// Copyright (C) 2024 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+
+use chrono::DateTime;
+use chrono::NaiveDate;
+use chrono::TimeZone as _;
+use chrono::Utc;
+
+use num_decimal::Num;
+
+use serde::Deserialize;
+use serde::Serialize;
+use serde::Serializer;
+use serde_urlencoded::to_string as to_query;
+
+use crate::Str;
+
+
+/// The period of time covered by a portfolio history request.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Period {
+  /// A period spanning the given number of days.
+  Days(u32),
+  /// A period spanning the given number of weeks.
+  Weeks(u32),
+  /// A period spanning the given number of months.
+  Months(u32),
+  /// A period spanning the given number of years.
+  Years(u32),
+}
+
+impl Display for Period {
+  fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+    match self {
+      Self::Days(count) => write!(fmt, "{count}D"),
+      Self::Weeks(count) => write!(fmt, "{count}W"),
+      Self::Months(count) => write!(fmt, "{count}M"),
+      Self::Years(count) => write!(fmt, "{count}A"),
+    }
+  }
+}
+
+impl Serialize for Period {
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: Serializer,
+  {
+    serializer.collect_str(self)
+  }
+}
+
+
+/// The resolution of the data points in a portfolio history.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub enum TimeFrame {
+  /// One data point per minute.
+  #[serde(rename = "1Min")]
+  OneMinute,
+  /// One data point every five minutes.
+  #[serde(rename = "5Min")]
+  FiveMinutes,
+  /// One data point every fifteen minutes.
+  #[serde(rename = "15Min")]
+  FifteenMinutes,
+  /// One data point per hour.
+  #[serde(rename = "1H")]
+  OneHour,
+  /// One data point per day.
+  #[serde(rename = "1D")]
+  OneDay,
+}
+
+
+/// The market sessions to report on for intraday time frames.
+///
+/// Data points outside of regular trading hours, as reported for
+/// [`ExtendedHours`][Self::ExtendedHours] and
+/// [`Continuous`][Self::Continuous], are not treated specially: their
+/// [`Point::time`] is the respective UTC time, which may fall on a
+/// different calendar day than the trading session it belongs to.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub enum IntradayReporting {
+  /// Only report data points during regular market hours.
+  #[serde(rename = "market_hours")]
+  MarketHours,
+  /// Include data points during pre-market and after-hours trading.
+  #[serde(rename = "extended_hours")]
+  ExtendedHours,
+  /// Report data points around the clock.
+  #[serde(rename = "continuous")]
+  Continuous,
+}
+
+
+/// How the profit & loss figures of intraday time frames are
+/// calculated.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub enum PnlReset {
+  /// Profit & loss is calculated relative to the previous day's
+  /// close.
+  #[serde(rename = "per_day")]
+  PerDay,
+  /// Profit & loss is calculated relative to the first data point of
+  /// the requested period.
+  #[serde(rename = "no_reset")]
+  NoReset,
+}
+
+
+/// A GET request to be made to the /v2/account/portfolio/history
+/// endpoint.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct GetReq {
+  /// The period of time to cover.
+  ///
+  /// Defaults to one month if not provided.
+  #[serde(rename = "period", skip_serializing_if = "Option::is_none")]
+  pub period: Option<Period>,
+  /// The resolution of the returned data points.
+  ///
+  /// If not provided, the server picks a resolution based on the
+  /// period.
+  #[serde(rename = "timeframe", skip_serializing_if = "Option::is_none")]
+  pub timeframe: Option<TimeFrame>,
+  /// The market sessions to report on when using an intraday time
+  /// frame.
+  #[serde(rename = "intraday_reporting", skip_serializing_if = "Option::is_none")]
+  pub intraday_reporting: Option<IntradayReporting>,
+  /// How profit & loss figures are calculated for intraday time
+  /// frames.
+  #[serde(rename = "pnl_reset", skip_serializing_if = "Option::is_none")]
+  pub pnl_reset: Option<PnlReset>,
+  /// The date the period ends at.
+  ///
+  /// Defaults to the current market date if not provided.
+  #[serde(rename = "date_end", skip_serializing_if = "Option::is_none")]
+  pub date_end: Option<NaiveDate>,
+  /// Whether to include extended hours data points when using an
+  /// intraday time frame.
+  #[serde(rename = "extended_hours", skip_serializing_if = "Option::is_none")]
+  pub extended_hours: Option<bool>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// A single data point in a portfolio history.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Point {
+  /// The time the data point refers to.
+  ///
+  /// The API reports points as seconds since the Unix epoch; they are
+  /// converted to UTC without any adjustment for market sessions.
+  pub time: DateTime<Utc>,
+  /// The equity of the account at this time.
+  pub equity: Option<Num>,
+  /// The profit & loss in dollars relative to the base value.
+  pub profit_loss: Option<Num>,
+  /// The profit & loss as a fraction of the base value.
+  pub profit_loss_pct: Option<Num>,
+  /// The cash flows at this time, keyed by the type of activity
+  /// causing them (e.g., `DIV` for dividends).
+  ///
+  /// Activity types not reporting a value for this time are absent.
+  pub cashflow: BTreeMap<String, Num>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+
+/// The representation of a portfolio history as reported by the API.
+#[derive(Debug, Deserialize)]
+struct PortfolioHistoryImpl {
+  #[serde(rename = "timestamp")]
+  timestamps: Vec<i64>,
+  #[serde(rename = "equity")]
+  equity: Vec<Option<Num>>,
+  #[serde(rename = "profit_loss")]
+  profit_loss: Vec<Option<Num>>,
+  #[serde(rename = "profit_loss_pct")]
+  profit_loss_pct: Vec<Option<Num>>,
+  #[serde(rename = "base_value")]
+  base_value: Option<Num>,
+  #[serde(rename = "base_value_asof", default)]
+  base_value_asof: Option<NaiveDate>,
+  #[serde(rename = "cashflow", default)]
+  cashflow: HashMap<String, Vec<Option<Num>>>,
+  #[serde(rename = "timeframe")]
+  timeframe: TimeFrame,
+}
+
+impl TryFrom<PortfolioHistoryImpl> for PortfolioHistory {
+  type Error = String;
+
+  fn try_from(other: PortfolioHistoryImpl) -> Result<Self, Self::Error> {
+    let PortfolioHistoryImpl {
+      timestamps,
+      equity,
+      profit_loss,
+      profit_loss_pct,
+      base_value,
+      base_value_asof,
+      mut cashflow,
+      timeframe,
+    } = other;
+
+    let count = timestamps.len();
+    if equity.len() != count || profit_loss.len() != count || profit_loss_pct.len() != count {
+      return Err(format!(
+        "portfolio history series are of different lengths ({}, {}, {}, {})",
+        count,
+        equity.len(),
+        profit_loss.len(),
+        profit_loss_pct.len(),
+      ))
+    }
+
+    if let Some((kind, values)) = cashflow.iter().find(|(_, values)| values.len() != count) {
+      return Err(format!(
+        "portfolio history cash flow series {kind} is of different length ({count}, {})",
+        values.len(),
+      ))
+    }
+
+    // Reverse the cash flow series, so that we can cheaply pop values
+    // off their ends in chronological order.
+    let () = cashflow.values_mut().for_each(|values| values.reverse());
+
+    let points = timestamps
+      .into_iter()
+      .zip(equity)
+      .zip(profit_loss)
+      .zip(profit_loss_pct)
+      .map(|(((timestamp, equity), profit_loss), profit_loss_pct)| {
+        let time = Utc
+          .timestamp_opt(timestamp, 0)
+          .single()
+          .ok_or_else(|| format!("encountered invalid timestamp: {timestamp}"))?;
+
+        let cashflow = cashflow
+          .iter_mut()
+          .filter_map(|(kind, values)| Some((kind.clone(), values.pop().flatten()?)))
+          .collect();
+
+        Ok(Point {
+          time,
+          equity,
+          profit_loss,
+          profit_loss_pct,
+          cashflow,
+          _non_exhaustive: (),
+        })
+      })
+      .collect::<Result<_, String>>()?;
+
+    Ok(Self {
+      points,
+      base_value,
+      base_value_asof,
+      timeframe,
+      _non_exhaustive: (),
+    })
+  }
+}
+
+
+/// The history of the account's equity and profit & loss over a
+/// period of time.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(try_from = "PortfolioHistoryImpl")]
+pub struct PortfolioHistory {
+  /// The data points making up the history, in chronological order.
+  pub points: Vec<Point>,
+  /// The equity value the profit & loss figures are calculated
+  /// against.
+  pub base_value: Option<Num>,
+  /// The date the base value refers to.
+  pub base_value_asof: Option<NaiveDate>,
+  /// The resolution of the data points.
+  pub timeframe: TimeFrame,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+
+Endpoint! {
+  /// The representation of a GET request to the
+  /// /v2/account/portfolio/history endpoint.
+  pub Get(GetReq),
+  Ok => PortfolioHistory, [
+    /// The portfolio history was retrieved successfully.
+    /* 200 */ OK,
+  ],
+  Err => GetError, [
+    /// Some of the provided data was invalid.
+    /* 400 */ BAD_REQUEST => InvalidInput,
+  ]
+
+  #[inline]
+  fn path(_input: &Self::Input) -> Str {
+    "/v2/account/portfolio/history".into()
+  }
+
+  fn query(input: &Self::Input) -> Result<Option<Str>, Self::ConversionError> {
+    Ok(Some(to_query(input)?.into()))
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use serde_json::from_str as from_json;
+
+  use test_log::test;
+
+  use crate::api_info::ApiInfo;
+  use crate::Client;
+  use crate::RequestError;
+
+
+  /// Check that the request parameters are serialized as expected.
+  #[test]
+  fn serialize_request() {
+    let request = GetReq::default();
+    assert_eq!(to_query(request).unwrap(), "");
+
+    let request = GetReq {
+      period: Some(Period::Weeks(2)),
+      timeframe: Some(TimeFrame::FifteenMinutes),
+      intraday_reporting: Some(IntradayReporting::ExtendedHours),
+      pnl_reset: Some(PnlReset::NoReset),
+      date_end: Some(NaiveDate::from_ymd_opt(2024, 3, 1).unwrap()),
+      extended_hours: Some(true),
+      ..Default::default()
+    };
+    let expected = "period=2W&timeframe=15Min&intraday_reporting=extended_hours&\
+                    pnl_reset=no_reset&date_end=2024-03-01&extended_hours=true";
+    assert_eq!(to_query(request).unwrap(), expected);
+  }
+
+  /// Verify that we can parse a reference portfolio history response.
+  #[test]
+  fn parse_reference_history() {
+    let response = r#"{
+  "timestamp": [1580826600, 1580913000, 1580999400],
+  "equity": [27423.73, 28137.24, null],
+  "profit_loss": [11.8, 713.51, null],
+  "profit_loss_pct": [0.000430469507254688, 0.026018329084162, null],
+  "base_value": 27411.93,
+  "timeframe": "1D"
+}"#;
+
+    let history = from_json::<PortfolioHistory>(response).unwrap();
+    assert_eq!(history.timeframe, TimeFrame::OneDay);
+    assert_eq!(history.base_value, Some(Num::new(2741193, 100)));
+    assert_eq!(history.points.len(), 3);
+
+    let point = &history.points[1];
+    assert_eq!(
+      point.time,
+      DateTime::parse_from_rfc3339("2020-02-05T14:30:00Z").unwrap()
+    );
+    assert_eq!(point.equity, Some(Num::new(2813724, 100)));
+    assert_eq!(point.profit_loss, Some(Num::new(71351, 100)));
+
+    let point = &history.points[2];
+    assert_eq!(point.equity, None);
+    assert_eq!(point.profit_loss, None);
+    assert_eq!(point.profit_loss_pct, None);
+    assert!(point.cashflow.is_empty());
+  }
+
+  /// Check that we can parse the cash flows and base value date of a
+  /// portfolio history.
+  #[test]
+  fn parse_history_with_cashflow() {
+    let response = r#"{
+  "timestamp": [1580826600, 1580913000, 1580999400],
+  "equity": [27423.73, 28137.24, 28200.00],
+  "profit_loss": [11.8, 713.51, 776.27],
+  "profit_loss_pct": [0.000430469507254688, 0.026018329084162, 0.02832],
+  "base_value": 27411.93,
+  "base_value_asof": "2020-02-03",
+  "cashflow": {
+    "DIV": [0, 12.5, null],
+    "FEE": [-1.25, 0, 0]
+  },
+  "timeframe": "1D"
+}"#;
+
+    let history = from_json::<PortfolioHistory>(response).unwrap();
+    assert_eq!(
+      history.base_value_asof,
+      Some(NaiveDate::from_ymd_opt(2020, 2, 3).unwrap())
+    );
+
+    let cashflow = &history.points[0].cashflow;
+    assert_eq!(cashflow.len(), 2);
+    assert_eq!(cashflow["DIV"], Num::from(0));
+    assert_eq!(cashflow["FEE"], Num::new(-125, 100));
+
+    let cashflow = &history.points[1].cashflow;
+    assert_eq!(cashflow["DIV"], Num::new(125, 10));
+
+    let cashflow = &history.points[2].cashflow;
+    assert_eq!(cashflow.keys().collect::<Vec<_>>(), vec!["FEE"]);
+
+    let response = r#"{
+  "timestamp": [1580826600, 1580913000],
+  "equity": [27423.73, 28137.24],
+  "profit_loss": [11.8, 713.51],
+  "profit_loss_pct": [0.000430469507254688, 0.026018329084162],
+  "base_value": 27411.93,
+  "cashflow": {"DIV": [0]},
+  "timeframe": "1D"
+}"#;
+
+    let err = from_json::<PortfolioHistory>(response).unwrap_err();
+    assert!(err.to_string().contains("different length"), "{err}");
+  }
+
+  /// Check that we can request and parse a continuously reported
+  /// intraday portfolio history.
+  #[test]
+  fn parse_continuous_history() {
+    let request = GetReq {
+      timeframe: Some(TimeFrame::OneHour),
+      intraday_reporting: Some(IntradayReporting::Continuous),
+      ..Default::default()
+    };
+    assert_eq!(
+      to_query(request).unwrap(),
+      "timeframe=1H&intraday_reporting=continuous"
+    );
+
+    // The data points lie at 23:00 and 00:00 UTC, well outside of
+    // regular trading hours.
+    let response = r#"{
+  "timestamp": [1580943600, 1580947200],
+  "equity": [28137.24, 28140.01],
+  "profit_loss": [713.51, 716.28],
+  "profit_loss_pct": [0.026018329084162, 0.026119],
+  "base_value": 27411.93,
+  "timeframe": "1H"
+}"#;
+
+    let history = from_json::<PortfolioHistory>(response).unwrap();
+    assert_eq!(history.timeframe, TimeFrame::OneHour);
+    assert_eq!(
+      history.points[0].time,
+      DateTime::parse_from_rfc3339("2020-02-05T23:00:00Z").unwrap()
+    );
+    assert_eq!(
+      history.points[1].time,
+      DateTime::parse_from_rfc3339("2020-02-06T00:00:00Z").unwrap()
+    );
+  }
+
+  /// Check that we reject a portfolio history with series of
+  /// mismatching lengths.
+  #[test]
+  fn parse_mismatching_history() {
+    let response = r#"{
+  "timestamp": [1580826600, 1580913000],
+  "equity": [27423.73],
+  "profit_loss": [11.8, 713.51],
+  "profit_loss_pct": [0.000430469507254688, 0.026018329084162],
+  "base_value": 27411.93,
+  "timeframe": "1D"
+}"#;
+
+    let err = from_json::<PortfolioHistory>(response).unwrap_err();
+    assert!(err.to_string().contains("different lengths"), "{err}");
+  }
+
+  /// Check that we can retrieve the portfolio history of the account.
+  #[test(tokio::test)]
+  async fn retrieve_portfolio_history() {
+    let api_info = ApiInfo::from_env().unwrap();
+    let client = Client::new(api_info);
+    let request = GetReq {
+      period: Some(Period::Weeks(1)),
+      timeframe: Some(TimeFrame::OneHour),
+      ..Default::default()
+    };
+    let history = client.issue::<Get>(&request).await.unwrap();
+
+    assert_eq!(history.timeframe, TimeFrame::OneHour);
+    assert!(history
+      .points
+      .windows(2)
+      .all(|window| window[0].time < window[1].time));
+  }
+
+  /// Check that we report an invalid request as such.
+  #[test(tokio::test)]
+  async fn retrieve_with_invalid_date() {
+    let api_info = ApiInfo::from_env().unwrap();
+    let client = Client::new(api_info);
+    let request = GetReq {
+      date_end: Some(NaiveDate::from_ymd_opt(1900, 1, 1).unwrap()),
+      period: Some(Period::Days(0)),
+      ..Default::default()
+    };
+    let result = client.issue::<Get>(&request).await;
+
+    let err = result.unwrap_err();
+    match err {
+      RequestError::Endpoint(GetError::InvalidInput(_)) => (),
+      e => panic!("received unexpected error: {e:?}"),
+    }
+  }
+}
@@ -40,6 +40,47 @@ impl Not for Side {
 }
 
 
+/// Position values denominated in USD, as reported for accounts
+/// trading in a currency other than USD.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct UsdValues {
+  /// The average entry price of the position, in USD.
+  #[serde(rename = "avg_entry_price")]
+  pub average_entry_price: Option<Num>,
+  /// The total dollar amount of the position, in USD.
+  #[serde(rename = "market_value")]
+  pub market_value: Option<Num>,
+  /// The total cost basis, in USD.
+  #[serde(rename = "cost_basis")]
+  pub cost_basis: Option<Num>,
+  /// The total unrealized profit/loss, in USD.
+  #[serde(rename = "unrealized_pl")]
+  pub unrealized_gain_total: Option<Num>,
+  /// The total unrealized profit/loss percent (as a factor of 1).
+  #[serde(rename = "unrealized_plpc")]
+  pub unrealized_gain_total_percent: Option<Num>,
+  /// The unrealized profit/loss for the day, in USD.
+  #[serde(rename = "unrealized_intraday_pl")]
+  pub unrealized_gain_today: Option<Num>,
+  /// The unrealized profit/loss percent for the day (as a factor of 1).
+  #[serde(rename = "unrealized_intraday_plpc")]
+  pub unrealized_gain_today_percent: Option<Num>,
+  /// The current asset price per share, in USD.
+  #[serde(rename = "current_price")]
+  pub current_price: Option<Num>,
+  /// The last day's asset price per share, in USD.
+  #[serde(rename = "lastday_price")]
+  pub last_day_price: Option<Num>,
+  /// The percent change from last day price (as a factor of 1).
+  #[serde(rename = "change_today")]
+  pub change_today: Option<Num>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
 /// A single position as returned by the /v2/positions endpoint on a GET
 /// request.
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -56,9 +97,16 @@ pub struct Position {
   /// The position's asset class.
   #[serde(rename = "asset_class")]
   pub asset_class: asset::Class,
+  /// Whether the position's asset is marginable.
+  #[serde(rename = "asset_marginable")]
+  pub asset_marginable: Option<bool>,
   /// The average entry price of the position.
   #[serde(rename = "avg_entry_price")]
   pub average_entry_price: Num,
+  /// The average exchange rate at which the position was entered, for
+  /// accounts trading in a currency other than USD.
+  #[serde(rename = "avg_entry_swap_rate")]
+  pub average_entry_swap_rate: Option<Num>,
   /// The number of shares.
   #[serde(rename = "qty", deserialize_with = "abs_num_from_str")]
   pub quantity: Num,
@@ -96,12 +144,111 @@ pub struct Position {
   /// The percent change from last day price (as a factor of 1).
   #[serde(rename = "change_today")]
   pub change_today: Option<Num>,
+  /// The position's values denominated in USD, for accounts trading in
+  /// a currency other than USD.
+  #[serde(rename = "usd")]
+  pub usd: Option<UsdValues>,
   /// The type is non-exhaustive and open to extension.
   #[doc(hidden)]
   #[serde(skip)]
   pub _non_exhaustive: (),
 }
 
+impl Position {
+  /// Check whether the position is a long one.
+  #[inline]
+  pub fn is_long(&self) -> bool {
+    self.side == Side::Long
+  }
+
+  /// Check whether the position is a short one.
+  #[inline]
+  pub fn is_short(&self) -> bool {
+    self.side == Side::Short
+  }
+
+  /// Retrieve the number of shares that can currently be sold.
+  ///
+  /// Shares covered by open orders, e.g., a resting stop loss, are not
+  /// available. Short positions have no shares to sell.
+  pub fn sellable_quantity(&self) -> Num {
+    if self.is_long() && !self.quantity_available.is_negative() {
+      self.quantity_available.clone()
+    } else {
+      Num::default()
+    }
+  }
+
+  /// Retrieve the number of shares, negative for short positions.
+  pub fn signed_quantity(&self) -> Num {
+    match self.side {
+      Side::Long => self.quantity.clone(),
+      Side::Short => -&self.quantity,
+    }
+  }
+
+  /// Retrieve the market value of the position, negative for short
+  /// positions.
+  ///
+  /// The value reported by Alpaca is used if available. Otherwise it
+  /// is computed from the current price, if that is known.
+  pub fn current_market_value(&self) -> Option<Num> {
+    self.market_value.clone().or_else(|| {
+      self
+        .current_price
+        .as_ref()
+        .map(|price| self.signed_quantity() * price)
+    })
+  }
+
+  /// Retrieve the total unrealized profit/loss of the position.
+  ///
+  /// The value reported by Alpaca is used if available. Otherwise it
+  /// is computed as the difference between the market value and the
+  /// cost basis.
+  pub fn unrealized_gain(&self) -> Option<Num> {
+    self.unrealized_gain_total.clone().or_else(|| {
+      self
+        .current_market_value()
+        .map(|value| value - &self.cost_basis)
+    })
+  }
+
+  /// Retrieve the total unrealized profit/loss percent of the position
+  /// (as a factor of 1).
+  ///
+  /// The value reported by Alpaca is used if available. Otherwise it
+  /// is computed relative to the cost basis. `None` is returned if the
+  /// cost basis is zero.
+  pub fn unrealized_gain_percent(&self) -> Option<Num> {
+    self.unrealized_gain_total_percent.clone().or_else(|| {
+      let cost_basis = if self.cost_basis.is_negative() {
+        -&self.cost_basis
+      } else {
+        self.cost_basis.clone()
+      };
+      if cost_basis.is_zero() {
+        None
+      } else {
+        self.unrealized_gain().map(|gain| gain / cost_basis)
+      }
+    })
+  }
+
+  /// Retrieve the unrealized profit/loss of the position for the day.
+  ///
+  /// The value reported by Alpaca is used if available. Otherwise it
+  /// is computed from the current and the last day's price, if both
+  /// are known.
+  pub fn unrealized_gain_intraday(&self) -> Option<Num> {
+    self.unrealized_gain_today.clone().or_else(|| {
+      let current = self.current_price.as_ref()?;
+      let last_day = self.last_day_price.as_ref()?;
+      Some(self.signed_quantity() * (current - last_day))
+    })
+  }
+}
+
 
 Endpoint! {
   /// The representation of a GET request to the /v2/positions/{symbol}
@@ -213,6 +360,40 @@ mod tests {
     assert_eq!(pos.change_today, Some(Num::new(84, 10000)));
   }
 
+  /// Check that position values are computed properly when they are
+  /// not reported.
+  #[test]
+  fn compute_position_values() {
+    let json = r#"{
+    "asset_id": "904837e3-3b76-47ec-b432-046db621571b",
+    "symbol": "AAPL",
+    "exchange": "NASDAQ",
+    "asset_class": "us_equity",
+    "avg_entry_price": "100.0",
+    "qty": "-5",
+    "qty_available": "-5",
+    "side": "short",
+    "cost_basis": "-500.0",
+    "current_price": "90.0",
+    "lastday_price": "92.0"
+}"#;
+
+    let pos = from_json::<Position>(json).unwrap();
+    assert!(pos.is_short());
+    assert!(!pos.is_long());
+    assert_eq!(pos.signed_quantity(), Num::from(-5));
+    assert_eq!(pos.current_market_value(), Some(Num::from(-450)));
+    assert_eq!(pos.unrealized_gain(), Some(Num::from(50)));
+    assert_eq!(pos.unrealized_gain_percent(), Some(Num::new(1, 10)));
+    assert_eq!(pos.unrealized_gain_intraday(), Some(Num::from(10)));
+
+    let mut pos = pos;
+    pos.current_price = None;
+    assert_eq!(pos.current_market_value(), None);
+    assert_eq!(pos.unrealized_gain(), None);
+    assert_eq!(pos.unrealized_gain_intraday(), None);
+  }
+
   /// Check that we can parse a position with a fractional quantity.
   #[test]
   fn parse_fractional_position() {
@@ -255,6 +436,53 @@ mod tests {
     assert_eq!(pos.change_today, Some(Num::new(84, 10000)));
   }
 
+  /// Check that we can parse a position of a non-USD account.
+  #[test]
+  fn parse_non_usd_position() {
+    let response = r#"{
+    "asset_id": "904837e3-3b76-47ec-b432-046db621571b",
+    "symbol": "AAPL",
+    "exchange": "NASDAQ",
+    "asset_class": "us_equity",
+    "asset_marginable": true,
+    "avg_entry_price": "150.0",
+    "avg_entry_swap_rate": "1.5",
+    "qty": "2",
+    "qty_available": "2",
+    "side": "long",
+    "market_value": "300.0",
+    "cost_basis": "300.0",
+    "unrealized_pl": "0",
+    "unrealized_plpc": "0",
+    "unrealized_intraday_pl": "0",
+    "unrealized_intraday_plpc": "0",
+    "current_price": "150.0",
+    "lastday_price": "150.0",
+    "change_today": "0",
+    "usd": {
+      "avg_entry_price": "100.0",
+      "market_value": "200.0",
+      "cost_basis": "200.0",
+      "unrealized_pl": "0",
+      "unrealized_plpc": "0",
+      "unrealized_intraday_pl": "0",
+      "unrealized_intraday_plpc": "0",
+      "current_price": "100.0",
+      "lastday_price": "100.0",
+      "change_today": "0"
+    }
+}"#;
+
+    let pos = from_json::<Position>(response).unwrap();
+    assert_eq!(pos.asset_marginable, Some(true));
+    assert_eq!(pos.average_entry_swap_rate, Some(Num::new(3, 2)));
+
+    let usd = pos.usd.unwrap();
+    assert_eq!(usd.average_entry_price, Some(Num::from(100)));
+    assert_eq!(usd.market_value, Some(Num::from(200)));
+    assert_eq!(usd.current_price, Some(Num::from(100)));
+  }
+
   /// Check that we can parse a short position.
   #[test]
   fn parse_short_position() {
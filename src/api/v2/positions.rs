@@ -1,7 +1,21 @@
 // Copyright (C) 2019-2024 The apca Developers
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use num_decimal::Num;
+
+use uuid::Uuid;
+
+use crate::api::v2::asset;
+use crate::api::v2::order;
+use crate::api::v2::position;
 use crate::api::v2::position::Position;
+use crate::api::v2::updates::OrderStatus;
+use crate::api::v2::updates::OrderUpdate;
+use crate::Client;
+use crate::RequestError;
 use crate::Str;
 
 
@@ -21,6 +35,313 @@ Endpoint! {
 }
 
 
+/// Retrieve the open positions in the given symbols.
+///
+/// All open positions are listed and subsequently filtered on the
+/// client side, meaning that only a single request is issued. Symbols
+/// for which no position exists are silently ignored. Positions are
+/// reported in the order in which they are listed by Alpaca.
+pub async fn get_by_symbols<S>(
+  client: &Client,
+  symbols: &[S],
+) -> Result<Vec<Position>, RequestError<ListError>>
+where
+  S: AsRef<str>,
+{
+  let positions = client.issue::<List>(&()).await?;
+  let positions = positions
+    .into_iter()
+    .filter(|position| {
+      symbols
+        .iter()
+        .any(|symbol| symbol.as_ref() == position.symbol)
+    })
+    .collect();
+  Ok(positions)
+}
+
+
+/// Check whether the given quantity of shares held in the provided
+/// symbol can be sold.
+///
+/// Shares tied up in open orders are not considered available, which
+/// is what the server would otherwise reject an exit order over with
+/// an "insufficient qty available" error. Without a long position in
+/// the symbol nothing can be sold; for opening a short position see
+/// [`Asset::can_short`][crate::api::v2::asset::Asset::can_short]
+/// instead.
+pub async fn can_sell(
+  client: &Client,
+  symbol: &asset::Symbol,
+  quantity: &Num,
+) -> Result<bool, RequestError<position::GetError>> {
+  match client.issue::<position::Get>(symbol).await {
+    Ok(position) => Ok(&position.sellable_quantity() >= quantity),
+    Err(RequestError::Endpoint(position::GetError::NotFound(_))) => Ok(false),
+    Err(err) => Err(err),
+  }
+}
+
+
+/// An aggregate view of a set of positions, as produced by
+/// [`summary`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Summary {
+  /// The combined market value of all long positions.
+  pub long_exposure: Num,
+  /// The combined absolute market value of all short positions.
+  pub short_exposure: Num,
+  /// The sum of long and short exposure.
+  pub gross_exposure: Num,
+  /// The long exposure minus the short exposure.
+  pub net_exposure: Num,
+  /// The total unrealized profit/loss.
+  pub unrealized_gain: Num,
+  /// The total unrealized profit/loss for the day.
+  pub unrealized_gain_intraday: Num,
+  /// The number of positions for which no market value could be
+  /// determined and that are hence not reflected in the summary.
+  pub unpriced: usize,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+
+/// Aggregate the provided positions into a [`Summary`].
+///
+/// Positions for which no market value can be determined, e.g.,
+/// because no current price is known, are skipped and counted in
+/// [`Summary::unpriced`].
+pub fn summary(positions: &[Position]) -> Summary {
+  positions
+    .iter()
+    .fold(Summary::default(), |mut summary, position| {
+      if let Some(value) = position.current_market_value() {
+        if position.is_long() {
+          summary.long_exposure += &value;
+        } else {
+          summary.short_exposure += if value.is_negative() { -value } else { value };
+        }
+        summary.unrealized_gain += position.unrealized_gain().unwrap_or_default();
+        summary.unrealized_gain_intraday += position.unrealized_gain_intraday().unwrap_or_default();
+      } else {
+        summary.unpriced += 1;
+      }
+      summary
+    })
+    .finish()
+}
+
+impl Summary {
+  /// Derive the gross and net exposure from the long and short one.
+  fn finish(mut self) -> Self {
+    self.gross_exposure = &self.long_exposure + &self.short_exposure;
+    self.net_exposure = &self.long_exposure - &self.short_exposure;
+    self
+  }
+}
+
+
+/// A position as maintained by a [`Tracker`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TrackedPosition {
+  /// The symbol of the asset the position is in.
+  pub symbol: String,
+  /// The size of the position, negative for short positions.
+  pub quantity: Num,
+  /// The average price at which the position was entered.
+  pub average_entry_price: Num,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+impl TrackedPosition {
+  /// Apply a fill of the given signed quantity at the provided price.
+  fn fill(&mut self, quantity: &Num, price: &Num) {
+    let current = abs(&self.quantity);
+    let filled = abs(quantity);
+    let extends = self.quantity.is_zero() || self.quantity.is_negative() == quantity.is_negative();
+    let remaining = &self.quantity + quantity;
+
+    if extends {
+      self.average_entry_price =
+        (&self.average_entry_price * &current + price * &filled) / (current + filled);
+    } else if !remaining.is_zero() && remaining.is_negative() != self.quantity.is_negative() {
+      // The fill closed the position and opened one on the other side.
+      self.average_entry_price = price.clone();
+    }
+    self.quantity = remaining;
+  }
+}
+
+impl From<&Position> for TrackedPosition {
+  fn from(position: &Position) -> Self {
+    Self {
+      symbol: position.symbol.clone(),
+      quantity: if position.is_long() {
+        position.quantity.clone()
+      } else {
+        -&position.quantity
+      },
+      average_entry_price: position.average_entry_price.clone(),
+      _non_exhaustive: (),
+    }
+  }
+}
+
+
+/// Retrieve the absolute value of a number.
+fn abs(num: &Num) -> Num {
+  if num.is_negative() {
+    -num
+  } else {
+    num.clone()
+  }
+}
+
+
+/// A locally maintained, live view of the account's positions.
+///
+/// A tracker is seeded with the positions reported by the [`List`]
+/// endpoint and kept up-to-date by applying the fills reported through
+/// the [`OrderUpdates`][crate::api::v2::updates::OrderUpdates] stream.
+/// Because fills can be missed, e.g., while the stream is reconnecting,
+/// the tracker should periodically be [reconciled][Tracker::reconcile]
+/// with the positions reported by Alpaca.
+#[derive(Clone, Debug, Default)]
+pub struct Tracker {
+  /// The tracked positions, keyed on their symbols.
+  positions: HashMap<String, TrackedPosition>,
+  /// The IDs of all executions applied, for detecting duplicate
+  /// updates.
+  executions: HashSet<Uuid>,
+}
+
+impl Tracker {
+  /// Create a tracker seeded with the provided positions.
+  pub fn from_positions(positions: &[Position]) -> Self {
+    Self {
+      positions: Self::index(positions),
+      executions: HashSet::new(),
+    }
+  }
+
+  /// Create a tracker seeded with the positions currently held by the
+  /// account.
+  pub async fn sync(client: &Client) -> Result<Self, RequestError<ListError>> {
+    let positions = client.issue::<List>(&()).await?;
+    Ok(Self::from_positions(&positions))
+  }
+
+  /// Index the provided positions by their symbols.
+  fn index(positions: &[Position]) -> HashMap<String, TrackedPosition> {
+    positions
+      .iter()
+      .map(|position| (position.symbol.clone(), TrackedPosition::from(position)))
+      .collect()
+  }
+
+  /// Apply an order update to the tracked positions.
+  ///
+  /// Only fill and partial fill events affect positions; all other
+  /// updates are ignored, as are fills that were applied already. The
+  /// return value indicates whether the update changed any position.
+  pub fn apply(&mut self, update: &OrderUpdate) -> bool {
+    if !matches!(update.event, OrderStatus::Filled | OrderStatus::PartialFill) {
+      return false
+    }
+
+    let (price, quantity) = match (&update.price, &update.quantity) {
+      (Some(price), Some(quantity)) => (price, quantity),
+      _ => return false,
+    };
+
+    if let Some(execution_id) = update.execution_id {
+      if !self.executions.insert(execution_id) {
+        return false
+      }
+    }
+
+    let quantity = match update.order.side {
+      order::Side::Buy => quantity.clone(),
+      order::Side::Sell => -quantity,
+    };
+
+    let symbol = &update.order.symbol;
+    let position = self
+      .positions
+      .entry(symbol.clone())
+      .or_insert_with(|| TrackedPosition {
+        symbol: symbol.clone(),
+        quantity: Num::default(),
+        average_entry_price: Num::default(),
+        _non_exhaustive: (),
+      });
+    let () = position.fill(&quantity, price);
+
+    if position.quantity.is_zero() {
+      let _removed = self.positions.remove(symbol);
+    }
+    true
+  }
+
+  /// Replace the tracked positions with the ones currently held by the
+  /// account.
+  ///
+  /// Reported are the symbols of all positions that were found to
+  /// deviate from the local view, in no particular order.
+  pub async fn reconcile(
+    &mut self,
+    client: &Client,
+  ) -> Result<Vec<String>, RequestError<ListError>> {
+    let positions = client.issue::<List>(&()).await?;
+    let positions = Self::index(&positions);
+
+    let mut deviating = positions
+      .iter()
+      .filter(|(symbol, position)| self.positions.get(*symbol) != Some(position))
+      .map(|(symbol, _)| symbol.clone())
+      .collect::<Vec<_>>();
+    let () = deviating.extend(
+      self
+        .positions
+        .keys()
+        .filter(|symbol| !positions.contains_key(*symbol))
+        .cloned(),
+    );
+
+    self.positions = positions;
+    Ok(deviating)
+  }
+
+  /// Retrieve the tracked position in the given symbol, if any.
+  #[inline]
+  pub fn get(&self, symbol: &str) -> Option<&TrackedPosition> {
+    self.positions.get(symbol)
+  }
+
+  /// Retrieve all tracked positions, in no particular order.
+  #[inline]
+  pub fn positions(&self) -> impl Iterator<Item = &TrackedPosition> {
+    self.positions.values()
+  }
+
+  /// Retrieve the number of tracked positions.
+  #[inline]
+  pub fn len(&self) -> usize {
+    self.positions.len()
+  }
+
+  /// Check whether no positions are being tracked.
+  #[inline]
+  pub fn is_empty(&self) -> bool {
+    self.positions.is_empty()
+  }
+}
+
+
 // TODO: There is the possibility to issue a DELETE against the
 //       /v2/positions endpoint in order to liquidate all open
 //       positions, which may be interesting to use. However, that
@@ -31,11 +352,222 @@ Endpoint! {
 mod tests {
   use super::*;
 
+  use chrono::Utc;
+
+  use http::StatusCode;
+
+  use serde_json::from_str as from_json;
+
   use test_log::test;
 
+  use crate::api::v2::order::Order;
   use crate::api_info::ApiInfo;
+  use crate::transport::test::mock_client;
   use crate::Client;
+  use crate::MockTransport;
+
+
+  /// A reference position in AAPL.
+  const POSITION: &str = r#"[{
+    "asset_id": "904837e3-3b76-47ec-b432-046db621571b",
+    "symbol": "AAPL",
+    "exchange": "NASDAQ",
+    "asset_class": "us_equity",
+    "avg_entry_price": "100",
+    "qty": "10",
+    "qty_available": "10",
+    "side": "long",
+    "cost_basis": "1000"
+  }]"#;
+
+  /// A reference order in AAPL.
+  const ORDER: &str = r#"{
+    "id": "904837e3-3b76-47ec-b432-046db621571b",
+    "client_order_id": "e5bd74f5b2e6f217a2e2aeee0e4e1b7b",
+    "created_at": "2018-10-05T05:48:59Z",
+    "updated_at": null,
+    "submitted_at": null,
+    "filled_at": null,
+    "expired_at": null,
+    "canceled_at": null,
+    "failed_at": null,
+    "replaced_at": null,
+    "replaced_by": null,
+    "replaces": null,
+    "asset_id": "904837e3-3b76-47ec-b432-046db621571b",
+    "symbol": "AAPL",
+    "asset_class": "us_equity",
+    "qty": "10",
+    "filled_qty": "0",
+    "type": "market",
+    "order_class": "simple",
+    "side": "buy",
+    "time_in_force": "day",
+    "limit_price": null,
+    "stop_price": null,
+    "hwm": null,
+    "filled_avg_price": null,
+    "status": "filled",
+    "extended_hours": false,
+    "legs": null
+  }"#;
+
+
+  /// Create a fill update for the reference order.
+  fn fill(side: order::Side, quantity: i32, price: i32) -> OrderUpdate {
+    let mut order = from_json::<Order>(ORDER).unwrap();
+    order.side = side;
+
+    OrderUpdate {
+      event: OrderStatus::Filled,
+      order,
+      timestamp: Utc::now(),
+      execution_id: Some(Uuid::new_v4()),
+      price: Some(Num::from(price)),
+      quantity: Some(Num::from(quantity)),
+      position_quantity: None,
+    }
+  }
+
+  /// Check that a [`Tracker`] applies fills to the tracked positions.
+  #[test]
+  fn track_fills() {
+    let positions = from_json::<Vec<Position>>(POSITION).unwrap();
+    let mut tracker = Tracker::from_positions(&positions);
+
+    let update = fill(order::Side::Buy, 10, 110);
+    assert!(tracker.apply(&update));
+    // Duplicate updates are ignored.
+    assert!(!tracker.apply(&update));
 
+    let position = tracker.get("AAPL").unwrap();
+    assert_eq!(position.quantity, Num::from(20));
+    assert_eq!(position.average_entry_price, Num::from(105));
+
+    // Reducing a position does not change the entry price.
+    assert!(tracker.apply(&fill(order::Side::Sell, 5, 120)));
+    let position = tracker.get("AAPL").unwrap();
+    assert_eq!(position.quantity, Num::from(15));
+    assert_eq!(position.average_entry_price, Num::from(105));
+
+    // Flipping the position enters it at the fill price.
+    assert!(tracker.apply(&fill(order::Side::Sell, 20, 90)));
+    let position = tracker.get("AAPL").unwrap();
+    assert_eq!(position.quantity, Num::from(-5));
+    assert_eq!(position.average_entry_price, Num::from(90));
+
+    assert!(tracker.apply(&fill(order::Side::Buy, 5, 80)));
+    assert!(tracker.is_empty());
+
+    let mut update = fill(order::Side::Buy, 1, 1);
+    update.event = OrderStatus::New;
+    assert!(!tracker.apply(&update));
+  }
+
+  /// Check that we can reconcile a [`Tracker`] with the positions
+  /// reported by Alpaca.
+  #[test(tokio::test)]
+  async fn reconcile_tracker() {
+    let mock = MockTransport::new();
+    let () = mock.respond::<List>(&(), StatusCode::OK, POSITION);
+    let client = mock_client(mock);
+
+    let mut tracker = Tracker::sync(&client).await.unwrap();
+    assert_eq!(tracker.len(), 1);
+    assert!(tracker.reconcile(&client).await.unwrap().is_empty());
+
+    assert!(tracker.apply(&fill(order::Side::Sell, 10, 100)));
+    assert!(tracker.get("AAPL").is_none());
+
+    let deviating = tracker.reconcile(&client).await.unwrap();
+    assert_eq!(deviating, ["AAPL"]);
+    assert_eq!(tracker.get("AAPL").unwrap().quantity, Num::from(10));
+  }
+
+
+  /// Check that [`can_sell`] takes into account shares covered by
+  /// open orders.
+  #[test(tokio::test)]
+  async fn check_sellable() {
+    let mock = MockTransport::new();
+    let position = r#"{
+    "asset_id": "904837e3-3b76-47ec-b432-046db621571b",
+    "symbol": "AAPL",
+    "exchange": "NASDAQ",
+    "asset_class": "us_equity",
+    "avg_entry_price": "100",
+    "qty": "10",
+    "qty_available": "4",
+    "side": "long",
+    "cost_basis": "1000"
+  }"#;
+    let aapl = asset::Symbol::Sym("AAPL".to_string());
+    let spy = asset::Symbol::Sym("SPY".to_string());
+    let () = mock.respond::<position::Get>(&aapl, StatusCode::OK, position);
+    let () = mock.respond::<position::Get>(
+      &spy,
+      StatusCode::NOT_FOUND,
+      r#"{"code":40410000,"message":"position does not exist"}"#,
+    );
+    let client = mock_client(mock);
+
+    assert!(can_sell(&client, &aapl, &Num::from(4)).await.unwrap());
+    assert!(!can_sell(&client, &aapl, &Num::from(5)).await.unwrap());
+    assert!(!can_sell(&client, &spy, &Num::from(1)).await.unwrap());
+  }
+
+  /// Check that we can summarize a set of positions.
+  #[test]
+  fn summarize_positions() {
+    let positions = r#"[{
+    "asset_id": "904837e3-3b76-47ec-b432-046db621571b",
+    "symbol": "AAPL",
+    "exchange": "NASDAQ",
+    "asset_class": "us_equity",
+    "avg_entry_price": "100.0",
+    "qty": "5",
+    "qty_available": "5",
+    "side": "long",
+    "market_value": "600.0",
+    "cost_basis": "500.0",
+    "unrealized_pl": "100.0",
+    "unrealized_intraday_pl": "10.0",
+    "current_price": "120.0",
+    "lastday_price": "118.0"
+  }, {
+    "asset_id": "b0b6dd9d-8b9b-48a9-ba46-b9d54906e415",
+    "symbol": "SPY",
+    "exchange": "ARCA",
+    "asset_class": "us_equity",
+    "avg_entry_price": "400.0",
+    "qty": "-1",
+    "qty_available": "-1",
+    "side": "short",
+    "cost_basis": "-400.0",
+    "current_price": "410.0",
+    "lastday_price": "405.0"
+  }, {
+    "asset_id": "fc6a5dcd-4a70-4b8d-b64f-d83a6dae9ba4",
+    "symbol": "VOO",
+    "exchange": "ARCA",
+    "asset_class": "us_equity",
+    "avg_entry_price": "300.0",
+    "qty": "1",
+    "qty_available": "1",
+    "side": "long",
+    "cost_basis": "300.0"
+  }]"#;
+
+    let positions = from_json::<Vec<Position>>(positions).unwrap();
+    let summary = summary(&positions);
+    assert_eq!(summary.long_exposure, Num::from(600));
+    assert_eq!(summary.short_exposure, Num::from(410));
+    assert_eq!(summary.gross_exposure, Num::from(1010));
+    assert_eq!(summary.net_exposure, Num::from(190));
+    assert_eq!(summary.unrealized_gain, Num::from(90));
+    assert_eq!(summary.unrealized_gain_intraday, Num::from(5));
+    assert_eq!(summary.unpriced, 1);
+  }
 
   #[test(tokio::test)]
   async fn list_positions() {
@@ -45,4 +577,18 @@ mod tests {
     let client = Client::new(api_info);
     let _ = client.issue::<List>(&()).await.unwrap();
   }
+
+  /// Check that we can retrieve positions for a set of symbols.
+  #[test(tokio::test)]
+  async fn get_positions_by_symbols() {
+    let api_info = ApiInfo::from_env().unwrap();
+    let client = Client::new(api_info);
+    let positions = get_by_symbols(&client, &["SPY", "XLK"]).await.unwrap();
+
+    // We have no control over which positions are open, so all we can
+    // check is that nothing else is reported.
+    for position in positions {
+      assert!(["SPY", "XLK"].contains(&position.symbol.as_str()));
+    }
+  }
 }
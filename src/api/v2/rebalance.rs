@@ -0,0 +1,433 @@
+// Copyright (C) 2024 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+
+use num_decimal::Num;
+
+use thiserror::Error;
+
+use crate::api::v2::account;
+use crate::api::v2::order;
+use crate::api::v2::order::Amount;
+use crate::api::v2::order::Order;
+use crate::api::v2::order::Side;
+use crate::api::v2::position::Position;
+use crate::api::v2::positions;
+use crate::Client;
+use crate::RequestError;
+
+
+/// The information about an asset required for planning orders in it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Instrument {
+  /// The asset's current price.
+  pub price: Num,
+  /// Whether the asset can be traded in fractional quantities.
+  pub fractionable: bool,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+impl Instrument {
+  /// Create an `Instrument` with the given price.
+  #[inline]
+  pub fn new(price: Num, fractionable: bool) -> Self {
+    Self {
+      price,
+      fractionable,
+      _non_exhaustive: (),
+    }
+  }
+}
+
+
+/// A request to plan the rebalancing of a portfolio.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlanReq {
+  /// The target weight of each symbol, as a fraction of the account's
+  /// equity.
+  ///
+  /// Weights have to be non-negative and may not sum up to more than
+  /// one. Whatever is not allocated remains in cash.
+  pub targets: BTreeMap<String, Num>,
+  /// Price and fractionability information for the symbols to trade.
+  ///
+  /// Symbols of open positions that are not listed are priced at the
+  /// position's current price and treated as not fractionable. For all
+  /// other symbols with a non-zero target weight an entry is required.
+  pub instruments: HashMap<String, Instrument>,
+  /// The minimum value of an order; smaller adjustments are skipped.
+  pub min_notional: Num,
+  /// Whether to close open positions in symbols without a target
+  /// weight. If not set, such positions are left untouched.
+  pub liquidate_untargeted: bool,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+impl Default for PlanReq {
+  fn default() -> Self {
+    Self {
+      targets: BTreeMap::new(),
+      instruments: HashMap::new(),
+      min_notional: Num::from(1),
+      liquidate_untargeted: false,
+      _non_exhaustive: (),
+    }
+  }
+}
+
+
+/// An order as part of a rebalancing [`Plan`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PlannedOrder {
+  /// The symbol to trade.
+  pub symbol: String,
+  /// The side of the order.
+  pub side: Side,
+  /// The amount to trade.
+  pub amount: Amount,
+  /// The market value of the position in the symbol before the order.
+  pub current_value: Num,
+  /// The market value the position is targeted to have.
+  pub target_value: Num,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+impl PlannedOrder {
+  /// Create a market order request good for the day for this planned
+  /// order.
+  pub fn to_request(&self) -> order::CreateReq {
+    order::CreateReqInit::default().init(self.symbol.clone(), self.side, self.amount.clone())
+  }
+}
+
+
+/// A set of orders to be submitted for rebalancing a portfolio, as
+/// produced by [`plan`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Plan {
+  /// The orders to submit, with all sell orders preceding buy orders
+  /// so that they can free up buying power first.
+  pub orders: Vec<PlannedOrder>,
+  /// The symbols which deviate from their target but for which no
+  /// order was planned, because the adjustment would fall below the
+  /// minimum notional or amount to less than a share of an asset that
+  /// is not fractionable.
+  pub skipped: Vec<String>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+
+/// An error as reported when planning the rebalancing of a portfolio.
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+#[non_exhaustive]
+pub enum PlanError {
+  /// The target weight of the given symbol is negative.
+  #[error("target weight of {0} is negative")]
+  NegativeWeight(String),
+  /// The target weights sum up to more than one.
+  #[error("target weights sum up to {0}, which exceeds one")]
+  ExcessiveWeights(Num),
+  /// No price is known for the given symbol.
+  #[error("no price is known for {0}")]
+  MissingPrice(String),
+}
+
+
+/// Compute the orders required to move a portfolio with the given
+/// equity and positions to the requested target weights.
+///
+/// Orders for fractionable assets are expressed as notional amounts,
+/// rounded down to whole cents, whereas orders for other assets are
+/// for whole shares, rounded towards zero. Positions in symbols with
+/// a zero target weight are closed entirely. No orders are submitted;
+/// use [`execute`] for that purpose after reviewing the plan.
+pub fn plan(equity: &Num, positions: &[Position], request: &PlanReq) -> Result<Plan, PlanError> {
+  let mut total = Num::from(0);
+  for (symbol, weight) in &request.targets {
+    if weight.is_negative() {
+      return Err(PlanError::NegativeWeight(symbol.clone()))
+    }
+    total += weight;
+  }
+  if total > Num::from(1) {
+    return Err(PlanError::ExcessiveWeights(total))
+  }
+
+  let mut targets = request
+    .targets
+    .iter()
+    .map(|(symbol, weight)| (symbol.as_str(), weight.clone()))
+    .collect::<BTreeMap<_, _>>();
+  if request.liquidate_untargeted {
+    for position in positions {
+      let _weight = targets
+        .entry(position.symbol.as_str())
+        .or_insert_with(|| Num::from(0));
+    }
+  }
+
+  let mut sells = Vec::new();
+  let mut buys = Vec::new();
+  let mut skipped = Vec::new();
+
+  for (symbol, weight) in targets {
+    let position = positions.iter().find(|position| position.symbol == symbol);
+    let instrument = request.instruments.get(symbol);
+
+    let price = instrument
+      .map(|instrument| instrument.price.clone())
+      .or_else(|| position.and_then(|position| position.current_price.clone()));
+    let fractionable = instrument
+      .map(|instrument| instrument.fractionable)
+      .unwrap_or(false);
+
+    let current_quantity = position
+      .map(Position::signed_quantity)
+      .unwrap_or_else(|| Num::from(0));
+    if weight.is_zero() && current_quantity.is_zero() {
+      continue
+    }
+
+    let price = price.ok_or_else(|| PlanError::MissingPrice(symbol.to_string()))?;
+    let current_value = position
+      .and_then(Position::current_market_value)
+      .unwrap_or_else(|| &current_quantity * &price);
+    let target_value = equity * &weight;
+    let delta = &target_value - &current_value;
+    let side = if delta.is_negative() {
+      Side::Sell
+    } else {
+      Side::Buy
+    };
+    let delta = if delta.is_negative() { -delta } else { delta };
+
+    let amount = if weight.is_zero() {
+      // Close out the position entirely, irrespective of the minimum
+      // notional, so that no dust remains.
+      let side = if current_quantity.is_negative() {
+        Side::Buy
+      } else {
+        Side::Sell
+      };
+      let quantity = if current_quantity.is_negative() {
+        -current_quantity
+      } else {
+        current_quantity
+      };
+      Some((side, Amount::quantity(quantity)))
+    } else if delta < request.min_notional {
+      None
+    } else if fractionable {
+      let notional = (&delta * Num::from(100)).trunc() / Num::from(100);
+      Some((side, Amount::notional(notional)))
+    } else {
+      let quantity = (&delta / &price).trunc();
+      (!quantity.is_zero()).then(|| (side, Amount::quantity(quantity)))
+    };
+
+    let (side, amount) = match amount {
+      Some(amount) => amount,
+      None => {
+        if !delta.is_zero() {
+          let () = skipped.push(symbol.to_string());
+        }
+        continue
+      },
+    };
+
+    let order = PlannedOrder {
+      symbol: symbol.to_string(),
+      side,
+      amount,
+      current_value,
+      target_value,
+      _non_exhaustive: (),
+    };
+    match side {
+      Side::Sell => sells.push(order),
+      Side::Buy => buys.push(order),
+    }
+  }
+
+  let () = sells.extend(buys);
+  Ok(Plan {
+    orders: sells,
+    skipped,
+    _non_exhaustive: (),
+  })
+}
+
+
+/// An error as reported by [`plan_for_account`].
+#[derive(Debug, Error)]
+pub enum PlanForAccountError {
+  /// The account could not be retrieved.
+  #[error("failed to retrieve account")]
+  Account(#[source] RequestError<account::GetError>),
+  /// The open positions could not be retrieved.
+  #[error("failed to retrieve positions")]
+  Positions(#[source] RequestError<positions::ListError>),
+  /// The plan could not be computed.
+  #[error("failed to plan rebalancing")]
+  Plan(#[source] PlanError),
+}
+
+
+/// Retrieve the account's equity and open positions and compute the
+/// orders required for rebalancing it, as per [`plan`].
+pub async fn plan_for_account(
+  client: &Client,
+  request: &PlanReq,
+) -> Result<Plan, PlanForAccountError> {
+  let account = client
+    .issue::<account::Get>(&())
+    .await
+    .map_err(PlanForAccountError::Account)?;
+  let positions = client
+    .issue::<positions::List>(&())
+    .await
+    .map_err(PlanForAccountError::Positions)?;
+  plan(&account.equity, &positions, request).map_err(PlanForAccountError::Plan)
+}
+
+
+/// Submit the orders of a [`Plan`], in order.
+///
+/// Submission stops at the first order that fails. The orders
+/// submitted up to that point are not canceled.
+pub async fn execute(
+  client: &Client,
+  plan: &Plan,
+) -> Result<Vec<Order>, RequestError<order::CreateError>> {
+  let mut orders = Vec::with_capacity(plan.orders.len());
+  for planned in &plan.orders {
+    let order = client.issue::<order::Create>(&planned.to_request()).await?;
+    let () = orders.push(order);
+  }
+  Ok(orders)
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use serde_json::from_str as from_json;
+
+
+  /// Create a long position in the given symbol.
+  fn position(symbol: &str, quantity: i64, price: i64) -> Position {
+    let json = format!(
+      r#"{{
+  "asset_id": "904837e3-3b76-47ec-b432-046db621571b",
+  "symbol": "{symbol}",
+  "exchange": "NASDAQ",
+  "asset_class": "us_equity",
+  "avg_entry_price": "{price}",
+  "qty": "{quantity}",
+  "qty_available": "{quantity}",
+  "side": "long",
+  "market_value": "{value}",
+  "cost_basis": "{value}",
+  "current_price": "{price}"
+}}"#,
+      value = quantity * price,
+    );
+    from_json::<Position>(&json).unwrap()
+  }
+
+
+  /// Check that we plan the expected orders for rebalancing a
+  /// portfolio.
+  #[test]
+  fn plan_rebalancing() {
+    let positions = [
+      position("AAPL", 10, 100),
+      position("SPY", 2, 400),
+      position("MSFT", 1, 300),
+    ];
+    let mut request = PlanReq {
+      liquidate_untargeted: true,
+      ..Default::default()
+    };
+    let _prev = request.targets.insert("AAPL".into(), Num::new(25, 100));
+    let _prev = request.targets.insert("SPY".into(), Num::new(40, 100));
+    let _prev = request.targets.insert("VOO".into(), Num::new(30, 100));
+    let _prev = request
+      .instruments
+      .insert("VOO".into(), Instrument::new(Num::new(33333, 100), true));
+    let _prev = request
+      .instruments
+      .insert("SPY".into(), Instrument::new(Num::from(400), false));
+
+    let plan = plan(&Num::from(4000), &positions, &request).unwrap();
+    let orders = plan
+      .orders
+      .iter()
+      .map(|order| (order.symbol.as_str(), order.side, order.amount.clone()))
+      .collect::<Vec<_>>();
+    // AAPL is already at its target value of 1000 and MSFT is not
+    // targeted at all.
+    assert_eq!(
+      orders,
+      vec![
+        ("MSFT", Side::Sell, Amount::quantity(1)),
+        // SPY is worth 800, but should be worth 1600.
+        ("SPY", Side::Buy, Amount::quantity(2)),
+        // VOO is not held, but should be worth 1200.
+        ("VOO", Side::Buy, Amount::notional(1200)),
+      ]
+    );
+    assert_eq!(plan.skipped, Vec::<String>::new());
+    assert_eq!(plan.orders[1].to_request().type_, order::Type::Market);
+  }
+
+  /// Check that adjustments that are too small to be executed are
+  /// skipped.
+  #[test]
+  fn plan_skips_small_adjustments() {
+    let positions = [position("AAPL", 10, 100), position("SPY", 1, 400)];
+    let mut request = PlanReq {
+      min_notional: Num::from(5),
+      ..Default::default()
+    };
+    // AAPL would need to be adjusted by 2, SPY by 200, which is less
+    // than a share.
+    let _prev = request.targets.insert("AAPL".into(), Num::new(1002, 10000));
+    let _prev = request.targets.insert("SPY".into(), Num::new(60, 1000));
+
+    let plan = plan(&Num::from(10000), &positions, &request).unwrap();
+    assert_eq!(plan.orders, Vec::new());
+    assert_eq!(plan.skipped, vec!["AAPL".to_string(), "SPY".to_string()]);
+  }
+
+  /// Check that we reject invalid rebalancing requests.
+  #[test]
+  fn plan_invalid_requests() {
+    let mut request = PlanReq::default();
+    let _prev = request.targets.insert("AAPL".into(), Num::new(-1, 10));
+    let err = plan(&Num::from(1000), &[], &request).unwrap_err();
+    assert_eq!(err, PlanError::NegativeWeight("AAPL".into()));
+
+    let mut request = PlanReq::default();
+    let _prev = request.targets.insert("AAPL".into(), Num::new(6, 10));
+    let _prev = request.targets.insert("SPY".into(), Num::new(6, 10));
+    let err = plan(&Num::from(1000), &[], &request).unwrap_err();
+    assert_eq!(err, PlanError::ExcessiveWeights(Num::new(12, 10)));
+
+    let mut request = PlanReq::default();
+    let _prev = request.targets.insert("AAPL".into(), Num::new(5, 10));
+    let err = plan(&Num::from(1000), &[], &request).unwrap_err();
+    assert_eq!(err, PlanError::MissingPrice("AAPL".into()));
+  }
+}
@@ -0,0 +1,386 @@
+// Copyright (C) 2024 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use futures::future::try_join3;
+use futures::TryFutureExt as _;
+
+use num_decimal::Num;
+
+use thiserror::Error;
+
+use crate::api::v2::account;
+use crate::api::v2::account::Account;
+use crate::api::v2::asset;
+use crate::api::v2::asset::Asset;
+use crate::api::v2::order::Amount;
+use crate::api::v2::order::CreateReq;
+use crate::api::v2::order::Side;
+use crate::api::v2::position;
+use crate::api::v2::position::Position;
+use crate::Client;
+use crate::RequestError;
+
+
+/// The equity below which accounts are restricted by the pattern day
+/// trader rule.
+const PDT_EQUITY_THRESHOLD: i64 = 25_000;
+/// The number of day trades an account restricted by the pattern day
+/// trader rule may perform within five business days.
+const PDT_DAYTRADE_LIMIT: u64 = 3;
+
+
+/// A likely reason for an order to be rejected.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Rejection {
+  /// The account is blocked from trading or trading was suspended.
+  TradingBlocked,
+  /// The asset is not tradable.
+  NotTradable,
+  /// The estimated cost of the order exceeds the account's buying
+  /// power.
+  InsufficientBuyingPower {
+    /// The estimated cost of the order.
+    required: Num,
+    /// The buying power available.
+    available: Num,
+  },
+  /// The order would open or increase a short position, but shorting
+  /// is not enabled for the account.
+  ShortingDisabled,
+  /// The order would open or increase a short position, but the asset
+  /// can not be sold short.
+  NotShortable,
+  /// The order is for a fractional quantity or a notional amount, but
+  /// the asset is not fractionable.
+  NotFractionable,
+  /// The order may constitute a day trade, but the account already
+  /// exhausted the day trades permitted by the pattern day trader rule.
+  DayTradeLimit {
+    /// The number of day trades performed by the account.
+    daytrade_count: u64,
+  },
+}
+
+
+/// An error encountered while retrieving the state to check an order
+/// against.
+#[derive(Debug, Error)]
+pub enum CheckError {
+  /// The account could not be retrieved.
+  #[error("failed to retrieve account")]
+  Account(#[source] RequestError<account::GetError>),
+  /// The asset could not be retrieved.
+  #[error("failed to retrieve asset")]
+  Asset(#[source] RequestError<asset::GetError>),
+  /// The position in the asset could not be retrieved.
+  #[error("failed to retrieve position")]
+  Position(#[source] RequestError<position::GetError>),
+}
+
+
+/// Retrieve the signed quantity held in the provided position, with
+/// short positions being negative.
+fn held_quantity(position: Option<&Position>) -> Num {
+  match position {
+    Some(position) => {
+      let quantity = if position.quantity.is_negative() {
+        -position.quantity.clone()
+      } else {
+        position.quantity.clone()
+      };
+
+      match position.side {
+        position::Side::Long => quantity,
+        position::Side::Short => -quantity,
+      }
+    },
+    None => Num::from(0),
+  }
+}
+
+
+/// Check the provided order request against the given account, asset,
+/// and position state, reporting the reasons for which it would likely
+/// be rejected.
+///
+/// The checks performed are heuristics mirroring Alpaca's rules and do
+/// not guarantee that an order passing them is accepted. In
+/// particular, buying power can only be checked for orders with a
+/// notional amount or a limit or stop price.
+pub fn check(
+  request: &CreateReq,
+  account: &Account,
+  asset: &Asset,
+  position: Option<&Position>,
+) -> Vec<Rejection> {
+  let mut rejections = Vec::new();
+
+  if account.trading_blocked || account.account_blocked || account.trading_suspended {
+    rejections.push(Rejection::TradingBlocked);
+  }
+
+  if !asset.tradable {
+    rejections.push(Rejection::NotTradable);
+  }
+
+  let fractional = match &request.amount {
+    Amount::Quantity { quantity } => !quantity.fract().is_zero(),
+    Amount::Notional { .. } => true,
+  };
+  if fractional && !asset.fractionable {
+    rejections.push(Rejection::NotFractionable);
+  }
+
+  let held = held_quantity(position);
+  match request.side {
+    Side::Buy => {
+      let price = request.limit_price.as_ref().or(request.stop_price.as_ref());
+      let required = match (&request.amount, price) {
+        (Amount::Notional { notional }, _) => Some(notional.clone()),
+        (Amount::Quantity { quantity }, Some(price)) => Some(quantity * price),
+        (Amount::Quantity { .. }, None) => None,
+      };
+
+      if let Some(required) = required {
+        if required > account.buying_power {
+          rejections.push(Rejection::InsufficientBuyingPower {
+            required,
+            available: account.buying_power.clone(),
+          });
+        }
+      }
+    },
+    Side::Sell => {
+      // Notional orders can never open a short position.
+      if let Amount::Quantity { quantity } = &request.amount {
+        if quantity > &held {
+          if !account.shorting_enabled {
+            rejections.push(Rejection::ShortingDisabled);
+          }
+          if !asset.shortable {
+            rejections.push(Rejection::NotShortable);
+          }
+        }
+      }
+    },
+  }
+
+  // An order reducing an existing position may close out a position
+  // opened on the same day, constituting a day trade.
+  let reduces = match request.side {
+    Side::Buy => held.is_negative(),
+    Side::Sell => held.is_positive(),
+  };
+  if reduces
+    && account.equity < Num::from(PDT_EQUITY_THRESHOLD)
+    && account.daytrade_count >= PDT_DAYTRADE_LIMIT
+  {
+    rejections.push(Rejection::DayTradeLimit {
+      daytrade_count: account.daytrade_count,
+    });
+  }
+
+  rejections
+}
+
+
+/// Retrieve the current account, asset, and position state and check
+/// the provided order request against it.
+///
+/// See [`check`] for details on the checks performed.
+pub async fn check_order(
+  client: &Client,
+  request: &CreateReq,
+) -> Result<Vec<Rejection>, CheckError> {
+  let account = client
+    .issue::<account::Get>(&())
+    .map_err(CheckError::Account);
+  let asset = client
+    .issue::<asset::Get>(&request.symbol)
+    .map_err(CheckError::Asset);
+  let position = client
+    .issue::<position::Get>(&request.symbol)
+    .map_ok(Some)
+    .or_else(|err| async move {
+      match err {
+        RequestError::Endpoint(position::GetError::NotFound(_)) => Ok(None),
+        err => Err(CheckError::Position(err)),
+      }
+    });
+
+  let (account, asset, position) = try_join3(account, asset, position).await?;
+  Ok(check(request, &account, &asset, position.as_ref()))
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use serde_json::from_str as from_json;
+
+  use test_log::test;
+
+  use crate::api::v2::asset::test::asset;
+  use crate::api::v2::order::CreateReqInit;
+  use crate::api::v2::order::Type;
+
+
+  /// Create an `Account` object with the given buying power, day
+  /// trade count, and shorting permission.
+  fn account(buying_power: &str, daytrade_count: u64, shorting_enabled: bool) -> Account {
+    let json = format!(
+      r#"{{
+  "id": "904837e3-3b76-47ec-b432-046db621571b",
+  "status": "ACTIVE",
+  "currency": "USD",
+  "buying_power": "{buying_power}",
+  "cash": "1000.00",
+  "pattern_day_trader": false,
+  "trade_suspended_by_user": false,
+  "trading_blocked": false,
+  "transfers_blocked": false,
+  "account_blocked": false,
+  "created_at": "2018-10-01T13:35:25Z",
+  "shorting_enabled": {shorting_enabled},
+  "multiplier": "2",
+  "long_market_value": "1000.00",
+  "short_market_value": "0.00",
+  "equity": "5000.00",
+  "last_equity": "5000.00",
+  "initial_margin": "0.00",
+  "maintenance_margin": "0.00",
+  "daytrade_count": {daytrade_count},
+  "sma": "0.0"
+}}"#
+    );
+    from_json::<Account>(&json).unwrap()
+  }
+
+  /// Create a long `Position` object of the given quantity.
+  fn position(quantity: u64) -> Position {
+    let json = format!(
+      r#"{{
+  "asset_id": "904837e3-3b76-47ec-b432-046db621571b",
+  "symbol": "AAPL",
+  "exchange": "NASDAQ",
+  "asset_class": "us_equity",
+  "avg_entry_price": "100.0",
+  "qty": "{quantity}",
+  "qty_available": "{quantity}",
+  "side": "long",
+  "market_value": "600.0",
+  "cost_basis": "500.0",
+  "unrealized_pl": "100.0",
+  "unrealized_plpc": "0.20",
+  "unrealized_intraday_pl": "10.0",
+  "unrealized_intraday_plpc": "0.0084",
+  "current_price": "120.0",
+  "lastday_price": "119.0",
+  "change_today": "0.0084"
+}}"#
+    );
+    from_json::<Position>(&json).unwrap()
+  }
+
+  /// Create a limit order request for the given side and quantity.
+  fn limit_order(side: Side, quantity: Num) -> CreateReq {
+    CreateReqInit {
+      type_: Type::Limit,
+      limit_price: Some(Num::from(100)),
+      ..Default::default()
+    }
+    .init("AAPL", side, Amount::quantity(quantity))
+  }
+
+  /// Check that orders within the account's limits pass all checks.
+  #[test]
+  fn check_acceptable_order() {
+    let request = limit_order(Side::Buy, Num::from(5));
+    let rejections = check(
+      &request,
+      &account("1000", 0, true),
+      &asset(true, true, true),
+      None,
+    );
+    assert_eq!(rejections, Vec::new());
+
+    // Closing out a long position is not a short sale.
+    let request = limit_order(Side::Sell, Num::from(5));
+    let position = position(5);
+    let rejections = check(
+      &request,
+      &account("0", 0, false),
+      &asset(true, false, true),
+      Some(&position),
+    );
+    assert_eq!(rejections, Vec::new());
+
+    // Market orders without a notional amount can not be checked
+    // against the buying power.
+    let request = CreateReqInit::default().init("AAPL", Side::Buy, Amount::quantity(5));
+    let rejections = check(
+      &request,
+      &account("0", 0, true),
+      &asset(true, true, true),
+      None,
+    );
+    assert_eq!(rejections, Vec::new());
+  }
+
+  /// Check that we report the likely reasons for an order to get
+  /// rejected.
+  #[test]
+  fn check_rejected_orders() {
+    let request = limit_order(Side::Buy, Num::from(11));
+    let rejections = check(
+      &request,
+      &account("1000", 0, true),
+      &asset(false, true, true),
+      None,
+    );
+    let expected = vec![
+      Rejection::NotTradable,
+      Rejection::InsufficientBuyingPower {
+        required: Num::from(1100),
+        available: Num::from(1000),
+      },
+    ];
+    assert_eq!(rejections, expected);
+
+    let request = CreateReqInit::default().init("AAPL", Side::Buy, Amount::notional(10));
+    let rejections = check(
+      &request,
+      &account("1000", 0, true),
+      &asset(true, true, false),
+      None,
+    );
+    assert_eq!(rejections, vec![Rejection::NotFractionable]);
+
+    let request = limit_order(Side::Sell, Num::from(6));
+    let position = position(5);
+    let rejections = check(
+      &request,
+      &account("1000", 0, false),
+      &asset(true, false, true),
+      Some(&position),
+    );
+    assert_eq!(
+      rejections,
+      vec![Rejection::ShortingDisabled, Rejection::NotShortable]
+    );
+
+    let request = limit_order(Side::Sell, Num::from(5));
+    let rejections = check(
+      &request,
+      &account("1000", 3, true),
+      &asset(true, true, true),
+      Some(&position),
+    );
+    assert_eq!(
+      rejections,
+      vec![Rejection::DayTradeLimit { daytrade_count: 3 }]
+    );
+  }
+}
@@ -2,17 +2,32 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fmt::Debug;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::Mutex;
 
 use async_trait::async_trait;
 
+use chrono::DateTime;
+use chrono::Utc;
+
+use futures::future::BoxFuture;
 use futures::stream::Fuse;
+use futures::stream::FusedStream;
 use futures::stream::Map;
 use futures::stream::SplitSink;
 use futures::stream::SplitStream;
+use futures::task::Context;
+use futures::task::Poll;
 use futures::FutureExt as _;
 use futures::Sink;
 use futures::StreamExt as _;
 
+use num_decimal::Num;
+
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
 use serde::Serialize;
 use serde_json::from_slice as json_from_slice;
@@ -20,10 +35,9 @@ use serde_json::from_str as json_from_str;
 use serde_json::to_string as to_json;
 use serde_json::Error as JsonError;
 
-use tokio::net::TcpStream;
+use tokio::time::timeout;
 
-use tungstenite::MaybeTlsStream;
-use tungstenite::WebSocketStream;
+use uuid::Uuid;
 
 use websocket_util::subscribe;
 use websocket_util::subscribe::MessageStream;
@@ -31,11 +45,14 @@ use websocket_util::tungstenite::Error as WebSocketError;
 use websocket_util::wrap;
 use websocket_util::wrap::Wrapper;
 
+use crate::api::v2::account;
 use crate::api::v2::order;
 use crate::api_info::ApiInfo;
+use crate::connection::ConnectionConfig;
 use crate::subscribable::Subscribable;
 use crate::websocket::connect;
 use crate::websocket::MessageResult;
+use crate::websocket::WebSocket;
 use crate::Error;
 
 
@@ -117,6 +134,9 @@ pub enum StreamType {
   /// A stream for order updates.
   #[serde(rename = "trade_updates")]
   OrderUpdates,
+  /// A stream for account updates.
+  #[serde(rename = "account_updates")]
+  AccountUpdates,
 }
 
 
@@ -240,27 +260,140 @@ pub struct OrderUpdate {
   /// The order that received an update.
   #[serde(rename = "order")]
   pub order: order::Order,
+  /// The time at which the event occurred.
+  #[serde(rename = "timestamp")]
+  pub timestamp: DateTime<Utc>,
+  /// The ID of the execution that caused the update, if any.
+  #[serde(rename = "execution_id")]
+  pub execution_id: Option<Uuid>,
+  /// The price at which the order was (partially) filled, for fill
+  /// events.
+  #[serde(rename = "price")]
+  pub price: Option<Num>,
+  /// The quantity filled as part of this event, for fill events.
+  #[serde(rename = "qty")]
+  pub quantity: Option<Num>,
+  /// The total size of the position after the event, for fill events.
+  #[serde(rename = "position_qty")]
+  pub position_quantity: Option<Num>,
+}
+
+
+/// An enum representing the different messages we may receive over our
+/// websocket channel when listening to account updates.
+#[derive(Debug, Deserialize, Serialize)]
+#[doc(hidden)]
+#[serde(tag = "stream", content = "data")]
+pub enum AccountMessage {
+  /// An account update.
+  #[serde(rename = "account_updates")]
+  AccountUpdate(AccountUpdate),
+  /// A control message indicating whether or not we were authenticated
+  /// successfully.
+  #[serde(rename = "authorization")]
+  AuthenticationMessage(Authentication),
+  /// A control message detailing the streams we are subscribed to.
+  #[serde(rename = "listening")]
+  ListeningMessage(Streams<'static>),
+}
+
+
+/// A representation of an account update that we receive through the
+/// "account_updates" stream.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct AccountUpdate {
+  /// The account's ID.
+  #[serde(rename = "id")]
+  pub id: account::Id,
+  /// The time at which the account was created.
+  #[serde(rename = "created_at")]
+  pub created_at: DateTime<Utc>,
+  /// The time at which the account was last updated.
+  #[serde(rename = "updated_at")]
+  pub updated_at: Option<DateTime<Utc>>,
+  /// The time at which the account was deleted.
+  #[serde(rename = "deleted_at")]
+  pub deleted_at: Option<DateTime<Utc>>,
+  /// The account's status.
+  #[serde(rename = "status")]
+  pub status: account::Status,
+  /// The currency the account uses.
+  #[serde(rename = "currency")]
+  pub currency: String,
+  /// The account's cash balance.
+  #[serde(rename = "cash")]
+  pub cash: Num,
+  /// The amount of cash that can be withdrawn.
+  #[serde(rename = "cash_withdrawable")]
+  pub withdrawable_cash: Num,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// A trait for the messages received over one of the update streams.
+#[doc(hidden)]
+pub trait StreamMessage {
+  /// The update type conveyed by the stream.
+  type Update: Debug;
+
+  /// Classify the message as either an update or a control message.
+  fn classify(self) -> subscribe::Classification<Self::Update, ControlMessage>;
+}
+
+impl StreamMessage for OrderMessage {
+  type Update = OrderUpdate;
+
+  fn classify(self) -> subscribe::Classification<Self::Update, ControlMessage> {
+    match self {
+      Self::OrderUpdate(update) => subscribe::Classification::UserMessage(update),
+      Self::AuthenticationMessage(authentication) => subscribe::Classification::ControlMessage(
+        ControlMessage::AuthenticationMessage(authentication),
+      ),
+      Self::ListeningMessage(streams) => {
+        subscribe::Classification::ControlMessage(ControlMessage::ListeningMessage(streams))
+      },
+    }
+  }
+}
+
+impl StreamMessage for AccountMessage {
+  type Update = AccountUpdate;
+
+  fn classify(self) -> subscribe::Classification<Self::Update, ControlMessage> {
+    match self {
+      Self::AccountUpdate(update) => subscribe::Classification::UserMessage(update),
+      Self::AuthenticationMessage(authentication) => subscribe::Classification::ControlMessage(
+        ControlMessage::AuthenticationMessage(authentication),
+      ),
+      Self::ListeningMessage(streams) => {
+        subscribe::Classification::ControlMessage(ControlMessage::ListeningMessage(streams))
+      },
+    }
+  }
 }
 
 
 /// A websocket message that we tried to parse.
-type ParsedMessage = MessageResult<Result<OrderMessage, JsonError>, WebSocketError>;
+type ParsedMessage<M = OrderMessage> = MessageResult<Result<M, JsonError>, WebSocketError>;
 
-impl subscribe::Message for ParsedMessage {
-  type UserMessage = Result<Result<OrderUpdate, JsonError>, WebSocketError>;
+impl<M> subscribe::Message for ParsedMessage<M>
+where
+  M: StreamMessage,
+{
+  type UserMessage = Result<Result<M::Update, JsonError>, WebSocketError>;
   type ControlMessage = ControlMessage;
 
   fn classify(self) -> subscribe::Classification<Self::UserMessage, Self::ControlMessage> {
     match self {
-      MessageResult::Ok(Ok(message)) => match message {
-        OrderMessage::OrderUpdate(update) => subscribe::Classification::UserMessage(Ok(Ok(update))),
-        OrderMessage::AuthenticationMessage(authentication) => {
-          subscribe::Classification::ControlMessage(ControlMessage::AuthenticationMessage(
-            authentication,
-          ))
+      MessageResult::Ok(Ok(message)) => match message.classify() {
+        subscribe::Classification::UserMessage(update) => {
+          subscribe::Classification::UserMessage(Ok(Ok(update)))
         },
-        OrderMessage::ListeningMessage(streams) => {
-          subscribe::Classification::ControlMessage(ControlMessage::ListeningMessage(streams))
+        subscribe::Classification::ControlMessage(control) => {
+          subscribe::Classification::ControlMessage(control)
         },
       },
       // JSON errors are directly passed through.
@@ -282,89 +415,303 @@ impl subscribe::Message for ParsedMessage {
 }
 
 
+/// The user message type of the order update stream.
+type UserMessage = <ParsedMessage as subscribe::Message>::UserMessage;
+
+
+/// The filters applied to order updates before handing them out.
+#[derive(Debug, Default)]
+struct Filter {
+  /// The symbols to yield updates for, if restricted.
+  symbols: Option<HashSet<String>>,
+  /// The client order IDs to yield updates for, if restricted.
+  client_order_ids: Option<HashSet<String>>,
+}
+
+impl Filter {
+  /// Check whether the given update passes the filter.
+  fn matches(&self, update: &OrderUpdate) -> bool {
+    let symbol = self
+      .symbols
+      .as_ref()
+      .map(|symbols| symbols.contains(&update.order.symbol))
+      .unwrap_or(true);
+    let client_order_id = self
+      .client_order_ids
+      .as_ref()
+      .map(|ids| ids.contains(&update.order.client_order_id))
+      .unwrap_or(true);
+
+    symbol && client_order_id
+  }
+}
+
+
+/// A wrapper around a stream of order updates that only yields those
+/// updates matching the filters of the associated [`Subscription`].
+///
+/// Errors are never filtered out.
+#[derive(Debug)]
+#[doc(hidden)]
+#[must_use = "streams do nothing unless polled"]
+pub struct Filtered<S> {
+  /// The wrapped stream.
+  inner: S,
+  /// The filter, shared with the subscription.
+  filter: Arc<Mutex<Filter>>,
+}
+
+impl<S> futures::Stream for Filtered<S>
+where
+  S: futures::Stream<Item = UserMessage> + Unpin,
+{
+  type Item = UserMessage;
+
+  fn poll_next(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+    loop {
+      match self.inner.poll_next_unpin(ctx) {
+        Poll::Ready(Some(Ok(Ok(update)))) => {
+          // A poisoned lock can only be the result of a panic while
+          // updating the filter, at which point we just continue
+          // using whatever state it is in.
+          let matches = self
+            .filter
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .matches(&update);
+
+          if matches {
+            break Poll::Ready(Some(Ok(Ok(update))))
+          }
+        },
+        result => break result,
+      }
+    }
+  }
+
+  #[inline]
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    (0, self.inner.size_hint().1)
+  }
+}
+
+impl<S> FusedStream for Filtered<S>
+where
+  S: FusedStream<Item = UserMessage> + Unpin,
+{
+  #[inline]
+  fn is_terminated(&self) -> bool {
+    self.inner.is_terminated()
+  }
+}
+
+
 /// A subscription allowing certain control operations pertaining order
 /// update retrieval.
 #[derive(Debug)]
-pub struct Subscription<S>(subscribe::Subscription<S, ParsedMessage, wrap::Message>);
+pub struct Subscription<S>(
+  subscribe::Subscription<S, ParsedMessage, wrap::Message>,
+  Arc<Mutex<Filter>>,
+);
+
+impl<S> Subscription<S> {
+  /// Apply the given function to the subscription's filter.
+  fn update_filter<F>(&mut self, f: F)
+  where
+    F: FnOnce(&mut Filter),
+  {
+    let mut filter = self.1.lock().unwrap_or_else(|err| err.into_inner());
+    f(&mut filter)
+  }
 
-impl<S> Subscription<S>
+  /// Only yield updates for orders with one of the given symbols.
+  ///
+  /// Any previously set symbol filter is replaced. Filters are applied
+  /// locally, to updates received after the call.
+  pub fn filter_symbols<I, T>(&mut self, symbols: I)
+  where
+    I: IntoIterator<Item = T>,
+    T: Into<String>,
+  {
+    let symbols = symbols.into_iter().map(T::into).collect();
+    self.update_filter(|filter| filter.symbols = Some(symbols))
+  }
+
+  /// Only yield updates for orders with one of the given client order
+  /// IDs.
+  ///
+  /// Any previously set client order ID filter is replaced. Filters
+  /// are applied locally, to updates received after the call.
+  pub fn filter_client_order_ids<I, T>(&mut self, client_order_ids: I)
+  where
+    I: IntoIterator<Item = T>,
+    T: Into<String>,
+  {
+    let ids = client_order_ids.into_iter().map(T::into).collect();
+    self.update_filter(|filter| filter.client_order_ids = Some(ids))
+  }
+
+  /// Remove all filters, yielding updates for all orders again.
+  pub fn clear_filters(&mut self) {
+    self.update_filter(|filter| *filter = Filter::default())
+  }
+}
+
+/// Authenticate the connection using Alpaca credentials.
+async fn authenticate<S, M>(
+  subscription: &mut subscribe::Subscription<S, M, wrap::Message>,
+  key_id: &str,
+  secret: &str,
+) -> Result<Result<(), Error>, S::Error>
 where
   S: Sink<wrap::Message> + Unpin,
+  M: subscribe::Message<ControlMessage = ControlMessage>,
 {
-  /// Authenticate the connection using Alpaca credentials.
-  async fn authenticate(
-    &mut self,
-    key_id: &str,
-    secret: &str,
-  ) -> Result<Result<(), Error>, S::Error> {
-    let request = Authenticate::Request {
-      key_id: key_id.into(),
-      secret: secret.into(),
-    };
-    let json = match to_json(&request) {
-      Ok(json) => json,
-      Err(err) => return Ok(Err(Error::Json(err))),
-    };
-    let message = wrap::Message::Text(json);
-    let response = self.0.send(message).await?;
-
-    match response {
-      Some(response) => match response {
-        Ok(ControlMessage::AuthenticationMessage(authentication)) => {
-          if authentication.status != AuthenticationStatus::Authorized {
-            return Ok(Err(Error::Str("authentication not successful".into())))
-          }
-          Ok(Ok(()))
-        },
-        Ok(_) => Ok(Err(Error::Str(
-          "server responded with an unexpected message".into(),
-        ))),
-        Err(()) => Ok(Err(Error::Str("failed to authenticate with server".into()))),
+  let request = Authenticate::Request {
+    key_id: key_id.into(),
+    secret: secret.into(),
+  };
+  let json = match to_json(&request) {
+    Ok(json) => json,
+    Err(err) => return Ok(Err(Error::Json(err))),
+  };
+  let message = wrap::Message::Text(json);
+  let response = subscription.send(message).await?;
+
+  match response {
+    Some(response) => match response {
+      Ok(ControlMessage::AuthenticationMessage(authentication)) => {
+        if authentication.status != AuthenticationStatus::Authorized {
+          return Ok(Err(Error::Str("authentication not successful".into())))
+        }
+        Ok(Ok(()))
       },
-      None => Ok(Err(Error::Str(
-        "stream was closed before authorization message was received".into(),
+      Ok(_) => Ok(Err(Error::Str(
+        "server responded with an unexpected message".into(),
       ))),
-    }
+      Err(()) => Ok(Err(Error::Str("failed to authenticate with server".into()))),
+    },
+    None => Ok(Err(Error::Str(
+      "stream was closed before authorization message was received".into(),
+    ))),
   }
+}
 
-  /// Subscribe and listen to order updates.
-  async fn listen(&mut self) -> Result<Result<(), Error>, S::Error> {
-    let streams = Streams::from([StreamType::OrderUpdates].as_ref());
-    let request = Listen::Request(streams);
-    let json = match to_json(&request) {
-      Ok(json) => json,
-      Err(err) => return Ok(Err(Error::Json(err))),
-    };
-    let message = wrap::Message::Text(json);
-    let response = self.0.send(message).await?;
-
-    match response {
-      Some(response) => match response {
-        Ok(ControlMessage::ListeningMessage(streams)) => {
-          if !streams.streams.contains(&StreamType::OrderUpdates) {
-            return Ok(Err(Error::Str(
-              "server did not subscribe us to order update stream".into(),
-            )))
-          }
-          Ok(Ok(()))
-        },
-        Ok(_) => Ok(Err(Error::Str(
-          "server responded with an unexpected message".into(),
-        ))),
-        Err(()) => Ok(Err(Error::Str(
-          "failed to listen to order update stream".into(),
-        ))),
+
+/// Subscribe and listen to the given stream.
+async fn listen<S, M>(
+  subscription: &mut subscribe::Subscription<S, M, wrap::Message>,
+  stream: StreamType,
+) -> Result<Result<(), Error>, S::Error>
+where
+  S: Sink<wrap::Message> + Unpin,
+  M: subscribe::Message<ControlMessage = ControlMessage>,
+{
+  let name = match stream {
+    StreamType::OrderUpdates => "order update",
+    StreamType::AccountUpdates => "account update",
+  };
+
+  let streams = [stream];
+  let streams = Streams::from(streams.as_ref());
+  let request = Listen::Request(streams);
+  let json = match to_json(&request) {
+    Ok(json) => json,
+    Err(err) => return Ok(Err(Error::Json(err))),
+  };
+  let message = wrap::Message::Text(json);
+  let response = subscription.send(message).await?;
+
+  match response {
+    Some(response) => match response {
+      Ok(ControlMessage::ListeningMessage(streams)) => {
+        if !streams.streams.contains(&stream) {
+          return Ok(Err(Error::Str(
+            format!("server did not subscribe us to {name} stream").into(),
+          )))
+        }
+        Ok(Ok(()))
       },
-      None => Ok(Err(Error::Str(
-        "stream was closed before listen message was received".into(),
+      Ok(_) => Ok(Err(Error::Str(
+        "server responded with an unexpected message".into(),
       ))),
-    }
+      Err(()) => Ok(Err(Error::Str(
+        format!("failed to listen to {name} stream").into(),
+      ))),
+    },
+    None => Ok(Err(Error::Str(
+      "stream was closed before listen message was received".into(),
+    ))),
   }
 }
 
 
-type Stream = Map<Wrapper<WebSocketStream<MaybeTlsStream<TcpStream>>>, MapFn>;
-type MapFn = fn(Result<wrap::Message, WebSocketError>) -> ParsedMessage;
+type Stream<M = OrderMessage> = Map<Wrapper<WebSocket>, MapFn<M>>;
+type MapFn<M = OrderMessage> = fn(Result<wrap::Message, WebSocketError>) -> ParsedMessage<M>;
+type RawStream<M> = Fuse<MessageStream<SplitStream<Stream<M>>, ParsedMessage<M>>>;
+type RawSubscription<M> =
+  subscribe::Subscription<SplitSink<Stream<M>, wrap::Message>, ParsedMessage<M>, wrap::Message>;
+
+
+/// Connect to the updates websocket, authenticate, and listen to the
+/// given stream.
+async fn connect_stream<M>(
+  api_info: &ApiInfo,
+  config: &ConnectionConfig,
+  stream_type: StreamType,
+) -> Result<(RawStream<M>, RawSubscription<M>), Error>
+where
+  M: StreamMessage + DeserializeOwned + Send,
+{
+  fn map<M>(result: Result<wrap::Message, WebSocketError>) -> ParsedMessage<M>
+  where
+    M: DeserializeOwned,
+  {
+    MessageResult::from(result.map(|message| match message {
+      wrap::Message::Text(string) => json_from_str::<M>(&string),
+      wrap::Message::Binary(data) => json_from_slice::<M>(&data),
+    }))
+  }
+
+  let ApiInfo {
+    api_stream_url: url,
+    key_id,
+    secret,
+    ..
+  } = api_info;
+
+  let stream = connect(url, config).await?.map(map::<M> as MapFn<M>);
+  let (send, recv) = stream.split();
+  let (stream, mut subscription) = subscribe::subscribe(recv, send);
+  let mut stream = stream.fuse();
+
+  let handshake_timeout = config.handshake_timeout();
+  let authenticate = authenticate(&mut subscription, key_id, secret).boxed();
+  let drive = subscribe::drive::<ParsedMessage<M>, _, _>(authenticate, &mut stream);
+  let () = timeout(handshake_timeout, drive)
+    .await
+    .map_err(|_elapsed| Error::Timeout(handshake_timeout))?
+    .map_err(|result| {
+      result
+        .map(|result| Error::Json(result.unwrap_err()))
+        .map_err(Error::WebSocket)
+        .unwrap_or_else(|err| err)
+    })???;
+
+  let listen = listen(&mut subscription, stream_type).boxed();
+  let drive = subscribe::drive::<ParsedMessage<M>, _, _>(listen, &mut stream);
+  let () = timeout(handshake_timeout, drive)
+    .await
+    .map_err(|_elapsed| Error::Timeout(handshake_timeout))?
+    .map_err(|result| {
+      result
+        .map(|result| Error::Json(result.unwrap_err()))
+        .map_err(Error::WebSocket)
+        .unwrap_or_else(|err| err)
+    })???;
+
+  Ok((stream, subscription))
+}
 
 
 /// A type used for requesting a subscription to the "trade_updates"
@@ -376,50 +723,74 @@ pub enum OrderUpdates {}
 impl Subscribable for OrderUpdates {
   type Input = ApiInfo;
   type Subscription = Subscription<SplitSink<Stream, wrap::Message>>;
-  type Stream = Fuse<MessageStream<SplitStream<Stream>, ParsedMessage>>;
+  type Stream = Filtered<RawStream<OrderMessage>>;
 
   async fn connect(api_info: &Self::Input) -> Result<(Self::Stream, Self::Subscription), Error> {
-    fn map(result: Result<wrap::Message, WebSocketError>) -> ParsedMessage {
-      MessageResult::from(result.map(|message| match message {
-        wrap::Message::Text(string) => json_from_str::<OrderMessage>(&string),
-        wrap::Message::Binary(data) => json_from_slice::<OrderMessage>(&data),
-      }))
+    Self::connect_with(api_info, &ConnectionConfig::default()).await
+  }
+
+  fn connect_with<'a>(
+    api_info: &'a Self::Input,
+    config: &'a ConnectionConfig,
+  ) -> BoxFuture<'a, Result<(Self::Stream, Self::Subscription), Error>>
+  where
+    Self: 'a,
+  {
+    async move {
+      let (stream, subscription) =
+        connect_stream::<OrderMessage>(api_info, config, StreamType::OrderUpdates).await?;
+
+      let subscription = Subscription(subscription, Arc::default());
+      let stream = Filtered {
+        inner: stream,
+        filter: subscription.1.clone(),
+      };
+      Ok((stream, subscription))
     }
+    .boxed()
+  }
+}
 
-    let ApiInfo {
-      api_stream_url: url,
-      key_id,
-      secret,
-      ..
-    } = api_info;
-
-    let stream = connect(url).await?.map(map as MapFn);
-    let (send, recv) = stream.split();
-    let (stream, subscription) = subscribe::subscribe(recv, send);
-    let mut stream = stream.fuse();
-
-    let mut subscription = Subscription(subscription);
-    let authenticate = subscription.authenticate(key_id, secret).boxed();
-    let () = subscribe::drive::<ParsedMessage, _, _>(authenticate, &mut stream)
-      .await
-      .map_err(|result| {
-        result
-          .map(|result| Error::Json(result.unwrap_err()))
-          .map_err(Error::WebSocket)
-          .unwrap_or_else(|err| err)
-      })???;
-
-    let listen = subscription.listen().boxed();
-    let () = subscribe::drive::<ParsedMessage, _, _>(listen, &mut stream)
-      .await
-      .map_err(|result| {
-        result
-          .map(|result| Error::Json(result.unwrap_err()))
-          .map_err(Error::WebSocket)
-          .unwrap_or_else(|err| err)
-      })???;
 
-    Ok((stream, subscription))
+/// A subscription to account updates.
+///
+/// The account updates stream does not support any control operations
+/// beyond the initial subscription, but the object has to be kept
+/// alive for the stream to continue receiving updates.
+#[derive(Debug)]
+pub struct AccountSubscription<S>(
+  subscribe::Subscription<S, ParsedMessage<AccountMessage>, wrap::Message>,
+);
+
+
+/// A type used for requesting a subscription to the "account_updates"
+/// event stream.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AccountUpdates {}
+
+#[async_trait]
+impl Subscribable for AccountUpdates {
+  type Input = ApiInfo;
+  type Subscription = AccountSubscription<SplitSink<Stream<AccountMessage>, wrap::Message>>;
+  type Stream = RawStream<AccountMessage>;
+
+  async fn connect(api_info: &Self::Input) -> Result<(Self::Stream, Self::Subscription), Error> {
+    Self::connect_with(api_info, &ConnectionConfig::default()).await
+  }
+
+  fn connect_with<'a>(
+    api_info: &'a Self::Input,
+    config: &'a ConnectionConfig,
+  ) -> BoxFuture<'a, Result<(Self::Stream, Self::Subscription), Error>>
+  where
+    Self: 'a,
+  {
+    async move {
+      let (stream, subscription) =
+        connect_stream::<AccountMessage>(api_info, config, StreamType::AccountUpdates).await?;
+      Ok((stream, AccountSubscription(subscription)))
+    }
+    .boxed()
   }
 }
 
@@ -428,6 +799,8 @@ impl Subscribable for OrderUpdates {
 mod tests {
   use super::*;
 
+  use std::time::Duration;
+
   use futures::channel::oneshot::channel;
   use futures::future::ok;
   use futures::future::ready;
@@ -449,6 +822,7 @@ mod tests {
   use crate::api::v2::order_util::order_aapl;
   use crate::api::API_BASE_URL;
   use crate::websocket::test::mock_stream;
+  use crate::websocket::test::mock_stream_with;
   use crate::Client;
   use crate::Error;
 
@@ -502,7 +876,8 @@ mod tests {
       "replaced_at":null,"replaced_by":null,"replaces":null,"side":"buy",
       "status":"new","stop_price":null,"submitted_at":"2021-12-09T19:48:46.175261379Z",
       "symbol":"AAPL","time_in_force":"day","trail_percent":null,"trail_price":null,
-      "type":"limit","updated_at":"2021-12-09T19:48:46.185346448Z"
+      "type":"limit","updated_at":"2021-12-09T19:48:46.185346448Z",
+      "subtag":null,"source":"access_key"
     },"timestamp":"2021-12-09T19:48:46.182987144Z"
   }
 }"#;
@@ -511,6 +886,52 @@ mod tests {
       OrderMessage::OrderUpdate(update) => {
         assert_eq!(update.event, OrderStatus::New);
         assert_eq!(update.order.side, order::Side::Buy);
+        assert_eq!(update.order.subtag, None);
+        assert_eq!(update.order.source.as_deref(), Some("access_key"));
+        assert_eq!(
+          update.timestamp,
+          DateTime::parse_from_rfc3339("2021-12-09T19:48:46.182987144Z").unwrap()
+        );
+        assert_eq!(
+          update.execution_id,
+          Some(Uuid::parse_str("11111111-2222-3333-4444-555555555555").unwrap())
+        );
+        assert_eq!(update.price, None);
+        assert_eq!(update.quantity, None);
+      },
+      _ => panic!("Decoded unexpected message variant: {message:?}"),
+    }
+  }
+
+  /// Check that we can decode the execution details of a fill event.
+  #[test]
+  fn decode_fill_update() {
+    let json = r#"{
+  "stream":"trade_updates","data":{
+    "event":"fill","execution_id":"c4d7a5e3-b6a1-4f3c-9b0e-1c2d3e4f5a6b","order":{
+      "asset_class":"us_equity","asset_id":"b0b6dd9d-8b9b-48a9-ba46-b9d54906e415",
+      "canceled_at":null,"client_order_id":"26bd3a2c-4c7b-4b22-9d8e-3c0e8c1f2d5f",
+      "created_at":"2021-12-09T19:48:46.176628398Z","expired_at":null,
+      "extended_hours":false,"failed_at":null,"filled_at":"2021-12-09T19:48:47.612345678Z",
+      "filled_avg_price":"179.08","filled_qty":"2","hwm":null,
+      "id":"0b5f0b5e-7a0c-4a8e-9a6c-2f6a1b7e3d11","legs":null,"limit_price":null,
+      "notional":null,"order_class":"simple","order_type":"market","qty":"2",
+      "replaced_at":null,"replaced_by":null,"replaces":null,"side":"buy",
+      "status":"filled","stop_price":null,"submitted_at":"2021-12-09T19:48:46.175261379Z",
+      "symbol":"AAPL","time_in_force":"day","trail_percent":null,"trail_price":null,
+      "type":"market","updated_at":"2021-12-09T19:48:47.614567890Z"
+    },
+    "price":"179.08","qty":"2","position_qty":"12",
+    "timestamp":"2021-12-09T19:48:47.612345678Z"
+  }
+}"#;
+    let message = json_from_str::<OrderMessage>(json).unwrap();
+    match message {
+      OrderMessage::OrderUpdate(update) => {
+        assert_eq!(update.event, OrderStatus::Filled);
+        assert_eq!(update.price, Some(Num::new(17908, 100)));
+        assert_eq!(update.quantity, Some(Num::from(2)));
+        assert_eq!(update.position_quantity, Some(Num::from(12)));
       },
       _ => panic!("Decoded unexpected message variant: {message:?}"),
     }
@@ -644,6 +1065,33 @@ mod tests {
     }
   }
 
+  /// Check that we time out if the server does not respond to the
+  /// authentication request.
+  #[test(tokio::test)]
+  async fn handshake_timeout() {
+    async fn test(mut stream: WebSocketStream) -> Result<(), WebSocketError> {
+      assert_eq!(
+        stream.next().await.unwrap()?,
+        Message::Text(Utf8Bytes::from_static(AUTH_REQ)),
+      );
+      // Never respond, but wait for the client to go away.
+      let _next = stream.next().await;
+      Ok(())
+    }
+
+    let config = ConnectionConfig {
+      handshake_timeout: Some(Duration::from_millis(50)),
+      ..Default::default()
+    };
+    let err = mock_stream_with::<OrderUpdates, _, _>(test, &config)
+      .await
+      .unwrap_err();
+    match err {
+      Error::Timeout(duration) => assert_eq!(duration, Duration::from_millis(50)),
+      e => panic!("received unexpected error: {e}"),
+    }
+  }
+
   /// Check a JSON decoding error during subscription is reported
   /// correctly.
   #[test(tokio::test)]
@@ -718,6 +1166,150 @@ mod tests {
       .unwrap();
   }
 
+  /// Check that order updates not matching the subscription's filters
+  /// are not yielded by the stream.
+  #[test(tokio::test)]
+  async fn filter_updates() {
+    fn update(symbol: &str, client_order_id: &str) -> Message {
+      let json = format!(
+        r#"{{"stream":"trade_updates","data":{{"event":"new","order":{{
+  "asset_class":"us_equity","asset_id":"11111111-2222-3333-4444-555555555555",
+  "canceled_at":null,"client_order_id":"{client_order_id}",
+  "created_at":"2021-12-09T19:48:46.176628398Z","expired_at":null,
+  "extended_hours":false,"failed_at":null,"filled_at":null,
+  "filled_avg_price":null,"filled_qty":"0","hwm":null,
+  "id":"11111111-2222-3333-4444-555555555555","legs":null,"limit_price":"1",
+  "notional":null,"order_class":"simple","order_type":"limit","qty":"1",
+  "replaced_at":null,"replaced_by":null,"replaces":null,"side":"buy",
+  "status":"new","stop_price":null,"submitted_at":"2021-12-09T19:48:46.175261379Z",
+  "symbol":"{symbol}","time_in_force":"day","trail_percent":null,"trail_price":null,
+  "type":"limit","updated_at":"2021-12-09T19:48:46.185346448Z"
+}},"timestamp":"2021-12-09T19:48:46.182987144Z"}}}}"#
+      );
+      Message::Text(json.into())
+    }
+
+    let (sender, receiver) = channel();
+
+    let test = |mut stream: WebSocketStream| {
+      async move {
+        // Authentication.
+        assert_eq!(
+          stream.next().await.unwrap()?,
+          Message::Text(Utf8Bytes::from_static(AUTH_REQ)),
+        );
+        stream
+          .send(Message::Text(Utf8Bytes::from_static(AUTH_RESP)))
+          .await?;
+
+        // Subscription.
+        assert_eq!(
+          stream.next().await.unwrap()?,
+          Message::Text(Utf8Bytes::from_static(STREAM_REQ)),
+        );
+        stream
+          .send(Message::Text(Utf8Bytes::from_static(STREAM_RESP)))
+          .await?;
+
+        // Wait until the filters have been set up.
+        let () = receiver.await.unwrap();
+
+        stream.send(update("AAPL", "order-1")).await?;
+        stream.send(update("SPY", "order-2")).await?;
+        stream.send(update("AAPL", "order-3")).await?;
+        stream.send(update("MSFT", "order-2")).await?;
+        stream.send(Message::Close(None)).await?;
+        Ok(())
+      }
+    };
+
+    let (stream, mut subscription) = mock_stream::<OrderUpdates, _, _>(test).await.unwrap();
+    let () = subscription.filter_symbols(["AAPL", "SPY"]);
+    let () = subscription.filter_client_order_ids(["order-2", "order-3"]);
+    let () = sender.send(()).unwrap();
+
+    let updates = stream
+      .map_err(Error::from)
+      .map(|result| result.map(|result| result.unwrap()))
+      .try_collect::<Vec<_>>()
+      .await
+      .unwrap();
+
+    let ids = updates
+      .iter()
+      .map(|update| update.order.client_order_id.as_str())
+      .collect::<Vec<_>>();
+    assert_eq!(ids, ["order-2", "order-3"]);
+  }
+
+  /// Check that we can stream account updates.
+  #[test(tokio::test)]
+  async fn stream_account_updates() {
+    const ACCOUNT_STREAM_REQ: &str =
+      r#"{"action":"listen","data":{"streams":["account_updates"]}}"#;
+    const ACCOUNT_STREAM_RESP: &str =
+      r#"{"stream":"listening","data":{"streams":["account_updates"]}}"#;
+    const ACCOUNT_UPDATE: &str = r#"{
+  "stream":"account_updates","data":{
+    "id":"ef505a9a-2f3c-4b8a-be95-6b6f185f8a03",
+    "created_at":"2018-02-26T19:22:31Z","updated_at":"2018-02-27T18:16:24Z",
+    "deleted_at":null,"status":"ACTIVE","currency":"USD",
+    "cash":"1241.54","cash_withdrawable":"523.71"
+  }
+}"#;
+
+    let (sender, receiver) = channel();
+
+    let test = |mut stream: WebSocketStream| {
+      async move {
+        // Authentication.
+        assert_eq!(
+          stream.next().await.unwrap()?,
+          Message::Text(Utf8Bytes::from_static(AUTH_REQ)),
+        );
+        stream
+          .send(Message::Text(Utf8Bytes::from_static(AUTH_RESP)))
+          .await?;
+
+        // Subscription.
+        assert_eq!(
+          stream.next().await.unwrap()?,
+          Message::Text(Utf8Bytes::from_static(ACCOUNT_STREAM_REQ)),
+        );
+        stream
+          .send(Message::Text(Utf8Bytes::from_static(ACCOUNT_STREAM_RESP)))
+          .await?;
+
+        // Wait until the subscription has been established.
+        let () = receiver.await.unwrap();
+
+        stream
+          .send(Message::Text(Utf8Bytes::from_static(ACCOUNT_UPDATE)))
+          .await?;
+        stream.send(Message::Close(None)).await?;
+        Ok(())
+      }
+    };
+
+    let (stream, _subscription) = mock_stream::<AccountUpdates, _, _>(test).await.unwrap();
+    let () = sender.send(()).unwrap();
+
+    let updates = stream
+      .map_err(Error::from)
+      .map(|result| result.map(|result| result.unwrap()))
+      .try_collect::<Vec<_>>()
+      .await
+      .unwrap();
+
+    assert_eq!(updates.len(), 1);
+    let update = &updates[0];
+    assert_eq!(update.status, account::Status::Active);
+    assert_eq!(update.currency, "USD");
+    assert_eq!(update.cash, Num::new(124154, 100));
+    assert_eq!(update.withdrawable_cash, Num::new(52371, 100));
+    assert_eq!(update.deleted_at, None);
+  }
+
   /// Verify that ping websocket messages are responded to with pongs.
   #[test(tokio::test)]
   async fn ping_pong() {
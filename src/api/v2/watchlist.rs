@@ -14,6 +14,7 @@ use serde::Serialize;
 
 use serde_json::from_slice as from_json;
 use serde_json::to_vec as to_json;
+use serde_urlencoded::to_string as to_query;
 
 use uuid::Uuid;
 
@@ -89,10 +90,16 @@ pub struct CreateReqInit {
   pub _non_exhaustive: (),
 }
 
+Setters! {
+  CreateReqInit {
+    symbols: Vec<String>,
+  }
+}
+
 impl CreateReqInit {
   /// Create a [`CreateReq`] from a `CreateReqInit`.
   #[inline]
-  pub fn init<S>(self, name: S) -> CreateReq
+  pub fn init<S>(&self, name: S) -> CreateReq
   where
     S: Into<String>,
   {
@@ -103,7 +110,7 @@ impl CreateReqInit {
 
     CreateReq {
       name: name.into(),
-      symbols,
+      symbols: symbols.clone(),
       _non_exhaustive: (),
     }
   }
@@ -116,6 +123,15 @@ pub type UpdateReq = CreateReq;
 pub type UpdateReqInit = CreateReqInit;
 
 
+/// The body of a request to add an asset to a watchlist.
+#[derive(Serialize)]
+struct AddAssetBody<'s> {
+  /// The symbol of the asset to add.
+  #[serde(rename = "symbol")]
+  symbol: &'s str,
+}
+
+
 Endpoint! {
   /// The representation of a POST request to the /v2/watchlists endpoint.
   pub Create(CreateReq),
@@ -166,6 +182,31 @@ Endpoint! {
 }
 
 
+Endpoint! {
+  /// The representation of a GET request to the
+  /// /v2/watchlists:by_name endpoint.
+  pub GetByName(String),
+  Ok => Watchlist, [
+    /// The watchlist object with the given name was retrieved
+    /// successfully.
+    /* 200 */ OK,
+  ],
+  Err => GetByNameError, [
+    /// No watchlist was found with the given name.
+    /* 404 */ NOT_FOUND => NotFound,
+  ]
+
+  #[inline]
+  fn path(_input: &Self::Input) -> Str {
+    "/v2/watchlists:by_name".into()
+  }
+
+  fn query(input: &Self::Input) -> Result<Option<Str>, Self::ConversionError> {
+    Ok(Some(to_query([("name", input)])?.into()))
+  }
+}
+
+
 Endpoint! {
   /// The representation of a PUT request to the
   /// /v2/watchlists/{watchlist-id} endpoint.
@@ -201,6 +242,67 @@ Endpoint! {
 }
 
 
+Endpoint! {
+  /// The representation of a POST request to the
+  /// /v2/watchlists/{watchlist-id} endpoint, adding the asset with the
+  /// given symbol to the watchlist.
+  pub AddAsset((Id, String)),
+  Ok => Watchlist, [
+    /// The asset was added to the watchlist successfully.
+    /* 200 */ OK,
+  ],
+  Err => AddAssetError, [
+    /// No watchlist was found with the given ID.
+    /* 404 */ NOT_FOUND => NotFound,
+    /// The symbol is not valid.
+    /* 422 */ UNPROCESSABLE_ENTITY => InvalidInput,
+  ]
+
+  fn path(input: &Self::Input) -> Str {
+    let (id, _) = input;
+    format!("/v2/watchlists/{}", id.as_simple()).into()
+  }
+
+  #[inline]
+  fn method() -> Method {
+    Method::POST
+  }
+
+  fn body(input: &Self::Input) -> Result<Option<Bytes>, Self::ConversionError> {
+    let (_, symbol) = input;
+    let json = to_json(&AddAssetBody { symbol })?;
+    let bytes = Bytes::from(json);
+    Ok(Some(bytes))
+  }
+}
+
+
+Endpoint! {
+  /// The representation of a DELETE request to the
+  /// /v2/watchlists/{watchlist-id}/{symbol} endpoint.
+  pub RemoveAsset((Id, String)),
+  Ok => Watchlist, [
+    /// The asset was removed from the watchlist successfully.
+    /* 200 */ OK,
+  ],
+  Err => RemoveAssetError, [
+    /// No watchlist was found with the given ID or the asset is not
+    /// part of it.
+    /* 404 */ NOT_FOUND => NotFound,
+  ]
+
+  fn path(input: &Self::Input) -> Str {
+    let (id, symbol) = input;
+    format!("/v2/watchlists/{}/{}", id.as_simple(), symbol).into()
+  }
+
+  #[inline]
+  fn method() -> Method {
+    Method::DELETE
+  }
+}
+
+
 EndpointNoParse! {
   /// The representation of a DELETE request to the
   /// /v2/watchlists/{watchlist-id} endpoint.
@@ -364,6 +466,64 @@ mod tests {
     assert_eq!(symbols, vec!["AMZN", "SPY"]);
   }
 
+  /// Check that we can retrieve a watchlist by name.
+  #[test(tokio::test)]
+  async fn get_by_name() {
+    let api_info = ApiInfo::from_env().unwrap();
+    let client = Client::new(api_info);
+    let name = Uuid::new_v4().to_string();
+    let request = CreateReqInit {
+      symbols: vec!["AAPL".to_string()],
+      ..Default::default()
+    }
+    .init(&name);
+
+    let created = client.issue::<Create>(&request).await.unwrap();
+    let result = client.issue::<GetByName>(&name).await;
+    let () = client.issue::<Delete>(&created.id).await.unwrap();
+
+    let watchlist = result.unwrap();
+    assert_eq!(watchlist.id, created.id);
+    assert_eq!(watchlist.name, name);
+
+    let err = client.issue::<GetByName>(&name).await.unwrap_err();
+    match err {
+      RequestError::Endpoint(GetByNameError::NotFound(_)) => (),
+      _ => panic!("Received unexpected error: {err:?}"),
+    };
+  }
+
+  /// Check that we can add assets to and remove them from a watchlist.
+  #[test(tokio::test)]
+  async fn add_remove_asset() {
+    let api_info = ApiInfo::from_env().unwrap();
+    let client = Client::new(api_info);
+    let request = CreateReqInit {
+      symbols: vec!["AAPL".to_string()],
+      ..Default::default()
+    }
+    .init(Uuid::new_v4().to_string());
+
+    let created = client.issue::<Create>(&request).await.unwrap();
+    let added = client
+      .issue::<AddAsset>(&(created.id, "SPY".to_string()))
+      .await;
+    let removed = client
+      .issue::<RemoveAsset>(&(created.id, "AAPL".to_string()))
+      .await;
+    let () = client.issue::<Delete>(&created.id).await.unwrap();
+
+    let symbols = |watchlist: Watchlist| {
+      watchlist
+        .assets
+        .into_iter()
+        .map(|asset| asset.symbol)
+        .collect::<Vec<_>>()
+    };
+    assert_eq!(symbols(added.unwrap()), vec!["AAPL", "SPY"]);
+    assert_eq!(symbols(removed.unwrap()), vec!["SPY"]);
+  }
+
   /// Verify that we report the appropriate error when attempting to
   /// delete a watchlist that does not exist.
   #[test(tokio::test)]
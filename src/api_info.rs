@@ -1,12 +1,19 @@
 // Copyright (C) 2019-2023 The apca Developers
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+use std::collections::HashMap;
 use std::env::var_os;
 use std::ffi::OsString;
+use std::fs::read_to_string;
+use std::path::Path;
+use std::path::PathBuf;
 
 use url::Url;
 
 use crate::api::API_BASE_URL;
+use crate::api::LIVE_API_BASE_URL;
+#[cfg(feature = "broker")]
+use crate::broker::BROKER_BASE_URL;
 use crate::data::DATA_BASE_URL;
 use crate::data::DATA_STREAM_BASE_URL;
 use crate::Error;
@@ -15,10 +22,18 @@ use crate::Error;
 const ENV_API_BASE_URL: &str = "APCA_API_BASE_URL";
 /// The URL of the websocket stream portion of the Trading API to use.
 const ENV_API_STREAM_URL: &str = "APCA_API_STREAM_URL";
+/// The URL of the websocket stream portion of the data API to use.
+const ENV_DATA_STREAM_BASE_URL: &str = "APCA_DATA_STREAM_BASE_URL";
 /// The environment variable representing the key ID.
 const ENV_KEY_ID: &str = "APCA_API_KEY_ID";
 /// The environment variable representing the secret key.
 const ENV_SECRET: &str = "APCA_API_SECRET_KEY";
+/// The environment variable representing the key ID to use for the
+/// data APIs.
+const ENV_DATA_KEY_ID: &str = "APCA_DATA_KEY_ID";
+/// The environment variable representing the secret key to use for
+/// the data APIs.
+const ENV_DATA_SECRET: &str = "APCA_DATA_SECRET_KEY";
 
 
 /// Convert a Trading API base URL into the corresponding one for
@@ -33,6 +48,91 @@ fn make_api_stream_url(base_url: Url) -> Result<Url, Error> {
 }
 
 
+/// The environment an [`ApiInfo`] object refers to.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum Environment {
+  /// The paper trading environment, working with simulated funds.
+  Paper,
+  /// The live trading environment, working with real funds.
+  Live,
+  /// An environment not known to the crate, e.g., a local mock
+  /// server.
+  Custom,
+}
+
+impl Environment {
+  /// Infer the environment from the base URL of the Trading API.
+  fn from_api_base_url(url: &Url) -> Self {
+    match url.host_str() {
+      Some("paper-api.alpaca.markets") => Self::Paper,
+      Some("api.alpaca.markets") => Self::Live,
+      _ => Self::Custom,
+    }
+  }
+}
+
+
+/// Expand a leading `~` component of a path to the user's home
+/// directory.
+fn expand_home(path: &Path) -> PathBuf {
+  match path.strip_prefix("~") {
+    Ok(rest) => match var_os("HOME") {
+      Some(home) => PathBuf::from(home).join(rest),
+      None => path.to_path_buf(),
+    },
+    Err(..) => path.to_path_buf(),
+  }
+}
+
+
+/// Retrieve the key-value pairs of the given profile from an INI
+/// style configuration.
+fn parse_profile<'c>(config: &'c str, profile: &str) -> Result<HashMap<&'c str, &'c str>, Error> {
+  let mut section = None;
+  let mut found = false;
+  let mut values = HashMap::new();
+
+  for (idx, line) in config.lines().enumerate() {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+      continue
+    }
+
+    if let Some(name) = line
+      .strip_prefix('[')
+      .and_then(|line| line.strip_suffix(']'))
+    {
+      let name = name.trim();
+      found |= name == profile;
+      section = Some(name);
+      continue
+    }
+
+    let (key, value) = line.split_once('=').ok_or_else(|| {
+      Error::Str(format!("line {}: expected a key-value pair or profile", idx + 1).into())
+    })?;
+    let section = section.ok_or_else(|| {
+      Error::Str(format!("line {}: key-value pair outside of a profile", idx + 1).into())
+    })?;
+
+    if section == profile {
+      let value = value.trim();
+      let value = ['"', '\'']
+        .iter()
+        .find_map(|quote| value.strip_prefix(*quote)?.strip_suffix(*quote))
+        .unwrap_or(value);
+      let _prev = values.insert(key.trim(), value);
+    }
+  }
+
+  if !found {
+    return Err(Error::Str(format!("profile `{profile}` not found").into()))
+  }
+  Ok(values)
+}
+
+
 /// An object encapsulating the information used for working with the
 /// Alpaca API.
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -41,18 +141,52 @@ pub struct ApiInfo {
   /// The base URL for the Trading API.
   pub api_base_url: Url,
   /// The websocket stream URL for the Trading API.
+  ///
+  /// Unless provided explicitly, the URL is derived from
+  /// `api_base_url`.
   pub api_stream_url: Url,
   /// The base URL for data retrieval.
   pub data_base_url: Url,
   /// The websocket base URL for streaming of data.
   pub data_stream_base_url: Url,
+  /// The base URL for the Broker API.
+  #[cfg(feature = "broker")]
+  pub broker_base_url: Url,
   /// The key ID to use for authentication.
   pub key_id: String,
   /// The secret to use for authentication.
   pub secret: String,
+  /// The key ID to use for authentication against the data APIs, if
+  /// it differs from `key_id`.
+  ///
+  /// The key ID is only used if `data_secret` is set as well.
+  pub data_key_id: Option<String>,
+  /// The secret to use for authentication against the data APIs, if
+  /// it differs from `secret`.
+  ///
+  /// The secret is only used if `data_key_id` is set as well.
+  pub data_secret: Option<String>,
 }
 
 impl ApiInfo {
+  /// Create an `ApiInfo` for working with the paper trading
+  /// environment.
+  pub fn paper(key_id: impl ToString, secret: impl ToString) -> Self {
+    // SANITY: The paper trading URL is known to be valid.
+    Self::from_parts(API_BASE_URL, key_id, secret).unwrap()
+  }
+
+  /// Create an `ApiInfo` for working with the live trading
+  /// environment.
+  ///
+  /// Consider guarding against accidentally placing orders with real
+  /// funds by means of the `guard_live_trading` method of the builder
+  /// returned by [`Client::builder`][crate::Client::builder].
+  pub fn live(key_id: impl ToString, secret: impl ToString) -> Self {
+    // SANITY: The live trading URL is known to be valid.
+    Self::from_parts(LIVE_API_BASE_URL, key_id, secret).unwrap()
+  }
+
   /// Create an `ApiInfo` from the required data. Note that using this
   /// constructor the websocket URL will be inferred based on the base
   /// URL provided.
@@ -67,7 +201,6 @@ impl ApiInfo {
   ) -> Result<Self, Error> {
     let api_base_url = Url::parse(api_base_url.as_ref())?;
     let api_stream_url = make_api_stream_url(api_base_url.clone())?;
-
     Ok(Self {
       api_base_url,
       api_stream_url,
@@ -76,11 +209,160 @@ impl ApiInfo {
       // fine.
       data_base_url: Url::parse(DATA_BASE_URL).unwrap(),
       data_stream_base_url: Url::parse(DATA_STREAM_BASE_URL).unwrap(),
+      #[cfg(feature = "broker")]
+      broker_base_url: Url::parse(BROKER_BASE_URL).unwrap(),
       key_id: key_id.to_string(),
       secret: secret.to_string(),
+      data_key_id: None,
+      data_secret: None,
     })
   }
 
+  /// Retrieve the environment the Trading API base URL refers to.
+  #[inline]
+  pub fn environment(&self) -> Environment {
+    Environment::from_api_base_url(&self.api_base_url)
+  }
+
+  /// Use the provided credentials for the data APIs, instead of the
+  /// ones used for the Trading API.
+  pub fn with_data_credentials(mut self, key_id: impl ToString, secret: impl ToString) -> Self {
+    self.data_key_id = Some(key_id.to_string());
+    self.data_secret = Some(secret.to_string());
+    self
+  }
+
+  /// Use the provided URL for streaming order updates, instead of the
+  /// one derived from the Trading API base URL.
+  ///
+  /// This may be necessary, for example, when connecting through a
+  /// proxy.
+  pub fn with_api_stream_url(mut self, url: Url) -> Self {
+    self.api_stream_url = url;
+    self
+  }
+
+  /// Use the provided base URL for streaming realtime market data,
+  /// e.g., to connect to a sandbox environment.
+  ///
+  /// Sources selecting a path below the base URL, such as
+  /// [`Test`][crate::data::v2::stream::Test], are resolved relative to
+  /// it.
+  pub fn with_data_stream_base_url(mut self, url: Url) -> Self {
+    self.data_stream_base_url = url;
+    self
+  }
+
+  /// Retrieve the key ID and secret to use for authentication against
+  /// the data APIs.
+  ///
+  /// The data API specific key ID and secret are only used if both
+  /// are set. Otherwise the Trading API credentials are used, as mixing
+  /// the two pairs can never authenticate successfully.
+  pub fn data_credentials(&self) -> (&str, &str) {
+    match (&self.data_key_id, &self.data_secret) {
+      (Some(key_id), Some(secret)) => (key_id, secret),
+      _ => (&self.key_id, &self.secret),
+    }
+  }
+
+  /// Create an `ApiInfo` object from the given profile of a
+  /// configuration file.
+  ///
+  /// The file is expected to be in INI style (which, for the subset
+  /// used, is also valid TOML), with each profile representing a
+  /// section. A leading `~` in `path` is expanded to the user's home
+  /// directory. E.g.,
+  /// ```ini
+  /// [paper]
+  /// key_id = "XXXXXXXXXXXXXXXXXXXX"
+  /// secret = "YYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYY"
+  ///
+  /// [live]
+  /// key_id = "XXXXXXXXXXXXXXXXXXXX"
+  /// secret = "YYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYYY"
+  /// api_base_url = "https://api.alpaca.markets"
+  /// ```
+  ///
+  /// Besides the mandatory `key_id` and `secret` keys, a profile may
+  /// contain `api_base_url`, `api_stream_url`, `data_base_url`,
+  /// `data_stream_base_url`, and `broker_base_url` (the latter
+  /// requiring the `broker` feature) for overriding the respective
+  /// default URLs. As for [`from_parts`][Self::from_parts], the stream
+  /// URL of the Trading API is inferred from its base URL when not
+  /// provided. Separate credentials for the data APIs can be provided
+  /// by means of the `data_key_id` and `data_secret` keys, both of
+  /// which need to be present for them to be used.
+  pub fn from_file(path: impl AsRef<Path>, profile: &str) -> Result<Self, Error> {
+    let path = expand_home(path.as_ref());
+    let config = read_to_string(&path).map_err(|err| {
+      Error::Str(
+        format!(
+          "failed to read configuration file {}: {err}",
+          path.display()
+        )
+        .into(),
+      )
+    })?;
+
+    Self::from_config(&config, profile).map_err(|err| match err {
+      Error::Str(message) => Error::Str(format!("{}: {message}", path.display()).into()),
+      err => err,
+    })
+  }
+
+  /// Create an `ApiInfo` object from the given profile of an INI style
+  /// configuration.
+  fn from_config(config: &str, profile: &str) -> Result<Self, Error> {
+    let mut values = parse_profile(config, profile)?;
+    let mut take = |key| {
+      values.remove(key).ok_or_else(|| {
+        Error::Str(format!("profile `{profile}` does not contain `{key}` key").into())
+      })
+    };
+
+    let key_id = take("key_id")?;
+    let secret = take("secret")?;
+    let api_base_url = take("api_base_url").unwrap_or(API_BASE_URL);
+    let api_stream_url = take("api_stream_url").ok();
+    let data_base_url = take("data_base_url").ok();
+    let data_stream_base_url = take("data_stream_base_url").ok();
+    let data_key_id = take("data_key_id").ok();
+    let data_secret = take("data_secret").ok();
+    #[cfg(feature = "broker")]
+    let broker_base_url = take("broker_base_url").ok();
+    #[cfg(not(feature = "broker"))]
+    if take("broker_base_url").is_ok() {
+      return Err(Error::Str(
+        format!("profile `{profile}` key `broker_base_url` requires the `broker` feature").into(),
+      ))
+    }
+
+    if let Some(key) = values.keys().next() {
+      return Err(Error::Str(
+        format!("profile `{profile}` contains unknown key `{key}`").into(),
+      ))
+    }
+
+    let mut api_info = Self::from_parts(api_base_url, key_id, secret)?;
+    if let Some(url) = api_stream_url {
+      api_info.api_stream_url = Url::parse(url)?;
+    }
+    if let Some(url) = data_base_url {
+      api_info.data_base_url = Url::parse(url)?;
+    }
+    if let Some(url) = data_stream_base_url {
+      api_info.data_stream_base_url = Url::parse(url)?;
+    }
+    #[cfg(feature = "broker")]
+    if let Some(url) = broker_base_url {
+      api_info.broker_base_url = Url::parse(url)?;
+    }
+    api_info.data_key_id = data_key_id.map(str::to_string);
+    api_info.data_secret = data_secret.map(str::to_string);
+    Ok(api_info)
+  }
+
   /// Create an `ApiInfo` object with information from the environment.
   ///
   /// This constructor retrieves API related information from the
@@ -94,10 +376,16 @@ impl ApiInfo {
   ///   `APCA_API_KEY_ID` variable
   /// - the Alpaca account secret is retrieved from the
   ///   `APCA_API_SECRET_KEY` variable
+  /// - the optional key ID and secret to use for the data APIs are
+  ///   retrieved from the `APCA_DATA_KEY_ID` and
+  ///   `APCA_DATA_SECRET_KEY` variables, respectively, and only used
+  ///   if both are set
+  /// - the optional base URL for streaming realtime market data is
+  ///   retrieved from the `APCA_DATA_STREAM_BASE_URL` variable
   ///
   /// # Notes
-  /// - Neither of the two data APIs can be configured via the
-  ///   environment currently; defaults will be used
+  /// - The base URL of the data API cannot be configured via the
+  ///   environment currently; the default will be used
   #[allow(unused_qualifications)]
   pub fn from_env() -> Result<Self, Error> {
     let api_base_url = var_os(ENV_API_BASE_URL)
@@ -138,6 +426,32 @@ impl ApiInfo {
         Error::Str(format!("{ENV_SECRET} environment variable is not a valid string").into())
       })?;
 
+    let data_key_id = var_os(ENV_DATA_KEY_ID)
+      .map(|key_id| {
+        key_id.into_string().map_err(|_| {
+          Error::Str(format!("{ENV_DATA_KEY_ID} environment variable is not a valid string").into())
+        })
+      })
+      .transpose()?;
+
+    let data_secret = var_os(ENV_DATA_SECRET)
+      .map(|secret| {
+        secret.into_string().map_err(|_| {
+          Error::Str(format!("{ENV_DATA_SECRET} environment variable is not a valid string").into())
+        })
+      })
+      .transpose()?;
+
+    let data_stream_base_url = var_os(ENV_DATA_STREAM_BASE_URL)
+      .unwrap_or_else(|| OsString::from(DATA_STREAM_BASE_URL))
+      .into_string()
+      .map_err(|_| {
+        Error::Str(
+          format!("{ENV_DATA_STREAM_BASE_URL} environment variable is not a valid string").into(),
+        )
+      })?;
+    let data_stream_base_url = Url::parse(&data_stream_base_url)?;
+
     Ok(Self {
       api_base_url,
       api_stream_url,
@@ -145,9 +459,13 @@ impl ApiInfo {
       // which we know can be parsed successfully, so unwrapping is
       // fine.
       data_base_url: Url::parse(DATA_BASE_URL).unwrap(),
-      data_stream_base_url: Url::parse(DATA_STREAM_BASE_URL).unwrap(),
+      data_stream_base_url,
+      #[cfg(feature = "broker")]
+      broker_base_url: Url::parse(BROKER_BASE_URL).unwrap(),
       key_id,
       secret,
+      data_key_id,
+      data_secret,
     })
   }
 }
@@ -170,5 +488,158 @@ mod tests {
     assert_eq!(api_info.api_base_url.as_str(), api_base_url);
     assert_eq!(api_info.key_id, key_id);
     assert_eq!(api_info.secret, secret);
+    assert_eq!(api_info.environment(), Environment::Paper);
+    assert_eq!(api_info.data_credentials(), (key_id, secret));
+  }
+
+  /// Check that separate credentials are used for the data APIs, if
+  /// both the key ID and secret are set.
+  #[test]
+  fn data_credentials() {
+    let api_info = ApiInfo::paper("KEY", "SECRET").with_data_credentials("DATA-KEY", "DATA-SECRET");
+    assert_eq!(api_info.key_id, "KEY");
+    assert_eq!(api_info.data_credentials(), ("DATA-KEY", "DATA-SECRET"));
+
+    let mut api_info = ApiInfo::paper("KEY", "SECRET");
+    api_info.data_secret = Some("DATA-SECRET".to_string());
+    assert_eq!(api_info.data_credentials(), ("KEY", "SECRET"));
+
+    let mut api_info = ApiInfo::paper("KEY", "SECRET");
+    api_info.data_key_id = Some("DATA-KEY".to_string());
+    assert_eq!(api_info.data_credentials(), ("KEY", "SECRET"));
+  }
+
+  /// Check that we can override the URL used for streaming order
+  /// updates.
+  #[test]
+  fn api_stream_url() {
+    let api_info = ApiInfo::live("KEY", "SECRET");
+    assert_eq!(
+      api_info.api_stream_url.as_str(),
+      "wss://api.alpaca.markets/stream"
+    );
+
+    let url = Url::parse("wss://proxy.example.com/stream").unwrap();
+    let api_info = api_info.with_api_stream_url(url.clone());
+    assert_eq!(api_info.api_stream_url, url);
+    assert_eq!(
+      api_info.api_base_url.as_str(),
+      "https://api.alpaca.markets/"
+    );
+  }
+
+  /// Check that we can override the base URL used for streaming
+  /// market data.
+  #[test]
+  fn data_stream_base_url() {
+    let url = Url::parse("wss://stream.data.sandbox.alpaca.markets").unwrap();
+    let api_info = ApiInfo::paper("KEY", "SECRET").with_data_stream_base_url(url.clone());
+    assert_eq!(api_info.data_stream_base_url, url);
+    assert_eq!(
+      api_info.api_stream_url,
+      ApiInfo::paper("KEY", "SECRET").api_stream_url
+    );
+  }
+
+  /// Check that we can create an [`ApiInfo`] object from a profile of a
+  /// configuration.
+  #[test]
+  fn from_config() {
+    let config = r#"
+# Credentials for the various accounts.
+[paper]
+key_id = "PAPER-KEY"
+secret = 'PAPER-SECRET'
+
+[ live ]
+key_id = LIVE-KEY
+secret = LIVE-SECRET
+api_base_url = "https://api.alpaca.markets"
+data_stream_base_url = "wss://stream.data.sandbox.alpaca.markets"
+data_key_id = LIVE-DATA-KEY
+data_secret = LIVE-DATA-SECRET
+"#;
+
+    let paper = ApiInfo::from_config(config, "paper").unwrap();
+    assert_eq!(paper.key_id, "PAPER-KEY");
+    assert_eq!(paper.secret, "PAPER-SECRET");
+    assert_eq!(paper.environment(), Environment::Paper);
+    assert_eq!(paper.data_credentials(), ("PAPER-KEY", "PAPER-SECRET"));
+    assert_eq!(
+      paper.data_stream_base_url,
+      Url::parse(DATA_STREAM_BASE_URL).unwrap()
+    );
+
+    let live = ApiInfo::from_config(config, "live").unwrap();
+    assert_eq!(live.key_id, "LIVE-KEY");
+    assert_eq!(live.environment(), Environment::Live);
+    assert_eq!(
+      live.data_credentials(),
+      ("LIVE-DATA-KEY", "LIVE-DATA-SECRET")
+    );
+    assert_eq!(
+      live.api_stream_url.as_str(),
+      "wss://api.alpaca.markets/stream"
+    );
+    assert_eq!(
+      live.data_stream_base_url.as_str(),
+      "wss://stream.data.sandbox.alpaca.markets/"
+    );
+
+    let err = ApiInfo::from_config(config, "other").unwrap_err();
+    assert_eq!(err.to_string(), "profile `other` not found");
+
+    let config = "[paper]\nkey_id = KEY\nsecrets = SECRET\n";
+    let err = ApiInfo::from_config(config, "paper").unwrap_err();
+    assert_eq!(
+      err.to_string(),
+      "profile `paper` does not contain `secret` key"
+    );
+
+    let config = "[paper]\nkey_id = KEY\nsecret = SECRET\nfoo = bar\n";
+    let err = ApiInfo::from_config(config, "paper").unwrap_err();
+    assert_eq!(
+      err.to_string(),
+      "profile `paper` contains unknown key `foo`"
+    );
+
+    let config = "[paper]\nkey_id = KEY\nsecret = SECRET\nbroker_base_url = https://example.com\n";
+    let result = ApiInfo::from_config(config, "paper");
+    #[cfg(feature = "broker")]
+    assert_eq!(
+      result.unwrap().broker_base_url.as_str(),
+      "https://example.com/"
+    );
+    #[cfg(not(feature = "broker"))]
+    assert_eq!(
+      result.unwrap_err().to_string(),
+      "profile `paper` key `broker_base_url` requires the `broker` feature"
+    );
+  }
+
+  /// Check that the environment presets use the expected URLs.
+  #[test]
+  fn environment_presets() {
+    let paper = ApiInfo::paper("key", "secret");
+    assert_eq!(
+      paper.api_base_url.as_str(),
+      "https://paper-api.alpaca.markets/"
+    );
+    assert_eq!(paper.environment(), Environment::Paper);
+
+    let live = ApiInfo::live("key", "secret");
+    assert_eq!(live.api_base_url.as_str(), "https://api.alpaca.markets/");
+    assert_eq!(
+      live.api_stream_url.as_str(),
+      "wss://api.alpaca.markets/stream"
+    );
+    assert_eq!(live.environment(), Environment::Live);
+
+    let custom = ApiInfo::from_parts("http://localhost:8080", "key", "secret").unwrap();
+    assert_eq!(custom.environment(), Environment::Custom);
+
+    let mut paper = paper;
+    paper.api_base_url = Url::parse(LIVE_API_BASE_URL).unwrap();
+    assert_eq!(paper.environment(), Environment::Live);
   }
 }
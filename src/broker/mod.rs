@@ -0,0 +1,8 @@
+// Copyright (C) 2024 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/// Definitions for the first version of the Alpaca Broker API.
+pub mod v1;
+
+/// The Broker API base URL used for the sandbox environment.
+pub(crate) const BROKER_BASE_URL: &str = "https://broker-api.sandbox.alpaca.markets";
@@ -0,0 +1,572 @@
+// Copyright (C) 2024 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use chrono::DateTime;
+use chrono::NaiveDate;
+use chrono::Utc;
+
+use http::Method;
+use http_endpoint::Bytes;
+
+use num_decimal::Num;
+
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::to_vec as to_json;
+use serde_urlencoded::to_string as to_query;
+
+use crate::api::v2::account::Id;
+use crate::api::v2::asset;
+use crate::util::enum_slice_to_str;
+use crate::Str;
+
+
+/// An enumeration of the various states a brokerage account can be
+/// in.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub enum Status {
+  /// The account application has been submitted for review.
+  #[serde(rename = "SUBMITTED")]
+  Submitted,
+  /// Additional information is required to process the application.
+  #[serde(rename = "ACTION_REQUIRED")]
+  ActionRequired,
+  /// The account information is being updated.
+  #[serde(rename = "ACCOUNT_UPDATED")]
+  Updating,
+  /// The final account approval is pending.
+  #[serde(rename = "APPROVAL_PENDING")]
+  ApprovalPending,
+  /// The account application has been approved and the account is
+  /// awaiting activation.
+  #[serde(rename = "APPROVED")]
+  Approved,
+  /// The account application has been rejected.
+  #[serde(rename = "REJECTED")]
+  Rejected,
+  /// The account is active for trading.
+  #[serde(rename = "ACTIVE")]
+  Active,
+  /// The account is not (yet) enabled.
+  #[serde(rename = "INACTIVE")]
+  Inactive,
+  /// The account has been disabled.
+  #[serde(rename = "DISABLED")]
+  Disabled,
+  /// The account has been closed.
+  #[serde(rename = "ACCOUNT_CLOSED")]
+  Closed,
+  /// Any other account status that we have not accounted for.
+  ///
+  /// Note that having any such status should be considered a bug.
+  #[doc(hidden)]
+  #[serde(other, rename(serialize = "unknown"))]
+  Unknown,
+}
+
+
+/// The source of the funds an account is funded with.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub enum FundingSource {
+  /// Income from employment.
+  #[serde(rename = "employment_income")]
+  EmploymentIncome,
+  /// Income from investments.
+  #[serde(rename = "investments")]
+  Investments,
+  /// An inheritance.
+  #[serde(rename = "inheritance")]
+  Inheritance,
+  /// Income from a business.
+  #[serde(rename = "business_income")]
+  BusinessIncome,
+  /// Savings.
+  #[serde(rename = "savings")]
+  Savings,
+  /// Funds provided by family.
+  #[serde(rename = "family")]
+  Family,
+  /// Any other funding source that we have not accounted for.
+  ///
+  /// Note that having any such funding source should be considered a
+  /// bug.
+  #[doc(hidden)]
+  #[serde(other, rename(serialize = "unknown"))]
+  Unknown,
+}
+
+
+/// An enumeration of the agreements an account holder may sign.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub enum AgreementType {
+  /// The customer agreement.
+  #[serde(rename = "customer_agreement")]
+  Customer,
+  /// The account agreement.
+  #[serde(rename = "account_agreement")]
+  Account,
+  /// The margin agreement.
+  #[serde(rename = "margin_agreement")]
+  Margin,
+  /// The crypto agreement.
+  #[serde(rename = "crypto_agreement")]
+  Crypto,
+  /// Any other agreement that we have not accounted for.
+  ///
+  /// Note that having any such agreement should be considered a bug.
+  #[doc(hidden)]
+  #[serde(other, rename(serialize = "unknown"))]
+  Unknown,
+}
+
+
+/// The contact information of an account holder.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Contact {
+  /// The e-mail address of the account holder.
+  #[serde(rename = "email_address")]
+  pub email_address: String,
+  /// The phone number of the account holder, including the country
+  /// code.
+  #[serde(rename = "phone_number")]
+  pub phone_number: String,
+  /// The lines making up the street address.
+  #[serde(rename = "street_address")]
+  pub street_address: Vec<String>,
+  /// The city of the address.
+  #[serde(rename = "city")]
+  pub city: String,
+  /// The state of the address.
+  #[serde(rename = "state", skip_serializing_if = "Option::is_none")]
+  pub state: Option<String>,
+  /// The postal code of the address.
+  #[serde(rename = "postal_code", skip_serializing_if = "Option::is_none")]
+  pub postal_code: Option<String>,
+  /// The ISO 3166-1 alpha-3 code of the country of the address.
+  #[serde(rename = "country", skip_serializing_if = "Option::is_none")]
+  pub country: Option<String>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// The identity of an account holder.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Identity {
+  /// The given name of the account holder.
+  #[serde(rename = "given_name")]
+  pub given_name: String,
+  /// The family name of the account holder.
+  #[serde(rename = "family_name")]
+  pub family_name: String,
+  /// The date of birth of the account holder.
+  #[serde(rename = "date_of_birth")]
+  pub date_of_birth: NaiveDate,
+  /// The tax ID of the account holder.
+  #[serde(rename = "tax_id", skip_serializing_if = "Option::is_none")]
+  pub tax_id: Option<String>,
+  /// The type of `tax_id`, e.g., "USA_SSN".
+  #[serde(rename = "tax_id_type", skip_serializing_if = "Option::is_none")]
+  pub tax_id_type: Option<String>,
+  /// The ISO 3166-1 alpha-3 code of the country the account holder is
+  /// a citizen of.
+  #[serde(
+    rename = "country_of_citizenship",
+    skip_serializing_if = "Option::is_none"
+  )]
+  pub country_of_citizenship: Option<String>,
+  /// The ISO 3166-1 alpha-3 code of the country the account holder
+  /// was born in.
+  #[serde(rename = "country_of_birth", skip_serializing_if = "Option::is_none")]
+  pub country_of_birth: Option<String>,
+  /// The ISO 3166-1 alpha-3 code of the country the account holder
+  /// is a tax resident of.
+  #[serde(rename = "country_of_tax_residence")]
+  pub country_of_tax_residence: String,
+  /// The sources of the funds the account is funded with.
+  #[serde(rename = "funding_source")]
+  pub funding_source: Vec<FundingSource>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// Regulatory disclosures of an account holder.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Disclosures {
+  /// Whether the account holder is a control person of a publicly
+  /// traded company.
+  #[serde(rename = "is_control_person")]
+  pub is_control_person: bool,
+  /// Whether the account holder is affiliated with an exchange or
+  /// FINRA.
+  #[serde(rename = "is_affiliated_exchange_or_finra")]
+  pub is_affiliated_exchange_or_finra: bool,
+  /// Whether the account holder is a politically exposed person.
+  #[serde(rename = "is_politically_exposed")]
+  pub is_politically_exposed: bool,
+  /// Whether an immediate family member of the account holder is
+  /// politically exposed.
+  #[serde(rename = "immediate_family_exposed")]
+  pub immediate_family_exposed: bool,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// An agreement signed by an account holder.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Agreement {
+  /// The agreement that was signed.
+  #[serde(rename = "agreement")]
+  pub agreement: AgreementType,
+  /// The time the agreement was signed at.
+  #[serde(rename = "signed_at")]
+  pub signed_at: DateTime<Utc>,
+  /// The IP address the agreement was signed from.
+  #[serde(rename = "ip_address")]
+  pub ip_address: String,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// A brokerage account.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct Account {
+  /// The account's ID.
+  #[serde(rename = "id")]
+  pub id: Id,
+  /// The human readable account number.
+  #[serde(rename = "account_number")]
+  pub account_number: String,
+  /// The account's status.
+  #[serde(rename = "status")]
+  pub status: Status,
+  /// The account's status with respect to crypto trading, if
+  /// reported.
+  #[serde(rename = "crypto_status")]
+  pub crypto_status: Option<Status>,
+  /// The currency the account uses.
+  #[serde(rename = "currency")]
+  pub currency: String,
+  /// Equity as of the previous trading day.
+  #[serde(rename = "last_equity")]
+  pub last_equity: Num,
+  /// Timestamp this account was created at.
+  #[serde(rename = "created_at")]
+  pub created_at: DateTime<Utc>,
+  /// The asset classes the account may trade.
+  #[serde(rename = "enabled_assets", default)]
+  pub enabled_assets: Vec<asset::Class>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// A POST request to be made to the /v1/accounts endpoint.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct CreateReq {
+  /// The contact information of the account holder.
+  #[serde(rename = "contact")]
+  pub contact: Contact,
+  /// The identity of the account holder.
+  #[serde(rename = "identity")]
+  pub identity: Identity,
+  /// The regulatory disclosures of the account holder.
+  #[serde(rename = "disclosures")]
+  pub disclosures: Disclosures,
+  /// The agreements signed by the account holder.
+  #[serde(rename = "agreements")]
+  pub agreements: Vec<Agreement>,
+  /// The asset classes the account may trade.
+  ///
+  /// If empty, the server decides on the enabled asset classes.
+  #[serde(rename = "enabled_assets", skip_serializing_if = "Vec::is_empty")]
+  pub enabled_assets: Vec<asset::Class>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// A helper for initializing [`CreateReq`] objects.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CreateReqInit {
+  /// See `CreateReq::disclosures`.
+  pub disclosures: Disclosures,
+  /// See `CreateReq::enabled_assets`.
+  pub enabled_assets: Vec<asset::Class>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+Setters! {
+  CreateReqInit {
+    disclosures: Disclosures,
+    enabled_assets: Vec<asset::Class>,
+  }
+}
+
+impl CreateReqInit {
+  /// Create a [`CreateReq`] from a `CreateReqInit`.
+  #[inline]
+  pub fn init<A>(&self, contact: Contact, identity: Identity, agreements: A) -> CreateReq
+  where
+    A: Into<Vec<Agreement>>,
+  {
+    CreateReq {
+      contact,
+      identity,
+      disclosures: self.disclosures,
+      agreements: agreements.into(),
+      enabled_assets: self.enabled_assets.clone(),
+      _non_exhaustive: (),
+    }
+  }
+}
+
+
+/// A GET request to be made to the /v1/accounts endpoint.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct ListReq {
+  /// A pattern matched against various account details, such as the
+  /// account number, names, and e-mail address.
+  #[serde(rename = "query", skip_serializing_if = "Option::is_none")]
+  pub query: Option<String>,
+  /// Only include accounts in one of the given states.
+  #[serde(rename = "status", serialize_with = "enum_slice_to_str")]
+  pub status: Vec<Status>,
+  /// Only include accounts created after this time.
+  #[serde(rename = "created_after", skip_serializing_if = "Option::is_none")]
+  pub created_after: Option<DateTime<Utc>>,
+  /// Only include accounts created before this time.
+  #[serde(rename = "created_before", skip_serializing_if = "Option::is_none")]
+  pub created_before: Option<DateTime<Utc>>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+Endpoint! {
+  /// The representation of a POST request to the /v1/accounts endpoint.
+  pub Create(CreateReq),
+  Api => Broker,
+  Ok => Account, [
+    /// The account was created successfully.
+    /* 200 */ OK,
+  ],
+  Err => CreateError, [
+    /// Some of the provided data was invalid.
+    /* 400 */ BAD_REQUEST => InvalidInput,
+    /// An account with the given e-mail address already exists.
+    /* 409 */ CONFLICT => AlreadyExists,
+    /// Some of the provided data could not be processed.
+    /* 422 */ UNPROCESSABLE_ENTITY => Unprocessable,
+  ]
+
+  #[inline]
+  fn path(_input: &Self::Input) -> Str {
+    "/v1/accounts".into()
+  }
+
+  #[inline]
+  fn method() -> Method {
+    Method::POST
+  }
+
+  fn body(input: &Self::Input) -> Result<Option<Bytes>, Self::ConversionError> {
+    let json = to_json(input)?;
+    let bytes = Bytes::from(json);
+    Ok(Some(bytes))
+  }
+}
+
+
+Endpoint! {
+  /// The representation of a GET request to the /v1/accounts/{account-id}
+  /// endpoint.
+  pub Get(Id),
+  Api => Broker,
+  Ok => Account, [
+    /// The account was retrieved successfully.
+    /* 200 */ OK,
+  ],
+  Err => GetError, [
+    /// No account was found with the given ID.
+    /* 404 */ NOT_FOUND => NotFound,
+  ]
+
+  fn path(input: &Self::Input) -> Str {
+    format!("/v1/accounts/{}", input.as_hyphenated()).into()
+  }
+}
+
+
+Endpoint! {
+  /// The representation of a GET request to the /v1/accounts endpoint.
+  pub List(ListReq),
+  Api => Broker,
+  Ok => Vec<Account>, [
+    /// The accounts were retrieved successfully.
+    /* 200 */ OK,
+  ],
+  Err => ListError, [
+    /// Some of the provided data was invalid.
+    /* 400 */ BAD_REQUEST => InvalidInput,
+  ]
+
+  #[inline]
+  fn path(_input: &Self::Input) -> Str {
+    "/v1/accounts".into()
+  }
+
+  fn query(input: &Self::Input) -> Result<Option<Str>, Self::ConversionError> {
+    Ok(Some(to_query(input)?.into()))
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use http_endpoint::Endpoint;
+
+  use serde_json::from_slice as from_json_slice;
+  use serde_json::from_str as from_json;
+  use serde_json::Value;
+
+
+  /// Check that we serialize an account creation request as expected.
+  #[test]
+  fn serialize_create_request() {
+    let contact = Contact {
+      email_address: "john.doe@example.com".to_string(),
+      phone_number: "555-666-7788".to_string(),
+      street_address: vec!["20 N San Mateo Dr".to_string()],
+      city: "San Mateo".to_string(),
+      state: Some("CA".to_string()),
+      postal_code: Some("94401".to_string()),
+      country: Some("USA".to_string()),
+      ..Default::default()
+    };
+    let identity = Identity {
+      given_name: "John".to_string(),
+      family_name: "Doe".to_string(),
+      date_of_birth: NaiveDate::from_ymd_opt(1990, 1, 1).unwrap(),
+      tax_id: Some("666-55-4321".to_string()),
+      tax_id_type: Some("USA_SSN".to_string()),
+      country_of_citizenship: None,
+      country_of_birth: None,
+      country_of_tax_residence: "USA".to_string(),
+      funding_source: vec![FundingSource::EmploymentIncome],
+      _non_exhaustive: (),
+    };
+    let agreement = Agreement {
+      agreement: AgreementType::Customer,
+      signed_at: DateTime::parse_from_rfc3339("2020-09-11T18:13:44Z")
+        .unwrap()
+        .with_timezone(&Utc),
+      ip_address: "185.13.21.99".to_string(),
+      _non_exhaustive: (),
+    };
+    let request = CreateReqInit::default().init(contact, identity, [agreement]);
+
+    let json = from_json_slice::<Value>(&Create::body(&request).unwrap().unwrap()).unwrap();
+    let expected = from_json::<Value>(
+      r#"{
+  "contact": {
+    "email_address": "john.doe@example.com",
+    "phone_number": "555-666-7788",
+    "street_address": ["20 N San Mateo Dr"],
+    "city": "San Mateo",
+    "state": "CA",
+    "postal_code": "94401",
+    "country": "USA"
+  },
+  "identity": {
+    "given_name": "John",
+    "family_name": "Doe",
+    "date_of_birth": "1990-01-01",
+    "tax_id": "666-55-4321",
+    "tax_id_type": "USA_SSN",
+    "country_of_tax_residence": "USA",
+    "funding_source": ["employment_income"]
+  },
+  "disclosures": {
+    "is_control_person": false,
+    "is_affiliated_exchange_or_finra": false,
+    "is_politically_exposed": false,
+    "immediate_family_exposed": false
+  },
+  "agreements": [
+    {
+      "agreement": "customer_agreement",
+      "signed_at": "2020-09-11T18:13:44Z",
+      "ip_address": "185.13.21.99"
+    }
+  ]
+}"#,
+    )
+    .unwrap();
+    assert_eq!(json, expected);
+  }
+
+  /// Verify that we can parse a reference account.
+  #[test]
+  fn parse_reference_account() {
+    let response = r#"{
+  "id": "b0b6dd9d-8b9b-48a9-ba46-b9d54906e415",
+  "account_number": "935142145",
+  "status": "APPROVED",
+  "crypto_status": "INACTIVE",
+  "currency": "USD",
+  "last_equity": "0",
+  "created_at": "2021-05-17T09:53:17.588248Z",
+  "account_type": "trading",
+  "enabled_assets": ["us_equity"]
+}"#;
+
+    let account = from_json::<Account>(response).unwrap();
+    assert_eq!(account.account_number, "935142145");
+    assert_eq!(account.status, Status::Approved);
+    assert_eq!(account.crypto_status, Some(Status::Inactive));
+    assert_eq!(account.last_equity, Num::from(0));
+    assert_eq!(account.enabled_assets, vec![asset::Class::UsEquity]);
+  }
+
+  /// Check that we serialize a list request as expected.
+  #[test]
+  fn serialize_list_request() {
+    let request = ListReq::default();
+    assert_eq!(to_query(&request).unwrap(), "");
+
+    let request = ListReq {
+      query: Some("doe".to_string()),
+      status: vec![Status::Active, Status::Approved],
+      ..Default::default()
+    };
+    assert_eq!(
+      to_query(&request).unwrap(),
+      "query=doe&status=ACTIVE%2CAPPROVED"
+    );
+  }
+}
@@ -0,0 +1,341 @@
+// Copyright (C) 2024 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::ops::Deref;
+
+use chrono::DateTime;
+use chrono::Utc;
+
+use http::Method;
+use http_endpoint::Bytes;
+
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::from_slice as from_json;
+use serde_json::to_vec as to_json;
+
+use uuid::Uuid;
+
+use crate::api::v2::account;
+use crate::Str;
+
+
+/// An ID uniquely identifying an ACH relationship.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct Id(pub Uuid);
+
+impl Deref for Id {
+  type Target = Uuid;
+
+  #[inline]
+  fn deref(&self) -> &Self::Target {
+    &self.0
+  }
+}
+
+
+/// An enumeration of the various states an ACH relationship can be in.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub enum Status {
+  /// The relationship has been queued for processing.
+  #[serde(rename = "QUEUED")]
+  Queued,
+  /// The relationship is pending approval.
+  #[serde(rename = "PENDING")]
+  Pending,
+  /// The relationship has been approved and can be used for
+  /// transfers.
+  #[serde(rename = "APPROVED")]
+  Approved,
+  /// Cancellation of the relationship has been requested.
+  #[serde(rename = "CANCEL_REQUESTED")]
+  CancelRequested,
+  /// The relationship has been canceled.
+  #[serde(rename = "CANCELED")]
+  Canceled,
+  /// Any other status that we have not accounted for.
+  ///
+  /// Note that having any such status should be considered a bug.
+  #[doc(hidden)]
+  #[serde(other, rename(serialize = "unknown"))]
+  Unknown,
+}
+
+
+/// The type of a bank account.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub enum BankAccountType {
+  /// A checking account.
+  #[serde(rename = "CHECKING")]
+  Checking,
+  /// A savings account.
+  #[serde(rename = "SAVINGS")]
+  Savings,
+  /// Any other account type that we have not accounted for.
+  ///
+  /// Note that having any such type should be considered a bug.
+  #[doc(hidden)]
+  #[serde(other, rename(serialize = "unknown"))]
+  Unknown,
+}
+
+
+/// An ACH relationship between a brokerage account and a bank
+/// account.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct AchRelationship {
+  /// The relationship's ID.
+  #[serde(rename = "id")]
+  pub id: Id,
+  /// The ID of the brokerage account the relationship belongs to.
+  #[serde(rename = "account_id")]
+  pub account_id: account::Id,
+  /// Timestamp this relationship was created at.
+  #[serde(rename = "created_at")]
+  pub created_at: DateTime<Utc>,
+  /// Timestamp this relationship was last updated at.
+  #[serde(rename = "updated_at")]
+  pub updated_at: DateTime<Utc>,
+  /// The relationship's status.
+  #[serde(rename = "status")]
+  pub status: Status,
+  /// The name of the bank account's owner.
+  #[serde(rename = "account_owner_name")]
+  pub account_owner_name: String,
+  /// The type of the bank account.
+  #[serde(rename = "bank_account_type")]
+  pub bank_account_type: BankAccountType,
+  /// The bank account number.
+  #[serde(rename = "bank_account_number")]
+  pub bank_account_number: String,
+  /// The routing number of the bank.
+  #[serde(rename = "bank_routing_number")]
+  pub bank_routing_number: String,
+  /// A user-defined name for the relationship.
+  #[serde(rename = "nickname")]
+  pub nickname: Option<String>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// A request to create an ACH relationship.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct CreateReq {
+  /// The name of the bank account's owner.
+  #[serde(rename = "account_owner_name")]
+  pub account_owner_name: String,
+  /// The type of the bank account.
+  #[serde(rename = "bank_account_type")]
+  pub bank_account_type: BankAccountType,
+  /// The bank account number.
+  #[serde(rename = "bank_account_number")]
+  pub bank_account_number: String,
+  /// The routing number of the bank.
+  #[serde(rename = "bank_routing_number")]
+  pub bank_routing_number: String,
+  /// A user-defined name for the relationship.
+  #[serde(rename = "nickname", skip_serializing_if = "Option::is_none")]
+  pub nickname: Option<String>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// A helper for initializing [`CreateReq`] objects.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CreateReqInit {
+  /// See `CreateReq::nickname`.
+  pub nickname: Option<String>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+Setters! {
+  CreateReqInit {
+    nickname: Option<String>,
+  }
+}
+
+impl CreateReqInit {
+  /// Create a [`CreateReq`] from a `CreateReqInit`.
+  #[inline]
+  pub fn init<O, A, R>(
+    &self,
+    account_owner_name: O,
+    bank_account_type: BankAccountType,
+    bank_account_number: A,
+    bank_routing_number: R,
+  ) -> CreateReq
+  where
+    O: Into<String>,
+    A: Into<String>,
+    R: Into<String>,
+  {
+    CreateReq {
+      account_owner_name: account_owner_name.into(),
+      bank_account_type,
+      bank_account_number: bank_account_number.into(),
+      bank_routing_number: bank_routing_number.into(),
+      nickname: self.nickname.clone(),
+      _non_exhaustive: (),
+    }
+  }
+}
+
+
+Endpoint! {
+  /// The representation of a POST request to the
+  /// /v1/accounts/{account-id}/ach_relationships endpoint.
+  pub Create((account::Id, CreateReq)),
+  Api => Broker,
+  Ok => AchRelationship, [
+    /// The ACH relationship was created successfully.
+    /* 200 */ OK,
+  ],
+  Err => CreateError, [
+    /// Some of the provided data was invalid.
+    /* 400 */ BAD_REQUEST => InvalidInput,
+    /// No account was found with the given ID.
+    /* 404 */ NOT_FOUND => NotFound,
+    /// The account already has an active ACH relationship.
+    /* 409 */ CONFLICT => AlreadyExists,
+  ]
+
+  fn path(input: &Self::Input) -> Str {
+    let (account_id, _) = input;
+    format!("/v1/accounts/{}/ach_relationships", account_id.as_hyphenated()).into()
+  }
+
+  #[inline]
+  fn method() -> Method {
+    Method::POST
+  }
+
+  fn body(input: &Self::Input) -> Result<Option<Bytes>, Self::ConversionError> {
+    let (_, request) = input;
+    let json = to_json(request)?;
+    let bytes = Bytes::from(json);
+    Ok(Some(bytes))
+  }
+}
+
+
+Endpoint! {
+  /// The representation of a GET request to the
+  /// /v1/accounts/{account-id}/ach_relationships endpoint.
+  pub List(account::Id),
+  Api => Broker,
+  Ok => Vec<AchRelationship>, [
+    /// The ACH relationships were retrieved successfully.
+    /* 200 */ OK,
+  ],
+  Err => ListError, [
+    /// No account was found with the given ID.
+    /* 404 */ NOT_FOUND => NotFound,
+  ]
+
+  fn path(input: &Self::Input) -> Str {
+    format!("/v1/accounts/{}/ach_relationships", input.as_hyphenated()).into()
+  }
+}
+
+
+EndpointNoParse! {
+  /// The representation of a DELETE request to the
+  /// /v1/accounts/{account-id}/ach_relationships/{ach-relationship-id}
+  /// endpoint.
+  pub Delete((account::Id, Id)),
+  Api => Broker,
+  Ok => (), [
+    /// The ACH relationship was deleted successfully.
+    /* 204 */ NO_CONTENT,
+  ],
+  Err => DeleteError, [
+    /// No account or ACH relationship was found with the given ID.
+    /* 404 */ NOT_FOUND => NotFound,
+  ]
+
+  #[inline]
+  fn method() -> Method {
+    Method::DELETE
+  }
+
+  fn path(input: &Self::Input) -> Str {
+    let (account_id, id) = input;
+    format!(
+      "/v1/accounts/{}/ach_relationships/{}",
+      account_id.as_hyphenated(),
+      id.as_hyphenated()
+    )
+    .into()
+  }
+
+  #[inline]
+  fn parse(body: &[u8]) -> Result<Self::Output, Self::ConversionError> {
+    debug_assert_eq!(body, b"");
+    Ok(())
+  }
+
+  fn parse_err(body: &[u8]) -> Result<Self::ApiError, Vec<u8>> {
+    from_json::<Self::ApiError>(body).map_err(|_| body.to_vec())
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use serde_json::from_str as from_json;
+  use serde_json::to_string as to_json;
+
+
+  /// Check that we can parse a reference ACH relationship.
+  #[test]
+  fn parse_reference_relationship() {
+    let response = r#"{
+  "id": "c9b420e0-ae4e-4f39-bcbf-649b407c2129",
+  "account_id": "b0b6dd9d-8b9b-48a9-ba46-b9d54906e415",
+  "created_at": "2021-05-17T09:54:58.114433723Z",
+  "updated_at": "2021-05-17T09:54:58.114433723Z",
+  "status": "QUEUED",
+  "account_owner_name": "Awesome Alpaca",
+  "bank_account_type": "CHECKING",
+  "bank_account_number": "32131231abc",
+  "bank_routing_number": "121000358",
+  "nickname": "Bank of America Checking"
+}"#;
+
+    let relationship = from_json::<AchRelationship>(response).unwrap();
+    assert_eq!(relationship.status, Status::Queued);
+    assert_eq!(relationship.bank_account_type, BankAccountType::Checking);
+    assert_eq!(relationship.bank_routing_number, "121000358");
+    assert_eq!(
+      relationship.nickname.as_deref(),
+      Some("Bank of America Checking")
+    );
+  }
+
+  /// Verify that we serialize a creation request as expected.
+  #[test]
+  fn serialize_create_request() {
+    let request = CreateReqInit::default().init(
+      "Awesome Alpaca",
+      BankAccountType::Savings,
+      "32131231abc",
+      "121000358",
+    );
+
+    let json = to_json(&request).unwrap();
+    let expected = r#"{"account_owner_name":"Awesome Alpaca","bank_account_type":"SAVINGS","bank_account_number":"32131231abc","bank_routing_number":"121000358"}"#;
+    assert_eq!(json, expected);
+  }
+}
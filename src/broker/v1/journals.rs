@@ -0,0 +1,350 @@
+// Copyright (C) 2024 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::ops::Deref;
+
+use chrono::NaiveDate;
+
+use http::Method;
+use http_endpoint::Bytes;
+
+use num_decimal::Num;
+
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::to_vec as to_json;
+use serde_urlencoded::to_string as to_query;
+
+use uuid::Uuid;
+
+use crate::api::v2::account;
+use crate::Str;
+
+
+/// An ID uniquely identifying a journal.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct Id(pub Uuid);
+
+impl Deref for Id {
+  type Target = Uuid;
+
+  #[inline]
+  fn deref(&self) -> &Self::Target {
+    &self.0
+  }
+}
+
+
+/// The kind of entry a journal represents.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub enum EntryType {
+  /// A journal moving cash between accounts.
+  #[serde(rename = "JNLC")]
+  Cash,
+  /// A journal moving securities between accounts.
+  #[serde(rename = "JNLS")]
+  Securities,
+  /// Any other entry type that we have not accounted for.
+  ///
+  /// Note that having any such type should be considered a bug.
+  #[doc(hidden)]
+  #[serde(other, rename(serialize = "unknown"))]
+  Unknown,
+}
+
+
+/// An enumeration of the various states a journal can be in.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub enum Status {
+  /// The journal has been queued for processing.
+  #[serde(rename = "queued")]
+  Queued,
+  /// The journal has been sent to the clearing firm.
+  #[serde(rename = "sent_to_clearing")]
+  SentToClearing,
+  /// The journal is pending.
+  #[serde(rename = "pending")]
+  Pending,
+  /// The journal has been executed.
+  #[serde(rename = "executed")]
+  Executed,
+  /// The journal has been rejected.
+  #[serde(rename = "rejected")]
+  Rejected,
+  /// The journal has been canceled.
+  #[serde(rename = "canceled")]
+  Canceled,
+  /// The journal has been refused.
+  #[serde(rename = "refused")]
+  Refused,
+  /// The journal has been deleted.
+  #[serde(rename = "deleted")]
+  Deleted,
+  /// Any other status that we have not accounted for.
+  ///
+  /// Note that having any such status should be considered a bug.
+  #[doc(hidden)]
+  #[serde(other, rename(serialize = "unknown"))]
+  Unknown,
+}
+
+
+/// A journal, moving cash or securities from one account to another.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct Journal {
+  /// The journal's ID.
+  #[serde(rename = "id")]
+  pub id: Id,
+  /// The kind of entry the journal represents.
+  #[serde(rename = "entry_type")]
+  pub entry_type: EntryType,
+  /// The ID of the account cash or securities are moved from.
+  #[serde(rename = "from_account")]
+  pub from_account: account::Id,
+  /// The ID of the account cash or securities are moved to.
+  #[serde(rename = "to_account")]
+  pub to_account: account::Id,
+  /// The symbol of the securities being moved, for securities
+  /// journals.
+  #[serde(rename = "symbol")]
+  pub symbol: Option<String>,
+  /// The quantity of securities being moved, for securities journals.
+  #[serde(rename = "qty")]
+  pub quantity: Option<Num>,
+  /// The price of the securities being moved, for securities
+  /// journals.
+  #[serde(rename = "price")]
+  pub price: Option<Num>,
+  /// The amount of cash being moved.
+  #[serde(rename = "net_amount")]
+  pub net_amount: Num,
+  /// A user-provided description of the journal.
+  #[serde(rename = "description")]
+  pub description: Option<String>,
+  /// The date the journal settles at.
+  #[serde(rename = "settle_date")]
+  pub settle_date: Option<NaiveDate>,
+  /// The date the journal was entered into the system.
+  #[serde(rename = "system_date")]
+  pub system_date: Option<NaiveDate>,
+  /// The journal's status.
+  #[serde(rename = "status")]
+  pub status: Status,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// A POST request to be made to the /v1/journals endpoint.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct CreateReq {
+  /// The kind of entry to create.
+  #[serde(rename = "entry_type")]
+  pub entry_type: EntryType,
+  /// The ID of the account to move cash or securities from.
+  #[serde(rename = "from_account")]
+  pub from_account: account::Id,
+  /// The ID of the account to move cash or securities to.
+  #[serde(rename = "to_account")]
+  pub to_account: account::Id,
+  /// The amount of cash to move, for cash journals.
+  #[serde(rename = "amount", skip_serializing_if = "Option::is_none")]
+  pub amount: Option<Num>,
+  /// The symbol of the securities to move, for securities journals.
+  #[serde(rename = "symbol", skip_serializing_if = "Option::is_none")]
+  pub symbol: Option<String>,
+  /// The quantity of securities to move, for securities journals.
+  #[serde(rename = "qty", skip_serializing_if = "Option::is_none")]
+  pub quantity: Option<Num>,
+  /// A description of the journal.
+  #[serde(rename = "description", skip_serializing_if = "Option::is_none")]
+  pub description: Option<String>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+impl CreateReq {
+  /// Create a request for a journal moving the given amount of cash.
+  pub fn cash(from_account: account::Id, to_account: account::Id, amount: Num) -> Self {
+    Self {
+      entry_type: EntryType::Cash,
+      from_account,
+      to_account,
+      amount: Some(amount),
+      symbol: None,
+      quantity: None,
+      description: None,
+      _non_exhaustive: (),
+    }
+  }
+
+  /// Create a request for a journal moving the given quantity of
+  /// securities.
+  pub fn securities<S>(
+    from_account: account::Id,
+    to_account: account::Id,
+    symbol: S,
+    quantity: Num,
+  ) -> Self
+  where
+    S: Into<String>,
+  {
+    Self {
+      entry_type: EntryType::Securities,
+      from_account,
+      to_account,
+      amount: None,
+      symbol: Some(symbol.into()),
+      quantity: Some(quantity),
+      description: None,
+      _non_exhaustive: (),
+    }
+  }
+}
+
+
+/// A GET request to be made to the /v1/journals endpoint.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct ListReq {
+  /// Only include journals settling after this date.
+  #[serde(rename = "after", skip_serializing_if = "Option::is_none")]
+  pub after: Option<NaiveDate>,
+  /// Only include journals settling before this date.
+  #[serde(rename = "before", skip_serializing_if = "Option::is_none")]
+  pub before: Option<NaiveDate>,
+  /// Only include journals with the given status.
+  #[serde(rename = "status", skip_serializing_if = "Option::is_none")]
+  pub status: Option<Status>,
+  /// Only include journals of the given entry type.
+  #[serde(rename = "entry_type", skip_serializing_if = "Option::is_none")]
+  pub entry_type: Option<EntryType>,
+  /// Only include journals moving cash or securities to the given
+  /// account.
+  #[serde(rename = "to_account", skip_serializing_if = "Option::is_none")]
+  pub to_account: Option<account::Id>,
+  /// Only include journals moving cash or securities from the given
+  /// account.
+  #[serde(rename = "from_account", skip_serializing_if = "Option::is_none")]
+  pub from_account: Option<account::Id>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+Endpoint! {
+  /// The representation of a POST request to the /v1/journals endpoint.
+  pub Create(CreateReq),
+  Api => Broker,
+  Ok => Journal, [
+    /// The journal was created successfully.
+    /* 200 */ OK,
+  ],
+  Err => CreateError, [
+    /// Some of the provided data was invalid.
+    /* 400 */ BAD_REQUEST => InvalidInput,
+    /// One of the accounts was not found.
+    /* 404 */ NOT_FOUND => NotFound,
+  ]
+
+  #[inline]
+  fn path(_input: &Self::Input) -> Str {
+    "/v1/journals".into()
+  }
+
+  #[inline]
+  fn method() -> Method {
+    Method::POST
+  }
+
+  fn body(input: &Self::Input) -> Result<Option<Bytes>, Self::ConversionError> {
+    let json = to_json(input)?;
+    let bytes = Bytes::from(json);
+    Ok(Some(bytes))
+  }
+}
+
+
+Endpoint! {
+  /// The representation of a GET request to the /v1/journals endpoint.
+  pub List(ListReq),
+  Api => Broker,
+  Ok => Vec<Journal>, [
+    /// The journals were retrieved successfully.
+    /* 200 */ OK,
+  ],
+  Err => ListError, [
+    /// Some of the provided data was invalid.
+    /* 400 */ BAD_REQUEST => InvalidInput,
+  ]
+
+  #[inline]
+  fn path(_input: &Self::Input) -> Str {
+    "/v1/journals".into()
+  }
+
+  fn query(input: &Self::Input) -> Result<Option<Str>, Self::ConversionError> {
+    Ok(Some(to_query(input)?.into()))
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use serde_json::from_str as from_json;
+  use serde_json::to_string as to_json;
+
+
+  /// Check that we can parse a reference cash journal.
+  #[test]
+  fn parse_reference_journal() {
+    let response = r#"{
+  "id": "c7d2ada2-1ea4-4bc7-a2da-c5f6845ff2a0",
+  "entry_type": "JNLC",
+  "from_account": "8f8c8cee-2591-4f83-be12-82c659b5e747",
+  "to_account": "b0b6dd9d-8b9b-48a9-ba46-b9d54906e415",
+  "symbol": null,
+  "qty": null,
+  "price": null,
+  "status": "executed",
+  "settle_date": "2021-05-18",
+  "system_date": "2021-05-17",
+  "net_amount": "100",
+  "description": "initial funding"
+}"#;
+
+    let journal = from_json::<Journal>(response).unwrap();
+    assert_eq!(journal.entry_type, EntryType::Cash);
+    assert_eq!(journal.status, Status::Executed);
+    assert_eq!(journal.net_amount, Num::from(100));
+    assert_eq!(journal.quantity, None);
+    assert_eq!(
+      journal.settle_date,
+      Some(NaiveDate::from_ymd_opt(2021, 5, 18).unwrap())
+    );
+  }
+
+  /// Verify that we serialize journal creation requests as expected.
+  #[test]
+  fn serialize_create_request() {
+    let from = account::Id(Uuid::parse_str("8f8c8cee-2591-4f83-be12-82c659b5e747").unwrap());
+    let to = account::Id(Uuid::parse_str("b0b6dd9d-8b9b-48a9-ba46-b9d54906e415").unwrap());
+
+    let request = CreateReq::cash(from, to, Num::from(100));
+    let expected = r#"{"entry_type":"JNLC","from_account":"8f8c8cee-2591-4f83-be12-82c659b5e747","to_account":"b0b6dd9d-8b9b-48a9-ba46-b9d54906e415","amount":"100"}"#;
+    assert_eq!(to_json(&request).unwrap(), expected);
+
+    let request = CreateReq::securities(from, to, "AAPL", Num::from(2));
+    let expected = r#"{"entry_type":"JNLS","from_account":"8f8c8cee-2591-4f83-be12-82c659b5e747","to_account":"b0b6dd9d-8b9b-48a9-ba46-b9d54906e415","symbol":"AAPL","qty":"2"}"#;
+    assert_eq!(to_json(&request).unwrap(), expected);
+  }
+}
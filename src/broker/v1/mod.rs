@@ -0,0 +1,11 @@
+// Copyright (C) 2024 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/// Functionality for creating, listing, and retrieving accounts.
+pub mod accounts;
+/// Functionality for managing the ACH relationships of accounts.
+pub mod ach_relationships;
+/// Functionality for moving cash and securities between accounts.
+pub mod journals;
+/// Functionality for funding accounts and withdrawing from them.
+pub mod transfers;
@@ -0,0 +1,297 @@
+// Copyright (C) 2024 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::ops::Deref;
+
+use chrono::DateTime;
+use chrono::Utc;
+
+use http::Method;
+use http_endpoint::Bytes;
+
+use num_decimal::Num;
+
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::to_vec as to_json;
+
+use uuid::Uuid;
+
+use crate::api::v2::account;
+use crate::broker::v1::ach_relationships;
+use crate::Str;
+
+
+/// An ID uniquely identifying a transfer.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct Id(pub Uuid);
+
+impl Deref for Id {
+  type Target = Uuid;
+
+  #[inline]
+  fn deref(&self) -> &Self::Target {
+    &self.0
+  }
+}
+
+
+/// The means by which funds are transferred.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub enum Type {
+  /// A transfer via an ACH relationship.
+  #[serde(rename = "ach")]
+  Ach,
+  /// A wire transfer.
+  #[serde(rename = "wire")]
+  Wire,
+  /// Any other transfer type that we have not accounted for.
+  ///
+  /// Note that having any such type should be considered a bug.
+  #[doc(hidden)]
+  #[serde(other, rename(serialize = "unknown"))]
+  Unknown,
+}
+
+
+/// The direction of a transfer, from the perspective of the brokerage
+/// account.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub enum Direction {
+  /// Funds are deposited into the account.
+  #[serde(rename = "INCOMING")]
+  Incoming,
+  /// Funds are withdrawn from the account.
+  #[serde(rename = "OUTGOING")]
+  Outgoing,
+  /// Any other direction that we have not accounted for.
+  ///
+  /// Note that having any such direction should be considered a bug.
+  #[doc(hidden)]
+  #[serde(other, rename(serialize = "unknown"))]
+  Unknown,
+}
+
+
+/// An enumeration of the various states a transfer can be in.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub enum Status {
+  /// The transfer has been queued for processing.
+  #[serde(rename = "QUEUED")]
+  Queued,
+  /// The transfer is pending approval.
+  #[serde(rename = "APPROVAL_PENDING")]
+  ApprovalPending,
+  /// The transfer is pending.
+  #[serde(rename = "PENDING")]
+  Pending,
+  /// The transfer has been sent to the clearing firm.
+  #[serde(rename = "SENT_TO_CLEARING")]
+  SentToClearing,
+  /// The transfer has been approved.
+  #[serde(rename = "APPROVED")]
+  Approved,
+  /// The transfer has been completed.
+  #[serde(rename = "COMPLETE")]
+  Complete,
+  /// The transfer has been rejected.
+  #[serde(rename = "REJECTED")]
+  Rejected,
+  /// The transfer has been canceled.
+  #[serde(rename = "CANCELED")]
+  Canceled,
+  /// The transfer has been returned.
+  #[serde(rename = "RETURNED")]
+  Returned,
+  /// Any other status that we have not accounted for.
+  ///
+  /// Note that having any such status should be considered a bug.
+  #[doc(hidden)]
+  #[serde(other, rename(serialize = "unknown"))]
+  Unknown,
+}
+
+
+/// A transfer of funds into or out of a brokerage account.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct Transfer {
+  /// The transfer's ID.
+  #[serde(rename = "id")]
+  pub id: Id,
+  /// The ID of the relationship funds are transferred through.
+  #[serde(rename = "relationship_id")]
+  pub relationship_id: Option<ach_relationships::Id>,
+  /// The ID of the brokerage account the transfer belongs to.
+  #[serde(rename = "account_id")]
+  pub account_id: account::Id,
+  /// The means by which funds are transferred.
+  #[serde(rename = "type")]
+  pub type_: Type,
+  /// The transfer's status.
+  #[serde(rename = "status")]
+  pub status: Status,
+  /// The amount of money being transferred.
+  #[serde(rename = "amount")]
+  pub amount: Num,
+  /// The direction of the transfer.
+  #[serde(rename = "direction")]
+  pub direction: Direction,
+  /// Timestamp this transfer was created at.
+  #[serde(rename = "created_at")]
+  pub created_at: DateTime<Utc>,
+  /// Timestamp this transfer was last updated at.
+  #[serde(rename = "updated_at")]
+  pub updated_at: Option<DateTime<Utc>>,
+  /// Timestamp this transfer expires at, if not completed by then.
+  #[serde(rename = "expires_at")]
+  pub expires_at: Option<DateTime<Utc>>,
+  /// An explanation of the transfer's status, if any.
+  #[serde(rename = "reason")]
+  pub reason: Option<String>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// A request to create a transfer.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct CreateReq {
+  /// The means by which funds are transferred.
+  #[serde(rename = "transfer_type")]
+  pub type_: Type,
+  /// The ID of the ACH relationship to transfer funds through.
+  #[serde(rename = "relationship_id")]
+  pub relationship_id: ach_relationships::Id,
+  /// The amount of money to transfer.
+  #[serde(rename = "amount")]
+  pub amount: Num,
+  /// The direction of the transfer.
+  #[serde(rename = "direction")]
+  pub direction: Direction,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+impl CreateReq {
+  /// Create a request for an ACH transfer through the given
+  /// relationship.
+  #[inline]
+  pub fn ach(relationship_id: ach_relationships::Id, amount: Num, direction: Direction) -> Self {
+    Self {
+      type_: Type::Ach,
+      relationship_id,
+      amount,
+      direction,
+      _non_exhaustive: (),
+    }
+  }
+}
+
+
+Endpoint! {
+  /// The representation of a POST request to the
+  /// /v1/accounts/{account-id}/transfers endpoint.
+  pub Create((account::Id, CreateReq)),
+  Api => Broker,
+  Ok => Transfer, [
+    /// The transfer was created successfully.
+    /* 200 */ OK,
+  ],
+  Err => CreateError, [
+    /// Some of the provided data was invalid.
+    /* 400 */ BAD_REQUEST => InvalidInput,
+    /// No account was found with the given ID.
+    /* 404 */ NOT_FOUND => NotFound,
+  ]
+
+  fn path(input: &Self::Input) -> Str {
+    let (account_id, _) = input;
+    format!("/v1/accounts/{}/transfers", account_id.as_hyphenated()).into()
+  }
+
+  #[inline]
+  fn method() -> Method {
+    Method::POST
+  }
+
+  fn body(input: &Self::Input) -> Result<Option<Bytes>, Self::ConversionError> {
+    let (_, request) = input;
+    let json = to_json(request)?;
+    let bytes = Bytes::from(json);
+    Ok(Some(bytes))
+  }
+}
+
+
+Endpoint! {
+  /// The representation of a GET request to the
+  /// /v1/accounts/{account-id}/transfers endpoint.
+  pub List(account::Id),
+  Api => Broker,
+  Ok => Vec<Transfer>, [
+    /// The transfers were retrieved successfully.
+    /* 200 */ OK,
+  ],
+  Err => ListError, [
+    /// No account was found with the given ID.
+    /* 404 */ NOT_FOUND => NotFound,
+  ]
+
+  fn path(input: &Self::Input) -> Str {
+    format!("/v1/accounts/{}/transfers", input.as_hyphenated()).into()
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use serde_json::from_str as from_json;
+  use serde_json::to_string as to_json;
+
+
+  /// Check that we can parse a reference transfer.
+  #[test]
+  fn parse_reference_transfer() {
+    let response = r#"{
+  "id": "be3c368a-4c7c-4384-808e-f02c9f5a8afe",
+  "relationship_id": "c9b420e0-ae4e-4f39-bcbf-649b407c2129",
+  "account_id": "b0b6dd9d-8b9b-48a9-ba46-b9d54906e415",
+  "type": "ach",
+  "status": "QUEUED",
+  "amount": "1234.56",
+  "direction": "INCOMING",
+  "created_at": "2021-05-17T09:56:05.445592162Z",
+  "updated_at": "2021-05-17T09:56:05.445592162Z",
+  "expires_at": "2021-05-24T09:56:05.445531104Z",
+  "reason": null
+}"#;
+
+    let transfer = from_json::<Transfer>(response).unwrap();
+    assert_eq!(transfer.type_, Type::Ach);
+    assert_eq!(transfer.status, Status::Queued);
+    assert_eq!(transfer.direction, Direction::Incoming);
+    assert_eq!(transfer.amount, Num::new(123456, 100));
+    assert_eq!(transfer.reason, None);
+  }
+
+  /// Verify that we serialize a transfer creation request as expected.
+  #[test]
+  fn serialize_create_request() {
+    let id =
+      ach_relationships::Id(Uuid::parse_str("c9b420e0-ae4e-4f39-bcbf-649b407c2129").unwrap());
+    let request = CreateReq::ach(id, Num::from(500), Direction::Outgoing);
+
+    let json = to_json(&request).unwrap();
+    let expected = r#"{"transfer_type":"ach","relationship_id":"c9b420e0-ae4e-4f39-bcbf-649b407c2129","amount":"500","direction":"OUTGOING"}"#;
+    assert_eq!(json, expected);
+  }
+}
@@ -0,0 +1,177 @@
+// Copyright (C) 2024 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use thiserror::Error;
+
+use crate::api::v2::account;
+use crate::api::v2::account_config;
+use crate::client::Client;
+use crate::data::v2::last_quotes;
+use crate::data::v2::Feed;
+use crate::error::RequestError;
+
+
+/// The symbol we request data for when probing for SIP access.
+const PROBE_SYMBOL: &str = "SPY";
+
+
+/// An error as reported when determining the capabilities of an
+/// account.
+#[derive(Debug, Error)]
+pub enum CapabilitiesError {
+  /// The account information could not be retrieved.
+  #[error("failed to retrieve account information")]
+  Account(#[source] RequestError<account::GetError>),
+  /// The account configuration could not be retrieved.
+  #[error("failed to retrieve account configuration")]
+  Configuration(#[source] RequestError<account_config::GetError>),
+  /// Probing for market data access failed in an unexpected way.
+  #[error("failed to probe for market data access")]
+  Data(#[source] RequestError<last_quotes::GetError>),
+}
+
+
+/// The set of functionality available to an account.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Capabilities {
+  /// Whether the account has access to real-time market data from the
+  /// SIP feed.
+  pub sip: bool,
+  /// Whether the account is enabled for crypto trading.
+  pub crypto: bool,
+  /// The options trading level the account is approved for, with
+  /// zero meaning that options trading is disabled.
+  pub options_level: u8,
+  /// Whether the account may open short positions.
+  pub shorting: bool,
+  /// Whether the account may trade fractional shares.
+  pub fractional: bool,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+impl Capabilities {
+  /// Assemble a `Capabilities` object from the various pieces of
+  /// information it is derived from.
+  fn new(account: &account::Account, config: &account_config::Configuration, sip: bool) -> Self {
+    Self {
+      sip,
+      crypto: account.crypto_status == Some(account::Status::Active),
+      options_level: account.options_trading_level.unwrap_or(0),
+      shorting: account.shorting_enabled && !config.no_shorting,
+      fractional: config.fractional_trading,
+      _non_exhaustive: (),
+    }
+  }
+}
+
+
+impl Client {
+  /// Determine the set of functionality available to the account the
+  /// client is configured for.
+  ///
+  /// Besides retrieving the account information and configuration,
+  /// this method issues a request for the latest SIP quote of a
+  /// well-known symbol to check whether SIP data can be accessed.
+  pub async fn capabilities(&self) -> Result<Capabilities, CapabilitiesError> {
+    let account = self
+      .issue::<account::Get>(&())
+      .await
+      .map_err(CapabilitiesError::Account)?;
+    let config = self
+      .issue::<account_config::Get>(&())
+      .await
+      .map_err(CapabilitiesError::Configuration)?;
+
+    let request = last_quotes::GetReqInit {
+      feed: Some(Feed::SIP),
+      ..Default::default()
+    }
+    .init([PROBE_SYMBOL]);
+    let sip = match self.issue::<last_quotes::Get>(&request).await {
+      Ok(..) => true,
+      Err(RequestError::Endpoint(last_quotes::GetError::NotPermitted(..))) => false,
+      Err(err) => return Err(CapabilitiesError::Data(err)),
+    };
+
+    Ok(Capabilities::new(&account, &config, sip))
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use serde_json::from_str as from_json;
+
+  use test_log::test;
+
+  use crate::api_info::ApiInfo;
+
+
+  /// Check that we derive the expected capabilities from account
+  /// information and configuration.
+  #[test]
+  fn derive_capabilities() {
+    let account = r#"{
+  "id": "904837e3-3b76-47ec-b432-046db621571b",
+  "status": "ACTIVE",
+  "crypto_status": "ACTIVE",
+  "currency": "USD",
+  "buying_power": "0.0",
+  "cash": "1000.00",
+  "pattern_day_trader": false,
+  "trade_suspended_by_user": false,
+  "trading_blocked": false,
+  "transfers_blocked": false,
+  "account_blocked": false,
+  "created_at": "2018-10-01T13:35:25Z",
+  "shorting_enabled": true,
+  "multiplier": "2",
+  "long_market_value": "7000.00",
+  "short_market_value": "-3000.00",
+  "equity": "5000.00",
+  "last_equity": "5000.00",
+  "initial_margin": "5000.00",
+  "maintenance_margin": "3000.00",
+  "daytrade_count": 0,
+  "options_trading_level": 2
+}"#;
+    let config = r#"{
+  "dtbp_check": "entry",
+  "no_shorting": true,
+  "suspend_trade": false,
+  "trade_confirm_email": "all",
+  "fractional_trading": true
+}"#;
+
+    let account = from_json::<account::Account>(account).unwrap();
+    let config = from_json::<account_config::Configuration>(config).unwrap();
+    let capabilities = Capabilities::new(&account, &config, false);
+
+    let expected = Capabilities {
+      sip: false,
+      crypto: true,
+      options_level: 2,
+      shorting: false,
+      fractional: true,
+      _non_exhaustive: (),
+    };
+    assert_eq!(capabilities, expected);
+  }
+
+  /// Check that we can determine the capabilities of the account.
+  #[test(tokio::test)]
+  async fn retrieve_capabilities() {
+    let api_info = ApiInfo::from_env().unwrap();
+    let client = Client::new(api_info);
+    let account = client.issue::<account::Get>(&()).await.unwrap();
+    let capabilities = client.capabilities().await.unwrap();
+
+    if !account.shorting_enabled {
+      assert!(!capabilities.shorting);
+    }
+  }
+}
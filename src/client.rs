@@ -3,45 +3,89 @@
 
 use std::borrow::Cow;
 use std::fmt::Debug;
+use std::fmt::Display;
 use std::fmt::Formatter;
 use std::fmt::Result as FmtResult;
 use std::future::Future;
-use std::str::from_utf8;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
 
+use chrono::Duration as ChronoDuration;
+
+use futures::FutureExt as _;
+use futures::Stream;
+
+use http::header::AUTHORIZATION;
 use http::request::Builder as HttpRequestBuilder;
 use http::HeaderMap;
 use http::HeaderValue;
+use http::Method;
 use http::Request;
 use http::Response;
+use http::StatusCode;
 use http_body_util::BodyExt;
 use http_body_util::Full;
-use http_endpoint::Endpoint;
 
 use hyper::body::Bytes;
 use hyper::body::Incoming;
 use hyper::Error as HyperError;
 use hyper_tls::HttpsConnector;
-use hyper_util::client::legacy::connect::HttpConnector;
 use hyper_util::client::legacy::Builder as HttpClientBuilder;
 use hyper_util::client::legacy::Client as HttpClient;
 use hyper_util::rt::TokioExecutor;
+use hyper_util::rt::TokioTimer;
+
+use native_tls::TlsConnector;
+
+use serde_json::from_slice as json_from_slice;
+use serde_json::Error as JsonError;
+use serde_json::Value as JsonValue;
+
+use tokio::time::timeout;
 
 use tracing::debug;
 use tracing::field::debug;
+use tracing::field::display;
 use tracing::field::DebugValue;
+use tracing::field::DisplayValue;
 use tracing::instrument;
 use tracing::span;
 use tracing::trace;
+use tracing::warn;
 use tracing::Level;
 use tracing_futures::Instrument;
 
 use url::Url;
 
+use crate::api::v2::asset::TtlCache;
 use crate::api::HDR_KEY_ID;
+use crate::api::HDR_REQUEST_ID;
 use crate::api::HDR_SECRET;
 use crate::api_info::ApiInfo;
+use crate::api_info::Environment;
+use crate::connection::ConnectionConfig;
+use crate::connection::ProxyConnector;
+use crate::data::v2::buffer::Buffered;
+use crate::data::v2::buffer::Overflow;
+use crate::data::v2::stream::connect_feed;
+use crate::data::v2::stream::connect_url;
+use crate::data::v2::stream::RealtimeData;
+use crate::data::v2::stream::IEX;
+use crate::data::v2::Feed;
+use crate::endpoint::Api;
+use crate::endpoint::ApiEndpoint;
+use crate::endpoint::EndpointError;
 use crate::error::RequestError;
+use crate::etag::Conditional;
+use crate::etag::EtagStore;
+use crate::middleware::Middleware;
 use crate::subscribable::Subscribable;
+use crate::telemetry::Observer;
+use crate::transport::Transport;
 use crate::Error;
 
 
@@ -51,13 +95,19 @@ struct DebugHeaders<'h> {
   headers: &'h HeaderMap<HeaderValue>,
 }
 
+/// Emit a debug representation of HTTP headers, with sensitive data
+/// being masked out.
+fn debug_headers(headers: &HeaderMap<HeaderValue>) -> DebugValue<DebugHeaders<'_>> {
+  debug(DebugHeaders { headers })
+}
+
 impl Debug for DebugHeaders<'_> {
   fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
     static MASKED: HeaderValue = HeaderValue::from_static("<masked>");
 
     f.debug_map()
       .entries(self.headers.iter().map(|(k, v)| {
-        if k == HDR_KEY_ID || k == HDR_SECRET {
+        if k == HDR_KEY_ID || k == HDR_SECRET || k == AUTHORIZATION {
           (k, &MASKED)
         } else {
           (k, v)
@@ -68,34 +118,182 @@ impl Debug for DebugHeaders<'_> {
 }
 
 
-/// A type providing a debug representation of an HTTP request, with
-/// sensitive data being masked out.
-struct DebugRequest<'r> {
-  request: &'r Request<Full<Bytes>>,
+/// The maximum number of bytes of a body logged by default.
+const DEFAULT_MAX_LOGGED_BODY: usize = 4096;
+
+
+/// A policy controlling how requests and responses are logged.
+///
+/// Requests and responses are emitted as `tracing` events at the
+/// `TRACE` level, with the method, URI, status, headers, and body
+/// being reported as individual fields. Secret credentials in headers
+/// are always masked.
+#[derive(Clone, Copy, Debug)]
+pub struct LogPolicy {
+  /// The maximum number of bytes of a request or response body to log.
+  ///
+  /// Longer bodies are truncated. `None` means that bodies are logged
+  /// in their entirety. Defaults to 4 KiB.
+  pub max_body_bytes: Option<usize>,
+  /// Whether to mask account numbers contained in bodies. Defaults to
+  /// `true`.
+  pub redact_account_numbers: bool,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+impl Default for LogPolicy {
+  #[inline]
+  fn default() -> Self {
+    Self {
+      max_body_bytes: Some(DEFAULT_MAX_LOGGED_BODY),
+      redact_account_numbers: true,
+      _non_exhaustive: (),
+    }
+  }
+}
+
+
+/// Mask the values of all `account_number` members in the provided
+/// JSON text.
+fn redact_account_numbers(text: &str) -> Cow<'_, str> {
+  const KEY: &str = "\"account_number\"";
+
+  let mut redacted = String::new();
+  let mut rest = text;
+
+  while let Some(idx) = rest.find(KEY) {
+    let (head, tail) = rest.split_at(idx + KEY.len());
+    let () = redacted.push_str(head);
+
+    let value = tail
+      .trim_start()
+      .strip_prefix(':')
+      .map(str::trim_start)
+      .and_then(|value| value.strip_prefix('"'))
+      .and_then(|value| value.find('"').map(|end| &value[end + 1..]));
+
+    match value {
+      Some(value) => {
+        let () = redacted.push_str(":\"<masked>\"");
+        rest = value;
+      },
+      None => rest = tail,
+    }
+  }
+
+  if redacted.is_empty() {
+    Cow::Borrowed(text)
+  } else {
+    let () = redacted.push_str(rest);
+    Cow::Owned(redacted)
+  }
 }
 
-impl Debug for DebugRequest<'_> {
+
+/// A type providing a display representation of an HTTP body, as
+/// governed by a [`LogPolicy`].
+struct LoggedBody<'b> {
+  body: &'b [u8],
+  policy: &'b LogPolicy,
+}
+
+impl Display for LoggedBody<'_> {
   fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
-    // Note that we do not print URL and version, because we assume they
-    // are already included as identifiers in the span of the usage
-    // site.
-    f.debug_struct("Request")
-      .field("version", &self.request.version())
-      .field(
-        "headers",
-        &DebugHeaders {
-          headers: self.request.headers(),
-        },
-      )
-      .field("body", self.request.body())
-      .finish()
+    let lossy = String::from_utf8_lossy(self.body);
+    let redacted;
+    let text = if self.policy.redact_account_numbers {
+      redacted = redact_account_numbers(&lossy);
+      redacted.as_ref()
+    } else {
+      lossy.as_ref()
+    };
+
+    match self.policy.max_body_bytes {
+      Some(max) if text.len() > max => {
+        let mut end = max;
+        while !text.is_char_boundary(end) {
+          end -= 1;
+        }
+        write!(f, "{}...", &text[..end])
+      },
+      _ => f.write_str(text),
+    }
+  }
+}
+
+
+/// Emit a display representation of an HTTP body, as governed by the
+/// provided policy.
+fn logged_body<'b>(body: &'b [u8], policy: &'b LogPolicy) -> DisplayValue<LoggedBody<'b>> {
+  display(LoggedBody { body, policy })
+}
+
+
+/// Options influencing how an individual request is issued.
+///
+/// Options not set fall back to the defaults configured on the
+/// builder (see [`Client::builder`]) used for creating the `Client`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RequestOptions {
+  /// The time after which the request is aborted with
+  /// [`RequestError::Timeout`].
+  pub timeout: Option<Duration>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+
+/// The decoded output of a request along with the raw body of the
+/// response it was decoded from.
+///
+/// The raw body provides access to data not (yet) represented in the
+/// decoded output.
+#[derive(Clone, Debug)]
+pub struct WithRaw<T> {
+  /// The decoded output.
+  pub output: T,
+  /// The raw body of the response.
+  pub body: Bytes,
+  /// The ID Alpaca assigned to the request, as reported in the
+  /// `X-Request-ID` response header.
+  pub request_id: Option<String>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+impl<T> WithRaw<T> {
+  /// Parse the raw body of the response as a generic JSON value.
+  #[inline]
+  pub fn json(&self) -> Result<JsonValue, JsonError> {
+    json_from_slice(&self.body)
   }
 }
 
 
-/// Emit a debug representation of an HTTP request.
-fn debug_request(request: &Request<Full<Bytes>>) -> DebugValue<DebugRequest<'_>> {
-  debug(DebugRequest { request })
+/// Extract the ID Alpaca assigned to a request from the headers of
+/// the response to it.
+fn request_id(headers: &HeaderMap<HeaderValue>) -> Option<String> {
+  headers
+    .get(HDR_REQUEST_ID)
+    .and_then(|value| value.to_str().ok())
+    .map(str::to_string)
+}
+
+
+/// Check whether a request to the Trading API using the given method
+/// and path may create, modify, or cancel orders or move funds.
+fn is_live_guarded(method: &Method, path: &str) -> bool {
+  // Closing positions happens by means of orders as well. Wallet
+  // requests cover crypto withdrawals and the addresses they may go
+  // to.
+  method != Method::GET
+    && (path.starts_with("/v2/orders")
+      || path.starts_with("/v2/positions")
+      || path.starts_with("/v2/wallets"))
 }
 
 
@@ -103,6 +301,15 @@ fn debug_request(request: &Request<Full<Bytes>>) -> DebugValue<DebugRequest<'_>>
 #[derive(Debug)]
 pub struct Builder {
   builder: HttpClientBuilder,
+  transport: Option<Arc<dyn Transport>>,
+  connection: ConnectionConfig,
+  timeout: Option<Duration>,
+  guard_live_trading: bool,
+  asset_cache_ttl: Option<Duration>,
+  log_policy: LogPolicy,
+  middleware: Vec<Arc<dyn Middleware>>,
+  etag_store: Option<Arc<dyn EtagStore>>,
+  clock_skew_threshold: Option<Duration>,
 }
 
 impl Builder {
@@ -113,12 +320,234 @@ impl Builder {
     self
   }
 
+  /// Set the time after which idle connections are removed from the
+  /// connection pool.
+  ///
+  /// By default, idle connections are kept around for 90 seconds.
+  #[inline]
+  pub fn pool_idle_timeout(&mut self, timeout: Duration) -> &mut Self {
+    // A timer is necessary for idle connections to be reaped.
+    let _ = self
+      .builder
+      .pool_idle_timeout(timeout)
+      .pool_timer(TokioTimer::new());
+    self
+  }
+
+  /// Send TCP keep-alive probes on otherwise idle HTTP connections at
+  /// the given interval, helping to keep pooled connections usable.
+  ///
+  /// By default, no keep-alive probes are sent.
+  #[inline]
+  pub fn tcp_keepalive(&mut self, interval: Duration) -> &mut Self {
+    self.connection.tcp_keepalive = Some(interval);
+    self
+  }
+
+  /// Offer HTTP/2 when establishing secure connections for HTTP
+  /// requests, using it with servers that select it during ALPN
+  /// negotiation, such as the one serving the data API.
+  ///
+  /// HTTP/2 allows for many concurrent requests to share a single
+  /// connection. By default, only HTTP/1.1 is used. A connector set via
+  /// [`tls_connector`][Self::tls_connector] has to request the `h2`
+  /// protocol itself for this setting to have an effect.
+  #[inline]
+  pub fn http2(&mut self, enable: bool) -> &mut Self {
+    self.connection.http2 = enable;
+    self
+  }
+
+  /// Adjust the flow control window of HTTP/2 connections dynamically,
+  /// based on the measured bandwidth-delay product, instead of using a
+  /// fixed size.
+  ///
+  /// By default, a fixed size window is used.
+  #[inline]
+  pub fn http2_adaptive_window(&mut self, enable: bool) -> &mut Self {
+    let _ = self.builder.http2_adaptive_window(enable);
+    self
+  }
+
+  /// Send HTTP/2 pings at the given interval to keep connections
+  /// alive.
+  ///
+  /// By default, no pings are sent.
+  #[inline]
+  pub fn http2_keep_alive_interval(&mut self, interval: Duration) -> &mut Self {
+    // A timer is necessary for pings to be sent.
+    let _ = self
+      .builder
+      .http2_keep_alive_interval(interval)
+      .timer(TokioTimer::new());
+    self
+  }
+
+  /// Set the time after which requests are aborted with
+  /// [`RequestError::Timeout`].
+  ///
+  /// By default, requests do not time out. The timeout can be
+  /// overwritten for individual requests by means of
+  /// [`Client::issue_with`].
+  #[inline]
+  pub fn timeout(&mut self, timeout: Duration) -> &mut Self {
+    self.timeout = Some(timeout);
+    self
+  }
+
+  /// Use the provided TLS connector for establishing secure
+  /// connections, instead of one trusting only the system's root
+  /// certificates.
+  ///
+  /// This setting applies to HTTP requests as well as to websocket
+  /// streams and can be used, for example, to trust the certificate of
+  /// a proxy intercepting TLS traffic.
+  #[inline]
+  pub fn tls_connector(&mut self, connector: TlsConnector) -> &mut Self {
+    self.connection.tls = Some(connector);
+    self
+  }
+
+  /// Tunnel all connections through the HTTP proxy at the given URL.
+  ///
+  /// This setting applies to HTTP requests as well as to websocket
+  /// streams. Credentials contained in the URL are used for
+  /// authenticating with the proxy.
+  #[inline]
+  pub fn proxy(&mut self, url: Url) -> &mut Self {
+    self.connection.proxy = Some(url);
+    self
+  }
+
+  /// Set the time to wait for the server to respond to control
+  /// messages, such as the authentication request, while setting up a
+  /// websocket stream.
+  ///
+  /// Setting up the stream fails with [`Error::Timeout`][crate::Error::Timeout]
+  /// if the server does not respond in time. The default is 30
+  /// seconds.
+  #[inline]
+  pub fn handshake_timeout(&mut self, timeout: Duration) -> &mut Self {
+    self.connection.handshake_timeout = Some(timeout);
+    self
+  }
+
+  /// Use the provided transport for issuing requests, instead of
+  /// connecting to the Alpaca servers directly.
+  ///
+  /// Connection related settings of the builder do not apply to custom
+  /// transports.
+  #[inline]
+  pub fn with_transport<T>(&mut self, transport: T) -> &mut Self
+  where
+    T: Transport + 'static,
+  {
+    self.transport = Some(Arc::new(transport));
+    self
+  }
+
+  /// Inform the provided observer about issued requests and the
+  /// activity of streams subscribed to through the `Client`.
+  #[inline]
+  pub fn observer<O>(&mut self, observer: O) -> &mut Self
+  where
+    O: Observer + 'static,
+  {
+    self.connection.observer = Some(Arc::new(observer));
+    self
+  }
+
+  /// Pass requests and responses through the provided middleware.
+  ///
+  /// Middleware registered earlier sees requests first and responses
+  /// last. Middleware applies irrespective of the transport used.
+  #[inline]
+  pub fn layer<M>(&mut self, middleware: M) -> &mut Self
+  where
+    M: Middleware + 'static,
+  {
+    self.middleware.push(Arc::new(middleware));
+    self
+  }
+
+  /// Issue `GET` requests conditionally, serving responses reported as
+  /// not modified from the provided store.
+  ///
+  /// See [`EtagStore`] for details. By default, no conditional
+  /// requests are issued.
+  #[inline]
+  pub fn etag_cache<S>(&mut self, store: S) -> &mut Self
+  where
+    S: EtagStore + 'static,
+  {
+    self.etag_store = Some(Arc::new(store));
+    self
+  }
+
+  /// Refuse requests creating, modifying, or canceling orders as well
+  /// as ones moving crypto funds (i.e., withdrawals and changes to the
+  /// whitelisted addresses) in the [live][Environment::Live]
+  /// environment with
+  /// [`RequestError::LiveTradingNotConfirmed`], until live trading has
+  /// been confirmed by means of [`Client::confirm_live_trading`].
+  ///
+  /// The guard is meant to prevent the accidental placement of orders
+  /// with real funds, e.g., by a strategy under development that
+  /// inadvertently ended up being configured for the live environment.
+  #[inline]
+  pub fn guard_live_trading(&mut self) -> &mut Self {
+    self.guard_live_trading = true;
+    self
+  }
+
+  /// Cache assets retrieved by means of
+  /// [`asset::get_cached`][crate::api::v2::asset::get_cached] for the
+  /// given amount of time, avoiding repeated lookups of the same
+  /// metadata.
+  ///
+  /// By default, assets are not cached.
+  #[inline]
+  pub fn asset_cache_ttl(&mut self, ttl: Duration) -> &mut Self {
+    self.asset_cache_ttl = Some(ttl);
+    self
+  }
+
+  /// Emit a warning whenever the skew between the local clock and the
+  /// server's, as measured by
+  /// [`clock::measure_skew`][crate::api::v2::clock::measure_skew],
+  /// exceeds the given threshold.
+  #[inline]
+  pub fn clock_skew_threshold(&mut self, threshold: Duration) -> &mut Self {
+    self.clock_skew_threshold = Some(threshold);
+    self
+  }
+
+  /// Set the policy governing how requests and responses are logged.
+  #[inline]
+  pub fn log_policy(&mut self, policy: LogPolicy) -> &mut Self {
+    self.log_policy = policy;
+    self
+  }
+
   /// Build the final `Client` object.
   pub fn build(&self, api_info: ApiInfo) -> Client {
-    let https = HttpsConnector::new();
+    let https = self.connection.https_connector();
     let client = self.builder.build(https);
 
-    Client { api_info, client }
+    Client {
+      api_info,
+      client,
+      transport: self.transport.clone(),
+      connection: self.connection.clone(),
+      timeout: self.timeout,
+      live_trading_confirmed: AtomicBool::new(!self.guard_live_trading),
+      asset_cache: self.asset_cache_ttl.map(TtlCache::new),
+      log_policy: self.log_policy,
+      middleware: self.middleware.clone(),
+      etag_store: self.etag_store.clone(),
+      clock_skew: Mutex::new(None),
+      clock_skew_threshold: self.clock_skew_threshold,
+    }
   }
 }
 
@@ -136,7 +565,18 @@ impl Default for Builder {
     let mut builder = HttpClient::builder(TokioExecutor::new());
     let _ = builder.pool_max_idle_per_host(0);
 
-    Self { builder }
+    Self {
+      builder,
+      transport: None,
+      connection: ConnectionConfig::default(),
+      timeout: None,
+      guard_live_trading: false,
+      asset_cache_ttl: None,
+      log_policy: LogPolicy::default(),
+      middleware: Vec::new(),
+      etag_store: None,
+      clock_skew_threshold: None,
+    }
   }
 
   #[cfg(not(test))]
@@ -144,6 +584,15 @@ impl Default for Builder {
   fn default() -> Self {
     Self {
       builder: HttpClient::builder(TokioExecutor::new()),
+      transport: None,
+      connection: ConnectionConfig::default(),
+      timeout: None,
+      guard_live_trading: false,
+      asset_cache_ttl: None,
+      log_policy: LogPolicy::default(),
+      middleware: Vec::new(),
+      etag_store: None,
+      clock_skew_threshold: None,
     }
   }
 }
@@ -154,7 +603,29 @@ impl Default for Builder {
 #[derive(Debug)]
 pub struct Client {
   api_info: ApiInfo,
-  client: HttpClient<HttpsConnector<HttpConnector>, Full<Bytes>>,
+  client: HttpClient<HttpsConnector<ProxyConnector>, Full<Bytes>>,
+  /// A custom transport to use instead of `client`, if any.
+  transport: Option<Arc<dyn Transport>>,
+  /// The settings used for establishing websocket connections.
+  connection: ConnectionConfig,
+  /// The default timeout for requests, if any.
+  timeout: Option<Duration>,
+  /// Whether order mutating requests to the live environment are
+  /// permitted.
+  live_trading_confirmed: AtomicBool,
+  /// The cache of asset metadata, if enabled.
+  asset_cache: Option<TtlCache>,
+  /// The policy governing how requests and responses are logged.
+  log_policy: LogPolicy,
+  /// The middleware requests and responses are passed through.
+  middleware: Vec<Arc<dyn Middleware>>,
+  /// The store used for conditional requests, if any.
+  etag_store: Option<Arc<dyn EtagStore>>,
+  /// The most recently measured skew of the server's clock relative to
+  /// the local one, if any.
+  clock_skew: Mutex<Option<ChronoDuration>>,
+  /// The clock skew beyond which to warn, if any.
+  clock_skew_threshold: Option<Duration>,
 }
 
 impl Client {
@@ -171,9 +642,22 @@ impl Client {
     Builder::default().build(api_info)
   }
 
+  /// Confirm (or revoke confirmation of) live trading, permitting
+  /// requests guarded as per the `guard_live_trading` method of the
+  /// builder returned by [`Client::builder`].
+  ///
+  /// Without the guard enabled live trading is confirmed from the
+  /// start.
+  #[inline]
+  pub fn confirm_live_trading(&self, confirmed: bool) {
+    let () = self
+      .live_trading_confirmed
+      .store(confirmed, Ordering::Relaxed);
+  }
+
   /// Add "gzip" as an accepted encoding to the request.
   #[cfg(feature = "gzip")]
-  fn maybe_add_gzip_header(request: &mut Request<Full<Bytes>>) {
+  fn maybe_add_gzip_header(request: &mut Request<Bytes>) {
     use http::header::ACCEPT_ENCODING;
 
     let _ = request
@@ -183,16 +667,58 @@ impl Client {
 
   /// An implementation stub not actually doing anything.
   #[cfg(not(feature = "gzip"))]
-  fn maybe_add_gzip_header(_request: &mut Request<Full<Bytes>>) {}
+  fn maybe_add_gzip_header(_request: &mut Request<Bytes>) {}
+
+  /// Add required authentication information to a request.
+  #[cfg(feature = "broker")]
+  fn authenticate(&self, builder: HttpRequestBuilder, api: Api) -> HttpRequestBuilder {
+    use data_encoding::BASE64;
+
+    if api == Api::Broker {
+      // The Broker API uses HTTP basic authentication instead of the
+      // custom headers used elsewhere.
+      let credentials = format!("{}:{}", self.api_info.key_id, self.api_info.secret);
+      let value = format!("Basic {}", BASE64.encode(credentials.as_bytes()));
+      builder.header(AUTHORIZATION, value)
+    } else {
+      self.authenticate_with_headers(builder, api)
+    }
+  }
+
+  /// Add required authentication information to a request.
+  #[cfg(not(feature = "broker"))]
+  fn authenticate(&self, builder: HttpRequestBuilder, api: Api) -> HttpRequestBuilder {
+    self.authenticate_with_headers(builder, api)
+  }
+
+  /// Add authentication information to a request in the form of key
+  /// ID and secret headers, using the data API credentials for
+  /// requests to the data API.
+  fn authenticate_with_headers(&self, builder: HttpRequestBuilder, api: Api) -> HttpRequestBuilder {
+    let (key_id, secret) = if api == Api::Data {
+      self.api_info.data_credentials()
+    } else {
+      (self.api_info.key_id.as_str(), self.api_info.secret.as_str())
+    };
+
+    builder
+      .header(HDR_KEY_ID, key_id)
+      .header(HDR_SECRET, secret)
+  }
 
   /// Create a `Request` to the endpoint.
-  fn request<R>(&self, input: &R::Input) -> Result<Request<Full<Bytes>>, R::Error>
+  fn request<R>(&self, input: &R::Input) -> Result<Request<Bytes>, R::Error>
   where
-    R: Endpoint,
+    R: ApiEndpoint,
   {
-    let mut url = R::base_url()
-      .map(|url| Url::parse(url.as_ref()).expect("endpoint definition contains invalid URL"))
-      .unwrap_or_else(|| self.api_info.api_base_url.clone());
+    let mut url = match R::API {
+      Api::Trading => self.api_info.api_base_url.clone(),
+      Api::Data => self.api_info.data_base_url.clone(),
+      // Broker API requests go to the user configured URL, allowing
+      // for switching between sandbox and production environments.
+      #[cfg(feature = "broker")]
+      Api::Broker => self.api_info.broker_base_url.clone(),
+    };
 
     url.set_path(&R::path(input));
     url.set_query(R::query(input)?.as_ref().map(AsRef::as_ref));
@@ -203,13 +729,10 @@ impl Client {
       Some(Cow::Owned(vec)) => Bytes::from(vec),
     };
 
-    let mut request = HttpRequestBuilder::new()
+    let builder = HttpRequestBuilder::new()
       .method(R::method())
-      .uri(url.as_str())
-      // Add required authentication information.
-      .header(HDR_KEY_ID, self.api_info.key_id.as_str())
-      .header(HDR_SECRET, self.api_info.secret.as_str())
-      .body(Full::new(body))?;
+      .uri(url.as_str());
+    let mut request = self.authenticate(builder, R::API).body(body)?;
 
 
     Self::maybe_add_gzip_header(&mut request);
@@ -266,51 +789,191 @@ impl Client {
   }
 
   /// Create and issue a request and decode the response.
+  #[inline]
   pub fn issue<R>(
     &self,
     input: &R::Input,
   ) -> impl Future<Output = Result<R::Output, RequestError<R::Error>>> + '_
   where
-    R: Endpoint,
+    R: ApiEndpoint,
+    R::Error: EndpointError,
+  {
+    self.issue_with::<R>(input, RequestOptions::default())
+  }
+
+  /// Create and issue a request using the provided options and decode
+  /// the response.
+  pub fn issue_with<R>(
+    &self,
+    input: &R::Input,
+    options: RequestOptions,
+  ) -> impl Future<Output = Result<R::Output, RequestError<R::Error>>> + '_
+  where
+    R: ApiEndpoint,
+    R::Error: EndpointError,
+  {
+    self
+      .issue_raw_with::<R>(input, options)
+      .map(|result| result.map(|raw| raw.output))
+  }
+
+  /// Create and issue a request and decode the response, retaining
+  /// the raw body of the response alongside the decoded output.
+  #[inline]
+  pub fn issue_raw<R>(
+    &self,
+    input: &R::Input,
+  ) -> impl Future<Output = Result<WithRaw<R::Output>, RequestError<R::Error>>> + '_
+  where
+    R: ApiEndpoint,
+    R::Error: EndpointError,
+  {
+    self.issue_raw_with::<R>(input, RequestOptions::default())
+  }
+
+  /// Create and issue a request using the provided options and decode
+  /// the response, retaining the raw body of the response.
+  fn issue_raw_with<R>(
+    &self,
+    input: &R::Input,
+    options: RequestOptions,
+  ) -> impl Future<Output = Result<WithRaw<R::Output>, RequestError<R::Error>>> + '_
+  where
+    R: ApiEndpoint,
+    R::Error: EndpointError,
   {
     let result = self.request::<R>(input);
+    let duration = options.timeout.or(self.timeout);
     async move {
       let request = result.map_err(RequestError::Endpoint)?;
+      if self.api_info.environment() == Environment::Live
+        && R::API == Api::Trading
+        && is_live_guarded(request.method(), request.uri().path())
+        && !self.live_trading_confirmed.load(Ordering::Relaxed)
+      {
+        return Err(RequestError::LiveTradingNotConfirmed)
+      }
+
       let span = span!(
         Level::INFO,
         "issue",
         method = display(request.method()),
         uri = display(request.uri())
       );
-      self.issue_::<R>(request).instrument(span).await
+
+      let observed = self.connection.observer.as_deref().map(|observer| {
+        let method = request.method().clone();
+        let path = request.uri().path().to_string();
+        let () = observer.request_started(&method, &path);
+        (observer, method, path, Instant::now())
+      });
+
+      let mut status = None;
+      let issue = self.issue_::<R>(request, &mut status).instrument(span);
+      let result = match duration {
+        Some(duration) => timeout(duration, issue)
+          .await
+          .unwrap_or(Err(RequestError::Timeout(duration))),
+        None => issue.await,
+      };
+
+      if let Some((observer, method, path, start)) = observed {
+        let () = observer.request_finished(&method, &path, status, start.elapsed());
+      }
+      result
     }
   }
 
   /// Issue a request.
+  ///
+  /// The status of the response is stored in `status` as soon as it is
+  /// known.
   #[allow(clippy::cognitive_complexity)]
   async fn issue_<R>(
     &self,
-    request: Request<Full<Bytes>>,
-  ) -> Result<R::Output, RequestError<R::Error>>
+    request: Request<Bytes>,
+    status: &mut Option<StatusCode>,
+  ) -> Result<WithRaw<R::Output>, RequestError<R::Error>>
   where
-    R: Endpoint,
+    R: ApiEndpoint,
+    R::Error: EndpointError,
   {
+    let mut request = request;
+    let conditional = self
+      .etag_store
+      .as_deref()
+      .and_then(|store| Conditional::prepare(store, &mut request));
+
+    for middleware in &self.middleware {
+      let () = middleware.on_request(&mut request);
+    }
+
     debug!("requesting");
-    trace!(request = debug_request(&request));
+    trace!(
+      version = debug(request.version()),
+      headers = debug_headers(request.headers()),
+      body = logged_body(request.body(), &self.log_policy),
+      body_len = request.body().len(),
+    );
+    let request = request.map(Full::new);
 
-    let result = self.client.request(request).await?;
-    let status = result.status();
-    debug!(status = debug(&status));
-    trace!(response = debug(&result));
+    let mut response = if let Some(transport) = &self.transport {
+      let response = transport
+        .issue(request)
+        .await
+        .map_err(RequestError::Transport)?;
+      let _status = status.insert(response.status());
+      debug!(status = debug(&response.status()));
+      trace!(headers = debug_headers(response.headers()));
+      response
+    } else {
+      let response = self.client.request(request).await?;
+      let _status = status.insert(response.status());
+      debug!(status = debug(&response.status()));
+      trace!(headers = debug_headers(response.headers()));
 
-    let bytes = Self::retrieve_body::<R::Error>(result).await?;
-    let body = bytes.as_ref();
-    match from_utf8(body) {
-      Ok(s) => trace!(body = display(&s)),
-      Err(b) => trace!(body = display(&b)),
+      let mut builder = Response::builder()
+        .status(response.status())
+        .version(response.version());
+      if let Some(headers) = builder.headers_mut() {
+        *headers = response.headers().clone();
+      }
+      let body = Self::retrieve_body::<R::Error>(response).await?;
+      // SANITY: The builder only contains parts of a valid response
+      //         and so building cannot fail.
+      builder.body(body).unwrap()
+    };
+
+    for middleware in self.middleware.iter().rev() {
+      let () = middleware.on_response(&mut response);
     }
 
-    R::evaluate(status, body).map_err(RequestError::Endpoint)
+    if let Some(conditional) = conditional {
+      let () = conditional.resolve(&mut response);
+    }
+
+    let status = *status.insert(response.status());
+    let request_id = request_id(response.headers());
+    let bytes = response.into_body();
+    debug!(request_id = debug(&request_id));
+    let body = bytes.as_ref();
+    trace!(
+      body = logged_body(body, &self.log_policy),
+      body_len = body.len()
+    );
+
+    let output = R::evaluate(status, body).map_err(|mut err| {
+      if let Some(api_err) = err.api_error_mut() {
+        api_err.request_id = request_id.clone();
+      }
+      RequestError::Endpoint(err)
+    })?;
+    Ok(WithRaw {
+      output,
+      body: bytes,
+      request_id,
+      _non_exhaustive: (),
+    })
   }
 
   /// Subscribe to the given subscribable in order to receive updates.
@@ -318,7 +981,8 @@ impl Client {
   /// # Notes
   /// - this method is only a short-hand for
   ///   [`S::connect`][Subscribable::connect] that supplies the client's
-  ///   [`ApiInfo`] object to the call; if your [`Subscribable`]
+  ///   [`ApiInfo`] object and connection settings (such as a proxy) to
+  ///   the call; if your [`Subscribable`]
   ///   requires a different input then invoke its `connect` method
   ///   yourself
   #[instrument(level = "debug", skip(self))]
@@ -326,7 +990,111 @@ impl Client {
   where
     S: Subscribable<Input = ApiInfo>,
   {
-    S::connect(&self.api_info).await
+    S::connect_with(&self.api_info, &self.connection).await
+  }
+
+  /// Subscribe to the given subscribable, buffering up to `capacity`
+  /// received items.
+  ///
+  /// The stream is driven by a task spawned onto the Tokio runtime and
+  /// `overflow` is applied once the buffer is full. See [`Buffered`]
+  /// for details.
+  #[allow(clippy::type_complexity)]
+  #[instrument(level = "debug", skip(self))]
+  pub async fn subscribe_buffered<S>(
+    &self,
+    capacity: usize,
+    overflow: Overflow,
+  ) -> Result<(Buffered<<S::Stream as Stream>::Item>, S::Subscription), Error>
+  where
+    S: Subscribable<Input = ApiInfo>,
+    S::Stream: Stream + Send + 'static,
+    <S::Stream as Stream>::Item: Send + 'static,
+  {
+    let (stream, subscription) = self.subscribe::<S>().await?;
+    let stream = Buffered::new(stream, capacity, overflow);
+    Ok((stream, subscription))
+  }
+
+  /// Subscribe to realtime market data of the given feed.
+  ///
+  /// This method is the runtime equivalent of subscribing to
+  /// [`RealtimeData`] with the [`Source`][crate::data::v2::stream::Source]
+  /// type corresponding to `feed`, using the default bar, quote, and
+  /// trade types.
+  #[allow(clippy::type_complexity)]
+  #[instrument(level = "debug", skip(self))]
+  pub async fn subscribe_data(
+    &self,
+    feed: Feed,
+  ) -> Result<
+    (
+      <RealtimeData<IEX> as Subscribable>::Stream,
+      <RealtimeData<IEX> as Subscribable>::Subscription,
+    ),
+    Error,
+  > {
+    connect_feed(&self.api_info, feed, &self.connection).await
+  }
+
+  /// Subscribe to realtime market data streamed from the given URL.
+  ///
+  /// This method is the runtime equivalent of subscribing to
+  /// [`RealtimeData`] with a
+  /// [`CustomUrl`][crate::data::v2::stream::CustomUrl] source, using
+  /// the default bar, quote, and trade types. The endpoint at `url`
+  /// has to follow the `v2` handshake and message protocol.
+  #[allow(clippy::type_complexity)]
+  #[instrument(level = "debug", skip(self))]
+  pub async fn subscribe_data_url(
+    &self,
+    url: Url,
+  ) -> Result<
+    (
+      <RealtimeData<IEX> as Subscribable>::Stream,
+      <RealtimeData<IEX> as Subscribable>::Subscription,
+    ),
+    Error,
+  > {
+    connect_url(&self.api_info, &url, &self.connection).await
+  }
+
+  /// Retrieve the skew of the server's clock relative to the local one,
+  /// as most recently measured by
+  /// [`clock::measure_skew`][crate::api::v2::clock::measure_skew].
+  ///
+  /// A positive skew means that the server's clock is ahead. `None` is
+  /// returned if no measurement has been taken yet.
+  #[inline]
+  pub fn clock_skew(&self) -> Option<ChronoDuration> {
+    *self
+      .clock_skew
+      .lock()
+      .unwrap_or_else(|err| err.into_inner())
+  }
+
+  /// Record a newly measured clock skew.
+  pub(crate) fn set_clock_skew(&self, skew: ChronoDuration) {
+    let magnitude = if skew < ChronoDuration::zero() {
+      -skew
+    } else {
+      skew
+    };
+    let threshold = self.clock_skew_threshold.map(ChronoDuration::from_std);
+    if matches!(threshold, Some(Ok(threshold)) if magnitude > threshold) {
+      warn!(skew = display(&skew), "clock skew exceeds threshold");
+    }
+
+    *self
+      .clock_skew
+      .lock()
+      .unwrap_or_else(|err| err.into_inner()) = Some(skew);
+  }
+
+  /// Retrieve the asset cache of the client, if enabled.
+  #[inline]
+  pub(crate) fn asset_cache(&self) -> Option<&TtlCache> {
+    self.asset_cache.as_ref()
   }
 
   /// Retrieve the `ApiInfo` object used by this `Client` instance.
@@ -341,14 +1109,36 @@ impl Client {
 mod tests {
   use super::*;
 
+  use futures::future::pending;
+  use futures::future::BoxFuture;
+
   use http::StatusCode;
 
   use test_log::test;
 
+  use crate::api::v2::clock;
   use crate::endpoint::ApiError;
+  use crate::transport::test::mock_api_info;
+  use crate::transport::test::mock_client;
+  use crate::transport::MockTransport;
+  use crate::transport::TransportError;
   use crate::Str;
 
 
+  /// A transport that never responds to any request.
+  #[derive(Debug)]
+  struct Unresponsive;
+
+  impl Transport for Unresponsive {
+    fn issue(
+      &self,
+      _request: Request<Full<Bytes>>,
+    ) -> BoxFuture<'_, Result<Response<Bytes>, TransportError>> {
+      pending().boxed()
+    }
+  }
+
+
   Endpoint! {
     GetNotFound(()),
     Ok => (), [],
@@ -368,18 +1158,339 @@ mod tests {
     assert_eq!(&api_info, client.api_info());
   }
 
-  /// Check that formatting a [`DebugRequest`] masks secret values.
+  /// Check that formatting request headers masks secret values.
   #[test]
   fn request_debugging() {
     let api_info = ApiInfo::from_env().unwrap();
     let client = Client::builder().build(api_info);
 
     let request = client.request::<GetNotFound>(&()).unwrap();
-    let value = debug_request(&request);
+    let value = debug_headers(request.headers());
     let string = format!("{value:?}");
     assert!(string.contains("<masked>"), "{string}");
   }
 
+  /// Check that logged bodies adhere to the configured policy.
+  #[test]
+  fn body_logging() {
+    let body = br#"{"id":"abc","account_number":"PA1234567","cash":"1000"}"#;
+
+    let policy = LogPolicy::default();
+    let string = LoggedBody {
+      body,
+      policy: &policy,
+    }
+    .to_string();
+    assert_eq!(
+      string,
+      r#"{"id":"abc","account_number":"<masked>","cash":"1000"}"#
+    );
+
+    let policy = LogPolicy {
+      max_body_bytes: Some(11),
+      redact_account_numbers: false,
+      ..Default::default()
+    };
+    let string = LoggedBody {
+      body,
+      policy: &policy,
+    }
+    .to_string();
+    assert_eq!(string, r#"{"id":"abc"..."#);
+
+    let policy = LogPolicy {
+      max_body_bytes: None,
+      redact_account_numbers: false,
+      ..Default::default()
+    };
+    let string = LoggedBody {
+      body,
+      policy: &policy,
+    }
+    .to_string();
+    assert_eq!(string.as_bytes(), body);
+  }
+
+  /// Check that we mask all account numbers in a JSON text.
+  #[test]
+  fn account_number_redaction() {
+    let text = r#"[{"account_number" : "1"},{"account_number":"2"},{"account_number":null}]"#;
+    let redacted = redact_account_numbers(text);
+    assert_eq!(
+      redacted,
+      r#"[{"account_number":"<masked>"},{"account_number":"<masked>"},{"account_number":null}]"#
+    );
+
+    let text = r#"{"id":"abc"}"#;
+    assert!(matches!(redact_account_numbers(text), Cow::Borrowed(_)));
+  }
+
+  /// Check that requests to the Broker API use HTTP basic
+  /// authentication and are directed at the configured URL.
+  #[cfg(feature = "broker")]
+  #[test]
+  fn broker_request_authentication() {
+    use crate::broker::v1::accounts;
+
+    let mut api_info = mock_api_info();
+    api_info.broker_base_url = Url::parse("https://broker-api.alpaca.markets").unwrap();
+    let client = Client::builder().build(api_info);
+
+    let request = client
+      .request::<accounts::List>(&accounts::ListReq::default())
+      .unwrap();
+    assert_eq!(request.uri().host(), Some("broker-api.alpaca.markets"));
+    assert_eq!(
+      request.headers().get(AUTHORIZATION).unwrap(),
+      "Basic a2V5OnNlY3JldA=="
+    );
+    assert!(request.headers().get(HDR_KEY_ID).is_none());
+
+    let string = format!("{:?}", debug_headers(request.headers()));
+    assert!(!string.contains("a2V5OnNlY3JldA=="), "{string}");
+  }
+
+  /// Check that requests to the data API use the separate data API
+  /// credentials, if configured.
+  #[test]
+  fn data_request_authentication() {
+    use crate::api::v2::clock;
+    use crate::data::v2::last_quotes;
+
+    let api_info = mock_api_info().with_data_credentials("data-key", "data-secret");
+    let client = Client::builder().build(api_info);
+
+    let request = client.request::<clock::Get>(&()).unwrap();
+    assert_eq!(request.headers().get(HDR_KEY_ID).unwrap(), "key");
+    assert_eq!(request.headers().get(HDR_SECRET).unwrap(), "secret");
+
+    let request = client
+      .request::<last_quotes::Get>(&last_quotes::GetReqInit::default().init(["SPY"]))
+      .unwrap();
+    assert_eq!(request.headers().get(HDR_KEY_ID).unwrap(), "data-key");
+    assert_eq!(request.headers().get(HDR_SECRET).unwrap(), "data-secret");
+  }
+
+  /// Check that requests to the data API are directed at the
+  /// configured data API base URL.
+  #[test]
+  fn data_request_url() {
+    use crate::api::v2::clock;
+    use crate::data::v2::last_quotes;
+
+    let mut api_info = mock_api_info();
+    api_info.data_base_url = Url::parse("https://data.sandbox.alpaca.markets").unwrap();
+    let client = Client::builder().build(api_info);
+
+    let request = client.request::<clock::Get>(&()).unwrap();
+    assert_eq!(request.uri().host(), Some("paper-api.alpaca.markets"));
+
+    let request = client
+      .request::<last_quotes::Get>(&last_quotes::GetReqInit::default().init(["SPY"]))
+      .unwrap();
+    assert_eq!(request.uri().host(), Some("data.sandbox.alpaca.markets"));
+    assert_eq!(request.uri().path(), "/v2/stocks/quotes/latest");
+  }
+
+  /// Check that requests are routed through a custom transport, if
+  /// one is configured.
+  #[test(tokio::test)]
+  async fn issue_with_mock_transport() {
+    let mock = MockTransport::new();
+    let () = mock.respond::<GetNotFound>(
+      &(),
+      StatusCode::NOT_FOUND,
+      r#"{"code":40410000,"message":"endpoint not found"}"#,
+    );
+
+    let client = mock_client(mock);
+    let err = client.issue::<GetNotFound>(&()).await.unwrap_err();
+
+    match err {
+      RequestError::Endpoint(GetNotFoundError::UnexpectedStatus(status, message)) => {
+        let expected = ApiError {
+          code: Some(40410000),
+          message: "endpoint not found".to_string(),
+          ..Default::default()
+        };
+        assert_eq!(message, Ok(expected));
+        assert_eq!(status, StatusCode::NOT_FOUND);
+      },
+      _ => panic!("Received unexpected error: {err:?}"),
+    };
+  }
+
+  /// Check that order mutating and fund moving requests to the live
+  /// environment are refused until live trading is confirmed, if so
+  /// configured.
+  #[test(tokio::test)]
+  async fn guard_live_trading() {
+    use num_decimal::Num;
+
+    use crate::api::v2::crypto_funding;
+    use crate::api::v2::order;
+
+    let id = order::Id(uuid::Uuid::nil());
+    let withdrawal = crypto_funding::WithdrawalReq::new("BTC", Num::from(1), "address");
+    let mock = MockTransport::new();
+    let () = mock.respond::<order::Delete>(&id, StatusCode::NO_CONTENT, "");
+    let () = mock.respond::<clock::Get>(
+      &(),
+      StatusCode::OK,
+      r#"{
+        "timestamp": "2018-04-01T12:00:00.000Z",
+        "is_open": true,
+        "next_open": "2018-04-01T12:00:00.000Z",
+        "next_close": "2018-04-01T12:00:00.000Z"
+      }"#,
+    );
+
+    let client = Client::builder()
+      .with_transport(mock)
+      .guard_live_trading()
+      .build(ApiInfo::live("key", "secret"));
+
+    // Read-only requests are not affected by the guard.
+    let _clock = client.issue::<clock::Get>(&()).await.unwrap();
+
+    let err = client.issue::<order::Delete>(&id).await.unwrap_err();
+    assert!(
+      matches!(err, RequestError::LiveTradingNotConfirmed),
+      "{err:?}"
+    );
+
+    let err = client
+      .issue::<crypto_funding::Withdraw>(&withdrawal)
+      .await
+      .unwrap_err();
+    assert!(
+      matches!(err, RequestError::LiveTradingNotConfirmed),
+      "{err:?}"
+    );
+
+    let () = client.confirm_live_trading(true);
+    let () = client.issue::<order::Delete>(&id).await.unwrap();
+  }
+
+  /// Check that we can retrieve the raw body of a response alongside
+  /// the decoded output.
+  #[test(tokio::test)]
+  async fn issue_raw_with_mock_transport() {
+    let mock = MockTransport::new();
+    let () = mock.respond::<clock::Get>(
+      &(),
+      StatusCode::OK,
+      r#"{
+        "timestamp": "2018-04-01T12:00:00.000Z",
+        "is_open": true,
+        "next_open": "2018-04-01T12:00:00.000Z",
+        "next_close": "2018-04-01T12:00:00.000Z",
+        "unmapped": 42
+      }"#,
+    );
+
+    let client = mock_client(mock);
+    let raw = client.issue_raw::<clock::Get>(&()).await.unwrap();
+    assert!(raw.output.open);
+
+    let json = raw.json().unwrap();
+    assert_eq!(json["unmapped"], 42);
+  }
+
+  /// Check that the request ID reported by the server is surfaced in
+  /// results as well as errors.
+  #[test(tokio::test)]
+  async fn report_request_id() {
+    let mut headers = HeaderMap::new();
+    let _prev = headers.insert(HDR_REQUEST_ID, HeaderValue::from_static("abc-123"));
+
+    let mock = MockTransport::new();
+    let () = mock.respond_with_headers::<clock::Get>(
+      &(),
+      StatusCode::OK,
+      headers.clone(),
+      r#"{
+        "timestamp": "2018-04-01T12:00:00.000Z",
+        "is_open": true,
+        "next_open": "2018-04-01T12:00:00.000Z",
+        "next_close": "2018-04-01T12:00:00.000Z"
+      }"#,
+    );
+    let () = mock.respond_with_headers::<GetNotFound>(
+      &(),
+      StatusCode::NOT_FOUND,
+      headers,
+      r#"{"code":40410000,"message":"endpoint not found"}"#,
+    );
+
+    let client = mock_client(mock);
+    let raw = client.issue_raw::<clock::Get>(&()).await.unwrap();
+    assert_eq!(raw.request_id.as_deref(), Some("abc-123"));
+
+    let err = client.issue::<GetNotFound>(&()).await.unwrap_err();
+    assert_eq!(err.request_id(), Some("abc-123"));
+    assert_eq!(
+      err.api_error().unwrap().request_id.as_deref(),
+      Some("abc-123")
+    );
+  }
+
+  /// Check that requests without a primed response are reported as
+  /// transport errors.
+  #[test(tokio::test)]
+  async fn issue_with_unprimed_mock_transport() {
+    let client = mock_client(MockTransport::new());
+    let err = client.issue::<GetNotFound>(&()).await.unwrap_err();
+
+    match err {
+      RequestError::Transport(err) => {
+        assert_eq!(err.to_string(), "no response primed for GET /v2/foobarbaz")
+      },
+      _ => panic!("Received unexpected error: {err:?}"),
+    };
+  }
+
+  /// Check that requests time out after the timeout configured on the
+  /// builder.
+  #[test(tokio::test)]
+  async fn issue_with_default_timeout() {
+    let api_info = mock_api_info();
+    let client = Client::builder()
+      .with_transport(Unresponsive)
+      .timeout(Duration::from_millis(10))
+      .build(api_info);
+    let err = client.issue::<GetNotFound>(&()).await.unwrap_err();
+
+    match err {
+      RequestError::Timeout(duration) => assert_eq!(duration, Duration::from_millis(10)),
+      _ => panic!("Received unexpected error: {err:?}"),
+    };
+  }
+
+  /// Check that a per-request timeout takes precedence over the one
+  /// configured on the builder.
+  #[test(tokio::test)]
+  async fn issue_with_timeout_override() {
+    let api_info = mock_api_info();
+    let client = Client::builder()
+      .with_transport(Unresponsive)
+      .timeout(Duration::from_secs(3600))
+      .build(api_info);
+    let options = RequestOptions {
+      timeout: Some(Duration::from_millis(10)),
+      ..Default::default()
+    };
+    let err = client
+      .issue_with::<GetNotFound>(&(), options)
+      .await
+      .unwrap_err();
+
+    match err {
+      RequestError::Timeout(duration) => assert_eq!(duration, Duration::from_millis(10)),
+      _ => panic!("Received unexpected error: {err:?}"),
+    };
+  }
+
   /// Check basic workings of the HTTP status evaluation logic.
   #[test(tokio::test)]
   async fn unexpected_status_code_return() {
@@ -391,7 +1502,9 @@ mod tests {
     match err {
       RequestError::Endpoint(GetNotFoundError::UnexpectedStatus(status, message)) => {
         let expected = ApiError {
+          code: None,
           message: "endpoint not found".to_string(),
+          ..Default::default()
         };
         assert_eq!(message, Ok(expected));
         assert_eq!(status, StatusCode::NOT_FOUND);
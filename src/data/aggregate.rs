@@ -0,0 +1,419 @@
+// Copyright (C) 2024 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use chrono::DateTime;
+use chrono::Utc;
+
+use num_decimal::Num;
+
+use crate::data::v2::stream::Bar;
+use crate::data::v2::stream::Trade;
+
+
+/// An aggregate bar, as produced by an [`Aggregator`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AggregateBar {
+  /// The bar's symbol.
+  pub symbol: String,
+  /// The beginning of the time interval covered by the bar.
+  pub start: DateTime<Utc>,
+  /// The bar's open price.
+  pub open_price: Num,
+  /// The bar's high price.
+  pub high_price: Num,
+  /// The bar's low price.
+  pub low_price: Num,
+  /// The bar's close price.
+  pub close_price: Num,
+  /// The bar's volume.
+  pub volume: Num,
+  /// The bar's volume weighted average price.
+  ///
+  /// When aggregating bars, the typical price (the average of high,
+  /// low, and close) of each bar is used as a stand-in for the prices
+  /// of the trades it comprises.
+  pub vwap: Num,
+  /// The number of trades (or bars) aggregated.
+  pub count: u64,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+
+/// An update emitted by an [`Aggregator`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Update {
+  /// A bar that is still in progress, reflecting the data aggregated
+  /// so far.
+  ///
+  /// Partial updates are only emitted if enabled by means of
+  /// [`Aggregator::partial`].
+  Partial(AggregateBar),
+  /// A bar whose time interval has passed.
+  Complete(AggregateBar),
+}
+
+
+/// A data point to fold into a bar, i.e., a trade or a bar.
+struct Sample<'s> {
+  /// The sample's symbol.
+  symbol: &'s str,
+  /// The sample's time stamp.
+  time: DateTime<Utc>,
+  /// The sample's open price.
+  open: &'s Num,
+  /// The sample's high price.
+  high: &'s Num,
+  /// The sample's low price.
+  low: &'s Num,
+  /// The sample's close price.
+  close: &'s Num,
+  /// The sample's volume.
+  volume: &'s Num,
+  /// The (estimated) price times volume of the sample.
+  notional: Num,
+}
+
+
+/// The state of a bar in progress.
+#[derive(Debug)]
+struct State {
+  /// The bar aggregated so far.
+  bar: AggregateBar,
+  /// The sum of price times volume of all aggregated data.
+  notional: Num,
+}
+
+impl State {
+  /// Create the state for a bar starting at `start`, beginning with the
+  /// provided sample.
+  fn new(sample: &Sample<'_>, start: DateTime<Utc>) -> Self {
+    let bar = AggregateBar {
+      symbol: sample.symbol.to_string(),
+      start,
+      open_price: sample.open.clone(),
+      high_price: sample.high.clone(),
+      low_price: sample.low.clone(),
+      close_price: sample.close.clone(),
+      volume: Num::from(0),
+      vwap: sample.close.clone(),
+      count: 0,
+      _non_exhaustive: (),
+    };
+
+    Self {
+      bar,
+      notional: Num::from(0),
+    }
+  }
+
+  /// Fold a sample into the bar.
+  fn fold(&mut self, sample: Sample<'_>) {
+    let bar = &mut self.bar;
+    if sample.high > &bar.high_price {
+      bar.high_price = sample.high.clone();
+    }
+    if sample.low < &bar.low_price {
+      bar.low_price = sample.low.clone();
+    }
+    bar.close_price = sample.close.clone();
+    bar.volume += sample.volume;
+    bar.count += 1;
+
+    self.notional += sample.notional;
+    if !bar.volume.is_zero() {
+      bar.vwap = &self.notional / &bar.volume;
+    }
+  }
+}
+
+
+/// An aggregator building bars of arbitrary duration from streamed
+/// trades or (typically one minute) bars, e.g., as received via
+/// [`RealtimeData`][crate::data::v2::stream::RealtimeData].
+///
+/// Bars are aligned to wall-clock boundaries, i.e., the time intervals
+/// they cover are multiples of the configured duration since the Unix
+/// epoch. For durations evenly dividing a day, such as five minutes or
+/// one hour, that means intervals start at the expected times. A bar is
+/// completed when data for a later interval of the same symbol arrive
+/// or when explicitly [closed][Aggregator::close], e.g., from a timer.
+/// Data for an interval prior to the one currently being aggregated
+/// are ignored.
+#[derive(Debug)]
+pub struct Aggregator {
+  /// The duration of a bar, in milliseconds.
+  interval: i64,
+  /// Whether to emit updates for bars in progress.
+  partial: bool,
+  /// The bars in progress, keyed by symbol.
+  bars: HashMap<String, State>,
+}
+
+impl Aggregator {
+  /// Create a new aggregator producing bars of the given duration.
+  ///
+  /// Durations are truncated to full milliseconds, with a minimum of
+  /// one millisecond.
+  pub fn new(duration: Duration) -> Self {
+    let interval = i64::try_from(duration.as_millis())
+      .unwrap_or(i64::MAX)
+      .max(1);
+
+    Self {
+      interval,
+      partial: false,
+      bars: HashMap::new(),
+    }
+  }
+
+  /// Set whether to emit [`Update::Partial`] updates for bars in
+  /// progress, after each aggregated trade or bar.
+  ///
+  /// By default, only completed bars are emitted.
+  pub fn partial(mut self, partial: bool) -> Self {
+    self.partial = partial;
+    self
+  }
+
+  /// Calculate the start of the interval containing the given time.
+  fn start(&self, time: DateTime<Utc>) -> DateTime<Utc> {
+    let millis = time.timestamp_millis();
+    let start = millis - millis.rem_euclid(self.interval);
+    DateTime::from_timestamp_millis(start).unwrap_or(time)
+  }
+
+  /// Fold a sample into the bar in progress for its symbol.
+  fn push(&mut self, sample: Sample<'_>) -> Vec<Update> {
+    let mut updates = Vec::new();
+    let start = self.start(sample.time);
+
+    match self.bars.get(sample.symbol).map(|state| state.bar.start) {
+      Some(current) if current == start => (),
+      Some(current) if current > start => return updates,
+      _ => {
+        let state = State::new(&sample, start);
+        if let Some(previous) = self.bars.insert(sample.symbol.to_string(), state) {
+          let () = updates.push(Update::Complete(previous.bar));
+        }
+      },
+    }
+
+    // SANITY: We made sure that a bar is in progress for the symbol.
+    let state = self.bars.get_mut(sample.symbol).unwrap();
+    let () = state.fold(sample);
+    if self.partial {
+      let () = updates.push(Update::Partial(state.bar.clone()));
+    }
+    updates
+  }
+
+  /// Aggregate a trade.
+  pub fn push_trade<S>(&mut self, trade: &Trade<S>) -> Vec<Update>
+  where
+    S: AsRef<str>,
+  {
+    let price = &trade.trade_price;
+    self.push(Sample {
+      symbol: trade.symbol.as_ref(),
+      time: trade.timestamp,
+      open: price,
+      high: price,
+      low: price,
+      close: price,
+      volume: &trade.trade_size,
+      notional: price * &trade.trade_size,
+    })
+  }
+
+  /// Aggregate a bar.
+  ///
+  /// The bar is attributed to the interval containing its start.
+  pub fn push_bar<S>(&mut self, bar: &Bar<S>) -> Vec<Update>
+  where
+    S: AsRef<str>,
+  {
+    let typical = (&bar.high_price + &bar.low_price + &bar.close_price) / 3;
+    self.push(Sample {
+      symbol: bar.symbol.as_ref(),
+      time: bar.timestamp,
+      open: &bar.open_price,
+      high: &bar.high_price,
+      low: &bar.low_price,
+      close: &bar.close_price,
+      volume: &bar.volume,
+      notional: typical * &bar.volume,
+    })
+  }
+
+  /// Complete and retrieve all bars whose interval ended at or before
+  /// the provided time.
+  pub fn close(&mut self, now: DateTime<Utc>) -> Vec<AggregateBar> {
+    let interval = self.interval;
+    let ended = self
+      .bars
+      .iter()
+      .filter(|(_, state)| state.bar.start.timestamp_millis() + interval <= now.timestamp_millis())
+      .map(|(symbol, _)| symbol.clone())
+      .collect::<Vec<_>>();
+
+    let mut bars = ended
+      .into_iter()
+      .filter_map(|symbol| self.bars.remove(&symbol))
+      .map(|state| state.bar)
+      .collect::<Vec<_>>();
+    let () = bars.sort_by(|x, y| x.symbol.cmp(&y.symbol));
+    bars
+  }
+
+  /// Complete and retrieve all bars in progress, irrespective of
+  /// whether their interval has ended.
+  pub fn finish(&mut self) -> Vec<AggregateBar> {
+    let mut bars = self
+      .bars
+      .drain()
+      .map(|(_, state)| state.bar)
+      .collect::<Vec<_>>();
+    let () = bars.sort_by(|x, y| x.symbol.cmp(&y.symbol));
+    bars
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use test_log::test;
+
+
+  /// Create a trade for the given symbol at the given time.
+  fn trade(symbol: &str, time: &str, price: i64, size: i64) -> Trade {
+    Trade {
+      symbol: symbol.to_string(),
+      trade_id: 0,
+      trade_price: Num::from(price),
+      trade_size: Num::from(size),
+      timestamp: DateTime::parse_from_rfc3339(time).unwrap().into(),
+    }
+  }
+
+  /// Parse an RFC 3339 time stamp.
+  fn time(time: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(time).unwrap().into()
+  }
+
+
+  /// Check that trades are aggregated into wall-clock aligned bars.
+  #[test]
+  fn aggregate_trades() {
+    let mut aggregator = Aggregator::new(Duration::from_secs(5 * 60));
+
+    assert_eq!(
+      aggregator.push_trade(&trade("SPY", "2024-03-12T14:31:10Z", 10, 1)),
+      vec![]
+    );
+    assert_eq!(
+      aggregator.push_trade(&trade("SPY", "2024-03-12T14:33:00Z", 13, 2)),
+      vec![]
+    );
+    assert_eq!(
+      aggregator.push_trade(&trade("AAPL", "2024-03-12T14:34:00Z", 170, 1)),
+      vec![]
+    );
+    assert_eq!(
+      aggregator.push_trade(&trade("SPY", "2024-03-12T14:34:59Z", 8, 1)),
+      vec![]
+    );
+
+    let updates = aggregator.push_trade(&trade("SPY", "2024-03-12T14:35:00Z", 11, 1));
+    let bar = match updates.as_slice() {
+      [Update::Complete(bar)] => bar,
+      _ => panic!("encountered unexpected updates: {updates:?}"),
+    };
+    assert_eq!(bar.symbol, "SPY");
+    assert_eq!(bar.start, time("2024-03-12T14:30:00Z"));
+    assert_eq!(bar.open_price, Num::from(10));
+    assert_eq!(bar.high_price, Num::from(13));
+    assert_eq!(bar.low_price, Num::from(8));
+    assert_eq!(bar.close_price, Num::from(8));
+    assert_eq!(bar.volume, Num::from(4));
+    assert_eq!(bar.vwap, Num::from(11));
+    assert_eq!(bar.count, 3);
+
+    // Late trades are ignored.
+    assert_eq!(
+      aggregator.push_trade(&trade("SPY", "2024-03-12T14:34:00Z", 100, 1)),
+      vec![]
+    );
+
+    let bars = aggregator.close(time("2024-03-12T14:35:00Z"));
+    assert_eq!(bars.len(), 1);
+    assert_eq!(bars[0].symbol, "AAPL");
+
+    let bars = aggregator.finish();
+    assert_eq!(bars.len(), 1);
+    assert_eq!(bars[0].symbol, "SPY");
+    assert_eq!(bars[0].close_price, Num::from(11));
+    assert_eq!(bars[0].count, 1);
+  }
+
+  /// Check that partial updates are emitted, if so configured.
+  #[test]
+  fn aggregate_partial() {
+    let mut aggregator = Aggregator::new(Duration::from_secs(60 * 60)).partial(true);
+    let _updates = aggregator.push_trade(&trade("SPY", "2024-03-12T14:31:10Z", 10, 1));
+    let updates = aggregator.push_trade(&trade("SPY", "2024-03-12T14:59:10Z", 12, 1));
+    let bar = match updates.as_slice() {
+      [Update::Partial(bar)] => bar,
+      _ => panic!("encountered unexpected updates: {updates:?}"),
+    };
+    assert_eq!(bar.start, time("2024-03-12T14:00:00Z"));
+    assert_eq!(bar.high_price, Num::from(12));
+    assert_eq!(bar.vwap, Num::from(11));
+
+    let updates = aggregator.push_trade(&trade("SPY", "2024-03-12T15:00:00Z", 9, 1));
+    assert!(
+      matches!(
+        updates.as_slice(),
+        [Update::Complete(..), Update::Partial(..)]
+      ),
+      "{updates:?}"
+    );
+  }
+
+  /// Check that one minute bars can be aggregated.
+  #[test]
+  fn aggregate_bars() {
+    let bar = |start: &str, open, high, low, close, volume| Bar {
+      symbol: "SPY".to_string(),
+      open_price: Num::from(open),
+      high_price: Num::from(high),
+      low_price: Num::from(low),
+      close_price: Num::from(close),
+      volume: Num::from(volume),
+      trade_count: None,
+      timestamp: time(start),
+    };
+
+    let mut aggregator = Aggregator::new(Duration::from_secs(15 * 60));
+    let _updates = aggregator.push_bar(&bar("2024-03-12T14:30:00Z", 10, 12, 9, 9, 10));
+    let _updates = aggregator.push_bar(&bar("2024-03-12T14:44:00Z", 9, 15, 9, 15, 10));
+
+    let bars = aggregator.close(time("2024-03-12T14:45:00Z"));
+    assert_eq!(bars.len(), 1);
+    assert_eq!(bars[0].start, time("2024-03-12T14:30:00Z"));
+    assert_eq!(bars[0].open_price, Num::from(10));
+    assert_eq!(bars[0].high_price, Num::from(15));
+    assert_eq!(bars[0].low_price, Num::from(9));
+    assert_eq!(bars[0].close_price, Num::from(15));
+    assert_eq!(bars[0].volume, Num::from(20));
+    // The typical prices are 10 and 13, respectively.
+    assert_eq!(bars[0].vwap, Num::new(23, 2));
+  }
+}
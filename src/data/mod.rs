@@ -1,6 +1,15 @@
 // Copyright (C) 2020-2022 The apca Developers
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+/// Functionality for aggregating streamed trades and bars into bars
+/// of arbitrary duration.
+pub mod aggregate;
+/// Functionality for persisting streamed market data to files.
+#[cfg(feature = "sink")]
+pub mod sink;
+/// Definitions for the beta version of the Alpaca Data API, covering
+/// functionality not yet available in the second version.
+pub mod v1beta1;
 /// Definitions for the second version of the Alpaca Data API.
 pub mod v2;
 
@@ -0,0 +1,990 @@
+// Copyright (C) 2024 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::fs::create_dir_all;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::BufWriter;
+use std::io::Error as IoError;
+#[cfg(feature = "parquet")]
+use std::io::ErrorKind;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+#[cfg(feature = "parquet")]
+use std::sync::Arc;
+
+use chrono::DateTime;
+use chrono::SecondsFormat;
+use chrono::Utc;
+
+use futures::Stream;
+use futures::StreamExt as _;
+
+use num_decimal::Num;
+
+#[cfg(feature = "parquet")]
+use parquet::data_type::ByteArray;
+#[cfg(feature = "parquet")]
+use parquet::data_type::ByteArrayType;
+#[cfg(feature = "parquet")]
+use parquet::data_type::DoubleType;
+#[cfg(feature = "parquet")]
+use parquet::data_type::Int64Type;
+#[cfg(feature = "parquet")]
+use parquet::errors::ParquetError;
+#[cfg(feature = "parquet")]
+use parquet::file::properties::WriterProperties;
+#[cfg(feature = "parquet")]
+use parquet::file::writer::SerializedColumnWriter;
+#[cfg(feature = "parquet")]
+use parquet::file::writer::SerializedFileWriter;
+#[cfg(feature = "parquet")]
+use parquet::schema::parser::parse_message_type;
+
+use serde_json::Error as JsonError;
+
+use thiserror::Error;
+
+use websocket_util::tungstenite::Error as WebSocketError;
+
+use crate::data::v2::stream::Bar;
+use crate::data::v2::stream::Data;
+use crate::data::v2::stream::Quote;
+use crate::data::v2::stream::Trade;
+
+
+/// The type of a column of a [`Record`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ColumnType {
+  /// A string.
+  ///
+  /// Strings are stored as UTF-8 strings in Parquet files.
+  Str,
+  /// A (decimal) number.
+  ///
+  /// Numbers are stored as doubles in Parquet files.
+  Num,
+  /// An unsigned integer.
+  ///
+  /// Unsigned integers are stored as unsigned 64 bit integers in Parquet
+  /// files.
+  UInt,
+  /// A time stamp.
+  ///
+  /// Time stamps are stored with nanosecond precision and adjusted to
+  /// UTC in Parquet files.
+  Time,
+}
+
+
+/// A single value of a [`Record`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum Value<'r> {
+  /// A string.
+  Str(&'r str),
+  /// A (decimal) number.
+  Num(&'r Num),
+  /// An unsigned integer.
+  UInt(u64),
+  /// A time stamp.
+  Time(&'r DateTime<Utc>),
+}
+
+
+/// A record that can be persisted as a row of a file.
+pub trait Record {
+  /// The kind of record, used as the base name of the files it is
+  /// written to.
+  const KIND: &'static str;
+  /// The names and types of the columns of the record.
+  const COLUMNS: &'static [(&'static str, ColumnType)];
+
+  /// Retrieve the record's time stamp.
+  fn timestamp(&self) -> &DateTime<Utc>;
+
+  /// Retrieve the values of the record, in the order of
+  /// [`COLUMNS`][Record::COLUMNS].
+  fn values(&self) -> Vec<Value<'_>>;
+}
+
+
+impl<S> Record for Bar<S>
+where
+  S: AsRef<str>,
+{
+  const KIND: &'static str = "bars";
+  const COLUMNS: &'static [(&'static str, ColumnType)] = &[
+    ("symbol", ColumnType::Str),
+    ("open", ColumnType::Num),
+    ("high", ColumnType::Num),
+    ("low", ColumnType::Num),
+    ("close", ColumnType::Num),
+    ("volume", ColumnType::Num),
+    ("timestamp", ColumnType::Time),
+  ];
+
+  #[inline]
+  fn timestamp(&self) -> &DateTime<Utc> {
+    &self.timestamp
+  }
+
+  fn values(&self) -> Vec<Value<'_>> {
+    vec![
+      Value::Str(self.symbol.as_ref()),
+      Value::Num(&self.open_price),
+      Value::Num(&self.high_price),
+      Value::Num(&self.low_price),
+      Value::Num(&self.close_price),
+      Value::Num(&self.volume),
+      Value::Time(&self.timestamp),
+    ]
+  }
+}
+
+impl<S> Record for Quote<S>
+where
+  S: AsRef<str>,
+{
+  const KIND: &'static str = "quotes";
+  const COLUMNS: &'static [(&'static str, ColumnType)] = &[
+    ("symbol", ColumnType::Str),
+    ("bid_price", ColumnType::Num),
+    ("bid_size", ColumnType::Num),
+    ("ask_price", ColumnType::Num),
+    ("ask_size", ColumnType::Num),
+    ("timestamp", ColumnType::Time),
+  ];
+
+  #[inline]
+  fn timestamp(&self) -> &DateTime<Utc> {
+    &self.timestamp
+  }
+
+  fn values(&self) -> Vec<Value<'_>> {
+    vec![
+      Value::Str(self.symbol.as_ref()),
+      Value::Num(&self.bid_price),
+      Value::Num(&self.bid_size),
+      Value::Num(&self.ask_price),
+      Value::Num(&self.ask_size),
+      Value::Time(&self.timestamp),
+    ]
+  }
+}
+
+impl<S> Record for Trade<S>
+where
+  S: AsRef<str>,
+{
+  const KIND: &'static str = "trades";
+  const COLUMNS: &'static [(&'static str, ColumnType)] = &[
+    ("symbol", ColumnType::Str),
+    ("trade_id", ColumnType::UInt),
+    ("price", ColumnType::Num),
+    ("size", ColumnType::Num),
+    ("timestamp", ColumnType::Time),
+  ];
+
+  #[inline]
+  fn timestamp(&self) -> &DateTime<Utc> {
+    &self.timestamp
+  }
+
+  fn values(&self) -> Vec<Value<'_>> {
+    vec![
+      Value::Str(self.symbol.as_ref()),
+      Value::UInt(self.trade_id),
+      Value::Num(&self.trade_price),
+      Value::Num(&self.trade_size),
+      Value::Time(&self.timestamp),
+    ]
+  }
+}
+
+
+/// A file format records can be persisted in.
+pub trait Format {
+  /// The writer for a single file.
+  type Writer;
+
+  /// Open a file for records of type `R` in `directory`, with `name`
+  /// (lacking an extension) as the base name.
+  fn open<R>(directory: &Path, name: &str) -> Result<Self::Writer, IoError>
+  where
+    R: Record;
+
+  /// Write the provided record to a file.
+  fn write<R>(writer: &mut Self::Writer, record: &R) -> Result<(), IoError>
+  where
+    R: Record;
+
+  /// Flush all buffered records to a file.
+  fn flush(writer: &mut Self::Writer) -> Result<(), IoError>;
+
+  /// Finish writing a file.
+  fn finish(writer: Self::Writer) -> Result<(), IoError>;
+}
+
+
+/// The number of rows buffered before they are written to a Parquet
+/// file as a row group.
+#[cfg(feature = "parquet")]
+const ROW_GROUP_SIZE: usize = 64 * 1024;
+
+
+/// Format a time stamp for inclusion in a CSV row.
+fn format_time(time: &DateTime<Utc>) -> String {
+  time.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+
+/// Escape a string for inclusion in a CSV row.
+fn escape(string: &str) -> String {
+  if string.contains([',', '"', '\n', '\r']) {
+    format!("\"{}\"", string.replace('"', "\"\""))
+  } else {
+    string.to_string()
+  }
+}
+
+
+/// The CSV file format.
+///
+/// Files have a header listing the names of the columns. Records get
+/// appended to existing files.
+#[derive(Clone, Copy, Debug)]
+pub struct Csv;
+
+impl Format for Csv {
+  type Writer = BufWriter<File>;
+
+  fn open<R>(directory: &Path, name: &str) -> Result<Self::Writer, IoError>
+  where
+    R: Record,
+  {
+    let file = OpenOptions::new()
+      .create(true)
+      .append(true)
+      .open(directory.join(format!("{name}.csv")))?;
+    let empty = file.metadata()?.len() == 0;
+    let mut writer = BufWriter::new(file);
+    if empty {
+      let header = R::COLUMNS
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(",");
+      let () = writeln!(writer, "{header}")?;
+    }
+    Ok(writer)
+  }
+
+  fn write<R>(writer: &mut Self::Writer, record: &R) -> Result<(), IoError>
+  where
+    R: Record,
+  {
+    for (i, value) in record.values().into_iter().enumerate() {
+      if i > 0 {
+        let () = writer.write_all(b",")?;
+      }
+
+      let () = match value {
+        Value::Str(string) => write!(writer, "{}", escape(string)),
+        Value::Num(num) => write!(writer, "{num}"),
+        Value::UInt(int) => write!(writer, "{int}"),
+        Value::Time(time) => write!(writer, "{}", format_time(time)),
+      }?;
+    }
+    writer.write_all(b"\n")
+  }
+
+  #[inline]
+  fn flush(writer: &mut Self::Writer) -> Result<(), IoError> {
+    writer.flush()
+  }
+
+  #[inline]
+  fn finish(mut writer: Self::Writer) -> Result<(), IoError> {
+    writer.flush()
+  }
+}
+
+
+/// The values of a single column of a Parquet file, buffered until
+/// written as part of a row group.
+#[cfg(feature = "parquet")]
+#[derive(Debug)]
+enum Column {
+  /// Values of a [`ColumnType::Str`] column.
+  ByteArray(Vec<ByteArray>),
+  /// Values of a [`ColumnType::Num`] column.
+  Double(Vec<f64>),
+  /// Values of a [`ColumnType::UInt`] or [`ColumnType::Time`] column.
+  Int64(Vec<i64>),
+}
+
+#[cfg(feature = "parquet")]
+impl Column {
+  /// Create an empty column for the given column type.
+  fn new(type_: ColumnType) -> Self {
+    match type_ {
+      ColumnType::Str => Self::ByteArray(Vec::new()),
+      ColumnType::Num => Self::Double(Vec::new()),
+      ColumnType::UInt | ColumnType::Time => Self::Int64(Vec::new()),
+    }
+  }
+
+  /// Buffer the provided value.
+  fn push(&mut self, value: Value<'_>) -> Result<(), IoError> {
+    match (self, value) {
+      (Self::ByteArray(values), Value::Str(string)) => values.push(ByteArray::from(string)),
+      // Numbers not representable as doubles end up as NaN.
+      (Self::Double(values), Value::Num(num)) => values.push(num.to_f64().unwrap_or(f64::NAN)),
+      // Unsigned integers are stored in the bit pattern of a signed one,
+      // as per the Parquet format specification.
+      (Self::Int64(values), Value::UInt(int)) => values.push(int as i64),
+      (Self::Int64(values), Value::Time(time)) => {
+        let nanos = time
+          .timestamp()
+          .checked_mul(1_000_000_000)
+          .and_then(|nanos| nanos.checked_add(i64::from(time.timestamp_subsec_nanos())))
+          .ok_or_else(|| {
+            IoError::new(
+              ErrorKind::InvalidData,
+              format!("time stamp {time} is out of range"),
+            )
+          })?;
+        values.push(nanos)
+      },
+      (_, value) => {
+        return Err(IoError::new(
+          ErrorKind::InvalidData,
+          format!("value {value:?} does not match column type"),
+        ))
+      },
+    }
+    Ok(())
+  }
+
+  /// Write all buffered values to the provided column writer.
+  fn write(&mut self, writer: &mut SerializedColumnWriter<'_>) -> Result<(), ParquetError> {
+    let _count = match self {
+      Self::ByteArray(values) => {
+        let count = writer
+          .typed::<ByteArrayType>()
+          .write_batch(values, None, None)?;
+        values.clear();
+        count
+      },
+      Self::Double(values) => {
+        let count = writer
+          .typed::<DoubleType>()
+          .write_batch(values, None, None)?;
+        values.clear();
+        count
+      },
+      Self::Int64(values) => {
+        let count = writer
+          .typed::<Int64Type>()
+          .write_batch(values, None, None)?;
+        values.clear();
+        count
+      },
+    };
+    Ok(())
+  }
+}
+
+
+/// A writer for a single Parquet file.
+///
+/// Writing of the file is finished when the object is dropped.
+#[cfg(feature = "parquet")]
+#[derive(Debug)]
+pub struct ParquetWriter {
+  /// The underlying file writer.
+  writer: Option<SerializedFileWriter<File>>,
+  /// The values buffered for each column.
+  columns: Vec<Column>,
+  /// The number of buffered rows.
+  rows: usize,
+}
+
+#[cfg(feature = "parquet")]
+impl ParquetWriter {
+  /// Write all buffered rows as a row group.
+  fn write_row_group(&mut self) -> Result<(), ParquetError> {
+    let writer = match &mut self.writer {
+      Some(writer) if self.rows > 0 => writer,
+      _ => return Ok(()),
+    };
+
+    let mut row_group = writer.next_row_group()?;
+    for column in &mut self.columns {
+      let mut writer = row_group
+        .next_column()?
+        .ok_or_else(|| ParquetError::General("schema is missing a column".to_string()))?;
+      let () = column.write(&mut writer)?;
+      let () = writer.close()?;
+    }
+    let _metadata = row_group.close()?;
+    self.rows = 0;
+    Ok(())
+  }
+
+  /// Write all buffered rows and finish the file.
+  fn finish(&mut self) -> Result<(), ParquetError> {
+    let () = self.write_row_group()?;
+    if let Some(writer) = self.writer.take() {
+      let _metadata = writer.close()?;
+    }
+    Ok(())
+  }
+}
+
+#[cfg(feature = "parquet")]
+impl Drop for ParquetWriter {
+  fn drop(&mut self) {
+    let _result = self.finish();
+  }
+}
+
+
+/// Convert a Parquet error into an I/O error.
+#[cfg(feature = "parquet")]
+fn io_error(error: ParquetError) -> IoError {
+  match error {
+    ParquetError::External(error) => match error.downcast::<IoError>() {
+      Ok(error) => *error,
+      Err(error) => IoError::new(ErrorKind::Other, error),
+    },
+    error => IoError::new(ErrorKind::Other, error),
+  }
+}
+
+
+/// The Parquet file format.
+///
+/// Records are buffered in memory and written as row groups. Because
+/// Parquet files cannot be appended to, existing files are never
+/// modified. Instead, a numeric suffix is added to the name of the
+/// file, e.g., `trades-2024-03-12.1.parquet`. Files are only complete
+/// once they got rotated or the sink got dropped.
+#[cfg(feature = "parquet")]
+#[derive(Clone, Copy, Debug)]
+pub struct Parquet;
+
+#[cfg(feature = "parquet")]
+impl Format for Parquet {
+  type Writer = ParquetWriter;
+
+  fn open<R>(directory: &Path, name: &str) -> Result<Self::Writer, IoError>
+  where
+    R: Record,
+  {
+    let fields = R::COLUMNS
+      .iter()
+      .map(|(name, type_)| {
+        let type_ = match type_ {
+          ColumnType::Str => "BYTE_ARRAY",
+          ColumnType::Num => "DOUBLE",
+          ColumnType::UInt | ColumnType::Time => "INT64",
+        };
+        let annotation = match R::COLUMNS.iter().find(|(column, _)| column == name) {
+          Some((_, ColumnType::Str)) => " (STRING)",
+          Some((_, ColumnType::UInt)) => " (INTEGER(64, false))",
+          Some((_, ColumnType::Time)) => " (TIMESTAMP(NANOS, true))",
+          _ => "",
+        };
+        format!("REQUIRED {type_} {name}{annotation};")
+      })
+      .collect::<Vec<_>>()
+      .join(" ");
+    let schema = parse_message_type(&format!("message {} {{ {fields} }}", R::KIND))
+      .map(Arc::new)
+      .map_err(io_error)?;
+
+    let file = (0..)
+      .map(|i| {
+        let name = if i == 0 {
+          format!("{name}.parquet")
+        } else {
+          format!("{name}.{i}.parquet")
+        };
+        OpenOptions::new()
+          .write(true)
+          .create_new(true)
+          .open(directory.join(name))
+      })
+      .find(|result| !matches!(result, Err(err) if err.kind() == ErrorKind::AlreadyExists))
+      .unwrap_or_else(|| unreachable!())?;
+
+    let properties = Arc::new(WriterProperties::builder().build());
+    let writer = SerializedFileWriter::new(file, schema, properties).map_err(io_error)?;
+    Ok(ParquetWriter {
+      writer: Some(writer),
+      columns: R::COLUMNS
+        .iter()
+        .map(|(_, type_)| Column::new(*type_))
+        .collect(),
+      rows: 0,
+    })
+  }
+
+  fn write<R>(writer: &mut Self::Writer, record: &R) -> Result<(), IoError>
+  where
+    R: Record,
+  {
+    for (column, value) in writer.columns.iter_mut().zip(record.values()) {
+      let () = column.push(value)?;
+    }
+    writer.rows += 1;
+
+    if writer.rows >= ROW_GROUP_SIZE {
+      let () = writer.write_row_group().map_err(io_error)?;
+    }
+    Ok(())
+  }
+
+  fn flush(writer: &mut Self::Writer) -> Result<(), IoError> {
+    let () = writer.write_row_group().map_err(io_error)?;
+    match &mut writer.writer {
+      Some(writer) => writer.flush(),
+      None => Ok(()),
+    }
+  }
+
+  fn finish(mut writer: Self::Writer) -> Result<(), IoError> {
+    writer.finish().map_err(io_error)
+  }
+}
+
+
+/// The policy for starting new files.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Rotation {
+  /// Write all records of a kind to a single file, e.g., `trades.csv`.
+  Never,
+  /// Start a new file for every (UTC) day, as per the time stamps of
+  /// the records, e.g., `trades-2024-03-12.csv`.
+  #[default]
+  Daily,
+  /// Start a new file after the given number of records, e.g.,
+  /// `trades-000001.csv`.
+  ///
+  /// Numbering starts from zero whenever a sink is created, meaning
+  /// that records get appended to already existing files.
+  Records(u64),
+}
+
+
+/// The file currently being written for a kind of record.
+struct Output<W> {
+  /// The key identifying the file in the context of the rotation
+  /// policy.
+  key: String,
+  /// The writer for the file.
+  writer: W,
+  /// The number of records written to the file.
+  records: u64,
+}
+
+
+/// The state of the files for a single kind of record.
+struct Files<F>
+where
+  F: Format,
+{
+  /// The file currently being written, if any.
+  output: Option<Output<F::Writer>>,
+  /// The number of files started so far with [`Rotation::Records`].
+  sequence: u64,
+}
+
+impl<F> Default for Files<F>
+where
+  F: Format,
+{
+  fn default() -> Self {
+    Self {
+      output: None,
+      sequence: 0,
+    }
+  }
+}
+
+impl<F> Files<F>
+where
+  F: Format,
+{
+  /// Write the provided record to a file in `directory`, honoring the
+  /// rotation policy.
+  fn write<R>(&mut self, directory: &Path, rotation: Rotation, record: &R) -> Result<(), IoError>
+  where
+    R: Record,
+  {
+    let key = match rotation {
+      Rotation::Never => String::new(),
+      Rotation::Daily => record.timestamp().format("%Y-%m-%d").to_string(),
+      Rotation::Records(count) => {
+        let full = self
+          .output
+          .as_ref()
+          .map(|output| output.records >= count.max(1))
+          .unwrap_or(false);
+        if full {
+          self.sequence += 1;
+        }
+        format!("{:06}", self.sequence)
+      },
+    };
+
+    let output = match &mut self.output {
+      Some(output) if output.key == key => output,
+      output => {
+        if let Some(previous) = output.take() {
+          let () = F::finish(previous.writer)?;
+        }
+
+        let name = if key.is_empty() {
+          R::KIND.to_string()
+        } else {
+          format!("{}-{key}", R::KIND)
+        };
+        output.insert(Output {
+          writer: F::open::<R>(directory, &name)?,
+          key,
+          records: 0,
+        })
+      },
+    };
+
+    let () = F::write(&mut output.writer, record)?;
+    output.records += 1;
+    Ok(())
+  }
+
+  /// Flush the file currently being written, if any.
+  fn flush(&mut self) -> Result<(), IoError> {
+    match &mut self.output {
+      Some(output) => F::flush(&mut output.writer),
+      None => Ok(()),
+    }
+  }
+}
+
+
+/// A sink persisting realtime bars, quotes, and trades to rotating
+/// files of format `F`, one set of files per kind of data.
+///
+/// Data is buffered in memory and only guaranteed to be written to disk
+/// after a [`flush`][Sink::flush] or once the sink is dropped.
+pub struct Sink<F>
+where
+  F: Format,
+{
+  /// The directory to write files to.
+  directory: PathBuf,
+  /// The policy for starting new files.
+  rotation: Rotation,
+  /// The files for bars.
+  bars: Files<F>,
+  /// The files for quotes.
+  quotes: Files<F>,
+  /// The files for trades.
+  trades: Files<F>,
+}
+
+impl<F> Sink<F>
+where
+  F: Format,
+{
+  /// Create a sink writing files to the given directory, creating it
+  /// if necessary.
+  pub fn new(directory: impl Into<PathBuf>) -> Result<Self, IoError> {
+    let directory = directory.into();
+    let () = create_dir_all(&directory)?;
+
+    Ok(Self {
+      directory,
+      rotation: Rotation::default(),
+      bars: Files::default(),
+      quotes: Files::default(),
+      trades: Files::default(),
+    })
+  }
+
+  /// Set the policy for starting new files.
+  ///
+  /// By default, [`Rotation::Daily`] is used.
+  pub fn rotation(mut self, rotation: Rotation) -> Self {
+    self.rotation = rotation;
+    self
+  }
+
+  /// Persist the provided data item.
+  ///
+  /// Items other than bars, quotes, and trades are ignored. Daily and
+  /// updated bars are persisted along with regular ones.
+  pub fn write<B, Q, T>(&mut self, data: &Data<B, Q, T>) -> Result<(), IoError>
+  where
+    B: Record,
+    Q: Record,
+    T: Record,
+  {
+    match data {
+      Data::Bar(bar) | Data::DailyBar(bar) | Data::UpdatedBar(bar) => {
+        self.bars.write(&self.directory, self.rotation, bar)
+      },
+      Data::Quote(quote) => self.quotes.write(&self.directory, self.rotation, quote),
+      Data::Trade(trade) => self.trades.write(&self.directory, self.rotation, trade),
+      _ => Ok(()),
+    }
+  }
+
+  /// Flush all buffered data to disk.
+  pub fn flush(&mut self) -> Result<(), IoError> {
+    let () = self.bars.flush()?;
+    let () = self.quotes.flush()?;
+    let () = self.trades.flush()?;
+    Ok(())
+  }
+}
+
+impl<F> Debug for Sink<F>
+where
+  F: Format,
+{
+  fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+    fmt
+      .debug_struct("Sink")
+      .field("directory", &self.directory)
+      .field("rotation", &self.rotation)
+      .finish_non_exhaustive()
+  }
+}
+
+
+/// A sink persisting data to CSV files.
+pub type CsvSink = Sink<Csv>;
+
+/// A sink persisting data to Parquet files.
+#[cfg(feature = "parquet")]
+pub type ParquetSink = Sink<Parquet>;
+
+
+/// An error as reported by [`persist`].
+#[derive(Debug, Error)]
+pub enum PersistError {
+  /// Writing data failed.
+  #[error("failed to persist data")]
+  Io(#[source] IoError),
+  /// A message received over the stream could not be decoded.
+  #[error("failed to decode message")]
+  Json(#[source] JsonError),
+  /// The websocket connection reported an error.
+  #[error("encountered a websocket related error")]
+  WebSocket(#[source] WebSocketError),
+}
+
+
+/// Persist all bars, quotes, and trades yielded by a realtime data
+/// stream (such as the one created for
+/// [`RealtimeData`][crate::data::v2::stream::RealtimeData]) until it
+/// ends or reports an error.
+///
+/// The sink is flushed before returning.
+pub async fn persist<S, F, B, Q, T>(stream: S, sink: &mut Sink<F>) -> Result<(), PersistError>
+where
+  S: Stream<Item = Result<Result<Data<B, Q, T>, JsonError>, WebSocketError>>,
+  F: Format,
+  B: Record,
+  Q: Record,
+  T: Record,
+{
+  let mut stream = Box::pin(stream);
+  let result = async {
+    while let Some(result) = stream.next().await {
+      let data = result
+        .map_err(PersistError::WebSocket)?
+        .map_err(PersistError::Json)?;
+      let () = sink.write(&data).map_err(PersistError::Io)?;
+    }
+    Ok(())
+  }
+  .await;
+
+  let () = sink.flush().map_err(PersistError::Io)?;
+  result
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use std::env::temp_dir;
+  use std::fs::read_to_string;
+  use std::fs::remove_dir_all;
+  use std::process::id;
+
+  use futures::stream::iter;
+
+  use serde_json::from_str as json_from_str;
+
+  use test_log::test;
+
+
+  /// Create a unique directory path for the test with the given name.
+  fn directory(name: &str) -> PathBuf {
+    let directory = temp_dir().join(format!("apca-sink-{}-{name}", id()));
+    let _result = remove_dir_all(&directory);
+    directory
+  }
+
+  /// Create a trade for the given symbol at the given time.
+  fn trade(symbol: &str, id: u64, time: &str) -> Trade {
+    Trade {
+      symbol: symbol.to_string(),
+      trade_id: id,
+      trade_price: Num::new(12345, 100),
+      trade_size: Num::from(10),
+      timestamp: DateTime::parse_from_rfc3339(time).unwrap().into(),
+    }
+  }
+
+
+  /// Check that records are written to daily rotated files.
+  #[test(tokio::test)]
+  async fn persist_daily() {
+    let directory = directory("daily");
+    let mut sink = CsvSink::new(&directory).unwrap();
+
+    let quote = json_from_str::<Quote>(
+      r#"{"S":"SPY","bp":415.1,"bs":3,"ap":415.12,"as":1,"t":"2023-04-06T13:45:26Z"}"#,
+    )
+    .unwrap();
+    let items: Vec<Result<Result<Data, JsonError>, WebSocketError>> = vec![
+      Ok(Ok(Data::Trade(trade("SPY", 1, "2023-04-06T23:59:59Z")))),
+      Ok(Ok(Data::Quote(quote))),
+      Ok(Ok(Data::Trade(trade("BRK,B", 2, "2023-04-07T00:00:00.5Z")))),
+    ];
+    let () = persist(iter(items), &mut sink).await.unwrap();
+
+    let trades = read_to_string(directory.join("trades-2023-04-06.csv")).unwrap();
+    assert_eq!(
+      trades,
+      "symbol,trade_id,price,size,timestamp\nSPY,1,123.45,10,2023-04-06T23:59:59Z\n"
+    );
+    let trades = read_to_string(directory.join("trades-2023-04-07.csv")).unwrap();
+    assert_eq!(
+      trades,
+      "symbol,trade_id,price,size,timestamp\n\"BRK,B\",2,123.45,10,2023-04-07T00:00:00.500Z\n"
+    );
+    let quotes = read_to_string(directory.join("quotes-2023-04-06.csv")).unwrap();
+    assert_eq!(
+      quotes,
+      "symbol,bid_price,bid_size,ask_price,ask_size,timestamp\nSPY,415.1,3,415.12,1,2023-04-06T13:45:26Z\n"
+    );
+
+    let () = remove_dir_all(&directory).unwrap();
+  }
+
+  /// Check that files are rotated after a given number of records and
+  /// that existing files are appended to.
+  #[test]
+  fn rotate_by_records() {
+    let directory = directory("records");
+    let time = "2023-04-06T13:45:26Z";
+
+    {
+      let mut sink = CsvSink::new(&directory)
+        .unwrap()
+        .rotation(Rotation::Records(2));
+      for id in 1..=3 {
+        let data = Data::<Bar, Quote, Trade>::Trade(trade("SPY", id, time));
+        let () = sink.write(&data).unwrap();
+      }
+    }
+
+    let mut sink = CsvSink::new(&directory).unwrap().rotation(Rotation::Never);
+    let data = Data::<Bar, Quote, Trade>::Trade(trade("SPY", 4, time));
+    let () = sink.write(&data).unwrap();
+    let () = sink.flush().unwrap();
+
+    let first = read_to_string(directory.join("trades-000000.csv")).unwrap();
+    assert_eq!(first.lines().count(), 3);
+    let second = read_to_string(directory.join("trades-000001.csv")).unwrap();
+    assert_eq!(second.lines().count(), 2);
+    let single = read_to_string(directory.join("trades.csv")).unwrap();
+    assert_eq!(single.lines().count(), 2);
+
+    let () = remove_dir_all(&directory).unwrap();
+  }
+
+  /// Check that records are written to Parquet files with typed
+  /// columns and that existing files are left untouched.
+  #[cfg(feature = "parquet")]
+  #[test]
+  fn write_parquet() {
+    use parquet::basic::LogicalType;
+    use parquet::basic::TimeUnit;
+    use parquet::file::reader::FileReader as _;
+    use parquet::file::reader::SerializedFileReader;
+
+    /// Open the given Parquet file for reading.
+    fn open(path: &Path) -> SerializedFileReader<File> {
+      SerializedFileReader::new(File::open(path).unwrap()).unwrap()
+    }
+
+    /// Read all rows of the given Parquet file as strings.
+    fn read_rows(reader: &SerializedFileReader<File>) -> Vec<String> {
+      let rows = reader
+        .get_row_iter(None)
+        .unwrap()
+        .map(|row| row.unwrap().to_string())
+        .collect();
+      rows
+    }
+
+    let directory = directory("parquet");
+    let time = "2023-04-06T13:45:26.5Z";
+
+    for id in 1..=2 {
+      let mut sink = ParquetSink::new(&directory)
+        .unwrap()
+        .rotation(Rotation::Never);
+      let data = Data::<Bar, Quote, Trade>::Trade(trade("SPY", id, time));
+      let () = sink.write(&data).unwrap();
+    }
+
+    let reader = open(&directory.join("trades.parquet"));
+    let schema = reader.metadata().file_metadata().schema_descr();
+    let timestamp = schema.column(4);
+    assert_eq!(timestamp.name(), "timestamp");
+    assert_eq!(
+      timestamp.logical_type_ref(),
+      Some(&LogicalType::timestamp(true, TimeUnit::NANOS))
+    );
+    assert_eq!(
+      read_rows(&reader),
+      vec![
+        r#"{symbol: "SPY", trade_id: 1, price: 123.45, size: 10.0, timestamp: 1680788726500000000}"#
+      ]
+    );
+
+    let reader = open(&directory.join("trades.1.parquet"));
+    let rows = read_rows(&reader);
+    assert_eq!(rows.len(), 1);
+    assert!(rows[0].contains("trade_id: 2"), "{rows:?}");
+
+    let () = remove_dir_all(&directory).unwrap();
+  }
+}
@@ -0,0 +1,569 @@
+// Copyright (C) 2024 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use chrono::NaiveDate;
+
+use num_decimal::Num;
+
+use serde::Deserialize;
+use serde::Serialize;
+use serde_urlencoded::to_string as to_query;
+
+use crate::util::enum_slice_to_str;
+use crate::util::string_slice_to_str;
+use crate::util::vec_from_str;
+use crate::PageToken;
+use crate::Str;
+
+
+/// An enumeration of the various types of corporate actions.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub enum Type {
+  /// A forward stock split.
+  #[serde(rename = "forward_split")]
+  ForwardSplit,
+  /// A reverse stock split.
+  #[serde(rename = "reverse_split")]
+  ReverseSplit,
+  /// A split of a unit into its components.
+  #[serde(rename = "unit_split")]
+  UnitSplit,
+  /// A dividend paid out in cash.
+  #[serde(rename = "cash_dividend")]
+  CashDividend,
+  /// A dividend paid out in stock.
+  #[serde(rename = "stock_dividend")]
+  StockDividend,
+  /// A spin-off of part of a company into a new one.
+  #[serde(rename = "spin_off")]
+  SpinOff,
+  /// A merger in which shareholders of the acquired company are paid
+  /// in cash.
+  #[serde(rename = "cash_merger")]
+  CashMerger,
+  /// A merger in which shareholders of the acquired company receive
+  /// stock of the acquirer.
+  #[serde(rename = "stock_merger")]
+  StockMerger,
+  /// A redemption of a security.
+  #[serde(rename = "redemption")]
+  Redemption,
+  /// A change of a symbol's name.
+  #[serde(rename = "name_change")]
+  NameChange,
+}
+
+
+/// A GET request to be issued to the /v1beta1/corporate-actions
+/// endpoint.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct ListReq {
+  /// The symbols to retrieve corporate actions for.
+  ///
+  /// If empty, corporate actions for all symbols are retrieved.
+  #[serde(rename = "symbols", serialize_with = "string_slice_to_str")]
+  pub symbols: Vec<String>,
+  /// The types of corporate actions to retrieve.
+  ///
+  /// If empty, corporate actions of all types are retrieved.
+  #[serde(rename = "types", serialize_with = "enum_slice_to_str")]
+  pub types: Vec<Type>,
+  /// Filter corporate actions processed on or after this date.
+  #[serde(rename = "start", skip_serializing_if = "Option::is_none")]
+  pub start: Option<NaiveDate>,
+  /// Filter corporate actions processed on or before this date.
+  #[serde(rename = "end", skip_serializing_if = "Option::is_none")]
+  pub end: Option<NaiveDate>,
+  /// The maximum number of corporate actions to be returned.
+  ///
+  /// It can be between 1 and 1000. Defaults to 100 if the provided
+  /// value is `None`.
+  #[serde(rename = "limit", skip_serializing_if = "Option::is_none")]
+  pub limit: Option<usize>,
+  /// If provided we will pass a page token to continue where we left off.
+  #[serde(rename = "page_token", skip_serializing_if = "Option::is_none")]
+  pub page_token: Option<PageToken>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// A forward, reverse, or unit split.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct Split {
+  /// The symbol of the security being split.
+  #[serde(rename = "symbol")]
+  pub symbol: String,
+  /// The number of shares held after the split per `old_rate` shares
+  /// held before.
+  #[serde(rename = "new_rate")]
+  pub new_rate: Num,
+  /// The number of shares held before the split per `new_rate` shares
+  /// held afterwards.
+  #[serde(rename = "old_rate")]
+  pub old_rate: Num,
+  /// The date the split got processed at.
+  #[serde(rename = "process_date")]
+  pub process_date: NaiveDate,
+  /// The first date the security trades with the split applied.
+  #[serde(rename = "ex_date")]
+  pub ex_date: NaiveDate,
+  /// The date a holder has to be on record to be entitled to the
+  /// split.
+  #[serde(rename = "record_date")]
+  pub record_date: Option<NaiveDate>,
+  /// The date the new shares get distributed.
+  #[serde(rename = "payable_date")]
+  pub payable_date: Option<NaiveDate>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// A dividend paid out in cash.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct CashDividend {
+  /// The symbol of the security paying the dividend.
+  #[serde(rename = "symbol")]
+  pub symbol: String,
+  /// The amount of cash paid out per share.
+  #[serde(rename = "rate")]
+  pub rate: Num,
+  /// Whether the dividend is a special (non-recurring) one.
+  #[serde(rename = "special")]
+  pub special: bool,
+  /// Whether the dividend is paid by a foreign company.
+  #[serde(rename = "foreign")]
+  pub foreign: bool,
+  /// The date the dividend got processed at.
+  #[serde(rename = "process_date")]
+  pub process_date: NaiveDate,
+  /// The first date the security trades without the dividend.
+  #[serde(rename = "ex_date")]
+  pub ex_date: NaiveDate,
+  /// The date a holder has to be on record to be entitled to the
+  /// dividend.
+  #[serde(rename = "record_date")]
+  pub record_date: Option<NaiveDate>,
+  /// The date the dividend gets paid out.
+  #[serde(rename = "payable_date")]
+  pub payable_date: Option<NaiveDate>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// A dividend paid out in stock.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct StockDividend {
+  /// The symbol of the security paying the dividend.
+  #[serde(rename = "symbol")]
+  pub symbol: String,
+  /// The number of new shares received per share held.
+  #[serde(rename = "rate")]
+  pub rate: Num,
+  /// The date the dividend got processed at.
+  #[serde(rename = "process_date")]
+  pub process_date: NaiveDate,
+  /// The first date the security trades without the dividend.
+  #[serde(rename = "ex_date")]
+  pub ex_date: NaiveDate,
+  /// The date a holder has to be on record to be entitled to the
+  /// dividend.
+  #[serde(rename = "record_date")]
+  pub record_date: Option<NaiveDate>,
+  /// The date the new shares get distributed.
+  #[serde(rename = "payable_date")]
+  pub payable_date: Option<NaiveDate>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// A spin-off of part of a company into a new one.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct SpinOff {
+  /// The symbol of the security being spun off from.
+  #[serde(rename = "source_symbol")]
+  pub source_symbol: String,
+  /// The number of source shares corresponding to `new_rate` new
+  /// shares.
+  #[serde(rename = "source_rate")]
+  pub source_rate: Num,
+  /// The symbol of the newly created security.
+  #[serde(rename = "new_symbol")]
+  pub new_symbol: String,
+  /// The number of new shares received per `source_rate` source
+  /// shares.
+  #[serde(rename = "new_rate")]
+  pub new_rate: Num,
+  /// The date the spin-off got processed at.
+  #[serde(rename = "process_date")]
+  pub process_date: NaiveDate,
+  /// The first date the source security trades without the new one.
+  #[serde(rename = "ex_date")]
+  pub ex_date: NaiveDate,
+  /// The date a holder has to be on record to be entitled to the new
+  /// shares.
+  #[serde(rename = "record_date")]
+  pub record_date: Option<NaiveDate>,
+  /// The date the new shares get distributed.
+  #[serde(rename = "payable_date")]
+  pub payable_date: Option<NaiveDate>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// A merger in which shareholders of the acquired company are paid in
+/// cash.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct CashMerger {
+  /// The symbol of the acquiring company.
+  #[serde(rename = "acquirer_symbol")]
+  pub acquirer_symbol: Option<String>,
+  /// The symbol of the acquired company.
+  #[serde(rename = "acquiree_symbol")]
+  pub acquiree_symbol: String,
+  /// The amount of cash paid out per share of the acquired company.
+  #[serde(rename = "rate")]
+  pub rate: Num,
+  /// The date the merger got processed at.
+  #[serde(rename = "process_date")]
+  pub process_date: NaiveDate,
+  /// The date the merger took effect.
+  #[serde(rename = "effective_date")]
+  pub effective_date: NaiveDate,
+  /// The date the cash gets paid out.
+  #[serde(rename = "payable_date")]
+  pub payable_date: Option<NaiveDate>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// A merger in which shareholders of the acquired company receive
+/// stock of the acquirer.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct StockMerger {
+  /// The symbol of the acquiring company.
+  #[serde(rename = "acquirer_symbol")]
+  pub acquirer_symbol: String,
+  /// The number of acquirer shares received per `acquiree_rate`
+  /// shares of the acquired company.
+  #[serde(rename = "acquirer_rate")]
+  pub acquirer_rate: Num,
+  /// The symbol of the acquired company.
+  #[serde(rename = "acquiree_symbol")]
+  pub acquiree_symbol: String,
+  /// The number of acquiree shares corresponding to `acquirer_rate`
+  /// shares of the acquirer.
+  #[serde(rename = "acquiree_rate")]
+  pub acquiree_rate: Num,
+  /// The date the merger got processed at.
+  #[serde(rename = "process_date")]
+  pub process_date: NaiveDate,
+  /// The date the merger took effect.
+  #[serde(rename = "effective_date")]
+  pub effective_date: NaiveDate,
+  /// The date the new shares get distributed.
+  #[serde(rename = "payable_date")]
+  pub payable_date: Option<NaiveDate>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// A redemption of a security.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct Redemption {
+  /// The symbol of the security being redeemed.
+  #[serde(rename = "symbol")]
+  pub symbol: String,
+  /// The amount of cash paid out per share.
+  #[serde(rename = "rate")]
+  pub rate: Num,
+  /// The date the redemption got processed at.
+  #[serde(rename = "process_date")]
+  pub process_date: NaiveDate,
+  /// The date the cash gets paid out.
+  #[serde(rename = "payable_date")]
+  pub payable_date: Option<NaiveDate>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// A change of a symbol's name.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct NameChange {
+  /// The symbol used before the change.
+  #[serde(rename = "old_symbol")]
+  pub old_symbol: String,
+  /// The symbol used after the change.
+  #[serde(rename = "new_symbol")]
+  pub new_symbol: String,
+  /// The date the name change got processed at.
+  #[serde(rename = "process_date")]
+  pub process_date: NaiveDate,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// The corporate actions of the various types, as reported by the
+/// /v1beta1/corporate-actions endpoint.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+pub struct CorporateActions {
+  /// Forward splits.
+  #[serde(rename = "forward_splits", default, deserialize_with = "vec_from_str")]
+  pub forward_splits: Vec<Split>,
+  /// Reverse splits.
+  #[serde(rename = "reverse_splits", default, deserialize_with = "vec_from_str")]
+  pub reverse_splits: Vec<Split>,
+  /// Unit splits.
+  #[serde(rename = "unit_splits", default, deserialize_with = "vec_from_str")]
+  pub unit_splits: Vec<Split>,
+  /// Dividends paid out in cash.
+  #[serde(rename = "cash_dividends", default, deserialize_with = "vec_from_str")]
+  pub cash_dividends: Vec<CashDividend>,
+  /// Dividends paid out in stock.
+  #[serde(rename = "stock_dividends", default, deserialize_with = "vec_from_str")]
+  pub stock_dividends: Vec<StockDividend>,
+  /// Spin-offs.
+  #[serde(rename = "spin_offs", default, deserialize_with = "vec_from_str")]
+  pub spin_offs: Vec<SpinOff>,
+  /// Mergers settled in cash.
+  #[serde(rename = "cash_mergers", default, deserialize_with = "vec_from_str")]
+  pub cash_mergers: Vec<CashMerger>,
+  /// Mergers settled in stock.
+  #[serde(rename = "stock_mergers", default, deserialize_with = "vec_from_str")]
+  pub stock_mergers: Vec<StockMerger>,
+  /// Redemptions.
+  #[serde(rename = "redemptions", default, deserialize_with = "vec_from_str")]
+  pub redemptions: Vec<Redemption>,
+  /// Name changes.
+  #[serde(rename = "name_changes", default, deserialize_with = "vec_from_str")]
+  pub name_changes: Vec<NameChange>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// A collection of corporate actions as returned by the API. This is
+/// one page of corporate actions.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct CorporateActionsPage {
+  /// The returned corporate actions.
+  #[serde(rename = "corporate_actions")]
+  pub corporate_actions: CorporateActions,
+  /// The token to provide to a request to get the next page of
+  /// corporate actions for this request.
+  #[serde(rename = "next_page_token")]
+  pub next_page_token: Option<PageToken>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+Endpoint! {
+  /// The representation of a GET request to the
+  /// /v1beta1/corporate-actions endpoint.
+  pub List(ListReq),
+  Api => Data,
+  Ok => CorporateActionsPage, [
+    /// The corporate actions were retrieved successfully.
+    /* 200 */ OK,
+  ],
+  Err => ListError, [
+    /// A query parameter was invalid.
+    /* 400 */ BAD_REQUEST => InvalidInput,
+  ]
+
+  #[inline]
+  fn path(_input: &Self::Input) -> Str {
+    "/v1beta1/corporate-actions".into()
+  }
+
+  fn query(input: &Self::Input) -> Result<Option<Str>, Self::ConversionError> {
+    Ok(Some(to_query(input)?.into()))
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use serde_json::from_str as from_json;
+
+  use test_log::test;
+
+  use crate::api_info::ApiInfo;
+  use crate::Client;
+  use crate::RequestError;
+
+
+  /// Check that we serialize a request as expected.
+  #[test]
+  fn serialize_request() {
+    let request = ListReq {
+      symbols: vec!["AAPL".to_string(), "TSLA".to_string()],
+      types: vec![Type::ForwardSplit, Type::CashDividend],
+      start: Some(NaiveDate::from_ymd_opt(2022, 1, 1).unwrap()),
+      limit: Some(5),
+      ..Default::default()
+    };
+
+    let expected =
+      "symbols=AAPL%2CTSLA&types=forward_split%2Ccash_dividend&start=2022-01-01&limit=5";
+    assert_eq!(to_query(&request).unwrap(), expected);
+  }
+
+  /// Verify that we can parse a reference corporate actions response.
+  #[test]
+  fn parse_reference_corporate_actions() {
+    let response = r#"{
+  "corporate_actions": {
+    "forward_splits": [
+      {
+        "symbol": "TSLA",
+        "new_rate": 3,
+        "old_rate": 1,
+        "process_date": "2022-08-25",
+        "ex_date": "2022-08-25",
+        "record_date": "2022-08-17",
+        "payable_date": "2022-08-24"
+      }
+    ],
+    "cash_dividends": [
+      {
+        "symbol": "AAPL",
+        "rate": 0.23,
+        "special": false,
+        "foreign": false,
+        "process_date": "2022-08-11",
+        "ex_date": "2022-08-05",
+        "record_date": "2022-08-08",
+        "payable_date": "2022-08-11"
+      }
+    ],
+    "stock_mergers": [
+      {
+        "acquirer_symbol": "AMD",
+        "acquirer_rate": 1.7234,
+        "acquiree_symbol": "XLNX",
+        "acquiree_rate": 1,
+        "process_date": "2022-02-14",
+        "effective_date": "2022-02-14",
+        "payable_date": "2022-02-14"
+      }
+    ],
+    "name_changes": [
+      {
+        "old_symbol": "FB",
+        "new_symbol": "META",
+        "process_date": "2022-06-09"
+      }
+    ]
+  },
+  "next_page_token": "MTAwMA=="
+}"#;
+
+    let page = from_json::<CorporateActionsPage>(response).unwrap();
+    let actions = &page.corporate_actions;
+    assert_eq!(page.next_page_token.as_deref(), Some("MTAwMA=="));
+
+    assert_eq!(actions.forward_splits.len(), 1);
+    assert_eq!(actions.forward_splits[0].symbol, "TSLA");
+    assert_eq!(actions.forward_splits[0].new_rate, Num::from(3));
+    assert_eq!(actions.forward_splits[0].old_rate, Num::from(1));
+    assert!(actions.reverse_splits.is_empty());
+
+    assert_eq!(actions.cash_dividends[0].rate, Num::new(23, 100));
+    assert_eq!(
+      actions.cash_dividends[0].ex_date,
+      NaiveDate::from_ymd_opt(2022, 8, 5).unwrap()
+    );
+    assert_eq!(actions.stock_mergers[0].acquirer_symbol, "AMD");
+    assert_eq!(
+      actions.stock_mergers[0].acquirer_rate,
+      Num::new(17234, 10000)
+    );
+    assert_eq!(actions.name_changes[0].new_symbol, "META");
+  }
+
+  /// Check that we can retrieve corporate actions for multiple
+  /// symbols across pages.
+  #[test(tokio::test)]
+  async fn list_corporate_actions() {
+    let api_info = ApiInfo::from_env().unwrap();
+    let client = Client::new(api_info);
+    let mut request = ListReq {
+      symbols: vec!["AAPL".to_string(), "TSLA".to_string()],
+      types: vec![Type::ForwardSplit],
+      start: Some(NaiveDate::from_ymd_opt(2020, 1, 1).unwrap()),
+      end: Some(NaiveDate::from_ymd_opt(2022, 12, 31).unwrap()),
+      limit: Some(1),
+      ..Default::default()
+    };
+
+    let mut splits = Vec::new();
+    loop {
+      let page = client.issue::<List>(&request).await.unwrap();
+      splits.extend(page.corporate_actions.forward_splits);
+
+      match page.next_page_token {
+        Some(token) => request.page_token = Some(token),
+        None => break,
+      }
+    }
+
+    // Both AAPL and TSLA split in August 2020 and TSLA once more in
+    // August 2022.
+    assert_eq!(splits.len(), 3);
+    assert!(splits.iter().any(|split| split.symbol == "AAPL"));
+    assert!(splits.iter().all(|split| split.new_rate > split.old_rate));
+  }
+
+  /// Check that we report an appropriate error when an invalid limit
+  /// is provided.
+  #[test(tokio::test)]
+  async fn list_with_invalid_limit() {
+    let api_info = ApiInfo::from_env().unwrap();
+    let client = Client::new(api_info);
+    let request = ListReq {
+      limit: Some(100_000),
+      ..Default::default()
+    };
+
+    let err = client.issue::<List>(&request).await.unwrap_err();
+    match err {
+      RequestError::Endpoint(ListError::InvalidInput(_)) => (),
+      _ => panic!("Received unexpected error: {err:?}"),
+    };
+  }
+}
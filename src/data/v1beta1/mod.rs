@@ -0,0 +1,5 @@
+// Copyright (C) 2024 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/// Functionality for retrieving corporate actions.
+pub mod corporate_actions;
@@ -0,0 +1,418 @@
+// Copyright (C) 2024 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::collections::BTreeMap;
+
+use chrono::DateTime;
+use chrono::NaiveDate;
+use chrono::Utc;
+
+use num_decimal::Num;
+
+use serde::Deserialize;
+use serde::Serialize;
+use serde_urlencoded::to_string as to_query;
+
+use crate::util::string_slice_to_str;
+use crate::util::vec_from_str;
+use crate::PageToken;
+use crate::Str;
+
+
+/// A GET request to be issued to the /v2/stocks/{symbol}/auctions
+/// endpoint.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct ListReq {
+  /// The symbol for which to retrieve auction data.
+  #[serde(skip)]
+  pub symbol: String,
+  /// The maximum number of auction days to be returned.
+  ///
+  /// It can be between 1 and 10000. Defaults to 1000 if the provided
+  /// value is `None`.
+  #[serde(rename = "limit", skip_serializing_if = "Option::is_none")]
+  pub limit: Option<usize>,
+  /// Filter auctions equal to or after this time.
+  #[serde(rename = "start")]
+  pub start: DateTime<Utc>,
+  /// Filter auctions equal to or before this time.
+  #[serde(rename = "end")]
+  pub end: DateTime<Utc>,
+  /// If provided we will pass a page token to continue where we left off.
+  #[serde(rename = "page_token", skip_serializing_if = "Option::is_none")]
+  pub page_token: Option<PageToken>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// A helper for initializing [`ListReq`] objects.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ListReqInit {
+  /// See `ListReq::limit`.
+  pub limit: Option<usize>,
+  /// See `ListReq::page_token`.
+  pub page_token: Option<PageToken>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+Setters! {
+  ListReqInit {
+    limit: Option<usize>,
+    page_token: Option<PageToken>,
+  }
+}
+
+impl ListReqInit {
+  /// Create a [`ListReq`] from a `ListReqInit`.
+  #[inline]
+  pub fn init<S>(&self, symbol: S, start: DateTime<Utc>, end: DateTime<Utc>) -> ListReq
+  where
+    S: Into<String>,
+  {
+    ListReq {
+      symbol: symbol.into(),
+      start,
+      end,
+      limit: self.limit,
+      page_token: self.page_token.clone(),
+      _non_exhaustive: (),
+    }
+  }
+}
+
+
+/// A GET request to be issued to the /v2/stocks/auctions endpoint,
+/// retrieving auction data for multiple symbols at once.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct ListMultiReq {
+  /// The symbols for which to retrieve auction data.
+  #[serde(rename = "symbols", serialize_with = "string_slice_to_str")]
+  pub symbols: Vec<String>,
+  /// The maximum number of auction days to be returned, across all
+  /// symbols.
+  ///
+  /// It can be between 1 and 10000. Defaults to 1000 if the provided
+  /// value is `None`.
+  #[serde(rename = "limit", skip_serializing_if = "Option::is_none")]
+  pub limit: Option<usize>,
+  /// Filter auctions equal to or after this time.
+  #[serde(rename = "start")]
+  pub start: DateTime<Utc>,
+  /// Filter auctions equal to or before this time.
+  #[serde(rename = "end")]
+  pub end: DateTime<Utc>,
+  /// If provided we will pass a page token to continue where we left off.
+  #[serde(rename = "page_token", skip_serializing_if = "Option::is_none")]
+  pub page_token: Option<PageToken>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// A helper for initializing [`ListMultiReq`] objects.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ListMultiReqInit {
+  /// See `ListMultiReq::limit`.
+  pub limit: Option<usize>,
+  /// See `ListMultiReq::page_token`.
+  pub page_token: Option<PageToken>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+Setters! {
+  ListMultiReqInit {
+    limit: Option<usize>,
+    page_token: Option<PageToken>,
+  }
+}
+
+impl ListMultiReqInit {
+  /// Create a [`ListMultiReq`] from a `ListMultiReqInit`.
+  #[inline]
+  pub fn init<I, S>(&self, symbols: I, start: DateTime<Utc>, end: DateTime<Utc>) -> ListMultiReq
+  where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+  {
+    ListMultiReq {
+      symbols: symbols.into_iter().map(S::into).collect(),
+      start,
+      end,
+      limit: self.limit,
+      page_token: self.page_token.clone(),
+      _non_exhaustive: (),
+    }
+  }
+}
+
+
+/// A single print that is part of an opening or closing auction.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct Print {
+  /// The time of the print.
+  #[serde(rename = "t")]
+  pub timestamp: DateTime<Utc>,
+  /// The exchange the auction took place on.
+  #[serde(rename = "x")]
+  pub exchange: String,
+  /// The price of the print.
+  #[serde(rename = "p")]
+  pub price: Num,
+  /// The size of the print, if reported.
+  #[serde(rename = "s")]
+  pub size: Option<u64>,
+  /// The condition flag of the print, indicating, for example, whether
+  /// it is the official opening or closing price.
+  #[serde(rename = "c")]
+  pub condition: String,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// The opening and closing auctions of a single trading day.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct Auction {
+  /// The date the auctions took place on.
+  #[serde(rename = "d")]
+  pub date: NaiveDate,
+  /// The prints of the opening auction.
+  #[serde(rename = "o", default, deserialize_with = "vec_from_str")]
+  pub opening: Vec<Print>,
+  /// The prints of the closing auction.
+  #[serde(rename = "c", default, deserialize_with = "vec_from_str")]
+  pub closing: Vec<Print>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// A collection of auctions as returned by the API. This is one page of
+/// auctions.
+#[derive(Debug, Deserialize, Eq, PartialEq)]
+pub struct Auctions {
+  /// The list of returned auctions.
+  #[serde(rename = "auctions", deserialize_with = "vec_from_str")]
+  pub auctions: Vec<Auction>,
+  /// The symbol the auctions correspond to.
+  #[serde(rename = "symbol")]
+  pub symbol: String,
+  /// The token to provide to a request to get the next page of auctions
+  /// for this request.
+  #[serde(rename = "next_page_token")]
+  pub next_page_token: Option<PageToken>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// A collection of auctions for multiple symbols as returned by the
+/// API. This is one page of auctions.
+#[derive(Debug, Deserialize, Eq, PartialEq)]
+pub struct MultiAuctions {
+  /// A mapping from symbols to the returned auctions.
+  #[serde(rename = "auctions", default)]
+  pub auctions: BTreeMap<String, Vec<Auction>>,
+  /// The token to provide to a request to get the next page of auctions
+  /// for this request.
+  #[serde(rename = "next_page_token")]
+  pub next_page_token: Option<PageToken>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+Endpoint! {
+  /// The representation of a GET request to the
+  /// /v2/stocks/{symbol}/auctions endpoint.
+  pub List(ListReq),
+  Api => Data,
+  Ok => Auctions, [
+    /// The auction data was retrieved successfully.
+    /* 200 */ OK,
+  ],
+  Err => ListError, [
+    /// A query parameter was invalid.
+    /* 400 */ BAD_REQUEST => InvalidInput,
+  ]
+
+  fn path(input: &Self::Input) -> Str {
+    format!("/v2/stocks/{}/auctions", input.symbol).into()
+  }
+
+  fn query(input: &Self::Input) -> Result<Option<Str>, Self::ConversionError> {
+    Ok(Some(to_query(input)?.into()))
+  }
+}
+
+
+Endpoint! {
+  /// The representation of a GET request to the /v2/stocks/auctions
+  /// endpoint.
+  pub ListMulti(ListMultiReq),
+  Api => Data,
+  Ok => MultiAuctions, [
+    /// The auction data was retrieved successfully.
+    /* 200 */ OK,
+  ],
+  Err => ListMultiError, [
+    /// A query parameter was invalid.
+    /* 400 */ BAD_REQUEST => InvalidInput,
+  ]
+
+  #[inline]
+  fn path(_input: &Self::Input) -> Str {
+    "/v2/stocks/auctions".into()
+  }
+
+  fn query(input: &Self::Input) -> Result<Option<Str>, Self::ConversionError> {
+    Ok(Some(to_query(input)?.into()))
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use std::str::FromStr as _;
+
+  use http_endpoint::Endpoint;
+
+  use serde_json::from_str as from_json;
+
+  use test_log::test;
+
+  use crate::api_info::ApiInfo;
+  use crate::Client;
+  use crate::RequestError;
+
+
+  /// Verify that we can properly parse a reference auctions response.
+  #[test]
+  fn parse_reference_auctions() {
+    let response = r#"{
+  "auctions": [
+    {
+      "d": "2022-10-13",
+      "o": [
+        {"c": "O", "p": 134.84, "t": "2022-10-13T13:30:00.228153Z", "x": "P"},
+        {"c": "Q", "p": 134.99, "s": 513976, "t": "2022-10-13T13:30:01.119536Z", "x": "Q"}
+      ],
+      "c": [
+        {"c": "M", "p": 142.99, "s": 9318, "t": "2022-10-13T20:00:00.002312Z", "x": "P"},
+        {"c": "6", "p": 142.99, "s": 7158760, "t": "2022-10-13T20:00:01.261024Z", "x": "Q"}
+      ]
+    }
+  ],
+  "symbol": "AAPL",
+  "next_page_token": null
+}"#;
+
+    let res = from_json::<<List as Endpoint>::Output>(response).unwrap();
+    assert_eq!(res.symbol, "AAPL");
+    assert_eq!(res.next_page_token, None);
+    assert_eq!(res.auctions.len(), 1);
+
+    let auction = &res.auctions[0];
+    assert_eq!(auction.date, NaiveDate::from_ymd_opt(2022, 10, 13).unwrap());
+    assert_eq!(auction.opening.len(), 2);
+    assert_eq!(auction.opening[0].condition, "O");
+    assert_eq!(auction.opening[0].size, None);
+    assert_eq!(auction.opening[1].price, Num::new(13499, 100));
+    assert_eq!(auction.opening[1].size, Some(513976));
+    assert_eq!(auction.closing.len(), 2);
+    assert_eq!(auction.closing[1].exchange, "Q");
+    assert_eq!(auction.closing[1].size, Some(7158760));
+  }
+
+  /// Verify that we can parse a reference multi-symbol auctions
+  /// response.
+  #[test]
+  fn parse_reference_multi_auctions() {
+    let response = r#"{
+  "auctions": {
+    "AAPL": [
+      {
+        "d": "2022-10-13",
+        "o": [{"c": "Q", "p": 134.99, "s": 513976, "t": "2022-10-13T13:30:01.119536Z", "x": "Q"}],
+        "c": null
+      }
+    ],
+    "MSFT": []
+  },
+  "next_page_token": "QUFQTHwyMDIyLTEwLTEz"
+}"#;
+
+    let res = from_json::<<ListMulti as Endpoint>::Output>(response).unwrap();
+    assert_eq!(res.auctions.len(), 2);
+    assert_eq!(res.auctions["AAPL"][0].opening.len(), 1);
+    assert_eq!(res.auctions["AAPL"][0].closing, Vec::new());
+    assert_eq!(res.auctions["MSFT"], Vec::new());
+    assert!(res.next_page_token.is_some());
+  }
+
+  /// Check that we can request historic auction data for a stock.
+  #[test(tokio::test)]
+  async fn request_auctions() {
+    let api_info = ApiInfo::from_env().unwrap();
+    let client = Client::new(api_info);
+    let start = DateTime::from_str("2022-10-13T00:00:00Z").unwrap();
+    let end = DateTime::from_str("2022-10-15T00:00:00Z").unwrap();
+    let request = ListReqInit::default().init("AAPL", start, end);
+
+    let result = client.issue::<List>(&request).await;
+    // Auction data is only available through the SIP feed, which not
+    // every user has access to.
+    match result {
+      Ok(res) => {
+        assert_eq!(res.symbol, "AAPL");
+        assert_eq!(res.auctions.len(), 2);
+        assert_eq!(
+          res.auctions[0].date,
+          NaiveDate::from_ymd_opt(2022, 10, 13).unwrap()
+        );
+      },
+      Err(RequestError::Endpoint(ListError::NotPermitted(_))) => (),
+      Err(err) => panic!("Received unexpected error: {err:?}"),
+    }
+  }
+
+  /// Check that we can request historic auction data for multiple
+  /// stocks at once.
+  #[test(tokio::test)]
+  async fn request_multi_auctions() {
+    let api_info = ApiInfo::from_env().unwrap();
+    let client = Client::new(api_info);
+    let start = DateTime::from_str("2022-10-13T00:00:00Z").unwrap();
+    let end = DateTime::from_str("2022-10-14T00:00:00Z").unwrap();
+    let request = ListMultiReqInit::default().init(["AAPL", "MSFT"], start, end);
+
+    let result = client.issue::<ListMulti>(&request).await;
+    match result {
+      Ok(res) => {
+        assert!(res.auctions.contains_key("AAPL"));
+        assert!(res.auctions.contains_key("MSFT"));
+      },
+      Err(RequestError::Endpoint(ListMultiError::NotPermitted(_))) => (),
+      Err(err) => panic!("Received unexpected error: {err:?}"),
+    }
+  }
+}
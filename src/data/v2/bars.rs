@@ -1,34 +1,98 @@
 // Copyright (C) 2021-2024 The apca Developers
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+
 use chrono::DateTime;
+use chrono::Duration as ChronoDuration;
+use chrono::NaiveDate;
 use chrono::Utc;
 
+use futures::Stream;
+
 use num_decimal::Num;
 
+use serde::ser::Error as _;
 use serde::Deserialize;
 use serde::Serialize;
+use serde::Serializer;
 use serde_urlencoded::to_string as to_query;
 
+use crate::data::v2::range;
+use crate::data::v2::range::RangeEndpoint;
 use crate::data::v2::Feed;
-use crate::data::DATA_BASE_URL;
+use crate::data::v2::Sort;
 use crate::util::vec_from_str;
+use crate::Client;
+use crate::PageToken;
+use crate::RequestError;
 use crate::Str;
 
 
 /// An enumeration of the various supported time frames.
-#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+///
+/// Not all multiples are supported by the API: minutes may range from
+/// 1 to 59, hours from 1 to 23, days and weeks are limited to 1, and
+/// months may be 1, 2, 3, 4, 6, or 12. Requests using any other time
+/// frame fail to be issued.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 #[non_exhaustive]
 pub enum TimeFrame {
+  /// A time frame of the given number of minutes.
+  Minutes(u8),
+  /// A time frame of the given number of hours.
+  Hours(u8),
+  /// A time frame of the given number of days.
+  Days(u8),
+  /// A time frame of the given number of weeks.
+  Weeks(u8),
+  /// A time frame of the given number of months.
+  Months(u8),
+}
+
+impl TimeFrame {
   /// A time frame of one minute.
-  #[serde(rename = "1Min")]
-  OneMinute,
+  pub const ONE_MINUTE: Self = Self::Minutes(1);
   /// A time frame of one hour.
-  #[serde(rename = "1Hour")]
-  OneHour,
+  pub const ONE_HOUR: Self = Self::Hours(1);
   /// A time frame of one day.
-  #[serde(rename = "1Day")]
-  OneDay,
+  pub const ONE_DAY: Self = Self::Days(1);
+
+  /// Check whether the time frame is supported by the API.
+  pub fn is_valid(&self) -> bool {
+    match *self {
+      Self::Minutes(count) => (1..=59).contains(&count),
+      Self::Hours(count) => (1..=23).contains(&count),
+      Self::Days(count) | Self::Weeks(count) => count == 1,
+      Self::Months(count) => matches!(count, 1 | 2 | 3 | 4 | 6 | 12),
+    }
+  }
+}
+
+impl Display for TimeFrame {
+  fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+    match self {
+      Self::Minutes(count) => write!(fmt, "{count}Min"),
+      Self::Hours(count) => write!(fmt, "{count}Hour"),
+      Self::Days(count) => write!(fmt, "{count}Day"),
+      Self::Weeks(count) => write!(fmt, "{count}Week"),
+      Self::Months(count) => write!(fmt, "{count}Month"),
+    }
+  }
+}
+
+impl Serialize for TimeFrame {
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: Serializer,
+  {
+    if !self.is_valid() {
+      return Err(S::Error::custom(format!("unsupported time frame: {self}")))
+    }
+    serializer.collect_str(self)
+  }
 }
 
 
@@ -81,9 +145,25 @@ pub struct ListReq {
   /// [`SIP`][Feed::SIP] for users with an unlimited subscription.
   #[serde(rename = "feed")]
   pub feed: Option<Feed>,
+  /// The date on which symbols are mapped, used to follow symbol
+  /// changes, e.g., due to a company rename.
+  ///
+  /// Defaults to the current day if the provided value is `None`.
+  #[serde(rename = "asof")]
+  pub asof: Option<NaiveDate>,
+  /// The currency in which prices are reported, e.g., "EUR".
+  ///
+  /// Defaults to USD if the provided value is `None`.
+  #[serde(rename = "currency")]
+  pub currency: Option<String>,
+  /// The order in which to return bars.
+  ///
+  /// Defaults to [`Asc`][Sort::Asc] if the provided value is `None`.
+  #[serde(rename = "sort")]
+  pub sort: Option<Sort>,
   /// If provided we will pass a page token to continue where we left off.
   #[serde(rename = "page_token", skip_serializing_if = "Option::is_none")]
-  pub page_token: Option<String>,
+  pub page_token: Option<PageToken>,
   /// The type is non-exhaustive and open to extension.
   #[doc(hidden)]
   #[serde(skip)]
@@ -100,18 +180,36 @@ pub struct ListReqInit {
   pub adjustment: Option<Adjustment>,
   /// See `ListReq::feed`.
   pub feed: Option<Feed>,
+  /// See `ListReq::asof`.
+  pub asof: Option<NaiveDate>,
+  /// See `ListReq::currency`.
+  pub currency: Option<String>,
+  /// See `ListReq::sort`.
+  pub sort: Option<Sort>,
   /// See `ListReq::page_token`.
-  pub page_token: Option<String>,
+  pub page_token: Option<PageToken>,
   /// The type is non-exhaustive and open to extension.
   #[doc(hidden)]
   pub _non_exhaustive: (),
 }
 
+Setters! {
+  ListReqInit {
+    limit: Option<usize>,
+    adjustment: Option<Adjustment>,
+    feed: Option<Feed>,
+    asof: Option<NaiveDate>,
+    currency: Option<String>,
+    sort: Option<Sort>,
+    page_token: Option<PageToken>,
+  }
+}
+
 impl ListReqInit {
   /// Create a [`ListReq`] from a `ListReqInit`.
   #[inline]
   pub fn init<S>(
-    self,
+    &self,
     symbol: S,
     start: DateTime<Utc>,
     end: DateTime<Utc>,
@@ -128,7 +226,10 @@ impl ListReqInit {
       limit: self.limit,
       adjustment: self.adjustment,
       feed: self.feed,
-      page_token: self.page_token,
+      asof: self.asof,
+      currency: self.currency.clone(),
+      sort: self.sort,
+      page_token: self.page_token.clone(),
       _non_exhaustive: (),
     }
   }
@@ -136,29 +237,37 @@ impl ListReqInit {
 
 
 /// A market data bar as returned by the /v2/stocks/{symbol}/bars endpoint.
+///
+/// Prices are represented as `P`, which defaults to the exact [`Num`]
+/// type. Parsing into [`Num`] is comparably slow, though, and for large
+/// historical data sets `f64` may be used instead, by means of the
+/// `ListF64` endpoint (requiring the `f64-prices` feature).
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
-pub struct Bar {
+pub struct Bar<P = Num> {
   /// The beginning time of this bar.
   #[serde(rename = "t")]
   pub time: DateTime<Utc>,
   /// The open price.
   #[serde(rename = "o")]
-  pub open: Num,
+  pub open: P,
   /// The close price.
   #[serde(rename = "c")]
-  pub close: Num,
+  pub close: P,
   /// The highest price.
   #[serde(rename = "h")]
-  pub high: Num,
+  pub high: P,
   /// The lowest price.
   #[serde(rename = "l")]
-  pub low: Num,
+  pub low: P,
   /// The trading volume.
   #[serde(rename = "v")]
   pub volume: usize,
   /// The volume weighted average price.
   #[serde(rename = "vw")]
-  pub weighted_average: Num,
+  pub weighted_average: P,
+  /// The number of trades that occurred during the bar's time frame.
+  #[serde(rename = "n", default)]
+  pub trade_count: Option<u64>,
   /// The type is non-exhaustive and open to extension.
   #[doc(hidden)]
   #[serde(skip)]
@@ -169,17 +278,18 @@ pub struct Bar {
 /// A collection of bars as returned by the API. This is one page of
 /// bars.
 #[derive(Debug, Deserialize, Eq, PartialEq)]
-pub struct Bars {
+#[serde(bound(deserialize = "P: Deserialize<'de>"))]
+pub struct Bars<P = Num> {
   /// The list of returned bars.
   #[serde(rename = "bars", deserialize_with = "vec_from_str")]
-  pub bars: Vec<Bar>,
+  pub bars: Vec<Bar<P>>,
   /// The symbol the bars correspond to.
   #[serde(rename = "symbol")]
   pub symbol: String,
   /// The token to provide to a request to get the next page of bars for
   /// this request.
   #[serde(rename = "next_page_token")]
-  pub next_page_token: Option<String>,
+  pub next_page_token: Option<PageToken>,
   /// The type is non-exhaustive and open to extension.
   #[doc(hidden)]
   #[serde(skip)]
@@ -190,6 +300,7 @@ pub struct Bars {
 Endpoint! {
   /// The representation of a GET request to the /v2/stocks/{symbol}/bars endpoint.
   pub List(ListReq),
+  Api => Data,
   Ok => Bars, [
     /// The market data was retrieved successfully.
     /* 200 */ OK,
@@ -199,12 +310,36 @@ Endpoint! {
     /* 400 */ BAD_REQUEST => InvalidInput,
   ]
 
-  fn base_url() -> Option<Str> {
-    Some(DATA_BASE_URL.into())
+  fn path(input: &Self::Input) -> Str {
+    format!("/v2/stocks/{}/bars", input.symbol).into()
   }
 
+  fn query(input: &Self::Input) -> Result<Option<Str>, Self::ConversionError> {
+    Ok(Some(to_query(input)?.into()))
+  }
+}
+
+
+#[cfg(feature = "f64-prices")]
+Endpoint! {
+  /// The representation of a GET request to the
+  /// /v2/stocks/{symbol}/bars endpoint, reporting prices as `f64`.
+  ///
+  /// Retrieving bars by means of this endpoint is considerably faster
+  /// than using [`List`], at the cost of precision.
+  pub ListF64(ListReq),
+  Api => Data,
+  Ok => Bars<f64>, [
+    /// The market data was retrieved successfully.
+    /* 200 */ OK,
+  ],
+  Err => ListF64Error, [
+    /// A query parameter was invalid.
+    /* 400 */ BAD_REQUEST => InvalidInput,
+  ]
+
   fn path(input: &Self::Input) -> Str {
-    format!("/v2/stocks/{}/bars", input.symbol).into()
+    List::path(input)
   }
 
   fn query(input: &Self::Input) -> Result<Option<Str>, Self::ConversionError> {
@@ -213,6 +348,49 @@ Endpoint! {
 }
 
 
+impl RangeEndpoint for List {
+  type Item = Bar;
+
+  #[inline]
+  fn range(input: &mut Self::Input) -> (&mut DateTime<Utc>, &mut DateTime<Utc>) {
+    (&mut input.start, &mut input.end)
+  }
+
+  #[inline]
+  fn page_token(input: &mut Self::Input) -> &mut Option<PageToken> {
+    &mut input.page_token
+  }
+
+  #[inline]
+  fn into_page(output: Self::Output) -> (Vec<Self::Item>, Option<PageToken>) {
+    (output.bars, output.next_page_token)
+  }
+
+  #[inline]
+  fn is_rate_limited(error: &Self::Error) -> bool {
+    matches!(error, ListError::RateLimitExceeded(..))
+  }
+}
+
+
+/// Retrieve all bars in the time range of the provided request as a
+/// stream.
+///
+/// The time range is split into chunks of the given length, each of
+/// which is retrieved by following page tokens until exhausted. That
+/// way, ranges spanning far more bars than a single response can hold
+/// can be retrieved conveniently. Requests exceeding the rate limit
+/// are retried a few times, backing off in between. A non-positive
+/// chunk length retrieves the full range in one go.
+pub fn fetch_range(
+  client: &Client,
+  request: ListReq,
+  chunk: ChronoDuration,
+) -> impl Stream<Item = Result<Bar, RequestError<ListError>>> + '_ {
+  range::fetch_range::<List>(client, request, chunk)
+}
+
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -220,6 +398,9 @@ mod tests {
   use std::ops::RangeInclusive;
   use std::str::FromStr as _;
 
+  use futures::TryStreamExt as _;
+
+  use http::StatusCode;
   use http_endpoint::Endpoint;
 
   use serde_json::from_str as from_json;
@@ -227,8 +408,8 @@ mod tests {
   use test_log::test;
 
   use crate::api_info::ApiInfo;
-  use crate::Client;
-  use crate::RequestError;
+  use crate::transport::test::mock_client;
+  use crate::MockTransport;
 
 
   #[track_caller]
@@ -240,6 +421,98 @@ mod tests {
   }
 
 
+  /// Check that time frames are serialized as expected and that
+  /// unsupported ones are rejected.
+  #[test]
+  fn serialize_time_frames() {
+    let time_frames = [
+      (TimeFrame::ONE_MINUTE, "1Min"),
+      (TimeFrame::Minutes(15), "15Min"),
+      (TimeFrame::Hours(2), "2Hour"),
+      (TimeFrame::ONE_DAY, "1Day"),
+      (TimeFrame::Weeks(1), "1Week"),
+      (TimeFrame::Months(12), "12Month"),
+    ];
+    for (time_frame, expected) in time_frames {
+      assert!(time_frame.is_valid());
+      assert_eq!(
+        serde_json::to_string(&time_frame).unwrap(),
+        format!("\"{expected}\"")
+      );
+    }
+
+    let invalid = [
+      TimeFrame::Minutes(0),
+      TimeFrame::Minutes(60),
+      TimeFrame::Hours(24),
+      TimeFrame::Days(2),
+      TimeFrame::Months(5),
+    ];
+    for time_frame in invalid {
+      assert!(!time_frame.is_valid());
+      assert!(serde_json::to_string(&time_frame).is_err());
+    }
+
+    let start = DateTime::from_str("2021-11-05T00:00:00Z").unwrap();
+    let end = DateTime::from_str("2021-11-05T00:00:00Z").unwrap();
+    let request = ListReqInit::default().init("AAPL", start, end, TimeFrame::Minutes(90));
+    assert!(List::query(&request).is_err());
+  }
+
+  /// Check that the optional symbol mapping, currency, and sort order
+  /// parameters are reflected in the request's query.
+  #[test]
+  fn serialize_optional_parameters() {
+    let start = DateTime::from_str("2021-11-05T00:00:00Z").unwrap();
+    let end = DateTime::from_str("2021-11-05T00:00:00Z").unwrap();
+    let request = ListReqInit {
+      asof: Some(NaiveDate::from_ymd_opt(2022, 6, 9).unwrap()),
+      currency: Some("EUR".to_string()),
+      sort: Some(Sort::Desc),
+      ..Default::default()
+    }
+    .init("META", start, end, TimeFrame::ONE_DAY);
+
+    let query = List::query(&request).unwrap().unwrap();
+    assert!(query.contains("asof=2022-06-09"), "{query}");
+    assert!(query.contains("currency=EUR"), "{query}");
+    assert!(query.contains("sort=desc"), "{query}");
+
+    let request = ListReqInit::default().init("META", start, end, TimeFrame::ONE_DAY);
+    let query = List::query(&request).unwrap().unwrap();
+    assert!(!query.contains("asof"), "{query}");
+    assert!(!query.contains("currency"), "{query}");
+    assert!(!query.contains("sort"), "{query}");
+  }
+
+  /// Check that the setters of `ListReqInit` produce the same request
+  /// as initializing its members directly.
+  #[test]
+  fn init_setters() {
+    let start = DateTime::from_str("2021-11-05T00:00:00Z").unwrap();
+    let end = DateTime::from_str("2021-11-05T00:00:00Z").unwrap();
+    let expected = ListReqInit {
+      limit: Some(10),
+      feed: Some(Feed::IEX),
+      sort: Some(Sort::Desc),
+      ..Default::default()
+    }
+    .init("AAPL", start, end, TimeFrame::ONE_DAY);
+
+    let mut init = ListReqInit::new();
+    let request = init
+      .limit(10)
+      .feed(Feed::IEX)
+      .sort(Sort::Desc)
+      .init("AAPL", start, end, TimeFrame::ONE_DAY);
+    assert_eq!(request, expected);
+
+    // The initializer is left intact and can be reused.
+    let request = init.init("MSFT", start, end, TimeFrame::ONE_DAY);
+    assert_eq!(request.symbol, "MSFT");
+    assert_eq!(request.limit, Some(10));
+  }
+
   /// Verify that we can properly parse a reference bar response.
   #[test]
   fn parse_reference_bars() {
@@ -252,6 +525,7 @@ mod tests {
         "l": 133.31,
         "c": 133.5,
         "v": 9876,
+        "n": 42,
         "vw": 133.4
 
       },
@@ -280,10 +554,49 @@ mod tests {
     assert_eq!(bars[0].high, Num::new(13374, 100));
     assert_eq!(bars[0].low, Num::new(13331, 100));
     assert_eq!(bars[0].weighted_average, Num::new(1334, 10));
+    assert_eq!(bars[0].trade_count, Some(42));
+    assert_eq!(bars[1].trade_count, None);
     assert_eq!(res.symbol, "AAPL".to_string());
     assert!(res.next_page_token.is_some())
   }
 
+  /// Verify that we can parse bars with prices represented as `f64`.
+  #[cfg(feature = "f64-prices")]
+  #[test(tokio::test)]
+  async fn list_f64_bars() {
+    let response = r#"{
+    "bars": [
+      {
+        "t": "2021-02-01T16:01:00Z",
+        "o": 133.32,
+        "h": 133.74,
+        "l": 133.31,
+        "c": 133.5,
+        "v": 9876,
+        "vw": 133.4
+      }
+    ],
+    "symbol": "AAPL",
+    "next_page_token": null
+}"#;
+
+    let start = DateTime::from_str("2021-02-01T16:00:00Z").unwrap();
+    let end = DateTime::from_str("2021-02-01T17:00:00Z").unwrap();
+    let request = ListReqInit::default().init("AAPL", start, end, TimeFrame::ONE_MINUTE);
+    assert_eq!(ListF64::path(&request), List::path(&request));
+
+    let mock = MockTransport::new();
+    let () = mock.respond::<ListF64>(&request, StatusCode::OK, response);
+    let client = mock_client(mock);
+
+    let bars = client.issue::<ListF64>(&request).await.unwrap();
+    assert_eq!(bars.bars.len(), 1);
+    assert_eq!(bars.bars[0].open, 133.32);
+    assert_eq!(bars.bars[0].close, 133.5);
+    assert_eq!(bars.bars[0].weighted_average, 133.4);
+    assert_eq!(bars.bars[0].volume, 9876);
+  }
+
   /// Check that we can decode a response containing no bars correctly.
   #[test(tokio::test)]
   async fn no_bars() {
@@ -291,7 +604,7 @@ mod tests {
     let client = Client::new(api_info);
     let start = DateTime::from_str("2021-11-05T00:00:00Z").unwrap();
     let end = DateTime::from_str("2021-11-05T00:00:00Z").unwrap();
-    let request = ListReqInit::default().init("AAPL", start, end, TimeFrame::OneDay);
+    let request = ListReqInit::default().init("AAPL", start, end, TimeFrame::ONE_DAY);
 
     let res = client.issue::<List>(&request).await.unwrap();
     assert_eq!(res.bars, Vec::new())
@@ -308,7 +621,7 @@ mod tests {
       limit: Some(2),
       ..Default::default()
     }
-    .init("AAPL", start, end, TimeFrame::OneDay);
+    .init("AAPL", start, end, TimeFrame::ONE_DAY);
 
     let res = client.issue::<List>(&request).await.unwrap();
     let bars = res.bars;
@@ -346,7 +659,7 @@ mod tests {
       limit: Some(2),
       ..Default::default()
     }
-    .init("AAPL", start, end, TimeFrame::OneDay);
+    .init("AAPL", start, end, TimeFrame::ONE_DAY);
 
     let mut res = client.issue::<List>(&request).await.unwrap();
     let bars = res.bars;
@@ -362,6 +675,66 @@ mod tests {
     assert!(res.next_page_token.is_none())
   }
 
+  /// Check that `fetch_range` splits the requested time range into
+  /// chunks.
+  #[test(tokio::test)]
+  async fn fetch_range_chunked() {
+    let response = r#"{
+    "bars": [
+      {"t": "2021-02-01T16:01:00Z", "o": 1, "h": 1, "l": 1, "c": 1, "v": 1, "vw": 1},
+      {"t": "2021-02-01T16:02:00Z", "o": 2, "h": 2, "l": 2, "c": 2, "v": 2, "vw": 2}
+    ],
+    "symbol": "AAPL",
+    "next_page_token": null
+}"#;
+
+    let start = DateTime::from_str("2021-02-01T00:00:00Z").unwrap();
+    let end = DateTime::from_str("2021-02-04T00:00:00Z").unwrap();
+    let request = ListReqInit::default().init("AAPL", start, end, TimeFrame::ONE_MINUTE);
+
+    let mock = MockTransport::new();
+    let () = mock.respond::<List>(&request, StatusCode::OK, response);
+    let client = mock_client(mock);
+
+    // The mock reports the same page for each of the three chunks.
+    let bars = fetch_range(&client, request, ChronoDuration::days(1))
+      .try_collect::<Vec<_>>()
+      .await
+      .unwrap();
+    assert_eq!(bars.len(), 6);
+
+    // Without chunking only a single request is issued.
+    let request = ListReqInit::default().init("AAPL", start, end, TimeFrame::ONE_MINUTE);
+    let bars = fetch_range(&client, request, ChronoDuration::zero())
+      .try_collect::<Vec<_>>()
+      .await
+      .unwrap();
+    assert_eq!(bars.len(), 2);
+  }
+
+  /// Verify that we can retrieve a time range of bars, following page
+  /// tokens along the way.
+  #[test(tokio::test)]
+  async fn fetch_bar_range() {
+    let api_info = ApiInfo::from_env().unwrap();
+    let client = Client::new(api_info);
+    let start = DateTime::from_str("2018-12-03T21:47:00Z").unwrap();
+    let end = DateTime::from_str("2018-12-07T21:47:00Z").unwrap();
+    let request = ListReqInit {
+      limit: Some(1),
+      ..Default::default()
+    }
+    .init("AAPL", start, end, TimeFrame::ONE_DAY);
+
+    let bars = fetch_range(&client, request, ChronoDuration::days(2))
+      .try_collect::<Vec<_>>()
+      .await
+      .unwrap();
+
+    assert_eq!(bars.len(), 3);
+    assert!(bars.windows(2).all(|bars| bars[0].time < bars[1].time));
+  }
+
   /// Request bars for `AAPL` for a predefined time frame with the
   /// provided adjustment.
   async fn request_with_adjustment(adjustment: Adjustment) -> Bars {
@@ -373,7 +746,7 @@ mod tests {
       adjustment: Some(adjustment),
       ..Default::default()
     }
-    .init("AAPL", start, end, TimeFrame::OneDay);
+    .init("AAPL", start, end, TimeFrame::ONE_DAY);
 
     client.issue::<List>(&request).await.unwrap()
   }
@@ -444,7 +817,7 @@ mod tests {
       feed: Some(Feed::SIP),
       ..Default::default()
     }
-    .init("AAPL", start, end, TimeFrame::OneDay);
+    .init("AAPL", start, end, TimeFrame::ONE_DAY);
 
     let result = client.issue::<List>(&request).await;
     // Unfortunately we can't really know whether the user has the
@@ -466,10 +839,10 @@ mod tests {
     let start = DateTime::from_str("2018-12-03T21:47:00Z").unwrap();
     let end = DateTime::from_str("2018-12-07T21:47:00Z").unwrap();
     let request = ListReqInit {
-      page_token: Some("123456789abcdefghi".to_string()),
+      page_token: Some("123456789abcdefghi".into()),
       ..Default::default()
     }
-    .init("SPY", start, end, TimeFrame::OneMinute);
+    .init("SPY", start, end, TimeFrame::ONE_MINUTE);
 
     let err = client.issue::<List>(&request).await.unwrap_err();
     match err {
@@ -487,7 +860,7 @@ mod tests {
 
     let start = DateTime::from_str("2022-02-01T00:00:00Z").unwrap();
     let end = DateTime::from_str("2022-02-20T00:00:00Z").unwrap();
-    let request = ListReqInit::default().init("ABC123", start, end, TimeFrame::OneDay);
+    let request = ListReqInit::default().init("ABC123", start, end, TimeFrame::ONE_DAY);
 
     let err = client.issue::<List>(&request).await.unwrap_err();
     match err {
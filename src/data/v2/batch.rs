@@ -0,0 +1,166 @@
+// Copyright (C) 2024 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::collections::HashMap;
+
+use futures::stream::iter;
+use futures::StreamExt as _;
+
+use crate::endpoint::ApiEndpoint;
+use crate::endpoint::EndpointError;
+use crate::Client;
+use crate::RequestError;
+
+
+/// The aggregated outcome of a [`fetch`] invocation.
+#[derive(Debug)]
+pub struct Batch<T, E> {
+  /// The successfully retrieved outputs, keyed on symbol.
+  pub results: HashMap<String, T>,
+  /// The errors encountered, keyed on symbol.
+  pub errors: HashMap<String, RequestError<E>>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+impl<T, E> Batch<T, E> {
+  /// Check whether all requests succeeded.
+  #[inline]
+  pub fn is_complete(&self) -> bool {
+    self.errors.is_empty()
+  }
+}
+
+
+/// Issue a request to endpoint `R` for each of the provided symbols,
+/// with at most `concurrency` requests in flight at any time.
+///
+/// `request` creates the request input for a single symbol. A failure
+/// for one symbol does not affect the remaining ones; all errors are
+/// collected in [`Batch::errors`] instead. A `concurrency` of zero is
+/// treated as one.
+///
+/// ```no_run
+/// # use std::str::FromStr as _;
+/// # use apca::ApiInfo;
+/// # use apca::Client;
+/// # use apca::data::v2::bars;
+/// # use apca::data::v2::batch;
+/// # use chrono::DateTime;
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let client = Client::new(ApiInfo::from_env().unwrap());
+/// let start = DateTime::from_str("2022-01-03T00:00:00Z").unwrap();
+/// let end = DateTime::from_str("2022-01-07T00:00:00Z").unwrap();
+///
+/// let batch = batch::fetch::<bars::List, _, _>(&client, ["AAPL", "MSFT", "SPY"], 2, |symbol| {
+///   bars::ListReqInit::default().init(symbol, start, end, bars::TimeFrame::ONE_DAY)
+/// })
+/// .await;
+///
+/// for (symbol, bars) in &batch.results {
+///   println!("{symbol}: {} bars", bars.bars.len());
+/// }
+/// # })
+/// ```
+pub async fn fetch<R, S, F>(
+  client: &Client,
+  symbols: impl IntoIterator<Item = S>,
+  concurrency: usize,
+  mut request: F,
+) -> Batch<R::Output, R::Error>
+where
+  R: ApiEndpoint,
+  R::Error: EndpointError,
+  S: Into<String>,
+  F: FnMut(&str) -> R::Input,
+{
+  let requests = symbols
+    .into_iter()
+    .map(|symbol| {
+      let symbol = symbol.into();
+      let input = request(&symbol);
+      (symbol, input)
+    })
+    .collect::<Vec<_>>();
+
+  let mut outcomes = iter(requests)
+    .map(|(symbol, input)| async move {
+      let result = client.issue::<R>(&input).await;
+      (symbol, result)
+    })
+    .buffer_unordered(concurrency.max(1));
+
+  let mut batch = Batch {
+    results: HashMap::new(),
+    errors: HashMap::new(),
+    _non_exhaustive: (),
+  };
+
+  while let Some((symbol, result)) = outcomes.next().await {
+    match result {
+      Ok(output) => {
+        let _prev = batch.results.insert(symbol, output);
+      },
+      Err(err) => {
+        let _prev = batch.errors.insert(symbol, err);
+      },
+    }
+  }
+  batch
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use std::str::FromStr as _;
+
+  use chrono::DateTime;
+
+  use http::StatusCode;
+
+  use test_log::test;
+
+  use crate::data::v2::bars;
+  use crate::transport::test::mock_client;
+  use crate::MockTransport;
+
+
+  /// Check that per-symbol results and errors are aggregated.
+  #[test(tokio::test)]
+  async fn fetch_batch() {
+    let response = r#"{
+    "bars": [
+      {"t": "2021-02-01T16:01:00Z", "o": 1, "h": 1, "l": 1, "c": 1, "v": 1, "vw": 1}
+    ],
+    "symbol": "AAPL",
+    "next_page_token": null
+}"#;
+
+    let start = DateTime::from_str("2021-02-01T00:00:00Z").unwrap();
+    let end = DateTime::from_str("2021-02-02T00:00:00Z").unwrap();
+    let request = |symbol: &str| {
+      bars::ListReqInit::default().init(symbol, start, end, bars::TimeFrame::ONE_MINUTE)
+    };
+
+    let mock = MockTransport::new();
+    let () = mock.respond::<bars::List>(&request("AAPL"), StatusCode::OK, response);
+    let () = mock.respond::<bars::List>(&request("SPY"), StatusCode::OK, response);
+    let client = mock_client(mock);
+
+    // No response is primed for `MSFT`, so retrieval fails for it.
+    let batch = fetch::<bars::List, _, _>(&client, ["AAPL", "MSFT", "SPY"], 2, request).await;
+    assert!(!batch.is_complete());
+    assert_eq!(batch.results.len(), 2);
+    assert_eq!(batch.results["AAPL"].bars.len(), 1);
+    assert_eq!(batch.results["SPY"].bars.len(), 1);
+    assert_eq!(batch.errors.len(), 1);
+
+    match &batch.errors["MSFT"] {
+      RequestError::Transport(..) => (),
+      err => panic!("received unexpected error: {err:?}"),
+    }
+  }
+}
@@ -0,0 +1,341 @@
+// Copyright (C) 2024 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+
+use futures::future::poll_fn;
+use futures::pin_mut;
+use futures::stream::FusedStream;
+use futures::task::Context;
+use futures::task::Poll;
+use futures::task::Waker;
+use futures::Stream;
+use futures::StreamExt as _;
+
+use tokio::spawn;
+use tokio::task::JoinHandle;
+
+
+/// The policy to apply once the buffer of a [`Buffered`] stream is
+/// full.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum Overflow {
+  /// Stop reading from the wrapped stream until the consumer caught
+  /// up, exerting back pressure on the producer.
+  ///
+  /// Note that for realtime market data this means that data pile up
+  /// on the server side, which may eventually terminate the
+  /// connection.
+  #[default]
+  Block,
+  /// Discard the oldest buffered item to make room for the new one.
+  DropOldest,
+  /// Discard the new item.
+  DropNewest,
+}
+
+
+/// The state shared between a [`Buffered`] stream and the task
+/// driving the wrapped stream.
+#[derive(Debug)]
+struct Shared<T> {
+  /// The buffered items.
+  buffer: VecDeque<T>,
+  /// The maximum number of buffered items.
+  capacity: usize,
+  /// The policy to apply once the buffer is full.
+  overflow: Overflow,
+  /// The number of items dropped so far.
+  dropped: u64,
+  /// Whether the wrapped stream has been exhausted.
+  exhausted: bool,
+  /// The waker of the consumer waiting for an item, if any.
+  consumer: Option<Waker>,
+  /// The waker of the task waiting for room in the buffer, if any.
+  producer: Option<Waker>,
+}
+
+impl<T> Shared<T> {
+  /// Check whether the task has to wait for room in the buffer before
+  /// reading the next item.
+  #[inline]
+  fn is_blocked(&self) -> bool {
+    self.overflow == Overflow::Block && self.buffer.len() >= self.capacity
+  }
+
+  /// Add an item to the buffer, honoring the overflow policy.
+  fn push(&mut self, item: T) {
+    if self.buffer.len() < self.capacity {
+      let () = self.buffer.push_back(item);
+    } else {
+      match self.overflow {
+        // When blocking, room is ensured before an item is read, so
+        // there is no need to make any.
+        Overflow::Block => {
+          let () = self.buffer.push_back(item);
+        },
+        Overflow::DropOldest => {
+          let _item = self.buffer.pop_front();
+          let () = self.buffer.push_back(item);
+          self.dropped += 1;
+        },
+        Overflow::DropNewest => {
+          self.dropped += 1;
+        },
+      }
+    }
+
+    if let Some(waker) = self.consumer.take() {
+      let () = waker.wake();
+    }
+  }
+}
+
+
+/// Lock the provided shared state, ignoring poisoning.
+#[inline]
+fn lock<T>(shared: &Mutex<Shared<T>>) -> MutexGuard<'_, Shared<T>> {
+  shared.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+
+/// A guard marking the wrapped stream as exhausted once the task
+/// driving it finished, for whatever reason.
+struct Exhausted<T>(Arc<Mutex<Shared<T>>>);
+
+impl<T> Drop for Exhausted<T> {
+  fn drop(&mut self) {
+    let mut shared = lock(&self.0);
+    shared.exhausted = true;
+
+    if let Some(waker) = shared.consumer.take() {
+      let () = waker.wake();
+    }
+  }
+}
+
+
+/// Read all items from `inner` into the shared buffer.
+async fn feed<S>(inner: S, shared: Arc<Mutex<Shared<S::Item>>>)
+where
+  S: Stream,
+{
+  pin_mut!(inner);
+  let _guard = Exhausted(shared.clone());
+
+  loop {
+    let () = poll_fn(|ctx| {
+      let mut shared = lock(&shared);
+      if shared.is_blocked() {
+        shared.producer = Some(ctx.waker().clone());
+        Poll::Pending
+      } else {
+        Poll::Ready(())
+      }
+    })
+    .await;
+
+    match inner.next().await {
+      Some(item) => lock(&shared).push(item),
+      None => break,
+    }
+  }
+}
+
+
+/// A stream adapter buffering a bounded number of items of the wrapped
+/// stream (typically a realtime market data stream).
+///
+/// The wrapped stream is driven by a task spawned onto the Tokio
+/// runtime, which reads items into the buffer as they arrive, applying
+/// the configured [`Overflow`] policy once the buffer is full. For a
+/// consumer not keeping up with the rate at which data arrive, that
+/// bounds memory usage while making sure that the connection is
+/// serviced.
+///
+/// Because the wrapped stream is polled continuously, market data
+/// subscription changes resolve without the need to
+/// [`drive`][crate::data::v2::stream::drive] them.
+///
+/// Dropping the adapter stops the task and drops the wrapped stream.
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct Buffered<T> {
+  /// The state shared with the task driving the wrapped stream.
+  shared: Arc<Mutex<Shared<T>>>,
+  /// The task driving the wrapped stream.
+  task: JoinHandle<()>,
+}
+
+impl<T> Buffered<T>
+where
+  T: Send + 'static,
+{
+  /// Wrap the provided stream, buffering up to `capacity` items and
+  /// applying the given policy once the buffer is full.
+  ///
+  /// A capacity of zero is treated as one.
+  ///
+  /// # Panics
+  /// This function panics if called outside of a Tokio runtime.
+  pub fn new<S>(inner: S, capacity: usize, overflow: Overflow) -> Self
+  where
+    S: Stream<Item = T> + Send + 'static,
+  {
+    let capacity = capacity.max(1);
+    let shared = Arc::new(Mutex::new(Shared {
+      buffer: VecDeque::with_capacity(capacity),
+      capacity,
+      overflow,
+      dropped: 0,
+      exhausted: false,
+      consumer: None,
+      producer: None,
+    }));
+    let task = spawn(feed(inner, shared.clone()));
+
+    Self { shared, task }
+  }
+}
+
+impl<T> Buffered<T> {
+  /// Retrieve the number of items dropped so far, due to the buffer
+  /// being full.
+  #[inline]
+  pub fn dropped(&self) -> u64 {
+    lock(&self.shared).dropped
+  }
+
+  /// Retrieve the number of currently buffered items.
+  #[inline]
+  pub fn len(&self) -> usize {
+    lock(&self.shared).buffer.len()
+  }
+
+  /// Check whether no items are currently buffered.
+  #[inline]
+  pub fn is_empty(&self) -> bool {
+    lock(&self.shared).buffer.is_empty()
+  }
+}
+
+impl<T> Drop for Buffered<T> {
+  fn drop(&mut self) {
+    let () = self.task.abort();
+  }
+}
+
+impl<T> Stream for Buffered<T> {
+  type Item = T;
+
+  fn poll_next(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+    let mut shared = lock(&self.shared);
+
+    match shared.buffer.pop_front() {
+      Some(item) => {
+        if let Some(waker) = shared.producer.take() {
+          let () = waker.wake();
+        }
+        Poll::Ready(Some(item))
+      },
+      None if shared.exhausted => Poll::Ready(None),
+      None => {
+        shared.consumer = Some(ctx.waker().clone());
+        Poll::Pending
+      },
+    }
+  }
+}
+
+impl<T> FusedStream for Buffered<T> {
+  #[inline]
+  fn is_terminated(&self) -> bool {
+    let shared = lock(&self.shared);
+    shared.exhausted && shared.buffer.is_empty()
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use futures::channel::mpsc::unbounded;
+  use futures::stream::iter;
+
+  use tokio::task::yield_now;
+
+  use test_log::test;
+
+
+  /// Check that items are passed through unchanged while the buffer
+  /// does not overflow.
+  #[test(tokio::test)]
+  async fn forward_items() {
+    let (send, recv) = unbounded();
+    let mut stream = Buffered::new(recv, 2, Overflow::DropOldest);
+
+    let () = send.unbounded_send(1).unwrap();
+    assert_eq!(stream.next().await, Some(1));
+    let () = send.unbounded_send(2).unwrap();
+    let () = send.unbounded_send(3).unwrap();
+    assert_eq!(stream.next().await, Some(2));
+    assert_eq!(stream.next().await, Some(3));
+
+    drop(send);
+    assert_eq!(stream.next().await, None);
+    assert!(stream.is_terminated());
+    assert_eq!(stream.dropped(), 0);
+  }
+
+  /// Check that the oldest items are dropped on overflow, if so
+  /// configured.
+  #[test(tokio::test)]
+  async fn drop_oldest() {
+    let mut stream = Buffered::new(iter(1..=5), 3, Overflow::DropOldest);
+    assert_eq!(stream.next().await, Some(3));
+    assert_eq!(stream.len(), 2);
+
+    let items = stream.by_ref().collect::<Vec<_>>().await;
+    assert_eq!(items, vec![4, 5]);
+    assert_eq!(stream.dropped(), 2);
+  }
+
+  /// Check that new items are dropped on overflow, if so configured.
+  #[test(tokio::test)]
+  async fn drop_newest() {
+    let mut stream = Buffered::new(iter(1..=5), 3, Overflow::DropNewest);
+    assert_eq!(stream.next().await, Some(1));
+
+    let items = stream.by_ref().collect::<Vec<_>>().await;
+    assert_eq!(items, vec![2, 3]);
+    assert_eq!(stream.dropped(), 2);
+  }
+
+  /// Check that no items are lost when blocking.
+  #[test(tokio::test)]
+  async fn block() {
+    let mut stream = Buffered::new(iter(1..=5), 2, Overflow::Block);
+    let items = stream.by_ref().collect::<Vec<_>>().await;
+    assert_eq!(items, vec![1, 2, 3, 4, 5]);
+    assert_eq!(stream.dropped(), 0);
+  }
+
+  /// Check that dropping a `Buffered` stream stops the task driving the
+  /// wrapped stream.
+  #[test(tokio::test)]
+  async fn drop_stops_task() {
+    let (send, recv) = unbounded::<u64>();
+    let stream = Buffered::new(recv, 1, Overflow::Block);
+    drop(stream);
+
+    while !send.is_closed() {
+      let () = yield_now().await;
+    }
+  }
+}
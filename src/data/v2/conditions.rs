@@ -0,0 +1,358 @@
+// Copyright (C) 2024 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use serde::Deserialize;
+use serde::Serialize;
+
+
+/// An enumeration of the consolidated tapes a security is reported on.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[non_exhaustive]
+pub enum Tape {
+  /// Tape A, comprising NYSE listed securities, as disseminated by the
+  /// Consolidated Tape Association (CTA).
+  #[serde(rename = "A")]
+  A,
+  /// Tape B, comprising securities listed on NYSE Arca, NYSE American,
+  /// and other regional exchanges, as disseminated by the CTA.
+  #[serde(rename = "B")]
+  B,
+  /// Tape C, comprising Nasdaq listed securities, as disseminated by
+  /// the Unlisted Trading Privileges (UTP) plan.
+  #[serde(rename = "C")]
+  C,
+  /// Any other tape that we have not accounted for.
+  #[doc(hidden)]
+  #[serde(other, rename(serialize = "unknown"))]
+  Unknown,
+}
+
+impl Tape {
+  /// Check whether the tape is disseminated by the Consolidated Tape
+  /// Association, as opposed to the UTP plan.
+  #[inline]
+  pub fn is_cta(self) -> bool {
+    matches!(self, Self::A | Self::B)
+  }
+}
+
+
+/// A condition attached to a trade.
+///
+/// Condition codes are defined separately by the CTA and the UTP plan,
+/// with some codes carrying a different meaning depending on the tape.
+/// Use [`TradeCondition::from_code`] to decode a code in the context of
+/// the tape it was reported on.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum TradeCondition {
+  /// A regular sale.
+  RegularSale,
+  /// An acquisition.
+  Acquisition,
+  /// An average price trade.
+  AveragePrice,
+  /// A bunched trade.
+  BunchedTrade,
+  /// A bunched sold trade.
+  BunchedSoldTrade,
+  /// A cash sale.
+  CashSale,
+  /// A distribution.
+  Distribution,
+  /// An automatic execution.
+  AutomaticExecution,
+  /// An intermarket sweep order.
+  IntermarketSweep,
+  /// A price variation trade.
+  PriceVariation,
+  /// An odd lot trade.
+  OddLot,
+  /// A trade executed under NYSE rule 127 or rule 155.
+  Rule155,
+  /// A trade reported late but in sequence.
+  SoldLast,
+  /// The market center's official closing price.
+  OfficialClose,
+  /// A next day trade.
+  NextDay,
+  /// A market center opening trade.
+  OpeningPrint,
+  /// A trade referring to a prior reference price.
+  PriorReferencePrice,
+  /// The market center's official opening price.
+  OfficialOpen,
+  /// A seller's option trade.
+  Seller,
+  /// A split trade.
+  SplitTrade,
+  /// A trade executed outside of regular trading hours (Form T).
+  ExtendedHours,
+  /// A trade executed outside of regular trading hours and reported
+  /// out of sequence.
+  ExtendedHoursOutOfSequence,
+  /// A contingent trade.
+  Contingent,
+  /// A cross or periodic auction trade.
+  Cross,
+  /// A regular trade of a security flagged as potentially erroneous.
+  YellowFlag,
+  /// A trade reported out of sequence.
+  SoldOutOfSequence,
+  /// A stopped stock trade.
+  StoppedStock,
+  /// A derivatively priced trade.
+  DerivativelyPriced,
+  /// A market center reopening trade.
+  ReopeningPrint,
+  /// A market center closing trade.
+  ClosingPrint,
+  /// A qualified contingent trade.
+  QualifiedContingent,
+  /// A corrected consolidated close price as per the listing market.
+  CorrectedClose,
+  /// A condition code we do not know about.
+  Unknown(String),
+}
+
+impl TradeCondition {
+  /// Decode the provided trade condition code as reported on the given
+  /// tape.
+  pub fn from_code(tape: Tape, code: &str) -> Self {
+    let condition = match code {
+      "@" => Some(Self::RegularSale),
+      "C" => Some(Self::CashSale),
+      "F" => Some(Self::IntermarketSweep),
+      "H" => Some(Self::PriceVariation),
+      "I" => Some(Self::OddLot),
+      "K" => Some(Self::Rule155),
+      "L" => Some(Self::SoldLast),
+      "M" => Some(Self::OfficialClose),
+      "N" => Some(Self::NextDay),
+      "O" => Some(Self::OpeningPrint),
+      "P" => Some(Self::PriorReferencePrice),
+      "Q" => Some(Self::OfficialOpen),
+      "R" => Some(Self::Seller),
+      "T" => Some(Self::ExtendedHours),
+      "U" => Some(Self::ExtendedHoursOutOfSequence),
+      "V" => Some(Self::Contingent),
+      "X" => Some(Self::Cross),
+      "Z" => Some(Self::SoldOutOfSequence),
+      "4" => Some(Self::DerivativelyPriced),
+      "5" => Some(Self::ReopeningPrint),
+      "6" => Some(Self::ClosingPrint),
+      "7" => Some(Self::QualifiedContingent),
+      "9" => Some(Self::CorrectedClose),
+      _ => None,
+    };
+
+    let specific = match tape {
+      Tape::A | Tape::B => match code {
+        "B" => Some(Self::AveragePrice),
+        "E" => Some(Self::AutomaticExecution),
+        _ => None,
+      },
+      Tape::C => match code {
+        "A" => Some(Self::Acquisition),
+        "B" => Some(Self::BunchedTrade),
+        "D" => Some(Self::Distribution),
+        "G" => Some(Self::BunchedSoldTrade),
+        "S" => Some(Self::SplitTrade),
+        "W" => Some(Self::AveragePrice),
+        "Y" => Some(Self::YellowFlag),
+        "1" => Some(Self::StoppedStock),
+        _ => None,
+      },
+      Tape::Unknown => None,
+    };
+
+    condition
+      .or(specific)
+      .unwrap_or_else(|| Self::Unknown(code.to_string()))
+  }
+}
+
+
+/// A condition attached to a quote.
+///
+/// Similar to trade conditions, the meaning of quote condition codes
+/// depends on the tape they were reported on. Use
+/// [`QuoteCondition::from_code`] for decoding.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum QuoteCondition {
+  /// A regular, two-sided open quote.
+  Regular,
+  /// A slow quote on the ask side.
+  SlowOnAsk,
+  /// A slow quote on the bid side.
+  SlowOnBid,
+  /// A slow quote on both the bid and the ask side.
+  SlowOnBidAndAsk,
+  /// A manual quote on the ask side with an automated bid.
+  ManualAskAutomatedBid,
+  /// A manual quote on the bid side with an automated ask.
+  ManualBidAutomatedAsk,
+  /// A manual quote on both the bid and the ask side.
+  ManualBidAndAsk,
+  /// A non-firm manual quote on both the bid and the ask side.
+  ManualBidAndAskNonFirm,
+  /// A closing quote.
+  Closing,
+  /// A quote issued due to news dissemination.
+  NewsDissemination,
+  /// A quote issued due to pending news.
+  NewsPending,
+  /// A trading range indication.
+  TradingRangeIndication,
+  /// A quote issued during fast trading.
+  FastTrading,
+  /// A quote issued due to an order imbalance.
+  OrderImbalance,
+  /// A quote issued due to an order influx.
+  OrderInflux,
+  /// A closed quote.
+  Closed,
+  /// A non-firm quote.
+  NonFirm,
+  /// An opening quote.
+  Opening,
+  /// A one-sided open quote, without either a bid or an offer.
+  OneSidedOpen,
+  /// A quote signaling the resumption of trading.
+  Resume,
+  /// A quote issued due to an equipment changeover.
+  EquipmentChangeover,
+  /// A quote for a security admitted to sub-penny trading.
+  SubPennyTrading,
+  /// A quote signaling that no opening or resumption is to be expected.
+  NoOpenNoResume,
+  /// An on-demand intraday auction quote.
+  OnDemandAuction,
+  /// A condition code we do not know about.
+  Unknown(String),
+}
+
+impl QuoteCondition {
+  /// Decode the provided quote condition code as reported on the given
+  /// tape.
+  pub fn from_code(tape: Tape, code: &str) -> Self {
+    let condition = match code {
+      "I" => Some(Self::OrderImbalance),
+      "L" => Some(Self::Closed),
+      "N" => Some(Self::NonFirm),
+      "O" => Some(Self::Opening),
+      "R" => Some(Self::Regular),
+      "Z" => Some(Self::NoOpenNoResume),
+      "4" => Some(Self::OnDemandAuction),
+      _ => None,
+    };
+
+    let specific = match tape {
+      Tape::A | Tape::B => match code {
+        "A" | "F" => Some(Self::SlowOnAsk),
+        "B" | "E" => Some(Self::SlowOnBid),
+        "C" => Some(Self::Closing),
+        "D" => Some(Self::NewsDissemination),
+        "G" => Some(Self::TradingRangeIndication),
+        "H" | "U" | "W" => Some(Self::SlowOnBidAndAsk),
+        "P" => Some(Self::NewsPending),
+        "T" => Some(Self::Resume),
+        "X" => Some(Self::EquipmentChangeover),
+        "Y" => Some(Self::SubPennyTrading),
+        _ => None,
+      },
+      Tape::C => match code {
+        "A" => Some(Self::ManualAskAutomatedBid),
+        "B" => Some(Self::ManualBidAutomatedAsk),
+        "F" => Some(Self::FastTrading),
+        "H" => Some(Self::ManualBidAndAsk),
+        "U" => Some(Self::ManualBidAndAskNonFirm),
+        "X" => Some(Self::OrderInflux),
+        "Y" => Some(Self::OneSidedOpen),
+        _ => None,
+      },
+      Tape::Unknown => None,
+    };
+
+    condition
+      .or(specific)
+      .unwrap_or_else(|| Self::Unknown(code.to_string()))
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use serde_json::from_str as from_json;
+  use serde_json::to_string as to_json;
+
+  use test_log::test;
+
+
+  /// Check that we can deserialize and serialize a `Tape`.
+  #[test]
+  fn deserialize_serialize_tape() {
+    let tape = from_json::<Tape>(r#""C""#).unwrap();
+    assert_eq!(tape, Tape::C);
+    assert!(!tape.is_cta());
+    assert_eq!(to_json(&tape).unwrap(), r#""C""#);
+
+    let tape = from_json::<Tape>(r#""O""#).unwrap();
+    assert_eq!(tape, Tape::Unknown);
+  }
+
+  /// Check that trade condition codes are decoded in the context of
+  /// the tape they were reported on.
+  #[test]
+  fn decode_trade_conditions() {
+    assert_eq!(
+      TradeCondition::from_code(Tape::A, "@"),
+      TradeCondition::RegularSale
+    );
+    assert_eq!(
+      TradeCondition::from_code(Tape::C, "I"),
+      TradeCondition::OddLot
+    );
+    assert_eq!(
+      TradeCondition::from_code(Tape::B, "B"),
+      TradeCondition::AveragePrice
+    );
+    assert_eq!(
+      TradeCondition::from_code(Tape::C, "B"),
+      TradeCondition::BunchedTrade
+    );
+    assert_eq!(
+      TradeCondition::from_code(Tape::C, "W"),
+      TradeCondition::AveragePrice
+    );
+    assert_eq!(
+      TradeCondition::from_code(Tape::A, "W"),
+      TradeCondition::Unknown("W".to_string())
+    );
+  }
+
+  /// Check that quote condition codes are decoded in the context of
+  /// the tape they were reported on.
+  #[test]
+  fn decode_quote_conditions() {
+    assert_eq!(
+      QuoteCondition::from_code(Tape::C, "R"),
+      QuoteCondition::Regular
+    );
+    assert_eq!(
+      QuoteCondition::from_code(Tape::A, "A"),
+      QuoteCondition::SlowOnAsk
+    );
+    assert_eq!(
+      QuoteCondition::from_code(Tape::C, "A"),
+      QuoteCondition::ManualAskAutomatedBid
+    );
+    assert_eq!(
+      QuoteCondition::from_code(Tape::Unknown, "A"),
+      QuoteCondition::Unknown("A".to_string())
+    );
+  }
+}
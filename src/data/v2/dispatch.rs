@@ -0,0 +1,273 @@
+// Copyright (C) 2024 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+
+use futures::future::BoxFuture;
+use futures::Future;
+use futures::FutureExt as _;
+use futures::Stream;
+use futures::StreamExt as _;
+
+use serde_json::Error as JsonError;
+
+use websocket_util::tungstenite::Error as WebSocketError;
+
+use crate::data::v2::stream::Bar;
+use crate::data::v2::stream::Data;
+use crate::data::v2::stream::Quote;
+use crate::data::v2::stream::Trade;
+use crate::Error;
+
+
+/// A type-erased asynchronous handler for items of type `I`.
+type Handler<I> = Box<dyn FnMut(I) -> BoxFuture<'static, ()> + Send>;
+
+
+/// Wrap the provided function into a [`Handler`].
+fn handler<I, F, R>(mut f: F) -> Handler<I>
+where
+  F: FnMut(I) -> R + Send + 'static,
+  R: Future<Output = ()> + Send + 'static,
+{
+  Box::new(move |item| f(item).boxed())
+}
+
+
+/// A dispatcher consuming a realtime market data stream and invoking
+/// user provided asynchronous handlers for each received item.
+///
+/// Items for which no handler has been registered are dropped.
+///
+/// ```no_run
+/// # use futures::FutureExt as _;
+/// # use apca::ApiInfo;
+/// # use apca::Client;
+/// # use apca::data::v2::dispatch::Dispatcher;
+/// # use apca::data::v2::stream::drive;
+/// # use apca::data::v2::stream::Bar;
+/// # use apca::data::v2::stream::MarketData;
+/// # use apca::data::v2::stream::RealtimeData;
+/// # use apca::data::v2::stream::IEX;
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let client = Client::new(ApiInfo::from_env().unwrap());
+/// let (mut stream, mut subscription) = client.subscribe::<RealtimeData<IEX>>().await.unwrap();
+///
+/// let mut data = MarketData::default();
+/// data.set_bars(["SPY"]);
+/// let subscribe = subscription.subscribe(&data).boxed();
+/// let () = drive(subscribe, &mut stream).await.unwrap().unwrap().unwrap();
+///
+/// let () = Dispatcher::new()
+///   .on_bar(|bar: Bar| async move { println!("{}: {}", bar.symbol, bar.close_price) })
+///   .run(stream)
+///   .await
+///   .unwrap();
+/// # })
+/// ```
+pub struct Dispatcher<B = Bar, Q = Quote, T = Trade> {
+  /// The handler invoked for bars.
+  on_bar: Option<Handler<B>>,
+  /// The handler invoked for quotes.
+  on_quote: Option<Handler<Q>>,
+  /// The handler invoked for trades.
+  on_trade: Option<Handler<T>>,
+  /// The handler invoked for items that could not be decoded.
+  on_error: Option<Handler<JsonError>>,
+}
+
+impl<B, Q, T> Dispatcher<B, Q, T> {
+  /// Create a new `Dispatcher` without any handlers registered.
+  #[inline]
+  pub fn new() -> Self {
+    Self {
+      on_bar: None,
+      on_quote: None,
+      on_trade: None,
+      on_error: None,
+    }
+  }
+
+  /// Register the handler to invoke for each received bar.
+  pub fn on_bar<F, R>(mut self, f: F) -> Self
+  where
+    F: FnMut(B) -> R + Send + 'static,
+    R: Future<Output = ()> + Send + 'static,
+  {
+    self.on_bar = Some(handler(f));
+    self
+  }
+
+  /// Register the handler to invoke for each received quote.
+  pub fn on_quote<F, R>(mut self, f: F) -> Self
+  where
+    F: FnMut(Q) -> R + Send + 'static,
+    R: Future<Output = ()> + Send + 'static,
+  {
+    self.on_quote = Some(handler(f));
+    self
+  }
+
+  /// Register the handler to invoke for each received trade.
+  pub fn on_trade<F, R>(mut self, f: F) -> Self
+  where
+    F: FnMut(T) -> R + Send + 'static,
+    R: Future<Output = ()> + Send + 'static,
+  {
+    self.on_trade = Some(handler(f));
+    self
+  }
+
+  /// Register the handler to invoke for each message that failed to
+  /// decode.
+  ///
+  /// If no such handler is registered, decoding errors terminate
+  /// [`run`][Self::run].
+  pub fn on_error<F, R>(mut self, f: F) -> Self
+  where
+    F: FnMut(JsonError) -> R + Send + 'static,
+    R: Future<Output = ()> + Send + 'static,
+  {
+    self.on_error = Some(handler(f));
+    self
+  }
+
+  /// Consume the provided stream, dispatching each item to the
+  /// respective handler.
+  ///
+  /// Handlers are invoked sequentially and in stream order, i.e., the
+  /// next item is only retrieved once the previous handler has
+  /// completed. This function returns once the stream is exhausted or
+  /// an error is encountered.
+  pub async fn run<S>(mut self, mut stream: S) -> Result<(), Error>
+  where
+    S: Stream<Item = Result<Result<Data<B, Q, T>, JsonError>, WebSocketError>> + Unpin,
+  {
+    while let Some(result) = stream.next().await {
+      match result.map_err(Error::WebSocket)? {
+        Ok(Data::Bar(bar)) => {
+          if let Some(on_bar) = &mut self.on_bar {
+            let () = on_bar(bar).await;
+          }
+        },
+        Ok(Data::Quote(quote)) => {
+          if let Some(on_quote) = &mut self.on_quote {
+            let () = on_quote(quote).await;
+          }
+        },
+        Ok(Data::Trade(trade)) => {
+          if let Some(on_trade) = &mut self.on_trade {
+            let () = on_trade(trade).await;
+          }
+        },
+        // Other kinds of data are not dispatched.
+        Ok(_) => (),
+        Err(err) => match &mut self.on_error {
+          Some(on_error) => {
+            let () = on_error(err).await;
+          },
+          None => return Err(Error::Json(err)),
+        },
+      }
+    }
+    Ok(())
+  }
+}
+
+impl<B, Q, T> Default for Dispatcher<B, Q, T> {
+  #[inline]
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<B, Q, T> Debug for Dispatcher<B, Q, T> {
+  fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+    f.debug_struct("Dispatcher")
+      .field("on_bar", &self.on_bar.is_some())
+      .field("on_quote", &self.on_quote.is_some())
+      .field("on_trade", &self.on_trade.is_some())
+      .field("on_error", &self.on_error.is_some())
+      .finish()
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use std::sync::Arc;
+  use std::sync::Mutex;
+
+  use futures::stream::iter;
+
+  use serde_json::from_str as json_from_str;
+
+  use test_log::test;
+
+
+  /// Create a JSON decoding error.
+  fn json_error() -> JsonError {
+    json_from_str::<u32>("foobar").unwrap_err()
+  }
+
+  /// Check that items are dispatched to the respective handlers, in
+  /// order.
+  #[test(tokio::test)]
+  async fn dispatch_items() {
+    let items = vec![
+      Ok(Ok(Data::Bar(1))),
+      Ok(Ok(Data::Quote(2))),
+      Ok(Ok(Data::Trade(3))),
+      Ok(Err(json_error())),
+      Ok(Ok(Data::Bar(4))),
+    ];
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let bars = seen.clone();
+    let quotes = seen.clone();
+    let errors = seen.clone();
+
+    let () = Dispatcher::<u32, u32, u32>::new()
+      .on_bar(move |bar| {
+        let bars = bars.clone();
+        async move { bars.lock().unwrap().push(format!("bar {bar}")) }
+      })
+      .on_quote(move |quote| {
+        let quotes = quotes.clone();
+        async move { quotes.lock().unwrap().push(format!("quote {quote}")) }
+      })
+      .on_error(move |_err| {
+        let errors = errors.clone();
+        async move { errors.lock().unwrap().push("error".to_string()) }
+      })
+      .run(iter(items))
+      .await
+      .unwrap();
+
+    // Trades have no handler registered and are dropped.
+    let seen = seen.lock().unwrap();
+    assert_eq!(*seen, ["bar 1", "quote 2", "error", "bar 4"]);
+  }
+
+  /// Check that decoding errors terminate the dispatcher if no error
+  /// handler is registered.
+  #[test(tokio::test)]
+  async fn dispatch_unhandled_error() {
+    let items = vec![Ok(Err(json_error())), Ok(Ok(Data::Bar(1)))];
+
+    let err = Dispatcher::<u32, u32, u32>::new()
+      .on_bar(|_bar| async { panic!("bar handler invoked unexpectedly") })
+      .run(iter(items))
+      .await
+      .unwrap_err();
+
+    match err {
+      Error::Json(..) => (),
+      e => panic!("received unexpected error: {e}"),
+    }
+  }
+}
@@ -20,4 +20,14 @@ pub enum Feed {
   /// This feed is only usable with the unlimited market data plan.
   #[serde(rename = "sip")]
   SIP,
+  /// Use the consolidated SIP data as the data source, delayed by 15
+  /// minutes.
+  ///
+  /// This feed is available unconditionally and allows users of the
+  /// free plan to work with consolidated data.
+  #[serde(rename = "delayed_sip")]
+  DelayedSip,
+  /// Use over-the-counter (OTC) exchanges as the data source.
+  #[serde(rename = "otc")]
+  Otc,
 }
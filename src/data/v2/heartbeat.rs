@@ -0,0 +1,219 @@
+// Copyright (C) 2024 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::pin::Pin;
+use std::time::Duration;
+
+use chrono::DateTime;
+use chrono::Utc;
+
+use futures::stream::FusedStream;
+use futures::task::Context;
+use futures::task::Poll;
+use futures::Future as _;
+use futures::Stream;
+use futures::StreamExt as _;
+
+use tokio::time::sleep;
+use tokio::time::Instant;
+use tokio::time::Sleep;
+
+
+/// A predicate checking whether the market is open at a given time.
+type MarketOpenFn = Box<dyn Fn(DateTime<Utc>) -> bool + Send + Sync>;
+
+
+/// An event as yielded by a [`Monitored`] stream.
+#[derive(Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Event<I> {
+  /// An item from the underlying stream.
+  Item(I),
+  /// No item has been received from the underlying stream for the
+  /// given duration, which is at least the configured window.
+  Stale(Duration),
+}
+
+
+/// A stream adapter monitoring the wrapped stream (typically a
+/// realtime market data stream) for liveness.
+///
+/// Whenever the wrapped stream does not yield an item within the
+/// configured window, a synthetic [`Event::Stale`] event is emitted.
+/// Staleness is reported at most once per window and only while the
+/// market is considered open, as no data may legitimately arrive
+/// otherwise.
+///
+/// Note that market data subscription changes only resolve while the
+/// wrapped stream is being polled. The wrapped stream can be
+/// accessed for this purpose using [`get_mut`][Monitored::get_mut].
+#[must_use = "streams do nothing unless polled"]
+pub struct Monitored<S> {
+  /// The wrapped stream.
+  inner: S,
+  /// The time window after which the stream is considered stale.
+  window: Duration,
+  /// The time at which the last item was received.
+  last: Instant,
+  /// The timer firing once the stream turns stale.
+  timer: Pin<Box<Sleep>>,
+  /// A predicate checking whether the market is open.
+  market_open: Option<MarketOpenFn>,
+}
+
+impl<S> Monitored<S> {
+  /// Wrap the provided stream, reporting staleness after `window`
+  /// elapsed without any items being received.
+  pub fn new(inner: S, window: Duration) -> Self {
+    Self {
+      inner,
+      window,
+      last: Instant::now(),
+      timer: Box::pin(sleep(window)),
+      market_open: None,
+    }
+  }
+
+  /// Only report staleness while the provided predicate indicates that
+  /// the market is open.
+  ///
+  /// By default, the market is considered open at all times.
+  pub fn market_open<F>(mut self, f: F) -> Self
+  where
+    F: Fn(DateTime<Utc>) -> bool + Send + Sync + 'static,
+  {
+    self.market_open = Some(Box::new(f));
+    self
+  }
+
+  /// Retrieve a mutable reference to the wrapped stream.
+  #[inline]
+  pub fn get_mut(&mut self) -> &mut S {
+    &mut self.inner
+  }
+
+  /// Unwrap the wrapped stream.
+  #[inline]
+  pub fn into_inner(self) -> S {
+    self.inner
+  }
+}
+
+impl<S> Stream for Monitored<S>
+where
+  S: Stream + Unpin,
+{
+  type Item = Event<S::Item>;
+
+  fn poll_next(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+    let this = &mut *self;
+
+    match this.inner.poll_next_unpin(ctx) {
+      Poll::Ready(Some(item)) => {
+        this.last = Instant::now();
+        let () = this.timer.as_mut().reset(this.last + this.window);
+        return Poll::Ready(Some(Event::Item(item)))
+      },
+      Poll::Ready(None) => return Poll::Ready(None),
+      Poll::Pending => (),
+    }
+
+    loop {
+      match this.timer.as_mut().poll(ctx) {
+        Poll::Ready(()) => {
+          let () = this.timer.as_mut().reset(Instant::now() + this.window);
+          let open = this
+            .market_open
+            .as_ref()
+            .map(|market_open| market_open(Utc::now()))
+            .unwrap_or(true);
+
+          if open {
+            break Poll::Ready(Some(Event::Stale(this.last.elapsed())))
+          }
+          // Poll the timer again, to make sure that we get woken up
+          // once it fires the next time.
+        },
+        Poll::Pending => break Poll::Pending,
+      }
+    }
+  }
+}
+
+impl<S> FusedStream for Monitored<S>
+where
+  S: FusedStream + Unpin,
+{
+  #[inline]
+  fn is_terminated(&self) -> bool {
+    self.inner.is_terminated()
+  }
+}
+
+impl<S> Debug for Monitored<S>
+where
+  S: Debug,
+{
+  fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+    f.debug_struct("Monitored")
+      .field("inner", &self.inner)
+      .field("window", &self.window)
+      .field("last", &self.last)
+      .field("market_open", &self.market_open.is_some())
+      .finish()
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use futures::channel::mpsc::unbounded;
+  use futures::stream::pending;
+
+  use test_log::test;
+
+  use tokio::time::timeout;
+
+
+  /// The window used in tests.
+  const WINDOW: Duration = Duration::from_millis(20);
+
+
+  /// Check that items are forwarded and staleness is reported once no
+  /// items arrive anymore.
+  #[test(tokio::test)]
+  async fn report_stale_stream() {
+    let (send, recv) = unbounded();
+    let mut stream = Monitored::new(recv, WINDOW);
+
+    let () = send.unbounded_send(42).unwrap();
+    assert_eq!(stream.next().await, Some(Event::Item(42)));
+
+    match stream.next().await {
+      Some(Event::Stale(duration)) => assert!(duration >= WINDOW, "{duration:?}"),
+      event => panic!("received unexpected event: {event:?}"),
+    }
+
+    // Staleness is reported repeatedly, until an item arrives.
+    assert!(matches!(stream.next().await, Some(Event::Stale(..))));
+    let () = send.unbounded_send(43).unwrap();
+    assert_eq!(stream.next().await, Some(Event::Item(43)));
+
+    drop(send);
+    assert_eq!(stream.next().await, None);
+  }
+
+  /// Check that no staleness is reported while the market is closed.
+  #[test(tokio::test)]
+  async fn ignore_stale_stream_on_closed_market() {
+    let mut stream = Monitored::new(pending::<()>(), WINDOW).market_open(|_time| false);
+
+    let result = timeout(WINDOW * 5, stream.next()).await;
+    assert!(result.is_err(), "{result:?}");
+  }
+}
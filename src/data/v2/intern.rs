@@ -0,0 +1,189 @@
+// Copyright (C) 2024 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::borrow::Borrow;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::ops::Deref;
+use std::sync::Arc;
+
+use serde::de::Deserializer;
+use serde::de::Error;
+use serde::de::Visitor;
+use serde::Deserialize;
+use serde::Serialize;
+use serde::Serializer;
+
+
+/// The maximum number of strings interned per thread.
+///
+/// Strings encountered once this limit is reached are still deduplicated
+/// against the already interned ones, but not interned themselves.
+const MAX_INTERNED: usize = 1 << 16;
+
+
+thread_local! {
+  /// The per-thread set of interned strings.
+  static INTERNED: RefCell<HashSet<Arc<str>>> = RefCell::new(HashSet::new());
+}
+
+
+/// An immutable, interned string.
+///
+/// Cloning an `Interned` object is cheap and deserializing one does
+/// not allocate if the string was encountered before on the same
+/// thread. As such, it lends itself to representing the symbols,
+/// exchange codes, and conditions of realtime market data, which are
+/// drawn from a limited set of values, at high message rates. E.g.,
+/// ```rust
+/// # use apca::data::v2::intern::Interned;
+/// # use apca::data::v2::stream::Bar;
+/// # use apca::data::v2::stream::Quote;
+/// # use apca::data::v2::stream::RealtimeData;
+/// # use apca::data::v2::stream::Trade;
+/// # use apca::data::v2::stream::SIP;
+/// type Data = RealtimeData<SIP, Bar<Interned>, Quote<Interned>, Trade<Interned>>;
+/// ```
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Interned(Arc<str>);
+
+impl Interned {
+  /// Retrieve the interned version of the provided string.
+  pub fn new(string: &str) -> Self {
+    INTERNED.with(|interned| {
+      let mut interned = interned.borrow_mut();
+      if let Some(string) = interned.get(string) {
+        return Self(string.clone())
+      }
+
+      let string = Arc::<str>::from(string);
+      if interned.len() < MAX_INTERNED {
+        let _inserted = interned.insert(string.clone());
+      }
+      Self(string)
+    })
+  }
+
+  /// Retrieve the string slice.
+  #[inline]
+  pub fn as_str(&self) -> &str {
+    &self.0
+  }
+}
+
+impl Deref for Interned {
+  type Target = str;
+
+  #[inline]
+  fn deref(&self) -> &Self::Target {
+    &self.0
+  }
+}
+
+impl AsRef<str> for Interned {
+  #[inline]
+  fn as_ref(&self) -> &str {
+    &self.0
+  }
+}
+
+impl Borrow<str> for Interned {
+  #[inline]
+  fn borrow(&self) -> &str {
+    &self.0
+  }
+}
+
+impl Display for Interned {
+  #[inline]
+  fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+    fmt.write_str(&self.0)
+  }
+}
+
+impl From<&str> for Interned {
+  #[inline]
+  fn from(string: &str) -> Self {
+    Self::new(string)
+  }
+}
+
+impl PartialEq<str> for Interned {
+  #[inline]
+  fn eq(&self, other: &str) -> bool {
+    self.as_str() == other
+  }
+}
+
+impl PartialEq<&str> for Interned {
+  #[inline]
+  fn eq(&self, other: &&str) -> bool {
+    self.as_str() == *other
+  }
+}
+
+impl<'de> Deserialize<'de> for Interned {
+  fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+  where
+    D: Deserializer<'de>,
+  {
+    struct InternedVisitor;
+
+    impl Visitor<'_> for InternedVisitor {
+      type Value = Interned;
+
+      fn expecting(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+        fmt.write_str("a string")
+      }
+
+      #[inline]
+      fn visit_str<E>(self, string: &str) -> Result<Self::Value, E>
+      where
+        E: Error,
+      {
+        Ok(Interned::new(string))
+      }
+    }
+
+    deserializer.deserialize_str(InternedVisitor)
+  }
+}
+
+impl Serialize for Interned {
+  #[inline]
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: Serializer,
+  {
+    serializer.serialize_str(&self.0)
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use serde_json::from_str as from_json;
+  use serde_json::to_string as to_json;
+
+  use test_log::test;
+
+
+  /// Check that equal strings share the same allocation.
+  #[test]
+  fn intern_strings() {
+    let aapl = Interned::new("AAPL");
+    let other = from_json::<Interned>(r#""AAPL""#).unwrap();
+    assert_eq!(aapl, other);
+    assert!(Arc::ptr_eq(&aapl.0, &other.0));
+
+    let spy = Interned::from("SPY");
+    assert_ne!(aapl, spy);
+    assert_eq!(spy, "SPY");
+    assert_eq!(to_json(&spy).unwrap(), r#""SPY""#);
+  }
+}
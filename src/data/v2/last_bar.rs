@@ -0,0 +1,327 @@
+// Copyright (C) 2024 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::from_slice as from_json;
+use serde_urlencoded::to_string as to_query;
+
+use crate::data::v2::bars::Bar;
+use crate::data::v2::Feed;
+use crate::util::string_slice_to_str;
+use crate::Str;
+
+
+/// A GET request to be made to the /v2/stocks/{symbol}/bars/latest
+/// endpoint.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct GetReq {
+  /// The symbol to retrieve the last bar for.
+  #[serde(skip)]
+  pub symbol: String,
+  /// The data feed to use.
+  #[serde(rename = "feed")]
+  pub feed: Option<Feed>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// A helper for initializing [`GetReq`] objects.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[allow(missing_copy_implementations)]
+pub struct GetReqInit {
+  /// See `GetReq::feed`.
+  pub feed: Option<Feed>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+Setters! {
+  GetReqInit {
+    feed: Option<Feed>,
+  }
+}
+
+impl GetReqInit {
+  /// Create a [`GetReq`] from a `GetReqInit`.
+  #[inline]
+  pub fn init<S>(&self, symbol: S) -> GetReq
+  where
+    S: Into<String>,
+  {
+    GetReq {
+      symbol: symbol.into(),
+      feed: self.feed,
+      _non_exhaustive: (),
+    }
+  }
+}
+
+
+EndpointNoParse! {
+  /// The representation of a GET request to the
+  /// /v2/stocks/{symbol}/bars/latest endpoint.
+  pub Get(GetReq),
+  Api => Data,
+  Ok => Bar, [
+    /// The last bar was retrieved successfully.
+    /* 200 */ OK,
+  ],
+  Err => GetError, [
+    /// The provided symbol was invalid or not found or the data feed is
+    /// not supported.
+    /* 400 */ BAD_REQUEST => InvalidInput,
+  ]
+
+  fn path(input: &Self::Input) -> Str {
+    format!("/v2/stocks/{}/bars/latest", input.symbol).into()
+  }
+
+  fn query(input: &Self::Input) -> Result<Option<Str>, Self::ConversionError> {
+    Ok(Some(to_query(input)?.into()))
+  }
+
+  fn parse(body: &[u8]) -> Result<Self::Output, Self::ConversionError> {
+    /// A helper object for parsing the response to a `Get` request.
+    #[derive(Deserialize)]
+    struct Response {
+      /// The most recent bar.
+      bar: Bar,
+    }
+
+    from_json::<Response>(body)
+      .map(|response| response.bar)
+      .map_err(Self::ConversionError::from)
+  }
+
+  fn parse_err(body: &[u8]) -> Result<Self::ApiError, Vec<u8>> {
+    from_json::<Self::ApiError>(body).map_err(|_| body.to_vec())
+  }
+}
+
+
+/// A GET request to be made to the /v2/stocks/bars/latest endpoint.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct ListReq {
+  /// The symbols to retrieve the last bar for.
+  #[serde(rename = "symbols", serialize_with = "string_slice_to_str")]
+  pub symbols: Vec<String>,
+  /// The data feed to use.
+  #[serde(rename = "feed")]
+  pub feed: Option<Feed>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+
+/// A helper for initializing [`ListReq`] objects.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[allow(missing_copy_implementations)]
+pub struct ListReqInit {
+  /// See `ListReq::feed`.
+  pub feed: Option<Feed>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+Setters! {
+  ListReqInit {
+    feed: Option<Feed>,
+  }
+}
+
+impl ListReqInit {
+  /// Create a [`ListReq`] from a `ListReqInit`.
+  #[inline]
+  pub fn init<I, S>(&self, symbols: I) -> ListReq
+  where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+  {
+    ListReq {
+      symbols: symbols.into_iter().map(S::into).collect(),
+      feed: self.feed,
+      _non_exhaustive: (),
+    }
+  }
+}
+
+
+EndpointNoParse! {
+  /// The representation of a GET request to the
+  /// /v2/stocks/bars/latest endpoint.
+  pub List(ListReq),
+  Api => Data,
+  Ok => Vec<(String, Bar)>, [
+    /// The last bars were retrieved successfully.
+    /* 200 */ OK,
+  ],
+  Err => ListError, [
+    /// The provided symbol was invalid or not found or the data feed is
+    /// not supported.
+    /* 400 */ BAD_REQUEST => InvalidInput,
+  ]
+
+  fn path(_input: &Self::Input) -> Str {
+    "/v2/stocks/bars/latest".into()
+  }
+
+  fn query(input: &Self::Input) -> Result<Option<Str>, Self::ConversionError> {
+    Ok(Some(to_query(input)?.into()))
+  }
+
+  fn parse(body: &[u8]) -> Result<Self::Output, Self::ConversionError> {
+    /// A helper object for parsing the response to a `List` request.
+    #[derive(Deserialize)]
+    struct Response {
+      /// A mapping from symbols to bar objects.
+      // We use a `BTreeMap` here to have a consistent ordering of
+      // bars.
+      bars: BTreeMap<String, Bar>,
+    }
+
+    from_json::<Response>(body)
+      .map(|response| response.bars.into_iter().collect())
+      .map_err(Self::ConversionError::from)
+  }
+
+  fn parse_err(body: &[u8]) -> Result<Self::ApiError, Vec<u8>> {
+    from_json::<Self::ApiError>(body).map_err(|_| body.to_vec())
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use chrono::DateTime;
+  use chrono::Duration;
+  use chrono::Utc;
+
+  use http_endpoint::Endpoint as _;
+
+  use num_decimal::Num;
+
+  use test_log::test;
+
+  use crate::api_info::ApiInfo;
+  use crate::Client;
+  use crate::RequestError;
+
+
+  /// Check that we can parse the reference bar from the documentation.
+  #[test]
+  fn parse_reference_bar() {
+    let response = br#"{
+      "symbol": "AAPL",
+      "bar": {
+        "t": "2022-04-12T17:26:00Z",
+        "o": 168.02,
+        "h": 168.11,
+        "l": 167.98,
+        "c": 168.03,
+        "v": 31663,
+        "n": 403,
+        "vw": 168.046145
+      }
+    }"#;
+
+    let bar = Get::parse(response).unwrap();
+    assert_eq!(
+      bar.time,
+      DateTime::parse_from_rfc3339("2022-04-12T17:26:00Z").unwrap()
+    );
+    assert_eq!(bar.open, Num::new(16802, 100));
+    assert_eq!(bar.close, Num::new(16803, 100));
+    assert_eq!(bar.volume, 31663);
+    assert_eq!(bar.trade_count, Some(403));
+  }
+
+  /// Check that we can parse the reference bars from the
+  /// documentation.
+  #[test]
+  fn parse_reference_bars() {
+    let response = br#"{
+      "bars": {
+        "TSLA": {
+          "t": "2022-04-12T17:26:00Z",
+          "o": 1020.01,
+          "h": 1021.5,
+          "l": 1019.5,
+          "c": 1020.5,
+          "v": 5130,
+          "n": 202,
+          "vw": 1020.43
+        },
+        "AAPL": {
+          "t": "2022-04-12T17:26:00Z",
+          "o": 168.02,
+          "h": 168.11,
+          "l": 167.98,
+          "c": 168.03,
+          "v": 31663,
+          "n": 403,
+          "vw": 168.046145
+        }
+      }
+    }"#;
+
+    let bars = List::parse(response).unwrap();
+    assert_eq!(bars.len(), 2);
+    assert_eq!(bars[0].0, "AAPL");
+    assert_eq!(bars[0].1.high, Num::new(16811, 100));
+    assert_eq!(bars[1].0, "TSLA");
+    assert_eq!(bars[1].1.low, Num::new(10195, 10));
+  }
+
+  /// Verify that we can retrieve the last bar for an asset.
+  #[test(tokio::test)]
+  async fn request_last_bar() {
+    let api_info = ApiInfo::from_env().unwrap();
+    let client = Client::new(api_info);
+
+    let req = GetReqInit::default().init("SPY");
+    let bar = client.issue::<Get>(&req).await.unwrap();
+    // Just as a rough sanity check, we require that the reported time
+    // is some time after two weeks before today.
+    assert!(bar.time >= Utc::now() - Duration::try_weeks(2).unwrap());
+  }
+
+  /// Retrieve the last bars for multiple symbols at once.
+  #[test(tokio::test)]
+  async fn request_last_bars() {
+    let api_info = ApiInfo::from_env().unwrap();
+    let client = Client::new(api_info);
+
+    let req = ListReqInit::default().init(["SPY", "AAPL"]);
+    let bars = client.issue::<List>(&req).await.unwrap();
+    assert_eq!(bars.len(), 2);
+    assert_eq!(bars[0].0, "AAPL");
+    assert_eq!(bars[1].0, "SPY");
+  }
+
+  /// Verify that we error out as expected when attempting to retrieve
+  /// the last bar for an invalid symbol.
+  #[test(tokio::test)]
+  async fn invalid_symbol() {
+    let api_info = ApiInfo::from_env().unwrap();
+    let client = Client::new(api_info);
+
+    let req = GetReqInit::default().init("ABC123");
+    let err = client.issue::<Get>(&req).await.unwrap_err();
+    match err {
+      RequestError::Endpoint(GetError::InvalidInput(_)) => (),
+      _ => panic!("Received unexpected error: {err:?}"),
+    };
+  }
+}
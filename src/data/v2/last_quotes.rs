@@ -14,7 +14,6 @@ use serde_json::from_slice as from_json;
 use serde_urlencoded::to_string as to_query;
 
 use crate::data::v2::Feed;
-use crate::data::DATA_BASE_URL;
 use crate::util::string_slice_to_str;
 use crate::Str;
 
@@ -46,10 +45,16 @@ pub struct GetReqInit {
   pub _non_exhaustive: (),
 }
 
+Setters! {
+  GetReqInit {
+    feed: Option<Feed>,
+  }
+}
+
 impl GetReqInit {
   /// Create a [`GetReq`] from a `GetReqInit`.
   #[inline]
-  pub fn init<I, S>(self, symbols: I) -> GetReq
+  pub fn init<I, S>(&self, symbols: I) -> GetReq
   where
     I: IntoIterator<Item = S>,
     S: Into<String>,
@@ -64,21 +69,24 @@ impl GetReqInit {
 
 
 /// A quote as returned by the /v2/stocks/quotes/latest endpoint.
+///
+/// Prices are represented as `P`, which defaults to the exact [`Num`]
+/// type.
 // TODO: Not all fields are hooked up.
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
-pub struct Quote {
+pub struct Quote<P = Num> {
   /// The time stamp of this quote.
   #[serde(rename = "t")]
   pub time: DateTime<Utc>,
   /// The ask price.
   #[serde(rename = "ap")]
-  pub ask_price: Num,
+  pub ask_price: P,
   /// The ask size.
   #[serde(rename = "as")]
   pub ask_size: u64,
   /// The bid price.
   #[serde(rename = "bp")]
-  pub bid_price: Num,
+  pub bid_price: P,
   /// The bid size.
   #[serde(rename = "bs")]
   pub bid_size: u64,
@@ -93,6 +101,7 @@ EndpointNoParse! {
   /// The representation of a GET request to the
   /// /v2/stocks/quotes/latest endpoint.
   pub Get(GetReq),
+  Api => Data,
   Ok => Vec<(String, Quote)>, [
     /// The last quotes were retrieved successfully.
     /* 200 */ OK,
@@ -103,10 +112,6 @@ EndpointNoParse! {
     /* 400 */ BAD_REQUEST => InvalidInput,
   ]
 
-  fn base_url() -> Option<Str> {
-    Some(DATA_BASE_URL.into())
-  }
-
   fn path(_input: &Self::Input) -> Str {
     "/v2/stocks/quotes/latest".into()
   }
@@ -162,6 +167,18 @@ mod tests {
   use crate::RequestError;
 
 
+  /// Check that the selected feed is reflected in the request's query.
+  #[test]
+  fn serialize_feed() {
+    let request = GetReqInit::new()
+      .feed(Feed::DelayedSip)
+      .init(["SPY", "MSFT"]);
+    assert_eq!(
+      Get::query(&request).unwrap().as_deref(),
+      Some("symbols=SPY%2CMSFT&feed=delayed_sip")
+    );
+  }
+
   /// Check that we can parse the reference quotes from the
   /// documentation.
   #[test]
@@ -0,0 +1,173 @@
+// Copyright (C) 2024 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+use serde::Serialize;
+use serde_urlencoded::to_string as to_query;
+
+use crate::data::v2::conditions::Tape;
+use crate::Str;
+
+
+/// A mapping from codes to their human readable names.
+pub type Names = BTreeMap<String, String>;
+
+
+Endpoint! {
+  /// The representation of a GET request to the
+  /// /v2/stocks/meta/exchanges endpoint.
+  pub GetExchanges(()),
+  Api => Data,
+  Ok => Names, [
+    /// The exchange codes were retrieved successfully.
+    /* 200 */ OK,
+  ],
+  Err => GetExchangesError, []
+
+  fn path(_input: &Self::Input) -> Str {
+    "/v2/stocks/meta/exchanges".into()
+  }
+}
+
+
+/// The kind of tick to retrieve condition codes for.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[non_exhaustive]
+pub enum TickType {
+  /// Trade conditions.
+  #[serde(rename = "trade")]
+  Trade,
+  /// Quote conditions.
+  #[serde(rename = "quote")]
+  Quote,
+}
+
+impl AsRef<str> for TickType {
+  #[inline]
+  fn as_ref(&self) -> &'static str {
+    match *self {
+      Self::Trade => "trade",
+      Self::Quote => "quote",
+    }
+  }
+}
+
+
+/// A GET request to be made to the
+/// /v2/stocks/meta/conditions/{ticktype} endpoint.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+pub struct GetConditionsReq {
+  /// The kind of tick to retrieve condition codes for.
+  #[serde(skip)]
+  pub tick_type: TickType,
+  /// The tape to retrieve condition codes for.
+  #[serde(rename = "tape")]
+  pub tape: Tape,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+impl GetConditionsReq {
+  /// Create a new `GetConditionsReq` for the given tick type and tape.
+  #[inline]
+  pub fn new(tick_type: TickType, tape: Tape) -> Self {
+    Self {
+      tick_type,
+      tape,
+      _non_exhaustive: (),
+    }
+  }
+}
+
+
+Endpoint! {
+  /// The representation of a GET request to the
+  /// /v2/stocks/meta/conditions/{ticktype} endpoint.
+  pub GetConditions(GetConditionsReq),
+  Api => Data,
+  Ok => Names, [
+    /// The condition codes were retrieved successfully.
+    /* 200 */ OK,
+  ],
+  Err => GetConditionsError, [
+    /// The tick type or tape was invalid.
+    /* 400 */ BAD_REQUEST => InvalidInput,
+  ]
+
+  fn path(input: &Self::Input) -> Str {
+    format!("/v2/stocks/meta/conditions/{}", input.tick_type.as_ref()).into()
+  }
+
+  fn query(input: &Self::Input) -> Result<Option<Str>, Self::ConversionError> {
+    Ok(Some(to_query(input)?.into()))
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use http_endpoint::Endpoint as _;
+
+  use test_log::test;
+
+  use crate::api_info::ApiInfo;
+  use crate::Client;
+
+
+  /// Check that we can parse the reference exchanges from the
+  /// documentation.
+  #[test]
+  fn parse_reference_exchanges() {
+    let response = br#"{
+      "A": "NYSE American (AMEX)",
+      "B": "NASDAQ OMX BX",
+      "V": "IEX"
+    }"#;
+
+    let exchanges = GetExchanges::parse(response).unwrap();
+    assert_eq!(exchanges.len(), 3);
+    assert_eq!(exchanges["V"], "IEX");
+  }
+
+  /// Check that we emit the expected path and query for a conditions
+  /// request.
+  #[test]
+  fn conditions_request() {
+    let request = GetConditionsReq::new(TickType::Quote, Tape::C);
+    assert_eq!(
+      GetConditions::path(&request),
+      "/v2/stocks/meta/conditions/quote"
+    );
+    assert_eq!(
+      GetConditions::query(&request).unwrap().as_deref(),
+      Some("tape=C")
+    );
+  }
+
+  /// Check that we can retrieve the exchange codes.
+  #[test(tokio::test)]
+  async fn request_exchanges() {
+    let api_info = ApiInfo::from_env().unwrap();
+    let client = Client::new(api_info);
+
+    let exchanges = client.issue::<GetExchanges>(&()).await.unwrap();
+    assert!(exchanges.contains_key("V"), "{exchanges:?}");
+  }
+
+  /// Check that we can retrieve the trade conditions of a tape.
+  #[test(tokio::test)]
+  async fn request_trade_conditions() {
+    let api_info = ApiInfo::from_env().unwrap();
+    let client = Client::new(api_info);
+
+    let request = GetConditionsReq::new(TickType::Trade, Tape::A);
+    let conditions = client.issue::<GetConditions>(&request).await.unwrap();
+    assert!(conditions.contains_key("@"), "{conditions:?}");
+  }
+}
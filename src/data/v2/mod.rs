@@ -2,12 +2,35 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 mod feed;
+mod msgpack;
+mod range;
+mod sort;
 mod unfold;
 
+/// Definitions for retrieval of opening and closing auction data.
+pub mod auctions;
 /// Definitions for retrieval of market data bars.
 pub mod bars;
+/// Functionality for retrieving data for multiple symbols concurrently.
+pub mod batch;
+/// Functionality for buffering realtime market data with bounded
+/// memory usage.
+pub mod buffer;
+/// Definitions of trade and quote conditions and tapes.
+pub mod conditions;
+/// Functionality for dispatching realtime market data to handlers.
+pub mod dispatch;
+/// Functionality for monitoring realtime market data streams for
+/// liveness.
+pub mod heartbeat;
+/// Definitions of interned strings for use with realtime market data.
+pub mod intern;
+/// Functionality for retrieval of most recent bars.
+pub mod last_bar;
 /// Functionality for retrieval of most recent quotes.
 pub mod last_quotes;
+/// Definitions for retrieval of exchange and condition code metadata.
+pub mod meta;
 /// Functionality for retrieving historic quotes.
 pub mod quotes;
 /// Definitions for real-time streaming of market data.
@@ -16,3 +39,4 @@ pub mod stream;
 pub mod trades;
 
 pub use feed::Feed;
+pub use sort::Sort;
@@ -0,0 +1,610 @@
+// Copyright (C) 2024 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Support for consuming the realtime market data stream in
+//! MessagePack format.
+//!
+//! Data are deserialized directly into the target types using
+//! `rmp-serde`. Because these types are shared with the JSON wire
+//! format, a thin adapter presents the few MessagePack specific
+//! representations in the form the `Deserialize` implementations
+//! expect: timestamps, which are sent as extension values, are handed
+//! out as RFC 3339 strings and single precision floats are widened
+//! based on their shortest decimal representation.
+
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+
+use chrono::SecondsFormat;
+use chrono::TimeZone as _;
+use chrono::Utc;
+
+use rmp_serde::decode::Error as DecodeError;
+use rmp_serde::Deserializer as RmpDeserializer;
+
+use serde::de::DeserializeOwned;
+use serde::de::DeserializeSeed;
+use serde::de::EnumAccess;
+use serde::de::Error as _;
+use serde::de::IgnoredAny;
+use serde::de::MapAccess;
+use serde::de::SeqAccess;
+use serde::de::VariantAccess;
+use serde::de::Visitor;
+use serde::Deserialize as _;
+use serde::Deserializer;
+
+
+/// The extension type used for timestamps.
+const TIMESTAMP_EXT: i8 = -1;
+
+/// The maximum nesting depth of data we are willing to decode.
+///
+/// Market data messages are nested only a few levels deep. The limit
+/// guards against stack exhaustion caused by malicious or corrupted
+/// data.
+const MAX_DEPTH: usize = 32;
+
+
+/// Convert the payload of a timestamp extension value into an RFC 3339
+/// string.
+fn timestamp<E>(data: &[u8]) -> Result<String, E>
+where
+  E: serde::de::Error,
+{
+  let (secs, nanos) = match *data {
+    [a, b, c, d] => (i64::from(u32::from_be_bytes([a, b, c, d])), 0),
+    [a, b, c, d, e, f, g, h] => {
+      let value = u64::from_be_bytes([a, b, c, d, e, f, g, h]);
+      ((value & 0x3_ffff_ffff) as i64, (value >> 34) as u32)
+    },
+    [a, b, c, d, ref rest @ ..] if rest.len() == 8 => {
+      let mut secs = [0; 8];
+      let () = secs.copy_from_slice(rest);
+      (i64::from_be_bytes(secs), u32::from_be_bytes([a, b, c, d]))
+    },
+    _ => return Err(E::custom("encountered invalid timestamp")),
+  };
+
+  let time = Utc
+    .timestamp_opt(secs, nanos)
+    .single()
+    .ok_or_else(|| E::custom("encountered out of range timestamp"))?;
+  Ok(time.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+}
+
+
+/// A visitor for the tag and payload of an extension value.
+struct ExtVisitor;
+
+impl<'de> Visitor<'de> for ExtVisitor {
+  type Value = (i8, Vec<u8>);
+
+  fn expecting(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+    fmt.write_str("a MessagePack extension value")
+  }
+
+  fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+  where
+    A: SeqAccess<'de>,
+  {
+    /// A visitor for the payload of an extension value.
+    struct Payload;
+
+    impl<'de> Visitor<'de> for Payload {
+      type Value = Vec<u8>;
+
+      fn expecting(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+        fmt.write_str("extension payload")
+      }
+
+      fn visit_bytes<E>(self, bytes: &[u8]) -> Result<Self::Value, E> {
+        Ok(bytes.to_vec())
+      }
+    }
+
+    /// A seed for deserializing the payload of an extension value.
+    struct PayloadSeed;
+
+    impl<'de> DeserializeSeed<'de> for PayloadSeed {
+      type Value = Vec<u8>;
+
+      fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+      where
+        D: Deserializer<'de>,
+      {
+        deserializer.deserialize_bytes(Payload)
+      }
+    }
+
+    let type_ = seq
+      .next_element::<i8>()?
+      .ok_or_else(|| A::Error::custom("extension value is missing type"))?;
+    let data = seq
+      .next_element_seed(PayloadSeed)?
+      .ok_or_else(|| A::Error::custom("extension value is missing data"))?;
+    Ok((type_, data))
+  }
+}
+
+
+/// A `Deserializer` adapting the one provided by `rmp-serde` to the
+/// expectations of our data types.
+struct Adapter<D>(D);
+
+/// A `Visitor` adapting MessagePack specific values before handing
+/// them to the wrapped one.
+struct Adapt<V>(V);
+
+/// A `DeserializeSeed` deserializing from an [`Adapter`].
+struct AdaptSeed<S>(S);
+
+
+impl<'de, S> DeserializeSeed<'de> for AdaptSeed<S>
+where
+  S: DeserializeSeed<'de>,
+{
+  type Value = S::Value;
+
+  #[inline]
+  fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+  where
+    D: Deserializer<'de>,
+  {
+    self.0.deserialize(Adapter(deserializer))
+  }
+}
+
+
+impl<'de, A> SeqAccess<'de> for Adapt<A>
+where
+  A: SeqAccess<'de>,
+{
+  type Error = A::Error;
+
+  #[inline]
+  fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+  where
+    T: DeserializeSeed<'de>,
+  {
+    self.0.next_element_seed(AdaptSeed(seed))
+  }
+
+  #[inline]
+  fn size_hint(&self) -> Option<usize> {
+    self.0.size_hint()
+  }
+}
+
+
+impl<'de, A> MapAccess<'de> for Adapt<A>
+where
+  A: MapAccess<'de>,
+{
+  type Error = A::Error;
+
+  #[inline]
+  fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+  where
+    K: DeserializeSeed<'de>,
+  {
+    self.0.next_key_seed(AdaptSeed(seed))
+  }
+
+  #[inline]
+  fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+  where
+    V: DeserializeSeed<'de>,
+  {
+    self.0.next_value_seed(AdaptSeed(seed))
+  }
+
+  #[inline]
+  fn size_hint(&self) -> Option<usize> {
+    self.0.size_hint()
+  }
+}
+
+
+impl<'de, A> EnumAccess<'de> for Adapt<A>
+where
+  A: EnumAccess<'de>,
+{
+  type Error = A::Error;
+  type Variant = Adapt<A::Variant>;
+
+  #[inline]
+  fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+  where
+    V: DeserializeSeed<'de>,
+  {
+    self
+      .0
+      .variant_seed(AdaptSeed(seed))
+      .map(|(value, variant)| (value, Adapt(variant)))
+  }
+}
+
+
+impl<'de, A> VariantAccess<'de> for Adapt<A>
+where
+  A: VariantAccess<'de>,
+{
+  type Error = A::Error;
+
+  #[inline]
+  fn unit_variant(self) -> Result<(), Self::Error> {
+    self.0.unit_variant()
+  }
+
+  #[inline]
+  fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+  where
+    T: DeserializeSeed<'de>,
+  {
+    self.0.newtype_variant_seed(AdaptSeed(seed))
+  }
+
+  #[inline]
+  fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+  where
+    V: Visitor<'de>,
+  {
+    self.0.tuple_variant(len, Adapt(visitor))
+  }
+
+  #[inline]
+  fn struct_variant<V>(
+    self,
+    fields: &'static [&'static str],
+    visitor: V,
+  ) -> Result<V::Value, Self::Error>
+  where
+    V: Visitor<'de>,
+  {
+    self.0.struct_variant(fields, Adapt(visitor))
+  }
+}
+
+
+/// Forward `visit_*` methods to the wrapped visitor unchanged.
+macro_rules! forward_visit {
+  ($($method:ident($ty:ty),)*) => {
+    $(
+      #[inline]
+      fn $method<E>(self, value: $ty) -> Result<Self::Value, E>
+      where
+        E: serde::de::Error,
+      {
+        self.0.$method(value)
+      }
+    )*
+  };
+}
+
+
+impl<'de, V> Visitor<'de> for Adapt<V>
+where
+  V: Visitor<'de>,
+{
+  type Value = V::Value;
+
+  fn expecting(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+    self.0.expecting(fmt)
+  }
+
+  forward_visit! {
+    visit_bool(bool),
+    visit_i8(i8),
+    visit_i16(i16),
+    visit_i32(i32),
+    visit_i64(i64),
+    visit_i128(i128),
+    visit_u8(u8),
+    visit_u16(u16),
+    visit_u32(u32),
+    visit_u64(u64),
+    visit_u128(u128),
+    visit_f64(f64),
+    visit_char(char),
+    visit_str(&str),
+    visit_borrowed_str(&'de str),
+    visit_string(String),
+    visit_bytes(&[u8]),
+    visit_borrowed_bytes(&'de [u8]),
+    visit_byte_buf(Vec<u8>),
+  }
+
+  fn visit_f32<E>(self, value: f32) -> Result<Self::Value, E>
+  where
+    E: serde::de::Error,
+  {
+    // Widening the binary representation would turn, say, 1.1 into
+    // 1.100000023841858. Go through the shortest decimal
+    // representation instead, which is what JSON would have provided.
+    let value = value.to_string().parse::<f64>().map_err(E::custom)?;
+    self.0.visit_f64(value)
+  }
+
+  #[inline]
+  fn visit_none<E>(self) -> Result<Self::Value, E>
+  where
+    E: serde::de::Error,
+  {
+    self.0.visit_none()
+  }
+
+  #[inline]
+  fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+  where
+    D: Deserializer<'de>,
+  {
+    self.0.visit_some(Adapter(deserializer))
+  }
+
+  #[inline]
+  fn visit_unit<E>(self) -> Result<Self::Value, E>
+  where
+    E: serde::de::Error,
+  {
+    self.0.visit_unit()
+  }
+
+  fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+  where
+    D: Deserializer<'de>,
+  {
+    // Outside of explicitly requested newtype structs, `rmp-serde`
+    // reports extension values this way.
+    let (type_, data) = deserializer.deserialize_any(ExtVisitor)?;
+    if type_ != TIMESTAMP_EXT {
+      return Err(D::Error::custom(format!(
+        "encountered unsupported extension type: {type_}"
+      )))
+    }
+    self.0.visit_string(timestamp(&data)?)
+  }
+
+  #[inline]
+  fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
+  where
+    A: SeqAccess<'de>,
+  {
+    self.0.visit_seq(Adapt(seq))
+  }
+
+  #[inline]
+  fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+  where
+    A: MapAccess<'de>,
+  {
+    self.0.visit_map(Adapt(map))
+  }
+
+  #[inline]
+  fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+  where
+    A: EnumAccess<'de>,
+  {
+    self.0.visit_enum(Adapt(data))
+  }
+}
+
+
+/// A `Visitor` for explicitly requested newtype structs, whose content
+/// is deserialized from an [`Adapter`].
+struct AdaptNewtype<V>(V);
+
+impl<'de, V> Visitor<'de> for AdaptNewtype<V>
+where
+  V: Visitor<'de>,
+{
+  type Value = V::Value;
+
+  fn expecting(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+    self.0.expecting(fmt)
+  }
+
+  #[inline]
+  fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+  where
+    D: Deserializer<'de>,
+  {
+    self.0.visit_newtype_struct(Adapter(deserializer))
+  }
+}
+
+
+/// Forward `deserialize_*` methods to the wrapped deserializer, with
+/// the visitor adapted.
+macro_rules! forward_deserialize {
+  ($($method:ident($($arg:ident: $ty:ty),*),)*) => {
+    $(
+      #[inline]
+      fn $method<V>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, Self::Error>
+      where
+        V: Visitor<'de>,
+      {
+        self.0.$method($($arg,)* Adapt(visitor))
+      }
+    )*
+  };
+}
+
+
+impl<'de, D> Deserializer<'de> for Adapter<D>
+where
+  D: Deserializer<'de>,
+{
+  type Error = D::Error;
+
+  forward_deserialize! {
+    deserialize_any(),
+    deserialize_bool(),
+    deserialize_i8(),
+    deserialize_i16(),
+    deserialize_i32(),
+    deserialize_i64(),
+    deserialize_i128(),
+    deserialize_u8(),
+    deserialize_u16(),
+    deserialize_u32(),
+    deserialize_u64(),
+    deserialize_u128(),
+    deserialize_f32(),
+    deserialize_f64(),
+    deserialize_char(),
+    deserialize_str(),
+    deserialize_string(),
+    deserialize_bytes(),
+    deserialize_byte_buf(),
+    deserialize_option(),
+    deserialize_unit(),
+    deserialize_unit_struct(name: &'static str),
+    deserialize_seq(),
+    deserialize_tuple(len: usize),
+    deserialize_tuple_struct(name: &'static str, len: usize),
+    deserialize_map(),
+    deserialize_struct(name: &'static str, fields: &'static [&'static str]),
+    deserialize_enum(name: &'static str, variants: &'static [&'static str]),
+    deserialize_identifier(),
+    deserialize_ignored_any(),
+  }
+
+  #[inline]
+  fn deserialize_newtype_struct<V>(
+    self,
+    name: &'static str,
+    visitor: V,
+  ) -> Result<V::Value, Self::Error>
+  where
+    V: Visitor<'de>,
+  {
+    self
+      .0
+      .deserialize_newtype_struct(name, AdaptNewtype(visitor))
+  }
+
+  #[inline]
+  fn is_human_readable(&self) -> bool {
+    self.0.is_human_readable()
+  }
+}
+
+
+/// Deserialize an instance of type `T` from MessagePack encoded data.
+pub(crate) fn from_slice<T>(data: &[u8]) -> Result<T, DecodeError>
+where
+  T: DeserializeOwned,
+{
+  // Our data types have been designed for JSON and some of them (e.g.,
+  // `Num`) deserialize differently from binary formats.
+  let mut deserializer = RmpDeserializer::from_read_ref(data).with_human_readable();
+  let () = deserializer.set_max_depth(MAX_DEPTH);
+
+  let value = T::deserialize(Adapter(&mut deserializer))?;
+  // Decoding another value only succeeds if there is trailing data.
+  if IgnoredAny::deserialize(&mut deserializer).is_ok() {
+    return Err(DecodeError::custom("encountered trailing MessagePack data"))
+  }
+  Ok(value)
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use chrono::DateTime;
+
+  use num_decimal::Num;
+
+  use serde::Deserialize;
+
+  use serde_json::json;
+  use serde_json::Value;
+
+  use test_log::test;
+
+
+  /// Check that we can decode the various compact encodings.
+  #[test]
+  fn decode_compact() {
+    // {"T": "q", "s": -1, "ap": 1.5, "c": ["R"], "x": nil, "b": true}
+    let data = [
+      0x86, 0xa1, b'T', 0xa1, b'q', 0xa1, b's', 0xff, 0xa2, b'a', b'p', 0xca, 0x3f, 0xc0, 0x00,
+      0x00, 0xa1, b'c', 0x91, 0xa1, b'R', 0xa1, b'x', 0xc0, 0xa1, b'b', 0xc3,
+    ];
+    let value = from_slice::<Value>(&data).unwrap();
+    let expected = json!({"T": "q", "s": -1, "ap": 1.5, "c": ["R"], "x": null, "b": true});
+    assert_eq!(value, expected);
+  }
+
+  /// Check that we decode timestamps into RFC 3339 strings.
+  #[test]
+  fn decode_timestamps() {
+    // A 32 bit timestamp.
+    let data = [0xd6, 0xff, 0x60, 0x33, 0xf6, 0x00];
+    let time = from_slice::<DateTime<Utc>>(&data).unwrap();
+    assert_eq!(time.to_rfc3339(), "2021-02-22T18:20:48+00:00");
+
+    // A 64 bit timestamp with nanoseconds.
+    let value = (500_000_000u64 << 34) | 0x6033_f600;
+    let mut data = vec![0xd7, 0xff];
+    data.extend_from_slice(&value.to_be_bytes());
+    let time = from_slice::<DateTime<Utc>>(&data).unwrap();
+    assert_eq!(
+      time.to_rfc3339_opts(SecondsFormat::Millis, true),
+      "2021-02-22T18:20:48.500Z"
+    );
+  }
+
+  /// Check that single precision floats are not subject to widening
+  /// artifacts.
+  #[test]
+  fn decode_f32() {
+    // 1.1 as f32
+    let data = [0xca, 0x3f, 0x8c, 0xcc, 0xcd];
+    assert_eq!(from_slice::<f64>(&data).unwrap(), 1.1);
+    assert_eq!(from_slice::<Num>(&data).unwrap(), Num::new(11, 10));
+  }
+
+  /// Check that we can decode a struct containing a timestamp
+  /// directly.
+  #[test]
+  fn decode_struct() {
+    #[derive(Debug, Deserialize)]
+    struct Trade {
+      #[serde(rename = "p")]
+      price: Num,
+      #[serde(rename = "t")]
+      time: DateTime<Utc>,
+    }
+
+    // {"p": 126.55, "t": <timestamp>}
+    let mut data = vec![0x82, 0xa1, b'p', 0xcb];
+    data.extend_from_slice(&126.55f64.to_be_bytes());
+    data.extend_from_slice(&[0xa1, b't', 0xd6, 0xff, 0x60, 0x33, 0xf6, 0x00]);
+
+    let trade = from_slice::<Trade>(&data).unwrap();
+    assert_eq!(trade.price, Num::new(12655, 100));
+    assert_eq!(trade.time.to_rfc3339(), "2021-02-22T18:20:48+00:00");
+  }
+
+  /// Check that we report errors on malformed data.
+  #[test]
+  fn decode_invalid() {
+    assert!(from_slice::<Value>(&[]).is_err());
+    assert!(from_slice::<Value>(&[0xc1]).is_err());
+    assert!(from_slice::<Value>(&[0xa2, b'a']).is_err());
+    assert!(from_slice::<Value>(&[0x01, 0x02]).is_err());
+  }
+
+  /// Check that we refuse to decode excessively nested data.
+  #[test]
+  fn decode_deeply_nested() {
+    let data = vec![0x91; MAX_DEPTH + 1];
+    let err = from_slice::<Value>(&data).unwrap_err();
+    assert!(matches!(err, DecodeError::DepthLimitExceeded), "{err}");
+  }
+}
@@ -2,15 +2,26 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 use chrono::DateTime;
+use chrono::Duration as ChronoDuration;
+use chrono::NaiveDate;
 use chrono::Utc;
 
+use futures::Stream;
+
+use num_decimal::Num;
+
 use serde::Deserialize;
 use serde::Serialize;
 use serde_urlencoded::to_string as to_query;
 
+use crate::data::v2::range;
+use crate::data::v2::range::RangeEndpoint;
 use crate::data::v2::Feed;
-use crate::data::DATA_BASE_URL;
+use crate::data::v2::Sort;
 use crate::util::vec_from_str;
+use crate::Client;
+use crate::PageToken;
+use crate::RequestError;
 use crate::Str;
 
 /// A quote as returned by the /v2/stocks/{symbol}/quotes endpoint.
@@ -20,17 +31,18 @@ pub use super::last_quotes::Quote;
 /// A collection of quotes as returned by the API. This is one page of
 /// quotes.
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
-pub struct Quotes {
+#[serde(bound(deserialize = "P: Deserialize<'de>"))]
+pub struct Quotes<P = Num> {
   /// The list of returned quotes.
   #[serde(rename = "quotes", deserialize_with = "vec_from_str")]
-  pub quotes: Vec<Quote>,
+  pub quotes: Vec<Quote<P>>,
   /// The symbol the quotes correspond to.
   #[serde(rename = "symbol")]
   pub symbol: String,
   /// The token to provide to a request to get the next page of quotes
   /// for this request.
   #[serde(rename = "next_page_token")]
-  pub next_page_token: Option<String>,
+  pub next_page_token: Option<PageToken>,
   /// The type is non-exhaustive and open to extension.
   #[doc(hidden)]
   #[serde(skip)]
@@ -45,17 +57,34 @@ pub struct ListReqInit {
   pub limit: Option<usize>,
   /// See `ListReq::feed`.
   pub feed: Option<Feed>,
+  /// See `ListReq::asof`.
+  pub asof: Option<NaiveDate>,
+  /// See `ListReq::currency`.
+  pub currency: Option<String>,
+  /// See `ListReq::sort`.
+  pub sort: Option<Sort>,
   /// See `ListReq::page_token`.
-  pub page_token: Option<String>,
+  pub page_token: Option<PageToken>,
   /// The type is non-exhaustive and open to extension.
   #[doc(hidden)]
   pub _non_exhaustive: (),
 }
 
+Setters! {
+  ListReqInit {
+    limit: Option<usize>,
+    feed: Option<Feed>,
+    asof: Option<NaiveDate>,
+    currency: Option<String>,
+    sort: Option<Sort>,
+    page_token: Option<PageToken>,
+  }
+}
+
 impl ListReqInit {
   /// Create a [`ListReq`] from a `ListReqInit`.
   #[inline]
-  pub fn init<S>(self, symbol: S, start: DateTime<Utc>, end: DateTime<Utc>) -> ListReq
+  pub fn init<S>(&self, symbol: S, start: DateTime<Utc>, end: DateTime<Utc>) -> ListReq
   where
     S: Into<String>,
   {
@@ -65,7 +94,10 @@ impl ListReqInit {
       end,
       limit: self.limit,
       feed: self.feed,
-      page_token: self.page_token,
+      asof: self.asof,
+      currency: self.currency.clone(),
+      sort: self.sort,
+      page_token: self.page_token.clone(),
       _non_exhaustive: (),
     }
   }
@@ -94,9 +126,25 @@ pub struct ListReq {
   /// The data feed to use.
   #[serde(rename = "feed")]
   pub feed: Option<Feed>,
+  /// The date on which symbols are mapped, used to follow symbol
+  /// changes, e.g., due to a company rename.
+  ///
+  /// Defaults to the current day if the provided value is `None`.
+  #[serde(rename = "asof")]
+  pub asof: Option<NaiveDate>,
+  /// The currency in which prices are reported, e.g., "EUR".
+  ///
+  /// Defaults to USD if the provided value is `None`.
+  #[serde(rename = "currency")]
+  pub currency: Option<String>,
+  /// The order in which to return quotes.
+  ///
+  /// Defaults to [`Asc`][Sort::Asc] if the provided value is `None`.
+  #[serde(rename = "sort")]
+  pub sort: Option<Sort>,
   /// Pagination token to continue from.
   #[serde(rename = "page_token")]
-  pub page_token: Option<String>,
+  pub page_token: Option<PageToken>,
   /// The type is non-exhaustive and open to extension.
   #[doc(hidden)]
   #[serde(skip)]
@@ -108,6 +156,7 @@ Endpoint! {
   /// The representation of a GET request to the
   /// /v2/stocks/{symbol}/quotes endpoint.
   pub List(ListReq),
+  Api => Data,
   Ok => Quotes, [
     /// The quote information was retrieved successfully.
     /* 200 */ OK,
@@ -117,13 +166,38 @@ Endpoint! {
     /* 400 */ BAD_REQUEST => InvalidInput,
   ]
 
-  fn base_url() -> Option<Str> {
-    Some(DATA_BASE_URL.into())
+  #[inline]
+  fn path(input: &Self::Input) -> Str {
+    format!("/v2/stocks/{}/quotes", input.symbol).into()
+  }
+
+  fn query(input: &Self::Input) -> Result<Option<Str>, Self::ConversionError> {
+    Ok(Some(to_query(input)?.into()))
   }
+}
+
+
+#[cfg(feature = "f64-prices")]
+Endpoint! {
+  /// The representation of a GET request to the
+  /// /v2/stocks/{symbol}/quotes endpoint, reporting prices as `f64`.
+  ///
+  /// Retrieving quotes by means of this endpoint is considerably faster
+  /// than using [`List`], at the cost of precision.
+  pub ListF64(ListReq),
+  Api => Data,
+  Ok => Quotes<f64>, [
+    /// The quote information was retrieved successfully.
+    /* 200 */ OK,
+  ],
+  Err => ListF64Error, [
+    /// Some of the provided data was invalid or not found.
+    /* 400 */ BAD_REQUEST => InvalidInput,
+  ]
 
   #[inline]
   fn path(input: &Self::Input) -> Str {
-    format!("/v2/stocks/{}/quotes", input.symbol).into()
+    List::path(input)
   }
 
   fn query(input: &Self::Input) -> Result<Option<Str>, Self::ConversionError> {
@@ -132,6 +206,65 @@ Endpoint! {
 }
 
 
+impl RangeEndpoint for List {
+  type Item = Quote;
+
+  #[inline]
+  fn range(input: &mut Self::Input) -> (&mut DateTime<Utc>, &mut DateTime<Utc>) {
+    (&mut input.start, &mut input.end)
+  }
+
+  #[inline]
+  fn page_token(input: &mut Self::Input) -> &mut Option<PageToken> {
+    &mut input.page_token
+  }
+
+  #[inline]
+  fn into_page(output: Self::Output) -> (Vec<Self::Item>, Option<PageToken>) {
+    (output.quotes, output.next_page_token)
+  }
+
+  #[inline]
+  fn is_rate_limited(error: &Self::Error) -> bool {
+    matches!(error, ListError::RateLimitExceeded(..))
+  }
+}
+
+
+/// Retrieve all quotes in the time range of the provided request as a
+/// stream.
+///
+/// Quotes are fetched lazily as the stream is polled, a page at a time.
+/// A zero `chunk` length retrieves the full range by following page
+/// tokens only. See
+/// [`bars::fetch_range`][crate::data::v2::bars::fetch_range] for
+/// details on how the time range is retrieved and [`pages`] for
+/// processing quotes page by page instead.
+pub fn fetch_range(
+  client: &Client,
+  request: ListReq,
+  chunk: ChronoDuration,
+) -> impl Stream<Item = Result<Quote, RequestError<ListError>>> + '_ {
+  range::fetch_range::<List>(client, request, chunk)
+}
+
+
+/// Retrieve the quotes covered by the provided request as a stream of
+/// pages.
+///
+/// Pages are fetched lazily as the stream is polled, following page
+/// tokens, so that only a single page has to be held in memory at any
+/// point in time. That makes it possible to process, say, a full
+/// day's worth of quotes page by page. Requests exceeding the rate
+/// limit are retried a few times, backing off in between.
+pub fn pages(
+  client: &Client,
+  request: ListReq,
+) -> impl Stream<Item = Result<Vec<Quote>, RequestError<ListError>>> + '_ {
+  range::fetch_pages::<List>(client, request, ChronoDuration::zero())
+}
+
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -170,6 +303,40 @@ mod tests {
     }
   }
 
+  /// Check that we can parse quotes with prices represented as `f64`.
+  #[cfg(feature = "f64-prices")]
+  #[test(tokio::test)]
+  async fn list_f64_quotes() {
+    use http::StatusCode;
+    use http_endpoint::Endpoint as _;
+
+    use crate::transport::test::mock_client;
+    use crate::MockTransport;
+
+    let response = r#"{
+    "quotes": [
+      {"t": "2022-01-04T13:35:59.8Z", "ax": "V", "ap": 478.71, "as": 2, "bx": "V", "bp": 478.7, "bs": 1, "c": ["R"], "z": "B"}
+    ],
+    "symbol": "SPY",
+    "next_page_token": null
+}"#;
+
+    let start = DateTime::from_str("2022-01-04T13:35:59Z").unwrap();
+    let end = DateTime::from_str("2022-01-04T13:36:00Z").unwrap();
+    let request = ListReqInit::default().init("SPY", start, end);
+    assert_eq!(ListF64::path(&request), List::path(&request));
+
+    let mock = MockTransport::new();
+    let () = mock.respond::<ListF64>(&request, StatusCode::OK, response);
+    let client = mock_client(mock);
+
+    let quotes = client.issue::<ListF64>(&request).await.unwrap();
+    assert_eq!(quotes.quotes.len(), 1);
+    assert_eq!(quotes.quotes[0].ask_price, 478.71);
+    assert_eq!(quotes.quotes[0].bid_price, 478.7);
+    assert_eq!(quotes.quotes[0].ask_size, 2);
+  }
+
   /// Verify that we can specify the SIP feed as the data source to use.
   #[test(tokio::test)]
   async fn sip_feed() {
@@ -216,7 +383,7 @@ mod tests {
     let start = DateTime::from_str("2022-01-04T13:35:59Z").unwrap();
     let end = DateTime::from_str("2022-01-04T13:36:00Z").unwrap();
     let request = ListReqInit {
-      page_token: Some("123456789abcdefghi".to_string()),
+      page_token: Some("123456789abcdefghi".into()),
       ..Default::default()
     }
     .init("SPY", start, end);
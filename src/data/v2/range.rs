@@ -0,0 +1,194 @@
+// Copyright (C) 2024 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::time::Duration;
+
+use chrono::DateTime;
+use chrono::Duration as ChronoDuration;
+use chrono::Utc;
+
+use futures::stream::iter;
+use futures::stream::unfold;
+use futures::Stream;
+use futures::TryStreamExt as _;
+
+use http_endpoint::Endpoint;
+
+use tokio::time::sleep;
+
+use crate::endpoint::ApiEndpoint;
+use crate::endpoint::EndpointError;
+use crate::Client;
+use crate::PageToken;
+use crate::RequestError;
+
+
+/// The number of times a request is retried after exceeding the rate
+/// limit.
+const MAX_RETRIES: u32 = 5;
+/// The initial back off time after exceeding the rate limit. It is
+/// doubled with every retry.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+
+/// A trait for historic data endpoints whose requests cover a time
+/// range and whose responses are paginated.
+pub(crate) trait RangeEndpoint: ApiEndpoint {
+  /// The type of the individual items reported.
+  type Item;
+
+  /// Retrieve mutable references to the start and end of the time
+  /// range covered by a request.
+  fn range(input: &mut Self::Input) -> (&mut DateTime<Utc>, &mut DateTime<Utc>);
+
+  /// Retrieve a mutable reference to the page token of a request.
+  fn page_token(input: &mut Self::Input) -> &mut Option<PageToken>;
+
+  /// Split a response into the items it contains and the token for
+  /// retrieving the next page, if any.
+  fn into_page(output: Self::Output) -> (Vec<Self::Item>, Option<PageToken>);
+
+  /// Check whether an error indicates that the rate limit was
+  /// exceeded.
+  fn is_rate_limited(error: &Self::Error) -> bool;
+}
+
+
+/// The state of a range retrieval.
+struct State<'c, R>
+where
+  R: Endpoint,
+{
+  /// The client used for issuing requests.
+  client: &'c Client,
+  /// The request for the current chunk and page.
+  request: R::Input,
+  /// The end of the overall time range.
+  end: DateTime<Utc>,
+  /// The length of an individual chunk.
+  chunk: Option<ChronoDuration>,
+  /// Whether the retrieval has concluded.
+  done: bool,
+}
+
+impl<R> State<'_, R>
+where
+  R: RangeEndpoint,
+  R::Error: EndpointError,
+{
+  /// Issue the current request, retrying with back off if the rate
+  /// limit was exceeded.
+  async fn issue(&self) -> Result<R::Output, RequestError<R::Error>> {
+    let mut backoff = INITIAL_BACKOFF;
+    let mut retries = 0;
+
+    loop {
+      match self.client.issue::<R>(&self.request).await {
+        Err(RequestError::Endpoint(err)) if R::is_rate_limited(&err) && retries < MAX_RETRIES => {
+          let () = sleep(backoff).await;
+          backoff *= 2;
+          retries += 1;
+        },
+        result => break result,
+      }
+    }
+  }
+
+  /// Advance the request to the next page or chunk.
+  fn advance(&mut self, next_page_token: Option<PageToken>) {
+    if next_page_token.is_some() {
+      *R::page_token(&mut self.request) = next_page_token;
+      return
+    }
+
+    *R::page_token(&mut self.request) = None;
+
+    let end = self.end;
+    let chunk = self.chunk;
+    let (start, chunk_end) = R::range(&mut self.request);
+    if *chunk_end >= end {
+      self.done = true;
+    } else {
+      // Both ends of the range are inclusive, so start the next chunk
+      // just past the end of the current one.
+      *start = *chunk_end + ChronoDuration::nanoseconds(1);
+      *chunk_end = chunk_end_for(*start, end, chunk);
+    }
+  }
+}
+
+
+/// Calculate the end of a chunk starting at `start`.
+fn chunk_end_for(
+  start: DateTime<Utc>,
+  end: DateTime<Utc>,
+  chunk: Option<ChronoDuration>,
+) -> DateTime<Utc> {
+  chunk
+    .and_then(|chunk| start.checked_add_signed(chunk))
+    .map(|chunk_end| chunk_end.min(end))
+    .unwrap_or(end)
+}
+
+
+/// Retrieve all pages of items in the time range of the provided
+/// request, splitting it into chunks of the given length and following
+/// page tokens.
+pub(crate) fn fetch_pages<R>(
+  client: &Client,
+  mut request: R::Input,
+  chunk: ChronoDuration,
+) -> impl Stream<Item = Result<Vec<R::Item>, RequestError<R::Error>>> + '_
+where
+  R: RangeEndpoint + 'static,
+  R::Error: EndpointError,
+{
+  let chunk = (chunk > ChronoDuration::zero()).then_some(chunk);
+  let (start, chunk_end) = R::range(&mut request);
+  let end = *chunk_end;
+  *chunk_end = chunk_end_for(*start, end, chunk);
+
+  let state = State::<R> {
+    client,
+    request,
+    end,
+    chunk,
+    done: false,
+  };
+
+  unfold(state, |mut state| async move {
+    if state.done {
+      return None
+    }
+
+    match state.issue().await {
+      Ok(output) => {
+        let (items, next_page_token) = R::into_page(output);
+        let () = state.advance(next_page_token);
+        Some((Ok(items), state))
+      },
+      Err(err) => {
+        state.done = true;
+        Some((Err(err), state))
+      },
+    }
+  })
+}
+
+
+/// Retrieve all items in the time range of the provided request,
+/// splitting it into chunks of the given length and following page
+/// tokens.
+pub(crate) fn fetch_range<R>(
+  client: &Client,
+  request: R::Input,
+  chunk: ChronoDuration,
+) -> impl Stream<Item = Result<R::Item, RequestError<R::Error>>> + '_
+where
+  R: RangeEndpoint + 'static,
+  R::Error: EndpointError,
+{
+  fetch_pages::<R>(client, request, chunk)
+    .map_ok(|items| iter(items.into_iter().map(Ok)))
+    .try_flatten()
+}
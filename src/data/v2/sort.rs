@@ -0,0 +1,18 @@
+// Copyright (C) 2024 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use serde::Serialize;
+
+
+/// An enumeration of the possible orders in which historical market
+/// data can be returned.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub enum Sort {
+  /// Return data in ascending order of time, i.e., oldest first.
+  #[serde(rename = "asc")]
+  Asc,
+  /// Return data in descending order of time, i.e., newest first.
+  #[serde(rename = "desc")]
+  Desc,
+}
@@ -4,15 +4,22 @@
 use std::borrow::Borrow as _;
 use std::borrow::Cow;
 use std::cmp::Ordering;
+use std::collections::HashSet;
 use std::fmt::Debug;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
 use std::marker::PhantomData;
+use std::mem::take;
 use std::ops::Deref;
+use std::time::Duration;
 
 use async_trait::async_trait;
 
 use chrono::DateTime;
 use chrono::Utc;
 
+use futures::future::BoxFuture;
 use futures::stream::Fuse;
 use futures::stream::FusedStream;
 use futures::stream::Map;
@@ -35,13 +42,11 @@ use serde_json::from_slice as json_from_slice;
 use serde_json::from_str as json_from_str;
 use serde_json::to_string as to_json;
 use serde_json::Error as JsonError;
+use serde_variant::to_variant_name;
 
 use thiserror::Error as ThisError;
 
-use tokio::net::TcpStream;
-
-use tungstenite::MaybeTlsStream;
-use tungstenite::WebSocketStream;
+use tokio::time::timeout;
 
 use url::Url;
 
@@ -51,32 +56,68 @@ use websocket_util::tungstenite::Error as WebSocketError;
 use websocket_util::wrap;
 use websocket_util::wrap::Wrapper;
 
+use super::conditions::QuoteCondition;
+use super::conditions::Tape;
+use super::conditions::TradeCondition;
+use super::msgpack;
 use super::unfold::Unfold;
+use super::Feed;
 
+use crate::connection::ConnectionConfig;
 use crate::subscribable::Subscribable;
-use crate::websocket::connect;
+use crate::websocket::connect_with_headers;
 use crate::websocket::MessageResult;
+use crate::websocket::WebSocket;
 use crate::ApiInfo;
 use crate::Error;
 use crate::Str;
 
 
-type UserMessage<B, Q, T> = <ParsedMessage<B, Q, T> as subscribe::Message>::UserMessage;
+/// The default maximum number of symbols referenced in a single
+/// subscribe or unsubscribe request.
+///
+/// See [`Subscription::set_max_symbols_per_request`].
+pub const MAX_SYMBOLS_PER_REQUEST: usize = 1000;
+
+
+type UserMessage<B, Q, T, E = JsonError> =
+  <ParsedMessage<B, Q, T, E> as subscribe::Message>::UserMessage;
 
 /// Helper function to drive a [`Subscription`] related future to
 /// completion. The function makes sure to poll the provided stream,
 /// which is assumed to be associated with the `Subscription` that the
 /// future belongs to, so that control messages can be received.
 #[inline]
-pub async fn drive<F, S, B, Q, T>(
+pub async fn drive<F, S, B, Q, T, E>(
+  future: F,
+  stream: &mut S,
+) -> Result<F::Output, UserMessage<B, Q, T, E>>
+where
+  F: Future + Unpin,
+  S: FusedStream<Item = UserMessage<B, Q, T, E>> + Unpin,
+{
+  subscribe::drive::<ParsedMessage<B, Q, T, E>, _, _>(future, stream).await
+}
+
+/// Drive a [`Subscription`] related future to completion, just like
+/// [`drive`], but give up if it did not complete within `duration`.
+///
+/// [`Error::Timeout`] is reported if the future did not complete in
+/// time, e.g., because the server never responded to a subscription
+/// request.
+#[inline]
+pub async fn drive_with_timeout<F, S, B, Q, T, E>(
   future: F,
   stream: &mut S,
-) -> Result<F::Output, UserMessage<B, Q, T>>
+  duration: Duration,
+) -> Result<Result<F::Output, UserMessage<B, Q, T, E>>, Error>
 where
   F: Future + Unpin,
-  S: FusedStream<Item = UserMessage<B, Q, T>> + Unpin,
+  S: FusedStream<Item = UserMessage<B, Q, T, E>> + Unpin,
 {
-  subscribe::drive::<ParsedMessage<B, Q, T>, _, _>(future, stream).await
+  timeout(duration, drive(future, stream))
+    .await
+    .map_err(|_elapsed| Error::Timeout(duration))
 }
 
 
@@ -97,6 +138,9 @@ pub enum SourceVariant {
 
 
 /// A trait representing the source from which to stream real time data.
+///
+/// Sources select the stream at the type level. To pick a [`Feed`] at
+/// runtime instead, use [`Client::subscribe_data`][crate::Client::subscribe_data].
 // TODO: Once we can use enumerations as const generic parameters we
 //       should probably switch over to repurposing `data::v2::Feed`
 //       here instead.
@@ -141,6 +185,24 @@ impl Source for SIP {
 impl private::Sealed for SIP {}
 
 
+/// Use the test stream as the data source.
+///
+/// The test stream is available around the clock, irrespective of
+/// market hours, and provides data for the fake `FAKEPACA` symbol. It
+/// is meant to be used for testing and development purposes.
+#[derive(Clone, Copy, Debug)]
+pub enum Test {}
+
+impl Source for Test {
+  #[inline]
+  fn source() -> SourceVariant {
+    SourceVariant::PathComponent("test")
+  }
+}
+
+impl private::Sealed for Test {}
+
+
 /// A realtime data source that uses a custom URL.
 ///
 /// This type provides a way to stream realtime data from a custom URL.
@@ -175,6 +237,10 @@ impl private::Sealed for SIP {}
 /// // Use `subscription` to subscribe to quotes, trades, or bars, then
 /// // handle `stream` as usual.
 /// ```
+///
+/// If the URL is only known at runtime, use
+/// [`Client::subscribe_data_url`][crate::Client::subscribe_data_url]
+/// instead.
 #[derive(Clone, Copy, Debug)]
 pub struct CustomUrl<URL> {
   _phantom: PhantomData<URL>,
@@ -194,6 +260,81 @@ where
 impl<URL> private::Sealed for CustomUrl<URL> {}
 
 
+/// A trait representing the wire format in which real time data are
+/// streamed.
+pub trait Format: private::Sealed {
+  /// The error reported for messages that failed to decode.
+  type Error: From<JsonError> + Into<Error> + Debug + Send + Unpin;
+
+  /// The content type to request when connecting, if any.
+  #[doc(hidden)]
+  fn content_type() -> Option<&'static str>;
+
+  /// Decode a binary message.
+  #[doc(hidden)]
+  fn decode<D>(data: &[u8]) -> Result<D, Self::Error>
+  where
+    D: DeserializeOwned;
+}
+
+
+/// Stream data as JSON.
+///
+/// This is the default format.
+#[derive(Clone, Copy, Debug)]
+pub enum Json {}
+
+impl Format for Json {
+  type Error = JsonError;
+
+  #[inline]
+  fn content_type() -> Option<&'static str> {
+    None
+  }
+
+  #[inline]
+  fn decode<D>(data: &[u8]) -> Result<D, JsonError>
+  where
+    D: DeserializeOwned,
+  {
+    json_from_slice::<D>(data)
+  }
+}
+
+impl private::Sealed for Json {}
+
+
+/// Stream data as MessagePack.
+///
+/// MessagePack is a binary format that is cheaper to decode than JSON,
+/// which may make a difference at high message rates. Data are decoded
+/// into the same types as used for JSON. Control messages continue to
+/// be sent as JSON. Because data may hence fail to decode in either
+/// format, messages that could not be decoded are reported as
+/// [`Error`].
+#[derive(Clone, Copy, Debug)]
+pub enum MsgPack {}
+
+impl Format for MsgPack {
+  type Error = Error;
+
+  #[inline]
+  fn content_type() -> Option<&'static str> {
+    Some("application/msgpack")
+  }
+
+  #[inline]
+  fn decode<D>(data: &[u8]) -> Result<D, Self::Error>
+  where
+    D: DeserializeOwned,
+  {
+    msgpack::from_slice::<D>(data).map_err(Error::MsgPack)
+  }
+}
+
+impl private::Sealed for MsgPack {}
+
+
 /// A symbol.
 pub type Symbol = Str;
 
@@ -250,11 +391,15 @@ fn normalize(symbols: Cow<'static, [Symbol]>) -> Cow<'static, [Symbol]> {
 
 
 /// Aggregate data for an equity.
+///
+/// The type is generic over the representation of strings, which
+/// can be substituted with [`Interned`][crate::data::v2::intern::Interned]
+/// to cut down on allocations.
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
-pub struct Bar {
+pub struct Bar<S = String> {
   /// The bar's symbol.
   #[serde(rename = "S")]
-  pub symbol: String,
+  pub symbol: S,
   /// The bar's open price.
   #[serde(rename = "o")]
   pub open_price: Num,
@@ -270,6 +415,9 @@ pub struct Bar {
   /// The bar's volume.
   #[serde(rename = "v")]
   pub volume: Num,
+  /// The number of trades that occurred during the bar's time frame.
+  #[serde(rename = "n", default)]
+  pub trade_count: Option<u64>,
   /// The bar's time stamp.
   #[serde(rename = "t")]
   pub timestamp: DateTime<Utc>,
@@ -277,11 +425,13 @@ pub struct Bar {
 
 
 /// A quote for an equity.
+///
+/// See [`Bar`] for the meaning of the type parameter.
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
-pub struct Quote {
+pub struct Quote<S = String> {
   /// The quote's symbol.
   #[serde(rename = "S")]
-  pub symbol: String,
+  pub symbol: S,
   /// The bid's price.
   #[serde(rename = "bp")]
   pub bid_price: Num,
@@ -301,14 +451,97 @@ pub struct Quote {
 
 
 /// A trade for an equity.
+///
+/// See [`Bar`] for the meaning of the type parameter.
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
-pub struct Trade {
+pub struct Trade<S = String> {
   /// The trade's symbol.
   #[serde(rename = "S")]
-  pub symbol: String,
+  pub symbol: S,
+  /// The trade's ID.
+  #[serde(rename = "i")]
+  pub trade_id: u64,
+  /// The trade's price.
+  #[serde(rename = "p")]
+  pub trade_price: Num,
+  /// The trade's size.
+  #[serde(rename = "s")]
+  pub trade_size: Num,
+  /// The trade's time stamp.
+  #[serde(rename = "t")]
+  pub timestamp: DateTime<Utc>,
+}
+
+
+/// A quote for an equity, including exchange, condition, and tape
+/// information.
+///
+/// This type can be used in lieu of [`Quote`] as the quote type of
+/// [`RealtimeData`].
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct DetailedQuote<S = String> {
+  /// The quote's symbol.
+  #[serde(rename = "S")]
+  pub symbol: S,
+  /// The bid's exchange code.
+  #[serde(rename = "bx")]
+  pub bid_exchange: S,
+  /// The bid's price.
+  #[serde(rename = "bp")]
+  pub bid_price: Num,
+  /// The bid's size.
+  #[serde(rename = "bs")]
+  pub bid_size: Num,
+  /// The ask's exchange code.
+  #[serde(rename = "ax")]
+  pub ask_exchange: S,
+  /// The ask's price.
+  #[serde(rename = "ap")]
+  pub ask_price: Num,
+  /// The ask's size.
+  #[serde(rename = "as")]
+  pub ask_size: Num,
+  /// The quote's time stamp.
+  #[serde(rename = "t")]
+  pub timestamp: DateTime<Utc>,
+  /// The quote's raw condition codes.
+  #[serde(rename = "c", default = "Vec::new")]
+  pub conditions: Vec<S>,
+  /// The tape the quote was reported on.
+  #[serde(rename = "z")]
+  pub tape: Tape,
+}
+
+impl<S> DetailedQuote<S>
+where
+  S: AsRef<str>,
+{
+  /// Decode the quote's conditions.
+  pub fn quote_conditions(&self) -> impl Iterator<Item = QuoteCondition> + '_ {
+    self
+      .conditions
+      .iter()
+      .map(|code| QuoteCondition::from_code(self.tape, code.as_ref()))
+  }
+}
+
+
+/// A trade for an equity, including exchange, condition, and tape
+/// information.
+///
+/// This type can be used in lieu of [`Trade`] as the trade type of
+/// [`RealtimeData`].
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct DetailedTrade<S = String> {
+  /// The trade's symbol.
+  #[serde(rename = "S")]
+  pub symbol: S,
   /// The trade's ID.
   #[serde(rename = "i")]
   pub trade_id: u64,
+  /// The trade's exchange code.
+  #[serde(rename = "x")]
+  pub exchange: S,
   /// The trade's price.
   #[serde(rename = "p")]
   pub trade_price: Num,
@@ -318,6 +551,213 @@ pub struct Trade {
   /// The trade's time stamp.
   #[serde(rename = "t")]
   pub timestamp: DateTime<Utc>,
+  /// The trade's raw condition codes.
+  #[serde(rename = "c", default = "Vec::new")]
+  pub conditions: Vec<S>,
+  /// The tape the trade was reported on.
+  #[serde(rename = "z")]
+  pub tape: Tape,
+  /// The trade's update, if any. May be "canceled", "corrected", or
+  /// "incorrect".
+  #[serde(rename = "u", default, skip_serializing_if = "Option::is_none")]
+  pub update: Option<String>,
+}
+
+impl<S> DetailedTrade<S>
+where
+  S: AsRef<str>,
+{
+  /// Decode the trade's conditions.
+  pub fn trade_conditions(&self) -> impl Iterator<Item = TradeCondition> + '_ {
+    self
+      .conditions
+      .iter()
+      .map(|code| TradeCondition::from_code(self.tape, code.as_ref()))
+  }
+}
+
+
+/// A correction of a previously reported trade.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TradeCorrection {
+  /// The symbol of the corrected trade.
+  #[serde(rename = "S")]
+  pub symbol: String,
+  /// The exchange the trade happened on.
+  #[serde(rename = "x")]
+  pub exchange: String,
+  /// The ID of the original trade.
+  #[serde(rename = "oi")]
+  pub original_trade_id: u64,
+  /// The price of the original trade.
+  #[serde(rename = "op")]
+  pub original_trade_price: Num,
+  /// The size of the original trade.
+  #[serde(rename = "os")]
+  pub original_trade_size: Num,
+  /// The conditions of the original trade.
+  #[serde(rename = "oc", default)]
+  pub original_conditions: Vec<String>,
+  /// The ID of the corrected trade.
+  #[serde(rename = "ci")]
+  pub corrected_trade_id: u64,
+  /// The price of the corrected trade.
+  #[serde(rename = "cp")]
+  pub corrected_trade_price: Num,
+  /// The size of the corrected trade.
+  #[serde(rename = "cs")]
+  pub corrected_trade_size: Num,
+  /// The conditions of the corrected trade.
+  #[serde(rename = "cc", default)]
+  pub corrected_conditions: Vec<String>,
+  /// The correction's time stamp.
+  #[serde(rename = "t")]
+  pub timestamp: DateTime<Utc>,
+}
+
+
+/// The action reported as part of a [`TradeCancelError`].
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub enum CancelErrorAction {
+  /// The trade was canceled.
+  #[serde(rename = "C")]
+  Cancel,
+  /// The trade was reported in error.
+  #[serde(rename = "E")]
+  Error,
+  /// Any other action that we have not accounted for.
+  ///
+  /// Note that having any such action should be considered a bug.
+  #[doc(hidden)]
+  #[serde(other, rename(serialize = "unknown"))]
+  Unknown,
+}
+
+
+/// A cancellation or error report of a previously reported trade.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TradeCancelError {
+  /// The symbol of the affected trade.
+  #[serde(rename = "S")]
+  pub symbol: String,
+  /// The ID of the affected trade.
+  #[serde(rename = "i")]
+  pub trade_id: u64,
+  /// The exchange the trade happened on.
+  #[serde(rename = "x")]
+  pub exchange: String,
+  /// The price of the affected trade.
+  #[serde(rename = "p")]
+  pub trade_price: Num,
+  /// The size of the affected trade.
+  #[serde(rename = "s")]
+  pub trade_size: Num,
+  /// Whether the trade was canceled or reported in error.
+  #[serde(rename = "a")]
+  pub action: CancelErrorAction,
+  /// The message's time stamp.
+  #[serde(rename = "t")]
+  pub timestamp: DateTime<Utc>,
+}
+
+
+/// A limit up-limit down (LULD) band update for an equity.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Luld {
+  /// The symbol the bands apply to.
+  #[serde(rename = "S")]
+  pub symbol: String,
+  /// The upper price band.
+  #[serde(rename = "u")]
+  pub limit_up_price: Num,
+  /// The lower price band.
+  #[serde(rename = "d")]
+  pub limit_down_price: Num,
+  /// The indicator describing the state of the bands.
+  #[serde(rename = "i")]
+  pub indicator: String,
+  /// The message's time stamp.
+  #[serde(rename = "t")]
+  pub timestamp: DateTime<Utc>,
+}
+
+
+/// A trading status update for an equity, e.g., reporting a halt or
+/// resumption of trading.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Status {
+  /// The symbol the status applies to.
+  #[serde(rename = "S")]
+  pub symbol: String,
+  /// The status code, as reported by the SIP.
+  #[serde(rename = "sc")]
+  pub status_code: String,
+  /// A human readable description of the status.
+  #[serde(rename = "sm")]
+  pub status_message: String,
+  /// The code of the reason for the status.
+  #[serde(rename = "rc")]
+  pub reason_code: String,
+  /// A human readable description of the reason for the status.
+  #[serde(rename = "rm")]
+  pub reason_message: String,
+  /// The status' time stamp.
+  #[serde(rename = "t")]
+  pub timestamp: DateTime<Utc>,
+}
+
+impl Status {
+  /// Check whether the status indicates that trading in the symbol is
+  /// halted or paused.
+  ///
+  /// This covers the trading halt codes of both the CTA (`2`) and UTP
+  /// (`H`) plans as well as volatility trading pauses (`P`).
+  #[inline]
+  pub fn is_halted(&self) -> bool {
+    matches!(self.status_code.as_str(), "2" | "H" | "P")
+  }
+}
+
+
+/// A single price level of an [`Orderbook`].
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct OrderbookEntry {
+  /// The price of the level.
+  #[serde(rename = "p")]
+  pub price: Num,
+  /// The size available at the level.
+  ///
+  /// A size of zero indicates that the level got removed from the book.
+  #[serde(rename = "s")]
+  pub size: Num,
+}
+
+
+/// An order book update for a crypto currency pair, as sent over the
+/// `orderbooks` channel of the crypto stream.
+///
+/// Updates are incremental: only changed levels are reported, unless
+/// [`reset`][Orderbook::reset] is set, in which case the update
+/// represents the full book and any previously maintained state should
+/// be discarded.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Orderbook {
+  /// The symbol of the currency pair the order book is for.
+  #[serde(rename = "S")]
+  pub symbol: String,
+  /// The bid side levels that changed.
+  #[serde(rename = "b", default)]
+  pub bids: Vec<OrderbookEntry>,
+  /// The ask side levels that changed.
+  #[serde(rename = "a", default)]
+  pub asks: Vec<OrderbookEntry>,
+  /// Whether the update contains the full order book.
+  #[serde(rename = "r", default)]
+  pub reset: bool,
+  /// The update's time stamp.
+  #[serde(rename = "t")]
+  pub timestamp: DateTime<Utc>,
 }
 
 
@@ -334,6 +774,38 @@ pub struct StreamApiError {
 }
 
 
+/// The error code the server reports when a subscription would exceed
+/// the number of symbols permitted by the market data plan.
+const CODE_SYMBOL_LIMIT_EXCEEDED: u64 = 405;
+
+
+/// An error indicating that a subscription request was refused.
+#[derive(Clone, Debug, Eq, PartialEq, ThisError)]
+#[non_exhaustive]
+pub enum SubscriptionError {
+  /// The server refused the request because it would exceed the number
+  /// of symbols permitted by the market data plan (e.g., 30 on the
+  /// free plan).
+  #[error("symbol limit exceeded")]
+  SymbolLimitExceeded(#[source] StreamApiError),
+  /// Subscribing to all symbols was requested for a channel for which
+  /// it is not permitted, as per
+  /// [`Subscription::permit_all_symbols`].
+  #[error("subscribing to all symbols is not permitted for {0}")]
+  AllSymbolsNotPermitted(Channel),
+  /// The request would leave more symbols subscribed than permitted,
+  /// as per [`Subscription::set_symbol_limit`].
+  #[error("subscription would reference {count} symbols, exceeding limit of {limit}")]
+  TooManySymbols {
+    /// The number of distinct symbols the subscription would
+    /// reference.
+    count: usize,
+    /// The configured symbol limit.
+    limit: usize,
+  },
+}
+
+
 /// An enum representing the different messages we may receive over our
 /// websocket channel.
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -344,12 +816,37 @@ pub enum DataMessage<B = Bar, Q = Quote, T = Trade> {
   /// A variant representing aggregate data for a given symbol.
   #[serde(rename = "b")]
   Bar(B),
+  /// A variant representing daily aggregate data for a given symbol.
+  #[serde(rename = "d")]
+  DailyBar(B),
+  /// A variant representing updated aggregate data for a given
+  /// symbol, as sent in response to late trades.
+  #[serde(rename = "u")]
+  UpdatedBar(B),
   /// A variant representing a quote for a given symbol.
   #[serde(rename = "q")]
   Quote(Q),
   /// A variant representing a trade for a given symbol.
   #[serde(rename = "t")]
   Trade(T),
+  /// A variant representing a trade correction for a given symbol.
+  #[serde(rename = "c")]
+  Correction(TradeCorrection),
+  /// A variant representing a trade cancellation or error for a given
+  /// symbol.
+  #[serde(rename = "x")]
+  CancelError(TradeCancelError),
+  /// A variant representing a LULD band update for a given symbol.
+  #[serde(rename = "l")]
+  Luld(Luld),
+  /// A variant representing a trading status update for a given
+  /// symbol.
+  #[serde(rename = "s")]
+  Status(Status),
+  /// A variant representing an order book update for a given crypto
+  /// currency pair.
+  #[serde(rename = "o")]
+  Orderbook(Orderbook),
   /// A control message describing the current list of subscriptions.
   #[serde(rename = "subscription")]
   Subscription(MarketData),
@@ -360,6 +857,9 @@ pub enum DataMessage<B = Bar, Q = Quote, T = Trade> {
   /// An error reported by the Alpaca Stream API.
   #[serde(rename = "error")]
   Error(StreamApiError),
+  /// Any other message that we have not accounted for.
+  #[serde(other, rename(serialize = "unknown"))]
+  Unknown,
 }
 
 
@@ -369,10 +869,33 @@ pub enum DataMessage<B = Bar, Q = Quote, T = Trade> {
 pub enum Data<B = Bar, Q = Quote, T = Trade> {
   /// A variant representing aggregate data for a given symbol.
   Bar(B),
+  /// A variant representing daily aggregate data for a given symbol.
+  DailyBar(B),
+  /// A variant representing updated aggregate data for a given
+  /// symbol, as sent in response to late trades.
+  UpdatedBar(B),
   /// A variant representing quote data for a given symbol.
   Quote(Q),
   /// A variant representing trade data for a given symbol.
   Trade(T),
+  /// A variant representing a trade correction for a given symbol.
+  Correction(TradeCorrection),
+  /// A variant representing a trade cancellation or error for a given
+  /// symbol.
+  CancelError(TradeCancelError),
+  /// A variant representing a LULD band update for a given symbol.
+  Luld(Luld),
+  /// A variant representing a trading status update for a given
+  /// symbol.
+  Status(Status),
+  /// A variant representing an order book update for a given crypto
+  /// currency pair.
+  Orderbook(Orderbook),
+  /// A message of a type that we have not accounted for.
+  ///
+  /// Note that receiving any such message should be considered a bug.
+  #[doc(hidden)]
+  Unknown,
 }
 
 impl<B, Q, T> Data<B, Q, T> {
@@ -382,6 +905,18 @@ impl<B, Q, T> Data<B, Q, T> {
     matches!(self, Self::Bar(..))
   }
 
+  /// Check whether this object is of the `DailyBar` variant.
+  #[inline]
+  pub fn is_daily_bar(&self) -> bool {
+    matches!(self, Self::DailyBar(..))
+  }
+
+  /// Check whether this object is of the `UpdatedBar` variant.
+  #[inline]
+  pub fn is_updated_bar(&self) -> bool {
+    matches!(self, Self::UpdatedBar(..))
+  }
+
   /// Check whether this object is of the `Quote` variant.
   #[inline]
   pub fn is_quote(&self) -> bool {
@@ -393,6 +928,36 @@ impl<B, Q, T> Data<B, Q, T> {
   pub fn is_trade(&self) -> bool {
     matches!(self, Self::Trade(..))
   }
+
+  /// Check whether this object is of the `Correction` variant.
+  #[inline]
+  pub fn is_correction(&self) -> bool {
+    matches!(self, Self::Correction(..))
+  }
+
+  /// Check whether this object is of the `CancelError` variant.
+  #[inline]
+  pub fn is_cancel_error(&self) -> bool {
+    matches!(self, Self::CancelError(..))
+  }
+
+  /// Check whether this object is of the `Luld` variant.
+  #[inline]
+  pub fn is_luld(&self) -> bool {
+    matches!(self, Self::Luld(..))
+  }
+
+  /// Check whether this object is of the `Status` variant.
+  #[inline]
+  pub fn is_status(&self) -> bool {
+    matches!(self, Self::Status(..))
+  }
+
+  /// Check whether this object is of the `Orderbook` variant.
+  #[inline]
+  pub fn is_orderbook(&self) -> bool {
+    matches!(self, Self::Orderbook(..))
+  }
 }
 
 
@@ -411,23 +976,42 @@ pub enum ControlMessage {
 
 
 /// A websocket message that we tried to parse.
-type ParsedMessage<B, Q, T> =
-  MessageResult<Result<DataMessage<B, Q, T>, JsonError>, WebSocketError>;
+type ParsedMessage<B, Q, T, E = JsonError> =
+  MessageResult<Result<DataMessage<B, Q, T>, E>, WebSocketError>;
 
-impl<B, Q, T> subscribe::Message for ParsedMessage<B, Q, T> {
-  type UserMessage = Result<Result<Data<B, Q, T>, JsonError>, WebSocketError>;
+impl<B, Q, T, E> subscribe::Message for ParsedMessage<B, Q, T, E> {
+  type UserMessage = Result<Result<Data<B, Q, T>, E>, WebSocketError>;
   type ControlMessage = ControlMessage;
 
   fn classify(self) -> subscribe::Classification<Self::UserMessage, Self::ControlMessage> {
     match self {
       MessageResult::Ok(Ok(message)) => match message {
         DataMessage::Bar(bar) => subscribe::Classification::UserMessage(Ok(Ok(Data::Bar(bar)))),
+        DataMessage::DailyBar(bar) => {
+          subscribe::Classification::UserMessage(Ok(Ok(Data::DailyBar(bar))))
+        },
+        DataMessage::UpdatedBar(bar) => {
+          subscribe::Classification::UserMessage(Ok(Ok(Data::UpdatedBar(bar))))
+        },
         DataMessage::Quote(quote) => {
           subscribe::Classification::UserMessage(Ok(Ok(Data::Quote(quote))))
         },
         DataMessage::Trade(trade) => {
           subscribe::Classification::UserMessage(Ok(Ok(Data::Trade(trade))))
         },
+        DataMessage::Correction(correction) => {
+          subscribe::Classification::UserMessage(Ok(Ok(Data::Correction(correction))))
+        },
+        DataMessage::CancelError(cancel_error) => {
+          subscribe::Classification::UserMessage(Ok(Ok(Data::CancelError(cancel_error))))
+        },
+        DataMessage::Luld(luld) => subscribe::Classification::UserMessage(Ok(Ok(Data::Luld(luld)))),
+        DataMessage::Status(status) => {
+          subscribe::Classification::UserMessage(Ok(Ok(Data::Status(status))))
+        },
+        DataMessage::Orderbook(orderbook) => {
+          subscribe::Classification::UserMessage(Ok(Ok(Data::Orderbook(orderbook))))
+        },
         DataMessage::Subscription(data) => {
           subscribe::Classification::ControlMessage(ControlMessage::Subscription(data))
         },
@@ -435,8 +1019,9 @@ impl<B, Q, T> subscribe::Message for ParsedMessage<B, Q, T> {
         DataMessage::Error(error) => {
           subscribe::Classification::ControlMessage(ControlMessage::Error(error))
         },
+        DataMessage::Unknown => subscribe::Classification::UserMessage(Ok(Ok(Data::Unknown))),
       },
-      // JSON errors are directly passed through.
+      // Decoding errors are directly passed through.
       MessageResult::Ok(Err(err)) => subscribe::Classification::UserMessage(Ok(Err(err))),
       // WebSocket errors are also directly pushed through.
       MessageResult::Err(err) => subscribe::Classification::UserMessage(Err(err)),
@@ -445,7 +1030,7 @@ impl<B, Q, T> subscribe::Message for ParsedMessage<B, Q, T> {
 
   #[inline]
   fn is_error(user_message: &Self::UserMessage) -> bool {
-    // Both outer `WebSocketError` and inner `JsonError` errors
+    // Both outer `WebSocketError` and inner decoding errors
     // constitute errors in our sense. Note, however, that an API error
     // does not. It's just a regular control message from our
     // perspective.
@@ -471,6 +1056,61 @@ where
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub struct SymbolList(#[serde(deserialize_with = "normalized_from_str")] Cow<'static, [Symbol]>);
 
+impl SymbolList {
+  /// Add a symbol to the list, if it is not already contained in it.
+  ///
+  /// The list is kept normalized, without requiring a full sort.
+  /// Returns `true` if the symbol was added.
+  pub fn add<S>(&mut self, symbol: S) -> bool
+  where
+    S: Into<Symbol>,
+  {
+    let symbol = symbol.into();
+    match self.0.binary_search(&symbol) {
+      Ok(_) => false,
+      Err(index) => {
+        let () = self.0.to_mut().insert(index, symbol);
+        debug_assert!(is_normalized(&self.0));
+        true
+      },
+    }
+  }
+
+  /// Remove a symbol from the list.
+  ///
+  /// Returns `true` if the symbol was contained in the list.
+  pub fn remove(&mut self, symbol: &str) -> bool {
+    match self.0.binary_search_by(|other| other.as_ref().cmp(symbol)) {
+      Ok(index) => {
+        let _symbol = self.0.to_mut().remove(index);
+        true
+      },
+      Err(_) => false,
+    }
+  }
+
+  /// Check whether the list contains the provided symbol.
+  #[inline]
+  pub fn contains(&self, symbol: &str) -> bool {
+    self
+      .0
+      .binary_search_by(|other| other.as_ref().cmp(symbol))
+      .is_ok()
+  }
+
+  /// Create a list of the symbols contained in `self` but not in
+  /// `other`.
+  fn difference(&self, other: &SymbolList) -> SymbolList {
+    // Filtering retains the order, so the result stays normalized.
+    let symbols = self
+      .iter()
+      .filter(|symbol| !other.contains(symbol))
+      .cloned()
+      .collect::<Vec<_>>();
+    Self(Cow::from(symbols))
+  }
+}
+
 impl Deref for SymbolList {
   type Target = [Symbol];
 
@@ -568,6 +1208,22 @@ impl Symbols {
   }
 }
 
+impl Symbols {
+  /// Determine the symbols to subscribe to and to unsubscribe from,
+  /// respectively, in order to get from `self` to `desired`.
+  fn diff(&self, desired: &Symbols) -> (Symbols, Symbols) {
+    match (self, desired) {
+      (Self::All, Self::All) => (Self::default(), Self::default()),
+      (Self::List(..), Self::All) => (Self::All, Self::default()),
+      (Self::All, Self::List(desired)) => (Self::List(desired.clone()), Self::All),
+      (Self::List(current), Self::List(desired)) => (
+        Self::List(desired.difference(current)),
+        Self::List(current.difference(desired)),
+      ),
+    }
+  }
+}
+
 impl Default for Symbols {
   fn default() -> Self {
     Self::List(SymbolList::from([]))
@@ -575,21 +1231,264 @@ impl Default for Symbols {
 }
 
 
-/// A type defining the market data a client intends to subscribe to.
-#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
-pub struct MarketData {
-  /// The aggregate bars to subscribe to.
-  #[serde(default)]
-  pub bars: Symbols,
-  /// The quotes to subscribe to.
+/// An enumeration of the channels market data can be subscribed to,
+/// corresponding to the members of [`MarketData`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum Channel {
+  /// Aggregate bars.
+  Bars,
+  /// Quotes.
+  Quotes,
+  /// Trades.
+  Trades,
+  /// Limit up-limit down (LULD) bands.
+  Lulds,
+  /// Trading statuses.
+  Statuses,
+  /// Daily aggregate bars.
+  DailyBars,
+  /// Updated aggregate bars.
+  UpdatedBars,
+  /// Order books.
+  Orderbooks,
+}
+
+impl AsRef<str> for Channel {
+  #[inline]
+  fn as_ref(&self) -> &'static str {
+    match *self {
+      Channel::Bars => "bars",
+      Channel::Quotes => "quotes",
+      Channel::Trades => "trades",
+      Channel::Lulds => "lulds",
+      Channel::Statuses => "statuses",
+      Channel::DailyBars => "dailyBars",
+      Channel::UpdatedBars => "updatedBars",
+      Channel::Orderbooks => "orderbooks",
+    }
+  }
+}
+
+impl Display for Channel {
+  #[inline]
+  fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+    fmt.write_str(self.as_ref())
+  }
+}
+
+
+/// A type defining the market data a client intends to subscribe to.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct MarketData {
+  /// The aggregate bars to subscribe to.
+  #[serde(default)]
+  pub bars: Symbols,
+  /// The quotes to subscribe to.
   #[serde(default)]
   pub quotes: Symbols,
   /// The trades to subscribe to.
+  ///
+  /// Subscribing to trades also subscribes to trade corrections and
+  /// cancellations for the same symbols.
   #[serde(default)]
   pub trades: Symbols,
+  /// The limit up-limit down (LULD) bands to subscribe to.
+  #[serde(default, skip_serializing_if = "Symbols::is_empty")]
+  pub lulds: Symbols,
+  /// The trading statuses (e.g., halts and resumptions) to subscribe
+  /// to.
+  #[serde(default, skip_serializing_if = "Symbols::is_empty")]
+  pub statuses: Symbols,
+  /// The daily aggregate bars to subscribe to.
+  #[serde(
+    rename = "dailyBars",
+    default,
+    skip_serializing_if = "Symbols::is_empty"
+  )]
+  pub daily_bars: Symbols,
+  /// The updated aggregate bars to subscribe to.
+  #[serde(
+    rename = "updatedBars",
+    default,
+    skip_serializing_if = "Symbols::is_empty"
+  )]
+  pub updated_bars: Symbols,
+  /// The order books to subscribe to.
+  ///
+  /// Order books are only available for crypto currency pairs, using
+  /// the crypto stream.
+  #[serde(default, skip_serializing_if = "Symbols::is_empty")]
+  pub orderbooks: Symbols,
 }
 
 impl MarketData {
+  /// Check whether the object represents no market data at all.
+  fn is_empty(&self) -> bool {
+    self.bars.is_empty()
+      && self.quotes.is_empty()
+      && self.trades.is_empty()
+      && self.lulds.is_empty()
+      && self.statuses.is_empty()
+      && self.daily_bars.is_empty()
+      && self.updated_bars.is_empty()
+      && self.orderbooks.is_empty()
+  }
+
+  /// Retrieve the symbols of all channels, alongside the channel they
+  /// belong to.
+  fn channels(&self) -> [(Channel, &Symbols); 8] {
+    [
+      (Channel::Bars, &self.bars),
+      (Channel::Quotes, &self.quotes),
+      (Channel::Trades, &self.trades),
+      (Channel::Lulds, &self.lulds),
+      (Channel::Statuses, &self.statuses),
+      (Channel::DailyBars, &self.daily_bars),
+      (Channel::UpdatedBars, &self.updated_bars),
+      (Channel::Orderbooks, &self.orderbooks),
+    ]
+  }
+
+  /// Count the distinct symbols referenced across all channels.
+  ///
+  /// Applied to the subscriptions confirmed by the server (as reported
+  /// by [`Subscription::subscriptions`]), the count can be compared
+  /// against the symbol limit of the market data plan in use.
+  /// [`Symbols::All`] subscriptions are not included in the count.
+  pub fn symbol_count(&self) -> usize {
+    self.listed_symbols().collect::<HashSet<_>>().len()
+  }
+
+  /// Retrieve the symbols explicitly listed on any of the channels,
+  /// potentially containing duplicates.
+  fn listed_symbols(&self) -> impl Iterator<Item = &Symbol> {
+    self
+      .channels()
+      .into_iter()
+      .filter_map(|(_, symbols)| match symbols {
+        Symbols::List(list) => Some(list.iter()),
+        Symbols::All => None,
+      })
+      .flatten()
+  }
+
+  /// Check whether the object references all symbols on any channel
+  /// other than the provided ones, reporting the first such channel.
+  fn all_symbols_outside(&self, permitted: &[Channel]) -> Option<Channel> {
+    self
+      .channels()
+      .into_iter()
+      .find(|(channel, symbols)| **symbols == Symbols::All && !permitted.contains(channel))
+      .map(|(channel, _)| channel)
+  }
+
+  /// Determine the market data to subscribe to and to unsubscribe
+  /// from, respectively, in order to get from `self` to `desired`.
+  fn diff(&self, desired: &MarketData) -> (MarketData, MarketData) {
+    let mut subscribe = MarketData::default();
+    let mut unsubscribe = MarketData::default();
+
+    (subscribe.bars, unsubscribe.bars) = self.bars.diff(&desired.bars);
+    (subscribe.quotes, unsubscribe.quotes) = self.quotes.diff(&desired.quotes);
+    (subscribe.trades, unsubscribe.trades) = self.trades.diff(&desired.trades);
+    (subscribe.lulds, unsubscribe.lulds) = self.lulds.diff(&desired.lulds);
+    (subscribe.statuses, unsubscribe.statuses) = self.statuses.diff(&desired.statuses);
+    (subscribe.daily_bars, unsubscribe.daily_bars) = self.daily_bars.diff(&desired.daily_bars);
+    (subscribe.updated_bars, unsubscribe.updated_bars) =
+      self.updated_bars.diff(&desired.updated_bars);
+    (subscribe.orderbooks, unsubscribe.orderbooks) = self.orderbooks.diff(&desired.orderbooks);
+
+    (subscribe, unsubscribe)
+  }
+
+  /// Split the market data into chunks, each referencing at most
+  /// `max` symbols.
+  ///
+  /// A [`Symbols::All`] subscription counts as a single symbol. At
+  /// least one (potentially empty) chunk is always produced.
+  fn chunks(&self, max: usize) -> Vec<MarketData> {
+    let max = max.max(1);
+    let mut chunks = Vec::new();
+    let mut chunk = <[Symbols; 8]>::default();
+    let mut count = 0;
+
+    for (index, symbols) in self.clone().into_array().into_iter().enumerate() {
+      match symbols {
+        Symbols::All => {
+          if count >= max {
+            let () = chunks.push(Self::from_array(take(&mut chunk)));
+            count = 0;
+          }
+          chunk[index] = Symbols::All;
+          count += 1;
+        },
+        Symbols::List(list) => {
+          let mut remaining = &list[..];
+          while !remaining.is_empty() {
+            if count >= max {
+              let () = chunks.push(Self::from_array(take(&mut chunk)));
+              count = 0;
+            }
+            let (head, tail) = remaining.split_at((max - count).min(remaining.len()));
+            // A contiguous slice of a normalized list is normalized
+            // as well.
+            chunk[index] = Symbols::List(SymbolList(Cow::from(head.to_vec())));
+            count += head.len();
+            remaining = tail;
+          }
+        },
+      }
+    }
+
+    if count > 0 || chunks.is_empty() {
+      let () = chunks.push(Self::from_array(chunk));
+    }
+    chunks
+  }
+
+  /// Convert the market data into an array of its members.
+  fn into_array(self) -> [Symbols; 8] {
+    let Self {
+      bars,
+      quotes,
+      trades,
+      lulds,
+      statuses,
+      daily_bars,
+      updated_bars,
+      orderbooks,
+    } = self;
+
+    [
+      bars,
+      quotes,
+      trades,
+      lulds,
+      statuses,
+      daily_bars,
+      updated_bars,
+      orderbooks,
+    ]
+  }
+
+  /// Create market data from an array as produced by
+  /// [`into_array`][Self::into_array].
+  fn from_array(array: [Symbols; 8]) -> Self {
+    let [bars, quotes, trades, lulds, statuses, daily_bars, updated_bars, orderbooks] = array;
+
+    Self {
+      bars,
+      quotes,
+      trades,
+      lulds,
+      statuses,
+      daily_bars,
+      updated_bars,
+      orderbooks,
+    }
+  }
+
   /// A convenience function for setting the [`bars`][MarketData::bars]
   /// member.
   #[inline]
@@ -619,6 +1518,56 @@ impl MarketData {
   {
     self.trades = Symbols::List(symbols.into());
   }
+
+  /// A convenience function for setting the [`lulds`][MarketData::lulds]
+  /// member.
+  #[inline]
+  pub fn set_lulds<S>(&mut self, symbols: S)
+  where
+    S: Into<SymbolList>,
+  {
+    self.lulds = Symbols::List(symbols.into());
+  }
+
+  /// A convenience function for setting the
+  /// [`statuses`][MarketData::statuses] member.
+  #[inline]
+  pub fn set_statuses<S>(&mut self, symbols: S)
+  where
+    S: Into<SymbolList>,
+  {
+    self.statuses = Symbols::List(symbols.into());
+  }
+
+  /// A convenience function for setting the
+  /// [`daily_bars`][MarketData::daily_bars] member.
+  #[inline]
+  pub fn set_daily_bars<S>(&mut self, symbols: S)
+  where
+    S: Into<SymbolList>,
+  {
+    self.daily_bars = Symbols::List(symbols.into());
+  }
+
+  /// A convenience function for setting the
+  /// [`updated_bars`][MarketData::updated_bars] member.
+  #[inline]
+  pub fn set_updated_bars<S>(&mut self, symbols: S)
+  where
+    S: Into<SymbolList>,
+  {
+    self.updated_bars = Symbols::List(symbols.into());
+  }
+
+  /// A convenience function for setting the
+  /// [`orderbooks`][MarketData::orderbooks] member.
+  #[inline]
+  pub fn set_orderbooks<S>(&mut self, symbols: S)
+  where
+    S: Into<SymbolList>,
+  {
+    self.orderbooks = Symbols::List(symbols.into());
+  }
 }
 
 
@@ -656,26 +1605,109 @@ pub enum Request<'d> {
 ///   the associated [`MessageStream`] stream needs to be polled;
 ///   consider using the [`drive`] function for that purpose
 #[derive(Debug)]
-pub struct Subscription<S, B, Q, T> {
+pub struct Subscription<S, B, Q, T, E = JsonError> {
   /// Our internally used subscription object for sending control
   /// messages.
-  subscription: subscribe::Subscription<S, ParsedMessage<B, Q, T>, wrap::Message>,
+  subscription: subscribe::Subscription<S, ParsedMessage<B, Q, T, E>, wrap::Message>,
   /// The currently active individual market data subscriptions.
   subscriptions: MarketData,
+  /// The maximum number of symbols to reference in a single subscribe
+  /// or unsubscribe request.
+  max_symbols: usize,
+  /// The channels on which subscribing to all symbols is permitted, if
+  /// restricted.
+  all_permitted: Option<Vec<Channel>>,
+  /// The maximum number of distinct symbols to be subscribed to, if
+  /// limited.
+  symbol_limit: Option<usize>,
 }
 
-impl<S, B, Q, T> Subscription<S, B, Q, T> {
+impl<S, B, Q, T, E> Subscription<S, B, Q, T, E> {
   /// Create a `Subscription` object wrapping the `websocket_util` based one.
   #[inline]
-  fn new(subscription: subscribe::Subscription<S, ParsedMessage<B, Q, T>, wrap::Message>) -> Self {
+  fn new(
+    subscription: subscribe::Subscription<S, ParsedMessage<B, Q, T, E>, wrap::Message>,
+  ) -> Self {
     Self {
       subscription,
       subscriptions: MarketData::default(),
+      max_symbols: MAX_SYMBOLS_PER_REQUEST,
+      all_permitted: None,
+      symbol_limit: None,
+    }
+  }
+
+  /// Set the maximum number of symbols to reference in a single
+  /// subscribe or unsubscribe request.
+  ///
+  /// Larger requests are transparently split into multiple ones, each
+  /// of which is confirmed by the server before the next one is sent.
+  /// The default is [`MAX_SYMBOLS_PER_REQUEST`].
+  #[inline]
+  pub fn set_max_symbols_per_request(&mut self, max: usize) {
+    self.max_symbols = max.max(1);
+  }
+
+  /// Restrict subscriptions to [`Symbols::All`] to the provided
+  /// channels.
+  ///
+  /// Subscribing to all symbols, say, on the quote channel can easily
+  /// flood a process. Once restricted, requests to subscribe to all
+  /// symbols on any other channel fail with
+  /// [`SubscriptionError::AllSymbolsNotPermitted`] without being sent
+  /// to the server. By default, no restriction is in place.
+  ///
+  /// Note that the rate at which messages are received is not capped.
+  /// To bound the amount of data piling up, wrap the stream in a
+  /// [`Buffered`][crate::data::v2::buffer::Buffered] stream with one
+  /// of the dropping overflow policies.
+  #[inline]
+  pub fn permit_all_symbols(&mut self, channels: &[Channel]) {
+    self.all_permitted = Some(channels.to_vec());
+  }
+
+  /// Limit the number of distinct symbols to be subscribed to across
+  /// all channels.
+  ///
+  /// Requests that would leave more than `limit` symbols subscribed
+  /// (e.g., more than 30 on Alpaca's free plan) fail with
+  /// [`SubscriptionError::TooManySymbols`] without being sent to the
+  /// server, instead of being refused by it. [`Symbols::All`]
+  /// subscriptions do not count towards the limit. By default, no
+  /// limit is in place.
+  #[inline]
+  pub fn set_symbol_limit(&mut self, limit: usize) {
+    self.symbol_limit = Some(limit);
+  }
+
+  /// Check that subscribing to `data`, while keeping the subscriptions
+  /// in `retained`, adheres to the configured guardrails.
+  fn check_guardrails(
+    &self,
+    data: &MarketData,
+    retained: &MarketData,
+  ) -> Result<(), SubscriptionError> {
+    if let Some(permitted) = &self.all_permitted {
+      if let Some(channel) = data.all_symbols_outside(permitted) {
+        return Err(SubscriptionError::AllSymbolsNotPermitted(channel))
+      }
     }
+
+    if let Some(limit) = self.symbol_limit {
+      let count = data
+        .listed_symbols()
+        .chain(retained.listed_symbols())
+        .collect::<HashSet<_>>()
+        .len();
+      if count > limit {
+        return Err(SubscriptionError::TooManySymbols { count, limit })
+      }
+    }
+    Ok(())
   }
 }
 
-impl<S, B, Q, T> Subscription<S, B, Q, T>
+impl<S, B, Q, T, E> Subscription<S, B, Q, T, E>
 where
   S: Sink<wrap::Message> + Unpin,
 {
@@ -717,8 +1749,31 @@ where
     }
   }
 
-  /// Handle sending of a subscribe or unsubscribe request.
+  /// Handle sending of a subscribe or unsubscribe request, split into
+  /// batches of at most `max_symbols` symbols.
+  ///
+  /// Each batch is confirmed by the server before the next one is
+  /// sent. Because every confirmation reports the complete state, the
+  /// tracked subscriptions stay accurate even if a later batch fails.
   async fn subscribe_unsubscribe(
+    &mut self,
+    data: &MarketData,
+    request: fn(Cow<'static, MarketData>) -> Request<'static>,
+  ) -> Result<Result<(), Error>, S::Error> {
+    for chunk in data.chunks(self.max_symbols) {
+      let result = self
+        .subscribe_unsubscribe_one(&request(Cow::Owned(chunk)))
+        .await?;
+      if result.is_err() {
+        return Ok(result)
+      }
+    }
+    Ok(Ok(()))
+  }
+
+  /// Send a single subscribe or unsubscribe request and await its
+  /// confirmation.
+  async fn subscribe_unsubscribe_one(
     &mut self,
     request: &Request<'_>,
   ) -> Result<Result<(), Error>, S::Error> {
@@ -735,6 +1790,9 @@ where
           self.subscriptions = data;
           Ok(Ok(()))
         },
+        Ok(ControlMessage::Error(error)) if error.code == CODE_SYMBOL_LIMIT_EXCEEDED => Ok(Err(
+          Error::Subscription(SubscriptionError::SymbolLimitExceeded(error)),
+        )),
         Ok(ControlMessage::Error(error)) => Ok(Err(Error::Str(
           format!("failed to subscribe: {error}").into(),
         ))),
@@ -754,10 +1812,20 @@ where
   /// Contained in `subscribe` are the *additional* symbols to subscribe
   /// to. Use the [`unsubscribe`][Self::unsubscribe] method to
   /// unsubscribe from receiving data for certain symbols.
-  #[inline]
+  ///
+  /// The subscriptions resulting from the request are checked against
+  /// the guardrails configured via
+  /// [`permit_all_symbols`][Self::permit_all_symbols] and
+  /// [`set_symbol_limit`][Self::set_symbol_limit] as a whole, before
+  /// sending anything.
   pub async fn subscribe(&mut self, subscribe: &MarketData) -> Result<Result<(), Error>, S::Error> {
-    let request = Request::Subscribe(Cow::Borrowed(subscribe));
-    self.subscribe_unsubscribe(&request).await
+    if let Err(err) = self.check_guardrails(subscribe, &self.subscriptions) {
+      return Ok(Err(Error::Subscription(err)))
+    }
+
+    self
+      .subscribe_unsubscribe(subscribe, Request::Subscribe)
+      .await
   }
 
   /// Unsubscribe from receiving market data for the provided symbols.
@@ -769,31 +1837,107 @@ where
     &mut self,
     unsubscribe: &MarketData,
   ) -> Result<Result<(), Error>, S::Error> {
-    let request = Request::Unsubscribe(Cow::Borrowed(unsubscribe));
-    self.subscribe_unsubscribe(&request).await
+    self
+      .subscribe_unsubscribe(unsubscribe, Request::Unsubscribe)
+      .await
+  }
+
+  /// Adjust the market data subscription to match `subscriptions`.
+  ///
+  /// In contrast to [`subscribe`][Self::subscribe] and
+  /// [`unsubscribe`][Self::unsubscribe], `subscriptions` describes the
+  /// complete desired state. The difference to the currently active
+  /// subscriptions is determined and only the necessary unsubscribe
+  /// and subscribe requests are sent, if any. The desired state is
+  /// checked against the configured guardrails as a whole, before
+  /// sending anything.
+  pub async fn set_subscriptions(
+    &mut self,
+    subscriptions: &MarketData,
+  ) -> Result<Result<(), Error>, S::Error> {
+    if let Err(err) = self.check_guardrails(subscriptions, &MarketData::default()) {
+      return Ok(Err(Error::Subscription(err)))
+    }
+
+    let (subscribe, unsubscribe) = self.subscriptions.diff(subscriptions);
+
+    // Unsubscribe first, to properly handle transitions from all
+    // symbols to a list of them.
+    if !unsubscribe.is_empty() {
+      let result = self.unsubscribe(&unsubscribe).await?;
+      if result.is_err() {
+        return Ok(result)
+      }
+    }
+
+    if !subscribe.is_empty() {
+      self
+        .subscribe_unsubscribe(&subscribe, Request::Subscribe)
+        .await
+    } else {
+      Ok(Ok(()))
+    }
+  }
+
+  /// Subscribe to all market data contained in `snapshot`.
+  ///
+  /// This method is meant to be used for restoring the subscriptions
+  /// captured via [`snapshot`][Self::snapshot] on a freshly
+  /// established connection, e.g., after a manual reconnect. In
+  /// contrast to [`set_subscriptions`][Self::set_subscriptions], the
+  /// complete snapshot is sent to the server irrespective of the
+  /// subscriptions tracked locally, and nothing is unsubscribed. As
+  /// with [`subscribe`][Self::subscribe], the snapshot is checked
+  /// against the configured guardrails before sending anything.
+  pub async fn resubscribe_all(
+    &mut self,
+    snapshot: &MarketData,
+  ) -> Result<Result<(), Error>, S::Error> {
+    if snapshot.is_empty() {
+      return Ok(Ok(()))
+    }
+    self.subscribe(snapshot).await
   }
 
   /// Inquire the currently active individual market data subscriptions.
+  ///
+  /// The reported state is the one last confirmed by the server, i.e.,
+  /// it reflects both subscribe and unsubscribe operations.
   #[inline]
   pub fn subscriptions(&self) -> &MarketData {
     &self.subscriptions
   }
+
+  /// Export the currently active market data subscriptions.
+  ///
+  /// The returned object can be persisted or handed to
+  /// [`resubscribe_all`][Self::resubscribe_all] of a new subscription
+  /// to restore the state.
+  #[inline]
+  pub fn snapshot(&self) -> MarketData {
+    self.subscriptions.clone()
+  }
 }
 
 
-type ParseFn<B, Q, T> = fn(
+type ParseFn<B, Q, T, E> = fn(
   Result<wrap::Message, WebSocketError>,
-) -> Result<Result<Vec<DataMessage<B, Q, T>>, JsonError>, WebSocketError>;
-type MapFn<B, Q, T> =
-  fn(Result<Result<DataMessage<B, Q, T>, JsonError>, WebSocketError>) -> ParsedMessage<B, Q, T>;
-type Stream<B, Q, T> = Map<
-  Unfold<
-    Map<Wrapper<WebSocketStream<MaybeTlsStream<TcpStream>>>, ParseFn<B, Q, T>>,
-    DataMessage<B, Q, T>,
-    JsonError,
-  >,
-  MapFn<B, Q, T>,
+) -> Result<Result<Vec<DataMessage<B, Q, T>>, E>, WebSocketError>;
+type MapFn<B, Q, T, E> =
+  fn(Result<Result<DataMessage<B, Q, T>, E>, WebSocketError>) -> ParsedMessage<B, Q, T, E>;
+type Stream<B, Q, T, E> = Map<
+  Unfold<Map<Wrapper<WebSocket>, ParseFn<B, Q, T, E>>, DataMessage<B, Q, T>, E>,
+  MapFn<B, Q, T, E>,
 >;
+type Connected<B, Q, T, E = JsonError> = (AnyStream<B, Q, T, E>, AnySubscription<B, Q, T, E>);
+
+/// The stream of realtime market data, as connected to for any source.
+pub(crate) type AnyStream<B, Q, T, E = JsonError> =
+  Fuse<MessageStream<SplitStream<Stream<B, Q, T, E>>, ParsedMessage<B, Q, T, E>>>;
+/// The subscription controlling a realtime market data stream, as
+/// connected to for any source.
+pub(crate) type AnySubscription<B, Q, T, E = JsonError> =
+  Subscription<SplitSink<Stream<B, Q, T, E>, wrap::Message>, B, Q, T, E>;
 
 
 /// A type used for requesting a subscription to real time market
@@ -802,48 +1946,111 @@ type Stream<B, Q, T> = Map<
 /// The bar (`B`), quote (`Q`), and trade (`T`) types used can be
 /// overwritten to extend/customize the default types ([`Bar`],
 /// [`Quote`], and [`Trade`], respectively) that are provided by the
-/// library.
+/// library. The wire format (`F`) defaults to [`Json`], but
+/// [`MsgPack`] may be used instead.
 #[derive(Debug)]
-pub struct RealtimeData<S, B = Bar, Q = Quote, T = Trade> {
+pub struct RealtimeData<S, B = Bar, Q = Quote, T = Trade, F = Json> {
   /// Phantom data to make sure that we "use" `S`.
-  _phantom: PhantomData<(S, B, Q, T)>,
+  _phantom: PhantomData<(S, B, Q, T, F)>,
 }
 
 #[async_trait]
-impl<S, B, Q, T> Subscribable for RealtimeData<S, B, Q, T>
+impl<S, B, Q, T, F> Subscribable for RealtimeData<S, B, Q, T, F>
 where
   S: Source,
+  F: Format,
   B: Send + Unpin + Debug + DeserializeOwned,
   Q: Send + Unpin + Debug + DeserializeOwned,
   T: Send + Unpin + Debug + DeserializeOwned,
 {
   type Input = ApiInfo;
-  type Subscription = Subscription<SplitSink<Stream<B, Q, T>, wrap::Message>, B, Q, T>;
-  type Stream = Fuse<MessageStream<SplitStream<Stream<B, Q, T>>, ParsedMessage<B, Q, T>>>;
+  type Subscription = AnySubscription<B, Q, T, F::Error>;
+  type Stream = AnyStream<B, Q, T, F::Error>;
 
   async fn connect(api_info: &Self::Input) -> Result<(Self::Stream, Self::Subscription), Error> {
-    fn parse<B, Q, T>(
+    Self::connect_with(api_info, &ConnectionConfig::default()).await
+  }
+
+  fn connect_with<'a>(
+    api_info: &'a Self::Input,
+    config: &'a ConnectionConfig,
+  ) -> BoxFuture<'a, Result<(Self::Stream, Self::Subscription), Error>>
+  where
+    Self: 'a,
+  {
+    connect::<B, Q, T, F>(api_info, S::source(), config)
+  }
+}
+
+
+/// Retrieve the source to stream realtime data of the given feed from.
+fn feed_source(feed: Feed) -> SourceVariant {
+  // Feeds are plain enum variants, for which retrieving the name never
+  // fails.
+  let component = to_variant_name(&feed).unwrap();
+  SourceVariant::PathComponent(component)
+}
+
+
+/// Connect to the realtime data stream of the provided feed, using
+/// the default data types and wire format.
+pub(crate) fn connect_feed<'a>(
+  api_info: &'a ApiInfo,
+  feed: Feed,
+  config: &'a ConnectionConfig,
+) -> BoxFuture<'a, Result<Connected<Bar, Quote, Trade>, Error>> {
+  connect::<Bar, Quote, Trade, Json>(api_info, feed_source(feed), config)
+}
+
+
+/// Connect to the realtime data stream at the provided URL, using the
+/// default data types and wire format.
+pub(crate) fn connect_url<'a>(
+  api_info: &'a ApiInfo,
+  url: &Url,
+  config: &'a ConnectionConfig,
+) -> BoxFuture<'a, Result<Connected<Bar, Quote, Trade>, Error>> {
+  let source = SourceVariant::Url(url.to_string());
+  connect::<Bar, Quote, Trade, Json>(api_info, source, config)
+}
+
+
+/// Connect to the realtime data stream at the provided source.
+#[allow(clippy::type_complexity)]
+fn connect<'a, B, Q, T, F>(
+  api_info: &'a ApiInfo,
+  source: SourceVariant,
+  config: &'a ConnectionConfig,
+) -> BoxFuture<'a, Result<Connected<B, Q, T, F::Error>, Error>>
+where
+  F: Format,
+  B: Send + Unpin + Debug + DeserializeOwned + 'a,
+  Q: Send + Unpin + Debug + DeserializeOwned + 'a,
+  T: Send + Unpin + Debug + DeserializeOwned + 'a,
+{
+  async move {
+    #[allow(clippy::type_complexity)]
+    fn parse<F, B, Q, T>(
       result: Result<wrap::Message, WebSocketError>,
-    ) -> Result<Result<Vec<DataMessage<B, Q, T>>, JsonError>, WebSocketError>
+    ) -> Result<Result<Vec<DataMessage<B, Q, T>>, F::Error>, WebSocketError>
     where
+      F: Format,
       B: DeserializeOwned,
       Q: DeserializeOwned,
       T: DeserializeOwned,
     {
       result.map(|message| match message {
-        wrap::Message::Text(string) => json_from_str::<Vec<DataMessage<B, Q, T>>>(&string),
-        wrap::Message::Binary(data) => json_from_slice::<Vec<DataMessage<B, Q, T>>>(&data),
+        wrap::Message::Text(string) => {
+          json_from_str::<Vec<DataMessage<B, Q, T>>>(&string).map_err(F::Error::from)
+        },
+        wrap::Message::Binary(data) => F::decode::<Vec<DataMessage<B, Q, T>>>(&data),
       })
     }
 
-    let ApiInfo {
-      data_stream_base_url: url,
-      key_id,
-      secret,
-      ..
-    } = api_info;
+    let url = &api_info.data_stream_base_url;
+    let (key_id, secret) = api_info.data_credentials();
 
-    let url = match S::source() {
+    let url = match source {
       SourceVariant::PathComponent(component) => {
         let mut url = url.clone();
         url.set_path(&format!("v2/{}", component));
@@ -852,24 +2059,31 @@ where
       SourceVariant::Url(url) => Url::parse(&url)?,
     };
 
+    let headers = F::content_type()
+      .map(|content_type| vec![("Content-Type", content_type)])
+      .unwrap_or_default();
+
     let stream = Unfold::new(
-      connect(&url)
+      connect_with_headers(&url, &headers, config)
         .await?
-        .map(parse::<B, Q, T> as ParseFn<_, _, _>),
+        .map(parse::<F, B, Q, T> as ParseFn<_, _, _, _>),
     )
-    .map(MessageResult::from as MapFn<B, Q, T>);
+    .map(MessageResult::from as MapFn<B, Q, T, F::Error>);
     let (send, recv) = stream.split();
     let (stream, subscription) = subscribe::subscribe(recv, send);
     let mut stream = stream.fuse();
     let mut subscription = Subscription::new(subscription);
 
+    let handshake_timeout = config.handshake_timeout();
     let connect = subscription.subscription.read().boxed();
-    let message = drive(connect, &mut stream).await.map_err(|result| {
-      result
-        .map(|result| Error::Json(result.unwrap_err()))
-        .map_err(Error::WebSocket)
-        .unwrap_or_else(|err| err)
-    })?;
+    let message = drive_with_timeout(connect, &mut stream, handshake_timeout)
+      .await?
+      .map_err(|result| {
+        result
+          .map(|result| result.unwrap_err().into())
+          .map_err(Error::WebSocket)
+          .unwrap_or_else(|err| err)
+      })?;
 
     match message {
       Some(Ok(ControlMessage::Success)) => (),
@@ -887,15 +2101,18 @@ where
     }
 
     let authenticate = subscription.authenticate(key_id, secret).boxed();
-    let () = drive(authenticate, &mut stream).await.map_err(|result| {
-      result
-        .map(|result| Error::Json(result.unwrap_err()))
-        .map_err(Error::WebSocket)
-        .unwrap_or_else(|err| err)
-    })???;
+    let () = drive_with_timeout(authenticate, &mut stream, handshake_timeout)
+      .await?
+      .map_err(|result| {
+        result
+          .map(|result| result.unwrap_err().into())
+          .map_err(Error::WebSocket)
+          .unwrap_or_else(|err| err)
+      })???;
 
     Ok((stream, subscription))
   }
+  .boxed()
 }
 
 
@@ -904,11 +2121,13 @@ where
 mod tests {
   use super::*;
 
+  use std::ptr;
   use std::str::FromStr;
   use std::time::Duration;
 
   use chrono::DateTime;
 
+  use futures::channel::oneshot::channel;
   use futures::SinkExt as _;
   use futures::TryStreamExt as _;
 
@@ -926,6 +2145,7 @@ mod tests {
   use websocket_util::tungstenite::Message;
 
   use crate::api::API_BASE_URL;
+  use crate::data::v2::intern::Interned;
   use crate::websocket::test::mock_stream;
   use crate::Client;
 
@@ -949,12 +2169,42 @@ mod tests {
     assert_ne!(format!("{:?}", SIP::source()), "");
   }
 
+  /// Check that the `Test` source selects the test stream.
+  #[test]
+  fn test_source() {
+    match Test::source() {
+      SourceVariant::PathComponent(component) => assert_eq!(component, "test"),
+      source => panic!("unexpected source: {source:?}"),
+    }
+  }
+
+  /// Check that we map feeds to the expected stream sources.
+  #[test]
+  fn feed_sources() {
+    let feeds = [
+      (Feed::IEX, "iex"),
+      (Feed::SIP, "sip"),
+      (Feed::DelayedSip, "delayed_sip"),
+    ];
+
+    for (feed, expected) in feeds {
+      match feed_source(feed) {
+        SourceVariant::PathComponent(component) => assert_eq!(component, expected),
+        source => panic!("unexpected source: {source:?}"),
+      }
+    }
+  }
+
   /// Exercise the various `is_*` methods of the `Data` enum.
   #[test]
   fn data_classification() {
     assert!(Data::<(), Quote, Trade>::Bar(()).is_bar());
+    assert!(Data::<(), Quote, Trade>::DailyBar(()).is_daily_bar());
+    assert!(Data::<(), Quote, Trade>::UpdatedBar(()).is_updated_bar());
+    assert!(!Data::<(), Quote, Trade>::UpdatedBar(()).is_bar());
     assert!(Data::<Bar, (), Trade>::Quote(()).is_quote());
     assert!(Data::<Bar, Quote, ()>::Trade(()).is_trade());
+    assert!(!Data::<Bar, Quote, ()>::Trade(()).is_correction());
   }
 
   /// Test that the [`Symbols::is_empty`] method works as expected.
@@ -977,6 +2227,7 @@ mod tests {
   "l": 388.975,
   "c": 389.12,
   "v": 49378,
+  "n": 571,
   "t": "2021-02-22T19:15:00Z"
 }"#;
 
@@ -991,6 +2242,7 @@ mod tests {
     assert_eq!(bar.low_price, Num::new(388975, 1000));
     assert_eq!(bar.close_price, Num::new(38912, 100));
     assert_eq!(bar.volume, Num::from(49378));
+    assert_eq!(bar.trade_count, Some(571));
     assert_eq!(
       bar.timestamp,
       DateTime::<Utc>::from_str("2021-02-22T19:15:00Z").unwrap()
@@ -1002,6 +2254,37 @@ mod tests {
     );
   }
 
+  /// Check that daily and updated bars are decoded into the respective
+  /// variants, using the custom bar type provided.
+  #[test]
+  fn deserialize_daily_updated_bar() {
+    /// A custom bar type only capturing the symbol.
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct SymbolBar {
+      #[serde(rename = "S")]
+      symbol: String,
+    }
+
+    let json = r#"[
+  {"T":"d","S":"SPY","o":388.985,"h":389.13,"l":388.975,"c":389.12,"v":49378,"t":"2021-02-22T05:00:00Z"},
+  {"T":"u","S":"AAPL","o":125.5,"h":125.9,"l":125.4,"c":125.8,"v":1200,"t":"2021-02-22T19:15:00Z"}
+]"#;
+
+    let messages = json_from_str::<Vec<DataMessage<SymbolBar>>>(json).unwrap();
+    assert_eq!(
+      messages[0],
+      DataMessage::DailyBar(SymbolBar {
+        symbol: "SPY".to_string()
+      })
+    );
+    assert_eq!(
+      messages[1],
+      DataMessage::UpdatedBar(SymbolBar {
+        symbol: "AAPL".to_string()
+      })
+    );
+  }
+
   /// Check that we can serialize and deserialize the
   /// [`DataMessage::Quote`] variant.
   #[test]
@@ -1242,6 +2525,101 @@ mod tests {
     );
   }
 
+  /// Check that we can decode detailed quotes and trades.
+  #[test]
+  fn serialize_deserialize_detailed_quote_trade() {
+    let json: &str = r#"{
+  "T": "q",
+  "S": "NVDA",
+  "bx": "P",
+  "bp": 258.8,
+  "bs": 2,
+  "ax": "A",
+  "ap": 259.99,
+  "as": 5,
+  "c": ["R"],
+  "z": "C",
+  "t": "2022-01-18T23:09:42.151875584Z"
+}"#;
+
+    type Message = DataMessage<Bar, super::DetailedQuote, super::DetailedTrade>;
+
+    let message = json_from_str::<Message>(json).unwrap();
+    let quote = match &message {
+      DataMessage::Quote(quote) => quote,
+      _ => panic!("Decoded unexpected message variant: {message:?}"),
+    };
+    assert_eq!(quote.bid_exchange, "P");
+    assert_eq!(quote.ask_exchange, "A");
+    assert_eq!(quote.tape, Tape::C);
+    assert_eq!(
+      quote.quote_conditions().collect::<Vec<_>>(),
+      vec![QuoteCondition::Regular]
+    );
+    assert_eq!(
+      json_from_str::<Message>(&to_json(&message).unwrap()).unwrap(),
+      message
+    );
+
+    let json: &str = r#"{
+  "T": "t",
+  "i": 96921,
+  "S": "AAPL",
+  "x": "D",
+  "p": 126.55,
+  "s": 1,
+  "t": "2021-02-22T15:51:44.208Z",
+  "c": ["@", "I"],
+  "z": "C"
+}"#;
+
+    let message = json_from_str::<Message>(json).unwrap();
+    let trade = match &message {
+      DataMessage::Trade(trade) => trade,
+      _ => panic!("Decoded unexpected message variant: {message:?}"),
+    };
+    assert_eq!(trade.exchange, "D");
+    assert_eq!(trade.update, None);
+    assert_eq!(
+      trade.trade_conditions().collect::<Vec<_>>(),
+      vec![TradeCondition::RegularSale, TradeCondition::OddLot]
+    );
+    assert_eq!(
+      json_from_str::<Message>(&to_json(&message).unwrap()).unwrap(),
+      message
+    );
+  }
+
+  /// Check that we can deserialize trades using interned strings.
+  #[test]
+  fn deserialize_interned_trade() {
+    let json = r#"[
+  {"T":"t","i":1,"S":"AAPL","x":"D","p":126.55,"s":1,"t":"2021-02-22T15:51:44.208Z","c":["@"],"z":"C"},
+  {"T":"t","i":2,"S":"AAPL","x":"D","p":126.56,"s":2,"t":"2021-02-22T15:51:44.209Z","c":["@"],"z":"C"}
+]"#;
+
+    type Message = DataMessage<Bar<Interned>, Quote<Interned>, super::DetailedTrade<Interned>>;
+
+    let messages = json_from_str::<Vec<Message>>(json).unwrap();
+    let trades = messages
+      .iter()
+      .map(|message| match message {
+        DataMessage::Trade(trade) => trade,
+        _ => panic!("Decoded unexpected message variant: {message:?}"),
+      })
+      .collect::<Vec<_>>();
+    assert_eq!(trades[0].symbol, "AAPL");
+    assert_eq!(trades[0].symbol, trades[1].symbol);
+    assert!(ptr::eq(
+      trades[0].symbol.as_str(),
+      trades[1].symbol.as_str()
+    ));
+    assert_eq!(
+      trades[1].trade_conditions().collect::<Vec<_>>(),
+      vec![TradeCondition::RegularSale]
+    );
+  }
+
   /// Check that we can serialize and deserialize the
   /// [`DataMessage::Success`] variant.
   #[test]
@@ -1259,6 +2637,15 @@ mod tests {
     );
   }
 
+  /// Check that we deserialize messages of unknown type into the
+  /// [`DataMessage::Unknown`] variant.
+  #[test]
+  fn deserialize_unknown_message() {
+    let json = r#"[{"T":"n","S":"AAPL","headline":"foobar"},{"T":"success","msg":"connected"}]"#;
+    let messages = json_from_str::<Vec<DataMessage>>(json).unwrap();
+    assert_eq!(messages, vec![DataMessage::Unknown, DataMessage::Success]);
+  }
+
   /// Check that we can serialize and deserialize the
   /// [`DataMessage::Error`] variant.
   #[test]
@@ -1294,16 +2681,209 @@ mod tests {
     );
   }
 
-  /// Check that we can serialize and deserialize the
-  /// [`Request::Authenticate`] variant properly.
+  /// Check that we can deserialize and serialize the
+  /// [`DataMessage::Correction`] variant.
   #[test]
-  fn serialize_deserialize_authentication_request() {
-    let request = Request::Authenticate {
-      key_id: "KEY-ID".into(),
-      secret: "SECRET-KEY".into(),
-    };
-    let json = to_json(&request).unwrap();
-    let expected = r#"{"action":"auth","key":"KEY-ID","secret":"SECRET-KEY"}"#;
+  fn serialize_deserialize_correction() {
+    let json = r#"{
+  "T": "c",
+  "S": "EEM",
+  "x": "M",
+  "oi": 52983525101527,
+  "op": 42.9,
+  "os": 100,
+  "oc": ["@", "F", "T", "I"],
+  "ci": 52983525118391,
+  "cp": 42.89,
+  "cs": 100,
+  "cc": ["@", "F", "T", "I"],
+  "z": "B",
+  "t": "2023-04-06T18:10:55.231Z"
+}"#;
+
+    let message = json_from_str::<DataMessage>(json).unwrap();
+    let correction = match &message {
+      DataMessage::Correction(correction) => correction,
+      _ => panic!("Decoded unexpected message variant: {message:?}"),
+    };
+    assert_eq!(correction.symbol, "EEM");
+    assert_eq!(correction.original_trade_id, 52983525101527);
+    assert_eq!(correction.original_trade_price, Num::new(429, 10));
+    assert_eq!(correction.corrected_trade_price, Num::new(4289, 100));
+    assert_eq!(correction.corrected_conditions.len(), 4);
+
+    assert_eq!(
+      json_from_str::<DataMessage>(&to_json(&message).unwrap()).unwrap(),
+      message
+    );
+  }
+
+  /// Check that we can deserialize and serialize the
+  /// [`DataMessage::CancelError`] variant.
+  #[test]
+  fn serialize_deserialize_cancel_error() {
+    let json = r#"{
+  "T": "x",
+  "S": "AAPL",
+  "i": 52983525033527,
+  "x": "D",
+  "p": 165.07,
+  "s": 10,
+  "a": "C",
+  "z": "C",
+  "t": "2023-04-06T13:45:26.955Z"
+}"#;
+
+    let message = json_from_str::<DataMessage>(json).unwrap();
+    let cancel_error = match &message {
+      DataMessage::CancelError(cancel_error) => cancel_error,
+      _ => panic!("Decoded unexpected message variant: {message:?}"),
+    };
+    assert_eq!(cancel_error.symbol, "AAPL");
+    assert_eq!(cancel_error.trade_id, 52983525033527);
+    assert_eq!(cancel_error.trade_size, Num::from(10));
+    assert_eq!(cancel_error.action, CancelErrorAction::Cancel);
+
+    assert_eq!(
+      json_from_str::<DataMessage>(&to_json(&message).unwrap()).unwrap(),
+      message
+    );
+  }
+
+  /// Check that we can deserialize and serialize the
+  /// [`DataMessage::Luld`] variant.
+  #[test]
+  fn serialize_deserialize_luld() {
+    let json = r#"{
+  "T": "l",
+  "S": "IONM",
+  "u": 3.79,
+  "d": 3.11,
+  "i": "B",
+  "t": "2023-04-06T14:01:05.265Z",
+  "z": "C"
+}"#;
+
+    let message = json_from_str::<DataMessage>(json).unwrap();
+    let luld = match &message {
+      DataMessage::Luld(luld) => luld,
+      _ => panic!("Decoded unexpected message variant: {message:?}"),
+    };
+    assert_eq!(luld.symbol, "IONM");
+    assert_eq!(luld.limit_up_price, Num::new(379, 100));
+    assert_eq!(luld.limit_down_price, Num::new(311, 100));
+    assert_eq!(luld.indicator, "B");
+
+    assert_eq!(
+      json_from_str::<DataMessage>(&to_json(&message).unwrap()).unwrap(),
+      message
+    );
+  }
+
+  /// Check that we can deserialize and serialize the
+  /// [`DataMessage::Status`] variant.
+  #[test]
+  fn serialize_deserialize_status() {
+    let json = r#"{
+  "T": "s",
+  "S": "AAPL",
+  "sc": "H",
+  "sm": "Trading Halt",
+  "rc": "T12",
+  "rm": "Trading Halted; For information requested by NASDAQ",
+  "t": "2021-02-22T19:15:00Z",
+  "z": "C"
+}"#;
+
+    let message = json_from_str::<DataMessage>(json).unwrap();
+    let status = match &message {
+      DataMessage::Status(status) => status,
+      _ => panic!("Decoded unexpected message variant: {message:?}"),
+    };
+    assert_eq!(status.symbol, "AAPL");
+    assert_eq!(status.status_code, "H");
+    assert_eq!(status.reason_code, "T12");
+    assert!(status.is_halted());
+
+    assert_eq!(
+      json_from_str::<DataMessage>(&to_json(&message).unwrap()).unwrap(),
+      message
+    );
+
+    let status = Status {
+      status_code: "T".to_string(),
+      status_message: "Trading Resumption".to_string(),
+      ..status.clone()
+    };
+    assert!(!status.is_halted());
+  }
+
+  /// Check that we can deserialize and serialize the
+  /// [`DataMessage::Orderbook`] variant.
+  #[test]
+  fn serialize_deserialize_orderbook() {
+    let json = r#"{
+  "T": "o",
+  "S": "BTC/USD",
+  "t": "2024-03-12T10:38:50.79613221Z",
+  "b": [{"p": 71859.53, "s": 0.27994}, {"p": 71849.4, "s": 0}],
+  "a": [{"p": 71939.7, "s": 0.83953}],
+  "r": true
+}"#;
+
+    let message = json_from_str::<DataMessage>(json).unwrap();
+    let orderbook = match &message {
+      DataMessage::Orderbook(orderbook) => orderbook,
+      _ => panic!("Decoded unexpected message variant: {message:?}"),
+    };
+    assert_eq!(orderbook.symbol, "BTC/USD");
+    assert_eq!(orderbook.bids.len(), 2);
+    assert_eq!(orderbook.bids[0].price, Num::new(7185953, 100));
+    assert_eq!(orderbook.bids[1].size, Num::from(0));
+    assert_eq!(orderbook.asks[0].size, Num::new(83953, 100000));
+    assert!(orderbook.reset);
+
+    assert_eq!(
+      json_from_str::<DataMessage>(&to_json(&message).unwrap()).unwrap(),
+      message
+    );
+
+    // Incremental updates may omit a side and the reset flag.
+    let json = r#"{"T":"o","S":"BTC/USD","t":"2024-03-12T10:38:51Z","a":[{"p":71939.7,"s":0}]}"#;
+    let message = json_from_str::<DataMessage>(json).unwrap();
+    let orderbook = match &message {
+      DataMessage::Orderbook(orderbook) => orderbook,
+      _ => panic!("Decoded unexpected message variant: {message:?}"),
+    };
+    assert!(orderbook.bids.is_empty());
+    assert!(!orderbook.reset);
+  }
+
+  /// Check that order book subscriptions are included in subscribe
+  /// requests when present.
+  #[test]
+  fn serialize_orderbook_subscribe_request() {
+    let mut data = MarketData::default();
+    data.set_orderbooks(["BTC/USD"]);
+    let request = Request::Subscribe(Cow::Borrowed(&data));
+
+    let json = to_json(&request).unwrap();
+    let expected =
+      r#"{"action":"subscribe","bars":[],"quotes":[],"trades":[],"orderbooks":["BTC/USD"]}"#;
+    assert_eq!(json, expected);
+    assert_eq!(json_from_str::<Request<'_>>(&json).unwrap(), request);
+  }
+
+  /// Check that we can serialize and deserialize the
+  /// [`Request::Authenticate`] variant properly.
+  #[test]
+  fn serialize_deserialize_authentication_request() {
+    let request = Request::Authenticate {
+      key_id: "KEY-ID".into(),
+      secret: "SECRET-KEY".into(),
+    };
+    let json = to_json(&request).unwrap();
+    let expected = r#"{"action":"auth","key":"KEY-ID","secret":"SECRET-KEY"}"#;
     assert_eq!(json, expected);
     assert_eq!(json_from_str::<Request<'_>>(&json).unwrap(), request);
   }
@@ -1322,6 +2902,50 @@ mod tests {
     assert_eq!(json_from_str::<Request<'_>>(&json).unwrap(), request);
   }
 
+  /// Check that LULD subscriptions are included in subscribe requests
+  /// when present.
+  #[test]
+  fn serialize_deserialize_luld_subscribe_request() {
+    let mut data = MarketData::default();
+    data.set_lulds(["SPY"]);
+    let request = Request::Subscribe(Cow::Borrowed(&data));
+
+    let json = to_json(&request).unwrap();
+    let expected = r#"{"action":"subscribe","bars":[],"quotes":[],"trades":[],"lulds":["SPY"]}"#;
+    assert_eq!(json, expected);
+    assert_eq!(json_from_str::<Request<'_>>(&json).unwrap(), request);
+  }
+
+  /// Check that trading status subscriptions are included in subscribe
+  /// requests when present.
+  #[test]
+  fn serialize_deserialize_status_subscribe_request() {
+    let mut data = MarketData::default();
+    data.set_statuses(["AAPL"]);
+    let request = Request::Subscribe(Cow::Borrowed(&data));
+
+    let json = to_json(&request).unwrap();
+    let expected =
+      r#"{"action":"subscribe","bars":[],"quotes":[],"trades":[],"statuses":["AAPL"]}"#;
+    assert_eq!(json, expected);
+    assert_eq!(json_from_str::<Request<'_>>(&json).unwrap(), request);
+  }
+
+  /// Check that daily and updated bar subscriptions are included in
+  /// subscribe requests when present.
+  #[test]
+  fn serialize_deserialize_daily_updated_bars_subscribe_request() {
+    let mut data = MarketData::default();
+    data.set_daily_bars(["SPY"]);
+    data.set_updated_bars(["AAPL", "SPY"]);
+    let request = Request::Subscribe(Cow::Borrowed(&data));
+
+    let json = to_json(&request).unwrap();
+    let expected = r#"{"action":"subscribe","bars":[],"quotes":[],"trades":[],"dailyBars":["SPY"],"updatedBars":["AAPL","SPY"]}"#;
+    assert_eq!(json, expected);
+    assert_eq!(json_from_str::<Request<'_>>(&json).unwrap(), request);
+  }
+
   /// Check that we can serialize and deserialize the
   /// [`Request::Subscribe`] variant properly.
   #[test]
@@ -1345,6 +2969,23 @@ mod tests {
     assert_eq!(list, expected);
   }
 
+  /// Check that adding and removing symbols keeps a `SymbolList`
+  /// normalized.
+  #[test]
+  fn add_remove_symbols() {
+    let mut list = SymbolList::from(["MSFT", "SPY"]);
+    assert!(list.add("AAPL"));
+    assert!(list.add(String::from("QQQ")));
+    assert!(!list.add("SPY"));
+    assert_eq!(list, SymbolList::from(["AAPL", "MSFT", "QQQ", "SPY"]));
+    assert!(list.contains("QQQ"));
+
+    assert!(list.remove("MSFT"));
+    assert!(!list.remove("MSFT"));
+    assert!(!list.contains("MSFT"));
+    assert_eq!(to_json(&list).unwrap(), r#"["AAPL","QQQ","SPY"]"#);
+  }
+
   /// Check that we can normalize `Symbol` slices.
   #[test]
   fn normalize_subscriptions() {
@@ -1419,6 +3060,397 @@ mod tests {
       .unwrap();
   }
 
+  /// Check that we determine the minimal changes required to get from
+  /// one subscription state to another.
+  #[test]
+  fn diff_market_data() {
+    let mut current = MarketData::default();
+    current.set_bars(["AAPL", "SPY"]);
+    current.trades = Symbols::All;
+
+    let mut desired = MarketData::default();
+    desired.set_bars(["MSFT", "SPY"]);
+    desired.quotes = Symbols::All;
+    desired.set_trades(["AAPL"]);
+
+    let (subscribe, unsubscribe) = current.diff(&desired);
+    assert_eq!(subscribe.bars, Symbols::List(SymbolList::from(["MSFT"])));
+    assert_eq!(subscribe.quotes, Symbols::All);
+    assert_eq!(subscribe.trades, Symbols::List(SymbolList::from(["AAPL"])));
+    assert_eq!(unsubscribe.bars, Symbols::List(SymbolList::from(["AAPL"])));
+    assert!(unsubscribe.quotes.is_empty());
+    assert_eq!(unsubscribe.trades, Symbols::All);
+
+    let (subscribe, unsubscribe) = desired.diff(&desired);
+    assert!(subscribe.is_empty());
+    assert!(unsubscribe.is_empty());
+  }
+
+  /// Check that setting the subscription state only sends the
+  /// necessary requests.
+  #[test(tokio::test)]
+  async fn set_subscriptions() {
+    async fn test(mut stream: WebSocketStream) -> Result<(), WebSocketError> {
+      let unsub_req = r#"{"action":"unsubscribe","bars":["VOO"],"quotes":[],"trades":[]}"#;
+      let unsub_resp = r#"[{"T":"subscription","bars":["AAPL"]}]"#;
+      let sub_req = r#"{"action":"subscribe","bars":["SPY"],"quotes":["SPY"],"trades":[]}"#;
+      let sub_resp = r#"[{"T":"subscription","bars":["AAPL","SPY"],"quotes":["SPY"]}]"#;
+
+      stream
+        .send(Message::Text(Utf8Bytes::from_static(CONN_RESP)))
+        .await?;
+      assert_eq!(
+        stream.next().await.unwrap()?,
+        Message::Text(Utf8Bytes::from_static(AUTH_REQ)),
+      );
+      stream
+        .send(Message::Text(Utf8Bytes::from_static(AUTH_RESP)))
+        .await?;
+
+      for (request, response) in [
+        (SUB_REQ, SUB_RESP),
+        (unsub_req, unsub_resp),
+        (sub_req, sub_resp),
+      ] {
+        assert_eq!(
+          stream.next().await.unwrap()?,
+          Message::Text(Utf8Bytes::from_static(request)),
+        );
+        stream
+          .send(Message::Text(Utf8Bytes::from_static(response)))
+          .await?;
+      }
+      stream.send(Message::Close(None)).await?;
+      Ok(())
+    }
+
+    let (mut stream, mut subscription) =
+      mock_stream::<RealtimeData<IEX>, _, _>(test).await.unwrap();
+
+    let mut data = MarketData::default();
+    data.set_bars(["AAPL", "VOO"]);
+
+    let set = subscription.set_subscriptions(&data).boxed_local();
+    let () = drive(set, &mut stream).await.unwrap().unwrap().unwrap();
+    assert_eq!(subscription.subscriptions(), &data);
+
+    // Setting the same state again should not result in any requests.
+    let set = subscription.set_subscriptions(&data).boxed_local();
+    let () = drive(set, &mut stream).await.unwrap().unwrap().unwrap();
+
+    let mut data = MarketData::default();
+    data.set_bars(["AAPL", "SPY"]);
+    data.set_quotes(["SPY"]);
+
+    let set = subscription.set_subscriptions(&data).boxed_local();
+    let () = drive(set, &mut stream).await.unwrap().unwrap().unwrap();
+    assert_eq!(subscription.subscriptions(), &data);
+  }
+
+  /// Check that the tracked subscriptions reflect unsubscribe
+  /// operations and that an exported snapshot can be replayed on a new
+  /// connection.
+  #[test(tokio::test)]
+  async fn snapshot_resubscribe_all() {
+    async fn test(mut stream: WebSocketStream) -> Result<(), WebSocketError> {
+      let unsub_req = r#"{"action":"unsubscribe","bars":["VOO"],"quotes":[],"trades":[]}"#;
+      let unsub_resp = r#"[{"T":"subscription","bars":["AAPL"]}]"#;
+
+      stream
+        .send(Message::Text(Utf8Bytes::from_static(CONN_RESP)))
+        .await?;
+      assert_eq!(
+        stream.next().await.unwrap()?,
+        Message::Text(Utf8Bytes::from_static(AUTH_REQ)),
+      );
+      stream
+        .send(Message::Text(Utf8Bytes::from_static(AUTH_RESP)))
+        .await?;
+
+      for (request, response) in [(SUB_REQ, SUB_RESP), (unsub_req, unsub_resp)] {
+        assert_eq!(
+          stream.next().await.unwrap()?,
+          Message::Text(Utf8Bytes::from_static(request)),
+        );
+        stream
+          .send(Message::Text(Utf8Bytes::from_static(response)))
+          .await?;
+      }
+      stream.send(Message::Close(None)).await?;
+      Ok(())
+    }
+
+    async fn replay(mut stream: WebSocketStream) -> Result<(), WebSocketError> {
+      let sub_req = r#"{"action":"subscribe","bars":["AAPL"],"quotes":[],"trades":[]}"#;
+      let sub_resp = r#"[{"T":"subscription","bars":["AAPL"]}]"#;
+
+      stream
+        .send(Message::Text(Utf8Bytes::from_static(CONN_RESP)))
+        .await?;
+      assert_eq!(
+        stream.next().await.unwrap()?,
+        Message::Text(Utf8Bytes::from_static(AUTH_REQ)),
+      );
+      stream
+        .send(Message::Text(Utf8Bytes::from_static(AUTH_RESP)))
+        .await?;
+      assert_eq!(
+        stream.next().await.unwrap()?,
+        Message::Text(Utf8Bytes::from_static(sub_req)),
+      );
+      stream
+        .send(Message::Text(Utf8Bytes::from_static(sub_resp)))
+        .await?;
+      stream.send(Message::Close(None)).await?;
+      Ok(())
+    }
+
+    let (mut stream, mut subscription) =
+      mock_stream::<RealtimeData<IEX>, _, _>(test).await.unwrap();
+
+    let mut data = MarketData::default();
+    data.set_bars(["AAPL", "VOO"]);
+
+    let subscribe = subscription.subscribe(&data).boxed_local();
+    let () = drive(subscribe, &mut stream)
+      .await
+      .unwrap()
+      .unwrap()
+      .unwrap();
+    assert_eq!(subscription.subscriptions(), &data);
+
+    let mut data = MarketData::default();
+    data.set_bars(["VOO"]);
+
+    let unsubscribe = subscription.unsubscribe(&data).boxed_local();
+    let () = drive(unsubscribe, &mut stream)
+      .await
+      .unwrap()
+      .unwrap()
+      .unwrap();
+
+    let mut expected = MarketData::default();
+    expected.set_bars(["AAPL"]);
+    assert_eq!(subscription.subscriptions(), &expected);
+
+    let snapshot = subscription.snapshot();
+    assert_eq!(snapshot, expected);
+
+    let (mut stream, mut subscription) = mock_stream::<RealtimeData<IEX>, _, _>(replay)
+      .await
+      .unwrap();
+    assert!(subscription.subscriptions().is_empty());
+
+    let resubscribe = subscription.resubscribe_all(&snapshot).boxed_local();
+    let () = drive(resubscribe, &mut stream)
+      .await
+      .unwrap()
+      .unwrap()
+      .unwrap();
+    assert_eq!(subscription.subscriptions(), &snapshot);
+  }
+
+  /// Check that `drive_with_timeout` gives up if the server does not
+  /// respond to a subscription request.
+  #[test(tokio::test)]
+  async fn subscribe_timeout() {
+    async fn test(mut stream: WebSocketStream) -> Result<(), WebSocketError> {
+      stream
+        .send(Message::Text(Utf8Bytes::from_static(CONN_RESP)))
+        .await?;
+      assert_eq!(
+        stream.next().await.unwrap()?,
+        Message::Text(Utf8Bytes::from_static(AUTH_REQ)),
+      );
+      stream
+        .send(Message::Text(Utf8Bytes::from_static(AUTH_RESP)))
+        .await?;
+      assert_eq!(
+        stream.next().await.unwrap()?,
+        Message::Text(Utf8Bytes::from_static(SUB_REQ)),
+      );
+      // Never respond, but wait for the client to go away.
+      let _next = stream.next().await;
+      Ok(())
+    }
+
+    let (mut stream, mut subscription) =
+      mock_stream::<RealtimeData<IEX>, _, _>(test).await.unwrap();
+
+    let mut data = MarketData::default();
+    data.set_bars(["AAPL", "VOO"]);
+
+    let duration = Duration::from_millis(50);
+    let subscribe = subscription.subscribe(&data).boxed_local();
+    let err = drive_with_timeout(subscribe, &mut stream, duration)
+      .await
+      .unwrap_err();
+    match err {
+      Error::Timeout(elapsed) => assert_eq!(elapsed, duration),
+      e => panic!("received unexpected error: {e}"),
+    }
+  }
+
+  /// Check that market data is split into chunks referencing a
+  /// limited number of symbols each.
+  #[test]
+  fn chunk_market_data() {
+    let mut data = MarketData::default();
+    data.set_bars(["AAPL", "MSFT", "SPY"]);
+    data.quotes = Symbols::All;
+    data.set_trades(["VOO"]);
+
+    let chunks = data.chunks(2);
+    assert_eq!(chunks.len(), 3);
+    assert_eq!(
+      chunks[0].bars,
+      Symbols::List(SymbolList::from(["AAPL", "MSFT"]))
+    );
+    assert!(chunks[0].quotes.is_empty());
+    assert_eq!(chunks[1].bars, Symbols::List(SymbolList::from(["SPY"])));
+    assert_eq!(chunks[1].quotes, Symbols::All);
+    assert!(chunks[1].trades.is_empty());
+    assert!(chunks[2].bars.is_empty());
+    assert_eq!(chunks[2].trades, Symbols::List(SymbolList::from(["VOO"])));
+
+    let chunks = data.chunks(10);
+    assert_eq!(chunks, vec![data]);
+
+    let chunks = MarketData::default().chunks(10);
+    assert_eq!(chunks, vec![MarketData::default()]);
+  }
+
+  /// Check that large subscriptions are sent in multiple batches.
+  #[test(tokio::test)]
+  async fn subscribe_batched() {
+    async fn test(mut stream: WebSocketStream) -> Result<(), WebSocketError> {
+      let sub_req1 = r#"{"action":"subscribe","bars":["AAPL","MSFT"],"quotes":[],"trades":[]}"#;
+      let sub_resp1 = r#"[{"T":"subscription","bars":["AAPL","MSFT"]}]"#;
+      let sub_req2 = r#"{"action":"subscribe","bars":["SPY"],"quotes":["SPY"],"trades":[]}"#;
+      let sub_resp2 = r#"[{"T":"subscription","bars":["AAPL","MSFT","SPY"],"quotes":["SPY"]}]"#;
+
+      stream
+        .send(Message::Text(Utf8Bytes::from_static(CONN_RESP)))
+        .await?;
+      assert_eq!(
+        stream.next().await.unwrap()?,
+        Message::Text(Utf8Bytes::from_static(AUTH_REQ)),
+      );
+      stream
+        .send(Message::Text(Utf8Bytes::from_static(AUTH_RESP)))
+        .await?;
+
+      for (request, response) in [(sub_req1, sub_resp1), (sub_req2, sub_resp2)] {
+        assert_eq!(
+          stream.next().await.unwrap()?,
+          Message::Text(Utf8Bytes::from_static(request)),
+        );
+        stream
+          .send(Message::Text(Utf8Bytes::from_static(response)))
+          .await?;
+      }
+      stream.send(Message::Close(None)).await?;
+      Ok(())
+    }
+
+    let (mut stream, mut subscription) =
+      mock_stream::<RealtimeData<IEX>, _, _>(test).await.unwrap();
+    let () = subscription.set_max_symbols_per_request(2);
+
+    let mut data = MarketData::default();
+    data.set_bars(["AAPL", "MSFT", "SPY"]);
+    data.set_quotes(["SPY"]);
+
+    let subscribe = subscription.subscribe(&data).boxed_local();
+    let () = drive(subscribe, &mut stream)
+      .await
+      .unwrap()
+      .unwrap()
+      .unwrap();
+    assert_eq!(subscription.subscriptions(), &data);
+  }
+
+  /// Check that we can stream data in MessagePack format.
+  #[test(tokio::test)]
+  async fn stream_msgpack() {
+    /// Encode the provided JSON string as a binary MessagePack message.
+    fn binary(json: &str) -> Message {
+      let value = json_from_str::<serde_json::Value>(json).unwrap();
+      Message::Binary(rmp_serde::to_vec(&value).unwrap().into())
+    }
+
+    /// Encode the provided JSON string as a binary MessagePack message,
+    /// with the given timestamp sent as an extension value, the way
+    /// the server does.
+    fn binary_with_time(json: &str, time: &str) -> Message {
+      let value = json_from_str::<serde_json::Value>(json).unwrap();
+      let data = rmp_serde::to_vec(&value).unwrap();
+      let string = rmp_serde::to_vec(time).unwrap();
+      let idx = data
+        .windows(string.len())
+        .position(|window| window == string)
+        .unwrap();
+
+      let time = DateTime::parse_from_rfc3339(time).unwrap();
+      let secs = u64::try_from(time.timestamp()).unwrap();
+      let nanos = u64::from(time.timestamp_subsec_nanos());
+      let mut ext = vec![0xd7, 0xff];
+      let () = ext.extend_from_slice(&((nanos << 34) | secs).to_be_bytes());
+
+      let data = [&data[..idx], &ext, &data[idx + string.len()..]].concat();
+      Message::Binary(data.into())
+    }
+
+    let (sender, receiver) = channel();
+
+    let test = |mut stream: WebSocketStream| {
+      async move {
+        let trade = r#"[{"T":"t","i":96921,"S":"AAPL","x":"D","p":126.55,"s":1,"t":"2021-02-22T15:51:44.208Z","c":["@","I"],"z":"C"}]"#;
+
+        stream.send(binary(CONN_RESP)).await?;
+        // Control messages are still sent as JSON.
+        assert_eq!(
+          stream.next().await.unwrap()?,
+          Message::Text(Utf8Bytes::from_static(AUTH_REQ)),
+        );
+        stream.send(binary(AUTH_RESP)).await?;
+
+        // Wait until the connection was established before sending any
+        // data.
+        let () = receiver.await.unwrap();
+
+        stream.send(binary(trade)).await?;
+        stream
+          .send(binary_with_time(trade, "2021-02-22T15:51:44.208Z"))
+          .await?;
+        stream.send(Message::Close(None)).await?;
+        Ok(())
+      }
+    };
+
+    let (stream, _subscription) =
+      mock_stream::<RealtimeData<IEX, Bar, Quote, Trade, MsgPack>, _, _>(test)
+        .await
+        .unwrap();
+    let () = sender.send(()).unwrap();
+
+    let data = stream
+      .map_err(Error::WebSocket)
+      .and_then(|result| async { result })
+      .try_collect::<Vec<_>>()
+      .await
+      .unwrap();
+
+    match data.as_slice() {
+      [Data::Trade(trade1), Data::Trade(trade2)] => {
+        assert_eq!(trade1.symbol, "AAPL");
+        assert_eq!(trade1.trade_price, Num::new(12655, 100));
+        assert_eq!(trade1, trade2);
+      },
+      data => panic!("received unexpected data: {data:?}"),
+    }
+  }
+
   /// Check that we correctly handle errors reported as part of
   /// subscription.
   #[test(tokio::test)]
@@ -1466,6 +3498,108 @@ mod tests {
     }
   }
 
+  /// Check that subscriptions to all symbols are refused on channels
+  /// for which they are not permitted, that the configured symbol limit
+  /// is enforced, and that exceeding the server's symbol limit is
+  /// reported as such.
+  #[test(tokio::test)]
+  async fn subscribe_guardrails() {
+    async fn test(mut stream: WebSocketStream) -> Result<(), WebSocketError> {
+      let sub_resp = r#"[{"T":"error","code":405,"msg":"symbol limit exceeded"}]"#;
+
+      stream
+        .send(Message::Text(Utf8Bytes::from_static(CONN_RESP)))
+        .await?;
+      assert_eq!(
+        stream.next().await.unwrap()?,
+        Message::Text(Utf8Bytes::from_static(AUTH_REQ)),
+      );
+      stream
+        .send(Message::Text(Utf8Bytes::from_static(AUTH_RESP)))
+        .await?;
+
+      // Only the permitted subscription makes it to the server.
+      assert_eq!(
+        stream.next().await.unwrap()?,
+        Message::Text(Utf8Bytes::from_static(SUB_REQ)),
+      );
+      stream
+        .send(Message::Text(Utf8Bytes::from_static(sub_resp)))
+        .await?;
+      stream.send(Message::Close(None)).await?;
+      Ok(())
+    }
+
+    let (mut stream, mut subscription) =
+      mock_stream::<RealtimeData<IEX>, _, _>(test).await.unwrap();
+    let () = subscription.permit_all_symbols(&[Channel::Trades]);
+
+    let data = MarketData {
+      trades: Symbols::All,
+      quotes: Symbols::All,
+      ..Default::default()
+    };
+    let error = subscription.subscribe(&data).await.unwrap().unwrap_err();
+    match error {
+      Error::Subscription(SubscriptionError::AllSymbolsNotPermitted(Channel::Quotes)) => (),
+      e => panic!("received unexpected error: {e}"),
+    }
+
+    // The desired state is checked as a whole, not just the part that
+    // is to be subscribed to.
+    let error = subscription
+      .set_subscriptions(&data)
+      .await
+      .unwrap()
+      .unwrap_err();
+    match error {
+      Error::Subscription(SubscriptionError::AllSymbolsNotPermitted(Channel::Quotes)) => (),
+      e => panic!("received unexpected error: {e}"),
+    }
+
+    let () = subscription.set_symbol_limit(1);
+    let mut data = MarketData::default();
+    data.set_bars(["AAPL", "VOO"]);
+    let error = subscription
+      .resubscribe_all(&data)
+      .await
+      .unwrap()
+      .unwrap_err();
+    match error {
+      Error::Subscription(SubscriptionError::TooManySymbols { count, limit }) => {
+        assert_eq!((count, limit), (2, 1))
+      },
+      e => panic!("received unexpected error: {e}"),
+    }
+
+    let () = subscription.set_symbol_limit(2);
+    let subscribe = subscription.subscribe(&data).boxed_local();
+    let error = drive(subscribe, &mut stream)
+      .await
+      .unwrap()
+      .unwrap()
+      .unwrap_err();
+    match error {
+      Error::Subscription(SubscriptionError::SymbolLimitExceeded(error)) => {
+        assert_eq!(error.code, 405)
+      },
+      e => panic!("received unexpected error: {e}"),
+    }
+  }
+
+  /// Check that we count the distinct symbols referenced by market
+  /// data.
+  #[test]
+  fn count_symbols() {
+    let mut data = MarketData::default();
+    assert_eq!(data.symbol_count(), 0);
+
+    data.set_bars(["AAPL", "SPY"]);
+    data.set_quotes(["SPY", "VOO"]);
+    data.trades = Symbols::All;
+    assert_eq!(data.symbol_count(), 3);
+  }
+
   /// Check that we can adjust the current market data subscription on
   /// the fly.
   #[test(tokio::test)]
@@ -1542,6 +3676,79 @@ mod tests {
     }
   }
 
+  /// Check that we can subscribe to realtime data of a feed selected
+  /// at runtime.
+  #[test(tokio::test)]
+  #[serial(realtime_data)]
+  async fn subscribe_data_feed() {
+    let api_info = ApiInfo::from_env().unwrap();
+    let client = Client::new(api_info);
+    let (mut stream, mut subscription) = client.subscribe_data(Feed::IEX).await.unwrap();
+
+    let mut data = MarketData::default();
+    let () = data.set_bars(["SPY"]);
+
+    let subscribe = subscription.subscribe(&data).boxed_local();
+    let () = drive(subscribe, &mut stream)
+      .await
+      .unwrap()
+      .unwrap()
+      .unwrap();
+
+    assert_eq!(subscription.subscriptions(), &data);
+  }
+
+  /// Check that we can subscribe to realtime data from a URL provided
+  /// at runtime.
+  #[test(tokio::test)]
+  #[serial(realtime_data)]
+  async fn subscribe_data_url() {
+    let api_info = ApiInfo::from_env().unwrap();
+    let client = Client::new(api_info);
+    let url = Url::parse("wss://stream.data.alpaca.markets/v2/iex").unwrap();
+    let (mut stream, mut subscription) = client.subscribe_data_url(url).await.unwrap();
+
+    let mut data = MarketData::default();
+    let () = data.set_bars(["SPY"]);
+
+    let subscribe = subscription.subscribe(&data).boxed_local();
+    let () = drive(subscribe, &mut stream)
+      .await
+      .unwrap()
+      .unwrap()
+      .unwrap();
+
+    assert_eq!(subscription.subscriptions(), &data);
+  }
+
+  /// Check that we can stream trades for the fake `FAKEPACA` symbol
+  /// from the test stream.
+  #[test(tokio::test)]
+  #[serial(realtime_data)]
+  async fn stream_test_trades() {
+    let api_info = ApiInfo::from_env().unwrap();
+    let client = Client::new(api_info);
+    let (mut stream, mut subscription) = client.subscribe::<RealtimeData<Test>>().await.unwrap();
+
+    let mut data = MarketData::default();
+    let () = data.set_trades(["FAKEPACA"]);
+
+    let subscribe = subscription.subscribe(&data).boxed_local();
+    let () = drive(subscribe, &mut stream)
+      .await
+      .unwrap()
+      .unwrap()
+      .unwrap();
+
+    let data = timeout(Duration::from_secs(10), stream.next())
+      .await
+      .unwrap()
+      .unwrap()
+      .unwrap()
+      .unwrap();
+    assert!(data.is_trade(), "{data:?}");
+  }
+
   /// Check that we can stream realtime stock quotes.
   ///
   /// Note that we do not have any control over whether the market is
@@ -2,17 +2,26 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 use chrono::DateTime;
+use chrono::Duration as ChronoDuration;
+use chrono::NaiveDate;
 use chrono::Utc;
 
+use futures::Stream;
+
 use num_decimal::Num;
 
 use serde::Deserialize;
 use serde::Serialize;
 use serde_urlencoded::to_string as to_query;
 
+use crate::data::v2::range;
+use crate::data::v2::range::RangeEndpoint;
 use crate::data::v2::Feed;
-use crate::data::DATA_BASE_URL;
+use crate::data::v2::Sort;
 use crate::util::vec_from_str;
+use crate::Client;
+use crate::PageToken;
+use crate::RequestError;
 use crate::Str;
 
 
@@ -40,9 +49,25 @@ pub struct ListReq {
   /// [`SIP`][Feed::SIP] for users with an unlimited subscription.
   #[serde(rename = "feed")]
   pub feed: Option<Feed>,
+  /// The date on which symbols are mapped, used to follow symbol
+  /// changes, e.g., due to a company rename.
+  ///
+  /// Defaults to the current day if the provided value is `None`.
+  #[serde(rename = "asof")]
+  pub asof: Option<NaiveDate>,
+  /// The currency in which prices are reported, e.g., "EUR".
+  ///
+  /// Defaults to USD if the provided value is `None`.
+  #[serde(rename = "currency")]
+  pub currency: Option<String>,
+  /// The order in which to return trades.
+  ///
+  /// Defaults to [`Asc`][Sort::Asc] if the provided value is `None`.
+  #[serde(rename = "sort")]
+  pub sort: Option<Sort>,
   /// If provided we will pass a page token to continue where we left off.
   #[serde(rename = "page_token", skip_serializing_if = "Option::is_none")]
-  pub page_token: Option<String>,
+  pub page_token: Option<PageToken>,
   /// The type is non-exhaustive and open to extension.
   #[doc(hidden)]
   #[serde(skip)]
@@ -57,17 +82,34 @@ pub struct ListReqInit {
   pub limit: Option<usize>,
   /// See `ListReq::feed`.
   pub feed: Option<Feed>,
+  /// See `ListReq::asof`.
+  pub asof: Option<NaiveDate>,
+  /// See `ListReq::currency`.
+  pub currency: Option<String>,
+  /// See `ListReq::sort`.
+  pub sort: Option<Sort>,
   /// See `ListReq::page_token`.
-  pub page_token: Option<String>,
+  pub page_token: Option<PageToken>,
   /// The type is non-exhaustive and open to extension.
   #[doc(hidden)]
   pub _non_exhaustive: (),
 }
 
+Setters! {
+  ListReqInit {
+    limit: Option<usize>,
+    feed: Option<Feed>,
+    asof: Option<NaiveDate>,
+    currency: Option<String>,
+    sort: Option<Sort>,
+    page_token: Option<PageToken>,
+  }
+}
+
 impl ListReqInit {
   /// Create a [`ListReq`] from a `ListReqInit`.
   #[inline]
-  pub fn init<S>(self, symbol: S, start: DateTime<Utc>, end: DateTime<Utc>) -> ListReq
+  pub fn init<S>(&self, symbol: S, start: DateTime<Utc>, end: DateTime<Utc>) -> ListReq
   where
     S: Into<String>,
   {
@@ -77,7 +119,10 @@ impl ListReqInit {
       end,
       limit: self.limit,
       feed: self.feed,
-      page_token: self.page_token,
+      asof: self.asof,
+      currency: self.currency.clone(),
+      sort: self.sort,
+      page_token: self.page_token.clone(),
       _non_exhaustive: (),
     }
   }
@@ -85,14 +130,19 @@ impl ListReqInit {
 
 
 /// A market data trade as returned by the /v2/stocks/{symbol}/trades endpoint.
+///
+/// Prices are represented as `P`, which defaults to the exact [`Num`]
+/// type. Parsing into [`Num`] is comparably slow, though, and for large
+/// historical data sets `f64` may be used instead, by means of the
+/// `ListF64` endpoint (requiring the `f64-prices` feature).
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
-pub struct Trade {
+pub struct Trade<P = Num> {
   /// Time of the trade.
   #[serde(rename = "t")]
   pub timestamp: DateTime<Utc>,
   /// The price of the trade.
   #[serde(rename = "p")]
-  pub price: Num,
+  pub price: P,
   /// The size of the trade.
   #[serde(rename = "s")]
   pub size: usize,
@@ -105,16 +155,17 @@ pub struct Trade {
 
 /// A collection of trades as returned by the API. This is one page of trades.
 #[derive(Debug, Deserialize, Eq, PartialEq)]
-pub struct Trades {
+#[serde(bound(deserialize = "P: Deserialize<'de>"))]
+pub struct Trades<P = Num> {
   /// The list of returned trades.
   #[serde(rename = "trades", deserialize_with = "vec_from_str")]
-  pub trades: Vec<Trade>,
+  pub trades: Vec<Trade<P>>,
   /// The symbol the trades correspond to.
   #[serde(rename = "symbol")]
   pub symbol: String,
   /// The token to provide to a request to get the next page of trades for this request.
   #[serde(rename = "next_page_token")]
-  pub next_page_token: Option<String>,
+  pub next_page_token: Option<PageToken>,
   /// The type is non-exhaustive and open to extension.
   #[doc(hidden)]
   #[serde(skip)]
@@ -125,6 +176,7 @@ pub struct Trades {
 Endpoint! {
   /// The representation of a GET request to the /v2/stocks/{symbol}/trades endpoint.
   pub List(ListReq),
+  Api => Data,
   Ok => Trades, [
     /// The market data was retrieved successfully.
     /* 200 */ OK,
@@ -134,12 +186,36 @@ Endpoint! {
     /* 400 */ BAD_REQUEST => InvalidInput,
   ]
 
-  fn base_url() -> Option<Str> {
-    Some(DATA_BASE_URL.into())
+  fn path(input: &Self::Input) -> Str {
+    format!("/v2/stocks/{}/trades", input.symbol).into()
+  }
+
+  fn query(input: &Self::Input) -> Result<Option<Str>, Self::ConversionError> {
+    Ok(Some(to_query(input)?.into()))
   }
+}
+
+
+#[cfg(feature = "f64-prices")]
+Endpoint! {
+  /// The representation of a GET request to the
+  /// /v2/stocks/{symbol}/trades endpoint, reporting prices as `f64`.
+  ///
+  /// Retrieving trades by means of this endpoint is considerably faster
+  /// than using [`List`], at the cost of precision.
+  pub ListF64(ListReq),
+  Api => Data,
+  Ok => Trades<f64>, [
+    /// The market data was retrieved successfully.
+    /* 200 */ OK,
+  ],
+  Err => ListF64Error, [
+    /// A query parameter was invalid.
+    /* 400 */ BAD_REQUEST => InvalidInput,
+  ]
 
   fn path(input: &Self::Input) -> Str {
-    format!("/v2/stocks/{}/trades", input.symbol).into()
+    List::path(input)
   }
 
   fn query(input: &Self::Input) -> Result<Option<Str>, Self::ConversionError> {
@@ -148,12 +224,74 @@ Endpoint! {
 }
 
 
+impl RangeEndpoint for List {
+  type Item = Trade;
+
+  #[inline]
+  fn range(input: &mut Self::Input) -> (&mut DateTime<Utc>, &mut DateTime<Utc>) {
+    (&mut input.start, &mut input.end)
+  }
+
+  #[inline]
+  fn page_token(input: &mut Self::Input) -> &mut Option<PageToken> {
+    &mut input.page_token
+  }
+
+  #[inline]
+  fn into_page(output: Self::Output) -> (Vec<Self::Item>, Option<PageToken>) {
+    (output.trades, output.next_page_token)
+  }
+
+  #[inline]
+  fn is_rate_limited(error: &Self::Error) -> bool {
+    matches!(error, ListError::RateLimitExceeded(..))
+  }
+}
+
+
+/// Retrieve all trades in the time range of the provided request as a
+/// stream.
+///
+/// Trades are fetched lazily as the stream is polled, a page at a time.
+/// A zero `chunk` length retrieves the full range by following page
+/// tokens only. See
+/// [`bars::fetch_range`][crate::data::v2::bars::fetch_range] for
+/// details on how the time range is retrieved and [`pages`] for
+/// processing trades page by page instead.
+pub fn fetch_range(
+  client: &Client,
+  request: ListReq,
+  chunk: ChronoDuration,
+) -> impl Stream<Item = Result<Trade, RequestError<ListError>>> + '_ {
+  range::fetch_range::<List>(client, request, chunk)
+}
+
+
+/// Retrieve the trades covered by the provided request as a stream of
+/// pages.
+///
+/// Pages are fetched lazily as the stream is polled, following page
+/// tokens, so that only a single page has to be held in memory at any
+/// point in time. That makes it possible to process, say, a full
+/// day's worth of trades page by page. Requests exceeding the rate
+/// limit are retried a few times, backing off in between.
+pub fn pages(
+  client: &Client,
+  request: ListReq,
+) -> impl Stream<Item = Result<Vec<Trade>, RequestError<ListError>>> + '_ {
+  range::fetch_pages::<List>(client, request, ChronoDuration::zero())
+}
+
+
 #[cfg(test)]
 mod tests {
   use super::*;
 
   use std::str::FromStr as _;
 
+  use futures::TryStreamExt as _;
+
+  use http::StatusCode;
   use http_endpoint::Endpoint;
 
   use serde_json::from_str as from_json;
@@ -161,10 +299,30 @@ mod tests {
   use test_log::test;
 
   use crate::api_info::ApiInfo;
+  use crate::transport::test::mock_client;
   use crate::Client;
+  use crate::MockTransport;
   use crate::RequestError;
 
 
+  /// Check that the various data feeds are serialized as expected.
+  #[test]
+  fn serialize_feed() {
+    let start = DateTime::from_str("2022-01-04T13:35:59Z").unwrap();
+    let end = DateTime::from_str("2022-01-04T13:36:00Z").unwrap();
+
+    for (feed, expected) in [
+      (Feed::IEX, "feed=iex"),
+      (Feed::SIP, "feed=sip"),
+      (Feed::DelayedSip, "feed=delayed_sip"),
+      (Feed::Otc, "feed=otc"),
+    ] {
+      let request = ListReqInit::new().feed(feed).init("SPY", start, end);
+      let query = List::query(&request).unwrap().unwrap();
+      assert!(query.contains(expected), "{query}");
+    }
+  }
+
   /// Verify that we can properly parse a reference trades response.
   #[test]
   fn parse_reference_trades() {
@@ -211,6 +369,68 @@ mod tests {
     assert!(res.next_page_token.is_some())
   }
 
+  /// Check that we can retrieve trades as a stream of pages as well as
+  /// of individual trades.
+  #[test(tokio::test)]
+  async fn stream_trades() {
+    let response = r#"{
+    "trades": [
+      {"t": "2021-02-06T13:04:56.334320128Z", "x": "C", "p": 387.62, "s": 100, "c": [" "], "i": 1, "z": "B"},
+      {"t": "2021-02-06T13:09:42.325484032Z", "x": "C", "p": 387.69, "s": 100, "c": [" "], "i": 2, "z": "B"}
+    ],
+    "symbol": "SPY",
+    "next_page_token": null
+}"#;
+
+    let start = DateTime::from_str("2021-02-06T00:00:00Z").unwrap();
+    let end = DateTime::from_str("2021-02-07T00:00:00Z").unwrap();
+    let request = ListReqInit::default().init("SPY", start, end);
+
+    let mock = MockTransport::new();
+    let () = mock.respond::<List>(&request, StatusCode::OK, response);
+    let client = mock_client(mock);
+
+    let pages = pages(&client, request.clone())
+      .try_collect::<Vec<_>>()
+      .await
+      .unwrap();
+    assert_eq!(pages.len(), 1);
+    assert_eq!(pages[0].len(), 2);
+
+    let trades = fetch_range(&client, request, ChronoDuration::zero())
+      .try_collect::<Vec<_>>()
+      .await
+      .unwrap();
+    assert_eq!(trades, pages[0]);
+  }
+
+  /// Check that we can parse trades with prices represented as `f64`.
+  #[cfg(feature = "f64-prices")]
+  #[test(tokio::test)]
+  async fn list_f64_trades() {
+    let response = r#"{
+    "trades": [
+      {"t": "2021-02-06T13:04:56.334320128Z", "x": "C", "p": 387.62, "s": 100, "c": [" "], "i": 1, "z": "B"}
+    ],
+    "symbol": "SPY",
+    "next_page_token": null
+}"#;
+
+    let start = DateTime::from_str("2021-02-06T00:00:00Z").unwrap();
+    let end = DateTime::from_str("2021-02-07T00:00:00Z").unwrap();
+    let request = ListReqInit::default().init("SPY", start, end);
+    assert_eq!(ListF64::path(&request), List::path(&request));
+
+    let mock = MockTransport::new();
+    let () = mock.respond::<ListF64>(&request, StatusCode::OK, response);
+    let client = mock_client(mock);
+
+    let trades = client.issue::<ListF64>(&request).await.unwrap();
+    assert_eq!(trades.trades.len(), 1);
+    assert_eq!(trades.trades[0].price, 387.62);
+    assert_eq!(trades.trades[0].size, 100);
+  }
+
   /// Check that we can decode a response containing no trades correctly.
   #[test(tokio::test)]
   async fn no_trades() {
@@ -310,7 +530,7 @@ mod tests {
     let start = DateTime::from_str("2018-12-03T21:47:00Z").unwrap();
     let end = DateTime::from_str("2018-12-07T21:47:00Z").unwrap();
     let request = ListReqInit {
-      page_token: Some("123456789abcdefghi".to_string()),
+      page_token: Some("123456789abcdefghi".into()),
       ..Default::default()
     }
     .init("SPY", start, end);
@@ -1,12 +1,16 @@
 // Copyright (C) 2019-2023 The apca Developers
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+use http_endpoint::Endpoint;
+
 use serde::Deserialize;
 use serde_json::Error as JsonError;
 use serde_urlencoded::ser::Error as UrlEncodeError;
 
 use thiserror::Error;
 
+use uuid::Uuid;
+
 
 /// An error type comprising various conversion errors we may encounter.
 #[derive(Debug, Error)]
@@ -20,16 +24,149 @@ pub enum ConversionError {
 }
 
 
+/// A code classifying an error reported by API endpoints.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum ErrorCode {
+  /// The request was malformed.
+  MalformedRequest,
+  /// Some of the provided data was invalid.
+  InvalidRequest,
+  /// The request lacked valid credentials.
+  Unauthorized,
+  /// The request was not permitted, e.g., because of insufficient
+  /// buying power or a pattern day trading violation.
+  Forbidden,
+  /// The requested resource was not found.
+  NotFound,
+  /// The request could not be processed, e.g., because it contained
+  /// an order type not supported during extended hours.
+  Unprocessable,
+  /// The rate limit was exceeded.
+  RateLimitExceeded,
+  /// An internal server error occurred.
+  InternalServerError,
+  /// A code that we have not accounted for.
+  Other(u64),
+}
+
+impl From<u64> for ErrorCode {
+  fn from(code: u64) -> Self {
+    match code {
+      40010000 => Self::MalformedRequest,
+      40010001 => Self::InvalidRequest,
+      40110000 => Self::Unauthorized,
+      40310000 => Self::Forbidden,
+      40410000 => Self::NotFound,
+      42210000 => Self::Unprocessable,
+      42910000 => Self::RateLimitExceeded,
+      50010000 => Self::InternalServerError,
+      code => Self::Other(code),
+    }
+  }
+}
+
+
 /// An error as reported by API endpoints.
 // Note that actually this type should probably be specific to the API
 // version in question. However, at this point we only support v2, so we
 // luck out here.
-#[derive(Clone, Debug, Deserialize, Error, Eq, PartialEq)]
+#[derive(Clone, Debug, Default, Deserialize, Error, Eq, PartialEq)]
 #[error("{message}")]
 pub struct ApiError {
+  /// The numeric error code as provided by Alpaca, if any.
+  #[serde(rename = "code", default)]
+  pub code: Option<u64>,
   /// A message as provided by Alpaca.
   #[serde(rename = "message")]
   pub message: String,
+  /// The reason an order was rejected, if reported.
+  #[serde(rename = "reject_reason", default)]
+  pub reject_reason: Option<String>,
+  /// The ID of an existing order conflicting with the request, e.g.,
+  /// because it would cause a potential wash trade.
+  #[serde(rename = "existing_order_id", default)]
+  pub existing_order_id: Option<Uuid>,
+  /// The IDs of orders related to the error, e.g., those holding the
+  /// quantity an order attempted to sell.
+  #[serde(rename = "related_orders", default)]
+  pub related_orders: Vec<Uuid>,
+  /// The ID Alpaca assigned to the request that caused the error, as
+  /// reported in the `X-Request-ID` response header.
+  ///
+  /// Providing this ID to Alpaca support helps with investigating
+  /// issues.
+  #[serde(skip)]
+  pub request_id: Option<String>,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  #[serde(skip)]
+  pub _non_exhaustive: (),
+}
+
+impl ApiError {
+  /// Retrieve the [`ErrorCode`] classifying this error, if a code was
+  /// reported.
+  #[inline]
+  pub fn error_code(&self) -> Option<ErrorCode> {
+    self.code.map(ErrorCode::from)
+  }
+}
+
+
+/// A trait for endpoint errors that may carry an [`ApiError`].
+///
+/// The trait is implemented for the error types of all endpoints.
+pub trait EndpointError {
+  /// Retrieve the [`ApiError`] reported by the endpoint, if any.
+  fn api_error(&self) -> Option<&ApiError>;
+
+  /// Retrieve a mutable reference to the [`ApiError`] reported by the
+  /// endpoint, if any.
+  #[doc(hidden)]
+  #[inline]
+  fn api_error_mut(&mut self) -> Option<&mut ApiError> {
+    None
+  }
+}
+
+
+/// An enumeration of the various Alpaca APIs an endpoint may belong to.
+///
+/// The API determines the base URL a request is sent to as well as the
+/// credentials used for authenticating it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Api {
+  /// The trading API, reachable via [`ApiInfo::api_base_url`][crate::ApiInfo::api_base_url].
+  Trading,
+  /// The market data API, reachable via [`ApiInfo::data_base_url`][crate::ApiInfo::data_base_url].
+  Data,
+  /// The broker API, reachable via [`ApiInfo::broker_base_url`][crate::ApiInfo::broker_base_url].
+  #[cfg(feature = "broker")]
+  Broker,
+}
+
+
+/// A trait for endpoints that can be issued by a [`Client`][crate::Client].
+///
+/// The trait is implemented for all endpoints. Custom endpoints
+/// targeting the trading API can use the default implementation.
+pub trait ApiEndpoint: Endpoint {
+  /// The API the endpoint belongs to.
+  const API: Api = Api::Trading;
+}
+
+
+/// A macro resolving to the [`Api`] an endpoint belongs to, defaulting
+/// to the trading API.
+macro_rules! EndpointApi {
+  () => {
+    crate::endpoint::Api::Trading
+  };
+  ($api:ident) => {
+    crate::endpoint::Api::$api
+  };
 }
 
 
@@ -37,6 +174,7 @@ pub struct ApiError {
 /// particular HTTP endpoint, without automated JSON parsing.
 macro_rules! EndpointNoParse {
   ( $(#[$docs:meta])* $pub:vis $name:ident($in:ty),
+    $(Api => $api:ident,)?
     Ok => $out:ty, [$($(#[$ok_docs:meta])* $ok_status:ident,)*],
     Err => $err:ident, [$($(#[$err_docs:meta])* $err_status:ident => $variant:ident,)*]
     $($defs:tt)* ) => {
@@ -68,6 +206,33 @@ macro_rules! EndpointNoParse {
 
       $($defs)*
     }
+
+    impl crate::endpoint::ApiEndpoint for $name {
+      const API: crate::endpoint::Api = EndpointApi!($($api)?);
+    }
+
+    #[allow(unused_qualifications)]
+    impl crate::endpoint::EndpointError for $err {
+      fn api_error(&self) -> Option<&crate::endpoint::ApiError> {
+        match self {
+          $err::NotPermitted(Ok(err))
+          | $err::RateLimitExceeded(Ok(err))
+          $(| $err::$variant(Ok(err)))*
+          | $err::UnexpectedStatus(_, Ok(err)) => Some(err),
+          _ => None,
+        }
+      }
+
+      fn api_error_mut(&mut self) -> Option<&mut crate::endpoint::ApiError> {
+        match self {
+          $err::NotPermitted(Ok(err))
+          | $err::RateLimitExceeded(Ok(err))
+          $(| $err::$variant(Ok(err)))*
+          | $err::UnexpectedStatus(_, Ok(err)) => Some(err),
+          _ => None,
+        }
+      }
+    }
   };
 }
 
@@ -88,3 +253,71 @@ macro_rules! Endpoint {
     }
   };
 }
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use serde_json::from_str as from_json;
+
+
+  /// Check that we can parse an API error with and without a code.
+  #[test]
+  fn parse_api_error() {
+    let response = r#"{"code":40310000,"message":"insufficient buying power"}"#;
+    let err = from_json::<ApiError>(response).unwrap();
+    assert_eq!(err.code, Some(40310000));
+    assert_eq!(err.error_code(), Some(ErrorCode::Forbidden));
+    assert_eq!(err.to_string(), "insufficient buying power");
+
+    let response = r#"{"message":"endpoint not found"}"#;
+    let err = from_json::<ApiError>(response).unwrap();
+    assert_eq!(err.code, None);
+    assert_eq!(err.error_code(), None);
+    assert_eq!(err.reject_reason, None);
+    assert_eq!(err.related_orders, Vec::<Uuid>::new());
+  }
+
+  /// Check that we can parse the details of an API error referencing
+  /// other orders.
+  #[test]
+  fn parse_api_error_with_orders() {
+    let response = r#"{
+  "code": 40310000,
+  "existing_order_id": "904837e3-3b76-47ec-b432-046db621571b",
+  "message": "potential wash trade detected. use complex orders",
+  "reject_reason": "opposite side market/stop order exists"
+}"#;
+    let err = from_json::<ApiError>(response).unwrap();
+    assert_eq!(
+      err.existing_order_id.unwrap().to_string(),
+      "904837e3-3b76-47ec-b432-046db621571b"
+    );
+    assert_eq!(
+      err.reject_reason.as_deref(),
+      Some("opposite side market/stop order exists")
+    );
+
+    let response = r#"{
+  "code": 40310000,
+  "message": "insufficient qty available for order (requested: 10, available: 0)",
+  "related_orders": ["61e69015-8549-4bfd-b9c3-01e75843f47d"]
+}"#;
+    let err = from_json::<ApiError>(response).unwrap();
+    assert_eq!(err.existing_order_id, None);
+    assert_eq!(err.related_orders.len(), 1);
+    assert_eq!(
+      err.related_orders[0].to_string(),
+      "61e69015-8549-4bfd-b9c3-01e75843f47d"
+    );
+  }
+
+  /// Check that we map numeric codes to the correct [`ErrorCode`].
+  #[test]
+  fn error_code_from_code() {
+    assert_eq!(ErrorCode::from(42210000), ErrorCode::Unprocessable);
+    assert_eq!(ErrorCode::from(40010001), ErrorCode::InvalidRequest);
+    assert_eq!(ErrorCode::from(12345678), ErrorCode::Other(12345678));
+  }
+}
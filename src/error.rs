@@ -7,15 +7,23 @@ use std::fmt::Formatter;
 use std::fmt::Result as FmtResult;
 use std::io::Error as IoError;
 use std::str::from_utf8;
+use std::time::Duration;
 
 use http::Error as HttpError;
 use http::StatusCode as HttpStatusCode;
 use hyper::Error as HyperError;
+use rmp_serde::decode::Error as MsgPackError;
+
 use serde_json::Error as JsonError;
 use thiserror::Error;
 use url::ParseError;
 use websocket_util::tungstenite::Error as WebSocketError;
 
+use crate::data::v2::stream::SubscriptionError;
+use crate::endpoint::ApiError;
+use crate::endpoint::EndpointError;
+use crate::endpoint::ErrorCode;
+use crate::transport::TransportError;
 use crate::Str;
 
 
@@ -46,6 +54,48 @@ pub enum RequestError<E> {
     #[source]
     IoError,
   ),
+  /// An error reported by a custom transport.
+  #[error("the transport reported an error")]
+  Transport(#[source] TransportError),
+  /// The request did not complete within the given time.
+  #[error("the request timed out after {0:?}")]
+  Timeout(Duration),
+  /// The request would have modified orders or moved funds in the live
+  /// trading environment, but live trading was not confirmed.
+  #[error("live trading has not been confirmed")]
+  LiveTradingNotConfirmed,
+}
+
+impl<E> RequestError<E>
+where
+  E: EndpointError,
+{
+  /// Retrieve the [`ApiError`] reported by the endpoint, if any.
+  #[inline]
+  pub fn api_error(&self) -> Option<&ApiError> {
+    match self {
+      Self::Endpoint(err) => err.api_error(),
+      Self::Hyper(..)
+      | Self::HyperUtil(..)
+      | Self::Io(..)
+      | Self::Transport(..)
+      | Self::Timeout(..)
+      | Self::LiveTradingNotConfirmed => None,
+    }
+  }
+
+  /// Retrieve the [`ErrorCode`] reported by the endpoint, if any.
+  #[inline]
+  pub fn error_code(&self) -> Option<ErrorCode> {
+    self.api_error().and_then(ApiError::error_code)
+  }
+
+  /// Retrieve the ID Alpaca assigned to the failed request, if it was
+  /// reported alongside an [`ApiError`].
+  #[inline]
+  pub fn request_id(&self) -> Option<&str> {
+    self.api_error().and_then(|err| err.request_id.as_deref())
+  }
 }
 
 
@@ -84,6 +134,9 @@ pub enum Error {
     #[source]
     JsonError,
   ),
+  /// A MessagePack decoding error.
+  #[error("failed to decode MessagePack data")]
+  MsgPack(#[source] MsgPackError),
   /// An error directly originating in this crate.
   #[error("{0}")]
   Str(Str),
@@ -101,6 +154,17 @@ pub enum Error {
     #[source]
     WebSocketError,
   ),
+  /// A market data subscription request was refused.
+  #[error("failed to subscribe")]
+  Subscription(
+    #[from]
+    #[source]
+    SubscriptionError,
+  ),
+  /// The server did not respond to a control request within the given
+  /// time.
+  #[error("timed out waiting for a response from the server after {0:?}")]
+  Timeout(Duration),
 }
 
 
@@ -126,4 +190,24 @@ mod tests {
       "encountered an unexpected HTTP status: 404 Not Found: invalid"
     );
   }
+
+  /// Check that we can retrieve the error code reported by an endpoint
+  /// through a [`RequestError`].
+  #[test]
+  fn request_error_code() {
+    use crate::api::v2::order::CreateError;
+
+    let api_err = ApiError {
+      code: Some(42210000),
+      message: "extended hours order must be DAY limit orders".to_string(),
+      ..Default::default()
+    };
+    let err = RequestError::Endpoint(CreateError::NotPermitted(Ok(api_err.clone())));
+    assert_eq!(err.api_error(), Some(&api_err));
+    assert_eq!(err.error_code(), Some(ErrorCode::Unprocessable));
+
+    let err = RequestError::Endpoint(CreateError::NotPermitted(Err(b"foobar".to_vec())));
+    assert_eq!(err.api_error(), None);
+    assert_eq!(err.error_code(), None);
+  }
 }
@@ -0,0 +1,259 @@
+// Copyright (C) 2024 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use http::header::ETAG;
+use http::header::IF_NONE_MATCH;
+use http::HeaderValue;
+use http::Method;
+use http::Request;
+use http::Response;
+use http::StatusCode;
+
+use hyper::body::Bytes;
+
+use tracing::debug;
+
+
+/// A response body cached alongside the entity tag it was reported
+/// with.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CachedResponse {
+  /// The entity tag, as reported in the `ETag` header.
+  pub etag: String,
+  /// The (decoded) body of the response.
+  pub body: Bytes,
+  /// The type is non-exhaustive and open to extension.
+  #[doc(hidden)]
+  pub _non_exhaustive: (),
+}
+
+impl CachedResponse {
+  /// Create a `CachedResponse` object for the given entity tag and
+  /// body.
+  #[inline]
+  pub fn new(etag: impl Into<String>, body: impl Into<Bytes>) -> Self {
+    Self {
+      etag: etag.into(),
+      body: body.into(),
+      _non_exhaustive: (),
+    }
+  }
+}
+
+
+/// A trait for stores of responses used for conditional requests.
+///
+/// A store is enabled by passing it to `etag_cache` when building a
+/// client with [`Client::builder`][crate::Client::builder]. From then
+/// on, successful responses to `GET` requests that carry an `ETag`
+/// header are stored, keyed by the request's URI. Subsequent
+/// requests for the same URI are issued with an `If-None-Match` header
+/// and a `304 Not Modified` reply is served from the store. That is
+/// most useful for large and rarely changing data, such as the list of
+/// assets or the trading calendar. [`Middleware`][crate::Middleware]
+/// observes requests and responses as exchanged with the server, i.e.,
+/// including the `If-None-Match` header and `304` status.
+///
+/// Keys do not include credentials. Sharing a store between clients
+/// for different accounts should be avoided.
+pub trait EtagStore: Debug + Send + Sync {
+  /// Retrieve the response stored for the given key, if any.
+  fn get(&self, key: &str) -> Option<CachedResponse>;
+
+  /// Store a response for the given key, replacing any previous one.
+  fn put(&self, key: &str, response: CachedResponse);
+}
+
+/// An `Arc` wrapped store stays accessible after registration, e.g.,
+/// for persisting its contents on shutdown.
+impl<S> EtagStore for Arc<S>
+where
+  S: EtagStore + ?Sized,
+{
+  #[inline]
+  fn get(&self, key: &str) -> Option<CachedResponse> {
+    (**self).get(key)
+  }
+
+  #[inline]
+  fn put(&self, key: &str, response: CachedResponse) {
+    (**self).put(key, response)
+  }
+}
+
+
+/// A simple in-memory [`EtagStore`].
+#[derive(Debug, Default)]
+pub struct MemoryEtagStore {
+  /// The stored responses.
+  responses: Mutex<HashMap<String, CachedResponse>>,
+}
+
+impl MemoryEtagStore {
+  /// Create a new empty `MemoryEtagStore`.
+  #[inline]
+  pub fn new() -> Self {
+    Self::default()
+  }
+}
+
+impl EtagStore for MemoryEtagStore {
+  fn get(&self, key: &str) -> Option<CachedResponse> {
+    self
+      .responses
+      .lock()
+      .unwrap_or_else(|err| err.into_inner())
+      .get(key)
+      .cloned()
+  }
+
+  fn put(&self, key: &str, response: CachedResponse) {
+    let _prev = self
+      .responses
+      .lock()
+      .unwrap_or_else(|err| err.into_inner())
+      .insert(key.to_string(), response);
+  }
+}
+
+
+/// The state of a conditional request in flight.
+#[derive(Debug)]
+pub(crate) struct Conditional<'s> {
+  /// The store responses are kept in.
+  store: &'s dyn EtagStore,
+  /// The key identifying the request.
+  key: String,
+  /// The response previously stored for the request, if any.
+  cached: Option<CachedResponse>,
+}
+
+impl<'s> Conditional<'s> {
+  /// Make the provided request conditional on the previously stored
+  /// response for it, if any.
+  ///
+  /// `None` is returned for requests not eligible for caching.
+  pub(crate) fn prepare(store: &'s dyn EtagStore, request: &mut Request<Bytes>) -> Option<Self> {
+    if request.method() != Method::GET {
+      return None
+    }
+
+    let key = request.uri().to_string();
+    let cached = store.get(&key);
+    if let Some(cached) = &cached {
+      if let Ok(value) = HeaderValue::from_str(&cached.etag) {
+        let _prev = request.headers_mut().insert(IF_NONE_MATCH, value);
+      }
+    }
+
+    Some(Self { store, key, cached })
+  }
+
+  /// Resolve a `304 Not Modified` response from the store or store a
+  /// successful one.
+  pub(crate) fn resolve(self, response: &mut Response<Bytes>) {
+    match response.status() {
+      StatusCode::NOT_MODIFIED => {
+        if let Some(cached) = self.cached {
+          debug!("serving response from ETag cache");
+          *response.status_mut() = StatusCode::OK;
+          *response.body_mut() = cached.body;
+        }
+      },
+      StatusCode::OK => {
+        let etag = response
+          .headers()
+          .get(ETAG)
+          .and_then(|value| value.to_str().ok());
+        if let Some(etag) = etag {
+          let cached = CachedResponse::new(etag, response.body().clone());
+          let () = self.store.put(&self.key, cached);
+        }
+      },
+      _ => (),
+    }
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use futures::future::ready;
+  use futures::future::BoxFuture;
+  use futures::FutureExt as _;
+
+  use http_body_util::Full;
+
+  use test_log::test;
+
+  use crate::api::v2::assets;
+  use crate::transport::test::mock_api_info;
+  use crate::transport::Transport;
+  use crate::transport::TransportError;
+  use crate::Client;
+
+
+  /// A transport honoring `If-None-Match` headers and recording the
+  /// statuses it replied with.
+  #[derive(Clone, Debug, Default)]
+  struct Server {
+    /// The statuses replied with.
+    statuses: Arc<Mutex<Vec<StatusCode>>>,
+  }
+
+  impl Transport for Server {
+    fn issue(
+      &self,
+      request: Request<Full<Bytes>>,
+    ) -> BoxFuture<'_, Result<Response<Bytes>, TransportError>> {
+      let etag = HeaderValue::from_static(r#""v1""#);
+      let response = if request.headers().get(IF_NONE_MATCH) == Some(&etag) {
+        Response::builder()
+          .status(StatusCode::NOT_MODIFIED)
+          .body(Bytes::new())
+      } else {
+        Response::builder()
+          .status(StatusCode::OK)
+          .header(ETAG, etag)
+          .body(Bytes::from_static(b"[]"))
+      };
+      let response = response.unwrap();
+      let () = self.statuses.lock().unwrap().push(response.status());
+      ready(Ok(response)).boxed()
+    }
+  }
+
+
+  /// Check that repeated requests are served from the store once the
+  /// server reports the resource as not modified.
+  #[test(tokio::test)]
+  async fn serve_not_modified() {
+    let server = Server::default();
+    let store = Arc::new(MemoryEtagStore::new());
+    let api_info = mock_api_info();
+    let client = Client::builder()
+      .with_transport(server.clone())
+      .etag_cache(Arc::clone(&store))
+      .build(api_info);
+
+    let request = assets::ListReq::default();
+    for _ in 0..2 {
+      let assets = client.issue::<assets::List>(&request).await.unwrap();
+      assert_eq!(assets, Vec::new());
+    }
+
+    assert_eq!(
+      *server.statuses.lock().unwrap(),
+      vec![StatusCode::OK, StatusCode::NOT_MODIFIED]
+    );
+    let key = "https://paper-api.alpaca.markets/v2/assets?status=active&asset_class=us_equity";
+    assert_eq!(store.get(key).unwrap().etag, r#""v1""#);
+  }
+}
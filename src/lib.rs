@@ -91,6 +91,8 @@ extern crate http_endpoint;
 
 #[macro_use]
 mod endpoint;
+#[macro_use]
+mod setters;
 
 /// A module comprising the functionality backing interactions with the
 /// trading API.
@@ -99,20 +101,59 @@ pub mod api;
 /// A module for retrieving market data.
 pub mod data;
 
+/// A module comprising the functionality backing interactions with the
+/// Broker API.
+#[cfg(feature = "broker")]
+pub mod broker;
+
 mod api_info;
+mod capabilities;
 mod client;
+mod connection;
 mod error;
+mod etag;
+mod middleware;
+mod multiplexed;
+mod page_token;
+mod registry;
 mod subscribable;
+mod telemetry;
+mod transport;
 mod util;
 mod websocket;
 
 use std::borrow::Cow;
 
 pub use crate::api_info::ApiInfo;
+pub use crate::api_info::Environment;
+pub use crate::capabilities::Capabilities;
+pub use crate::capabilities::CapabilitiesError;
 pub use crate::client::Client;
+pub use crate::client::LogPolicy;
+pub use crate::client::RequestOptions;
+pub use crate::client::WithRaw;
+pub use crate::connection::ConnectionConfig;
+pub use crate::endpoint::Api;
+pub use crate::endpoint::ApiEndpoint;
 pub use crate::endpoint::ApiError;
+pub use crate::endpoint::EndpointError;
+pub use crate::endpoint::ErrorCode;
 pub use crate::error::Error;
 pub use crate::error::RequestError;
+pub use crate::etag::CachedResponse;
+pub use crate::etag::EtagStore;
+pub use crate::etag::MemoryEtagStore;
+pub use crate::middleware::Middleware;
+pub use crate::multiplexed::Event;
+pub use crate::multiplexed::Multiplexed;
+pub use crate::multiplexed::MultiplexedStream;
+pub use crate::multiplexed::MultiplexedSubscription;
+pub use crate::page_token::PageToken;
+pub use crate::registry::Registry;
 pub use crate::subscribable::Subscribable;
+pub use crate::telemetry::Observer;
+pub use crate::transport::MockTransport;
+pub use crate::transport::Transport;
+pub use crate::transport::TransportError;
 
 type Str = Cow<'static, str>;
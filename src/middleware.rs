@@ -0,0 +1,188 @@
+// Copyright (C) 2024 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use http::Request;
+use http::Response;
+
+use hyper::body::Bytes;
+
+
+/// A trait for inspecting and modifying the HTTP requests issued by a
+/// [`Client`][crate::Client] and the responses received for them.
+///
+/// Middleware is added with `layer` when building a client using
+/// [`Client::builder`][crate::Client::builder], for example for adding
+/// custom headers, signing requests, or audit logging. Requests are
+/// passed through middleware in the order of registration, after
+/// authentication information has been added. Responses are passed
+/// through in reverse order, before they are evaluated. Response
+/// bodies are fully collected and decoded at this point.
+///
+/// Both methods default to doing nothing. They run as part of issuing
+/// a request and any time spent in them delays it.
+pub trait Middleware: Debug + Send + Sync {
+  /// Inspect or modify a request before it is issued.
+  #[inline]
+  fn on_request(&self, request: &mut Request<Bytes>) {
+    let _ = request;
+  }
+
+  /// Inspect or modify a response before it is evaluated.
+  #[inline]
+  fn on_response(&self, response: &mut Response<Bytes>) {
+    let _ = response;
+  }
+}
+
+/// Middleware wrapped in an `Arc` can be registered as well, keeping
+/// any state it collects accessible.
+impl<M> Middleware for Arc<M>
+where
+  M: Middleware + ?Sized,
+{
+  #[inline]
+  fn on_request(&self, request: &mut Request<Bytes>) {
+    (**self).on_request(request)
+  }
+
+  #[inline]
+  fn on_response(&self, response: &mut Response<Bytes>) {
+    (**self).on_response(response)
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use std::sync::Mutex;
+
+  use futures::future::BoxFuture;
+
+  use http::HeaderValue;
+  use http::StatusCode;
+  use http_body_util::Full;
+
+  use test_log::test;
+
+  use crate::api::v2::clock;
+  use crate::transport::test::mock_api_info;
+  use crate::transport::MockTransport;
+  use crate::Client;
+  use crate::Transport;
+  use crate::TransportError;
+
+
+  /// Middleware adding a header to requests and recording the order in
+  /// which it got invoked.
+  #[derive(Debug)]
+  struct Tagging {
+    /// The tag to use.
+    tag: &'static str,
+    /// The log of invocations, shared among multiple instances.
+    log: Arc<Mutex<Vec<String>>>,
+  }
+
+  impl Middleware for Tagging {
+    fn on_request(&self, request: &mut Request<Bytes>) {
+      let _prev = request
+        .headers_mut()
+        .append("X-Tag", HeaderValue::from_static(self.tag));
+      let () = self
+        .log
+        .lock()
+        .unwrap()
+        .push(format!("request {}", self.tag));
+    }
+
+    fn on_response(&self, response: &mut Response<Bytes>) {
+      let () = self
+        .log
+        .lock()
+        .unwrap()
+        .push(format!("response {}", self.tag));
+
+      // Patch up the market state, to check that modifications take
+      // effect.
+      if self.tag == "outer" {
+        let body = String::from_utf8(response.body().to_vec()).unwrap();
+        *response.body_mut() = body.replace("true", "false").into();
+      }
+    }
+  }
+
+
+  /// A transport checking that requests carry the headers added by
+  /// `Tagging` middleware, before replying by means of a
+  /// `MockTransport`.
+  #[derive(Debug)]
+  struct Checking(MockTransport);
+
+  impl Transport for Checking {
+    fn issue(
+      &self,
+      request: Request<Full<Bytes>>,
+    ) -> BoxFuture<'_, Result<Response<Bytes>, TransportError>> {
+      let tags = request
+        .headers()
+        .get_all("X-Tag")
+        .iter()
+        .map(|value| value.to_str().unwrap())
+        .collect::<Vec<_>>();
+      assert_eq!(tags, vec!["outer", "inner"]);
+
+      self.0.issue(request)
+    }
+  }
+
+
+  /// Check that middleware is invoked in the expected order and can
+  /// modify requests as well as responses.
+  #[test(tokio::test)]
+  async fn layered_middleware() {
+    let mock = MockTransport::new();
+    let () = mock.respond::<clock::Get>(
+      &(),
+      StatusCode::OK,
+      r#"{
+  "timestamp": "2018-04-01T12:00:00.000Z",
+  "is_open": true,
+  "next_open": "2018-04-01T12:00:00.000Z",
+  "next_close": "2018-04-01T12:00:00.000Z"
+}"#,
+    );
+
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let outer = Tagging {
+      tag: "outer",
+      log: Arc::clone(&log),
+    };
+    let inner = Tagging {
+      tag: "inner",
+      log: Arc::clone(&log),
+    };
+
+    let api_info = mock_api_info();
+    let client = Client::builder()
+      .with_transport(Checking(mock))
+      .layer(outer)
+      .layer(inner)
+      .build(api_info);
+
+    let clock = client.issue::<clock::Get>(&()).await.unwrap();
+    assert!(!clock.open);
+    assert_eq!(
+      *log.lock().unwrap(),
+      vec![
+        "request outer",
+        "request inner",
+        "response inner",
+        "response outer"
+      ]
+    );
+  }
+}
@@ -0,0 +1,317 @@
+// Copyright (C) 2024 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use std::pin::Pin;
+
+use async_trait::async_trait;
+
+use futures::future::try_join;
+use futures::future::BoxFuture;
+use futures::stream::FusedStream;
+use futures::task::Context;
+use futures::task::Poll;
+use futures::FutureExt as _;
+use futures::Stream;
+use futures::StreamExt as _;
+
+use serde::de::DeserializeOwned;
+use serde_json::Error as JsonError;
+
+use websocket_util::tungstenite::Error as WebSocketError;
+
+use crate::api::v2::updates::OrderUpdate;
+use crate::api::v2::updates::OrderUpdates;
+use crate::data::v2::stream::Bar;
+use crate::data::v2::stream::Data;
+use crate::data::v2::stream::Quote;
+use crate::data::v2::stream::RealtimeData;
+use crate::data::v2::stream::Source;
+use crate::data::v2::stream::Trade;
+use crate::ApiInfo;
+use crate::ConnectionConfig;
+use crate::Error;
+use crate::Subscribable;
+
+
+pub(crate) type UpdatesStream = <OrderUpdates as Subscribable>::Stream;
+pub(crate) type UpdatesSubscription = <OrderUpdates as Subscribable>::Subscription;
+pub(crate) type DataStream<S, B, Q, T> = <RealtimeData<S, B, Q, T> as Subscribable>::Stream;
+pub(crate) type DataSubscription<S, B, Q, T> =
+  <RealtimeData<S, B, Q, T> as Subscribable>::Subscription;
+
+
+/// An event as yielded by a [`MultiplexedStream`].
+#[derive(Debug)]
+#[non_exhaustive]
+#[allow(clippy::large_enum_variant)]
+pub enum Event<B = Bar, Q = Quote, T = Trade> {
+  /// An item from the order updates stream.
+  OrderUpdate(Result<Result<OrderUpdate, JsonError>, WebSocketError>),
+  /// An item from the realtime market data stream.
+  Data(Result<Result<Data<B, Q, T>, JsonError>, WebSocketError>),
+}
+
+
+/// A stream combining order updates and realtime market data.
+///
+/// Both underlying streams are polled in an alternating fashion, so
+/// that neither can starve the other. The stream ends once both
+/// underlying streams have ended.
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct MultiplexedStream<S, B = Bar, Q = Quote, T = Trade>
+where
+  S: Source,
+  B: Send + Unpin + Debug + DeserializeOwned,
+  Q: Send + Unpin + Debug + DeserializeOwned,
+  T: Send + Unpin + Debug + DeserializeOwned,
+{
+  /// The order updates stream.
+  updates: UpdatesStream,
+  /// The realtime market data stream.
+  data: DataStream<S, B, Q, T>,
+  /// Whether to poll the data stream first on the next poll.
+  data_first: bool,
+}
+
+impl<S, B, Q, T> MultiplexedStream<S, B, Q, T>
+where
+  S: Source,
+  B: Send + Unpin + Debug + DeserializeOwned,
+  Q: Send + Unpin + Debug + DeserializeOwned,
+  T: Send + Unpin + Debug + DeserializeOwned,
+{
+  /// Retrieve the underlying realtime market data stream.
+  ///
+  /// Market data subscription changes only resolve while this stream
+  /// is being polled, so it should be provided to
+  /// [`drive`][crate::data::v2::stream::drive] for that purpose.
+  #[inline]
+  pub fn data_stream(&mut self) -> &mut DataStream<S, B, Q, T> {
+    &mut self.data
+  }
+
+  /// Poll the order updates stream, if it has not ended yet.
+  fn poll_updates(&mut self, ctx: &mut Context<'_>) -> Option<Event<B, Q, T>> {
+    if self.updates.is_terminated() {
+      return None
+    }
+
+    match self.updates.poll_next_unpin(ctx) {
+      Poll::Ready(Some(update)) => Some(Event::OrderUpdate(update)),
+      Poll::Ready(None) | Poll::Pending => None,
+    }
+  }
+
+  /// Poll the realtime market data stream, if it has not ended yet.
+  fn poll_data(&mut self, ctx: &mut Context<'_>) -> Option<Event<B, Q, T>> {
+    if self.data.is_terminated() {
+      return None
+    }
+
+    match self.data.poll_next_unpin(ctx) {
+      Poll::Ready(Some(data)) => Some(Event::Data(data)),
+      Poll::Ready(None) | Poll::Pending => None,
+    }
+  }
+}
+
+impl<S, B, Q, T> Stream for MultiplexedStream<S, B, Q, T>
+where
+  S: Source,
+  B: Send + Unpin + Debug + DeserializeOwned,
+  Q: Send + Unpin + Debug + DeserializeOwned,
+  T: Send + Unpin + Debug + DeserializeOwned,
+{
+  type Item = Event<B, Q, T>;
+
+  fn poll_next(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+    let this = &mut *self;
+    this.data_first = !this.data_first;
+
+    let event = if this.data_first {
+      this.poll_data(ctx).or_else(|| this.poll_updates(ctx))
+    } else {
+      this.poll_updates(ctx).or_else(|| this.poll_data(ctx))
+    };
+
+    match event {
+      Some(event) => Poll::Ready(Some(event)),
+      None if this.is_terminated() => Poll::Ready(None),
+      None => Poll::Pending,
+    }
+  }
+}
+
+impl<S, B, Q, T> FusedStream for MultiplexedStream<S, B, Q, T>
+where
+  S: Source,
+  B: Send + Unpin + Debug + DeserializeOwned,
+  Q: Send + Unpin + Debug + DeserializeOwned,
+  T: Send + Unpin + Debug + DeserializeOwned,
+{
+  #[inline]
+  fn is_terminated(&self) -> bool {
+    self.updates.is_terminated() && self.data.is_terminated()
+  }
+}
+
+
+/// A subscription combining the control handles of the order updates
+/// and realtime market data streams.
+#[derive(Debug)]
+pub struct MultiplexedSubscription<S, B = Bar, Q = Quote, T = Trade>
+where
+  S: Source,
+  B: Send + Unpin + Debug + DeserializeOwned,
+  Q: Send + Unpin + Debug + DeserializeOwned,
+  T: Send + Unpin + Debug + DeserializeOwned,
+{
+  /// The order updates subscription.
+  updates: UpdatesSubscription,
+  /// The realtime market data subscription.
+  data: DataSubscription<S, B, Q, T>,
+}
+
+impl<S, B, Q, T> MultiplexedSubscription<S, B, Q, T>
+where
+  S: Source,
+  B: Send + Unpin + Debug + DeserializeOwned,
+  Q: Send + Unpin + Debug + DeserializeOwned,
+  T: Send + Unpin + Debug + DeserializeOwned,
+{
+  /// Retrieve the subscription controlling order updates.
+  #[inline]
+  pub fn order_updates(&mut self) -> &mut UpdatesSubscription {
+    &mut self.updates
+  }
+
+  /// Retrieve the subscription controlling realtime market data.
+  #[inline]
+  pub fn data(&mut self) -> &mut DataSubscription<S, B, Q, T> {
+    &mut self.data
+  }
+}
+
+
+/// A type used for requesting a combined subscription to order updates
+/// and realtime market data from the given source.
+///
+/// The bar (`B`), quote (`Q`), and trade (`T`) types are the same as
+/// used by [`RealtimeData`].
+#[derive(Debug)]
+pub struct Multiplexed<S, B = Bar, Q = Quote, T = Trade> {
+  /// Phantom data to make sure that we "use" `S`.
+  _phantom: PhantomData<(S, B, Q, T)>,
+}
+
+#[async_trait]
+impl<S, B, Q, T> Subscribable for Multiplexed<S, B, Q, T>
+where
+  S: Source,
+  B: Send + Unpin + Debug + DeserializeOwned,
+  Q: Send + Unpin + Debug + DeserializeOwned,
+  T: Send + Unpin + Debug + DeserializeOwned,
+{
+  type Input = ApiInfo;
+  type Subscription = MultiplexedSubscription<S, B, Q, T>;
+  type Stream = MultiplexedStream<S, B, Q, T>;
+
+  async fn connect(api_info: &Self::Input) -> Result<(Self::Stream, Self::Subscription), Error> {
+    Self::connect_with(api_info, &ConnectionConfig::default()).await
+  }
+
+  fn connect_with<'a>(
+    api_info: &'a Self::Input,
+    config: &'a ConnectionConfig,
+  ) -> BoxFuture<'a, Result<(Self::Stream, Self::Subscription), Error>>
+  where
+    Self: 'a,
+  {
+    async move {
+      let ((updates, updates_subscription), (data, data_subscription)) = try_join(
+        OrderUpdates::connect_with(api_info, config),
+        RealtimeData::<S, B, Q, T>::connect_with(api_info, config),
+      )
+      .await?;
+
+      let stream = MultiplexedStream {
+        updates,
+        data,
+        data_first: false,
+      };
+      let subscription = MultiplexedSubscription {
+        updates: updates_subscription,
+        data: data_subscription,
+      };
+      Ok((stream, subscription))
+    }
+    .boxed()
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use std::time::Duration;
+
+  use serial_test::serial;
+
+  use test_log::test;
+
+  use tokio::time::timeout;
+
+  use crate::data::v2::stream::drive;
+  use crate::data::v2::stream::MarketData;
+  use crate::data::v2::stream::Symbols;
+  use crate::data::v2::stream::IEX;
+  use crate::Client;
+
+
+  /// Check that we can stream order updates and realtime market data
+  /// through a single stream.
+  ///
+  /// Note that we do not have any control over whether the market is
+  /// open or not and as such we can only try on a best-effort basis to
+  /// receive and decode updates.
+  #[test(tokio::test)]
+  #[serial(realtime_data)]
+  async fn stream_multiplexed() {
+    let api_info = ApiInfo::from_env().unwrap();
+    let client = Client::new(api_info);
+    let (mut stream, mut subscription) = client.subscribe::<Multiplexed<IEX>>().await.unwrap();
+
+    let data = MarketData {
+      bars: Symbols::All,
+      ..Default::default()
+    };
+
+    let subscribe = subscription.data().subscribe(&data).boxed_local();
+    let () = drive(subscribe, stream.data_stream())
+      .await
+      .unwrap()
+      .unwrap()
+      .unwrap();
+
+    assert_eq!(subscription.data().subscriptions(), &data);
+
+    let read = stream.for_each(|event| async {
+      match event {
+        Event::OrderUpdate(update) => {
+          let _update = update.unwrap().unwrap();
+        },
+        Event::Data(data) => {
+          assert!(data.unwrap().unwrap().is_bar());
+        },
+      }
+    });
+
+    if timeout(Duration::from_millis(100), read).await.is_ok() {
+      panic!("multiplexed stream got exhausted unexpectedly")
+    }
+  }
+}
@@ -0,0 +1,79 @@
+// Copyright (C) 2024 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::ops::Deref;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+
+/// An opaque token identifying a page of results of a paginated
+/// request.
+///
+/// Responses to paginated requests report the token of the next page,
+/// if any. Provided as part of the next request, it continues the
+/// retrieval from there.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct PageToken(pub String);
+
+impl Deref for PageToken {
+  type Target = str;
+
+  #[inline]
+  fn deref(&self) -> &Self::Target {
+    &self.0
+  }
+}
+
+impl Display for PageToken {
+  #[inline]
+  fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+    fmt.write_str(&self.0)
+  }
+}
+
+impl From<String> for PageToken {
+  #[inline]
+  fn from(token: String) -> Self {
+    Self(token)
+  }
+}
+
+impl From<&str> for PageToken {
+  #[inline]
+  fn from(token: &str) -> Self {
+    Self(token.to_string())
+  }
+}
+
+impl From<PageToken> for String {
+  #[inline]
+  fn from(token: PageToken) -> Self {
+    token.0
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use serde_json::from_str as from_json;
+  use serde_json::to_string as to_json;
+
+
+  /// Check that page tokens are serialized as plain strings.
+  #[test]
+  fn serialize_deserialize_page_token() {
+    let token = PageToken::from("MTAwMA==");
+    let json = to_json(&token).unwrap();
+    assert_eq!(json, r#""MTAwMA==""#);
+    assert_eq!(from_json::<PageToken>(&json).unwrap(), token);
+    assert_eq!(&*token, "MTAwMA==");
+    assert_eq!(token.to_string(), "MTAwMA==");
+  }
+}
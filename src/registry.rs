@@ -0,0 +1,475 @@
+// Copyright (C) 2024 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::fmt::Debug;
+use std::pin::Pin;
+
+use futures::stream::FusedStream;
+use futures::task::Context;
+use futures::task::Poll;
+use futures::task::Waker;
+use futures::Stream;
+use futures::StreamExt as _;
+
+use serde::de::DeserializeOwned;
+
+use crate::data::v2::stream::AnyStream;
+use crate::data::v2::stream::AnySubscription;
+use crate::data::v2::stream::Bar;
+use crate::data::v2::stream::Quote;
+use crate::data::v2::stream::Source;
+use crate::data::v2::stream::Trade;
+use crate::multiplexed::DataStream;
+use crate::multiplexed::DataSubscription;
+use crate::multiplexed::UpdatesStream;
+use crate::multiplexed::UpdatesSubscription;
+use crate::Event;
+
+
+/// A stream along with the subscription controlling it.
+#[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
+enum Entry<B, Q, T>
+where
+  B: Send + Unpin + Debug + DeserializeOwned,
+  Q: Send + Unpin + Debug + DeserializeOwned,
+  T: Send + Unpin + Debug + DeserializeOwned,
+{
+  /// An order updates stream.
+  OrderUpdates(UpdatesStream, UpdatesSubscription),
+  /// A realtime market data stream.
+  Data(AnyStream<B, Q, T>, AnySubscription<B, Q, T>),
+}
+
+impl<B, Q, T> Entry<B, Q, T>
+where
+  B: Send + Unpin + Debug + DeserializeOwned,
+  Q: Send + Unpin + Debug + DeserializeOwned,
+  T: Send + Unpin + Debug + DeserializeOwned,
+{
+  fn is_terminated(&self) -> bool {
+    match self {
+      Self::OrderUpdates(stream, _) => stream.is_terminated(),
+      Self::Data(stream, _) => stream.is_terminated(),
+    }
+  }
+
+  fn poll_next(&mut self, ctx: &mut Context<'_>) -> Poll<Option<Event<B, Q, T>>> {
+    match self {
+      Self::OrderUpdates(stream, _) => stream
+        .poll_next_unpin(ctx)
+        .map(|update| update.map(Event::OrderUpdate)),
+      Self::Data(stream, _) => stream
+        .poll_next_unpin(ctx)
+        .map(|data| data.map(Event::Data)),
+    }
+  }
+}
+
+
+/// A collection of order update and realtime market data streams
+/// that can be changed at runtime.
+///
+/// Each stream is registered under a user provided key, along with the
+/// subscription controlling it. The registry itself is a stream
+/// yielding the events of all registered streams, tagged with the key
+/// of the stream they originate from. Streams are polled in a round
+/// robin fashion, so that none can starve the others.
+///
+/// The stream ends once none of the registered streams has any more
+/// events to yield. While the registry is empty, it stays pending and
+/// is woken up once a stream gets registered.
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct Registry<K, B = Bar, Q = Quote, T = Trade>
+where
+  B: Send + Unpin + Debug + DeserializeOwned,
+  Q: Send + Unpin + Debug + DeserializeOwned,
+  T: Send + Unpin + Debug + DeserializeOwned,
+{
+  /// The registered streams, in order of registration.
+  entries: Vec<(K, Entry<B, Q, T>)>,
+  /// The index of the entry to poll first on the next poll.
+  next: usize,
+  /// The waker of the task that last polled the registry without it
+  /// yielding an event.
+  waker: Option<Waker>,
+}
+
+impl<K, B, Q, T> Registry<K, B, Q, T>
+where
+  K: PartialEq,
+  B: Send + Unpin + Debug + DeserializeOwned,
+  Q: Send + Unpin + Debug + DeserializeOwned,
+  T: Send + Unpin + Debug + DeserializeOwned,
+{
+  /// Create a new, empty registry.
+  #[inline]
+  pub fn new() -> Self {
+    Self {
+      entries: Vec::new(),
+      next: 0,
+      waker: None,
+    }
+  }
+
+  fn position(&self, key: &K) -> Option<usize> {
+    self.entries.iter().position(|(k, _)| k == key)
+  }
+
+  fn insert(&mut self, key: K, entry: Entry<B, Q, T>) -> bool {
+    // The new stream has not been polled yet and so could not have
+    // registered the waker of the task polling us.
+    if let Some(waker) = self.waker.take() {
+      let () = waker.wake();
+    }
+
+    if let Some(idx) = self.position(&key) {
+      self.entries[idx].1 = entry;
+      true
+    } else {
+      let () = self.entries.push((key, entry));
+      false
+    }
+  }
+
+  /// Register an order updates stream, as connected to via
+  /// [`OrderUpdates`][crate::api::v2::updates::OrderUpdates], under
+  /// the provided key.
+  ///
+  /// A stream already registered under the same key is replaced. The
+  /// return value indicates whether that was the case.
+  pub fn insert_order_updates(
+    &mut self,
+    key: K,
+    stream: UpdatesStream,
+    subscription: UpdatesSubscription,
+  ) -> bool {
+    self.insert(key, Entry::OrderUpdates(stream, subscription))
+  }
+
+  /// Register a realtime market data stream, as connected to via
+  /// [`RealtimeData`][crate::data::v2::stream::RealtimeData] for any
+  /// source, under the provided key.
+  ///
+  /// A stream already registered under the same key is replaced. The
+  /// return value indicates whether that was the case.
+  pub fn insert_data<S>(
+    &mut self,
+    key: K,
+    stream: DataStream<S, B, Q, T>,
+    subscription: DataSubscription<S, B, Q, T>,
+  ) -> bool
+  where
+    S: Source,
+  {
+    self.insert(key, Entry::Data(stream, subscription))
+  }
+
+  /// Remove the stream registered under the provided key, closing
+  /// the underlying connection.
+  ///
+  /// The return value indicates whether such a stream was registered.
+  pub fn remove(&mut self, key: &K) -> bool {
+    if let Some(idx) = self.position(key) {
+      let _entry = self.entries.remove(idx);
+      if self.next > idx {
+        self.next -= 1;
+      }
+      true
+    } else {
+      false
+    }
+  }
+
+  /// Check whether a stream is registered under the provided key.
+  #[inline]
+  pub fn contains_key(&self, key: &K) -> bool {
+    self.position(key).is_some()
+  }
+
+  /// Retrieve an iterator over the keys of all registered streams.
+  #[inline]
+  pub fn keys(&self) -> impl Iterator<Item = &K> {
+    self.entries.iter().map(|(key, _)| key)
+  }
+
+  /// Retrieve the number of registered streams.
+  #[inline]
+  pub fn len(&self) -> usize {
+    self.entries.len()
+  }
+
+  /// Check whether the registry is empty.
+  #[inline]
+  pub fn is_empty(&self) -> bool {
+    self.entries.is_empty()
+  }
+
+  /// Retrieve the subscription of the order updates stream registered
+  /// under the provided key.
+  ///
+  /// `None` is returned if no stream is registered under this key or
+  /// if it is not an order updates stream.
+  pub fn order_updates_mut(&mut self, key: &K) -> Option<&mut UpdatesSubscription> {
+    let idx = self.position(key)?;
+    match &mut self.entries[idx].1 {
+      Entry::OrderUpdates(_, subscription) => Some(subscription),
+      Entry::Data(..) => None,
+    }
+  }
+
+  /// Retrieve the subscription of the realtime market data stream
+  /// registered under the provided key, along with the stream itself.
+  ///
+  /// Market data subscription changes only resolve while the stream is
+  /// being polled, so the stream should be provided to
+  /// [`drive`][crate::data::v2::stream::drive] for that purpose.
+  ///
+  /// `None` is returned if no stream is registered under this key or
+  /// if it is not a realtime market data stream.
+  #[allow(clippy::type_complexity)]
+  pub fn data_mut(
+    &mut self,
+    key: &K,
+  ) -> Option<(&mut AnySubscription<B, Q, T>, &mut AnyStream<B, Q, T>)> {
+    let idx = self.position(key)?;
+    match &mut self.entries[idx].1 {
+      Entry::Data(stream, subscription) => Some((subscription, stream)),
+      Entry::OrderUpdates(..) => None,
+    }
+  }
+}
+
+impl<K, B, Q, T> Default for Registry<K, B, Q, T>
+where
+  K: PartialEq,
+  B: Send + Unpin + Debug + DeserializeOwned,
+  Q: Send + Unpin + Debug + DeserializeOwned,
+  T: Send + Unpin + Debug + DeserializeOwned,
+{
+  #[inline]
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<K, B, Q, T> Stream for Registry<K, B, Q, T>
+where
+  K: Clone + Unpin,
+  B: Send + Unpin + Debug + DeserializeOwned,
+  Q: Send + Unpin + Debug + DeserializeOwned,
+  T: Send + Unpin + Debug + DeserializeOwned,
+{
+  type Item = (K, Event<B, Q, T>);
+
+  fn poll_next(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+    let this = &mut *self;
+    let count = this.entries.len();
+
+    for i in 0..count {
+      let idx = (this.next + i) % count;
+      let (key, entry) = &mut this.entries[idx];
+      if entry.is_terminated() {
+        continue
+      }
+
+      if let Poll::Ready(Some(event)) = entry.poll_next(ctx) {
+        this.next = (idx + 1) % count;
+        return Poll::Ready(Some((key.clone(), event)))
+      }
+    }
+
+    if this.is_terminated() {
+      Poll::Ready(None)
+    } else {
+      this.waker = Some(ctx.waker().clone());
+      Poll::Pending
+    }
+  }
+}
+
+impl<K, B, Q, T> FusedStream for Registry<K, B, Q, T>
+where
+  K: Clone + Unpin,
+  B: Send + Unpin + Debug + DeserializeOwned,
+  Q: Send + Unpin + Debug + DeserializeOwned,
+  T: Send + Unpin + Debug + DeserializeOwned,
+{
+  #[inline]
+  fn is_terminated(&self) -> bool {
+    !self.entries.is_empty() && self.entries.iter().all(|(_, entry)| entry.is_terminated())
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use std::sync::atomic::AtomicBool;
+  use std::sync::atomic::Ordering;
+  use std::sync::Arc;
+  use std::task::Wake;
+
+  use futures::channel::oneshot::channel;
+  use futures::channel::oneshot::Receiver;
+  use futures::FutureExt as _;
+  use futures::SinkExt as _;
+
+  use test_log::test;
+
+  use tungstenite::tungstenite::Utf8Bytes;
+
+  use websocket_util::test::WebSocketStream;
+  use websocket_util::tungstenite::Error as WebSocketError;
+  use websocket_util::tungstenite::Message;
+
+  use crate::api::v2::updates::OrderUpdates;
+  use crate::data::v2::stream::drive;
+  use crate::data::v2::stream::Data;
+  use crate::data::v2::stream::MarketData;
+  use crate::data::v2::stream::RealtimeData;
+  use crate::data::v2::stream::SIP;
+  use crate::websocket::test::mock_stream;
+
+
+  const UPDATES_AUTH_RESP: &str =
+    r#"{"stream":"authorization","data":{"action":"authenticate","status":"authorized"}}"#;
+  const UPDATES_STREAM_RESP: &str =
+    r#"{"stream":"listening","data":{"streams":["trade_updates"]}}"#;
+  const DATA_CONN_RESP: &str = r#"[{"T":"success","msg":"connected"}]"#;
+  const DATA_AUTH_RESP: &str = r#"[{"T":"success","msg":"authenticated"}]"#;
+  const DATA_SUB_REQ: &str = r#"{"action":"subscribe","bars":["SPY"],"quotes":[],"trades":[]}"#;
+  const DATA_SUB_RESP: &str = r#"[{"T":"subscription","bars":["SPY"]}]"#;
+  const DATA_BAR: &str = r#"[{"T":"b","S":"SPY","o":388.985,"h":389.13,"l":388.975,"c":389.12,"v":49378,"t":"2021-02-22T19:15:00Z"}]"#;
+
+
+  /// Serve an order updates stream that ends right after it was
+  /// established.
+  async fn serve_updates(mut stream: WebSocketStream) -> Result<(), WebSocketError> {
+    let _auth = stream.next().await.unwrap()?;
+    stream
+      .send(Message::Text(Utf8Bytes::from_static(UPDATES_AUTH_RESP)))
+      .await?;
+    let _listen = stream.next().await.unwrap()?;
+    stream
+      .send(Message::Text(Utf8Bytes::from_static(UPDATES_STREAM_RESP)))
+      .await?;
+    stream.send(Message::Close(None)).await?;
+    Ok(())
+  }
+
+  /// Serve a realtime market data stream sending a single bar once a
+  /// subscription was made and the provided receiver fired.
+  async fn serve_data(
+    mut stream: WebSocketStream,
+    receiver: Receiver<()>,
+  ) -> Result<(), WebSocketError> {
+    stream
+      .send(Message::Text(Utf8Bytes::from_static(DATA_CONN_RESP)))
+      .await?;
+    let _auth = stream.next().await.unwrap()?;
+    stream
+      .send(Message::Text(Utf8Bytes::from_static(DATA_AUTH_RESP)))
+      .await?;
+    assert_eq!(
+      stream.next().await.unwrap()?,
+      Message::Text(Utf8Bytes::from_static(DATA_SUB_REQ)),
+    );
+    stream
+      .send(Message::Text(Utf8Bytes::from_static(DATA_SUB_RESP)))
+      .await?;
+
+    // Wait until the subscription was confirmed to the client before
+    // sending any data, as it would get discarded otherwise.
+    let () = receiver.await.unwrap();
+    stream
+      .send(Message::Text(Utf8Bytes::from_static(DATA_BAR)))
+      .await?;
+    stream.send(Message::Close(None)).await?;
+    Ok(())
+  }
+
+
+  /// Check that we can manage streams in a `Registry`.
+  #[test]
+  fn manage_entries() {
+    let mut registry = Registry::<&str>::new();
+    assert!(registry.is_empty());
+    assert!(!registry.is_terminated());
+    assert!(registry.order_updates_mut(&"updates").is_none());
+    assert!(registry.data_mut(&"data").is_none());
+    assert!(!registry.remove(&"updates"));
+  }
+
+  /// Check that an empty `Registry` is pending and gets woken up once a
+  /// stream is registered.
+  #[test(tokio::test)]
+  async fn wake_on_insert() {
+    struct Flag(AtomicBool);
+
+    impl Wake for Flag {
+      fn wake(self: Arc<Self>) {
+        let () = self.0.store(true, Ordering::SeqCst);
+      }
+    }
+
+    let flag = Arc::new(Flag(AtomicBool::new(false)));
+    let waker = Waker::from(Arc::clone(&flag));
+    let mut ctx = Context::from_waker(&waker);
+
+    let mut registry = Registry::<&str>::new();
+    assert!(registry.poll_next_unpin(&mut ctx).is_pending());
+    assert!(!flag.0.load(Ordering::SeqCst));
+
+    let (updates, subscription) = mock_stream::<OrderUpdates, _, _>(serve_updates)
+      .await
+      .unwrap();
+    assert!(!registry.insert_order_updates("updates", updates, subscription));
+    assert!(flag.0.load(Ordering::SeqCst));
+  }
+
+  /// Check that a `Registry` yields the events of streams of different
+  /// kinds, tagged with their keys.
+  #[test(tokio::test)]
+  async fn stream_registered() {
+    let (updates, updates_subscription) = mock_stream::<OrderUpdates, _, _>(serve_updates)
+      .await
+      .unwrap();
+    let (sender, receiver) = channel();
+    let serve = |stream| serve_data(stream, receiver);
+    let (data, data_subscription) = mock_stream::<RealtimeData<SIP>, _, _>(serve).await.unwrap();
+
+    let mut registry = Registry::new();
+    assert!(!registry.insert_order_updates("updates", updates, updates_subscription));
+    assert!(!registry.insert_data::<SIP>("data", data, data_subscription));
+    assert_eq!(registry.len(), 2);
+    assert!(registry.contains_key(&"data"));
+    assert_eq!(
+      registry.keys().collect::<Vec<_>>(),
+      vec![&"updates", &"data"]
+    );
+    assert!(registry.data_mut(&"updates").is_none());
+    assert!(registry.order_updates_mut(&"data").is_none());
+
+    let mut data = MarketData::default();
+    data.set_bars(["SPY"]);
+
+    let (subscription, stream) = registry.data_mut(&"data").unwrap();
+    let subscribe = subscription.subscribe(&data).boxed_local();
+    let () = drive(subscribe, stream).await.unwrap().unwrap().unwrap();
+
+    let () = sender.send(()).unwrap();
+    let events = (&mut registry).collect::<Vec<_>>().await;
+    assert_eq!(events.len(), 1);
+    match &events[0] {
+      ("data", Event::Data(Ok(Ok(Data::Bar(bar))))) => assert_eq!(bar.symbol, "SPY"),
+      event => panic!("received unexpected event: {event:?}"),
+    }
+    assert!(registry.is_terminated());
+
+    assert!(registry.remove(&"updates"));
+    assert_eq!(registry.keys().collect::<Vec<_>>(), vec![&"data"]);
+  }
+}
@@ -0,0 +1,48 @@
+// Copyright (C) 2024 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/// A macro for generating a `new` constructor as well as non-consuming
+/// setter methods for the members of a `*ReqInit` type.
+///
+/// Members of type `Option<T>` get a setter accepting a `T`, all others
+/// one accepting the member's type itself.
+macro_rules! Setters {
+  ( $name:ident { $($members:tt)* } ) => {
+    Setters! { @munch $name { $($members)* } -> {
+      #[doc = concat!("Create a new `", stringify!($name), "` with default values.")]
+      #[inline]
+      pub fn new() -> Self {
+        Self::default()
+      }
+    }}
+  };
+  ( @munch $name:ident { } -> { $($setters:tt)* } ) => {
+    impl $name {
+      $($setters)*
+    }
+  };
+  ( @munch $name:ident { $member:ident: Option<$ty:ty>, $($rest:tt)* } -> { $($setters:tt)* } ) => {
+    Setters! { @munch $name { $($rest)* } -> {
+      $($setters)*
+
+      #[doc = concat!("Set the `", stringify!($member), "` member.")]
+      #[inline]
+      pub fn $member(&mut self, $member: $ty) -> &mut Self {
+        self.$member = Some($member);
+        self
+      }
+    }}
+  };
+  ( @munch $name:ident { $member:ident: $ty:ty, $($rest:tt)* } -> { $($setters:tt)* } ) => {
+    Setters! { @munch $name { $($rest)* } -> {
+      $($setters)*
+
+      #[doc = concat!("Set the `", stringify!($member), "` member.")]
+      #[inline]
+      pub fn $member(&mut self, $member: $ty) -> &mut Self {
+        self.$member = $member;
+        self
+      }
+    }}
+  };
+}
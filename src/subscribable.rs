@@ -3,6 +3,9 @@
 
 use async_trait::async_trait;
 
+use futures::future::BoxFuture;
+
+use crate::connection::ConnectionConfig;
 use crate::Error;
 
 
@@ -20,4 +23,20 @@ pub trait Subscribable {
   /// Establish a connection to receive updates and return a stream
   /// along with a subscription to control the stream, if applicable.
   async fn connect(input: &Self::Input) -> Result<(Self::Stream, Self::Subscription), Error>;
+
+  /// Establish a connection the same way [`connect`][Self::connect]
+  /// does, but using the provided connection settings.
+  ///
+  /// The default implementation ignores the settings.
+  #[doc(hidden)]
+  #[allow(clippy::type_complexity)]
+  fn connect_with<'a>(
+    input: &'a Self::Input,
+    _config: &'a ConnectionConfig,
+  ) -> BoxFuture<'a, Result<(Self::Stream, Self::Subscription), Error>>
+  where
+    Self: 'a,
+  {
+    Self::connect(input)
+  }
 }
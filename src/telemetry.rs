@@ -0,0 +1,243 @@
+// Copyright (C) 2024 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::time::Duration;
+
+use http::Method;
+use http::StatusCode;
+
+use url::Url;
+
+
+/// A trait for observing the operation of a [`Client`][crate::Client]
+/// and the streams created through it, e.g., for the purpose of
+/// exporting metrics.
+///
+/// Observers are registered using `observer` when building a client
+/// with [`Client::builder`][crate::Client::builder]. All methods have
+/// default implementations doing nothing, so that implementors only
+/// need to provide the ones they are interested in. Callbacks happen on
+/// the task issuing the request or polling the stream, so expensive
+/// processing is best deferred.
+pub trait Observer: Debug + Send + Sync {
+  /// Invoked before an HTTP request to the given path is issued.
+  #[inline]
+  fn request_started(&self, method: &Method, path: &str) {
+    let _ = (method, path);
+  }
+
+  /// Invoked once an HTTP request to the given path has finished,
+  /// successfully or not, after the provided duration.
+  ///
+  /// `status` is `None` if no response was received, e.g., because
+  /// the connection failed or the request timed out.
+  #[inline]
+  fn request_finished(
+    &self,
+    method: &Method,
+    path: &str,
+    status: Option<StatusCode>,
+    elapsed: Duration,
+  ) {
+    let _ = (method, path, status, elapsed);
+  }
+
+  /// Invoked whenever a websocket connection to the provided URL has
+  /// been established.
+  ///
+  /// Reconnects manifest as repeated invocations for the same URL.
+  #[inline]
+  fn stream_connected(&self, url: &Url) {
+    let _ = url;
+  }
+
+  /// Invoked for every text or binary message received over the
+  /// websocket connection to the provided URL.
+  ///
+  /// Note that a single websocket message may carry multiple
+  /// updates, e.g., a batch of trades.
+  #[inline]
+  fn stream_message(&self, url: &Url) {
+    let _ = url;
+  }
+}
+
+/// Registering an `Arc` allows for keeping a handle to the observer,
+/// e.g., to read out collected metrics.
+impl<O> Observer for Arc<O>
+where
+  O: Observer + ?Sized,
+{
+  #[inline]
+  fn request_started(&self, method: &Method, path: &str) {
+    (**self).request_started(method, path)
+  }
+
+  #[inline]
+  fn request_finished(
+    &self,
+    method: &Method,
+    path: &str,
+    status: Option<StatusCode>,
+    elapsed: Duration,
+  ) {
+    (**self).request_finished(method, path, status, elapsed)
+  }
+
+  #[inline]
+  fn stream_connected(&self, url: &Url) {
+    (**self).stream_connected(url)
+  }
+
+  #[inline]
+  fn stream_message(&self, url: &Url) {
+    (**self).stream_message(url)
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use std::sync::Mutex;
+
+  use futures::channel::oneshot::channel;
+  use futures::SinkExt as _;
+  use futures::StreamExt as _;
+
+  use test_log::test;
+
+  use websocket_util::test::WebSocketStream;
+  use websocket_util::tungstenite::Message;
+
+  use crate::api::v2::clock;
+  use crate::api::v2::updates::OrderUpdates;
+  use crate::connection::ConnectionConfig;
+  use crate::transport::test::mock_api_info;
+  use crate::transport::MockTransport;
+  use crate::websocket::test::mock_stream_with;
+  use crate::Client;
+
+
+  /// An observer recording all events it is informed about.
+  #[derive(Debug, Default)]
+  struct Recorder {
+    events: Mutex<Vec<String>>,
+  }
+
+  impl Recorder {
+    fn record(&self, event: String) {
+      let () = self.events.lock().unwrap().push(event);
+    }
+
+    fn events(&self) -> Vec<String> {
+      self.events.lock().unwrap().clone()
+    }
+  }
+
+  impl Observer for Recorder {
+    fn request_started(&self, method: &Method, path: &str) {
+      self.record(format!("started {method} {path}"))
+    }
+
+    fn request_finished(
+      &self,
+      method: &Method,
+      path: &str,
+      status: Option<StatusCode>,
+      _elapsed: Duration,
+    ) {
+      self.record(format!("finished {method} {path} {status:?}"))
+    }
+
+    fn stream_connected(&self, _url: &Url) {
+      self.record("connected".to_string())
+    }
+
+    fn stream_message(&self, _url: &Url) {
+      self.record("message".to_string())
+    }
+  }
+
+
+  /// Check that the observer is informed about issued requests.
+  #[test(tokio::test)]
+  async fn observe_requests() {
+    let mock = MockTransport::new();
+    let () = mock.respond::<clock::Get>(
+      &(),
+      StatusCode::OK,
+      r#"{
+        "timestamp": "2018-04-01T12:00:00.000Z",
+        "is_open": true,
+        "next_open": "2018-04-01T12:00:00.000Z",
+        "next_close": "2018-04-01T12:00:00.000Z"
+      }"#,
+    );
+
+    let recorder = Arc::new(Recorder::default());
+    let api_info = mock_api_info();
+    let client = Client::builder()
+      .with_transport(mock)
+      .observer(Arc::clone(&recorder))
+      .build(api_info);
+
+    let _clock = client.issue::<clock::Get>(&()).await.unwrap();
+    assert_eq!(
+      recorder.events(),
+      vec![
+        "started GET /v2/clock".to_string(),
+        "finished GET /v2/clock Some(200)".to_string(),
+      ]
+    );
+  }
+
+  /// Check that the observer is informed about stream connections and
+  /// received messages.
+  #[test(tokio::test)]
+  async fn observe_stream() {
+    let (sender, receiver) = channel();
+    let test = |mut stream: WebSocketStream| async move {
+      let _auth = stream.next().await.unwrap()?;
+      let () = stream
+        .send(Message::Text(
+          r#"{"stream":"authorization","data":{"action":"authenticate","status":"authorized"}}"#
+            .into(),
+        ))
+        .await?;
+      let _listen = stream.next().await.unwrap()?;
+      let () = stream
+        .send(Message::Text(
+          r#"{"stream":"listening","data":{"streams":["trade_updates"]}}"#.into(),
+        ))
+        .await?;
+
+      let () = receiver.await.unwrap();
+      stream.send(Message::Close(None)).await
+    };
+
+    let recorder = Arc::new(Recorder::default());
+    let config = ConnectionConfig {
+      observer: Some(recorder.clone()),
+      ..Default::default()
+    };
+
+    let (stream, _subscription) = mock_stream_with::<OrderUpdates, _, _>(test, &config)
+      .await
+      .unwrap();
+    let () = sender.send(()).unwrap();
+    let _updates = stream.collect::<Vec<_>>().await;
+
+    assert_eq!(
+      recorder.events(),
+      vec![
+        "connected".to_string(),
+        "message".to_string(),
+        "message".to_string(),
+      ]
+    );
+  }
+}
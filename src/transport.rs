@@ -0,0 +1,203 @@
+// Copyright (C) 2024 The apca Developers
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::collections::HashMap;
+use std::error::Error as StdError;
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use futures::future::ready;
+use futures::future::BoxFuture;
+use futures::FutureExt as _;
+
+use http::HeaderMap;
+use http::Method;
+use http::Request;
+use http::Response;
+use http::StatusCode;
+use http_body_util::Full;
+use http_endpoint::Endpoint;
+
+use hyper::body::Bytes;
+
+
+/// The error type reported by [`Transport`] implementations.
+pub type TransportError = Box<dyn StdError + Send + Sync>;
+
+
+/// A trait abstracting over the mechanism used by a
+/// [`Client`][crate::Client] for issuing HTTP requests.
+///
+/// By default, a `Client` talks to the Alpaca servers directly. Tests
+/// without network access, for example, can install a custom transport
+/// with `with_transport` when using
+/// [`Client::builder`][crate::Client::builder].
+pub trait Transport: Debug + Send + Sync {
+  /// Issue the provided request, retrieving the response.
+  ///
+  /// The body of the response is expected to be fully collected and
+  /// not to use any content encoding.
+  fn issue(
+    &self,
+    request: Request<Full<Bytes>>,
+  ) -> BoxFuture<'_, Result<Response<Bytes>, TransportError>>;
+}
+
+
+/// A canned response, as used by [`MockTransport`].
+#[derive(Clone, Debug)]
+struct Canned {
+  /// The HTTP status to report.
+  status: StatusCode,
+  /// The headers to report.
+  headers: HeaderMap,
+  /// The body to report.
+  body: Bytes,
+}
+
+
+/// An in-memory [`Transport`] replying to requests with previously
+/// primed responses.
+///
+/// Responses are keyed on the HTTP method and path of the request and
+/// may be retrieved any number of times. Requests for which no response
+/// has been primed result in an error.
+///
+/// ```
+/// # use apca::ApiInfo;
+/// # use apca::Client;
+/// # use apca::MockTransport;
+/// use apca::api::v2::clock;
+/// use http::StatusCode;
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let mock = MockTransport::new();
+/// let () = mock.respond::<clock::Get>(
+///   &(),
+///   StatusCode::OK,
+///   r#"{
+///     "timestamp": "2018-04-01T12:00:00.000Z",
+///     "is_open": true,
+///     "next_open": "2018-04-01T12:00:00.000Z",
+///     "next_close": "2018-04-01T12:00:00.000Z"
+///   }"#,
+/// );
+///
+/// let api_info = ApiInfo::from_parts("https://paper-api.alpaca.markets", "key", "secret").unwrap();
+/// let client = Client::builder().with_transport(mock).build(api_info);
+/// let clock = client.issue::<clock::Get>(&()).await.unwrap();
+/// assert!(clock.open);
+/// # })
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct MockTransport {
+  /// The primed responses, keyed on method and path.
+  responses: Arc<Mutex<HashMap<(Method, String), Canned>>>,
+}
+
+impl MockTransport {
+  /// Create a new `MockTransport` without any responses primed.
+  #[inline]
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Prime the response to report for requests to the given endpoint.
+  ///
+  /// The endpoint's method and the path for the provided input
+  /// identify the request. Any previously primed response for the same
+  /// request is replaced.
+  #[inline]
+  pub fn respond<R>(&self, input: &R::Input, status: StatusCode, body: impl Into<Bytes>)
+  where
+    R: Endpoint,
+  {
+    self.respond_with_headers::<R>(input, status, HeaderMap::new(), body)
+  }
+
+  /// Prime the response to report for requests to the given endpoint,
+  /// including the provided headers.
+  pub fn respond_with_headers<R>(
+    &self,
+    input: &R::Input,
+    status: StatusCode,
+    headers: HeaderMap,
+    body: impl Into<Bytes>,
+  ) where
+    R: Endpoint,
+  {
+    let key = (R::method(), R::path(input).into_owned());
+    let canned = Canned {
+      status,
+      headers,
+      body: body.into(),
+    };
+
+    let _prev = self
+      .responses
+      .lock()
+      .unwrap_or_else(|err| err.into_inner())
+      .insert(key, canned);
+  }
+}
+
+impl Transport for MockTransport {
+  fn issue(
+    &self,
+    request: Request<Full<Bytes>>,
+  ) -> BoxFuture<'_, Result<Response<Bytes>, TransportError>> {
+    let key = (request.method().clone(), request.uri().path().to_string());
+    let canned = self
+      .responses
+      .lock()
+      .unwrap_or_else(|err| err.into_inner())
+      .get(&key)
+      .cloned();
+
+    let result = match canned {
+      Some(Canned {
+        status,
+        headers,
+        body,
+      }) => {
+        let mut builder = Response::builder().status(status);
+        if let Some(map) = builder.headers_mut() {
+          let () = map.extend(headers);
+        }
+        builder.body(body).map_err(TransportError::from)
+      },
+      None => Err(TransportError::from(format!(
+        "no response primed for {} {}",
+        key.0, key.1
+      ))),
+    };
+    ready(result).boxed()
+  }
+}
+
+
+#[cfg(test)]
+pub(crate) mod test {
+  use super::*;
+
+  use crate::ApiInfo;
+  use crate::Client;
+
+
+  /// Create dummy API information, as used by clients in tests.
+  pub(crate) fn mock_api_info() -> ApiInfo {
+    ApiInfo::from_parts("https://paper-api.alpaca.markets", "key", "secret").unwrap()
+  }
+
+  /// Create a [`Client`] issuing all requests through the provided
+  /// transport, typically a [`MockTransport`].
+  pub(crate) fn mock_client<T>(transport: T) -> Client
+  where
+    T: Transport + 'static,
+  {
+    Client::builder()
+      .with_transport(transport)
+      .build(mock_api_info())
+  }
+}
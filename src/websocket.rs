@@ -1,6 +1,16 @@
 // Copyright (C) 2019-2023 The apca Developers
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::Context;
+use std::task::Poll;
+
+use futures::Sink;
+use futures::SinkExt as _;
+use futures::Stream;
+use futures::StreamExt as _;
+
 use url::Url;
 
 use tokio::net::TcpStream;
@@ -11,15 +21,28 @@ use tracing::trace;
 use tracing::Level;
 use tracing_futures::Instrument;
 
-use tungstenite::connect_async;
+use tungstenite::client_async_tls_with_config;
+use tungstenite::connect_async_tls_with_config;
+use tungstenite::tungstenite::client::IntoClientRequest as _;
+use tungstenite::tungstenite::http::HeaderValue;
+use tungstenite::tungstenite::Error as WebSocketError;
+use tungstenite::tungstenite::Message as WebSocketMessage;
+use tungstenite::Connector;
 use tungstenite::MaybeTlsStream;
 use tungstenite::WebSocketStream;
 
 use websocket_util::wrap::Wrapper;
 
+use crate::connection::tunnel;
+use crate::connection::ConnectionConfig;
+use crate::telemetry::Observer;
 use crate::Error;
 
 
+/// The type of websocket stream we connect with.
+pub(crate) type WebSocket = Observed<WebSocketStream<MaybeTlsStream<TcpStream>>>;
+
+
 /// A custom [`Result`]-style type that we can implement a foreign trait
 /// on.
 #[derive(Debug)]
@@ -42,21 +65,106 @@ impl<T, E> From<Result<T, E>> for MessageResult<T, E> {
 }
 
 
+/// A websocket stream informing an [`Observer`] about received
+/// messages.
+#[derive(Debug)]
+#[doc(hidden)]
+pub struct Observed<S> {
+  /// The wrapped stream.
+  inner: S,
+  /// The URL the stream is connected to.
+  url: Url,
+  /// The observer to inform, if any.
+  observer: Option<Arc<dyn Observer>>,
+}
+
+impl<S> Stream for Observed<S>
+where
+  S: Stream<Item = Result<WebSocketMessage, WebSocketError>> + Unpin,
+{
+  type Item = S::Item;
+
+  fn poll_next(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+    let this = &mut *self;
+    let poll = this.inner.poll_next_unpin(ctx);
+    if let Poll::Ready(Some(Ok(WebSocketMessage::Text(..) | WebSocketMessage::Binary(..)))) = &poll
+    {
+      if let Some(observer) = &this.observer {
+        let () = observer.stream_message(&this.url);
+      }
+    }
+    poll
+  }
+}
+
+impl<S> Sink<WebSocketMessage> for Observed<S>
+where
+  S: Sink<WebSocketMessage, Error = WebSocketError> + Unpin,
+{
+  type Error = WebSocketError;
+
+  fn poll_ready(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+    self.inner.poll_ready_unpin(ctx)
+  }
+
+  fn start_send(mut self: Pin<&mut Self>, message: WebSocketMessage) -> Result<(), Self::Error> {
+    self.inner.start_send_unpin(message)
+  }
+
+  fn poll_flush(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+    self.inner.poll_flush_unpin(ctx)
+  }
+
+  fn poll_close(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+    self.inner.poll_close_unpin(ctx)
+  }
+}
+
+
 /// Internal function to connect to websocket server.
-async fn connect_internal(url: &Url) -> Result<WebSocketStream<MaybeTlsStream<TcpStream>>, Error> {
+async fn connect_internal(
+  url: &Url,
+  headers: &[(&'static str, &'static str)],
+  config: &ConnectionConfig,
+) -> Result<WebSocket, Error> {
   let span = span!(Level::DEBUG, "stream");
 
   async move {
     debug!(message = "connecting", url = display(url));
 
+    let mut request = url.as_str().into_client_request()?;
+    for (name, value) in headers {
+      let _prev = request
+        .headers_mut()
+        .insert(*name, HeaderValue::from_static(value));
+    }
+
+    let connector = config.tls.clone().map(Connector::NativeTls);
     // We just ignore the response & headers that are sent along after
     // the connection is made. Alpaca does not seem to be using them,
     // really.
-    let (stream, response) = connect_async(url).await?;
+    let (stream, response) = if let Some(proxy) = &config.proxy {
+      let host = url.host_str().unwrap_or_default();
+      let port = url.port_or_known_default().unwrap_or(443);
+      let stream = tunnel(proxy, host, port)
+        .await
+        .map_err(WebSocketError::Io)?;
+      client_async_tls_with_config(request, stream, None, connector).await?
+    } else {
+      connect_async_tls_with_config(request, None, false, connector).await?
+    };
     debug!("connection successful");
     trace!(response = debug(&response));
 
-    Ok(stream)
+    if let Some(observer) = &config.observer {
+      let () = observer.stream_connected(url);
+    }
+
+    Ok(Observed {
+      inner: stream,
+      url: url.clone(),
+      observer: config.observer.clone(),
+    })
   }
   .instrument(span)
   .await
@@ -66,8 +174,20 @@ async fn connect_internal(url: &Url) -> Result<WebSocketStream<MaybeTlsStream<Tc
 /// Connect to a websocket server.
 pub(crate) async fn connect(
   url: &Url,
-) -> Result<Wrapper<WebSocketStream<MaybeTlsStream<TcpStream>>>, Error> {
-  connect_internal(url)
+  config: &ConnectionConfig,
+) -> Result<Wrapper<WebSocket>, Error> {
+  connect_with_headers(url, &[], config).await
+}
+
+
+/// Connect to a websocket server, sending the provided additional
+/// headers along with the upgrade request.
+pub(crate) async fn connect_with_headers(
+  url: &Url,
+  headers: &[(&'static str, &'static str)],
+  config: &ConnectionConfig,
+) -> Result<Wrapper<WebSocket>, Error> {
+  connect_internal(url, headers, config)
     .await
     .map(|stream| Wrapper::builder().build(stream))
 }
@@ -97,6 +217,21 @@ pub(crate) mod test {
   /// provided function `f` and attempt to connect to it to stream
   /// messages.
   pub(crate) async fn mock_stream<S, F, R>(f: F) -> Result<(S::Stream, S::Subscription), Error>
+  where
+    S: Subscribable<Input = ApiInfo>,
+    F: FnOnce(WebSocketStream) -> R + Send + Sync + 'static,
+    R: Future<Output = Result<(), WebSocketError>> + Send + Sync + 'static,
+  {
+    mock_stream_with::<S, F, R>(f, &ConnectionConfig::default()).await
+  }
+
+  /// Instantiate a dummy websocket server the same way
+  /// [`mock_stream`] does, but connect to it using the provided
+  /// connection settings.
+  pub(crate) async fn mock_stream_with<S, F, R>(
+    f: F,
+    config: &ConnectionConfig,
+  ) -> Result<(S::Stream, S::Subscription), Error>
   where
     S: Subscribable<Input = ApiInfo>,
     F: FnOnce(WebSocketStream) -> R + Send + Sync + 'static,
@@ -113,10 +248,14 @@ pub(crate) mod test {
       api_stream_url: stream_url.clone(),
       data_base_url: Url::parse("http://example.com").unwrap(),
       data_stream_base_url: stream_url.clone(),
+      #[cfg(feature = "broker")]
+      broker_base_url: Url::parse("http://example.com").unwrap(),
       key_id: KEY_ID.to_string(),
       secret: SECRET.to_string(),
+      data_key_id: None,
+      data_secret: None,
     };
 
-    S::connect(&api_info).await
+    S::connect_with(&api_info, config).await
   }
 }